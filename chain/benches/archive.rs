@@ -0,0 +1,95 @@
+//! Throughput of persisting finalized blocks (as during backfill) with different [batch::Config]s.
+//!
+//! Blocks are written under `ALTO_BENCH_DIR` (defaults to the system temporary directory), so
+//! the same benchmark can be pointed at different storage devices.
+
+use alto_chain::{
+    batch::{self, Batched},
+    engine,
+};
+use alto_types::{Block, DigestMode};
+use commonware_consensus::{marshal::store::Blocks, types::Height};
+use commonware_cryptography::{Digestible, Hasher, Sha256};
+use commonware_runtime::{tokio, Runner};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Number of blocks written per iteration.
+const BLOCKS: u64 = 256;
+
+/// Counter used to write each iteration to fresh partitions.
+static RUN: AtomicU64 = AtomicU64::new(0);
+
+fn directory() -> PathBuf {
+    std::env::var_os("ALTO_BENCH_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("alto-bench-archive")
+}
+
+/// Write [BLOCKS] blocks to a fresh archive (syncing according to `cfg`), returning the time
+/// spent writing.
+fn write(cfg: Option<batch::Config>) -> Duration {
+    let config = tokio::Config::default().with_storage_directory(directory());
+    tokio::Runner::new(config).start(|context| async move {
+        let prefix = format!("run-{}", RUN.fetch_add(1, Ordering::Relaxed));
+        let tuning = engine::Tuning::default();
+        let archive = engine::init_block_store(
+            context.clone(),
+            &prefix,
+            engine::DEFAULT_FREEZER_TABLE_INITIAL_SIZE,
+            engine::buffer_pool(&tuning),
+            DigestMode::Sha256,
+            &tuning,
+            None,
+        )
+        .await;
+        let mut archive = Batched::new(context, archive, cfg);
+        let mut parent = Sha256::hash(b"genesis");
+        let start = Instant::now();
+        for height in 1..=BLOCKS {
+            let block = Block::new(parent, Height::new(height), height);
+            parent = block.digest();
+            Blocks::put(&mut archive, block).await.unwrap();
+        }
+        archive.sync().await.unwrap();
+        start.elapsed()
+    })
+}
+
+fn benchmark_archive(c: &mut Criterion) {
+    let mut group = c.benchmark_group("put_blocks");
+    group.throughput(Throughput::Elements(BLOCKS));
+    group.sample_size(10);
+    let configs = [
+        ("sync_every_write", None),
+        (
+            "batch_16",
+            Some(batch::Config {
+                max_items: 16,
+                max_delay: 1_000,
+            }),
+        ),
+        (
+            "batch_256",
+            Some(batch::Config {
+                max_items: 256,
+                max_delay: 1_000,
+            }),
+        ),
+    ];
+    for (name, cfg) in configs {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &cfg, |b, cfg| {
+            b.iter_custom(|iters| (0..iters).map(|_| write(*cfg)).sum())
+        });
+    }
+    group.finish();
+    let _ = std::fs::remove_dir_all(directory());
+}
+
+criterion_group!(benches, benchmark_archive);
+criterion_main!(benches);
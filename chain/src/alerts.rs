@@ -0,0 +1,291 @@
+//! Operator alerts.
+//!
+//! The [Monitor] periodically inspects the validator's [Sources] and notifies the operator (by
+//! executing a command and/or POSTing to a webhook) when:
+//! - no block has been processed for `finalization_timeout` seconds,
+//! - the number of participants voting in recent views leaves no room for another failure before
+//!   quorum is lost, or
+//! - more than `indexer_failures` indexer uploads fail between two checks.
+//!
+//! Each alert is sent once when its condition starts to hold and is logged again when it clears.
+
+use crate::{application::Processed, views};
+use commonware_runtime::{Clock, Metrics, Spawner};
+use commonware_utils::{Faults, N3f1};
+use prometheus_client::metrics::counter::Counter;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fmt,
+    process::Command,
+    time::{Duration, SystemTime},
+};
+use tracing::{info, warn};
+
+/// How often to evaluate alert conditions.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Maximum amount of time to wait for a webhook to respond.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Configuration for operator alerts.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// Shell command to execute when an alert fires.
+    ///
+    /// The alert name and message are provided in the `ALTO_ALERT` and `ALTO_ALERT_MESSAGE`
+    /// environment variables.
+    pub command: Option<String>,
+
+    /// URL to POST a JSON payload (`{"alert": ..., "message": ...}`) to when an alert fires.
+    pub webhook: Option<String>,
+
+    /// Seconds without a new processed block before alerting.
+    pub finalization_timeout: Option<u64>,
+
+    /// Alert when losing one more voting participant would prevent reaching quorum.
+    #[serde(default)]
+    pub quorum: bool,
+
+    /// Number of failed indexer uploads (between two checks) before alerting.
+    pub indexer_failures: Option<u64>,
+}
+
+/// A condition that requires operator attention.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Alert {
+    /// No block has been processed recently.
+    Stalled,
+
+    /// Quorum will be lost if another participant stops voting.
+    QuorumAtRisk,
+
+    /// Indexer uploads are failing.
+    IndexerFailures,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stalled => write!(f, "stalled"),
+            Self::QuorumAtRisk => write!(f, "quorum_at_risk"),
+            Self::IndexerFailures => write!(f, "indexer_failures"),
+        }
+    }
+}
+
+/// Payload sent to a webhook.
+#[derive(Serialize)]
+struct Payload<'a> {
+    alert: String,
+    message: &'a str,
+}
+
+/// Handles to the validator state inspected by the [Monitor].
+#[derive(Clone)]
+pub struct Sources<E: Clock> {
+    /// Latest processed height.
+    pub processed: Processed,
+
+    /// Current view (and the participants voting in it).
+    pub views: views::Tracker<E>,
+
+    /// Failed indexer uploads (if an indexer is configured).
+    pub upload_failures: Option<Counter>,
+}
+
+impl<E: Clock> Sources<E> {
+    /// Take a [Snapshot] of the current state.
+    pub fn snapshot(&self) -> Snapshot {
+        let status = self.views.status();
+        Snapshot {
+            height: self.processed.get(),
+            voters: (status.view > 0).then_some(status.voters),
+            upload_failures: self.upload_failures.as_ref().map(Counter::get),
+        }
+    }
+}
+
+/// State of the validator at some point in time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Latest processed height.
+    pub height: u64,
+
+    /// Number of participants that voted in recent views (if any view was entered).
+    pub voters: Option<usize>,
+
+    /// Total number of failed indexer uploads (if an indexer is configured).
+    pub upload_failures: Option<u64>,
+}
+
+/// Tracks alert conditions across [Snapshot]s.
+pub struct Conditions {
+    cfg: Config,
+    participants: usize,
+
+    height: Option<(u64, SystemTime)>,
+    failures: Option<u64>,
+    active: HashSet<Alert>,
+}
+
+impl Conditions {
+    /// Create a new [Conditions] for a validator set with `participants` members.
+    pub fn new(cfg: Config, participants: usize) -> Self {
+        Self {
+            cfg,
+            participants,
+            height: None,
+            failures: None,
+            active: HashSet::new(),
+        }
+    }
+
+    /// Evaluate a [Snapshot] taken at `now`, returning any alerts that started firing.
+    pub fn evaluate(&mut self, snapshot: Snapshot, now: SystemTime) -> Vec<(Alert, String)> {
+        // Check for stalled finalization
+        let mut firing = Vec::new();
+        let height = snapshot.height;
+        if let Some(timeout) = self.cfg.finalization_timeout {
+            let last = match self.height {
+                Some((last, at)) if last == height => at,
+                _ => {
+                    self.height = Some((height, now));
+                    now
+                }
+            };
+            let elapsed = now.duration_since(last).unwrap_or_default();
+            if elapsed >= Duration::from_secs(timeout) {
+                firing.push((
+                    Alert::Stalled,
+                    format!(
+                        "no block processed for {}s (height={height})",
+                        elapsed.as_secs()
+                    ),
+                ));
+            }
+        }
+
+        // Check for quorum risk
+        if let (true, Some(voters)) = (self.cfg.quorum, snapshot.voters) {
+            let quorum = N3f1::quorum(self.participants) as usize;
+            if voters <= quorum {
+                firing.push((
+                    Alert::QuorumAtRisk,
+                    format!(
+                        "{voters} of {} participants voting (quorum={quorum})",
+                        self.participants
+                    ),
+                ));
+            }
+        }
+
+        // Check for indexer upload failures
+        if let (Some(threshold), Some(failures)) =
+            (self.cfg.indexer_failures, snapshot.upload_failures)
+        {
+            let previous = self.failures.replace(failures).unwrap_or(failures);
+            let recent = failures.saturating_sub(previous);
+            if recent > threshold {
+                firing.push((
+                    Alert::IndexerFailures,
+                    format!("{recent} indexer uploads failed since last check"),
+                ));
+            }
+        }
+
+        // Only report alerts that were not already active
+        let current: HashSet<Alert> = firing.iter().map(|(alert, _)| *alert).collect();
+        for alert in self.active.difference(&current) {
+            info!(%alert, "alert resolved");
+        }
+        let active = std::mem::replace(&mut self.active, current);
+        firing
+            .into_iter()
+            .filter(|(alert, _)| !active.contains(alert))
+            .collect()
+    }
+}
+
+/// Evaluates [Conditions] periodically and notifies the operator of new alerts.
+pub struct Monitor<E: Clock + Spawner + Metrics> {
+    context: E,
+    sources: Sources<E>,
+    command: Option<String>,
+    webhook: Option<String>,
+    conditions: Conditions,
+}
+
+impl<E: Clock + Spawner + Metrics> Monitor<E> {
+    /// Create a new [Monitor].
+    pub fn new(context: E, cfg: Config, participants: usize, sources: Sources<E>) -> Self {
+        Self {
+            context,
+            sources,
+            command: cfg.command.clone(),
+            webhook: cfg.webhook.clone(),
+            conditions: Conditions::new(cfg, participants),
+        }
+    }
+
+    /// Start the [Monitor].
+    pub fn start(self) -> commonware_runtime::Handle<()> {
+        self.context.clone().spawn(|_| self.run())
+    }
+
+    async fn run(mut self) {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .expect("failed to create webhook client");
+        loop {
+            self.context.sleep(CHECK_INTERVAL).await;
+            let snapshot = self.sources.snapshot();
+            let now = self.context.current();
+            for (alert, message) in self.conditions.evaluate(snapshot, now) {
+                warn!(%alert, message, "alert fired");
+
+                // Execute command
+                if let Some(command) = &self.command {
+                    let command = command.clone();
+                    let (name, body) = (alert.to_string(), message.clone());
+                    let result = self
+                        .context
+                        .clone()
+                        .shared(true)
+                        .spawn(move |_| async move {
+                            Command::new("sh")
+                                .arg("-c")
+                                .arg(command)
+                                .env("ALTO_ALERT", name)
+                                .env("ALTO_ALERT_MESSAGE", body)
+                                .status()
+                        })
+                        .await;
+                    match result {
+                        Ok(Ok(status)) if status.success() => {}
+                        Ok(Ok(status)) => warn!(%alert, ?status, "alert command failed"),
+                        Ok(Err(e)) => warn!(%alert, ?e, "failed to execute alert command"),
+                        Err(e) => warn!(%alert, ?e, "alert command task failed"),
+                    }
+                }
+
+                // Post to webhook
+                if let Some(webhook) = &self.webhook {
+                    let payload = Payload {
+                        alert: alert.to_string(),
+                        message: &message,
+                    };
+                    let result = client.post(webhook).json(&payload).send().await;
+                    match result {
+                        Ok(response) if response.status().is_success() => {}
+                        Ok(response) => {
+                            warn!(%alert, status = %response.status(), "alert webhook rejected")
+                        }
+                        Err(e) => warn!(%alert, ?e, "failed to post alert webhook"),
+                    }
+                }
+            }
+        }
+    }
+}
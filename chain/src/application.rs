@@ -1,4 +1,7 @@
-use alto_types::{Block, PublicKey, Scheme};
+use crate::{mempool::Pool, rules::Rules, skew::Detector, storage::Guardrail};
+use alto_types::{Block, DigestMode, PublicKey, Scheme, MAX_BLOCK_TRANSACTIONS, MAX_RECEIPTS_SIZE};
+use bytes::Bytes;
+use commonware_codec::EncodeSize;
 use commonware_consensus::{
     marshal::{ingress::mailbox::AncestorStream, Update},
     simplex::types::Context,
@@ -10,32 +13,305 @@ use commonware_runtime::{Clock, Metrics, Spawner};
 use commonware_utils::{Acknowledgement, SystemTimeExt};
 use futures::StreamExt;
 use rand::Rng;
-use std::sync::Arc;
-use tracing::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tracing::{error, info};
 
 /// Genesis message to use during initialization.
 const GENESIS: &[u8] = b"commonware is neat";
 
-/// Milliseconds in the future to allow for block timestamps.
-const SYNCHRONY_BOUND: u64 = 500;
+/// Default milliseconds in the future to allow for block timestamps.
+pub const SYNCHRONY_BOUND: u64 = 500;
+
+/// Default maximum encoded size (in bytes) of a block.
+pub const MAX_BLOCK_SIZE: usize = 512 * 1024;
+
+/// How block timestamps are assigned and validated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampPolicy {
+    /// Timestamps track the proposer's wall clock and may not be more than
+    /// `synchrony_bound` milliseconds ahead of the verifier's wall clock.
+    #[default]
+    WallClock,
+
+    /// Timestamps are logical (each block increments its parent's timestamp) and the
+    /// wall clock is ignored.
+    Monotonic,
+}
+
+/// Configuration for the [Application].
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// Milliseconds in the future to allow for block timestamps (ignored if
+    /// `timestamp_policy` is [TimestampPolicy::Monotonic]).
+    pub synchrony_bound: u64,
+
+    /// Milliseconds in the past to allow for block timestamps (unbounded if `None` and ignored
+    /// if `timestamp_policy` is [TimestampPolicy::Monotonic]).
+    pub max_drift: Option<u64>,
+
+    /// Earliest timestamp (in milliseconds since the Unix epoch) allowed for a block (ignored if
+    /// `timestamp_policy` is [TimestampPolicy::Monotonic]). Must match all other participants.
+    ///
+    /// Blocks built by another network before this network launched (like those of an old
+    /// chain that reused the same identity) are rejected.
+    pub genesis_time: u64,
+
+    /// How block timestamps are assigned and validated.
+    pub timestamp_policy: TimestampPolicy,
+
+    /// Hash function used to compute block digests (must match all other participants).
+    pub digest_mode: DigestMode,
+
+    /// Maximum encoded size (in bytes) of a block, including its transactions (must match all
+    /// other participants).
+    pub max_block_size: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            synchrony_bound: SYNCHRONY_BOUND,
+            max_drift: None,
+            genesis_time: 0,
+            timestamp_policy: TimestampPolicy::default(),
+            digest_mode: DigestMode::default(),
+            max_block_size: MAX_BLOCK_SIZE,
+        }
+    }
+}
+
+impl Config {
+    /// Select the timestamp for a block built on `parent` at local time `now` (if `parent` can
+    /// be extended).
+    pub fn timestamp(&self, parent: u64, now: u64) -> Option<u64> {
+        let next = parent.checked_add(1)?;
+        match self.timestamp_policy {
+            TimestampPolicy::WallClock => Some(now.max(next).max(self.genesis_time)),
+            TimestampPolicy::Monotonic => Some(next),
+        }
+    }
+
+    /// Returns true if a block with `timestamp` built on `parent` is valid at local time `now`.
+    ///
+    /// Proposers apply the same check to their own blocks, so a block is never proposed that
+    /// the proposer would not verify.
+    pub fn valid(&self, parent: u64, timestamp: u64, now: u64) -> bool {
+        match self.timestamp_policy {
+            TimestampPolicy::WallClock => {
+                timestamp > parent
+                    && timestamp >= self.genesis_time
+                    && timestamp <= now.saturating_add(self.synchrony_bound)
+                    && self
+                        .max_drift
+                        .is_none_or(|drift| timestamp >= now.saturating_sub(drift))
+            }
+            TimestampPolicy::Monotonic => parent.checked_add(1) == Some(timestamp),
+        }
+    }
+}
+
+/// Executes blocks to produce their receipts (see [Block::receipts]).
+///
+/// Blocks are executed on top of the state after their parent when they are proposed or verified
+/// (so the states after several competing blocks may be held at once), and the state after a
+/// block is committed once it is finalized. Receipts are committed to by a block's digest, so
+/// every participant must install the same executor and it must be deterministic.
+pub trait Executor: Send + Sync {
+    /// Returns true if the state after the block with `digest` is held (so its children can be
+    /// executed).
+    fn executed(&self, digest: &Digest) -> bool;
+
+    /// Execute `block` on top of the state after its parent (which is held, or is genesis), hold
+    /// the resulting state as the state after `block` (by its digest), and return its receipts
+    /// (at most [MAX_RECEIPTS_SIZE] bytes).
+    ///
+    /// The receipts of `block` must be ignored: proposals are executed before their receipts are
+    /// known (so the same block may be executed more than once).
+    fn execute(&self, block: &Block) -> Bytes;
+
+    /// Commit the state after `block` (which was executed) and drop the states after any other
+    /// block at or below its height.
+    ///
+    /// Called once for each finalized block, in height order. Finalized blocks are not executed
+    /// again after a restart, so the committed state must be persisted by the executor.
+    fn finalize(&self, block: &Block);
+}
+
+/// Height of the latest block processed by the [Application] (shared with monitors).
+#[derive(Clone, Default)]
+pub struct Processed(Arc<AtomicU64>);
+
+impl Processed {
+    /// Get the latest processed height (`0` until a block is processed).
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set(&self, height: u64) {
+        self.0.store(height, Ordering::Relaxed);
+    }
+}
 
 #[derive(Clone)]
 pub struct Application {
     genesis: Arc<Block>,
+    cfg: Config,
+    rules: Rules,
+    guardrail: Guardrail,
+    skew: Detector,
+    mempool: Pool,
+    executor: Option<Arc<dyn Executor>>,
+    processed: Processed,
 }
 
 impl Application {
-    pub fn new() -> Self {
-        let genesis = Block::new(Sha256::hash(GENESIS), Height::zero(), 0);
+    /// Create a new [Application] that builds and verifies blocks using `cfg` (with the
+    /// validity rules of each height taken from `rules`), including transactions from `mempool`
+    /// in its proposals.
+    pub fn new(
+        cfg: Config,
+        rules: Rules,
+        guardrail: Guardrail,
+        skew: Detector,
+        mempool: Pool,
+    ) -> Self {
+        let genesis =
+            Block::new_with_mode(cfg.digest_mode, Sha256::hash(GENESIS), Height::zero(), 0);
         Self {
             genesis: Arc::new(genesis),
+            cfg,
+            rules,
+            guardrail,
+            skew,
+            mempool,
+            executor: None,
+            processed: Processed::default(),
+        }
+    }
+
+    /// Execute blocks with `executor`, including the receipts of each proposal (and rejecting
+    /// blocks whose receipts do not match).
+    pub fn with_executor(mut self, executor: Arc<dyn Executor>) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+
+    /// Get a [Processed] handle that follows the latest processed height.
+    pub fn processed(&self) -> Processed {
+        self.processed.clone()
+    }
+
+    /// Get the version of the rules and the [Config] to build (and verify) blocks at `height`
+    /// with.
+    fn config(&self, height: Height) -> (u16, Config) {
+        let ruleset = self.rules.at(height.get());
+        let cfg = Config {
+            timestamp_policy: ruleset.timestamp_policy,
+            ..self.cfg
+        };
+        (ruleset.version, cfg)
+    }
+
+    /// Read the ancestors of a block built on `parent` from `ancestry` (newest first, starting
+    /// with `parent`) down to the latest processed height.
+    ///
+    /// Returns the ancestors above the processed height and the ancestor at (or below) it, or
+    /// `None` if the ancestry ends early.
+    async fn ancestors(
+        &self,
+        parent: Block,
+        ancestry: &mut AncestorStream<Scheme, Block>,
+    ) -> Option<(Vec<Block>, Block)> {
+        let processed = self.processed.get();
+        let mut ancestors = Vec::new();
+        let mut ancestor = parent;
+        while ancestor.height.get() > processed {
+            // Ancestry ends at height 1 (it does not yield genesis)
+            let next = if ancestor.height.get() == 1 {
+                self.genesis.as_ref().clone()
+            } else {
+                ancestry.next().await?
+            };
+            ancestors.push(ancestor);
+            ancestor = next;
+        }
+        Some((ancestors, ancestor))
+    }
+
+    /// Returns true if `block` includes a transaction already included by one of its unfinalized
+    /// `ancestors` or by a recently finalized block (remembered by the mempool).
+    fn replays(&self, block: &Block, ancestors: &[Block]) -> bool {
+        if block.transactions.is_empty() {
+            return false;
         }
+        let proposed = included(ancestors);
+        block.transactions.iter().any(|transaction| {
+            let digest = transaction.digest();
+            proposed.contains(&digest) || self.mempool.included(&digest)
+        })
     }
+
+    /// Execute `block` with `executor` after any of its unfinalized `ancestors` (newest first)
+    /// that were not executed, returning its receipts.
+    ///
+    /// Returns `None` if no ancestor was executed and the `finalized` ancestor below them was
+    /// not either (the block does not extend the finalized chain).
+    fn execute(
+        executor: &dyn Executor,
+        block: &Block,
+        ancestors: &[Block],
+        finalized: &Block,
+    ) -> Option<Bytes> {
+        // Find the latest executed ancestor (genesis needs no execution)
+        let unexecuted = ancestors
+            .iter()
+            .position(|ancestor| executor.executed(&ancestor.digest()))
+            .unwrap_or(ancestors.len());
+        if unexecuted == ancestors.len()
+            && !finalized.height.is_zero()
+            && !executor.executed(&finalized.digest())
+        {
+            return None;
+        }
+
+        // Execute the missing ancestors (oldest first) and then the block
+        for ancestor in ancestors[..unexecuted].iter().rev() {
+            executor.execute(ancestor);
+        }
+        Some(executor.execute(block))
+    }
+}
+
+/// Get the digests of the transactions included by `blocks`.
+fn included(blocks: &[Block]) -> HashSet<Digest> {
+    blocks
+        .iter()
+        .flat_map(|block| {
+            block
+                .transactions
+                .iter()
+                .map(|transaction| transaction.digest())
+        })
+        .collect()
 }
 
 impl Default for Application {
     fn default() -> Self {
-        Self::new()
+        Self::new(
+            Config::default(),
+            Rules::default(),
+            Guardrail::default(),
+            Detector::default(),
+            Pool::default(),
+        )
     }
 }
 
@@ -56,15 +332,85 @@ where
         (runtime_context, _context): (E, Self::Context),
         mut ancestry: AncestorStream<Self::SigningScheme, Self::Block>,
     ) -> Option<Self::Block> {
+        // Refuse to produce new data if storage is nearly exhausted
+        if self.guardrail.exhausted() {
+            error!("storage guardrail tripped: skipping proposal");
+            return None;
+        }
         let parent = ancestry.next().await?;
+        let parent_digest = parent.digest();
+        let parent_timestamp = parent.timestamp;
 
-        // Create a new block
-        let mut current = runtime_context.current().epoch_millis();
-        if current <= parent.timestamp {
-            current = parent.timestamp + 1;
+        // Create a new block (under the rules active at its height)
+        let height = parent.height.next();
+        let (rules, cfg) = self.config(height);
+        let current = runtime_context.current().epoch_millis();
+        let Some(timestamp) = cfg.timestamp(parent_timestamp, current) else {
+            error!(
+                parent = parent_timestamp,
+                "parent timestamp cannot be extended: skipping proposal"
+            );
+            return None;
+        };
+        if !cfg.valid(parent_timestamp, timestamp, current) {
+            error!(
+                parent = parent_timestamp,
+                current, "timestamp outside of the allowed range: skipping proposal"
+            );
+            return None;
         }
 
-        Some(Block::new(parent.digest(), parent.height.next(), current))
+        // Include as many pending transactions as fit (leaving room for the largest possible
+        // length prefix of the transactions and, if they are executed, the largest receipts),
+        // skipping those already included by an unfinalized ancestor
+        let (ancestors, finalized) = self.ancestors(parent, &mut ancestry).await?;
+        let header =
+            Block::new_with_rules(cfg.digest_mode, rules, parent_digest, height, timestamp);
+        let mut reserved = header.encode_size() + MAX_BLOCK_TRANSACTIONS.encode_size();
+        if self.executor.is_some() {
+            reserved += MAX_RECEIPTS_SIZE.encode_size() + MAX_RECEIPTS_SIZE;
+        }
+        let available = cfg.max_block_size.saturating_sub(reserved);
+        let transactions = self.mempool.take(
+            height.get(),
+            MAX_BLOCK_TRANSACTIONS,
+            available,
+            &included(&ancestors),
+        );
+        let block = Block::new_with_transactions(
+            cfg.digest_mode,
+            rules,
+            parent_digest,
+            height,
+            timestamp,
+            transactions,
+        );
+        let Some(executor) = self.executor.clone() else {
+            return Some(block);
+        };
+
+        // Execute the block
+        let Some(receipts) = Self::execute(executor.as_ref(), &block, &ancestors, &finalized)
+        else {
+            error!("parent cannot be executed: skipping proposal");
+            return None;
+        };
+        if receipts.len() > MAX_RECEIPTS_SIZE {
+            error!(
+                size = receipts.len(),
+                "receipts exceed the maximum size: skipping proposal"
+            );
+            return None;
+        }
+        Some(Block::new_with_receipts(
+            cfg.digest_mode,
+            rules,
+            block.parent,
+            block.height,
+            block.timestamp,
+            block.transactions,
+            receipts,
+        ))
     }
 }
 
@@ -84,12 +430,54 @@ where
             return false;
         };
 
-        // Verify the block
-        if block.timestamp <= parent.timestamp {
+        // Verify the block (under the rules active at its height)
+        let (rules, cfg) = self.config(block.height);
+        if block.rules != rules {
             return false;
         }
         let current = runtime_context.current().epoch_millis();
-        if block.timestamp > current + SYNCHRONY_BOUND {
+        if cfg.timestamp_policy == TimestampPolicy::WallClock {
+            self.skew.observe(block.timestamp, current);
+        }
+        if !cfg.valid(parent.timestamp, block.timestamp, current) {
+            return false;
+        }
+
+        // Verify the block is not too large (and does not include a transaction twice or one
+        // that fails the mempool's validator)
+        if block.encode_size() > cfg.max_block_size {
+            return false;
+        }
+        let mut digests = HashSet::with_capacity(block.transactions.len());
+        if !block.transactions.iter().all(|transaction| {
+            digests.insert(transaction.digest()) && self.mempool.valid(transaction)
+        }) {
+            return false;
+        }
+
+        // Verify the block does not replay a transaction included by an unfinalized ancestor or
+        // a recently finalized block
+        let Some((ancestors, finalized)) = self.ancestors(parent, &mut ancestry).await else {
+            return false;
+        };
+        if self.replays(&block, &ancestors) {
+            return false;
+        }
+
+        // Verify the receipts match those of executing the block on top of its parent (or are
+        // empty without an executor)
+        let receipts = match self.executor.clone() {
+            Some(executor) => {
+                let Some(receipts) =
+                    Self::execute(executor.as_ref(), &block, &ancestors, &finalized)
+                else {
+                    return false;
+                };
+                receipts
+            }
+            None => Bytes::new(),
+        };
+        if block.receipts != receipts {
             return false;
         }
 
@@ -106,8 +494,72 @@ impl Reporter for Application {
 
     async fn report(&mut self, activity: Self::Activity) {
         if let Update::Block(block, ack_rx) = activity {
-            info!(height = %block.height(), "finalized block");
+            info!(
+                height = %block.height(),
+                transactions = block.transactions.len(),
+                receipts = block.receipts.len(),
+                "finalized block"
+            );
+            self.mempool
+                .finalized(block.height().get(), &block.transactions);
+
+            // Commit the state after the block (executing it first if it was never verified)
+            if let Some(executor) = &self.executor {
+                if !executor.executed(&block.digest()) {
+                    executor.execute(&block);
+                }
+                executor.finalize(&block);
+            }
+            self.processed.set(block.height().get());
             ack_rx.acknowledge();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_types::Transaction;
+
+    #[test]
+    fn test_replays() {
+        let mempool = Pool::default();
+        let application = Application::new(
+            Config::default(),
+            Rules::default(),
+            Guardrail::default(),
+            Detector::default(),
+            mempool.clone(),
+        );
+        let transaction = |data: &[u8]| Transaction::new(data.to_vec()).unwrap();
+        let block = |parent: &Block, transactions: Vec<Transaction>| {
+            Block::new_with_transactions(
+                DigestMode::Sha256,
+                0,
+                parent.digest(),
+                parent.height.next(),
+                parent.timestamp + 1,
+                transactions,
+            )
+        };
+
+        // Transactions in a finalized block are remembered by the mempool
+        let finalized = block(&application.genesis, vec![transaction(b"a")]);
+        mempool.finalized(1, &finalized.transactions);
+
+        // Blocks may not include a transaction that is finalized or in an unfinalized ancestor
+        let ancestor = block(&finalized, vec![transaction(b"b")]);
+        let ancestors = vec![ancestor.clone()];
+        let replay = block(&ancestor, vec![transaction(b"c"), transaction(b"a")]);
+        assert!(application.replays(&replay, &ancestors));
+        let replay = block(&ancestor, vec![transaction(b"b")]);
+        assert!(application.replays(&replay, &ancestors));
+
+        // Transactions in competing forks may be included again
+        let fresh = block(&ancestor, vec![transaction(b"c")]);
+        assert!(!application.replays(&fresh, &ancestors));
+        let fork = block(&finalized, vec![transaction(b"b")]);
+        assert!(!application.replays(&fork, &[]));
+        assert!(!application.replays(&block(&ancestor, Vec::new()), &ancestors));
+    }
+}
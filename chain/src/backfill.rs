@@ -0,0 +1,839 @@
+//! Backfill source preference.
+//!
+//! Marshal backfills missing blocks and certificates from peers, but peers prune old history, so
+//! a validator that falls far behind may never receive what it asks for. [init] wraps the p2p
+//! resolver so that every request is still sent to peers first, but a request that is not
+//! delivered after `max_timeouts` consecutive timeouts is also fetched from a [Source] (like an
+//! indexer). Deliveries (valid or not) are counted per source.
+//!
+//! Peers rate limit the requests they serve, so a validator that backfills faster than peers
+//! allow sees its requests silently dropped (and time out). Requests are therefore paced
+//! adaptively: each round of timeouts (or invalid delivery) from peers doubles the delay between
+//! requests (up to `max_pace`), and each valid delivery halves it until requests are again
+//! sent as soon as marshal makes them. The current pace, the number of delayed requests, and the
+//! number of timeouts are exported as metrics.
+//!
+//! Peers that fall behind together (or a fresh validator joining) request the same blocks and
+//! certificates from many validators at once. The encoded responses marshal produces for peers
+//! are therefore kept in a small cache (evicting the least recently served), so repeated
+//! requests are answered without reading (and serializing) from the archives again. Only
+//! finalized, notarized, and digest-addressed values are served, so a cached response never
+//! goes stale.
+
+use alto_types::Block;
+use bytes::Bytes;
+use commonware_codec::Encode;
+use commonware_consensus::marshal::ingress::handler::{Message, Request};
+use commonware_cryptography::ed25519::PublicKey;
+use commonware_macros::select;
+use commonware_parallel::Strategy;
+use commonware_resolver::Resolver;
+use commonware_runtime::{Clock, Handle, Metrics, Spawner};
+use commonware_utils::vec::NonEmptyVec;
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{self, Either},
+    SinkExt, StreamExt,
+};
+use prometheus_client::metrics::{counter::Counter, family::Family, gauge::Gauge};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+use tracing::{debug, warn};
+
+/// Default time to wait for peers to deliver a request before counting a timeout.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default number of consecutive timeouts after which a request is fetched from the [Source].
+pub const DEFAULT_MAX_TIMEOUTS: u32 = 5;
+
+/// Default maximum delay between requests sent to peers while pacing.
+pub const DEFAULT_MAX_PACE: Duration = Duration::from_millis(500);
+
+/// Default number of responses to peers kept in the cache.
+pub const DEFAULT_CACHE_SIZE: usize = 128;
+
+/// Smallest delay between requests sent to peers while pacing (shorter delays stop pacing).
+const MIN_PACE: Duration = Duration::from_millis(10);
+
+/// Configuration for backfill failover.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// Milliseconds to wait for peers to deliver a request before counting a timeout (defaults
+    /// to [DEFAULT_TIMEOUT]).
+    pub timeout: Option<u64>,
+
+    /// Number of consecutive timeouts after which a request is also fetched from the [Source]
+    /// (defaults to [DEFAULT_MAX_TIMEOUTS]).
+    pub max_timeouts: Option<u32>,
+
+    /// Maximum milliseconds to wait between requests sent to peers while they are timing out
+    /// (defaults to [DEFAULT_MAX_PACE], `0` disables pacing).
+    pub max_pace: Option<u64>,
+
+    /// Number of recently served responses to peers to cache (defaults to
+    /// [DEFAULT_CACHE_SIZE], `0` disables caching).
+    pub cache_size: Option<usize>,
+}
+
+impl Config {
+    fn timeout(&self) -> Duration {
+        self.timeout
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_TIMEOUT)
+    }
+
+    fn max_timeouts(&self) -> u32 {
+        self.max_timeouts.unwrap_or(DEFAULT_MAX_TIMEOUTS).max(1)
+    }
+
+    fn max_pace(&self) -> Duration {
+        self.max_pace
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_MAX_PACE)
+    }
+
+    fn cache_size(&self) -> usize {
+        self.cache_size.unwrap_or(DEFAULT_CACHE_SIZE)
+    }
+}
+
+/// A source of backfill other than peers.
+pub trait Source: Clone + Send + Sync + 'static {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Fetch the value for `key`, encoded as a peer would serve it (or `None` if the source
+    /// does not have it).
+    fn fetch(
+        &self,
+        key: Request<Block>,
+    ) -> impl Future<Output = Result<Option<Bytes>, Self::Error>> + Send;
+}
+
+impl<S: Strategy> Source for alto_client::Client<S> {
+    type Error = alto_client::Error;
+
+    async fn fetch(&self, key: Request<Block>) -> Result<Option<Bytes>, Self::Error> {
+        // Peers serve certificates and blocks as `(certificate, block)`, which is encoded the
+        // same way as the [alto_types::Finalized] and [alto_types::Notarized] served by the
+        // indexer.
+        let result = match key {
+            Request::Block(digest) => self
+                .block_get(alto_client::Query::Digest(digest))
+                .await
+                .map(|payload| payload.block().encode()),
+            Request::Finalized { height } => self
+                .block_get(alto_client::Query::Index(height.get()))
+                .await
+                .and_then(|payload| match payload {
+                    alto_types::Payload::Finalized(finalized) => Ok(finalized.encode()),
+                    alto_types::Payload::Block(_) => Err(alto_client::Error::UnexpectedResponse),
+                }),
+            Request::Notarized { round } => self
+                .notarized_get(alto_client::IndexQuery::Index(round.view().get()))
+                .await
+                .map(|notarized| notarized.encode()),
+        };
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(alto_client::Error::Failed(status)) if status.as_u16() == 404 => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Where a delivery came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Origin {
+    Peers,
+    Source,
+}
+
+impl Origin {
+    fn label(&self) -> Vec<(String, String)> {
+        let origin = match self {
+            Self::Peers => "p2p",
+            Self::Source => "indexer",
+        };
+        vec![("source".to_string(), origin.to_string())]
+    }
+}
+
+/// A predicate shared between the wrapped resolver and the failover actor.
+type Predicate = Arc<Mutex<dyn Fn(&Request<Block>) -> bool + Send>>;
+
+/// A request (and the peers to send it to, if targeted) waiting for its turn to be sent.
+type Deferred = (Request<Block>, Option<NonEmptyVec<PublicKey>>);
+
+enum Event {
+    Fetch(Vec<Request<Block>>),
+    Defer(Vec<Deferred>),
+    Cancel(Request<Block>),
+    Clear,
+    Retain(Predicate),
+    Fetched(Request<Block>, Option<Bytes>),
+    Delivered(Request<Block>, Origin, bool),
+    Produced(Request<Block>, Bytes),
+}
+
+/// A request that has not been delivered yet.
+struct Pending {
+    timeouts: u32,
+    deadline: SystemTime,
+    fetching: bool,
+    queued: bool,
+}
+
+/// Responses recently served to peers (evicting the least recently served).
+struct Responses {
+    capacity: usize,
+    values: HashMap<Request<Block>, (Bytes, u64)>,
+    recency: BTreeMap<u64, Request<Block>>,
+    tick: u64,
+}
+
+impl Responses {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            values: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Get the response cached for `key` (marking it as the most recently served).
+    fn get(&mut self, key: &Request<Block>) -> Option<Bytes> {
+        let (value, served) = self.values.get_mut(key)?;
+        self.recency.remove(served);
+        self.tick += 1;
+        *served = self.tick;
+        self.recency.insert(self.tick, key.clone());
+        Some(value.clone())
+    }
+
+    /// Cache `value` for `key`, evicting the least recently served response if full.
+    fn insert(&mut self, key: Request<Block>, value: Bytes) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if let Some((_, served)) = self.values.insert(key.clone(), (value, self.tick)) {
+            self.recency.remove(&served);
+        }
+        self.recency.insert(self.tick, key);
+        if self.values.len() > self.capacity {
+            let (_, oldest) = self.recency.pop_first().unwrap();
+            self.values.remove(&oldest);
+        }
+    }
+}
+
+/// A [Resolver] that sends requests to peers and fails over to a [Source].
+#[derive(Clone)]
+pub struct Mailbox<R> {
+    inner: R,
+    events: mpsc::UnboundedSender<Event>,
+    pace: Arc<AtomicU64>,
+}
+
+impl<R> Mailbox<R> {
+    /// Returns true if requests must wait their turn (see [Actor::release]) instead of being
+    /// sent to peers immediately.
+    fn paced(&self) -> bool {
+        self.pace.load(Ordering::Relaxed) > 0
+    }
+}
+
+impl<R: Resolver<Key = Request<Block>, PublicKey = PublicKey>> Resolver for Mailbox<R> {
+    type Key = Request<Block>;
+    type PublicKey = PublicKey;
+
+    async fn fetch(&mut self, key: Self::Key) {
+        if self.paced() {
+            let _ = self.events.unbounded_send(Event::Defer(vec![(key, None)]));
+            return;
+        }
+        let _ = self.events.unbounded_send(Event::Fetch(vec![key.clone()]));
+        self.inner.fetch(key).await;
+    }
+
+    async fn fetch_all(&mut self, keys: Vec<Self::Key>) {
+        if self.paced() {
+            let deferred = keys.into_iter().map(|key| (key, None)).collect();
+            let _ = self.events.unbounded_send(Event::Defer(deferred));
+            return;
+        }
+        let _ = self.events.unbounded_send(Event::Fetch(keys.clone()));
+        self.inner.fetch_all(keys).await;
+    }
+
+    async fn fetch_targeted(&mut self, key: Self::Key, targets: NonEmptyVec<Self::PublicKey>) {
+        if self.paced() {
+            let _ = self
+                .events
+                .unbounded_send(Event::Defer(vec![(key, Some(targets))]));
+            return;
+        }
+        let _ = self.events.unbounded_send(Event::Fetch(vec![key.clone()]));
+        self.inner.fetch_targeted(key, targets).await;
+    }
+
+    async fn fetch_all_targeted(
+        &mut self,
+        requests: Vec<(Self::Key, NonEmptyVec<Self::PublicKey>)>,
+    ) {
+        if self.paced() {
+            let deferred = requests
+                .into_iter()
+                .map(|(key, targets)| (key, Some(targets)))
+                .collect();
+            let _ = self.events.unbounded_send(Event::Defer(deferred));
+            return;
+        }
+        let keys = requests.iter().map(|(key, _)| key.clone()).collect();
+        let _ = self.events.unbounded_send(Event::Fetch(keys));
+        self.inner.fetch_all_targeted(requests).await;
+    }
+
+    async fn cancel(&mut self, key: Self::Key) {
+        let _ = self.events.unbounded_send(Event::Cancel(key.clone()));
+        self.inner.cancel(key).await;
+    }
+
+    async fn clear(&mut self) {
+        let _ = self.events.unbounded_send(Event::Clear);
+        self.inner.clear().await;
+    }
+
+    async fn retain(&mut self, predicate: impl Fn(&Self::Key) -> bool + Send + 'static) {
+        let predicate: Predicate = Arc::new(Mutex::new(predicate));
+        let _ = self.events.unbounded_send(Event::Retain(predicate.clone()));
+        self.inner
+            .retain(move |key| (predicate.lock().unwrap())(key))
+            .await;
+    }
+}
+
+/// Wrap the p2p resolver (and the deliveries it makes to marshal) so that requests to peers are
+/// paced and requests peers do not deliver are fetched from `source` (if any).
+///
+/// Returns the receiver and [Resolver] to start marshal with (in place of `p2p`) and the
+/// [Handle] of the failover actor.
+#[allow(clippy::type_complexity)]
+pub fn init<E, R, S>(
+    context: E,
+    config: Config,
+    p2p: (mpsc::Receiver<Message<Block>>, R),
+    source: Option<S>,
+) -> ((mpsc::Receiver<Message<Block>>, Mailbox<R>), Handle<()>)
+where
+    E: Spawner + Clock + Metrics,
+    R: Resolver<Key = Request<Block>, PublicKey = PublicKey>,
+    S: Source,
+{
+    let (receiver, resolver) = p2p;
+    let (marshal, deliveries) = mpsc::channel(1);
+    let (events, inbox) = mpsc::unbounded();
+    let pace = Arc::new(AtomicU64::new(0));
+    let mailbox = Mailbox {
+        inner: resolver.clone(),
+        events: events.clone(),
+        pace: pace.clone(),
+    };
+    let actor = Actor::new(&context, config, resolver, source, marshal, events, pace);
+    let handle = context.spawn(move |context| actor.run(context, receiver, inbox));
+    ((deliveries, mailbox), handle)
+}
+
+struct Actor<R, S> {
+    timeout: Duration,
+    max_timeouts: u32,
+    resolver: R,
+    source: Option<S>,
+    marshal: mpsc::Sender<Message<Block>>,
+    events: mpsc::UnboundedSender<Event>,
+    pending: BTreeMap<Request<Block>, Pending>,
+
+    max_pace: Duration,
+    pace: Duration,
+    shared_pace: Arc<AtomicU64>,
+    deferred: VecDeque<Deferred>,
+    released: SystemTime,
+
+    responses: Responses,
+
+    delivered: Family<Vec<(String, String)>, Counter>,
+    invalid: Family<Vec<(String, String)>, Counter>,
+    failovers: Counter,
+    unavailable: Counter,
+    timeouts: Counter,
+    paced: Counter,
+    pace_ms: Gauge,
+    cache_hits: Counter,
+    cache_misses: Counter,
+}
+
+impl<R, S> Actor<R, S>
+where
+    R: Resolver<Key = Request<Block>, PublicKey = PublicKey>,
+    S: Source,
+{
+    fn new(
+        context: &(impl Metrics + Clock),
+        config: Config,
+        resolver: R,
+        source: Option<S>,
+        marshal: mpsc::Sender<Message<Block>>,
+        events: mpsc::UnboundedSender<Event>,
+        shared_pace: Arc<AtomicU64>,
+    ) -> Self {
+        let delivered = Family::default();
+        context.register(
+            "delivered",
+            "Number of valid backfill deliveries (by source)",
+            delivered.clone(),
+        );
+        let invalid = Family::default();
+        context.register(
+            "invalid",
+            "Number of invalid backfill deliveries (by source)",
+            invalid.clone(),
+        );
+        let failovers = Counter::default();
+        context.register(
+            "failovers",
+            "Number of requests fetched from the indexer after timing out with peers",
+            failovers.clone(),
+        );
+        let unavailable = Counter::default();
+        context.register(
+            "unavailable",
+            "Number of failover fetches the indexer could not serve",
+            unavailable.clone(),
+        );
+        let timeouts = Counter::default();
+        context.register(
+            "timeouts",
+            "Number of times peers did not deliver a request before the timeout",
+            timeouts.clone(),
+        );
+        let paced = Counter::default();
+        context.register(
+            "paced",
+            "Number of requests delayed (instead of sent to peers immediately) by pacing",
+            paced.clone(),
+        );
+        let pace_ms = Gauge::default();
+        context.register(
+            "pace",
+            "Milliseconds between requests sent to peers (zero when not pacing)",
+            pace_ms.clone(),
+        );
+        let cache_hits = Counter::default();
+        context.register(
+            "cache_hits",
+            "Number of requests from peers served from the response cache",
+            cache_hits.clone(),
+        );
+        let cache_misses = Counter::default();
+        context.register(
+            "cache_misses",
+            "Number of requests from peers passed to marshal (not found in the response cache)",
+            cache_misses.clone(),
+        );
+        Self {
+            timeout: config.timeout(),
+            max_timeouts: config.max_timeouts(),
+            resolver,
+            source,
+            marshal,
+            events,
+            pending: BTreeMap::new(),
+            max_pace: config.max_pace(),
+            pace: Duration::ZERO,
+            shared_pace,
+            deferred: VecDeque::new(),
+            released: context.current(),
+            responses: Responses::new(config.cache_size()),
+            delivered,
+            invalid,
+            failovers,
+            unavailable,
+            timeouts,
+            paced,
+            pace_ms,
+            cache_hits,
+            cache_misses,
+        }
+    }
+
+    async fn run(
+        mut self,
+        context: impl Spawner + Clock,
+        mut receiver: mpsc::Receiver<Message<Block>>,
+        mut inbox: mpsc::UnboundedReceiver<Event>,
+    ) {
+        loop {
+            let deadline = self
+                .pending
+                .values()
+                .filter(|pending| !pending.fetching && !pending.queued)
+                .map(|pending| pending.deadline)
+                .min();
+            let expired = match deadline {
+                Some(deadline) => Either::Left(context.sleep_until(deadline)),
+                None => Either::Right(future::pending()),
+            };
+            let turn = match self.deferred.is_empty() {
+                false => Either::Left(context.sleep_until(self.released + self.pace)),
+                true => Either::Right(future::pending()),
+            };
+            select! {
+                message = receiver.next() => {
+                    let Some(message) = message else {
+                        debug!("p2p resolver stopped");
+                        return;
+                    };
+                    self.forward(&context, message).await;
+                },
+                event = inbox.next() => {
+                    // The actor holds a sender, so the inbox never closes
+                    let event = event.expect("inbox closed");
+                    self.handle(&context, event).await;
+                },
+                _ = expired => {
+                    self.expire(&context);
+                },
+                _ = turn => {
+                    self.release(&context).await;
+                },
+            }
+        }
+    }
+
+    /// Forward a message from the p2p resolver to marshal (observing the result of deliveries and
+    /// answering requests from peers with cached responses).
+    async fn forward(&mut self, context: &(impl Spawner + Clock), message: Message<Block>) {
+        let message = match message {
+            Message::Deliver {
+                key,
+                value,
+                response,
+            } => {
+                let (sender, receiver) = oneshot::channel();
+                let events = self.events.clone();
+                let observed = key.clone();
+                context.clone().spawn(move |_| async move {
+                    let valid = receiver.await.unwrap_or(false);
+                    let _ = response.send(valid);
+                    let _ = events.unbounded_send(Event::Delivered(observed, Origin::Peers, valid));
+                });
+                Message::Deliver {
+                    key,
+                    value,
+                    response: sender,
+                }
+            }
+            Message::Produce { key, response } => {
+                if let Some(value) = self.responses.get(&key) {
+                    self.cache_hits.inc();
+                    let _ = response.send(value);
+                    return;
+                }
+                self.cache_misses.inc();
+                let (sender, receiver) = oneshot::channel::<Bytes>();
+                let events = self.events.clone();
+                let produced = key.clone();
+                context.clone().spawn(move |_| async move {
+                    // Marshal drops the sender if it does not have the value
+                    let Ok(value) = receiver.await else {
+                        return;
+                    };
+                    let _ = response.send(value.clone());
+                    let _ = events.unbounded_send(Event::Produced(produced, value));
+                });
+                Message::Produce {
+                    key,
+                    response: sender,
+                }
+            }
+        };
+        if self.marshal.send(message).await.is_err() {
+            warn!("marshal stopped");
+        }
+    }
+
+    async fn handle(&mut self, context: &(impl Spawner + Clock), event: Event) {
+        match event {
+            Event::Fetch(keys) => {
+                let deadline = context.current() + self.timeout;
+                for key in keys {
+                    self.pending.entry(key).or_insert(Pending {
+                        timeouts: 0,
+                        deadline,
+                        fetching: false,
+                        queued: false,
+                    });
+                }
+            }
+            Event::Defer(requests) => {
+                let deadline = context.current() + self.timeout;
+                for (key, targets) in requests {
+                    if self.pending.contains_key(&key) {
+                        continue;
+                    }
+                    self.pending.insert(
+                        key.clone(),
+                        Pending {
+                            timeouts: 0,
+                            deadline,
+                            fetching: false,
+                            queued: true,
+                        },
+                    );
+                    self.deferred.push_back((key, targets));
+                    self.paced.inc();
+                }
+            }
+            Event::Cancel(key) => {
+                self.pending.remove(&key);
+            }
+            Event::Clear => {
+                self.pending.clear();
+                self.deferred.clear();
+            }
+            Event::Retain(predicate) => {
+                let predicate = predicate.lock().unwrap();
+                self.pending.retain(|key, _| predicate(key));
+            }
+            Event::Fetched(key, value) => {
+                let Some(value) = value else {
+                    self.unavailable.inc();
+                    self.retry(context, &key);
+                    return;
+                };
+                if !self.pending.contains_key(&key) {
+                    // Delivered (or canceled) while fetching
+                    return;
+                }
+                let (response, receiver) = oneshot::channel();
+                let message = Message::Deliver {
+                    key: key.clone(),
+                    value,
+                    response,
+                };
+                if self.marshal.send(message).await.is_err() {
+                    warn!("marshal stopped");
+                    return;
+                }
+                let events = self.events.clone();
+                context.clone().spawn(move |_| async move {
+                    let valid = receiver.await.unwrap_or(false);
+                    let _ = events.unbounded_send(Event::Delivered(key, Origin::Source, valid));
+                });
+            }
+            Event::Delivered(key, origin, valid) => {
+                if !valid {
+                    self.invalid.get_or_create(&origin.label()).inc();
+                    match origin {
+                        Origin::Peers => self.slow_down(),
+                        Origin::Source => {
+                            warn!(%key, "indexer delivered invalid backfill");
+                            self.retry(context, &key);
+                        }
+                    }
+                    return;
+                }
+                self.delivered.get_or_create(&origin.label()).inc();
+                if origin == Origin::Peers {
+                    self.speed_up();
+                }
+                if self.pending.remove(&key).is_some() && origin == Origin::Source {
+                    // Stop asking peers
+                    self.resolver.cancel(key).await;
+                }
+            }
+            Event::Produced(key, value) => {
+                self.responses.insert(key, value);
+            }
+        }
+    }
+
+    /// Count a timeout for every expired request, fetching those that timed out too many times
+    /// from the [Source].
+    fn expire(&mut self, context: &(impl Spawner + Clock)) {
+        let now = context.current();
+        let mut timed_out = false;
+        for (key, pending) in self.pending.iter_mut() {
+            if pending.fetching || pending.queued || pending.deadline > now {
+                continue;
+            }
+            timed_out = true;
+            self.timeouts.inc();
+            pending.timeouts += 1;
+            pending.deadline = now + self.timeout;
+            let Some(source) = &self.source else {
+                continue;
+            };
+            if pending.timeouts < self.max_timeouts {
+                continue;
+            }
+            debug!(%key, timeouts = pending.timeouts, "failing over to indexer");
+            pending.fetching = true;
+            self.failovers.inc();
+            let source = source.clone();
+            let events = self.events.clone();
+            let key = key.clone();
+            context.clone().spawn(move |_| async move {
+                let value = match source.fetch(key.clone()).await {
+                    Ok(value) => value,
+                    Err(e) => {
+                        warn!(%key, error = ?e, "failed to fetch from indexer");
+                        None
+                    }
+                };
+                let _ = events.unbounded_send(Event::Fetched(key, value));
+            });
+        }
+
+        // Give peers room to recover (once per round of timeouts)
+        if timed_out {
+            self.slow_down();
+        }
+    }
+
+    /// Send the next deferred request to peers (or, if no longer pacing, all of them).
+    async fn release(&mut self, context: &impl Clock) {
+        let now = context.current();
+        self.released = now;
+        while let Some((key, targets)) = self.deferred.pop_front() {
+            // Skip requests canceled (or delivered) while waiting
+            let Some(pending) = self.pending.get_mut(&key) else {
+                continue;
+            };
+            if !pending.queued {
+                continue;
+            }
+            pending.queued = false;
+            pending.deadline = now + self.timeout;
+            match targets {
+                Some(targets) => self.resolver.fetch_targeted(key, targets).await,
+                None => self.resolver.fetch(key).await,
+            }
+            if !self.pace.is_zero() {
+                return;
+            }
+        }
+    }
+
+    /// Double the delay between requests sent to peers (up to `max_pace`).
+    fn slow_down(&mut self) {
+        if self.max_pace.is_zero() {
+            return;
+        }
+        let pace = (self.pace * 2).clamp(MIN_PACE, self.max_pace);
+        self.set_pace(pace);
+    }
+
+    /// Halve the delay between requests sent to peers (stopping pacing once it is below
+    /// [MIN_PACE]).
+    fn speed_up(&mut self) {
+        let pace = self.pace / 2;
+        self.set_pace(if pace < MIN_PACE {
+            Duration::ZERO
+        } else {
+            pace
+        });
+    }
+
+    fn set_pace(&mut self, pace: Duration) {
+        if pace == self.pace {
+            return;
+        }
+        debug!(?pace, "adjusted backfill pace");
+        self.pace = pace;
+        self.shared_pace
+            .store(pace.as_millis() as u64, Ordering::Relaxed);
+        self.pace_ms.set(pace.as_millis() as i64);
+    }
+
+    /// Wait for another `max_timeouts` timeouts before fetching `key` from the [Source] again.
+    fn retry(&mut self, context: &impl Clock, key: &Request<Block>) {
+        if let Some(pending) = self.pending.get_mut(key) {
+            pending.timeouts = 0;
+            pending.deadline = context.current() + self.timeout;
+            pending.fetching = false;
+        }
+    }
+}
+
+/// Mocks for testing failover.
+#[cfg(test)]
+pub mod mocks {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// A [Resolver] that records requests (and never delivers).
+    #[derive(Clone, Default)]
+    pub struct Resolver {
+        pub fetched: Arc<Mutex<Vec<Request<Block>>>>,
+        pub canceled: Arc<Mutex<Vec<Request<Block>>>>,
+    }
+
+    impl commonware_resolver::Resolver for Resolver {
+        type Key = Request<Block>;
+        type PublicKey = PublicKey;
+
+        async fn fetch(&mut self, key: Self::Key) {
+            self.fetched.lock().unwrap().push(key);
+        }
+
+        async fn fetch_all(&mut self, keys: Vec<Self::Key>) {
+            self.fetched.lock().unwrap().extend(keys);
+        }
+
+        async fn fetch_targeted(&mut self, key: Self::Key, _: NonEmptyVec<Self::PublicKey>) {
+            self.fetched.lock().unwrap().push(key);
+        }
+
+        async fn fetch_all_targeted(
+            &mut self,
+            requests: Vec<(Self::Key, NonEmptyVec<Self::PublicKey>)>,
+        ) {
+            let mut fetched = self.fetched.lock().unwrap();
+            fetched.extend(requests.into_iter().map(|(key, _)| key));
+        }
+
+        async fn cancel(&mut self, key: Self::Key) {
+            self.canceled.lock().unwrap().push(key);
+        }
+
+        async fn clear(&mut self) {}
+
+        async fn retain(&mut self, _: impl Fn(&Self::Key) -> bool + Send + 'static) {}
+    }
+
+    /// A [Source] that serves fixed values.
+    #[derive(Clone, Default)]
+    pub struct Source {
+        pub values: Arc<Mutex<HashMap<Request<Block>, Bytes>>>,
+        pub requests: Arc<Mutex<Vec<Request<Block>>>>,
+    }
+
+    impl super::Source for Source {
+        type Error = std::io::Error;
+
+        async fn fetch(&self, key: Request<Block>) -> Result<Option<Bytes>, Self::Error> {
+            self.requests.lock().unwrap().push(key.clone());
+            Ok(self.values.lock().unwrap().get(&key).cloned())
+        }
+    }
+}
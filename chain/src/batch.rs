@@ -0,0 +1,453 @@
+//! Batched writes to the finalized archives.
+//!
+//! Marshal persists every finalized block (and its finalization) with a synced write, so
+//! backfilling a long range of blocks pays one fsync per artifact. A [Batched] archive instead
+//! syncs once every [Config::max_items] writes (or once the oldest unsynced write is
+//! [Config::max_delay] milliseconds old), trading durability for throughput.
+//!
+//! Unsynced writes are lost on a crash. Marshal only repairs gaps above the last block it
+//! processed, so artifacts lost this way leave holes in local history (they are still served by
+//! peers and the indexer). Without a [Config], every write is synced (the default).
+
+use crate::{
+    engine::{BlockStore, FinalizationStore},
+    retention,
+};
+use alto_types::{Block, Finalization, Scheme};
+use commonware_codec::CodecShared;
+use commonware_consensus::{
+    marshal::store::{Blocks, Certificates},
+    types::Height,
+    Heightable,
+};
+use commonware_cryptography::{sha256::Digest, Committable};
+use commonware_runtime::{Clock, Metrics, Spawner, Storage};
+use commonware_storage::archive::{self, Archive, Identifier};
+use futures::lock::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Weak,
+    },
+    time::{Duration, SystemTime},
+};
+use tracing::warn;
+
+/// Configuration for batched writes.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// Maximum number of unsynced writes.
+    pub max_items: usize,
+
+    /// Maximum age (in milliseconds) of the oldest unsynced write (enforced by a timer, so a
+    /// batch is flushed even if no further writes arrive).
+    pub max_delay: u64,
+}
+
+/// Highest indices written to (and synced by) a [Batched] archive.
+#[derive(Clone, Default)]
+pub struct Heights {
+    written: Arc<AtomicU64>,
+    synced: Arc<AtomicU64>,
+}
+
+impl Heights {
+    /// Get the highest index written (`0` if none).
+    pub fn written(&self) -> u64 {
+        self.written.load(Ordering::Relaxed)
+    }
+
+    /// Get the highest index synced to disk (`0` if none).
+    pub fn synced(&self) -> u64 {
+        self.synced.load(Ordering::Relaxed)
+    }
+}
+
+/// The archive and its unsynced writes (shared with the flusher).
+struct Inner<A: Archive> {
+    archive: A,
+    pending: usize,
+    oldest: Option<SystemTime>,
+    heights: Heights,
+}
+
+impl<A: Archive> Inner<A> {
+    async fn sync(&mut self) -> Result<(), archive::Error> {
+        if self.pending == 0 {
+            return Ok(());
+        }
+        self.archive.sync().await?;
+        self.pending = 0;
+        self.oldest = None;
+        let written = self.heights.written();
+        self.heights.synced.store(written, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Ranges of stored indices (start to inclusive end), mirrored from the archive after every
+/// write or prune so that synchronous lookups never wait on an in-progress flush.
+#[derive(Default)]
+struct Ranges(BTreeMap<u64, u64>);
+
+impl Ranges {
+    fn new(ranges: impl Iterator<Item = (u64, u64)>) -> Self {
+        Self(ranges.collect())
+    }
+
+    fn last_index(&self) -> Option<u64> {
+        self.0.last_key_value().map(|(_, end)| *end)
+    }
+
+    fn next_gap(&self, index: u64) -> (Option<u64>, Option<u64>) {
+        let end = self
+            .0
+            .range(..=index)
+            .next_back()
+            .map(|(_, end)| *end)
+            .filter(|end| *end >= index);
+        let next = index
+            .checked_add(1)
+            .and_then(|after| self.0.range(after..).next().map(|(start, _)| *start));
+        (end, next)
+    }
+
+    fn missing_items(&self, index: u64, max: usize) -> Vec<u64> {
+        let mut missing = Vec::new();
+        let mut current = index;
+        while missing.len() < max {
+            let (end, next) = self.next_gap(current);
+            if let Some(end) = end {
+                let Some(after) = end.checked_add(1) else {
+                    break;
+                };
+                current = after;
+                continue;
+            }
+            let Some(next) = next else {
+                break;
+            };
+            let take = (next - current).min((max - missing.len()) as u64);
+            missing.extend(current..current + take);
+            current = next;
+        }
+        missing
+    }
+}
+
+/// An [Archive] that syncs writes in batches (or immediately, without a [Config]).
+///
+/// With a [Config], a background task flushes any batch whose oldest write reaches
+/// [Config::max_delay] (exiting once the [Batched] archive is dropped).
+pub struct Batched<E: Clock, A: Archive> {
+    context: E,
+    inner: Arc<Mutex<Inner<A>>>,
+    ranges: Ranges,
+
+    max_items: usize,
+    max_delay: Duration,
+
+    heights: Heights,
+}
+
+impl<E, A> Batched<E, A>
+where
+    E: Clock + Spawner + Metrics,
+    A: Archive + Send + 'static,
+{
+    /// Wrap `archive`, syncing writes according to `cfg`.
+    pub fn new(context: E, archive: A, cfg: Option<Config>) -> Self {
+        let (max_items, max_delay) = match cfg {
+            Some(cfg) => (cfg.max_items.max(1), Duration::from_millis(cfg.max_delay)),
+            None => (1, Duration::ZERO),
+        };
+        let heights = Heights::default();
+        let last = archive.last_index().unwrap_or_default();
+        heights.written.store(last, Ordering::Relaxed);
+        heights.synced.store(last, Ordering::Relaxed);
+        let ranges = Ranges::new(archive.ranges());
+        let inner = Arc::new(Mutex::new(Inner {
+            archive,
+            pending: 0,
+            oldest: None,
+            heights: heights.clone(),
+        }));
+        if max_items > 1 && !max_delay.is_zero() {
+            let inner = Arc::downgrade(&inner);
+            context
+                .with_label("flusher")
+                .spawn(move |context| flush(context, inner, max_delay));
+        }
+        Self {
+            context,
+            inner,
+            ranges,
+            max_items,
+            max_delay,
+            heights,
+        }
+    }
+}
+
+impl<E: Clock, A: Archive> Batched<E, A> {
+    /// Store an item, syncing if the batch is full (or its oldest write is too old).
+    pub async fn put(
+        &mut self,
+        index: u64,
+        key: A::Key,
+        value: A::Value,
+    ) -> Result<(), archive::Error> {
+        let mut inner = self.inner.lock().await;
+        let result = inner.archive.put(index, key, value).await;
+        self.ranges = Ranges::new(inner.archive.ranges());
+        result?;
+        self.heights.written.fetch_max(index, Ordering::Relaxed);
+        inner.pending += 1;
+        let now = self.context.current();
+        let oldest = *inner.oldest.get_or_insert(now);
+        let age = now.duration_since(oldest).unwrap_or_default();
+        if inner.pending >= self.max_items || age >= self.max_delay {
+            inner.sync().await?;
+        }
+        Ok(())
+    }
+
+    /// Get an item by index or key.
+    pub async fn get(
+        &self,
+        id: Identifier<'_, A::Key>,
+    ) -> Result<Option<A::Value>, archive::Error> {
+        self.inner.lock().await.archive.get(id).await
+    }
+
+    /// Sync all pending writes.
+    pub async fn sync(&mut self) -> Result<(), archive::Error> {
+        self.inner.lock().await.sync().await
+    }
+
+    /// Get the number of unsynced writes.
+    pub async fn pending(&self) -> usize {
+        self.inner.lock().await.pending
+    }
+
+    /// Get a [Heights] handle that follows the indices written to (and synced by) the archive.
+    pub fn heights(&self) -> Heights {
+        self.heights.clone()
+    }
+
+    /// Get the highest index stored (if any).
+    pub fn last_index(&self) -> Option<u64> {
+        self.ranges.last_index()
+    }
+
+    /// Get the end of the range containing `index` (if stored) and the start of the next range.
+    pub fn next_gap(&self, index: u64) -> (Option<u64>, Option<u64>) {
+        self.ranges.next_gap(index)
+    }
+
+    /// Get up to `max` missing indices at or above `index` (below the highest index stored).
+    pub fn missing_items(&self, index: u64, max: usize) -> Vec<u64> {
+        self.ranges.missing_items(index, max)
+    }
+}
+
+/// Sync the batch in `inner` whenever its oldest write reaches `max_delay` (until the [Batched]
+/// archive that owns it is dropped).
+async fn flush<E: Clock, A: Archive>(
+    context: E,
+    inner: Weak<Mutex<Inner<A>>>,
+    max_delay: Duration,
+) {
+    let mut deadline = context.current() + max_delay;
+    loop {
+        context.sleep_until(deadline).await;
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+        let mut inner = inner.lock().await;
+        let now = context.current();
+        deadline = match inner.oldest {
+            Some(oldest) if oldest + max_delay <= now => {
+                if let Err(err) = inner.sync().await {
+                    warn!(?err, "failed to flush batched writes");
+                }
+                now + max_delay
+            }
+            Some(oldest) => oldest + max_delay,
+            None => now + max_delay,
+        };
+    }
+}
+
+impl<E, V> Batched<E, retention::Archive<E, V>>
+where
+    E: Storage + Metrics + Clock,
+    V: CodecShared,
+{
+    /// Prune everything below `min`.
+    pub async fn prune(&mut self, min: u64) -> Result<(), archive::Error> {
+        let mut inner = self.inner.lock().await;
+        let result = inner.archive.prune(min).await;
+        self.ranges = Ranges::new(inner.archive.ranges());
+        result
+    }
+}
+
+impl<E> Certificates for Batched<E, FinalizationStore<E>>
+where
+    E: Storage + Metrics + Clock,
+{
+    type Commitment = Digest;
+    type Scheme = Scheme;
+    type Error = archive::Error;
+
+    async fn put(
+        &mut self,
+        height: Height,
+        commitment: Digest,
+        finalization: Finalization,
+    ) -> Result<(), Self::Error> {
+        Batched::put(self, height.get(), commitment, finalization).await
+    }
+
+    async fn get(&self, id: Identifier<'_, Digest>) -> Result<Option<Finalization>, Self::Error> {
+        Batched::get(self, id).await
+    }
+
+    async fn prune(&mut self, min: Height) -> Result<(), Self::Error> {
+        Batched::prune(self, min.get()).await
+    }
+
+    fn last_index(&self) -> Option<Height> {
+        Batched::last_index(self).map(Height::new)
+    }
+}
+
+impl<E> Blocks for Batched<E, BlockStore<E>>
+where
+    E: Storage + Metrics + Clock,
+{
+    type Block = Block;
+    type Error = archive::Error;
+
+    async fn put(&mut self, block: Block) -> Result<(), Self::Error> {
+        Batched::put(self, block.height().get(), block.commitment(), block).await
+    }
+
+    async fn get(&self, id: Identifier<'_, Digest>) -> Result<Option<Block>, Self::Error> {
+        Batched::get(self, id).await
+    }
+
+    async fn prune(&mut self, min: Height) -> Result<(), Self::Error> {
+        Batched::prune(self, min.get()).await
+    }
+
+    fn missing_items(&self, start: Height, max: usize) -> Vec<Height> {
+        Batched::missing_items(self, start.get(), max)
+            .into_iter()
+            .map(Height::new)
+            .collect()
+    }
+
+    fn next_gap(&self, value: Height) -> (Option<Height>, Option<Height>) {
+        let (end, start) = Batched::next_gap(self, value.get());
+        (end.map(Height::new), start.map(Height::new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine;
+    use alto_types::DigestMode;
+    use commonware_cryptography::{Digestible, Hasher, Sha256};
+    use commonware_runtime::{deterministic, Runner};
+
+    #[test]
+    fn test_batched() {
+        let executor = deterministic::Runner::default();
+        executor.start(|context| async move {
+            let tuning = engine::Tuning::default();
+            let archive = engine::init_block_store(
+                context.clone(),
+                "batch",
+                engine::DEFAULT_FREEZER_TABLE_INITIAL_SIZE,
+                engine::buffer_pool(&tuning),
+                DigestMode::Sha256,
+                &tuning,
+                None,
+            )
+            .await;
+            let cfg = Config {
+                max_items: 3,
+                max_delay: 1_000,
+            };
+            let mut batched = Batched::new(context.clone(), archive, Some(cfg));
+            let block = |height| Block::new(Sha256::hash(b"genesis"), Height::new(height), height);
+
+            // Writes are readable before they are synced
+            Blocks::put(&mut batched, block(1)).await.unwrap();
+            Blocks::put(&mut batched, block(2)).await.unwrap();
+            assert_eq!(batched.pending().await, 2);
+            let heights = batched.heights();
+            assert_eq!((heights.written(), heights.synced()), (2, 0));
+            let stored = Blocks::get(&batched, Identifier::Index(2)).await.unwrap();
+            assert_eq!(stored, Some(block(2)));
+
+            // Full batches are synced
+            Blocks::put(&mut batched, block(3)).await.unwrap();
+            assert_eq!(batched.pending().await, 0);
+            assert_eq!((heights.written(), heights.synced()), (3, 3));
+
+            // So are batches whose oldest write is too old
+            Blocks::put(&mut batched, block(4)).await.unwrap();
+            context.sleep(Duration::from_millis(1_000)).await;
+            Blocks::put(&mut batched, block(5)).await.unwrap();
+            assert_eq!(batched.pending().await, 0);
+            let digest = block(5).digest();
+            let stored = Blocks::get(&batched, Identifier::Key(&digest))
+                .await
+                .unwrap();
+            assert_eq!(stored, Some(block(5)));
+            assert_eq!(batched.last_index(), Some(5));
+
+            // Batches are flushed once their oldest write is too old, even without further writes
+            Blocks::put(&mut batched, block(6)).await.unwrap();
+            assert_eq!((heights.written(), heights.synced()), (6, 5));
+            context.sleep(Duration::from_millis(500)).await;
+            assert_eq!(batched.pending().await, 1);
+            context.sleep(Duration::from_millis(1_000)).await;
+            assert_eq!(batched.pending().await, 0);
+            assert_eq!((heights.written(), heights.synced()), (6, 6));
+
+            // Gaps are tracked without waiting on the archive
+            Blocks::put(&mut batched, block(9)).await.unwrap();
+            assert_eq!(
+                Blocks::next_gap(&batched, Height::new(3)),
+                (Some(Height::new(6)), Some(Height::new(9)))
+            );
+            let missing: Vec<_> = Blocks::missing_items(&batched, Height::new(1), 5)
+                .into_iter()
+                .map(|height| height.get())
+                .collect();
+            assert_eq!(missing, vec![7, 8]);
+
+            // Without a config, every write is synced
+            let archive = engine::init_block_store(
+                context.with_label("immediate"),
+                "immediate",
+                engine::DEFAULT_FREEZER_TABLE_INITIAL_SIZE,
+                engine::buffer_pool(&tuning),
+                DigestMode::Sha256,
+                &tuning,
+                None,
+            )
+            .await;
+            let mut immediate = Batched::new(context, archive, None);
+            Blocks::put(&mut immediate, block(1)).await.unwrap();
+            assert_eq!(immediate.pending().await, 0);
+        });
+    }
+}
@@ -0,0 +1,73 @@
+use alto_chain::{engine, export, Config};
+use clap::{value_parser, Arg, Command};
+use commonware_runtime::{tokio, Runner};
+use commonware_utils::hex;
+use std::{fs, path::PathBuf};
+use tracing::info;
+
+fn main() {
+    // Initialize logger
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    // Parse arguments
+    let matches = Command::new("export-finalization")
+        .about("Export a finalization (and its block) from a stopped validator's storage as a hex-encoded frame.")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("height")
+                .long("height")
+                .help("Height of the finalized block to export (defaults to the latest)")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .help("File to write the frame to (defaults to stdout)")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .get_matches();
+    let height = matches.get_one::<u64>("height").copied();
+    let output = matches.get_one::<PathBuf>("output").cloned();
+
+    // Load config
+    let config_file = matches.get_one::<PathBuf>("config").unwrap();
+    let config_file = fs::read_to_string(config_file).expect("Could not read config file");
+    let config: Config = serde_yaml::from_str(&config_file).expect("Could not parse config file");
+
+    // Read the finalization from storage
+    let cfg = tokio::Config::default().with_storage_directory(PathBuf::from(&config.directory));
+    let executor = tokio::Runner::new(cfg);
+    let finalized = executor.start(|context| async move {
+        export::finalized(
+            context,
+            engine::DEFAULT_PARTITION_PREFIX,
+            engine::DEFAULT_FREEZER_TABLE_INITIAL_SIZE,
+            config.digest_mode.unwrap_or_default(),
+            &config.tuning.unwrap_or_default(),
+            config.retain_blocks,
+            height,
+        )
+        .await
+        .expect("Could not export finalization")
+    });
+    info!(
+        height = finalized.block.height.get(),
+        view = finalized.proof.proposal.round.view().get(),
+        "exported finalization"
+    );
+
+    // Write the frame
+    let frame = hex(&export::encode(&finalized));
+    match output {
+        Some(path) => fs::write(&path, frame).expect("Could not write frame"),
+        None => println!("{frame}"),
+    }
+}
@@ -0,0 +1,72 @@
+use alto_chain::{engine, journal, Config};
+use clap::{value_parser, Arg, Command};
+use commonware_consensus::types::Epoch;
+use commonware_runtime::{tokio, Runner};
+use std::{fs, path::PathBuf};
+use tracing::info;
+
+fn main() {
+    // Initialize logger
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    // Parse arguments
+    let matches = Command::new("inspect-journal")
+        .about("Print the votes and certificates in a stopped validator's consensus journal (one line per view).")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("epoch")
+                .long("epoch")
+                .help("Epoch whose journal to print (defaults to the first epoch)")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .help("First view to print (defaults to the oldest journaled view)")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .help("Last view to print (defaults to the latest journaled view)")
+                .value_parser(value_parser!(u64)),
+        )
+        .get_matches();
+    let epoch = matches.get_one::<u64>("epoch").copied().unwrap_or(0);
+    let from = matches.get_one::<u64>("from").copied().unwrap_or(0);
+    let to = matches.get_one::<u64>("to").copied().unwrap_or(u64::MAX);
+
+    // Load config
+    let config_file = matches.get_one::<PathBuf>("config").unwrap();
+    let config_file = fs::read_to_string(config_file).expect("Could not read config file");
+    let config: Config = serde_yaml::from_str(&config_file).expect("Could not parse config file");
+
+    // Read the journal from storage
+    let cfg = tokio::Config::default().with_storage_directory(PathBuf::from(&config.directory));
+    let executor = tokio::Runner::new(cfg);
+    let records = executor.start(|context| async move {
+        journal::read(
+            context,
+            engine::DEFAULT_PARTITION_PREFIX,
+            Epoch::new(epoch),
+            &config.tuning.unwrap_or_default(),
+            from,
+        )
+        .await
+        .expect("Could not read consensus journal")
+    });
+    info!(views = records.len(), "read consensus journal");
+
+    // Print each view
+    for record in records.iter().filter(|record| record.view <= to) {
+        println!("{record}");
+    }
+}
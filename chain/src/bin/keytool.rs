@@ -0,0 +1,186 @@
+use alto_chain::{
+    keys::{self, Kdf, Keystore},
+    Config,
+};
+use clap::{value_parser, Arg, Command};
+use commonware_codec::{Decode, DecodeExt, Encode};
+use commonware_cryptography::bls12381::primitives::{
+    group::Share, sharing::Sharing, variant::MinSig,
+};
+use commonware_utils::{from_hex_formatted, hex, NZU32};
+use rand::rngs::OsRng;
+use std::{fs, path::PathBuf};
+use tracing::info;
+
+/// Name of the exported ed25519 key.
+const ED25519_FILE: &str = "ed25519.pem";
+
+/// Name of the exported share keystore.
+const SHARE_FILE: &str = "share.json";
+
+/// Upper bound on the number of participants in a polynomial (only used to decode the config).
+const MAX_PARTICIPANTS: u32 = u16::MAX as u32;
+
+fn main() {
+    // Initialize logger
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    // Parse arguments
+    let matches = Command::new("keytool")
+        .about("Import and export validator keys in standard formats (PKCS#8 PEM and EIP-2335 keystores).")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("export")
+                .about("Export the keys of a validator config.")
+                .arg(
+                    Arg::new("config")
+                        .long("config")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .required(true)
+                        .help("Directory to write ed25519.pem and share.json to")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("password_file")
+                        .long("password-file")
+                        .required(true)
+                        .help("File containing the password used to encrypt the share")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("kdf")
+                        .long("kdf")
+                        .default_value("scrypt")
+                        .value_parser(["scrypt", "pbkdf2"]),
+                ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Import keys into a validator config (in place).")
+                .arg(
+                    Arg::new("config")
+                        .long("config")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("ed25519")
+                        .long("ed25519")
+                        .help("PKCS#8 PEM file containing the ed25519 validator key")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("share")
+                        .long("share")
+                        .requires("password_file")
+                        .help("EIP-2335 keystore containing the BLS12-381 share")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("password_file")
+                        .long("password-file")
+                        .help("File containing the password used to decrypt the share")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("index")
+                        .long("index")
+                        .help("Index of the share (if not stored in the keystore)")
+                        .value_parser(value_parser!(u32)),
+                ),
+        )
+        .get_matches();
+
+    match matches.subcommand() {
+        Some(("export", matches)) => {
+            let config_file = matches.get_one::<PathBuf>("config").unwrap();
+            let output = matches.get_one::<PathBuf>("output").unwrap();
+            let password = read_password(matches.get_one::<PathBuf>("password_file").unwrap());
+            let kdf = match matches.get_one::<String>("kdf").unwrap().as_str() {
+                "pbkdf2" => Kdf::Pbkdf2 { c: 262_144 },
+                _ => Kdf::default(),
+            };
+            let config = read_config(config_file);
+
+            // Export ed25519 key
+            let key = from_hex_formatted(&config.private_key).expect("Could not parse private key");
+            let key = commonware_cryptography::ed25519::PrivateKey::decode(key.as_ref())
+                .expect("Private key is invalid");
+            fs::create_dir_all(output).expect("Could not create output directory");
+            let path = output.join(ED25519_FILE);
+            fs::write(&path, keys::ed25519_to_pem(&key)).expect("Could not write ed25519 key");
+            info!(path = ?path, "exported ed25519 key");
+
+            // Export share
+            let share = config.share.as_ref().expect("Config has no share");
+            let share = from_hex_formatted(share).expect("Could not parse share");
+            let share = Share::decode(share.as_ref()).expect("Share is invalid");
+            let keystore = Keystore::from_share(&mut OsRng, &share, &password, kdf);
+            let path = output.join(SHARE_FILE);
+            fs::write(&path, keystore.to_json()).expect("Could not write share keystore");
+            info!(path = ?path, index = %share.index, "exported share");
+        }
+        Some(("import", matches)) => {
+            let config_file = matches.get_one::<PathBuf>("config").unwrap();
+            let mut config = read_config(config_file);
+
+            // Import ed25519 key
+            if let Some(path) = matches.get_one::<PathBuf>("ed25519") {
+                let pem = fs::read_to_string(path).expect("Could not read ed25519 key");
+                let key = keys::ed25519_from_pem(&pem).expect("Could not import ed25519 key");
+                config.private_key = hex(&key.encode());
+                info!(path = ?path, "imported ed25519 key");
+            }
+
+            // Import share (ensuring it belongs to the configured polynomial)
+            if let Some(path) = matches.get_one::<PathBuf>("share") {
+                let password = read_password(matches.get_one::<PathBuf>("password_file").unwrap());
+                let json = fs::read_to_string(path).expect("Could not read share keystore");
+                let keystore = Keystore::from_json(&json).expect("Could not parse share keystore");
+                let share = keystore
+                    .to_share(&password, matches.get_one::<u32>("index").copied())
+                    .expect("Could not import share");
+                let polynomial =
+                    from_hex_formatted(&config.polynomial).expect("Could not parse polynomial");
+                let polynomial =
+                    Sharing::<MinSig>::decode_cfg(polynomial.as_ref(), &NZU32!(MAX_PARTICIPANTS))
+                        .expect("Polynomial is invalid");
+                let expected = polynomial
+                    .partial_public(share.index)
+                    .expect("Share index is not in the polynomial");
+                assert_eq!(
+                    share.public::<MinSig>(),
+                    expected,
+                    "Share does not match the polynomial"
+                );
+                config.share = Some(hex(&share.encode()));
+                info!(path = ?path, index = %share.index, "imported share");
+            }
+
+            // Write config
+            let config = serde_yaml::to_string(&config).expect("Could not serialize config");
+            fs::write(config_file, config).expect("Could not write config file");
+            info!(path = ?config_file, "updated config");
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Read a validator config.
+fn read_config(path: &PathBuf) -> Config {
+    let config = fs::read_to_string(path).expect("Could not read config file");
+    serde_yaml::from_str(&config).expect("Could not parse config file")
+}
+
+/// Read a password (ignoring a trailing newline).
+fn read_password(path: &PathBuf) -> String {
+    let password = fs::read_to_string(path).expect("Could not read password file");
+    password.trim_end_matches(['\r', '\n']).to_string()
+}
@@ -0,0 +1,165 @@
+use alto_chain::Peers;
+use clap::{value_parser, Arg, Command as App};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{ExitStatus, Stdio},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::Command,
+    sync::watch,
+    task::JoinSet,
+};
+use tracing::{error, info, warn};
+
+/// Number of characters of a validator's name used to prefix its logs.
+const PREFIX_LENGTH: usize = 8;
+
+/// Time to wait before restarting a validator that exited.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+#[tokio::main]
+async fn main() {
+    // Initialize logger
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    // Parse arguments
+    let matches = App::new("alto-localnet")
+        .about("Run every validator generated by `setup generate ... local` (restarting them if they crash) until Ctrl-C.")
+        .arg(
+            Arg::new("dir")
+                .long("dir")
+                .required(true)
+                .help("Output directory of `setup generate ... local`")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .default_value("dev")
+                .help("Launch profile passed to each validator")
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new("validator")
+                .long("validator")
+                .help("Path to the validator binary (defaults to the one built alongside this binary)")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .get_matches();
+    let dir = matches.get_one::<PathBuf>("dir").unwrap();
+    let profile = matches.get_one::<String>("profile").unwrap().clone();
+    let validator = match matches.get_one::<PathBuf>("validator") {
+        Some(path) => path.clone(),
+        None => std::env::current_exe()
+            .expect("Could not locate current binary")
+            .with_file_name(format!("validator{}", std::env::consts::EXE_SUFFIX)),
+    };
+    if !validator.exists() {
+        error!(
+            path = ?validator,
+            "validator binary not found (run `cargo build --bin validator` first or pass --validator)"
+        );
+        std::process::exit(1);
+    }
+
+    // Load peers
+    let peers_path = dir.join("peers.yaml");
+    let peers_file = fs::read_to_string(&peers_path).expect("Could not read peers file");
+    let peers: Peers = serde_yaml::from_str(&peers_file).expect("Could not parse peers file");
+    let mut names: Vec<_> = peers.addresses.into_keys().collect();
+    names.sort();
+
+    // Share the chain spec with every validator (if setup wrote one)
+    let spec_path = dir.join("spec.json");
+    let spec = spec_path.exists().then_some(spec_path);
+
+    // Supervise each validator until shutdown
+    let (shutdown, stopped) = watch::channel(false);
+    let mut supervisors = JoinSet::new();
+    for name in names {
+        let config = dir.join(format!("{name}.yaml"));
+        let mut args = vec![
+            format!("--profile={profile}"),
+            format!("--peers={}", peers_path.display()),
+            format!("--config={}", config.display()),
+        ];
+        if let Some(spec) = &spec {
+            args.push(format!("--spec={}", spec.display()));
+        }
+        supervisors.spawn(supervise(
+            validator.clone(),
+            args,
+            name[..PREFIX_LENGTH.min(name.len())].to_string(),
+            stopped.clone(),
+        ));
+    }
+    info!(validators = supervisors.len(), "started localnet");
+
+    // Tear everything down on Ctrl-C
+    tokio::signal::ctrl_c()
+        .await
+        .expect("Could not listen for Ctrl-C");
+    info!("stopping localnet");
+    let _ = shutdown.send(true);
+    supervisors.join_all().await;
+    info!("stopped localnet");
+}
+
+/// Run `validator` with `args` (restarting it whenever it exits) until `stopped` is set.
+async fn supervise(
+    validator: PathBuf,
+    args: Vec<String>,
+    prefix: String,
+    mut stopped: watch::Receiver<bool>,
+) {
+    loop {
+        let status = tokio::select! {
+            status = run(&validator, &args, &prefix) => status,
+            _ = stopped.wait_for(|stopped| *stopped) => return,
+        };
+        match status {
+            Ok(status) => warn!(validator = prefix, %status, "validator exited, restarting"),
+            Err(e) => warn!(validator = prefix, error = %e, "failed to start validator, retrying"),
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(RESTART_DELAY) => {}
+            _ = stopped.wait_for(|stopped| *stopped) => return,
+        }
+    }
+}
+
+/// Run `validator` with `args` until it exits, prefixing every line it logs with `prefix`.
+///
+/// The child is killed if the returned future is dropped.
+async fn run(validator: &Path, args: &[String], prefix: &str) -> std::io::Result<ExitStatus> {
+    let mut child = Command::new(validator)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    info!(validator = prefix, pid = child.id(), "started validator");
+    let stdout = tokio::spawn(forward(child.stdout.take(), prefix.to_string()));
+    let stderr = tokio::spawn(forward(child.stderr.take(), prefix.to_string()));
+    let status = child.wait().await;
+    let _ = stdout.await;
+    let _ = stderr.await;
+    status
+}
+
+/// Print each line of `stream` (prefixed with `prefix`) until it closes.
+async fn forward(stream: Option<impl AsyncRead + Unpin>, prefix: String) {
+    let Some(stream) = stream else {
+        return;
+    };
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        println!("[{prefix}] {line}");
+    }
+}
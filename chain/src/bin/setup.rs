@@ -1,7 +1,7 @@
-use alto_chain::{Config, Peers};
-use alto_types::NAMESPACE;
-use clap::{value_parser, Arg, ArgMatches, Command};
-use commonware_codec::{Decode, DecodeExt, Encode};
+use alto_chain::{application, discovery, genesis, profile::Profile, Config, Peers};
+use alto_types::{Genesis, Spec, ValidatorInfo, NAMESPACE};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use commonware_codec::{DecodeExt, Encode};
 use commonware_consensus::simplex::scheme::bls12381_threshold;
 use commonware_cryptography::{
     bls12381::primitives::{sharing::Sharing, variant::MinSig},
@@ -11,12 +11,13 @@ use commonware_cryptography::{
 };
 use commonware_deployer::ec2::{self, METRICS_PORT};
 use commonware_math::algebra::Random;
-use commonware_utils::{from_hex_formatted, hex, NZU32};
+use commonware_utils::{from_hex_formatted, hex, SystemTimeExt};
 use rand::{rngs::OsRng, seq::IteratorRandom};
 use std::{
     collections::{BTreeMap, HashMap},
     fs,
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::SystemTime,
 };
 use tracing::{error, info};
 use uuid::Uuid;
@@ -25,6 +26,9 @@ const BINARY_NAME: &str = "validator";
 const PORT: u16 = 4545;
 const STORAGE_CLASS: &str = "gp3";
 const DASHBOARD_FILE: &str = "dashboard.json";
+const GENESIS_FILE: &str = "genesis.hex";
+const SPEC_FILE: &str = "spec.json";
+const VALIDATORS_FILE: &str = "validators.json";
 
 fn main() {
     // Initialize logger
@@ -102,6 +106,11 @@ fn main() {
                         .required(false)
                         .value_parser(value_parser!(u16)),
                     )
+                    .arg(Arg::new("discovery")
+                        .long("discovery")
+                        .help("Configure validators to learn peer addresses from bootstrappers (instead of a peers file)")
+                        .action(ArgAction::SetTrue),
+                    )
                 )
                 .subcommand(
                     Command::new("remote")
@@ -159,7 +168,7 @@ fn main() {
         )
         .subcommand(
             Command::new("explorer")
-                .about("Generate a config.ts for the explorer.")
+                .about("Generate a config.ts for the explorer (and the validators.json served by the indexer).")
                 .arg(
                     Arg::new("dir")
                         .long("dir")
@@ -260,6 +269,7 @@ fn generate_local(
     // Extract arguments
     let start_port = *sub_matches.get_one::<u16>("start_port").unwrap();
     let indexer_port = sub_matches.get_one::<u16>("indexer_port").copied();
+    let discovery = sub_matches.get_flag("discovery");
 
     // Construct output path
     let raw_current_dir = std::env::current_dir().unwrap();
@@ -301,6 +311,12 @@ fn generate_local(
     let identity = schemes[0].polynomial().public();
     info!(%identity, "generated network key");
 
+    // Sign genesis transcript (rejecting blocks from before the network launched)
+    let genesis_time = SystemTime::now().epoch_millis();
+    let genesis = sign_genesis(&peer_signers, schemes[0].polynomial(), genesis_time);
+    let genesis_hex = hex(&genesis.encode());
+    info!(digest = ?genesis.digest(), genesis_time, "signed genesis transcript");
+
     // Generate instance configurations
     let mut port = start_port;
     let mut addresses = HashMap::new();
@@ -316,7 +332,7 @@ fn generate_local(
         let directory = format!("{storage_output}/{name}");
         let peer_config = Config {
             private_key: hex(&signer.encode()),
-            share: hex(&scheme.share().unwrap().encode()),
+            share: Some(hex(&scheme.share().unwrap().encode())),
             polynomial: hex(&scheme.polynomial().encode()),
 
             port,
@@ -324,10 +340,14 @@ fn generate_local(
             directory,
             worker_threads,
             log_level: log_level.clone(),
+            runtime: None,
 
             local: true,
             allowed_peers: allowed_peers.clone(),
             bootstrappers: bootstrappers.clone(),
+            discovery: None,
+            observers: None,
+            relay: None,
 
             message_backlog,
             mailbox_size,
@@ -336,6 +356,40 @@ fn generate_local(
             signature_threads,
 
             indexer: None,
+            indexers: None,
+            upload_batch: None,
+            upload_transport: None,
+            spool: None,
+            backfill: None,
+            state_sync: None,
+
+            max_disk_usage: None,
+
+            alerts: None,
+            health: None,
+            replica: None,
+            query: None,
+            batch: None,
+            retain_blocks: None,
+            tuning: None,
+
+            synchrony_bound: None,
+            max_drift: None,
+            genesis_time: Some(genesis_time),
+            max_block_size: None,
+            mempool: None,
+
+            timestamp_policy: None,
+            upgrades: None,
+            epochs: None,
+
+            digest_mode: None,
+
+            genesis: Some(genesis_hex.clone()),
+
+            overrides: None,
+            #[cfg(feature = "faults")]
+            faults: None,
         };
         configurations.push((name, peer_config_file.clone(), peer_config));
         port += 2;
@@ -345,10 +399,35 @@ fn generate_local(
     let (_, _, first_config) = &mut configurations[0];
     first_config.indexer = indexer_port.map(|port| format!("http://localhost:{}", port));
 
+    // Only provide the addresses of bootstrappers (if discovery is enabled)
+    if discovery {
+        let bootstrapper_addresses: HashMap<_, _> = bootstrappers
+            .iter()
+            .map(|bootstrapper| (bootstrapper.clone(), addresses[bootstrapper]))
+            .collect();
+        for (name, _, peer_config) in &mut configurations {
+            peer_config.discovery = Some(discovery::Config {
+                address: addresses[name],
+                bootstrappers: bootstrapper_addresses.clone(),
+            });
+        }
+    }
+
     // Create required output directories
     fs::create_dir_all(&output).unwrap();
     fs::create_dir_all(&storage_output).unwrap();
 
+    // Write genesis transcript (served by the indexer)
+    let genesis_path = format!("{output}/{GENESIS_FILE}");
+    fs::write(&genesis_path, &genesis_hex).unwrap();
+    info!(path = GENESIS_FILE, "wrote genesis transcript");
+
+    // Write chain spec (consumed by validators, the indexer, and the inspector)
+    let spec_path = format!("{output}/{SPEC_FILE}");
+    let spec = chain_spec("alto-local", &genesis, Profile::Dev);
+    fs::write(&spec_path, spec.to_json()).unwrap();
+    info!(path = SPEC_FILE, "wrote chain spec");
+
     // Write peers file
     let peers_path = format!("{output}/peers.yaml");
     let file = fs::File::create(&peers_path).unwrap();
@@ -366,14 +445,21 @@ fn generate_local(
     info!(?bootstrappers, "setup complete");
     if let Some(indexer_port) = &indexer_port {
         let command =
-            format!("cargo run --bin indexer -- --port {indexer_port} --identity {identity}",);
+            format!("cargo run --bin indexer -- --port {indexer_port} --spec {spec_path}",);
         println!("To start local indexer, run:\n{command}");
     }
     println!("To start validators, run:");
     for (name, peer_config_file, _) in &configurations {
         let path = format!("{output}/{peer_config_file}");
-        let command =
-            format!("cargo run --bin {BINARY_NAME} -- --peers={peers_path} --config={path}");
+        let command = if discovery {
+            format!(
+                "cargo run --bin {BINARY_NAME} -- --profile=dev --spec={spec_path} --config={path}"
+            )
+        } else {
+            format!(
+                "cargo run --bin {BINARY_NAME} -- --profile=dev --spec={spec_path} --peers={peers_path} --config={path}"
+            )
+        };
         println!("{name}: {command}");
     }
     if let Some(indexer_port) = &indexer_port {
@@ -445,6 +531,7 @@ fn generate_remote(
 
     // Generate UUID
     let tag = Uuid::new_v4().to_string();
+    let chain_id = format!("alto-{tag}");
     info!(tag, "generated deployment tag");
 
     // Generate peers
@@ -475,6 +562,12 @@ fn generate_remote(
     let identity = schemes[0].polynomial().public();
     info!(%identity, "generated network key");
 
+    // Sign genesis transcript (rejecting blocks from before the network launched)
+    let genesis_time = SystemTime::now().epoch_millis();
+    let genesis = sign_genesis(&peer_signers, schemes[0].polynomial(), genesis_time);
+    let genesis_hex = hex(&genesis.encode());
+    info!(digest = ?genesis.digest(), genesis_time, "signed genesis transcript");
+
     // Generate instance configurations
     assert!(
         regions.len() <= peers,
@@ -488,7 +581,7 @@ fn generate_remote(
         let peer_config_file = format!("{name}.yaml");
         let peer_config = Config {
             private_key: hex(&signer.encode()),
-            share: hex(&scheme.share().unwrap().encode()),
+            share: Some(hex(&scheme.share().unwrap().encode())),
             polynomial: hex(&scheme.polynomial().encode()),
 
             port: PORT,
@@ -496,10 +589,14 @@ fn generate_remote(
             directory: "/home/ubuntu/data".to_string(),
             worker_threads,
             log_level: log_level.clone(),
+            runtime: None,
 
             local: false,
             allowed_peers: allowed_peers.clone(),
             bootstrappers: bootstrappers.clone(),
+            discovery: None,
+            observers: None,
+            relay: None,
 
             message_backlog,
             mailbox_size,
@@ -508,6 +605,40 @@ fn generate_remote(
             signature_threads,
 
             indexer: None,
+            indexers: None,
+            upload_batch: None,
+            upload_transport: None,
+            spool: None,
+            backfill: None,
+            state_sync: None,
+
+            max_disk_usage: None,
+
+            alerts: None,
+            health: None,
+            replica: None,
+            query: None,
+            batch: None,
+            retain_blocks: None,
+            tuning: None,
+
+            synchrony_bound: None,
+            max_drift: None,
+            genesis_time: Some(genesis_time),
+            max_block_size: None,
+            mempool: None,
+
+            timestamp_policy: None,
+            upgrades: None,
+            epochs: None,
+
+            digest_mode: None,
+
+            genesis: Some(genesis_hex.clone()),
+
+            overrides: None,
+            #[cfg(feature = "faults")]
+            faults: None,
         };
         peer_configs.push((peer_config_file.clone(), peer_config));
 
@@ -609,12 +740,61 @@ fn generate_remote(
         serde_yaml::to_writer(file, &peer_config).unwrap();
         info!(path = peer_config_file, "wrote peer configuration file");
     }
+    fs::write(format!("{output}/{GENESIS_FILE}"), &genesis_hex).unwrap();
+    info!(path = GENESIS_FILE, "wrote genesis transcript");
+    let spec = chain_spec(chain_id, &genesis, Profile::Mainnet);
+    fs::write(format!("{output}/{SPEC_FILE}"), spec.to_json()).unwrap();
+    info!(path = SPEC_FILE, "wrote chain spec");
     let path = format!("{output}/config.yaml");
     let file = fs::File::create(&path).unwrap();
     serde_yaml::to_writer(file, &config).unwrap();
     info!(path = "config.yaml", "wrote configuration file");
 }
 
+/// Create a [Genesis] transcript for `peer_signers` and `polynomial` (with the default network
+/// config launched at `genesis_time`) signed by every peer.
+fn sign_genesis(
+    peer_signers: &[PrivateKey],
+    polynomial: &Sharing<MinSig>,
+    genesis_time: u64,
+) -> Genesis {
+    let participants = peer_signers
+        .iter()
+        .map(|signer| signer.public_key())
+        .collect();
+    let config = genesis::config_digest(&application::Config {
+        genesis_time,
+        ..Default::default()
+    });
+    let mut genesis = Genesis::new(participants, polynomial.clone(), config);
+    for signer in peer_signers {
+        assert!(genesis.sign(signer), "signer is not a participant");
+    }
+    genesis
+}
+
+/// Describe the network formed by `genesis` (using the timeouts of `profile`).
+fn chain_spec(chain_id: impl Into<String>, genesis: &Genesis, profile: Profile) -> Spec {
+    let digest_mode = application::Config::default().digest_mode;
+    let timeouts = profile.parameters().timeouts();
+    Spec::new(chain_id, genesis, digest_mode, Some(timeouts))
+}
+
+/// Read the identity (hex-encoded) from the chain spec in `dir`.
+fn spec_identity(dir: &str) -> String {
+    let spec = Spec::load(format!("{dir}/{SPEC_FILE}")).expect("failed to read chain spec");
+    spec.genesis().expect("chain spec is invalid");
+    spec.identity
+}
+
+/// Write the metadata the indexer serves at `/validators` (ordered by public key) to `dir`.
+fn write_validators(dir: &str, validators: BTreeMap<PublicKey, ValidatorInfo>) {
+    let validators: Vec<_> = validators.into_values().collect();
+    let json = serde_json::to_string_pretty(&validators).expect("failed to encode validators");
+    fs::write(format!("{dir}/{VALIDATORS_FILE}"), json).expect("failed to write validators");
+    info!(path = VALIDATORS_FILE, "wrote validator metadata");
+}
+
 // Region-to-location mapping
 fn get_aws_location(region: &str) -> Option<([f64; 2], String)> {
     match region {
@@ -635,38 +815,34 @@ fn get_aws_location(region: &str) -> Option<([f64; 2], String)> {
 }
 
 fn explorer_local(dir: String, backend_url: String) {
-    // Read peers.yaml to get participant count
-    let peers_path = format!("{dir}/peers.yaml");
-    let peers_content = fs::read_to_string(&peers_path).expect("failed to read peers.yaml");
-    let peers: Peers = serde_yaml::from_str(&peers_content).expect("failed to parse peers.yaml");
-    let num_peers = peers.addresses.len();
-
-    // Read polynomial from first peer config
-    let first_peer = peers.addresses.keys().next().expect("no peers found");
-    let peer_config_path = format!("{dir}/{first_peer}.yaml");
-    let peer_config_content =
-        fs::read_to_string(&peer_config_path).expect("failed to read peer config");
-    let peer_config: Config =
-        serde_yaml::from_str(&peer_config_content).expect("failed to parse peer config");
-    let polynomial_hex = peer_config.polynomial;
-    let polynomial = from_hex_formatted(&polynomial_hex).expect("invalid polynomial");
-    let polynomial = Sharing::<MinSig>::decode_cfg(polynomial.as_ref(), &NZU32!(num_peers as u32))
-        .expect("polynomial is invalid");
-    let identity = polynomial.public();
+    let identity = spec_identity(&dir);
 
     // Generate config.ts with empty locations (explorer will hide map)
     let config_ts = format!(
         "export const BACKEND_URL = \"{}\";\n\
         export const PUBLIC_KEY_HEX = \"{}\";\n\
         export const LOCATIONS: [[number, number], string][] = [];",
-        backend_url,
-        hex(&identity.encode()),
+        backend_url, identity,
     );
 
     // Write config.ts
     let config_ts_path = format!("{dir}/config.ts");
     fs::write(&config_ts_path, config_ts).expect("failed to write config.ts");
     info!(path = "config.ts", "wrote explorer configuration file");
+
+    // Write validators.json (without locations)
+    let spec = Spec::load(format!("{dir}/{SPEC_FILE}")).expect("failed to read chain spec");
+    let participants = spec.participants().expect("chain spec is invalid");
+    let validators = participants
+        .iter()
+        .enumerate()
+        .map(|(index, public_key)| {
+            let mut validator = ValidatorInfo::new(public_key);
+            validator.name = Some(format!("validator-{index}"));
+            (public_key.clone(), validator)
+        })
+        .collect();
+    write_validators(&dir, validators);
 }
 
 fn explorer_remote(dir: String, backend_url: String) {
@@ -676,15 +852,21 @@ fn explorer_remote(dir: String, backend_url: String) {
     let config: ec2::Config =
         serde_yaml::from_str(&config_content).expect("failed to parse config.yaml");
     let mut participants = BTreeMap::new();
+    let mut validators = BTreeMap::new();
     for instance in &config.instances {
         let region = &instance.region;
         let public_key = from_hex_formatted(&instance.name).expect("invalid public key");
         let public_key = PublicKey::decode(public_key.as_ref()).expect("invalid public key");
         let (coords, city) = get_aws_location(region).expect("unknown region");
         participants.insert(
-            public_key,
+            public_key.clone(),
             format!("    [[{}, {}], \"{}\"]", coords[0], coords[1], city),
         );
+        let mut validator = ValidatorInfo::new(&public_key);
+        validator.region = Some(region.clone());
+        validator.location = Some(city);
+        validator.coordinates = Some(coords);
+        validators.insert(public_key, validator);
     }
 
     // Order by public key
@@ -695,29 +877,19 @@ fn explorer_remote(dir: String, backend_url: String) {
 
     // Generate config.ts
     let locations_str = locations.join(",\n");
-    let first_instance = &config.instances[0];
-    let peer_config_path = format!("{}/{}", dir, first_instance.config);
-    let peer_config_content =
-        fs::read_to_string(&peer_config_path).expect("failed to read peer config");
-    let peer_config: Config =
-        serde_yaml::from_str(&peer_config_content).expect("failed to parse peer config");
-    let polynomial_hex = peer_config.polynomial;
-    let polynomial = from_hex_formatted(&polynomial_hex).expect("invalid polynomial");
-    let polynomial =
-        Sharing::<MinSig>::decode_cfg(polynomial.as_ref(), &NZU32!(locations.len() as u32))
-            .expect("polynomial is invalid");
-    let identity = polynomial.public();
+    let identity = spec_identity(&dir);
     let config_ts = format!(
         "export const BACKEND_URL = \"{}\";\n\
         export const PUBLIC_KEY_HEX = \"{}\";\n\
         export const LOCATIONS: [[number, number], string][] = [\n{}\n];",
-        backend_url,
-        hex(&identity.encode()),
-        locations_str
+        backend_url, identity, locations_str
     );
 
     // Write config.ts
     let config_ts_path = format!("{dir}/config.ts");
     fs::write(&config_ts_path, config_ts).expect("failed to write config.ts");
     info!(path = "config.ts", "wrote explorer configuration file");
+
+    // Write validators.json
+    write_validators(&dir, validators);
 }
@@ -0,0 +1,119 @@
+use alto_chain::simulation::{self, Config};
+use clap::{value_parser, Arg, Command};
+use commonware_p2p::simulated::Link;
+use std::time::Duration;
+
+fn main() {
+    // Initialize logger (validator logs are noisy, so only show warnings)
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .init();
+
+    // Parse arguments
+    let matches = Command::new("simulate")
+        .about("Predict view time and finalization latency of an alto chain with a deterministic simulation.")
+        .arg(
+            Arg::new("validators")
+                .long("validators")
+                .required(true)
+                .value_parser(value_parser!(u32)),
+        )
+        .arg(
+            Arg::new("latency")
+                .long("latency")
+                .help("Mean one-way link latency (in milliseconds)")
+                .default_value("50")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("jitter")
+                .long("jitter")
+                .help("Link latency jitter (in milliseconds)")
+                .default_value("10")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("success_rate")
+                .long("success-rate")
+                .help("Probability a message is delivered")
+                .default_value("1.0")
+                .value_parser(value_parser!(f64)),
+        )
+        .arg(
+            Arg::new("block_size")
+                .long("block-size")
+                .help("Size of each block (in bytes)")
+                .default_value("0")
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("bandwidth")
+                .long("bandwidth")
+                .help("Bandwidth of each link (in bytes per second)")
+                .default_value("125000000")
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("blocks")
+                .long("blocks")
+                .help("Number of blocks to finalize")
+                .default_value("100")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .help("Simulated time to wait for the blocks to be finalized (in seconds)")
+                .default_value("600")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .default_value("0")
+                .value_parser(value_parser!(u64)),
+        )
+        .get_matches();
+    let validators = *matches.get_one::<u32>("validators").unwrap();
+    let latency = *matches.get_one::<u64>("latency").unwrap();
+    let jitter = *matches.get_one::<u64>("jitter").unwrap();
+    let success_rate = *matches.get_one::<f64>("success_rate").unwrap();
+    let bandwidth = *matches.get_one::<usize>("bandwidth").unwrap();
+    assert!(validators > 0, "validators must be greater than 0");
+    assert!(bandwidth > 0, "bandwidth must be greater than 0");
+    assert!(
+        (0.0..=1.0).contains(&success_rate),
+        "success rate must be in [0, 1]"
+    );
+    let config = Config {
+        seed: *matches.get_one::<u64>("seed").unwrap(),
+        validators,
+        link: Link {
+            latency: Duration::from_millis(latency),
+            jitter: Duration::from_millis(jitter),
+            success_rate,
+        },
+        block_size: *matches.get_one::<usize>("block_size").unwrap(),
+        bandwidth,
+        blocks: *matches.get_one::<u64>("blocks").unwrap(),
+        timeout: Duration::from_secs(*matches.get_one::<u64>("timeout").unwrap()),
+    };
+
+    // Run simulation
+    let report = match simulation::run(config) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("simulation failed: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    // Print results
+    println!("simulated time: {:?}", report.elapsed);
+    println!("views: {}", report.views);
+    println!("view time: {:?}", report.view_time());
+    for p in [50.0, 90.0, 99.0] {
+        let latency = report.percentile(p).unwrap_or_default();
+        println!("finalization latency (p{p}): {latency:?}");
+    }
+}
@@ -1,9 +1,15 @@
-use alto_chain::{engine, Config, Peers};
-use alto_client::Client;
-use alto_types::{EPOCH, NAMESPACE};
-use clap::{Arg, Command};
+use alto_chain::{
+    alerts, application, backfill, checkpoint, crash, engine, epochs, genesis, health, indexer,
+    mempool, metrics,
+    observers::{self, Channel},
+    profile::Profile,
+    query, relay, replica, runtime, storage, Config, Peers,
+};
+use alto_client::ClientBuilder;
+use alto_types::{IdentitySchedule, Spec, EPOCH, MAX_GENESIS_PARTICIPANTS, NAMESPACE};
+use clap::{value_parser, Arg, ArgAction, Command};
 use commonware_codec::{Decode, DecodeExt};
-use commonware_consensus::{marshal, types::ViewDelta};
+use commonware_consensus::marshal;
 use commonware_cryptography::{
     bls12381::primitives::{group, sharing::Sharing, variant::MinSig},
     ed25519::{PrivateKey, PublicKey},
@@ -11,7 +17,8 @@ use commonware_cryptography::{
 };
 use commonware_deployer::ec2::Hosts;
 use commonware_p2p::{authenticated::discovery as authenticated, Ingress, Manager};
-use commonware_runtime::{tokio, Metrics, RayonPoolSpawner, Runner};
+use commonware_parallel::Rayon;
+use commonware_runtime::{tokio, Metrics, Runner};
 use commonware_utils::{from_hex_formatted, ordered::Set, union_unique, NZUsize, NZU32};
 use futures::future::try_join_all;
 use governor::Quota;
@@ -23,26 +30,16 @@ use std::{
     str::FromStr,
     time::Duration,
 };
-use tracing::{error, info, Level};
+use tracing::{error, info, warn, Level};
 
 const PENDING_CHANNEL: u64 = 0;
 const RECOVERED_CHANNEL: u64 = 1;
 const RESOLVER_CHANNEL: u64 = 2;
 const BROADCASTER_CHANNEL: u64 = 3;
 const MARSHAL_CHANNEL: u64 = 4;
+const TRANSACTIONS_CHANNEL: u64 = 5;
 
-const LEADER_TIMEOUT: Duration = Duration::from_secs(1);
-const NOTARIZATION_TIMEOUT: Duration = Duration::from_secs(2);
-const NULLIFY_RETRY: Duration = Duration::from_secs(10);
-const ACTIVITY_TIMEOUT: ViewDelta = ViewDelta::new(256);
-const SKIP_TIMEOUT: ViewDelta = ViewDelta::new(32);
-const FETCH_TIMEOUT: Duration = Duration::from_secs(2);
-const FETCH_CONCURRENT: usize = 4;
 const MAX_MESSAGE_SIZE: u32 = 1024 * 1024;
-const MAX_FETCH_COUNT: usize = 16;
-const MAX_FETCH_SIZE: usize = 512 * 1024;
-const BLOCKS_FREEZER_TABLE_INITIAL_SIZE: u32 = 2u32.pow(21); // 100MB
-const FINALIZED_FREEZER_TABLE_INITIAL_SIZE: u32 = 2u32.pow(21); // 100MB
 
 fn main() {
     // Parse arguments
@@ -51,29 +48,76 @@ fn main() {
         .arg(Arg::new("hosts").long("hosts").required(false))
         .arg(Arg::new("peers").long("peers").required(false))
         .arg(Arg::new("config").long("config").required(true))
+        .arg(
+            Arg::new("spec")
+                .long("spec")
+                .required(false)
+                .help("Chain spec (written by setup) providing the genesis transcript, digest mode, and consensus timeouts"),
+        )
+        .arg(
+            Arg::new("verify-storage")
+                .long("verify-storage")
+                .action(ArgAction::SetTrue)
+                .help("Verify the integrity of all stored blocks and finalizations on startup"),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .default_value("mainnet")
+                .value_parser(value_parser!(Profile))
+                .help("Parameter bundle to use (dev, testnet, or mainnet), overridden by the config's overrides"),
+        )
         .get_matches();
 
     // Load ip file
     let hosts_file = matches.get_one::<String>("hosts");
     let peers_file = matches.get_one::<String>("peers");
-    assert!(
-        hosts_file.is_some() || peers_file.is_some(),
-        "Either --hosts or --peers must be provided"
-    );
+    let verify_storage = matches.get_flag("verify-storage");
+    let profile = *matches.get_one::<Profile>("profile").unwrap();
 
     // Load config
     let config_file = matches.get_one::<String>("config").unwrap();
     let config_file = std::fs::read_to_string(config_file).expect("Could not read config file");
-    let config: Config = serde_yaml::from_str(&config_file).expect("Could not parse config file");
+    let mut config: Config =
+        serde_yaml::from_str(&config_file).expect("Could not parse config file");
+
+    // Load chain spec (if provided)
+    let spec = matches
+        .get_one::<String>("spec")
+        .map(|path| Spec::load(path).expect("Could not read chain spec"));
+    if let Some(spec) = &spec {
+        config = config
+            .with_spec(spec)
+            .expect("Config does not match chain spec");
+    }
+
+    // Write a crash report (and abort) if any actor panics
+    let crashes =
+        crash::Reporter::install(PathBuf::from(&config.directory), config_file.as_bytes());
+    assert!(
+        hosts_file.is_some() || peers_file.is_some() || config.discovery.is_some(),
+        "Either --hosts, --peers, or a discovery config must be provided"
+    );
     let key = from_hex_formatted(&config.private_key).expect("Could not parse private key");
     let signer = PrivateKey::decode(key.as_ref()).expect("Private key is invalid");
     let public_key = signer.public_key();
+    let mut parameters = profile.parameters();
+    if let Some(timeouts) = spec.as_ref().and_then(|spec| spec.timeouts) {
+        parameters = parameters.with_timeouts(&timeouts);
+    }
+    let parameters = parameters.with_overrides(&config.overrides.clone().unwrap_or_default());
 
     // Initialize runtime
+    let runtime = config.runtime.clone().unwrap_or_default();
+    assert!(
+        runtime.max_blocking_threads() > 0,
+        "max_blocking_threads must be greater than zero"
+    );
     let cfg = tokio::Config::default()
         .with_tcp_nodelay(Some(true))
         .with_worker_threads(config.worker_threads)
-        .with_storage_directory(PathBuf::from(config.directory))
+        .with_max_blocking_threads(runtime.max_blocking_threads())
+        .with_storage_directory(PathBuf::from(&config.directory))
         .with_catch_panics(false);
     let executor = tokio::Runner::new(cfg);
 
@@ -88,15 +132,20 @@ fn main() {
                 // If we are using `commonware-deployer`, we should use structured logging.
                 json: hosts_file.is_some(),
             },
-            Some(SocketAddr::new(
-                IpAddr::V4(Ipv4Addr::UNSPECIFIED),
-                config.metrics_port,
-            )),
+            None,
             None,
         );
 
+        // Serve metrics (labeled with the subsystem and instance that registered them)
+        metrics::Server::new(
+            context.with_label("metrics"),
+            config.metrics_port,
+            public_key.to_string(),
+        )
+        .start();
+
         // Load peers
-        let (ip, peers, bootstrappers) = if let Some(hosts_file) = hosts_file {
+        let (address, peers, bootstrappers) = if let Some(hosts_file) = hosts_file {
             let hosts_file = std::fs::read_to_string(hosts_file).unwrap();
             let hosts: Hosts =
                 serde_yaml::from_str(&hosts_file).expect("Could not parse peers file");
@@ -123,9 +172,9 @@ fn main() {
                 bootstrappers.push((key, Ingress::Socket(bootstrapper_socket)));
             }
             let ip = peers.get(&public_key).expect("Could not find self in IPs");
-            (*ip, peer_keys, bootstrappers)
-        } else {
-            let peers_file = std::fs::read_to_string(peers_file.unwrap()).unwrap();
+            (SocketAddr::new(*ip, config.port), peer_keys, bootstrappers)
+        } else if let Some(peers_file) = peers_file {
+            let peers_file = std::fs::read_to_string(peers_file).unwrap();
             let peers: Peers =
                 serde_yaml::from_str(&peers_file).expect("Could not parse peers file");
             let peers: HashMap<PublicKey, SocketAddr> = peers
@@ -151,123 +200,396 @@ fn main() {
                 .get(&public_key)
                 .expect("Could not find self in IPs")
                 .ip();
-            (ip, peer_keys, bootstrappers)
+            (SocketAddr::new(ip, config.port), peer_keys, bootstrappers)
+        } else {
+            // Learn the participants from the genesis transcript (and their addresses from
+            // bootstrappers)
+            let discovery = config.discovery.as_ref().unwrap();
+            let transcript = config
+                .genesis
+                .as_ref()
+                .expect("Discovery requires a genesis transcript");
+            let transcript = genesis::decode(transcript, MAX_GENESIS_PARTICIPANTS)
+                .expect("Could not parse genesis transcript");
+            let peer_keys = transcript.participants.to_vec();
+            let bootstrappers = discovery
+                .resolve(&config.bootstrappers)
+                .expect("Could not resolve bootstrappers")
+                .into_iter()
+                .map(|(key, socket)| (key, Ingress::Socket(socket)))
+                .collect();
+            (discovery.address, peer_keys, bootstrappers)
         };
         info!(peers = peers.len(), "loaded peers");
+        info!(%profile, ?parameters, "loaded profile");
+        if let Some(spec) = &spec {
+            info!(chain_id = spec.chain_id, "loaded chain spec");
+        }
         let peers_u32 = peers.len() as u32;
 
-        // Parse config
-        let share = from_hex_formatted(&config.share).expect("Could not parse share");
-        let share = group::Share::decode(share.as_ref()).expect("Share is invalid");
+        // Parse the validator sets of later epochs (if any)
+        let transitions: Vec<epochs::Transition> = config
+            .epochs
+            .iter()
+            .flatten()
+            .map(|epoch| epoch.parse().expect("Could not parse epoch"))
+            .collect();
+        let later_validators = transitions
+            .iter()
+            .flat_map(|transition| transition.committee.participants.iter().cloned())
+            .collect::<Vec<_>>();
+        if !transitions.is_empty() {
+            info!(epochs = transitions.len(), "scheduled epochs");
+        }
+
+        // Relay blocks instead of participating in consensus (if configured)
+        if let Some(relay) = config.relay {
+            let transcript = config
+                .genesis
+                .as_ref()
+                .expect("Relays require a genesis transcript");
+            let transcript = genesis::decode(transcript, MAX_GENESIS_PARTICIPANTS)
+                .expect("Could not parse genesis transcript");
+            info!(
+                ?public_key,
+                ?address,
+                port = config.port,
+                "loaded relay config"
+            );
+            let p2p_cfg = network_config(&config, signer, address, bootstrappers);
+            let participants =
+                Set::from_iter_dedup(transcript.participants.into_iter().chain(later_validators));
+            run_relay(context, &config, relay, p2p_cfg, participants).await;
+            return;
+        }
+
+        // Learn the validators of the first epoch (if the validator set changes in later epochs,
+        // peers include the validators of every epoch and the genesis transcript records the
+        // first)
+        let participants: Set<PublicKey> = match transitions.is_empty() {
+            true => Set::from_iter_dedup(peers.clone()),
+            false => {
+                let transcript = config
+                    .genesis
+                    .as_ref()
+                    .expect("Epochs require a genesis transcript");
+                let transcript = genesis::decode(transcript, MAX_GENESIS_PARTICIPANTS)
+                    .expect("Could not parse genesis transcript");
+                Set::from_iter_dedup(transcript.participants)
+            }
+        };
+
+        // Parse config (validators that only join in a later epoch have no share of the first)
+        let share = config.share.as_ref().map(|share| {
+            let share = from_hex_formatted(share).expect("Could not parse share");
+            group::Share::decode(share.as_ref()).expect("Share is invalid")
+        });
         let polynomial =
             from_hex_formatted(&config.polynomial).expect("Could not parse polynomial");
-        let polynomial = Sharing::<MinSig>::decode_cfg(polynomial.as_ref(), &NZU32!(peers_u32))
-            .expect("polynomial is invalid");
+        let polynomial =
+            Sharing::<MinSig>::decode_cfg(polynomial.as_ref(), &NZU32!(participants.len() as u32))
+                .expect("polynomial is invalid");
         let identity = polynomial.public();
         info!(
             ?public_key,
             ?identity,
-            ?ip,
+            ?address,
             port = config.port,
             "loaded config"
         );
 
-        // Configure network
-        let p2p_namespace = union_unique(NAMESPACE, b"_P2P");
-        let mut p2p_cfg = if config.local {
-            authenticated::Config::local(
-                signer.clone(),
-                &p2p_namespace,
-                SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), config.port),
-                SocketAddr::new(ip, config.port),
-                bootstrappers,
-                MAX_MESSAGE_SIZE,
-            )
-        } else {
-            authenticated::Config::recommended(
-                signer.clone(),
-                &p2p_namespace,
-                SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), config.port),
-                SocketAddr::new(ip, config.port),
-                bootstrappers,
-                MAX_MESSAGE_SIZE,
-            )
+        // Verify the config matches the genesis transcript (if provided)
+        let application = application::Config {
+            synchrony_bound: config
+                .synchrony_bound
+                .unwrap_or(application::SYNCHRONY_BOUND),
+            max_drift: config.max_drift,
+            genesis_time: config.genesis_time.unwrap_or_default(),
+            timestamp_policy: config.timestamp_policy.unwrap_or_default(),
+            digest_mode: config.digest_mode.unwrap_or_default(),
+            max_block_size: config.max_block_size.unwrap_or(application::MAX_BLOCK_SIZE),
         };
-        p2p_cfg.mailbox_size = config.mailbox_size;
+        match &config.genesis {
+            Some(transcript) => {
+                let transcript = genesis::decode(transcript, NZU32!(peers_u32))
+                    .expect("Could not parse genesis transcript");
+                genesis::verify(&transcript, &participants, &polynomial, &application)
+                    .expect("Config does not match genesis transcript");
+                info!(digest = ?transcript.digest(), "verified genesis transcript");
+            }
+            None => warn!("no genesis transcript configured"),
+        }
+
+        // Configure network
+        let p2p_cfg = network_config(&config, signer.clone(), address, bootstrappers);
 
         // Start p2p
         let (mut network, mut oracle) =
             authenticated::Network::new(context.with_label("network"), p2p_cfg);
 
-        // Provide authorized peers
-        let participants: Set<PublicKey> = Set::from_iter_dedup(peers.clone());
-        oracle.update(EPOCH.get(), participants.clone()).await;
+        // Provide authorized peers (participants of every epoch and any observers)
+        let validators = Set::from_iter_dedup(
+            participants
+                .iter()
+                .cloned()
+                .chain(later_validators.iter().cloned()),
+        );
+        let acl = observers::Acl::new(
+            &context.with_label("observers"),
+            validators,
+            config.observers.as_ref(),
+        )
+        .expect("Could not parse observers");
+        oracle.update(EPOCH.get(), acl.peers()).await;
 
         // Register pending channel
-        let pending_limit = Quota::per_second(NonZeroU32::new(128).unwrap());
-        let pending = network.register(PENDING_CHANNEL, pending_limit, config.message_backlog);
+        let consensus_rate = NonZeroU32::new(parameters.consensus_rate_per_peer)
+            .expect("consensus rate must be non-zero");
+        let pending_limit = Quota::per_second(consensus_rate);
+        let (pending_sender, pending_receiver) =
+            network.register(PENDING_CHANNEL, pending_limit, config.message_backlog);
+        let pending = (
+            pending_sender,
+            acl.filter(Channel::Pending, pending_receiver),
+        );
 
         // Register recovered channel
-        let recovered_limit = Quota::per_second(NonZeroU32::new(128).unwrap());
-        let recovered =
+        let recovered_limit = Quota::per_second(consensus_rate);
+        let (recovered_sender, recovered_receiver) =
             network.register(RECOVERED_CHANNEL, recovered_limit, config.message_backlog);
+        let recovered = (
+            recovered_sender,
+            acl.filter(Channel::Recovered, recovered_receiver),
+        );
 
         // Register resolver channel
-        let resolver_limit = Quota::per_second(NonZeroU32::new(128).unwrap());
-        let resolver = network.register(RESOLVER_CHANNEL, resolver_limit, config.message_backlog);
+        let resolver_limit = Quota::per_second(consensus_rate);
+        let (resolver_sender, resolver_receiver) =
+            network.register(RESOLVER_CHANNEL, resolver_limit, config.message_backlog);
+        let resolver = (
+            resolver_sender,
+            acl.filter(Channel::Resolver, resolver_receiver),
+        );
 
         // Register broadcast channel
         let broadcaster_limit = Quota::per_second(NonZeroU32::new(8).unwrap());
-        let broadcaster = network.register(
+        let (broadcaster_sender, broadcaster_receiver) = network.register(
             BROADCASTER_CHANNEL,
             broadcaster_limit,
             config.message_backlog,
         );
+        let broadcaster = (
+            broadcaster_sender,
+            acl.filter(Channel::Broadcaster, broadcaster_receiver),
+        );
 
         // Register marshal channel
         let marshal_quota = Quota::per_second(NonZeroU32::new(8).unwrap());
-        let marshal = network.register(MARSHAL_CHANNEL, marshal_quota, config.message_backlog);
+        let (marshal_sender, marshal_receiver) =
+            network.register(MARSHAL_CHANNEL, marshal_quota, config.message_backlog);
+        let marshal = (
+            marshal_sender,
+            acl.filter(Channel::Marshal, marshal_receiver),
+        );
+
+        // Register transactions channel
+        let transactions_quota = Quota::per_second(NonZeroU32::new(128).unwrap());
+        let (transactions_sender, transactions_receiver) = network.register(
+            TRANSACTIONS_CHANNEL,
+            transactions_quota,
+            config.message_backlog,
+        );
+        let transactions = (
+            transactions_sender,
+            acl.filter(Channel::Transactions, transactions_receiver),
+        );
 
         // Create network
         let p2p = network.start();
 
-        let strategy = context
-            .create_strategy(NZUsize!(config.signature_threads))
-            .unwrap();
+        // Collect transactions to propose
+        let (mempool, pool) = mempool::Actor::new(
+            context.with_label("mempool"),
+            config.mempool.unwrap_or_default(),
+        );
+        let mempool = mempool.start(transactions);
 
-        // Create indexer
-        let mut indexer = None;
-        if let Some(uri) = config.indexer {
-            indexer = Some(Client::new(&uri, *identity, strategy.clone()));
+        // Verify signatures on named threads (and measure how long work waits to run)
+        let signature_pool = runtime
+            .signature_pool(NZUsize!(config.signature_threads))
+            .expect("Could not create signature threads");
+        if let Some(interval) = runtime.probe_interval() {
+            runtime::Probe::new(
+                context.with_label("saturation"),
+                interval,
+                signature_pool.clone(),
+            )
+            .start();
+        }
+        let strategy = Rayon::with_pool(signature_pool);
+
+        // Create indexers (the first is also used for backfill)
+        let mut indexers = Vec::new();
+        let uris = config
+            .indexer
+            .iter()
+            .chain(config.indexers.iter().flatten());
+        for uri in uris {
+            let mut builder = ClientBuilder::new(uri, *identity, strategy.clone())
+                .with_signer(signer.clone())
+                .with_digest_mode(config.digest_mode.unwrap_or_default());
+            for (index, transition) in transitions.iter().enumerate() {
+                let epoch = EPOCH.get() + index as u64 + 1;
+                builder = builder.with_epoch(epoch, *transition.committee.polynomial.public());
+            }
+
+            // Spread uploads across every replica behind the indexer's name
+            let client = match builder.clone().discover().await {
+                Ok(builder) => builder.build(),
+                Err(err) => {
+                    warn!(%uri, ?err, "failed to discover indexer replicas");
+                    builder.build()
+                }
+            };
+            indexers.push((uri.clone(), client));
+        }
+        let indexer = indexers.first().map(|(_, client)| client.clone());
+
+        // Fetch a checkpoint to start from (if state syncing)
+        let mut checkpoint = None;
+        if let Some(state_sync) = &config.state_sync {
+            let mut identities = IdentitySchedule::new(*identity);
+            for (index, transition) in transitions.iter().enumerate() {
+                let epoch = EPOCH.get() + index as u64 + 1;
+                identities.reconfigure(epoch, *transition.committee.polynomial.public());
+            }
+            let clients: Vec<_> = match state_sync.indexers {
+                true => indexers.iter().map(|(_, client)| client.clone()).collect(),
+                false => Vec::new(),
+            };
+            checkpoint = checkpoint::fetch(
+                &context,
+                &identities,
+                &strategy,
+                config.digest_mode.unwrap_or_default(),
+                &state_sync.replicas,
+                &clients,
+                state_sync.timeout(),
+            )
+            .await;
+        }
+
+        // Monitor storage usage
+        let mut guardrail = None;
+        if let Some(max_disk_usage) = config.max_disk_usage {
+            let (monitor, tripwire) = storage::Monitor::new(
+                context.with_label("storage"),
+                PathBuf::from(&config.directory),
+                max_disk_usage,
+            );
+            monitor.start();
+            guardrail = Some(tripwire);
         }
 
         // Create engine
-        let engine_cfg = engine::Config {
-            blocker: oracle.clone(),
-            partition_prefix: "engine".to_string(),
-            blocks_freezer_table_initial_size: BLOCKS_FREEZER_TABLE_INITIAL_SIZE,
-            finalized_freezer_table_initial_size: FINALIZED_FREEZER_TABLE_INITIAL_SIZE,
-            me: public_key.clone(),
-            participants,
-            mailbox_size: config.mailbox_size,
-            deque_size: config.deque_size,
-            leader_timeout: LEADER_TIMEOUT,
-            notarization_timeout: NOTARIZATION_TIMEOUT,
-            nullify_retry: NULLIFY_RETRY,
-            activity_timeout: ACTIVITY_TIMEOUT,
-            skip_timeout: SKIP_TIMEOUT,
-            fetch_timeout: FETCH_TIMEOUT,
-            max_fetch_count: MAX_FETCH_COUNT,
-            max_fetch_size: MAX_FETCH_SIZE,
-            fetch_concurrent: FETCH_CONCURRENT,
-            fetch_rate_per_peer: resolver_limit,
-            indexer,
+        let mut engine_cfg = engine::Config::builder(
+            oracle.clone(),
+            public_key.clone(),
             polynomial,
             share,
+            participants,
             strategy,
-        };
+        )
+        .with_mailbox_size(config.mailbox_size)
+        .with_deque_size(config.deque_size)
+        .with_freezer_table_initial_size(parameters.freezer_table_initial_size)
+        .with_timeouts(parameters.leader_timeout, parameters.notarization_timeout)
+        .with_nullify_retry(parameters.nullify_retry)
+        .with_fetch(parameters.fetch)
+        .with_application(application)
+        .with_upgrades(config.upgrades.clone().unwrap_or_default())
+        .with_epochs(transitions)
+        .with_mempool(pool)
+        .with_tuning(config.tuning.unwrap_or_default())
+        .with_verify_storage(verify_storage);
+        if !indexers.is_empty() {
+            let transport = config.upload_transport.unwrap_or_default();
+            let remotes = indexers
+                .into_iter()
+                .map(|(uri, client)| (uri, indexer::Remote::new(client, transport)))
+                .collect();
+            let fastest = indexer::Fastest::new(context.with_label("indexers"), remotes);
+            engine_cfg = engine_cfg.with_indexer(fastest);
+        }
+        if let Some(upload_batch) = config.upload_batch {
+            engine_cfg = engine_cfg.with_upload_batch(upload_batch);
+        }
+        if let Some(spool) = config.spool {
+            engine_cfg = engine_cfg.with_spool(spool);
+        }
+        if let Some(guardrail) = guardrail {
+            engine_cfg = engine_cfg.with_guardrail(guardrail);
+        }
+        if let Some(batch) = config.batch {
+            engine_cfg = engine_cfg.with_batch(batch);
+        }
+        if let Some(retain_blocks) = config.retain_blocks {
+            engine_cfg = engine_cfg.with_retain_blocks(retain_blocks);
+        }
+        if let Some(replica) = config.replica.clone() {
+            let directory = PathBuf::from(&config.directory).join("replica");
+            let sender = replica::start(directory, config.digest_mode.unwrap_or_default(), replica);
+            engine_cfg = engine_cfg.with_replica(sender);
+        }
+        #[cfg(feature = "faults")]
+        if let Some(faults) = config.faults.clone() {
+            engine_cfg = engine_cfg.with_faults(faults);
+        }
+        if let Some(checkpoint) = checkpoint {
+            engine_cfg = engine_cfg.with_checkpoint(checkpoint);
+        }
+        let engine_cfg = engine_cfg.build().expect("invalid engine config");
         let engine = engine::Engine::new(context.with_label("engine"), engine_cfg).await;
 
+        // Describe the engine's state in crash reports
+        let (blocks, finalizations) = engine.stored();
+        crashes.attach(crash::Sources {
+            processed: engine.processed(),
+            views: engine.views(),
+            blocks: blocks.clone(),
+            finalizations: finalizations.clone(),
+        });
+
+        // Monitor alert conditions
+        if let Some(alerts) = config.alerts {
+            let sources = alerts::Sources {
+                processed: engine.processed(),
+                views: engine.views(),
+                upload_failures: engine.upload_failures(),
+            };
+            alerts::Monitor::new(context.with_label("alerts"), alerts, peers.len(), sources)
+                .start();
+        }
+
+        // Serve healthchecks
+        if let Some(health) = config.health {
+            health::Server::new(context.with_label("health"), health, engine.processed()).start();
+        }
+
+        // Serve local queries
+        if let Some(query) = config.query.clone() {
+            let sources = query::Sources {
+                public_key: public_key.clone(),
+                marshal: engine.marshal(),
+                processed: engine.processed(),
+                views: engine.views(),
+                blocks,
+                finalizations,
+                peers: acl.peers(),
+            };
+            query::Server::new(context.with_label("query"), query, sources).start();
+        }
+
         let marshal_resolver_cfg = marshal::resolver::p2p::Config {
             public_key: public_key.clone(),
             manager: oracle.clone(),
@@ -282,12 +604,124 @@ fn main() {
         let marshal_resolver =
             marshal::resolver::p2p::init(&context, marshal_resolver_cfg, marshal);
 
-        // Start engine
+        // Start engine (pacing backfill and failing over to the indexer for backfill peers do
+        // not deliver)
+        let (marshal_resolver, backfill) = backfill::init(
+            context.with_label("backfill"),
+            config.backfill.unwrap_or_default(),
+            marshal_resolver,
+            indexer,
+        );
         let engine = engine.start(pending, recovered, resolver, broadcaster, marshal_resolver);
+        let tasks = vec![p2p, mempool, backfill, engine];
 
         // Wait for any task to error
-        if let Err(e) = try_join_all(vec![p2p, engine]).await {
+        if let Err(e) = try_join_all(tasks).await {
             error!(?e, "task failed");
         }
     });
 }
+
+/// Configure the p2p network.
+fn network_config(
+    config: &Config,
+    signer: PrivateKey,
+    address: SocketAddr,
+    bootstrappers: Vec<(PublicKey, Ingress)>,
+) -> authenticated::Config<PrivateKey> {
+    let p2p_namespace = union_unique(NAMESPACE, b"_P2P");
+    let listen = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), config.port);
+    let mut p2p_cfg = if config.local {
+        authenticated::Config::local(
+            signer,
+            &p2p_namespace,
+            listen,
+            address,
+            bootstrappers,
+            MAX_MESSAGE_SIZE,
+        )
+    } else {
+        authenticated::Config::recommended(
+            signer,
+            &p2p_namespace,
+            listen,
+            address,
+            bootstrappers,
+            MAX_MESSAGE_SIZE,
+        )
+    };
+    p2p_cfg.mailbox_size = config.mailbox_size;
+    p2p_cfg
+}
+
+/// Relay blocks between `participants` (see [relay::Relay]) until the network fails.
+async fn run_relay(
+    context: tokio::Context,
+    config: &Config,
+    relay: relay::Config,
+    p2p_cfg: authenticated::Config<PrivateKey>,
+    participants: Set<PublicKey>,
+) {
+    let public_key = p2p_cfg.crypto.public_key();
+    let (mut network, mut oracle) =
+        authenticated::Network::new(context.with_label("network"), p2p_cfg);
+
+    // Provide authorized peers (participants, any observers, and ourself)
+    let acl = observers::Acl::new(
+        &context.with_label("observers"),
+        participants.clone(),
+        config.observers.as_ref(),
+    )
+    .expect("Could not parse observers");
+    let peers = Set::from_iter_dedup(acl.peers().iter().cloned().chain([public_key.clone()]));
+    oracle.update(EPOCH.get(), peers).await;
+
+    // Register broadcast channel (with the same quota as validators)
+    let broadcaster_limit = Quota::per_second(NonZeroU32::new(8).unwrap());
+    let (broadcaster_sender, broadcaster_receiver) = network.register(
+        BROADCASTER_CHANNEL,
+        broadcaster_limit,
+        config.message_backlog,
+    );
+    let broadcaster = (
+        broadcaster_sender,
+        acl.filter(Channel::Broadcaster, broadcaster_receiver),
+    );
+
+    // Register marshal channel (to serve backfill of relayed blocks)
+    let marshal_quota = Quota::per_second(NonZeroU32::new(8).unwrap());
+    let (marshal_sender, marshal_receiver) =
+        network.register(MARSHAL_CHANNEL, marshal_quota, config.message_backlog);
+    let marshal = (
+        marshal_sender,
+        acl.filter(Channel::Marshal, marshal_receiver),
+    );
+    let p2p = network.start();
+
+    // Answer backfill requests (the resolver stops if its mailbox is dropped)
+    let marshal_resolver_cfg = marshal::resolver::p2p::Config {
+        public_key,
+        manager: oracle.clone(),
+        blocker: oracle,
+        mailbox_size: config.mailbox_size,
+        initial: Duration::from_secs(1),
+        timeout: Duration::from_secs(2),
+        fetch_retry_timeout: Duration::from_millis(100),
+        priority_requests: false,
+        priority_responses: false,
+    };
+    let (backfill, _resolver) =
+        marshal::resolver::p2p::init(&context, marshal_resolver_cfg, marshal);
+
+    // Start relay
+    let relay = relay::Relay::new(
+        context.with_label("relay"),
+        relay,
+        participants,
+        config.digest_mode.unwrap_or_default(),
+    )
+    .start(broadcaster, backfill);
+    if let Err(e) = try_join_all([p2p, relay]).await {
+        error!(?e, "task failed");
+    }
+}
@@ -0,0 +1,192 @@
+//! Checkpoints for state sync.
+//!
+//! Marshal backfills (and the application processes) every finalized block since genesis, so
+//! the time it takes a fresh validator to catch up grows with the length of the chain. Instead,
+//! the engine can start after a recent checkpoint (a finalized block and its finalization, see
+//! [crate::engine::ConfigBuilder::with_checkpoint]) and never fetch the blocks below it.
+//!
+//! [fetch] asks peers' read replicas (`GET /finalized/latest`, see [crate::replica]) and indexers
+//! for their latest finalized block, verifies each response against the identity of its epoch,
+//! and returns the highest. Sources that fail (or do not respond in time) are skipped.
+//!
+//! The engine only starts from a checkpoint if it has not processed any block yet (so restarts
+//! resume from local storage). Blocks below the checkpoint are never stored, so a state-synced
+//! validator cannot serve them to peers that backfill from it.
+
+use alto_client::{
+    consensus::{parse_message, Message},
+    Client, Query,
+};
+use alto_types::{DigestMode, Finalized, IdentitySchedule, Payload};
+use commonware_consensus::Heightable;
+use commonware_macros::select;
+use commonware_parallel::Strategy;
+use commonware_runtime::Clock;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::{future::Future, time::Duration};
+use tracing::{debug, info, warn};
+
+/// Default time to wait for each source to respond.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Configuration for state sync.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// URLs of peers' read replicas (like `http://validator:9093`) to fetch checkpoints from.
+    #[serde(default)]
+    pub replicas: Vec<String>,
+
+    /// Also fetch checkpoints from the configured indexers.
+    #[serde(default)]
+    pub indexers: bool,
+
+    /// Milliseconds to wait for each source to respond (defaults to [DEFAULT_TIMEOUT]).
+    pub timeout: Option<u64>,
+}
+
+impl Config {
+    /// Time to wait for each source to respond.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_TIMEOUT)
+    }
+}
+
+/// Parse and verify a checkpoint served by a replica (a [Kind](alto_types::Kind)-prefixed
+/// finalization frame).
+pub fn parse(
+    identities: &IdentitySchedule,
+    strategy: &impl Strategy,
+    digest_mode: &DigestMode,
+    frame: &[u8],
+) -> Option<Finalized> {
+    match parse_message(identities, strategy, digest_mode, frame) {
+        Ok(Message::Finalization(finalized)) => Some(finalized),
+        Ok(_) => {
+            debug!("checkpoint is not a finalization");
+            None
+        }
+        Err(e) => {
+            debug!(?e, "invalid checkpoint");
+            None
+        }
+    }
+}
+
+/// Fetch the latest finalized block from each of `replicas` and `indexers` (waiting at most
+/// `timeout` for each), returning the highest that verifies against `identities`.
+pub async fn fetch<E: Clock, S: Strategy>(
+    context: &E,
+    identities: &IdentitySchedule,
+    strategy: &S,
+    digest_mode: DigestMode,
+    replicas: &[String],
+    indexers: &[Client<S>],
+    timeout: Duration,
+) -> Option<Finalized> {
+    let http = reqwest::Client::new();
+    let from_replicas = join_all(replicas.iter().map(|url| {
+        let http = http.clone();
+        within(context, timeout, async move {
+            let response = match http.get(format!("{url}/finalized/latest")).send().await {
+                Ok(response) if response.status().is_success() => response,
+                Ok(response) => {
+                    warn!(%url, status = %response.status(), "replica did not serve a checkpoint");
+                    return None;
+                }
+                Err(e) => {
+                    warn!(%url, ?e, "failed to fetch checkpoint from replica");
+                    return None;
+                }
+            };
+            let frame = response.bytes().await.ok()?;
+            let checkpoint = parse(identities, strategy, &digest_mode, &frame);
+            if checkpoint.is_none() {
+                warn!(%url, "replica served an invalid checkpoint");
+            }
+            checkpoint
+        })
+    }));
+    let from_indexers = join_all(indexers.iter().map(|client| {
+        within(context, timeout, async move {
+            match client.block_get(Query::Latest).await {
+                Ok(Payload::Finalized(finalized)) => Some(*finalized),
+                Ok(Payload::Block(_)) => None,
+                Err(e) => {
+                    warn!(?e, "failed to fetch checkpoint from indexer");
+                    None
+                }
+            }
+        })
+    }));
+    let (from_replicas, from_indexers) = futures::join!(from_replicas, from_indexers);
+
+    // Start from the highest checkpoint
+    let checkpoint = from_replicas
+        .into_iter()
+        .chain(from_indexers)
+        .flatten()
+        .max_by_key(|checkpoint| checkpoint.block.height());
+    match &checkpoint {
+        Some(checkpoint) => info!(height = %checkpoint.block.height(), "fetched checkpoint"),
+        None => warn!("no checkpoint available"),
+    }
+    checkpoint
+}
+
+/// Wait at most `timeout` for `future`.
+async fn within<T>(
+    context: &impl Clock,
+    timeout: Duration,
+    future: impl Future<Output = Option<T>>,
+) -> Option<T> {
+    select! {
+        result = future => {
+            result
+        },
+        _ = context.sleep(timeout) => {
+            warn!(?timeout, "checkpoint source timed out");
+            None
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export;
+    use alto_types::{fixtures, Block, Kind};
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Hasher, Sha256};
+    use commonware_parallel::Sequential;
+
+    #[test]
+    fn test_parse() {
+        let fixture = fixtures::seeded(0, 4);
+        let identities = IdentitySchedule::new(fixtures::identity(&fixture.schemes));
+        let parent = Sha256::hash(b"genesis");
+        let finalized = fixtures::finalized(
+            &fixture.schemes,
+            10,
+            Block::new(parent, Height::new(10), 10),
+        );
+
+        // Replicas serve finalizations as exported
+        let frame = export::encode(&finalized);
+        assert_eq!(
+            parse(&identities, &Sequential, &DigestMode::Sha256, &frame),
+            Some(finalized.clone())
+        );
+
+        // Checkpoints of another network are rejected
+        let other = IdentitySchedule::new(fixtures::identity(&fixtures::seeded(1, 4).schemes));
+        assert!(parse(&other, &Sequential, &DigestMode::Sha256, &frame).is_none());
+
+        // Only finalizations are checkpoints
+        let seed = fixtures::frame(Kind::Seed, &fixtures::seed(&fixture.schemes, 10));
+        assert!(parse(&identities, &Sequential, &DigestMode::Sha256, &seed).is_none());
+        assert!(parse(&identities, &Sequential, &DigestMode::Sha256, &[]).is_none());
+    }
+}
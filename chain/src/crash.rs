@@ -0,0 +1,220 @@
+//! Crash reports.
+//!
+//! A panic in any actor leaves the validator in an unknown state, so the validator aborts
+//! instead of continuing without it. Logs alone rarely explain a crash in the field (they may
+//! be rotated away or only capture the last line), so before aborting, the hook installed by
+//! [Reporter::install] writes a report to `<directory>/crashes/<unix millis>.txt` with:
+//! - the panic message and location (and the thread that panicked),
+//! - a digest of the config file (to match the report with the config that was running),
+//! - the current view (and how many views in a row were nullified before it),
+//! - the latest processed height and the heights written to (and synced by) the finalized
+//!   archives, and
+//! - a backtrace.
+//!
+//! State is read without blocking, so a section is reported as unavailable if the panic left
+//! it locked.
+
+use crate::{application::Processed, batch, views};
+use commonware_cryptography::{sha256::Digest, Hasher, Sha256};
+use commonware_runtime::Clock;
+use std::{
+    backtrace::Backtrace,
+    fmt::Write as _,
+    fs, io,
+    panic::{self, PanicHookInfo},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::error;
+
+/// Directory (within the validator's storage directory) crash reports are written to.
+pub const DIRECTORY: &str = "crashes";
+
+/// Handles to the validator state included in crash reports.
+pub struct Sources<E: Clock> {
+    /// Latest processed height.
+    pub processed: Processed,
+
+    /// Current view.
+    pub views: views::Tracker<E>,
+
+    /// Heights of the finalized blocks archive.
+    pub blocks: batch::Heights,
+
+    /// Heights of the finalizations archive.
+    pub finalizations: batch::Heights,
+}
+
+impl<E: Clock> Sources<E> {
+    /// Describe the current state.
+    fn describe(&self) -> String {
+        let mut state = String::new();
+        match self.views.try_status() {
+            Some(status) => {
+                let leader = status.leader.map(|leader| leader.to_string());
+                let _ = writeln!(state, "epoch: {}", status.epoch);
+                let _ = writeln!(state, "view: {}", status.view);
+                let _ = writeln!(state, "leader: {}", leader.as_deref().unwrap_or("unknown"));
+                let _ = writeln!(state, "time_in_view: {:?}", status.time_in_view);
+                let _ = writeln!(state, "nullified_in_a_row: {}", status.nullified);
+                for leader in &status.nullified_leaders {
+                    let _ = writeln!(state, "nullified_leader: {leader}");
+                }
+                let _ = writeln!(state, "voters: {}", status.voters);
+            }
+            None => {
+                let _ = writeln!(state, "view: unavailable");
+            }
+        }
+        let _ = writeln!(state, "processed_height: {}", self.processed.get());
+        for (name, heights) in [
+            ("blocks", &self.blocks),
+            ("finalizations", &self.finalizations),
+        ] {
+            let _ = writeln!(
+                state,
+                "{name}_height: {} (synced: {})",
+                heights.written(),
+                heights.synced()
+            );
+        }
+        state
+    }
+}
+
+/// Describes the validator state (see [Sources]).
+type Describe = Box<dyn Fn() -> String + Send + Sync>;
+
+/// Writes crash reports.
+#[derive(Clone)]
+pub struct Reporter {
+    directory: PathBuf,
+    config: Digest,
+    state: Arc<Mutex<Option<Describe>>>,
+}
+
+impl Reporter {
+    /// Create a [Reporter] that writes reports to `directory` (the validator's storage
+    /// directory), identifying the running config by its contents (`config`).
+    pub fn new(directory: PathBuf, config: &[u8]) -> Self {
+        Self {
+            directory: directory.join(DIRECTORY),
+            config: Sha256::hash(config),
+            state: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Create a [Reporter] (see [Self::new]) and install it as the panic hook.
+    ///
+    /// After the default hook prints the panic, the report is written and the process is
+    /// aborted (even if the panic would otherwise be caught).
+    pub fn install(directory: PathBuf, config: &[u8]) -> Self {
+        let reporter = Self::new(directory, config);
+        let default = panic::take_hook();
+        panic::set_hook(Box::new({
+            let reporter = reporter.clone();
+            move |info| {
+                default(info);
+                match reporter.write(info, &Backtrace::force_capture().to_string()) {
+                    Ok(path) => error!(path = %path.display(), "wrote crash report"),
+                    Err(e) => error!(?e, "failed to write crash report"),
+                }
+                std::process::abort();
+            }
+        }));
+        reporter
+    }
+
+    /// Include the state in `sources` in future reports.
+    pub fn attach<E: Clock + Send + Sync + 'static>(&self, sources: Sources<E>) {
+        *self.state.lock().unwrap() = Some(Box::new(move || sources.describe()));
+    }
+
+    /// Write a report for the panic described by `info`.
+    pub fn write(&self, info: &PanicHookInfo<'_>, backtrace: &str) -> io::Result<PathBuf> {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown".to_string());
+        let location = info
+            .location()
+            .map(|location| location.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        self.write_report(&message, &location, backtrace)
+    }
+
+    fn write_report(&self, message: &str, location: &str, backtrace: &str) -> io::Result<PathBuf> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let state = match self.state.try_lock() {
+            Ok(state) => state.as_ref().map(|describe| describe()),
+            Err(_) => None,
+        };
+        let thread = std::thread::current();
+        let mut report = String::new();
+        let _ = writeln!(report, "time: {}", now.as_millis());
+        let _ = writeln!(report, "thread: {}", thread.name().unwrap_or("unnamed"));
+        let _ = writeln!(report, "panic: {message}");
+        let _ = writeln!(report, "location: {location}");
+        let _ = writeln!(report, "config: {}", self.config);
+        let _ = writeln!(report, "version: {}", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(report, "\n[state]");
+        report.push_str(state.as_deref().unwrap_or("unavailable\n"));
+        let _ = writeln!(report, "\n[backtrace]\n{backtrace}");
+
+        fs::create_dir_all(&self.directory)?;
+        let path = self.directory.join(format!("{}.txt", now.as_millis()));
+        fs::write(&path, report)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commonware_cryptography::{ed25519::PrivateKey, Signer};
+    use commonware_runtime::{deterministic, Metrics, Runner};
+    use commonware_utils::ordered::Set;
+
+    #[test]
+    fn test_report() {
+        let executor = deterministic::Runner::default();
+        executor.start(|context| async move {
+            let directory = std::env::temp_dir().join(format!("alto_crash_{}", std::process::id()));
+            let reporter = Reporter::new(directory.clone(), b"config");
+
+            // Reports without attached state only describe the panic
+            let path = reporter
+                .write_report("boom", "src/lib.rs:1:1", "backtrace")
+                .unwrap();
+            assert!(path.starts_with(directory.join(DIRECTORY)));
+            let report = fs::read_to_string(&path).unwrap();
+            assert!(report.contains("panic: boom\n"));
+            assert!(report.contains("location: src/lib.rs:1:1\n"));
+            assert!(report.contains(&format!("config: {}\n", Sha256::hash(b"config"))));
+            assert!(report.contains("[state]\nunavailable\n"));
+            assert!(report.contains("[backtrace]\nbacktrace"));
+
+            // Attached state is included
+            let participants = Set::from_iter_dedup([PrivateKey::from_seed(0).public_key()]);
+            let views = views::Tracker::new(context.with_label("views"), participants);
+            reporter.attach(Sources {
+                processed: Processed::default(),
+                views,
+                blocks: batch::Heights::default(),
+                finalizations: batch::Heights::default(),
+            });
+            let path = reporter.write_report("boom", "unknown", "").unwrap();
+            let report = fs::read_to_string(path).unwrap();
+            assert!(report.contains("view: 0\n"));
+            assert!(report.contains("processed_height: 0\n"));
+            assert!(report.contains("blocks_height: 0 (synced: 0)\n"));
+
+            fs::remove_dir_all(directory).unwrap();
+        });
+    }
+}
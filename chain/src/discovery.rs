@@ -0,0 +1,84 @@
+//! Peer discovery without a complete peers file.
+//!
+//! By default, every validator is started with a hosts (or peers) file listing the address of
+//! every participant. In discovery mode, a validator instead learns the participants from its
+//! genesis transcript and only needs the addresses of its `bootstrappers`: the p2p layer dials
+//! them and learns the addresses of all other participants from their signed address books (so
+//! adding a node only requires distributing its config, not updating every other node).
+
+use commonware_codec::DecodeExt;
+use commonware_cryptography::ed25519::PublicKey;
+use commonware_utils::from_hex_formatted;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, net::SocketAddr};
+use thiserror::Error;
+
+/// Configuration for discovery mode.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// Address other peers dial this validator at.
+    pub address: SocketAddr,
+
+    /// Addresses of the validator's bootstrappers (keyed by hex-encoded public key).
+    pub bootstrappers: HashMap<String, SocketAddr>,
+}
+
+/// Errors that can occur when resolving bootstrappers.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("invalid bootstrapper key: {0}")]
+    InvalidKey(String),
+    #[error("no address for bootstrapper: {0}")]
+    MissingAddress(String),
+}
+
+impl Config {
+    /// Resolve the address of each of `bootstrappers` (hex-encoded public keys).
+    pub fn resolve(&self, bootstrappers: &[String]) -> Result<Vec<(PublicKey, SocketAddr)>, Error> {
+        bootstrappers
+            .iter()
+            .map(|bootstrapper| {
+                let key = from_hex_formatted(bootstrapper)
+                    .and_then(|key| PublicKey::decode(key.as_ref()).ok())
+                    .ok_or_else(|| Error::InvalidKey(bootstrapper.clone()))?;
+                let address = self
+                    .bootstrappers
+                    .get(bootstrapper)
+                    .ok_or_else(|| Error::MissingAddress(bootstrapper.clone()))?;
+                Ok((key, *address))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commonware_cryptography::{ed25519::PrivateKey, Signer};
+
+    #[test]
+    fn test_resolve() {
+        let key = PrivateKey::from_seed(0).public_key();
+        let name = key.to_string();
+        let address: SocketAddr = "127.0.0.1:3000".parse().unwrap();
+        let config = Config {
+            address: "127.0.0.1:3002".parse().unwrap(),
+            bootstrappers: HashMap::from([(name.clone(), address)]),
+        };
+
+        // Configured bootstrappers are resolved
+        assert_eq!(config.resolve(&[name]).unwrap(), vec![(key, address)]);
+        assert!(config.resolve(&[]).unwrap().is_empty());
+
+        // Unknown (or invalid) bootstrappers are rejected
+        let other = PrivateKey::from_seed(1).public_key().to_string();
+        assert_eq!(
+            config.resolve(std::slice::from_ref(&other)).unwrap_err(),
+            Error::MissingAddress(other)
+        );
+        assert_eq!(
+            config.resolve(&["zz".to_string()]).unwrap_err(),
+            Error::InvalidKey("zz".to_string())
+        );
+    }
+}
@@ -1,61 +1,104 @@
+#[cfg(feature = "faults")]
+use crate::faults;
 use crate::{
-    application::Application,
+    application::{self, Application, Processed},
+    batch::{self, Batched},
+    epochs::{self, Schedule},
     indexer::{self, Indexer},
+    mempool, nullifications, propagation, replica,
+    retention::{self, Retention},
+    rules::{self, Rules},
+    skew, spool,
+    storage::{self, Guardrail},
+    views,
 };
-use alto_types::{Activity, Block, Finalization, Scheme, EPOCH, EPOCH_LENGTH, NAMESPACE};
+use alto_types::{Activity, Block, DigestMode, Finalization, Finalized, Scheme, EPOCH};
 use commonware_broadcast::buffered;
 use commonware_consensus::{
     application::marshaled::Marshaled as ConsensusMarshaled,
     marshal::{self, ingress::handler},
     simplex::{self, elector::Random, Engine as Consensus},
-    types::{Epoch, FixedEpocher, ViewDelta},
-    Reporters,
+    types::{Epoch, Epocher, ViewDelta},
+    Epochable, Heightable, Reporters,
 };
 use commonware_cryptography::{
     bls12381::primitives::{group, sharing::Sharing, variant::MinSig},
-    certificate::{ConstantProvider, Scheme as _},
+    certificate::{Provider, Scheme as _},
     ed25519::PublicKey,
     sha256::Digest,
 };
-use commonware_p2p::{Blocker, Receiver, Sender};
+use commonware_macros::select;
+use commonware_p2p::{
+    utils::mux::{Builder as _, Muxer},
+    Blocker, Receiver, Sender,
+};
 use commonware_parallel::Strategy;
 use commonware_resolver::Resolver;
 use commonware_runtime::{
     buffer::PoolRef, spawn_cell, Clock, ContextCell, Handle, Metrics, RayonPoolSpawner, Spawner,
     Storage,
 };
-use commonware_storage::archive::immutable;
-use commonware_utils::{ordered::Set, NZU16};
+use commonware_storage::{
+    archive::{immutable, prunable},
+    journal::segmented::variable,
+    translator::EightCap,
+};
+use commonware_utils::{ordered::Set, vec::NonEmptyVec, Participant, NZU16};
 use commonware_utils::{NZUsize, NZU64};
-use futures::{channel::mpsc, future::try_join_all};
+use futures::{channel::mpsc, future::try_join_all, StreamExt};
 use governor::clock::Clock as GClock;
 use governor::Quota;
+use prometheus_client::metrics::counter::Counter;
 use rand::{CryptoRng, Rng};
+use serde::{Deserialize, Serialize};
 use std::{
-    num::NonZero,
+    collections::HashSet,
+    num::{NonZero, NonZeroU32, NonZeroU64},
+    sync::Arc,
     time::{Duration, Instant},
 };
-use tracing::{error, info, warn};
+use thiserror::Error;
+use tracing::{debug, error, info, warn};
+
+/// Uploads consensus artifacts to an indexer (delayed when injecting faults).
+#[cfg(feature = "faults")]
+type Pusher<E, I> = indexer::Pusher<E, indexer::Batcher<faults::Delayed<E, I>>>;
+#[cfg(not(feature = "faults"))]
+type Pusher<E, I> = indexer::Pusher<E, indexer::Batcher<I>>;
+
+/// Retries failed uploads of the [Pusher].
+#[cfg(feature = "faults")]
+type Spool<E, I> = spool::Spool<E, indexer::Batcher<faults::Delayed<E, I>>>;
+#[cfg(not(feature = "faults"))]
+type Spool<E, I> = spool::Spool<E, indexer::Batcher<I>>;
 
 /// Reporter type for [simplex::Engine].
-type Reporter<E, I> =
-    Reporters<Activity, marshal::Mailbox<Scheme, Block>, Option<indexer::Pusher<E, I>>>;
+type Reporter<E, I> = Reporters<
+    Activity,
+    marshal::Mailbox<Scheme, Block>,
+    Reporters<
+        Activity,
+        Option<Pusher<E, I>>,
+        Reporters<
+            Activity,
+            Option<replica::Feeder<E>>,
+            Reporters<
+                Activity,
+                propagation::Tracker<E>,
+                Reporters<Activity, nullifications::Store<E>, views::Tracker<E>>,
+            >,
+        >,
+    >,
+>;
 
 /// To better support peers near tip during network instability, we multiply
 /// the consensus activity timeout by this factor.
 const SYNCER_ACTIVITY_TIMEOUT_MULTIPLIER: u64 = 10;
-const PRUNABLE_ITEMS_PER_SECTION: NonZero<u64> = NZU64!(4_096);
-const IMMUTABLE_ITEMS_PER_SECTION: NonZero<u64> = NZU64!(262_144);
-const FREEZER_TABLE_RESIZE_FREQUENCY: u8 = 4;
-const FREEZER_TABLE_RESIZE_CHUNK_SIZE: u32 = 2u32.pow(16); // 3MB
-const FREEZER_JOURNAL_TARGET_SIZE: u64 = 1024 * 1024 * 1024; // 1GB
-const FREEZER_JOURNAL_COMPRESSION: Option<u8> = Some(3);
-const REPLAY_BUFFER: NonZero<usize> = NZUsize!(8 * 1024 * 1024); // 8MB
-const WRITE_BUFFER: NonZero<usize> = NZUsize!(1024 * 1024); // 1MB
-const BUFFER_POOL_PAGE_SIZE: NonZero<u16> = NZU16!(4_096); // 4KB
-const BUFFER_POOL_CAPACITY: NonZero<usize> = NZUsize!(8_192); // 32MB
 const MAX_REPAIR: NonZero<usize> = NZUsize!(20);
 
+/// How often to check whether the last block of the current epoch was processed.
+const EPOCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// Configuration for the [Engine].
 pub struct Config<B: Blocker<PublicKey = PublicKey>, I: Indexer, S: Strategy> {
     pub blocker: B,
@@ -64,8 +107,11 @@ pub struct Config<B: Blocker<PublicKey = PublicKey>, I: Indexer, S: Strategy> {
     pub finalized_freezer_table_initial_size: u32,
     pub me: PublicKey,
     pub polynomial: Sharing<MinSig>,
-    pub share: group::Share,
+    /// Share of `polynomial` (if a participant of the first epoch).
+    pub share: Option<group::Share>,
     pub participants: Set<PublicKey>,
+    pub epochs: Vec<epochs::Transition>,
+    pub checkpoint: Option<Finalized>,
     pub mailbox_size: usize,
     pub deque_size: usize,
 
@@ -83,9 +129,835 @@ pub struct Config<B: Blocker<PublicKey = PublicKey>, I: Indexer, S: Strategy> {
     pub strategy: S,
 
     pub indexer: Option<I>,
+    pub upload_batch: Option<indexer::BatchConfig>,
+    pub spool: Option<spool::Config>,
+    pub replica: Option<mpsc::Sender<Finalized>>,
+
+    pub application: application::Config,
+    pub upgrades: Vec<rules::Upgrade>,
+    pub batch: Option<batch::Config>,
+    pub retain_blocks: Option<NonZeroU64>,
+    pub guardrail: Option<Guardrail>,
+    pub mempool: Option<mempool::Pool>,
+    pub executor: Option<Arc<dyn application::Executor>>,
+
+    pub verify_storage: bool,
+    pub tuning: Tuning,
+
+    #[cfg(feature = "faults")]
+    pub faults: faults::Config,
+}
+
+/// Default prefix for all storage partitions.
+pub const DEFAULT_PARTITION_PREFIX: &str = "engine";
+/// Default initial size of the freezer tables (~100MB).
+pub const DEFAULT_FREEZER_TABLE_INITIAL_SIZE: u32 = 2u32.pow(21);
+/// Default size of actor mailboxes.
+pub const DEFAULT_MAILBOX_SIZE: usize = 16_384;
+/// Default number of recent items to keep in the broadcast buffer (per peer).
+pub const DEFAULT_DEQUE_SIZE: usize = 10;
+/// Default time to wait for a leader to propose.
+pub const DEFAULT_LEADER_TIMEOUT: Duration = Duration::from_secs(1);
+/// Default time to wait for a proposal to be notarized.
+pub const DEFAULT_NOTARIZATION_TIMEOUT: Duration = Duration::from_secs(2);
+/// Default time to wait before rebroadcasting a nullify vote.
+pub const DEFAULT_NULLIFY_RETRY: Duration = Duration::from_secs(10);
+/// Default number of views to retain consensus activity for.
+pub const DEFAULT_ACTIVITY_TIMEOUT: ViewDelta = ViewDelta::new(256);
+/// Default number of views a leader may be inactive before being skipped.
+pub const DEFAULT_SKIP_TIMEOUT: ViewDelta = ViewDelta::new(32);
+/// Default time to wait for a fetch response.
+pub const DEFAULT_FETCH_TIMEOUT: Duration = Duration::from_secs(2);
+/// Default maximum number of items to request in a single fetch.
+pub const DEFAULT_MAX_FETCH_COUNT: usize = 16;
+/// Default maximum size (in bytes) of a fetch response.
+pub const DEFAULT_MAX_FETCH_SIZE: usize = 512 * 1024;
+/// Default number of concurrent fetches.
+pub const DEFAULT_FETCH_CONCURRENT: usize = 4;
+/// Default rate at which fetches may be sent to a single peer (per second).
+pub const DEFAULT_FETCH_RATE_PER_PEER: u32 = 128;
+/// Default number of items per section of the prunable archives (nullifications and
+/// notarized blocks).
+pub const DEFAULT_PRUNABLE_ITEMS_PER_SECTION: NonZero<u64> = NZU64!(4_096);
+/// Default number of items per section of the immutable archives (finalized blocks and
+/// finalizations).
+pub const DEFAULT_IMMUTABLE_ITEMS_PER_SECTION: NonZero<u64> = NZU64!(262_144);
+/// Default number of items in a freezer table bucket before the table is resized.
+pub const DEFAULT_FREEZER_TABLE_RESIZE_FREQUENCY: u8 = 4;
+/// Default number of freezer table entries to resize at once (~3MB).
+pub const DEFAULT_FREEZER_TABLE_RESIZE_CHUNK_SIZE: u32 = 2u32.pow(16);
+/// Default target size (in bytes) of each freezer value journal (1GB).
+pub const DEFAULT_FREEZER_JOURNAL_TARGET_SIZE: u64 = 1024 * 1024 * 1024;
+/// Default zstd level used to compress freezer values.
+pub const DEFAULT_FREEZER_JOURNAL_COMPRESSION: Option<u8> = Some(3);
+/// Default size (in bytes) of the buffer used to replay journals (8MB).
+pub const DEFAULT_REPLAY_BUFFER: NonZero<usize> = NZUsize!(8 * 1024 * 1024);
+/// Default size (in bytes) of the buffer used to write journals (1MB).
+pub const DEFAULT_WRITE_BUFFER: NonZero<usize> = NZUsize!(1024 * 1024);
+/// Default size (in bytes) of a buffer pool page (4KB).
+pub const DEFAULT_BUFFER_POOL_PAGE_SIZE: NonZero<u16> = NZU16!(4_096);
+/// Default number of pages in the buffer pool (32MB).
+pub const DEFAULT_BUFFER_POOL_CAPACITY: NonZero<usize> = NZUsize!(8_192);
+
+/// Highest zstd compression level.
+const MAX_COMPRESSION_LEVEL: u8 = 22;
+
+/// Limits on fetching missing consensus artifacts from peers.
+#[derive(Clone, Copy, Debug)]
+pub struct Fetch {
+    /// Time to wait for a fetch response.
+    pub timeout: Duration,
+
+    /// Maximum number of items to request in a single fetch.
+    pub max_count: usize,
+
+    /// Maximum size (in bytes) of a fetch response.
+    pub max_size: usize,
+
+    /// Number of concurrent fetches.
+    pub concurrent: usize,
+
+    /// Rate at which fetches may be sent to a single peer.
+    pub rate_per_peer: Quota,
+}
+
+impl Default for Fetch {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_FETCH_TIMEOUT,
+            max_count: DEFAULT_MAX_FETCH_COUNT,
+            max_size: DEFAULT_MAX_FETCH_SIZE,
+            concurrent: DEFAULT_FETCH_CONCURRENT,
+            rate_per_peer: Quota::per_second(NonZeroU32::new(DEFAULT_FETCH_RATE_PER_PEER).unwrap()),
+        }
+    }
+}
+
+/// Tuning of the storage backing the engine (archives, journals, and their buffers).
+///
+/// Section sizes, the freezer journal target size, and compression determine how data is laid
+/// out on disk, so they must not change once storage is created (tools that open the storage,
+/// like [crate::export], must use the same values). Buffer sizes may change between restarts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Tuning {
+    /// Number of items per section of the prunable archives.
+    pub prunable_items_per_section: NonZero<u64>,
+
+    /// Number of items per section of the immutable archives.
+    pub immutable_items_per_section: NonZero<u64>,
+
+    /// Number of items in a freezer table bucket before the table is resized.
+    pub freezer_table_resize_frequency: u8,
+
+    /// Number of freezer table entries to resize at once.
+    pub freezer_table_resize_chunk_size: u32,
+
+    /// Target size (in bytes) of each freezer value journal.
+    pub freezer_journal_target_size: u64,
+
+    /// zstd level used to compress freezer values (if any).
+    pub freezer_journal_compression: Option<u8>,
+
+    /// Size (in bytes) of the buffer used to replay journals.
+    pub replay_buffer: NonZero<usize>,
+
+    /// Size (in bytes) of the buffer used to write journals.
+    pub write_buffer: NonZero<usize>,
+
+    /// Size (in bytes) of a buffer pool page.
+    pub buffer_pool_page_size: NonZero<u16>,
+
+    /// Number of pages in the buffer pool.
+    pub buffer_pool_capacity: NonZero<usize>,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            prunable_items_per_section: DEFAULT_PRUNABLE_ITEMS_PER_SECTION,
+            immutable_items_per_section: DEFAULT_IMMUTABLE_ITEMS_PER_SECTION,
+            freezer_table_resize_frequency: DEFAULT_FREEZER_TABLE_RESIZE_FREQUENCY,
+            freezer_table_resize_chunk_size: DEFAULT_FREEZER_TABLE_RESIZE_CHUNK_SIZE,
+            freezer_journal_target_size: DEFAULT_FREEZER_JOURNAL_TARGET_SIZE,
+            freezer_journal_compression: DEFAULT_FREEZER_JOURNAL_COMPRESSION,
+            replay_buffer: DEFAULT_REPLAY_BUFFER,
+            write_buffer: DEFAULT_WRITE_BUFFER,
+            buffer_pool_page_size: DEFAULT_BUFFER_POOL_PAGE_SIZE,
+            buffer_pool_capacity: DEFAULT_BUFFER_POOL_CAPACITY,
+        }
+    }
+}
+
+/// Errors that can occur when building a [Config].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("not a participant")]
+    NotParticipant,
+    #[error("{0} must be non-zero")]
+    Zero(&'static str),
+    #[error("notarization timeout must not be less than leader timeout")]
+    NotarizationBeforeLeader,
+    #[error("skip timeout must not exceed activity timeout")]
+    SkipExceedsActivity,
+    #[error("freezer table initial size must be a power of two")]
+    FreezerTableSize,
+    #[error("compression level must be at most {MAX_COMPRESSION_LEVEL}")]
+    Compression,
+    #[error("invalid upgrades: {0}")]
+    Upgrades(#[from] rules::Error),
+    #[error("invalid epochs: {0}")]
+    Epochs(#[from] epochs::Error),
+    #[error("checkpoint is not finalized by the validators of its epoch")]
+    Checkpoint,
+}
+
+impl<B: Blocker<PublicKey = PublicKey>, I: Indexer, S: Strategy> Config<B, I, S> {
+    /// Create a [ConfigBuilder] with the required keys and participants of the first epoch
+    /// (all other values use defaults).
+    pub fn builder(
+        blocker: B,
+        me: PublicKey,
+        polynomial: Sharing<MinSig>,
+        share: Option<group::Share>,
+        participants: Set<PublicKey>,
+        strategy: S,
+    ) -> ConfigBuilder<B, I, S> {
+        ConfigBuilder {
+            config: Config {
+                blocker,
+                partition_prefix: DEFAULT_PARTITION_PREFIX.to_string(),
+                blocks_freezer_table_initial_size: DEFAULT_FREEZER_TABLE_INITIAL_SIZE,
+                finalized_freezer_table_initial_size: DEFAULT_FREEZER_TABLE_INITIAL_SIZE,
+                me,
+                polynomial,
+                share,
+                participants,
+                epochs: Vec::new(),
+                checkpoint: None,
+                mailbox_size: DEFAULT_MAILBOX_SIZE,
+                deque_size: DEFAULT_DEQUE_SIZE,
+                leader_timeout: DEFAULT_LEADER_TIMEOUT,
+                notarization_timeout: DEFAULT_NOTARIZATION_TIMEOUT,
+                nullify_retry: DEFAULT_NULLIFY_RETRY,
+                fetch_timeout: DEFAULT_FETCH_TIMEOUT,
+                activity_timeout: DEFAULT_ACTIVITY_TIMEOUT,
+                skip_timeout: DEFAULT_SKIP_TIMEOUT,
+                max_fetch_count: DEFAULT_MAX_FETCH_COUNT,
+                max_fetch_size: DEFAULT_MAX_FETCH_SIZE,
+                fetch_concurrent: DEFAULT_FETCH_CONCURRENT,
+                fetch_rate_per_peer: Quota::per_second(
+                    NonZeroU32::new(DEFAULT_FETCH_RATE_PER_PEER).unwrap(),
+                ),
+                strategy,
+                indexer: None,
+                upload_batch: None,
+                spool: None,
+                replica: None,
+                application: application::Config::default(),
+                upgrades: Vec::new(),
+                batch: None,
+                retain_blocks: None,
+                guardrail: None,
+                mempool: None,
+                executor: None,
+                verify_storage: false,
+                tuning: Tuning::default(),
+                #[cfg(feature = "faults")]
+                faults: faults::Config::default(),
+            },
+        }
+    }
+
+    /// Create the [Schedule] of the validators of every epoch.
+    fn schedule(&self) -> Result<Schedule, epochs::Error> {
+        let genesis = epochs::Committee {
+            participants: self.participants.clone(),
+            polynomial: self.polynomial.clone(),
+            share: self.share.clone(),
+        };
+        Schedule::new(&self.me, genesis, self.epochs.clone())
+    }
+}
+
+/// Builder for a [Config].
+pub struct ConfigBuilder<B: Blocker<PublicKey = PublicKey>, I: Indexer, S: Strategy> {
+    config: Config<B, I, S>,
+}
+
+impl<B: Blocker<PublicKey = PublicKey>, I: Indexer, S: Strategy> ConfigBuilder<B, I, S> {
+    /// Set the prefix for all storage partitions.
+    pub fn with_partition_prefix(mut self, partition_prefix: impl Into<String>) -> Self {
+        self.config.partition_prefix = partition_prefix.into();
+        self
+    }
+
+    /// Set the initial size of the blocks and finalizations freezer tables.
+    pub fn with_freezer_table_initial_size(mut self, size: u32) -> Self {
+        self.config.blocks_freezer_table_initial_size = size;
+        self.config.finalized_freezer_table_initial_size = size;
+        self
+    }
+
+    /// Set the size of actor mailboxes.
+    pub fn with_mailbox_size(mut self, mailbox_size: usize) -> Self {
+        self.config.mailbox_size = mailbox_size;
+        self
+    }
+
+    /// Set the number of recent items to keep in the broadcast buffer (per peer).
+    pub fn with_deque_size(mut self, deque_size: usize) -> Self {
+        self.config.deque_size = deque_size;
+        self
+    }
+
+    /// Set the leader and notarization timeouts.
+    pub fn with_timeouts(mut self, leader: Duration, notarization: Duration) -> Self {
+        self.config.leader_timeout = leader;
+        self.config.notarization_timeout = notarization;
+        self
+    }
+
+    /// Set the time to wait before rebroadcasting a nullify vote.
+    pub fn with_nullify_retry(mut self, nullify_retry: Duration) -> Self {
+        self.config.nullify_retry = nullify_retry;
+        self
+    }
+
+    /// Set the number of views to retain activity for and the number of views a leader may be
+    /// inactive before being skipped.
+    pub fn with_view_timeouts(mut self, activity: ViewDelta, skip: ViewDelta) -> Self {
+        self.config.activity_timeout = activity;
+        self.config.skip_timeout = skip;
+        self
+    }
+
+    /// Set the [Fetch] timeout, limits, concurrency, and per-peer rate.
+    pub fn with_fetch(mut self, fetch: Fetch) -> Self {
+        self.config.fetch_timeout = fetch.timeout;
+        self.config.max_fetch_count = fetch.max_count;
+        self.config.max_fetch_size = fetch.max_size;
+        self.config.fetch_concurrent = fetch.concurrent;
+        self.config.fetch_rate_per_peer = fetch.rate_per_peer;
+        self
+    }
+
+    /// Upload consensus artifacts to an indexer.
+    pub fn with_indexer(mut self, indexer: I) -> Self {
+        self.config.indexer = Some(indexer);
+        self
+    }
+
+    /// Upload artifacts to the indexer in batches (see [indexer::Batcher]) instead of one per
+    /// request.
+    pub fn with_upload_batch(mut self, batch: indexer::BatchConfig) -> Self {
+        self.config.upload_batch = Some(batch);
+        self
+    }
+
+    /// Set how failed indexer uploads are retried (see [spool::Spool]).
+    pub fn with_spool(mut self, spool: spool::Config) -> Self {
+        self.config.spool = Some(spool);
+        self
+    }
+
+    /// Copy finalized blocks to a read replica (see [replica::start]).
+    pub fn with_replica(mut self, replica: mpsc::Sender<Finalized>) -> Self {
+        self.config.replica = Some(replica);
+        self
+    }
+
+    /// Set the [application::Config].
+    pub fn with_application(mut self, application: application::Config) -> Self {
+        self.config.application = application;
+        self
+    }
+
+    /// Hand consensus over to a new validator set at the height of each of `epochs` (ordered by
+    /// height, see [epochs]).
+    pub fn with_epochs(mut self, epochs: Vec<epochs::Transition>) -> Self {
+        self.config.epochs = epochs;
+        self
+    }
+
+    /// Start processing after `checkpoint` instead of from genesis (if no block has been
+    /// processed yet, see [crate::checkpoint]).
+    pub fn with_checkpoint(mut self, checkpoint: Finalized) -> Self {
+        self.config.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Change the block validity rules at the height of each of `upgrades` (ordered by height).
+    pub fn with_upgrades(mut self, upgrades: Vec<rules::Upgrade>) -> Self {
+        self.config.upgrades = upgrades;
+        self
+    }
+
+    /// Sync writes to the finalized archives in batches (see [batch]) instead of after every
+    /// write.
+    pub fn with_batch(mut self, batch: batch::Config) -> Self {
+        self.config.batch = Some(batch);
+        self
+    }
+
+    /// Only keep finalized blocks (and their finalizations) up to `retain_blocks` heights below
+    /// the latest processed block (see [retention]) instead of every height.
+    pub fn with_retain_blocks(mut self, retain_blocks: NonZeroU64) -> Self {
+        self.config.retain_blocks = Some(retain_blocks);
+        self
+    }
+
+    /// Refuse to propose when the [Guardrail] is tripped.
+    pub fn with_guardrail(mut self, guardrail: Guardrail) -> Self {
+        self.config.guardrail = Some(guardrail);
+        self
+    }
+
+    /// Include transactions from the [mempool::Pool] in proposals (and reject blocks with
+    /// transactions that fail its [mempool::Validator], if any).
+    pub fn with_mempool(mut self, mempool: mempool::Pool) -> Self {
+        self.config.mempool = Some(mempool);
+        self
+    }
+
+    /// Include the receipts of executing each proposal with the [application::Executor] (and
+    /// reject blocks whose receipts do not match it). Every participant must install the same
+    /// executor.
+    pub fn with_executor(mut self, executor: Arc<dyn application::Executor>) -> Self {
+        self.config.executor = Some(executor);
+        self
+    }
+
+    /// Verify the integrity of all stored items on startup.
+    pub fn with_verify_storage(mut self, verify_storage: bool) -> Self {
+        self.config.verify_storage = verify_storage;
+        self
+    }
+
+    /// Set the [Tuning] of the engine's storage.
+    pub fn with_tuning(mut self, tuning: Tuning) -> Self {
+        self.config.tuning = tuning;
+        self
+    }
+
+    /// Inject faults (for chaos testing).
+    #[cfg(feature = "faults")]
+    pub fn with_faults(mut self, faults: faults::Config) -> Self {
+        self.config.faults = faults;
+        self
+    }
+
+    /// Validate and build the [Config].
+    pub fn build(self) -> Result<Config<B, I, S>, ConfigError> {
+        let config = self.config;
+        let participating = std::iter::once(&config.participants)
+            .chain(
+                config
+                    .epochs
+                    .iter()
+                    .map(|epoch| &epoch.committee.participants),
+            )
+            .any(|participants| participants.position(&config.me).is_some());
+        if !participating {
+            return Err(ConfigError::NotParticipant);
+        }
+        for (name, value) in [
+            ("leader timeout", config.leader_timeout),
+            ("notarization timeout", config.notarization_timeout),
+            ("nullify retry", config.nullify_retry),
+            ("fetch timeout", config.fetch_timeout),
+        ] {
+            if value.is_zero() {
+                return Err(ConfigError::Zero(name));
+            }
+        }
+        for (name, value) in [
+            ("mailbox size", config.mailbox_size),
+            ("deque size", config.deque_size),
+            ("max fetch count", config.max_fetch_count),
+            ("max fetch size", config.max_fetch_size),
+            ("fetch concurrency", config.fetch_concurrent),
+        ] {
+            if value == 0 {
+                return Err(ConfigError::Zero(name));
+            }
+        }
+        if config.activity_timeout.get() == 0 {
+            return Err(ConfigError::Zero("activity timeout"));
+        }
+        if config.skip_timeout.get() == 0 {
+            return Err(ConfigError::Zero("skip timeout"));
+        }
+        if config.notarization_timeout < config.leader_timeout {
+            return Err(ConfigError::NotarizationBeforeLeader);
+        }
+        if config.skip_timeout > config.activity_timeout {
+            return Err(ConfigError::SkipExceedsActivity);
+        }
+        if !config.blocks_freezer_table_initial_size.is_power_of_two()
+            || !config
+                .finalized_freezer_table_initial_size
+                .is_power_of_two()
+        {
+            return Err(ConfigError::FreezerTableSize);
+        }
+        if config
+            .tuning
+            .freezer_journal_compression
+            .is_some_and(|level| level > MAX_COMPRESSION_LEVEL)
+        {
+            return Err(ConfigError::Compression);
+        }
+        Rules::new(config.application.timestamp_policy, &config.upgrades)?;
+        let schedule = config.schedule()?;
+        if let Some(checkpoint) = &config.checkpoint {
+            let epoch = schedule
+                .containing(checkpoint.block.height())
+                .expect("every height is scheduled")
+                .epoch();
+            let scheme = schedule.scoped(epoch).expect("epoch is scheduled");
+            if checkpoint.proof.epoch() != epoch || !checkpoint.verify(&scheme, &config.strategy) {
+                return Err(ConfigError::Checkpoint);
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Archive of finalizations (indexed by height and keyed by block digest).
+pub type FinalizationArchive<E> = immutable::Archive<E, Digest, Finalization>;
+
+/// Archive of finalized blocks (indexed by height and keyed by digest).
+pub type BlockArchive<E> = immutable::Archive<E, Digest, Block>;
+
+/// Finalizations stored by the engine (pruned if [Config::retain_blocks] is set).
+pub type FinalizationStore<E> = retention::Archive<E, Finalization>;
+
+/// Finalized blocks stored by the engine (pruned if [Config::retain_blocks] is set).
+pub type BlockStore<E> = retention::Archive<E, Block>;
+
+/// Create the buffer pool shared by all archives.
+pub fn buffer_pool(tuning: &Tuning) -> PoolRef {
+    PoolRef::new(tuning.buffer_pool_page_size, tuning.buffer_pool_capacity)
+}
+
+/// Partition of the ordinal (height index) of the [FinalizationArchive].
+fn finalizations_ordinal_partition(partition_prefix: &str) -> String {
+    format!("{partition_prefix}-finalizations-by-height-ordinal")
+}
+
+/// Partition of the ordinal (height index) of the [BlockArchive].
+fn blocks_ordinal_partition(partition_prefix: &str) -> String {
+    format!("{partition_prefix}-finalized-blocks-ordinal")
+}
+
+/// Partitions of the pruned finalizations (keys and values).
+fn retained_finalizations_partitions(partition_prefix: &str) -> [String; 2] {
+    [
+        format!("{partition_prefix}-retained-finalizations-key"),
+        format!("{partition_prefix}-retained-finalizations-value"),
+    ]
+}
+
+/// Partitions of the pruned finalized blocks (keys and values).
+fn retained_blocks_partitions(partition_prefix: &str) -> [String; 2] {
+    [
+        format!("{partition_prefix}-retained-blocks-key"),
+        format!("{partition_prefix}-retained-blocks-value"),
+    ]
+}
+
+/// Initialize the [FinalizationArchive] stored under `partition_prefix`.
+pub async fn init_finalizations<E: Storage + Metrics + Clock>(
+    context: E,
+    partition_prefix: &str,
+    freezer_table_initial_size: u32,
+    buffer_pool: PoolRef,
+    tuning: &Tuning,
+) -> FinalizationArchive<E> {
+    immutable::Archive::init(
+        context,
+        immutable::Config {
+            metadata_partition: format!("{}-finalizations-by-height-metadata", partition_prefix),
+            freezer_table_partition: format!(
+                "{}-finalizations-by-height-freezer-table",
+                partition_prefix
+            ),
+            freezer_table_initial_size,
+            freezer_table_resize_frequency: tuning.freezer_table_resize_frequency,
+            freezer_table_resize_chunk_size: tuning.freezer_table_resize_chunk_size,
+            freezer_key_partition: format!(
+                "{}-finalizations-by-height-freezer-key-journal",
+                partition_prefix
+            ),
+            freezer_key_buffer_pool: buffer_pool,
+            freezer_key_write_buffer: tuning.write_buffer,
+            freezer_value_partition: format!(
+                "{}-finalizations-by-height-freezer-value-journal",
+                partition_prefix
+            ),
+            freezer_value_write_buffer: tuning.write_buffer,
+            freezer_value_target_size: tuning.freezer_journal_target_size,
+            freezer_value_compression: tuning.freezer_journal_compression,
+            ordinal_partition: finalizations_ordinal_partition(partition_prefix),
+            ordinal_write_buffer: tuning.write_buffer,
+            items_per_section: tuning.immutable_items_per_section,
+            codec_config: Scheme::certificate_codec_config_unbounded(),
+            replay_buffer: tuning.replay_buffer,
+        },
+    )
+    .await
+    .expect("failed to initialize finalizations by height archive")
+}
+
+/// Initialize the [BlockArchive] stored under `partition_prefix` (decoding blocks with
+/// `digest_mode`).
+pub async fn init_blocks<E: Storage + Metrics + Clock>(
+    context: E,
+    partition_prefix: &str,
+    freezer_table_initial_size: u32,
+    buffer_pool: PoolRef,
+    digest_mode: DigestMode,
+    tuning: &Tuning,
+) -> BlockArchive<E> {
+    immutable::Archive::init(
+        context,
+        immutable::Config {
+            metadata_partition: format!("{}-finalized_blocks-metadata", partition_prefix),
+            freezer_table_partition: format!("{}-finalized_blocks-freezer-table", partition_prefix),
+            freezer_table_initial_size,
+            freezer_table_resize_frequency: tuning.freezer_table_resize_frequency,
+            freezer_table_resize_chunk_size: tuning.freezer_table_resize_chunk_size,
+            freezer_key_partition: format!(
+                "{}-finalized-blocks-freezer-key-journal",
+                partition_prefix
+            ),
+            freezer_key_buffer_pool: buffer_pool,
+            freezer_key_write_buffer: tuning.write_buffer,
+            freezer_value_partition: format!(
+                "{}-finalized-blocks-freezer-value-journal",
+                partition_prefix
+            ),
+            freezer_value_write_buffer: tuning.write_buffer,
+            freezer_value_target_size: tuning.freezer_journal_target_size,
+            freezer_value_compression: tuning.freezer_journal_compression,
+            ordinal_partition: blocks_ordinal_partition(partition_prefix),
+            ordinal_write_buffer: tuning.write_buffer,
+            items_per_section: tuning.immutable_items_per_section,
+            codec_config: digest_mode,
+            replay_buffer: tuning.replay_buffer,
+        },
+    )
+    .await
+    .expect("failed to initialize finalized blocks archive")
+}
+
+/// Initialize the [FinalizationStore] stored under `partition_prefix` (pruned according to
+/// `retention`, if set).
+///
+/// # Panics
+///
+/// Panics if the finalizations were stored in the other mode.
+pub async fn init_finalization_store<E: Storage + Metrics + Clock>(
+    context: E,
+    partition_prefix: &str,
+    freezer_table_initial_size: u32,
+    buffer_pool: PoolRef,
+    tuning: &Tuning,
+    retention: Option<Retention>,
+) -> FinalizationStore<E> {
+    let [key_partition, value_partition] = retained_finalizations_partitions(partition_prefix);
+    if let Err(e) = retention::check(
+        &context,
+        retention.is_some(),
+        std::slice::from_ref(&key_partition),
+        &[finalizations_ordinal_partition(partition_prefix)],
+    )
+    .await
+    {
+        panic!("failed to initialize finalizations: {e}");
+    }
+    let Some(retention) = retention else {
+        let archive = init_finalizations(
+            context,
+            partition_prefix,
+            freezer_table_initial_size,
+            buffer_pool,
+            tuning,
+        )
+        .await;
+        return retention::Archive::All(archive);
+    };
+    let archive = prunable::Archive::init(
+        context,
+        prunable::Config {
+            translator: EightCap,
+            key_partition,
+            key_buffer_pool: buffer_pool,
+            value_partition,
+            compression: tuning.freezer_journal_compression,
+            codec_config: Scheme::certificate_codec_config_unbounded(),
+            items_per_section: tuning.prunable_items_per_section,
+            key_write_buffer: tuning.write_buffer,
+            value_write_buffer: tuning.write_buffer,
+            replay_buffer: tuning.replay_buffer,
+        },
+    )
+    .await
+    .expect("failed to initialize retained finalizations archive");
+    retention::Archive::Recent { archive, retention }
+}
+
+/// Initialize the [BlockStore] stored under `partition_prefix` (decoding blocks with
+/// `digest_mode` and pruning according to `retention`, if set).
+///
+/// # Panics
+///
+/// Panics if the blocks were stored in the other mode.
+pub async fn init_block_store<E: Storage + Metrics + Clock>(
+    context: E,
+    partition_prefix: &str,
+    freezer_table_initial_size: u32,
+    buffer_pool: PoolRef,
+    digest_mode: DigestMode,
+    tuning: &Tuning,
+    retention: Option<Retention>,
+) -> BlockStore<E> {
+    let [key_partition, value_partition] = retained_blocks_partitions(partition_prefix);
+    if let Err(e) = retention::check(
+        &context,
+        retention.is_some(),
+        std::slice::from_ref(&key_partition),
+        &[blocks_ordinal_partition(partition_prefix)],
+    )
+    .await
+    {
+        panic!("failed to initialize finalized blocks: {e}");
+    }
+    let Some(retention) = retention else {
+        let archive = init_blocks(
+            context,
+            partition_prefix,
+            freezer_table_initial_size,
+            buffer_pool,
+            digest_mode,
+            tuning,
+        )
+        .await;
+        return retention::Archive::All(archive);
+    };
+    let archive = prunable::Archive::init(
+        context,
+        prunable::Config {
+            translator: EightCap,
+            key_partition,
+            key_buffer_pool: buffer_pool,
+            value_partition,
+            compression: tuning.freezer_journal_compression,
+            codec_config: digest_mode,
+            items_per_section: tuning.prunable_items_per_section,
+            key_write_buffer: tuning.write_buffer,
+            value_write_buffer: tuning.write_buffer,
+            replay_buffer: tuning.replay_buffer,
+        },
+    )
+    .await
+    .expect("failed to initialize retained blocks archive");
+    retention::Archive::Recent { archive, retention }
+}
+
+/// Journal of the votes and certificates verified by consensus (sectioned by view).
+pub type ConsensusJournal<E> = variable::Journal<E, simplex::types::Artifact<Scheme, Digest>>;
+
+/// Partition of the [ConsensusJournal] of `epoch` (each epoch restarts its views, so each is
+/// journaled separately).
+fn consensus_partition(partition_prefix: &str, epoch: Epoch) -> String {
+    match epoch == EPOCH {
+        true => format!("{partition_prefix}-consensus"),
+        false => format!("{partition_prefix}-consensus-epoch-{}", epoch.get()),
+    }
+}
+
+/// Get the partition of the [spool::Spool] stored under `partition_prefix`.
+fn spool_partition(partition_prefix: &str) -> String {
+    format!("{partition_prefix}-upload-spool")
+}
+
+/// Open the [ConsensusJournal] of `epoch` stored under `partition_prefix` (as consensus does at
+/// startup).
+pub async fn init_consensus_journal<E: Storage + Metrics>(
+    context: E,
+    partition_prefix: &str,
+    epoch: Epoch,
+    buffer_pool: PoolRef,
+    tuning: &Tuning,
+) -> Result<ConsensusJournal<E>, commonware_storage::journal::Error> {
+    variable::Journal::init(
+        context,
+        variable::Config {
+            partition: consensus_partition(partition_prefix, epoch),
+            compression: None,
+            codec_config: (),
+            buffer_pool,
+            write_buffer: tuning.write_buffer,
+        },
+    )
+    .await
 }
 
-type Marshaled<E> = ConsensusMarshaled<E, Scheme, Application, Block, FixedEpocher>;
+/// Verify every finalization and finalized block stored under `partition_prefix`,
+/// [storage::quarantine]ing corrupted sections so they are no longer served.
+///
+/// Only the unpruned archives are verified (see [retention]).
+///
+/// Must be called before the archives are opened for use.
+pub async fn verify_storage<E: Storage + Metrics + Clock>(
+    context: E,
+    partition_prefix: &str,
+    freezer_table_initial_size: u32,
+    digest_mode: DigestMode,
+    tuning: &Tuning,
+) -> Result<(storage::Report, storage::Report), commonware_runtime::Error> {
+    let items_per_section = tuning.immutable_items_per_section.get();
+    let finalizations = {
+        let archive = init_finalizations(
+            context.with_label("verify_finalizations"),
+            partition_prefix,
+            freezer_table_initial_size,
+            buffer_pool(tuning),
+            tuning,
+        )
+        .await;
+        storage::verify(&archive, items_per_section).await
+    };
+    let blocks = {
+        let archive = init_blocks(
+            context.with_label("verify_blocks"),
+            partition_prefix,
+            freezer_table_initial_size,
+            buffer_pool(tuning),
+            digest_mode,
+            tuning,
+        )
+        .await;
+        storage::verify(&archive, items_per_section).await
+    };
+    storage::quarantine(
+        &context,
+        &finalizations_ordinal_partition(partition_prefix),
+        &finalizations.corrupted,
+    )
+    .await?;
+    storage::quarantine(
+        &context,
+        &blocks_ordinal_partition(partition_prefix),
+        &blocks.corrupted,
+    )
+    .await?;
+    Ok((finalizations, blocks))
+}
+
+type Marshaled<E> = ConsensusMarshaled<E, Scheme, Application, Block, Schedule>;
 
 /// The engine that drives the [Application].
 #[allow(clippy::type_complexity)]
@@ -99,19 +971,29 @@ pub struct Engine<
 
     buffer: buffered::Engine<E, PublicKey, Block>,
     buffer_mailbox: buffered::Mailbox<PublicKey, Block>,
+    propagation: propagation::Tracker<E>,
+    views: views::Tracker<E>,
+    nullifications: nullifications::Store<E>,
+    processed: Processed,
+    stored: (batch::Heights, batch::Heights),
+    upload_failures: Option<Counter>,
+    spool: Option<Spool<E, I>>,
     marshal: marshal::Actor<
         E,
         Block,
-        ConstantProvider<Scheme, Epoch>,
-        immutable::Archive<E, Digest, Finalization>,
-        immutable::Archive<E, Digest, Block>,
-        FixedEpocher,
+        Schedule,
+        Batched<E, FinalizationStore<E>>,
+        Batched<E, BlockStore<E>>,
+        Schedule,
         S,
     >,
+    marshal_mailbox: marshal::Mailbox<Scheme, Block>,
     marshaled: Marshaled<E>,
 
-    consensus:
-        Consensus<E, Scheme, Random, B, Digest, Marshaled<E>, Marshaled<E>, Reporter<E, I>, S>,
+    epochs: Epochs<E, B, S, I>,
+
+    #[cfg(feature = "faults")]
+    faults: faults::Injector<E>,
 }
 
 impl<
@@ -123,6 +1005,27 @@ impl<
 {
     /// Create a new [Engine].
     pub async fn new(context: E, cfg: Config<B, I, S>) -> Self {
+        // Track block propagation
+        let propagation = propagation::Tracker::new(
+            context.with_label("propagation"),
+            cfg.participants
+                .position(&cfg.me)
+                .map(Participant::from_usize),
+            cfg.application.digest_mode,
+        );
+
+        // Track the current view (of every epoch)
+        let schedule = cfg
+            .schedule()
+            .expect("epochs are validated when building the config");
+        let mut views = views::Tracker::new(context.with_label("views"), cfg.participants.clone());
+        let mut epoch = EPOCH;
+        while epoch < schedule.latest() {
+            epoch = epoch.next();
+            let participants = schedule.participants(epoch).expect("epoch is scheduled");
+            views = views.with_epoch(epoch, participants.clone());
+        }
+
         // Create the buffer
         let (buffer, buffer_mailbox) = buffered::Engine::new(
             context.with_label("buffer"),
@@ -131,105 +1034,128 @@ impl<
                 mailbox_size: cfg.mailbox_size,
                 deque_size: cfg.deque_size,
                 priority: true,
-                codec_config: (),
+                codec_config: cfg.application.digest_mode,
             },
         );
 
         // Create the buffer pool
-        let buffer_pool = PoolRef::new(BUFFER_POOL_PAGE_SIZE, BUFFER_POOL_CAPACITY);
+        let tuning = cfg.tuning;
+        let buffer_pool = buffer_pool(&tuning);
+
+        // Verify the integrity of all stored items (if requested)
+        if cfg.verify_storage && cfg.retain_blocks.is_some() {
+            warn!("skipping storage verification of pruned archives");
+        } else if cfg.verify_storage {
+            let start = Instant::now();
+            let (finalizations, blocks) = verify_storage(
+                context.with_label("verify_storage"),
+                &cfg.partition_prefix,
+                cfg.finalized_freezer_table_initial_size,
+                cfg.application.digest_mode,
+                &tuning,
+            )
+            .await
+            .expect("failed to quarantine corrupted sections");
+            info!(
+                elapsed = ?start.elapsed(),
+                finalizations = finalizations.checked,
+                blocks = blocks.checked,
+                "verified storage"
+            );
+            if !finalizations.corrupted.is_empty() || !blocks.corrupted.is_empty() {
+                error!(
+                    finalizations = ?finalizations.corrupted,
+                    blocks = ?blocks.corrupted,
+                    "quarantined corrupted sections"
+                );
+            }
+        }
+
+        // Create the application
+        let digest_mode = cfg.application.digest_mode;
+        let skew = skew::Detector::new(context.with_label("clock_skew"));
+        let rules = Rules::new(cfg.application.timestamp_policy, &cfg.upgrades)
+            .expect("upgrades are validated when building the config");
+        let mut app = Application::new(
+            cfg.application,
+            rules,
+            cfg.guardrail.unwrap_or_default(),
+            skew,
+            cfg.mempool.unwrap_or_default(),
+        );
+        if let Some(executor) = cfg.executor {
+            app = app.with_executor(executor);
+        }
+        let processed = app.processed();
+        let retention = cfg
+            .retain_blocks
+            .map(|blocks| Retention::new(blocks, processed.clone()));
 
         // Initialize finalizations by height
         let start = Instant::now();
-        let finalizations_by_height = immutable::Archive::init(
+        let finalizations_by_height = init_finalization_store(
             context.with_label("finalizations_by_height"),
-            immutable::Config {
-                metadata_partition: format!(
-                    "{}-finalizations-by-height-metadata",
-                    cfg.partition_prefix
-                ),
-                freezer_table_partition: format!(
-                    "{}-finalizations-by-height-freezer-table",
-                    cfg.partition_prefix
-                ),
-                freezer_table_initial_size: cfg.finalized_freezer_table_initial_size,
-                freezer_table_resize_frequency: FREEZER_TABLE_RESIZE_FREQUENCY,
-                freezer_table_resize_chunk_size: FREEZER_TABLE_RESIZE_CHUNK_SIZE,
-                freezer_key_partition: format!(
-                    "{}-finalizations-by-height-freezer-key-journal",
-                    cfg.partition_prefix
-                ),
-                freezer_key_buffer_pool: buffer_pool.clone(),
-                freezer_key_write_buffer: WRITE_BUFFER,
-                freezer_value_partition: format!(
-                    "{}-finalizations-by-height-freezer-value-journal",
-                    cfg.partition_prefix
-                ),
-                freezer_value_write_buffer: WRITE_BUFFER,
-                freezer_value_target_size: FREEZER_JOURNAL_TARGET_SIZE,
-                freezer_value_compression: FREEZER_JOURNAL_COMPRESSION,
-                ordinal_partition: format!(
-                    "{}-finalizations-by-height-ordinal",
-                    cfg.partition_prefix
-                ),
-                ordinal_write_buffer: WRITE_BUFFER,
-                items_per_section: IMMUTABLE_ITEMS_PER_SECTION,
-                codec_config: Scheme::certificate_codec_config_unbounded(),
-                replay_buffer: REPLAY_BUFFER,
-            },
+            &cfg.partition_prefix,
+            cfg.finalized_freezer_table_initial_size,
+            buffer_pool.clone(),
+            &tuning,
+            retention.clone(),
         )
-        .await
-        .expect("failed to initialize finalizations by height archive");
+        .await;
         info!(elapsed = ?start.elapsed(), "restored finalizations by height archive");
 
         // Initialize finalized blocks
         let start = Instant::now();
-        let finalized_blocks = immutable::Archive::init(
+        let finalized_blocks = init_block_store(
             context.with_label("finalized_blocks"),
-            immutable::Config {
-                metadata_partition: format!("{}-finalized_blocks-metadata", cfg.partition_prefix),
-                freezer_table_partition: format!(
-                    "{}-finalized_blocks-freezer-table",
-                    cfg.partition_prefix
-                ),
-                freezer_table_initial_size: cfg.blocks_freezer_table_initial_size,
-                freezer_table_resize_frequency: FREEZER_TABLE_RESIZE_FREQUENCY,
-                freezer_table_resize_chunk_size: FREEZER_TABLE_RESIZE_CHUNK_SIZE,
-                freezer_key_partition: format!(
-                    "{}-finalized-blocks-freezer-key-journal",
-                    cfg.partition_prefix
-                ),
-                freezer_key_buffer_pool: buffer_pool.clone(),
-                freezer_key_write_buffer: WRITE_BUFFER,
-                freezer_value_partition: format!(
-                    "{}-finalized-blocks-freezer-value-journal",
-                    cfg.partition_prefix
-                ),
-                freezer_value_write_buffer: WRITE_BUFFER,
-                freezer_value_target_size: FREEZER_JOURNAL_TARGET_SIZE,
-                freezer_value_compression: FREEZER_JOURNAL_COMPRESSION,
-                ordinal_partition: format!("{}-finalized-blocks-ordinal", cfg.partition_prefix),
-                ordinal_write_buffer: WRITE_BUFFER,
-                items_per_section: IMMUTABLE_ITEMS_PER_SECTION,
-                codec_config: (),
-                replay_buffer: REPLAY_BUFFER,
+            &cfg.partition_prefix,
+            cfg.blocks_freezer_table_initial_size,
+            buffer_pool.clone(),
+            digest_mode,
+            &tuning,
+            retention,
+        )
+        .await;
+        info!(elapsed = ?start.elapsed(), "restored finalized blocks archive");
+        let finalizations_by_height =
+            Batched::new(context.clone(), finalizations_by_height, cfg.batch);
+        let finalized_blocks = Batched::new(context.clone(), finalized_blocks, cfg.batch);
+        let stored = (
+            finalized_blocks.heights(),
+            finalizations_by_height.heights(),
+        );
+
+        // Initialize nullifications
+        let start = Instant::now();
+        let nullifications = prunable::Archive::init(
+            context.with_label("nullifications_archive"),
+            prunable::Config {
+                translator: EightCap,
+                key_partition: format!("{}-nullifications-key", cfg.partition_prefix),
+                key_buffer_pool: buffer_pool.clone(),
+                value_partition: format!("{}-nullifications-value", cfg.partition_prefix),
+                compression: None,
+                codec_config: Scheme::certificate_codec_config_unbounded(),
+                items_per_section: tuning.prunable_items_per_section,
+                key_write_buffer: tuning.write_buffer,
+                value_write_buffer: tuning.write_buffer,
+                replay_buffer: tuning.replay_buffer,
             },
         )
         .await
-        .expect("failed to initialize finalized blocks archive");
-        info!(elapsed = ?start.elapsed(), "restored finalized blocks archive");
+        .expect("failed to initialize nullifications archive");
+        let nullifications =
+            nullifications::Store::new(context.with_label("nullifications"), nullifications);
+        info!(elapsed = ?start.elapsed(), "restored nullifications archive");
 
         // Create marshal
-        let scheme = Scheme::signer(NAMESPACE, cfg.participants, cfg.polynomial, cfg.share)
-            .expect("failed to create scheme");
-        let provider = ConstantProvider::new(scheme.clone());
-        let epocher = FixedEpocher::new(EPOCH_LENGTH);
-        let (marshal, marshal_mailbox, _) = marshal::Actor::init(
+        let (marshal, mut marshal_mailbox, mut processed_height) = marshal::Actor::init(
             context.with_label("marshal"),
             finalizations_by_height,
             finalized_blocks,
             marshal::Config {
-                provider,
-                epocher: epocher.clone(),
+                provider: schedule.clone(),
+                epocher: schedule.clone(),
                 partition_prefix: cfg.partition_prefix.clone(),
                 mailbox_size: cfg.mailbox_size,
                 view_retention_timeout: ViewDelta::new(
@@ -237,11 +1163,11 @@ impl<
                         .get()
                         .saturating_mul(SYNCER_ACTIVITY_TIMEOUT_MULTIPLIER),
                 ),
-                prunable_items_per_section: PRUNABLE_ITEMS_PER_SECTION,
-                replay_buffer: REPLAY_BUFFER,
-                key_write_buffer: WRITE_BUFFER,
-                value_write_buffer: WRITE_BUFFER,
-                block_codec_config: (),
+                prunable_items_per_section: tuning.prunable_items_per_section,
+                replay_buffer: tuning.replay_buffer,
+                key_write_buffer: tuning.write_buffer,
+                value_write_buffer: tuning.write_buffer,
+                block_codec_config: digest_mode,
                 max_repair: MAX_REPAIR,
                 buffer_pool: buffer_pool.clone(),
                 strategy: cfg.strategy.clone(),
@@ -249,54 +1175,102 @@ impl<
         )
         .await;
 
-        // Create the application
-        let app = Application::new();
+        // Start after the checkpoint (if no block has been processed yet)
+        if let Some(checkpoint) = cfg.checkpoint {
+            let height = checkpoint.block.height();
+            if processed_height.get() == 0 {
+                marshal_mailbox.set_floor(height).await;
+                processed_height = height;
+                info!(%height, "starting from checkpoint");
+            } else {
+                debug!(%height, processed = %processed_height, "ignoring checkpoint");
+            }
+        }
+
+        // Resume from the last processed block (so proposals and verifications only walk back
+        // to it, instead of to genesis, until the next block is finalized)
+        processed.set(processed_height.get());
         let marshaled = Marshaled::new(
             context.with_label("marshaled"),
             app,
             marshal_mailbox.clone(),
-            epocher,
+            schedule.clone(),
         );
 
-        // Create the reporter
-        let reporter = (
-            marshal_mailbox.clone(),
-            cfg.indexer.map(|indexer| {
-                indexer::Pusher::new(
+        // Inject faults (if configured)
+        #[cfg(feature = "faults")]
+        let faults = faults::Injector::new(context.with_label("faults"), cfg.faults);
+        #[cfg(feature = "faults")]
+        let indexer = cfg.indexer.map(|indexer| faults.delayed(indexer));
+        #[cfg(not(feature = "faults"))]
+        let indexer = cfg.indexer;
+
+        // Create the reporter (retrying failed uploads from the spool)
+        let (pusher, spool) = match indexer {
+            Some(indexer) => {
+                let indexer =
+                    indexer::Batcher::new(context.with_label("uploads"), indexer, cfg.upload_batch);
+                let (spool, mailbox) = spool::Spool::init(
+                    context.with_label("spool"),
+                    indexer.clone(),
+                    spool_partition(&cfg.partition_prefix),
+                    digest_mode,
+                    buffer_pool.clone(),
+                    tuning.write_buffer,
+                    cfg.spool.unwrap_or_default(),
+                )
+                .await;
+                let pusher = indexer::Pusher::new(
                     context.with_label("indexer"),
                     indexer,
                     marshal_mailbox.clone(),
                 )
-            }),
-        )
-            .into();
+                .with_spool(mailbox);
+                (Some(pusher), Some(spool))
+            }
+            None => (None, None),
+        };
+        let upload_failures = pusher.as_ref().map(|pusher| pusher.failures());
+        let feeder = cfg.replica.map(|sender| {
+            replica::Feeder::new(
+                context.with_label("replica"),
+                sender,
+                marshal_mailbox.clone(),
+            )
+        });
+        let trackers: Reporters<_, _, _> = (nullifications.clone(), views.clone()).into();
+        let observers: Reporters<_, _, _> = (propagation.clone(), trackers).into();
+        let copies: Reporters<_, _, _> = (feeder, observers).into();
+        let reporters: Reporters<_, _, _> = (pusher, copies).into();
+        let reporter = (marshal_mailbox.clone(), reporters).into();
 
-        // Create the consensus engine
-        let consensus = Consensus::new(
-            context.with_label("consensus"),
-            simplex::Config {
-                epoch: EPOCH,
-                scheme,
-                automaton: marshaled.clone(),
-                relay: marshaled.clone(),
-                reporter,
-                partition: format!("{}-consensus", cfg.partition_prefix),
-                mailbox_size: cfg.mailbox_size,
-                leader_timeout: cfg.leader_timeout,
-                notarization_timeout: cfg.notarization_timeout,
-                nullify_retry: cfg.nullify_retry,
-                fetch_timeout: cfg.fetch_timeout,
-                activity_timeout: cfg.activity_timeout,
-                skip_timeout: cfg.skip_timeout,
-                fetch_concurrent: cfg.fetch_concurrent,
-                replay_buffer: REPLAY_BUFFER,
-                write_buffer: WRITE_BUFFER,
-                blocker: cfg.blocker,
-                buffer_pool,
-                elector: Random,
-                strategy: cfg.strategy,
-            },
-        );
+        // Run consensus in the epoch after the last processed block
+        let epoch = schedule
+            .containing(processed_height.next())
+            .expect("every height is scheduled")
+            .epoch();
+        let epochs = Epochs {
+            context: ContextCell::new(context.with_label("epochs")),
+            schedule,
+            epoch,
+            processed: processed.clone(),
+            marshal: marshal_mailbox.clone(),
+            marshaled: marshaled.clone(),
+            reporter,
+            blocker: cfg.blocker,
+            strategy: cfg.strategy,
+            partition_prefix: cfg.partition_prefix,
+            mailbox_size: cfg.mailbox_size,
+            leader_timeout: cfg.leader_timeout,
+            notarization_timeout: cfg.notarization_timeout,
+            nullify_retry: cfg.nullify_retry,
+            fetch_timeout: cfg.fetch_timeout,
+            activity_timeout: cfg.activity_timeout,
+            skip_timeout: cfg.skip_timeout,
+            fetch_concurrent: cfg.fetch_concurrent,
+            tuning,
+            buffer_pool,
+        };
 
         // Return the engine
         Self {
@@ -304,13 +1278,49 @@ impl<
 
             buffer,
             buffer_mailbox,
+            propagation,
+            views,
+            nullifications,
+            processed,
+            stored,
+            upload_failures,
+            spool,
             marshal,
+            marshal_mailbox,
             marshaled,
-            consensus,
+            epochs,
+            #[cfg(feature = "faults")]
+            faults,
         }
     }
 
-    /// Start the [simplex::Engine].
+    /// Get a [views::Tracker] that follows the current consensus view (and its leader).
+    pub fn views(&self) -> views::Tracker<E> {
+        self.views.clone()
+    }
+
+    /// Get a [Processed] handle that follows the latest processed height.
+    pub fn processed(&self) -> Processed {
+        self.processed.clone()
+    }
+
+    /// Get the [batch::Heights] of the finalized blocks and finalizations archives (in that
+    /// order).
+    pub fn stored(&self) -> (batch::Heights, batch::Heights) {
+        self.stored.clone()
+    }
+
+    /// Get a [marshal::Mailbox] to read finalized blocks (and finalizations) from the archives.
+    pub fn marshal(&self) -> marshal::Mailbox<Scheme, Block> {
+        self.marshal_mailbox.clone()
+    }
+
+    /// Get the [Counter] of failed indexer uploads (if an indexer is configured).
+    pub fn upload_failures(&self) -> Option<Counter> {
+        self.upload_failures.clone()
+    }
+
+    /// Start the [simplex::Engine] (of each epoch in turn).
     #[allow(clippy::too_many_arguments)]
     pub fn start(
         mut self,
@@ -366,25 +1376,250 @@ impl<
             impl Resolver<Key = handler::Request<Block>, PublicKey = PublicKey>,
         ),
     ) {
+        // Track the time spent in each view
+        let views_handle = self.views.start();
+
+        // Sync persisted nullifications
+        let nullifications_handle = self.nullifications.start();
+
+        // Retry failed uploads (if uploading to an indexer)
+        let spool_handle = self.spool.map(|spool| spool.start());
+
+        // Inject faults into broadcasts and the finalizer (if configured)
+        #[cfg(feature = "faults")]
+        let (broadcast, marshaled) = (
+            self.faults.lossy(broadcast),
+            self.faults.paused(self.marshaled),
+        );
+        #[cfg(not(feature = "faults"))]
+        let marshaled = self.marshaled;
+
         // Start the buffer
-        let buffer_handle = self.buffer.start(broadcast);
+        let buffer_handle = self.buffer.start(self.propagation.wrap(broadcast));
 
         // Start marshal
-        let marshal_handle = self
-            .marshal
-            .start(self.marshaled, self.buffer_mailbox, marshal);
+        let marshal_handle = self.marshal.start(marshaled, self.buffer_mailbox, marshal);
 
         // Start consensus
         //
         // We start the application prior to consensus to ensure we can handle enqueued events from consensus (otherwise
         // restart could block).
-        let consensus_handle = self.consensus.start(pending, recovered, resolver);
+        let consensus_handle = self.epochs.start(pending, recovered, resolver);
 
         // Wait for any actor to finish
-        if let Err(e) = try_join_all(vec![buffer_handle, marshal_handle, consensus_handle]).await {
+        let mut handles = vec![
+            views_handle,
+            nullifications_handle,
+            buffer_handle,
+            marshal_handle,
+            consensus_handle,
+        ];
+        handles.extend(spool_handle);
+        if let Err(e) = try_join_all(handles).await {
             error!(?e, "engine failed");
         } else {
             warn!("engine stopped");
         }
     }
 }
+
+/// Runs the [simplex::Engine] of each epoch in turn (see [epochs]).
+///
+/// Consensus messages are routed to the engine of their epoch, so the engine of an epoch only
+/// runs until the last block of that epoch is processed (after which the next epoch's engine
+/// takes over). A validator that falls behind its peers learns the last block of its epoch
+/// (and its finalization) from the first peer it hears from in a later epoch.
+struct Epochs<
+    E: Clock + GClock + Rng + CryptoRng + Spawner + Storage + Metrics,
+    B: Blocker<PublicKey = PublicKey>,
+    S: Strategy,
+    I: Indexer,
+> {
+    context: ContextCell<E>,
+
+    schedule: Schedule,
+    epoch: Epoch,
+    processed: Processed,
+    marshal: marshal::Mailbox<Scheme, Block>,
+    marshaled: Marshaled<E>,
+    reporter: Reporter<E, I>,
+
+    blocker: B,
+    strategy: S,
+    partition_prefix: String,
+    mailbox_size: usize,
+    leader_timeout: Duration,
+    notarization_timeout: Duration,
+    nullify_retry: Duration,
+    fetch_timeout: Duration,
+    activity_timeout: ViewDelta,
+    skip_timeout: ViewDelta,
+    fetch_concurrent: usize,
+    tuning: Tuning,
+    buffer_pool: PoolRef,
+}
+
+impl<
+        E: Clock + GClock + Rng + CryptoRng + Spawner + RayonPoolSpawner + Storage + Metrics,
+        B: Blocker<PublicKey = PublicKey>,
+        S: Strategy,
+        I: Indexer,
+    > Epochs<E, B, S, I>
+{
+    fn start(
+        mut self,
+        pending: (
+            impl Sender<PublicKey = PublicKey>,
+            impl Receiver<PublicKey = PublicKey>,
+        ),
+        recovered: (
+            impl Sender<PublicKey = PublicKey>,
+            impl Receiver<PublicKey = PublicKey>,
+        ),
+        resolver: (
+            impl Sender<PublicKey = PublicKey>,
+            impl Receiver<PublicKey = PublicKey>,
+        ),
+    ) -> Handle<()> {
+        spawn_cell!(self.context, self.run(pending, recovered, resolver).await)
+    }
+
+    async fn run(
+        mut self,
+        pending: (
+            impl Sender<PublicKey = PublicKey>,
+            impl Receiver<PublicKey = PublicKey>,
+        ),
+        recovered: (
+            impl Sender<PublicKey = PublicKey>,
+            impl Receiver<PublicKey = PublicKey>,
+        ),
+        resolver: (
+            impl Sender<PublicKey = PublicKey>,
+            impl Receiver<PublicKey = PublicKey>,
+        ),
+    ) {
+        // Route consensus messages by epoch (keeping messages from other epochs aside)
+        let context = self.context.as_present().clone();
+        let (mux, mut pending_mux, mut others) = Muxer::builder(
+            context.with_label("pending_mux"),
+            pending.0,
+            pending.1,
+            self.mailbox_size,
+        )
+        .with_backup()
+        .build();
+        mux.start();
+        let (mux, mut recovered_mux) = Muxer::new(
+            context.with_label("recovered_mux"),
+            recovered.0,
+            recovered.1,
+            self.mailbox_size,
+        );
+        mux.start();
+        let (mux, mut resolver_mux) = Muxer::new(
+            context.with_label("resolver_mux"),
+            resolver.0,
+            resolver.1,
+            self.mailbox_size,
+        );
+        mux.start();
+
+        loop {
+            // Start the engine of the current epoch
+            let epoch = self.epoch;
+            let subchannel = epoch.get();
+            let (Ok(pending), Ok(recovered), Ok(resolver)) = (
+                pending_mux.register(subchannel).await,
+                recovered_mux.register(subchannel).await,
+                resolver_mux.register(subchannel).await,
+            ) else {
+                error!(epoch = subchannel, "failed to route consensus messages");
+                return;
+            };
+            let mut consensus = self
+                .consensus(&context, epoch)
+                .start(pending, recovered, resolver);
+            info!(epoch = subchannel, "started consensus");
+
+            // The last epoch never ends
+            if epoch == self.schedule.latest() {
+                if let Err(e) = consensus.await {
+                    error!(?e, epoch = subchannel, "consensus failed");
+                }
+                return;
+            }
+
+            // Wait for the last block of the epoch to be processed
+            let last = self.schedule.last(epoch).expect("epoch is scheduled");
+            let mut hinted = HashSet::new();
+            while self.processed.get() < last.get() {
+                select! {
+                    _ = context.sleep(EPOCH_POLL_INTERVAL) => {},
+                    message = others.next() => {
+                        let Some((subchannel, (peer, _))) = message else {
+                            error!("consensus muxer stopped");
+                            return;
+                        };
+
+                        // Peers in a later epoch have already finalized the last block of ours
+                        if subchannel > epoch.get() && hinted.insert(peer.clone()) {
+                            self.marshal
+                                .hint_finalized(last, NonEmptyVec::new(peer))
+                                .await;
+                        }
+                    },
+                    result = &mut consensus => {
+                        if let Err(e) = result {
+                            error!(?e, epoch = subchannel, "consensus failed");
+                        }
+                        return;
+                    },
+                }
+            }
+            consensus.abort();
+            info!(epoch = subchannel, height = last.get(), "finished epoch");
+            self.epoch = epoch.next();
+        }
+    }
+
+    /// Create the [simplex::Engine] of `epoch`.
+    #[allow(clippy::type_complexity)]
+    fn consensus(
+        &self,
+        context: &E,
+        epoch: Epoch,
+    ) -> Consensus<E, Scheme, Random, B, Digest, Marshaled<E>, Marshaled<E>, Reporter<E, I>, S>
+    {
+        let scheme = self.schedule.scoped(epoch).expect("epoch is scheduled");
+        let label = match epoch == EPOCH {
+            true => "consensus".to_string(),
+            false => format!("consensus_epoch_{}", epoch.get()),
+        };
+        Consensus::new(
+            context.with_label(&label),
+            simplex::Config {
+                epoch,
+                scheme: scheme.as_ref().clone(),
+                automaton: self.marshaled.clone(),
+                relay: self.marshaled.clone(),
+                reporter: self.reporter.clone(),
+                partition: consensus_partition(&self.partition_prefix, epoch),
+                mailbox_size: self.mailbox_size,
+                leader_timeout: self.leader_timeout,
+                notarization_timeout: self.notarization_timeout,
+                nullify_retry: self.nullify_retry,
+                fetch_timeout: self.fetch_timeout,
+                activity_timeout: self.activity_timeout,
+                skip_timeout: self.skip_timeout,
+                fetch_concurrent: self.fetch_concurrent,
+                replay_buffer: self.tuning.replay_buffer,
+                write_buffer: self.tuning.write_buffer,
+                blocker: self.blocker.clone(),
+                buffer_pool: self.buffer_pool.clone(),
+                elector: Random,
+                strategy: self.strategy.clone(),
+            },
+        )
+    }
+}
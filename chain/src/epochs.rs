@@ -0,0 +1,410 @@
+//! Validator set reconfiguration.
+//!
+//! A network starts in [EPOCH] with the validators (and threshold key) of its genesis. To change
+//! its validator set (or reshare its key) without restarting from genesis, operators schedule a
+//! [Transition] at a future height in every validator's config: blocks from that height on are
+//! finalized by a new instance of consensus in the next epoch, run by the new [Committee] (with
+//! views restarting at `1`). The last block of each epoch is the genesis of the next (see
+//! [commonware_consensus::application::marshaled]), so no transition may take over before
+//! [MIN_TRANSITION_HEIGHT].
+//!
+//! Certificates from each epoch are signed under the identity of that epoch's polynomial, so
+//! indexers and clients must be told about every transition too (see
+//! [alto_types::IdentitySchedule]). Like [crate::rules] upgrades, transitions are not part of
+//! the genesis transcript: operators must ensure every validator schedules the same transitions
+//! before the first of their heights is reached.
+
+use alto_types::{PublicKey, Scheme, EPOCH, NAMESPACE};
+use commonware_codec::{Decode, DecodeExt};
+use commonware_consensus::types::{Epoch, EpochInfo, Epocher, Height};
+use commonware_cryptography::{
+    bls12381::primitives::{group, sharing::Sharing, variant::MinSig},
+    certificate::Provider,
+};
+use commonware_utils::{from_hex_formatted, ordered::Set};
+use serde::{Deserialize, Serialize};
+use std::{num::NonZeroU32, sync::Arc};
+use thiserror::Error;
+
+/// First height a [Transition] may take over at (the last block of the previous epoch, which
+/// must follow genesis, is the genesis of the next).
+pub const MIN_TRANSITION_HEIGHT: u64 = 2;
+
+/// A [Transition] as written in the validator config.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Config {
+    /// First height finalized by the new validators.
+    pub height: u64,
+
+    /// Hex-encoded ed25519 public keys of the new validators.
+    pub participants: Vec<String>,
+
+    /// Hex-encoded public polynomial of the new validators.
+    pub polynomial: String,
+
+    /// Hex-encoded share of the new polynomial (required if this validator is one of the new
+    /// validators).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share: Option<String>,
+}
+
+impl Config {
+    /// Decode the [Transition] described by this config.
+    pub fn parse(&self) -> Result<Transition, Error> {
+        let invalid = |field| Error::Invalid(field, self.height);
+        let mut participants = Vec::with_capacity(self.participants.len());
+        for participant in &self.participants {
+            let bytes = from_hex_formatted(participant).ok_or(invalid("participant"))?;
+            let participant =
+                PublicKey::decode(bytes.as_ref()).map_err(|_| invalid("participant"))?;
+            participants.push(participant);
+        }
+        let participants = Set::from_iter_dedup(participants);
+        let max = NonZeroU32::new(participants.len() as u32).ok_or(invalid("participants"))?;
+        let bytes = from_hex_formatted(&self.polynomial).ok_or(invalid("polynomial"))?;
+        let polynomial = Sharing::<MinSig>::decode_cfg(bytes.as_ref(), &max)
+            .map_err(|_| invalid("polynomial"))?;
+        let share = match &self.share {
+            Some(share) => {
+                let bytes = from_hex_formatted(share).ok_or(invalid("share"))?;
+                Some(group::Share::decode(bytes.as_ref()).map_err(|_| invalid("share"))?)
+            }
+            None => None,
+        };
+        Ok(Transition {
+            height: self.height,
+            committee: Committee {
+                participants,
+                polynomial,
+                share,
+            },
+        })
+    }
+}
+
+/// Validators (and their threshold key) that finalize the blocks of an epoch.
+#[derive(Clone)]
+pub struct Committee {
+    /// Public keys of the validators.
+    pub participants: Set<PublicKey>,
+
+    /// Public polynomial of the validators' threshold key.
+    pub polynomial: Sharing<MinSig>,
+
+    /// Share of `polynomial` held by this validator (if it is one of `participants`).
+    pub share: Option<group::Share>,
+}
+
+/// A [Committee] that takes over at a height.
+#[derive(Clone)]
+pub struct Transition {
+    /// First height finalized by `committee`.
+    pub height: u64,
+
+    /// Validators of the new epoch.
+    pub committee: Committee,
+}
+
+/// Errors that can occur when scheduling [Transition]s.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("invalid {0} in transition at height {1}")]
+    Invalid(&'static str, u64),
+    #[error("transition at height {0} takes over before height {MIN_TRANSITION_HEIGHT}")]
+    Early(u64),
+    #[error("transition at height {0} does not follow the previous transition")]
+    HeightNotIncreasing(u64),
+    #[error("polynomial of epoch {0} does not match its participants")]
+    Polynomial(u64),
+    #[error("share of epoch {0} does not belong to this validator")]
+    Share(u64),
+    #[error("missing share for epoch {0}")]
+    MissingShare(u64),
+}
+
+/// An epoch of a [Schedule].
+struct Entry {
+    first: u64,
+    participants: Set<PublicKey>,
+    scheme: Arc<Scheme>,
+}
+
+/// The [Committee] of every epoch (and the heights it finalizes).
+///
+/// Serves as both the [Epocher] and the certificate [Provider] of consensus: each epoch's scheme
+/// signs with this validator's share (if it is one of the epoch's validators) and verifies
+/// certificates against the epoch's polynomial.
+#[derive(Clone)]
+pub struct Schedule {
+    /// Epochs (sorted by the first height they finalize, starting at [EPOCH] and genesis).
+    epochs: Arc<Vec<Entry>>,
+}
+
+impl Schedule {
+    /// Create a new [Schedule] where `genesis` finalizes blocks from genesis and each of
+    /// `transitions` (ordered by height) takes over at its height, signing as `me` in every
+    /// epoch it is a participant of.
+    pub fn new(
+        me: &PublicKey,
+        genesis: Committee,
+        transitions: Vec<Transition>,
+    ) -> Result<Self, Error> {
+        let committees = std::iter::once((0, genesis)).chain(
+            transitions
+                .into_iter()
+                .map(|transition| (transition.height, transition.committee)),
+        );
+        let mut epochs: Vec<Entry> = Vec::new();
+        for (first, committee) in committees {
+            let epoch = EPOCH.get() + epochs.len() as u64;
+            if let Some(previous) = epochs.last() {
+                if first < MIN_TRANSITION_HEIGHT {
+                    return Err(Error::Early(first));
+                }
+                if first <= previous.first {
+                    return Err(Error::HeightNotIncreasing(first));
+                }
+            }
+            let Committee {
+                participants,
+                polynomial,
+                share,
+            } = committee;
+            if polynomial.total().get() as usize != participants.len() {
+                return Err(Error::Polynomial(epoch));
+            }
+            let scheme = match (participants.position(me), share) {
+                (Some(position), Some(share)) => {
+                    if share.index.get() as usize != position {
+                        return Err(Error::Share(epoch));
+                    }
+                    Scheme::signer(NAMESPACE, participants.clone(), polynomial, share)
+                        .ok_or(Error::Share(epoch))?
+                }
+                (Some(_), None) => return Err(Error::MissingShare(epoch)),
+                (None, Some(_)) => return Err(Error::Share(epoch)),
+                (None, None) => Scheme::verifier(NAMESPACE, participants.clone(), polynomial),
+            };
+            epochs.push(Entry {
+                first,
+                participants,
+                scheme: Arc::new(scheme),
+            });
+        }
+        Ok(Self {
+            epochs: Arc::new(epochs),
+        })
+    }
+
+    fn entry(&self, epoch: Epoch) -> Option<&Entry> {
+        let index = epoch.get().checked_sub(EPOCH.get())?;
+        self.epochs.get(usize::try_from(index).ok()?)
+    }
+
+    /// Get the last epoch (which finalizes every height after its first).
+    pub fn latest(&self) -> Epoch {
+        Epoch::new(EPOCH.get() + self.epochs.len() as u64 - 1)
+    }
+
+    /// Get the validators of `epoch` (if scheduled).
+    pub fn participants(&self, epoch: Epoch) -> Option<&Set<PublicKey>> {
+        self.entry(epoch).map(|entry| &entry.participants)
+    }
+
+    /// Get the validators of every epoch.
+    pub fn peers(&self) -> Set<PublicKey> {
+        Set::from_iter_dedup(
+            self.epochs
+                .iter()
+                .flat_map(|entry| entry.participants.iter().cloned()),
+        )
+    }
+}
+
+impl Epocher for Schedule {
+    fn containing(&self, height: Height) -> Option<EpochInfo> {
+        let index = self
+            .epochs
+            .partition_point(|entry| entry.first <= height.get());
+        let epoch = Epoch::new(EPOCH.get() + index as u64 - 1);
+        Some(EpochInfo::new(
+            epoch,
+            height,
+            self.first(epoch)?,
+            self.last(epoch)?,
+        ))
+    }
+
+    fn first(&self, epoch: Epoch) -> Option<Height> {
+        self.entry(epoch).map(|entry| Height::new(entry.first))
+    }
+
+    fn last(&self, epoch: Epoch) -> Option<Height> {
+        self.entry(epoch)?;
+        let next = self.entry(epoch.next());
+        Some(Height::new(next.map_or(u64::MAX, |next| next.first - 1)))
+    }
+}
+
+impl Provider for Schedule {
+    type Scope = Epoch;
+    type Scheme = Scheme;
+
+    fn scoped(&self, epoch: Epoch) -> Option<Arc<Scheme>> {
+        self.entry(epoch).map(|entry| entry.scheme.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_types::{
+        fixtures::{self, Fixture},
+        Scheme,
+    };
+    use commonware_codec::Encode;
+    use commonware_cryptography::certificate::Scheme as _;
+    use commonware_utils::hex;
+
+    fn committee(fixture: &Fixture<Scheme>, signer: Option<usize>) -> Committee {
+        Committee {
+            participants: Set::from_iter_dedup(fixture.participants.clone()),
+            polynomial: fixture.schemes[0].polynomial().clone(),
+            share: signer.map(|index| fixture.schemes[index].share().cloned().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_schedule() {
+        let genesis = fixtures::seeded(0, 4);
+        let next = fixtures::seeded(1, 5);
+        let me = genesis.participants[0].clone();
+        let schedule = Schedule::new(
+            &me,
+            committee(&genesis, Some(0)),
+            vec![Transition {
+                height: 10,
+                committee: committee(&next, None),
+            }],
+        )
+        .unwrap();
+        assert_eq!(schedule.latest(), Epoch::new(1));
+        assert_eq!(schedule.peers().len(), 9);
+
+        // Each height belongs to the epoch of the committee that finalizes it
+        let epoch = |height| schedule.containing(Height::new(height)).unwrap();
+        assert_eq!(epoch(0).epoch(), EPOCH);
+        assert_eq!(epoch(9).epoch(), EPOCH);
+        assert_eq!(epoch(9).last(), Height::new(9));
+        assert_eq!(epoch(10).epoch(), Epoch::new(1));
+        assert_eq!(epoch(10).first(), Height::new(10));
+        assert_eq!(epoch(u64::MAX).last(), Height::new(u64::MAX));
+        assert!(schedule.first(Epoch::new(2)).is_none());
+
+        // Signs in the genesis epoch but only verifies in the next
+        let genesis_scheme = schedule.scoped(EPOCH).unwrap();
+        assert!(genesis_scheme.me().is_some());
+        assert_eq!(
+            genesis_scheme.identity(),
+            &fixtures::identity(&genesis.schemes)
+        );
+        let next_scheme = schedule.scoped(Epoch::new(1)).unwrap();
+        assert!(next_scheme.me().is_none());
+        assert_eq!(next_scheme.identity(), &fixtures::identity(&next.schemes));
+        assert_eq!(
+            schedule.participants(Epoch::new(1)).unwrap().len(),
+            next.participants.len()
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let genesis = fixtures::seeded(0, 4);
+        let next = fixtures::seeded(1, 4);
+        let me = genesis.participants[0].clone();
+        let transition = |height, committee| Transition { height, committee };
+        let schedule = |transitions| Schedule::new(&me, committee(&genesis, Some(0)), transitions);
+
+        // Transitions must leave room for the previous epoch and be ordered
+        assert_eq!(
+            schedule(vec![transition(1, committee(&next, None))]).err(),
+            Some(Error::Early(1))
+        );
+        assert_eq!(
+            schedule(vec![
+                transition(5, committee(&next, None)),
+                transition(5, committee(&next, None)),
+            ])
+            .err(),
+            Some(Error::HeightNotIncreasing(5))
+        );
+
+        // Participants must hold a share of their own (and only participants)
+        let mut member = committee(&next, None);
+        member.participants =
+            Set::from_iter_dedup(next.participants[1..].iter().cloned().chain([me.clone()]));
+        assert_eq!(
+            schedule(vec![transition(5, member)]).err(),
+            Some(Error::MissingShare(1))
+        );
+        assert_eq!(
+            schedule(vec![transition(5, committee(&next, Some(0)))]).err(),
+            Some(Error::Share(1))
+        );
+        let mut mismatched = committee(&genesis, Some(0));
+        mismatched.polynomial = next.schemes[0].polynomial().clone();
+        assert_eq!(
+            Schedule::new(&me, mismatched, Vec::new()).err(),
+            Some(Error::Share(0))
+        );
+
+        // The polynomial must cover every participant
+        let mut small = committee(&next, None);
+        small.polynomial = fixtures::seeded(2, 3).schemes[0].polynomial().clone();
+        assert_eq!(
+            schedule(vec![transition(5, small)]).err(),
+            Some(Error::Polynomial(1))
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let next = fixtures::seeded(1, 4);
+        let config = Config {
+            height: 10,
+            participants: next
+                .participants
+                .iter()
+                .map(|participant| hex(&participant.encode()))
+                .collect(),
+            polynomial: hex(&next.schemes[0].polynomial().encode()),
+            share: Some(hex(&next.schemes[2].share().unwrap().encode())),
+        };
+        let transition = config.parse().unwrap();
+        assert_eq!(transition.height, 10);
+        assert_eq!(transition.committee.participants.len(), 4);
+        assert_eq!(
+            &transition.committee.polynomial,
+            next.schemes[0].polynomial()
+        );
+        assert_eq!(transition.committee.share.as_ref(), next.schemes[2].share());
+
+        // Malformed fields are rejected
+        let invalid = Config {
+            polynomial: "zz".to_string(),
+            ..config.clone()
+        };
+        assert_eq!(
+            invalid.parse().err(),
+            Some(Error::Invalid("polynomial", 10))
+        );
+        let empty = Config {
+            participants: Vec::new(),
+            ..config
+        };
+        assert_eq!(
+            empty.parse().err(),
+            Some(Error::Invalid("participants", 10))
+        );
+    }
+}
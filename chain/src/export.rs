@@ -0,0 +1,105 @@
+//! Evidence of what a validator finalized.
+//!
+//! During incident analysis, operators may need to share exactly what their node finalized at
+//! some height. [finalized] reads the finalization and block at a height from local storage and
+//! [encode] serializes them as a [Kind]-prefixed frame (the format the indexer broadcasts over
+//! its consensus websocket), so anyone that knows the network identity can verify the evidence
+//! without trusting the operator.
+
+use crate::{application::Processed, engine, retention::Retention};
+use alto_types::{DigestMode, Finalized, Kind};
+use commonware_codec::{EncodeSize, FixedSize, Write};
+use commonware_cryptography::Digestible;
+use commonware_runtime::{Clock, Metrics, Storage};
+use commonware_storage::archive::{Archive, Identifier};
+use std::num::NonZeroU64;
+use thiserror::Error;
+
+/// Errors that can occur when exporting a finalization.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("nothing has been finalized")]
+    Empty,
+    #[error("no finalization stored at height {0}")]
+    MissingFinalization(u64),
+    #[error("no block stored at height {0}")]
+    MissingBlock(u64),
+    #[error("stored block does not match the finalization at height {0}")]
+    Mismatch(u64),
+    #[error("storage error: {0}")]
+    Storage(#[from] commonware_storage::archive::Error),
+}
+
+impl From<Error> for alto_types::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Empty | Error::MissingFinalization(_) | Error::MissingBlock(_) => {
+                Self::NotFound(error.to_string())
+            }
+            Error::Mismatch(_) => Self::Consistency(error.to_string()),
+            Error::Storage(_) => Self::Io(std::io::Error::other(error)),
+        }
+    }
+}
+
+/// Read the [Finalized] block at `height` (or the latest finalized block, if `None`) stored
+/// under `partition_prefix` (with the [engine::Tuning] and `retain_blocks` the validator used).
+///
+/// The archives are opened (and may be repaired) like the engine does at startup, so the
+/// validator using them must be stopped first.
+pub async fn finalized<E: Storage + Metrics + Clock>(
+    context: E,
+    partition_prefix: &str,
+    freezer_table_initial_size: u32,
+    digest_mode: DigestMode,
+    tuning: &engine::Tuning,
+    retain_blocks: Option<NonZeroU64>,
+    height: Option<u64>,
+) -> Result<Finalized, Error> {
+    // Reading never prunes (nothing is processed)
+    let retention = retain_blocks.map(|blocks| Retention::new(blocks, Processed::default()));
+    let buffer_pool = engine::buffer_pool(tuning);
+    let finalizations = engine::init_finalization_store(
+        context.with_label("finalizations"),
+        partition_prefix,
+        freezer_table_initial_size,
+        buffer_pool.clone(),
+        tuning,
+        retention.clone(),
+    )
+    .await;
+    let height = match height {
+        Some(height) => height,
+        None => finalizations.last_index().ok_or(Error::Empty)?,
+    };
+    let proof = finalizations
+        .get(Identifier::Index(height))
+        .await?
+        .ok_or(Error::MissingFinalization(height))?;
+    let blocks = engine::init_block_store(
+        context.with_label("blocks"),
+        partition_prefix,
+        freezer_table_initial_size,
+        buffer_pool,
+        digest_mode,
+        tuning,
+        retention,
+    )
+    .await;
+    let block = blocks
+        .get(Identifier::Index(height))
+        .await?
+        .ok_or(Error::MissingBlock(height))?;
+    if proof.proposal.payload != block.digest() {
+        return Err(Error::Mismatch(height));
+    }
+    Ok(Finalized::new(proof, block))
+}
+
+/// Encode `finalized` as a [Kind::Finalization] frame.
+pub fn encode(finalized: &Finalized) -> Vec<u8> {
+    let mut data = vec![0u8; u8::SIZE + finalized.encode_size()];
+    data[0] = Kind::Finalization as u8;
+    finalized.write(&mut data[1..].as_mut());
+    data
+}
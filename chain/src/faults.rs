@@ -0,0 +1,300 @@
+//! Fault injection for chaos testing.
+//!
+//! Rehearsing incident response requires incidents. When built with the `faults` feature, a
+//! validator accepts a `faults` section in its config that degrades it at runtime:
+//! - `drop_broadcasts`: percentage of block broadcasts to drop (peers must fetch the blocks
+//!   from someone else).
+//! - `delay_uploads`: milliseconds to wait before every indexer upload.
+//! - `pause_finalizer`: stop delivering finalized blocks to the application for the last
+//!   `duration` milliseconds of every `interval` milliseconds.
+//!
+//! The feature must never be enabled in production builds.
+
+use crate::indexer::{Artifact, Indexer};
+use alto_types::{Block, Finalized, Notarized, Nullification, Seed};
+use bytes::Buf;
+use commonware_consensus::{marshal::Update, Reporter};
+use commonware_cryptography::ed25519::PublicKey;
+use commonware_p2p::{CheckedSender, LimitedSender, Recipients};
+use commonware_runtime::{Clock, Metrics};
+use prometheus_client::metrics::counter::Counter;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+/// Faults to inject.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Percentage (`0..=100`) of block broadcasts to drop.
+    pub drop_broadcasts: u8,
+
+    /// Milliseconds to wait before every indexer upload.
+    pub delay_uploads: u64,
+
+    /// Periodically stop delivering finalized blocks to the application.
+    pub pause_finalizer: Option<Pause>,
+}
+
+/// A recurring pause (in milliseconds).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pause {
+    /// Period of the pause.
+    pub interval: u64,
+
+    /// Length of the pause (at the end of each `interval`).
+    pub duration: u64,
+}
+
+impl Pause {
+    /// Get the time remaining in the pause `elapsed` after startup (zero if not paused).
+    fn remaining(&self, elapsed: Duration) -> Duration {
+        if self.interval == 0 {
+            return Duration::ZERO;
+        }
+        let offset = (elapsed.as_millis() % self.interval as u128) as u64;
+        let start = self.interval.saturating_sub(self.duration);
+        if offset < start {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(self.interval - offset)
+    }
+}
+
+/// Injects the faults of a [Config] into the components of a validator.
+#[derive(Clone)]
+pub struct Injector<E: Clock + Rng + Metrics> {
+    context: E,
+    cfg: Config,
+    start: SystemTime,
+
+    dropped: Counter,
+    paused: Counter,
+}
+
+impl<E: Clock + Rng + Metrics> Injector<E> {
+    /// Create a new [Injector] that injects the faults in `cfg`.
+    pub fn new(context: E, cfg: Config) -> Self {
+        let dropped = Counter::default();
+        context.register(
+            "dropped_broadcasts",
+            "Number of broadcasts dropped by fault injection",
+            dropped.clone(),
+        );
+        let paused = Counter::default();
+        context.register(
+            "finalizer_pauses",
+            "Number of finalized blocks held by fault injection",
+            paused.clone(),
+        );
+        if cfg != Config::default() {
+            warn!(?cfg, "injecting faults");
+        }
+        let start = context.current();
+        Self {
+            context,
+            cfg,
+            start,
+            dropped,
+            paused,
+        }
+    }
+
+    /// Wrap the broadcast channel so that [Config::drop_broadcasts] of sends are dropped.
+    pub fn lossy<S, R>(&self, (sender, receiver): (S, R)) -> (LossySender<S, E>, R)
+    where
+        S: LimitedSender<PublicKey = PublicKey>,
+    {
+        (
+            LossySender {
+                inner: sender,
+                injector: self.clone(),
+            },
+            receiver,
+        )
+    }
+
+    /// Wrap an [Indexer] so that uploads are delayed by [Config::delay_uploads].
+    pub fn delayed<I: Indexer>(&self, indexer: I) -> Delayed<E, I> {
+        Delayed {
+            inner: indexer,
+            injector: self.clone(),
+        }
+    }
+
+    /// Wrap the finalizer's [Reporter] so that it pauses per [Config::pause_finalizer].
+    pub fn paused<R: Reporter<Activity = Update<Block>>>(&self, reporter: R) -> Paused<E, R> {
+        Paused {
+            inner: reporter,
+            injector: self.clone(),
+        }
+    }
+
+    /// Sleep for [Config::delay_uploads].
+    async fn delay(&self) {
+        if self.cfg.delay_uploads > 0 {
+            self.context
+                .sleep(Duration::from_millis(self.cfg.delay_uploads))
+                .await;
+        }
+    }
+}
+
+/// A broadcast [LimitedSender] that drops a percentage of sends.
+#[derive(Clone)]
+pub struct LossySender<S, E: Clock + Rng + Metrics> {
+    inner: S,
+    injector: Injector<E>,
+}
+
+impl<S, E> LimitedSender for LossySender<S, E>
+where
+    S: LimitedSender<PublicKey = PublicKey>,
+    E: Clock + Rng + Metrics,
+{
+    type PublicKey = PublicKey;
+    type Checked<'a>
+        = LossyCheckedSender<S::Checked<'a>>
+    where
+        Self: 'a;
+
+    async fn check<'a>(
+        &'a mut self,
+        recipients: Recipients<Self::PublicKey>,
+    ) -> Result<Self::Checked<'a>, SystemTime> {
+        let drop = self.injector.context.gen_range(0..100u8) < self.injector.cfg.drop_broadcasts;
+        let inner = self.inner.check(recipients).await?;
+        Ok(LossyCheckedSender {
+            inner,
+            dropped: drop.then(|| self.injector.dropped.clone()),
+        })
+    }
+}
+
+/// A [CheckedSender] that may drop its send.
+pub struct LossyCheckedSender<C> {
+    inner: C,
+
+    /// Counter to increment instead of sending (if the send is dropped).
+    dropped: Option<Counter>,
+}
+
+impl<C: CheckedSender<PublicKey = PublicKey>> CheckedSender for LossyCheckedSender<C> {
+    type PublicKey = PublicKey;
+    type Error = C::Error;
+
+    async fn send(
+        self,
+        message: impl Buf + Send,
+        priority: bool,
+    ) -> Result<Vec<Self::PublicKey>, Self::Error> {
+        if let Some(dropped) = self.dropped {
+            dropped.inc();
+            return Ok(Vec::new());
+        }
+        self.inner.send(message, priority).await
+    }
+}
+
+/// An [Indexer] that delays every upload.
+#[derive(Clone)]
+pub struct Delayed<E: Clock + Rng + Metrics, I: Indexer> {
+    inner: I,
+    injector: Injector<E>,
+}
+
+impl<E: Clock + Rng + Metrics, I: Indexer> Indexer for Delayed<E, I> {
+    type Error = I::Error;
+
+    async fn seed_upload(&self, seed: Seed) -> Result<(), Self::Error> {
+        self.injector.delay().await;
+        self.inner.seed_upload(seed).await
+    }
+
+    async fn notarized_upload(&self, notarized: Notarized) -> Result<(), Self::Error> {
+        self.injector.delay().await;
+        self.inner.notarized_upload(notarized).await
+    }
+
+    async fn finalized_upload(&self, finalized: Finalized) -> Result<(), Self::Error> {
+        self.injector.delay().await;
+        self.inner.finalized_upload(finalized).await
+    }
+
+    async fn nullification_upload(&self, nullification: Nullification) -> Result<(), Self::Error> {
+        self.injector.delay().await;
+        self.inner.nullification_upload(nullification).await
+    }
+
+    async fn batch_upload(&self, artifacts: Vec<Artifact>) -> Result<Vec<bool>, Self::Error> {
+        self.injector.delay().await;
+        self.inner.batch_upload(artifacts).await
+    }
+}
+
+/// A finalizer [Reporter] that holds finalized blocks while paused.
+#[derive(Clone)]
+pub struct Paused<E: Clock + Rng + Metrics, R> {
+    inner: R,
+    injector: Injector<E>,
+}
+
+impl<E, R> Reporter for Paused<E, R>
+where
+    E: Clock + Rng + Metrics,
+    R: Reporter<Activity = Update<Block>>,
+{
+    type Activity = Update<Block>;
+
+    async fn report(&mut self, activity: Self::Activity) {
+        if let (Some(pause), Update::Block(..)) = (self.injector.cfg.pause_finalizer, &activity) {
+            let elapsed = self
+                .injector
+                .context
+                .current()
+                .duration_since(self.injector.start)
+                .unwrap_or_default();
+            let remaining = pause.remaining(elapsed);
+            if !remaining.is_zero() {
+                self.injector.paused.inc();
+                self.injector.context.sleep(remaining).await;
+            }
+        }
+        self.inner.report(activity).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause() {
+        let pause = Pause {
+            interval: 1_000,
+            duration: 200,
+        };
+
+        // Not paused for most of the interval
+        assert_eq!(pause.remaining(Duration::ZERO), Duration::ZERO);
+        assert_eq!(pause.remaining(Duration::from_millis(799)), Duration::ZERO);
+
+        // Paused at the end of each interval
+        assert_eq!(
+            pause.remaining(Duration::from_millis(800)),
+            Duration::from_millis(200)
+        );
+        assert_eq!(
+            pause.remaining(Duration::from_millis(2_950)),
+            Duration::from_millis(50)
+        );
+
+        // Empty intervals never pause
+        let never = Pause {
+            interval: 0,
+            duration: 200,
+        };
+        assert_eq!(never.remaining(Duration::from_millis(100)), Duration::ZERO);
+    }
+}
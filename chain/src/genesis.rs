@@ -0,0 +1,97 @@
+//! Verification of genesis transcripts.
+//!
+//! A validator configured with a different participant set, polynomial, or network-wide
+//! parameter (like the [DigestMode]) than its peers still starts but never agrees with them. The
+//! [Genesis] transcript produced by `setup` commits to all of these (and is signed by every
+//! participant), so each validator can refuse to start if its configuration does not match.
+
+use crate::application::{self, TimestampPolicy};
+use alto_types::{DigestMode, Genesis, PublicKey};
+use commonware_codec::Decode;
+use commonware_cryptography::{
+    bls12381::primitives::{sharing::Sharing, variant::MinSig},
+    sha256::Digest,
+    Hasher, Sha256,
+};
+use commonware_utils::{from_hex_formatted, ordered::Set};
+use std::num::NonZeroU32;
+use thiserror::Error;
+
+/// Errors that can occur when verifying a [Genesis] transcript.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid hex")]
+    InvalidHex,
+    #[error("invalid transcript: {0}")]
+    InvalidTranscript(#[from] commonware_codec::Error),
+    #[error("transcript is not signed by every participant")]
+    InvalidSignature,
+    #[error("participants do not match the transcript")]
+    ParticipantsMismatch,
+    #[error("polynomial does not match the transcript")]
+    PolynomialMismatch,
+    #[error("network config does not match the transcript")]
+    ConfigMismatch,
+}
+
+impl From<Error> for alto_types::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::InvalidHex => {
+                Self::Codec(commonware_codec::Error::Invalid("Genesis", "invalid hex"))
+            }
+            Error::InvalidTranscript(e) => Self::Codec(e),
+            Error::InvalidSignature => Self::Signature,
+            Error::ParticipantsMismatch | Error::PolynomialMismatch | Error::ConfigMismatch => {
+                Self::Consistency(error.to_string())
+            }
+        }
+    }
+}
+
+/// Compute the digest of the network-wide parameters of `config` (those all participants must
+/// agree on).
+///
+/// `synchrony_bound` and `max_drift` are excluded because they may be tuned per validator.
+pub fn config_digest(config: &application::Config) -> Digest {
+    let mut hasher = Sha256::new();
+    hasher.update(match config.digest_mode {
+        DigestMode::Sha256 => b"sha256".as_slice(),
+        DigestMode::Blake3 => b"blake3".as_slice(),
+    });
+    hasher.update(match config.timestamp_policy {
+        TimestampPolicy::WallClock => b"wall_clock".as_slice(),
+        TimestampPolicy::Monotonic => b"monotonic".as_slice(),
+    });
+    hasher.update(&(config.max_block_size as u64).to_be_bytes());
+    hasher.update(&config.genesis_time.to_be_bytes());
+    hasher.finalize()
+}
+
+/// Decode a hex-encoded [Genesis] transcript with at most `max` participants.
+pub fn decode(genesis: &str, max: NonZeroU32) -> Result<Genesis, Error> {
+    let bytes = from_hex_formatted(genesis).ok_or(Error::InvalidHex)?;
+    Ok(Genesis::decode_cfg(bytes.as_ref(), &max)?)
+}
+
+/// Verify `genesis` is signed by every participant and matches the local configuration.
+pub fn verify(
+    genesis: &Genesis,
+    participants: &Set<PublicKey>,
+    polynomial: &Sharing<MinSig>,
+    config: &application::Config,
+) -> Result<(), Error> {
+    if !genesis.verify() {
+        return Err(Error::InvalidSignature);
+    }
+    if !participants.iter().eq(genesis.participants.iter()) {
+        return Err(Error::ParticipantsMismatch);
+    }
+    if *polynomial != genesis.polynomial {
+        return Err(Error::PolynomialMismatch);
+    }
+    if config_digest(config) != genesis.config {
+        return Err(Error::ConfigMismatch);
+    }
+    Ok(())
+}
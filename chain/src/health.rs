@@ -0,0 +1,189 @@
+//! Container healthchecks.
+//!
+//! The [Server] answers HTTP requests on a dedicated port (separate from metrics), so
+//! orchestrators (like Kubernetes or ECS) can probe a validator without parsing Prometheus text:
+//! - `GET /healthz` returns `200` while the validator is running (liveness).
+//! - `GET /readyz` returns `200` if a block was processed within `finalization_timeout` seconds
+//!   and `503` otherwise (readiness).
+//!
+//! Both respond with a JSON [Status].
+
+use crate::application::Processed;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use commonware_runtime::{Clock, Metrics, Spawner};
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+/// Default seconds without a new processed block before reporting not ready.
+pub const DEFAULT_FINALIZATION_TIMEOUT: u64 = 30;
+
+/// How often to sample the processed height.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Configuration for the healthcheck [Server].
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Config {
+    /// Port to serve healthchecks on.
+    pub port: u16,
+
+    /// Seconds without a new processed block before reporting not ready (defaults to
+    /// [DEFAULT_FINALIZATION_TIMEOUT]).
+    pub finalization_timeout: Option<u64>,
+}
+
+/// Health reported by the [Server].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    /// Whether a block was processed within the finalization timeout.
+    pub ready: bool,
+
+    /// Latest processed height (if known).
+    pub height: Option<u64>,
+
+    /// Seconds since the processed height last advanced (if it has advanced since startup).
+    pub since_finalized: Option<u64>,
+}
+
+/// Tracks progress of the processed height across samples.
+pub struct Readiness {
+    timeout: Duration,
+    height: Option<(u64, Option<SystemTime>)>,
+}
+
+impl Readiness {
+    /// Create a new [Readiness] that requires a block to be processed every `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            height: None,
+        }
+    }
+
+    /// Observe the processed `height` at `now`.
+    ///
+    /// The first height observed is only used as a baseline (it may have been restored from
+    /// storage), so the validator is not ready until the height advances.
+    pub fn observe(&mut self, height: u64, now: SystemTime) {
+        match self.height {
+            Some((last, _)) if last == height => {}
+            Some(_) => self.height = Some((height, Some(now))),
+            None => self.height = Some((height, None)),
+        }
+    }
+
+    /// Get the [Status] at `now`.
+    pub fn status(&self, now: SystemTime) -> Status {
+        let height = self.height.map(|(height, _)| height);
+        let since_finalized = self
+            .height
+            .and_then(|(_, at)| at)
+            .map(|at| now.duration_since(at).unwrap_or_default());
+        Status {
+            ready: since_finalized.is_some_and(|since| since < self.timeout),
+            height,
+            since_finalized: since_finalized.map(|since| since.as_secs()),
+        }
+    }
+}
+
+/// State shared by request handlers.
+#[derive(Clone)]
+struct Shared<E: Clock> {
+    context: E,
+    processed: Processed,
+    readiness: Arc<Mutex<Readiness>>,
+}
+
+impl<E: Clock> Shared<E> {
+    /// Observe the processed height and get the resulting [Status].
+    fn status(&self) -> Status {
+        let now = self.context.current();
+        let mut readiness = self.readiness.lock().unwrap();
+        readiness.observe(self.processed.get(), now);
+        readiness.status(now)
+    }
+}
+
+/// Serves healthchecks over HTTP.
+pub struct Server<E: Clock + Spawner + Metrics> {
+    context: E,
+    port: u16,
+    shared: Shared<E>,
+}
+
+impl<E: Clock + Spawner + Metrics> Server<E> {
+    /// Create a new [Server] that reports the progress of `processed`.
+    pub fn new(context: E, cfg: Config, processed: Processed) -> Self {
+        let timeout = cfg
+            .finalization_timeout
+            .unwrap_or(DEFAULT_FINALIZATION_TIMEOUT);
+        let shared = Shared {
+            context: context.clone(),
+            processed,
+            readiness: Arc::new(Mutex::new(Readiness::new(Duration::from_secs(timeout)))),
+        };
+        Self {
+            context,
+            port: cfg.port,
+            shared,
+        }
+    }
+
+    /// Start the [Server].
+    pub fn start(self) -> commonware_runtime::Handle<()> {
+        self.context.clone().spawn(|_| self.run())
+    }
+
+    async fn run(self) {
+        // Sample the processed height in the background (so progress is tracked between requests)
+        let shared = self.shared.clone();
+        self.context
+            .with_label("sampler")
+            .spawn(|context| async move {
+                loop {
+                    shared.status();
+                    context.sleep(SAMPLE_INTERVAL).await;
+                }
+            });
+
+        // Serve requests
+        let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), self.port);
+        let listener = match TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!(?e, port = self.port, "failed to bind healthcheck server");
+                return;
+            }
+        };
+        info!(port = self.port, "serving healthchecks");
+        let router = Router::new()
+            .route("/healthz", get(healthz::<E>))
+            .route("/readyz", get(readyz::<E>))
+            .with_state(self.shared);
+        if let Err(e) = axum::serve(listener, router).await {
+            warn!(?e, "healthcheck server failed");
+        }
+    }
+}
+
+/// Report liveness (always `200`).
+async fn healthz<E: Clock>(State(shared): State<Shared<E>>) -> Json<Status> {
+    Json(shared.status())
+}
+
+/// Report readiness (`200` if ready, `503` otherwise).
+async fn readyz<E: Clock>(State(shared): State<Shared<E>>) -> (StatusCode, Json<Status>) {
+    let status = shared.status();
+    let code = if status.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (code, Json(status))
+}
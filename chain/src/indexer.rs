@@ -1,18 +1,48 @@
+use crate::spool;
+use alto_client::consensus::Uploader;
 #[cfg(test)]
 use alto_types::Identity;
-use alto_types::{Activity, Block, Finalized, Notarized, Scheme, Seed, Seedable};
-use commonware_consensus::{marshal, Reporter, Viewable};
+use alto_types::{
+    Ack, Activity, Block, Finalized, Kind, Notarized, Nullification, Scheme, Seed, Seedable,
+    Subscription, LATENCY_BUCKETS, MAX_BATCH_UPLOADS,
+};
+use commonware_codec::Encode;
+use commonware_consensus::{marshal, types::View, Reporter, Viewable};
+use commonware_macros::select;
 use commonware_parallel::Strategy;
-use commonware_runtime::{Metrics, Spawner};
-use std::future::Future;
+use commonware_runtime::{Clock, Metrics, Spawner};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{self, join_all, Either, FutureExt, Shared},
+    SinkExt, StreamExt,
+};
+use prometheus_client::metrics::{
+    counter::Counter, exemplar::HistogramWithExemplars, family::Family, gauge::Gauge,
+};
+use serde::{Deserialize, Serialize};
 #[cfg(test)]
-use std::{sync::atomic::AtomicBool, sync::Arc};
+use std::sync::atomic::AtomicBool;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{atomic::AtomicU64, Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use thiserror::Error;
 use tracing::{debug, warn};
 
 /// Trait for interacting with an indexer.
 pub trait Indexer: Clone + Send + Sync + 'static {
     type Error: std::error::Error + Send + Sync + 'static;
 
+    /// Whether an upload that failed with `error` may be accepted if retried.
+    ///
+    /// By default, every failed upload is retried.
+    fn retryable(error: &Self::Error) -> bool {
+        let _ = error;
+        true
+    }
+
     /// Upload a seed to the indexer.
     fn seed_upload(&self, seed: Seed) -> impl Future<Output = Result<(), Self::Error>> + Send;
 
@@ -27,6 +57,80 @@ pub trait Indexer: Clone + Send + Sync + 'static {
         &self,
         finalized: Finalized,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Upload a nullification to the indexer.
+    fn nullification_upload(
+        &self,
+        nullification: Nullification,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Upload several artifacts to the indexer, returning whether each (in order) was accepted.
+    ///
+    /// By default, each artifact is uploaded on its own (concurrently).
+    fn batch_upload(
+        &self,
+        artifacts: Vec<Artifact>,
+    ) -> impl Future<Output = Result<Vec<bool>, Self::Error>> + Send {
+        async move {
+            let uploads = artifacts.into_iter().map(|artifact| artifact.upload(self));
+            let results = join_all(uploads).await;
+            Ok(results.iter().map(Result::is_ok).collect())
+        }
+    }
+}
+
+/// An artifact uploaded to an [Indexer].
+#[derive(Clone)]
+pub enum Artifact {
+    Seed(Seed),
+    Notarized(Notarized),
+    Finalized(Finalized),
+    Nullification(Nullification),
+}
+
+impl Artifact {
+    /// Get the [Kind] of the artifact.
+    pub fn kind(&self) -> Kind {
+        match self {
+            Self::Seed(_) => Kind::Seed,
+            Self::Notarized(_) => Kind::Notarization,
+            Self::Finalized(_) => Kind::Finalization,
+            Self::Nullification(_) => Kind::Nullification,
+        }
+    }
+
+    /// Encode the artifact (as it is uploaded on its own).
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Seed(seed) => seed.encode().to_vec(),
+            Self::Notarized(notarized) => notarized.encode().to_vec(),
+            Self::Finalized(finalized) => finalized.encode().to_vec(),
+            Self::Nullification(nullification) => nullification.encode().to_vec(),
+        }
+    }
+
+    /// Upload the artifact on its own.
+    pub(crate) async fn upload<I: Indexer>(self, indexer: &I) -> Result<(), Failure<I::Error>> {
+        let result = match self {
+            Self::Seed(seed) => indexer.seed_upload(seed).await,
+            Self::Notarized(notarized) => indexer.notarized_upload(notarized).await,
+            Self::Finalized(finalized) => indexer.finalized_upload(finalized).await,
+            Self::Nullification(nullification) => indexer.nullification_upload(nullification).await,
+        };
+        result.map_err(|error| Failure {
+            retryable: I::retryable(&error),
+            error,
+        })
+    }
+}
+
+/// A failed upload of an [Artifact].
+#[derive(Debug)]
+pub(crate) struct Failure<E> {
+    pub error: E,
+
+    /// Whether the upload may be accepted if retried (see [Indexer::retryable]).
+    pub retryable: bool,
 }
 
 /// A mock indexer implementation for testing.
@@ -34,8 +138,10 @@ pub trait Indexer: Clone + Send + Sync + 'static {
 #[derive(Clone)]
 pub struct Mock {
     pub seed_seen: Arc<AtomicBool>,
+    pub seeds: Arc<Mutex<BTreeMap<View, usize>>>,
     pub notarization_seen: Arc<AtomicBool>,
     pub finalization_seen: Arc<AtomicBool>,
+    pub nullification_seen: Arc<AtomicBool>,
 }
 
 #[cfg(test)]
@@ -43,8 +149,10 @@ impl Mock {
     pub fn new(_: &str, _: Identity) -> Self {
         Self {
             seed_seen: Arc::new(AtomicBool::new(false)),
+            seeds: Arc::new(Mutex::new(BTreeMap::new())),
             notarization_seen: Arc::new(AtomicBool::new(false)),
             finalization_seen: Arc::new(AtomicBool::new(false)),
+            nullification_seen: Arc::new(AtomicBool::new(false)),
         }
     }
 }
@@ -53,9 +161,10 @@ impl Mock {
 impl Indexer for Mock {
     type Error = std::io::Error;
 
-    async fn seed_upload(&self, _: Seed) -> Result<(), Self::Error> {
+    async fn seed_upload(&self, seed: Seed) -> Result<(), Self::Error> {
         self.seed_seen
             .store(true, std::sync::atomic::Ordering::Relaxed);
+        *self.seeds.lock().unwrap().entry(seed.view()).or_default() += 1;
         Ok(())
     }
 
@@ -70,11 +179,21 @@ impl Indexer for Mock {
             .store(true, std::sync::atomic::Ordering::Relaxed);
         Ok(())
     }
+
+    async fn nullification_upload(&self, _: Nullification) -> Result<(), Self::Error> {
+        self.nullification_seen
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
 }
 
 impl<S: Strategy> Indexer for alto_client::Client<S> {
     type Error = alto_client::Error;
 
+    fn retryable(error: &Self::Error) -> bool {
+        error.retryable()
+    }
+
     fn seed_upload(&self, seed: Seed) -> impl Future<Output = Result<(), Self::Error>> + Send {
         self.seed_upload(seed)
     }
@@ -92,53 +211,944 @@ impl<S: Strategy> Indexer for alto_client::Client<S> {
     ) -> impl Future<Output = Result<(), Self::Error>> + Send {
         self.finalized_upload(finalized)
     }
+
+    fn nullification_upload(
+        &self,
+        nullification: Nullification,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.nullification_upload(nullification)
+    }
+
+    async fn batch_upload(&self, artifacts: Vec<Artifact>) -> Result<Vec<bool>, Self::Error> {
+        let artifacts = artifacts
+            .iter()
+            .map(|artifact| (artifact.kind(), artifact.encode()))
+            .collect();
+        let acks = self.batch_upload(artifacts).await?;
+        Ok(acks.iter().map(Ack::accepted).collect())
+    }
+}
+
+/// How uploads reach an indexer (see [Remote]).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    /// A request per upload (or batch).
+    #[default]
+    Http,
+
+    /// A single consensus websocket per indexer (see [Socket]).
+    Socket,
+}
+
+/// How long to wait before reopening the websocket of a [Socket] after it fails to open.
+pub const SOCKET_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// The open websocket of a [Socket] (if any).
+struct Connection<S: Strategy> {
+    uploader: Option<Uploader<S>>,
+
+    /// Incremented every time the websocket is opened (so a failed upload only closes the
+    /// websocket it was sent on).
+    generation: u64,
+
+    /// When the websocket last failed to open (if it has not opened since).
+    failed: Option<SystemTime>,
+}
+
+/// An [Indexer] that uploads over a single consensus websocket (see
+/// [alto_client::Client::socket]) instead of issuing a request per artifact.
+///
+/// The websocket is opened on the first upload and reopened on the next upload after it fails
+/// (at most once every [SOCKET_RECONNECT_DELAY]). Uploads are pipelined: each waits for its
+/// acknowledgment without blocking the uploads behind it. Uploads that fail because the
+/// websocket closed are returned as errors (to be retried, like failed HTTP uploads).
+pub struct Socket<S: Strategy> {
+    client: alto_client::Client<S>,
+    connection: Arc<futures::lock::Mutex<Connection<S>>>,
+}
+
+impl<S: Strategy> Clone for Socket<S> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            connection: self.connection.clone(),
+        }
+    }
+}
+
+impl<S: Strategy> Socket<S> {
+    /// Create a new [Socket] that uploads to the indexer of `client`.
+    pub fn new(client: alto_client::Client<S>) -> Self {
+        Self {
+            client,
+            connection: Arc::new(futures::lock::Mutex::new(Connection {
+                uploader: None,
+                generation: 0,
+                failed: None,
+            })),
+        }
+    }
+
+    /// Upload an encoded artifact of `kind` (opening the websocket if needed), failing if it is
+    /// not accepted.
+    async fn upload(&self, kind: Kind, body: Vec<u8>) -> Result<(), alto_client::Error> {
+        let (generation, ack) = {
+            let mut connection = self.connection.lock().await;
+
+            // Send on the open websocket (reopening it once if it has closed since it was used)
+            let mut sent = None;
+            for _ in 0..2 {
+                if connection.uploader.is_none() {
+                    let now = SystemTime::now();
+                    let recent = connection.failed.is_some_and(|failed| {
+                        now.duration_since(failed).unwrap_or_default() < SOCKET_RECONNECT_DELAY
+                    });
+                    if recent {
+                        return Err(alto_client::Error::StreamClosed);
+                    }
+
+                    // Only acknowledgments are needed (so no consensus messages are received)
+                    let subscription = Subscription::default().with_end_view(0);
+                    match self.client.socket_filtered(&subscription).await {
+                        Ok((uploader, _)) => {
+                            debug!("opened upload websocket");
+                            connection.uploader = Some(uploader);
+                            connection.generation += 1;
+                            connection.failed = None;
+                        }
+                        Err(e) => {
+                            warn!(?e, "failed to open upload websocket");
+                            connection.failed = Some(now);
+                            return Err(e);
+                        }
+                    }
+                }
+                let uploader = connection.uploader.as_mut().expect("websocket is open");
+                match uploader.send(kind, body.clone()).await {
+                    Ok(ack) => {
+                        sent = Some((connection.generation, ack));
+                        break;
+                    }
+                    Err(e) => {
+                        debug!(?e, "upload websocket closed");
+                        connection.uploader = None;
+                    }
+                }
+            }
+            sent.ok_or(alto_client::Error::StreamClosed)?
+        };
+
+        // Close the websocket if it closed before acknowledging the upload (unless it was
+        // already reopened)
+        let result = ack.await;
+        if matches!(result, Err(alto_client::Error::StreamClosed)) {
+            let mut connection = self.connection.lock().await;
+            if connection.generation == generation {
+                connection.uploader = None;
+            }
+        }
+        result
+    }
+}
+
+impl<S: Strategy> Indexer for Socket<S> {
+    type Error = alto_client::Error;
+
+    fn retryable(error: &Self::Error) -> bool {
+        error.retryable()
+    }
+
+    async fn seed_upload(&self, seed: Seed) -> Result<(), Self::Error> {
+        self.upload(Kind::Seed, seed.encode().to_vec()).await
+    }
+
+    async fn notarized_upload(&self, notarized: Notarized) -> Result<(), Self::Error> {
+        self.upload(Kind::Notarization, notarized.encode().to_vec())
+            .await
+    }
+
+    async fn finalized_upload(&self, finalized: Finalized) -> Result<(), Self::Error> {
+        self.upload(Kind::Finalization, finalized.encode().to_vec())
+            .await
+    }
+
+    async fn nullification_upload(&self, nullification: Nullification) -> Result<(), Self::Error> {
+        self.upload(Kind::Nullification, nullification.encode().to_vec())
+            .await
+    }
+}
+
+/// An [Indexer] reached over either [Transport].
+#[derive(Clone)]
+pub enum Remote<S: Strategy> {
+    Http(alto_client::Client<S>),
+    Socket(Socket<S>),
+}
+
+impl<S: Strategy> Remote<S> {
+    /// Upload to the indexer of `client` over `transport`.
+    pub fn new(client: alto_client::Client<S>, transport: Transport) -> Self {
+        match transport {
+            Transport::Http => Self::Http(client),
+            Transport::Socket => Self::Socket(Socket::new(client)),
+        }
+    }
+}
+
+impl<S: Strategy> Indexer for Remote<S> {
+    type Error = alto_client::Error;
+
+    fn retryable(error: &Self::Error) -> bool {
+        error.retryable()
+    }
+
+    async fn seed_upload(&self, seed: Seed) -> Result<(), Self::Error> {
+        match self {
+            Self::Http(client) => Indexer::seed_upload(client, seed).await,
+            Self::Socket(socket) => socket.seed_upload(seed).await,
+        }
+    }
+
+    async fn notarized_upload(&self, notarized: Notarized) -> Result<(), Self::Error> {
+        match self {
+            Self::Http(client) => Indexer::notarized_upload(client, notarized).await,
+            Self::Socket(socket) => socket.notarized_upload(notarized).await,
+        }
+    }
+
+    async fn finalized_upload(&self, finalized: Finalized) -> Result<(), Self::Error> {
+        match self {
+            Self::Http(client) => Indexer::finalized_upload(client, finalized).await,
+            Self::Socket(socket) => socket.finalized_upload(finalized).await,
+        }
+    }
+
+    async fn nullification_upload(&self, nullification: Nullification) -> Result<(), Self::Error> {
+        match self {
+            Self::Http(client) => Indexer::nullification_upload(client, nullification).await,
+            Self::Socket(socket) => socket.nullification_upload(nullification).await,
+        }
+    }
+
+    async fn batch_upload(&self, artifacts: Vec<Artifact>) -> Result<Vec<bool>, Self::Error> {
+        match self {
+            Self::Http(client) => Indexer::batch_upload(client, artifacts).await,
+            Self::Socket(socket) => socket.batch_upload(artifacts).await,
+        }
+    }
+}
+
+/// How long to prefer other indexers after an upload to one fails.
+pub const INDEXER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Weight of the latest round-trip time in an indexer's moving average.
+const RTT_WEIGHT: f64 = 0.2;
+
+/// Observed health of an indexer behind [Fastest].
+#[derive(Default)]
+struct Status {
+    /// Moving average of upload round-trip times (in seconds), if any upload succeeded.
+    rtt: Option<f64>,
+
+    /// Time before which the indexer should be avoided (if an upload recently failed).
+    unhealthy_until: Option<SystemTime>,
+}
+
+/// An indexer behind [Fastest].
+struct Endpoint<I> {
+    name: String,
+    indexer: I,
+    status: Mutex<Status>,
+    rtt: Gauge<f64, AtomicU64>,
+}
+
+/// An [Indexer] that uploads to the fastest of several indexers.
+///
+/// Seeds, notarizations, and nullifications (which only feed live views) are uploaded to the
+/// healthy indexer with the lowest average round-trip time, failing over to the others (in
+/// order of their round-trip time) if the upload fails. Finalizations are uploaded to every
+/// indexer, which also refreshes the round-trip time of each indexer as the chain progresses
+/// (and lets an indexer that recovered from a failure be preferred again once it is fast).
+///
+/// Indexers that have not completed an upload yet are tried first (to measure them), and an
+/// indexer that fails an upload is tried last for [INDEXER_COOLDOWN].
+pub struct Fastest<E: Clock, I: Indexer> {
+    context: E,
+    endpoints: Arc<Vec<Endpoint<I>>>,
+}
+
+impl<E: Clock, I: Indexer> Clone for Fastest<E, I> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+            endpoints: self.endpoints.clone(),
+        }
+    }
+}
+
+impl<E: Clock + Metrics, I: Indexer> Fastest<E, I> {
+    /// Create a new [Fastest] over `indexers` (labeled by name).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `indexers` is empty.
+    pub fn new(context: E, indexers: Vec<(String, I)>) -> Self {
+        assert!(!indexers.is_empty(), "at least one indexer is required");
+        let rtt = Family::<Labels, Gauge<f64, AtomicU64>>::default();
+        context.register(
+            "rtt",
+            "Average round-trip time (in seconds) of uploads to each indexer",
+            rtt.clone(),
+        );
+        let endpoints = indexers
+            .into_iter()
+            .map(|(name, indexer)| Endpoint {
+                rtt: rtt
+                    .get_or_create(&vec![("indexer".to_string(), name.clone())])
+                    .clone(),
+                name,
+                indexer,
+                status: Mutex::new(Status::default()),
+            })
+            .collect();
+        Self {
+            context,
+            endpoints: Arc::new(endpoints),
+        }
+    }
+}
+
+impl<E: Clock, I: Indexer> Fastest<E, I> {
+    /// Get the names of the indexers in the order uploads try them: healthy indexers (fastest
+    /// first) followed by unhealthy ones.
+    pub fn order(&self) -> Vec<&str> {
+        self.ordered()
+            .into_iter()
+            .map(|endpoint| endpoint.name.as_str())
+            .collect()
+    }
+
+    fn ordered(&self) -> Vec<&Endpoint<I>> {
+        let now = self.context.current();
+        let mut ordered: Vec<_> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let status = endpoint.status.lock().unwrap();
+                let unhealthy = status.unhealthy_until.is_some_and(|until| until > now);
+                (unhealthy, status.rtt.unwrap_or_default(), endpoint)
+            })
+            .collect();
+        ordered.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        ordered
+            .into_iter()
+            .map(|(_, _, endpoint)| endpoint)
+            .collect()
+    }
+
+    /// Run `upload` against `endpoint`, recording its round-trip time (or failure).
+    async fn timed<T, F>(&self, endpoint: &Endpoint<I>, upload: F) -> Result<T, I::Error>
+    where
+        F: Future<Output = Result<T, I::Error>>,
+    {
+        let start = self.context.current();
+        let result = upload.await;
+        let now = self.context.current();
+        let mut status = endpoint.status.lock().unwrap();
+        match &result {
+            Ok(_) => {
+                let elapsed = now.duration_since(start).unwrap_or_default().as_secs_f64();
+                let rtt = match status.rtt {
+                    Some(rtt) => rtt + RTT_WEIGHT * (elapsed - rtt),
+                    None => elapsed,
+                };
+                status.rtt = Some(rtt);
+                status.unhealthy_until = None;
+                endpoint.rtt.set(rtt);
+            }
+            Err(e) => {
+                warn!(indexer = %endpoint.name, ?e, "upload to indexer failed");
+                status.unhealthy_until = Some(now + INDEXER_COOLDOWN);
+            }
+        }
+        result
+    }
+
+    /// Upload to the fastest healthy indexer (failing over to the others).
+    async fn preferred<T, F, Fut>(&self, upload: F) -> Result<T, I::Error>
+    where
+        F: Fn(I) -> Fut,
+        Fut: Future<Output = Result<T, I::Error>>,
+    {
+        let mut failure = None;
+        for endpoint in self.ordered() {
+            match self.timed(endpoint, upload(endpoint.indexer.clone())).await {
+                Ok(result) => return Ok(result),
+                Err(e) => failure = Some(e),
+            }
+        }
+        Err(failure.expect("at least one indexer is configured"))
+    }
+
+    /// Upload to every indexer (succeeding if any upload succeeds).
+    async fn fanout<T, F, Fut>(&self, upload: F) -> Result<T, I::Error>
+    where
+        F: Fn(I) -> Fut,
+        Fut: Future<Output = Result<T, I::Error>>,
+    {
+        let uploads = self
+            .endpoints
+            .iter()
+            .map(|endpoint| self.timed(endpoint, upload(endpoint.indexer.clone())));
+        let mut failure = None;
+        for result in join_all(uploads).await {
+            match result {
+                Ok(result) => return Ok(result),
+                Err(e) => failure = failure.or(Some(e)),
+            }
+        }
+        Err(failure.expect("at least one indexer is configured"))
+    }
+}
+
+impl<E: Clock, I: Indexer> Indexer for Fastest<E, I> {
+    type Error = I::Error;
+
+    fn retryable(error: &Self::Error) -> bool {
+        I::retryable(error)
+    }
+
+    async fn seed_upload(&self, seed: Seed) -> Result<(), Self::Error> {
+        self.preferred(|indexer| {
+            let seed = seed.clone();
+            async move { indexer.seed_upload(seed).await }
+        })
+        .await
+    }
+
+    async fn notarized_upload(&self, notarized: Notarized) -> Result<(), Self::Error> {
+        self.preferred(|indexer| {
+            let notarized = notarized.clone();
+            async move { indexer.notarized_upload(notarized).await }
+        })
+        .await
+    }
+
+    async fn finalized_upload(&self, finalized: Finalized) -> Result<(), Self::Error> {
+        self.fanout(|indexer| {
+            let finalized = finalized.clone();
+            async move { indexer.finalized_upload(finalized).await }
+        })
+        .await
+    }
+
+    async fn nullification_upload(&self, nullification: Nullification) -> Result<(), Self::Error> {
+        self.preferred(|indexer| {
+            let nullification = nullification.clone();
+            async move { indexer.nullification_upload(nullification).await }
+        })
+        .await
+    }
+
+    /// Batches containing a finalization are uploaded to every indexer (like finalizations) and
+    /// all others to the fastest healthy indexer.
+    async fn batch_upload(&self, artifacts: Vec<Artifact>) -> Result<Vec<bool>, Self::Error> {
+        let upload = |indexer: I| {
+            let artifacts = artifacts.clone();
+            async move { indexer.batch_upload(artifacts).await }
+        };
+        let finalized = artifacts
+            .iter()
+            .any(|artifact| matches!(artifact, Artifact::Finalized(_)));
+        if finalized {
+            self.fanout(upload).await
+        } else {
+            self.preferred(upload).await
+        }
+    }
+}
+
+/// Configuration for batched uploads (see [Batcher]).
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct BatchConfig {
+    /// Maximum number of artifacts uploaded in a single request (at most [MAX_BATCH_UPLOADS]).
+    pub max_items: usize,
+
+    /// Maximum time (in milliseconds) an artifact waits for its batch to fill before the batch
+    /// is uploaded.
+    pub max_delay: u64,
+}
+
+/// Error returned by uploads through a [Batcher].
+#[derive(Debug, Error)]
+pub enum BatchError<E: std::error::Error> {
+    #[error("upload failed: {0}")]
+    Upload(Arc<E>),
+    #[error("upload rejected by the indexer")]
+    Rejected,
+    #[error("batcher stopped")]
+    Stopped,
+}
+
+/// An artifact waiting in a [Batcher] (and where to send the result of its upload).
+type Queued<E> = (Artifact, oneshot::Sender<Result<(), BatchError<E>>>);
+
+/// An [Indexer] that uploads artifacts in batches (or immediately, without a [BatchConfig]).
+///
+/// At high throughput, uploading each seed, notarization, nullification, and finalization with
+/// its own request creates a large volume of small requests. A [Batcher] instead collects
+/// artifacts until [BatchConfig::max_items] are waiting (or the oldest has waited
+/// [BatchConfig::max_delay] milliseconds) and uploads them with [Indexer::batch_upload]. Each
+/// upload resolves once its batch has been uploaded (batches are uploaded concurrently).
+///
+/// Notarizations and finalizations wait for the seed of their view (see [Sequencer]), so they
+/// are usually uploaded in the batch after the one containing their seed.
+pub struct Batcher<I: Indexer> {
+    inner: I,
+    queue: Option<mpsc::Sender<Queued<I::Error>>>,
+}
+
+impl<I: Indexer> Clone for Batcher<I> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl<I: Indexer> Batcher<I> {
+    /// Create a new [Batcher] over `inner` (uploading each artifact immediately if `config` is
+    /// `None`).
+    pub fn new<E: Spawner + Clock + Metrics>(
+        context: E,
+        inner: I,
+        config: Option<BatchConfig>,
+    ) -> Self {
+        let Some(config) = config else {
+            return Self { inner, queue: None };
+        };
+        let max_items = config.max_items.clamp(1, MAX_BATCH_UPLOADS);
+        let batches = Counter::default();
+        context.register(
+            "batches",
+            "Number of batches uploaded to the indexer",
+            batches.clone(),
+        );
+        let batched = Counter::default();
+        context.register(
+            "batched",
+            "Number of artifacts uploaded to the indexer in batches",
+            batched.clone(),
+        );
+        let (sender, receiver) = mpsc::channel(max_items);
+        let collector = Collector {
+            indexer: inner.clone(),
+            max_items,
+            max_delay: Duration::from_millis(config.max_delay),
+            batches,
+            batched,
+        };
+        context.spawn(move |context| collector.run(context, receiver));
+        Self {
+            inner,
+            queue: Some(sender),
+        }
+    }
+
+    /// Upload `artifact` (with the next batch, if batching).
+    async fn upload(&self, artifact: Artifact) -> Result<(), BatchError<I::Error>> {
+        let Some(queue) = &self.queue else {
+            return artifact
+                .upload(&self.inner)
+                .await
+                .map_err(|failure| BatchError::Upload(Arc::new(failure.error)));
+        };
+        let (sender, receiver) = oneshot::channel();
+        queue
+            .clone()
+            .send((artifact, sender))
+            .await
+            .map_err(|_| BatchError::Stopped)?;
+        receiver.await.map_err(|_| BatchError::Stopped)?
+    }
+}
+
+impl<I: Indexer> Indexer for Batcher<I> {
+    type Error = BatchError<I::Error>;
+
+    /// Artifacts rejected within a batch are retried (batches do not report why they were
+    /// rejected).
+    fn retryable(error: &Self::Error) -> bool {
+        match error {
+            BatchError::Upload(error) => I::retryable(error),
+            BatchError::Rejected | BatchError::Stopped => true,
+        }
+    }
+
+    async fn seed_upload(&self, seed: Seed) -> Result<(), Self::Error> {
+        self.upload(Artifact::Seed(seed)).await
+    }
+
+    async fn notarized_upload(&self, notarized: Notarized) -> Result<(), Self::Error> {
+        self.upload(Artifact::Notarized(notarized)).await
+    }
+
+    async fn finalized_upload(&self, finalized: Finalized) -> Result<(), Self::Error> {
+        self.upload(Artifact::Finalized(finalized)).await
+    }
+
+    async fn nullification_upload(&self, nullification: Nullification) -> Result<(), Self::Error> {
+        self.upload(Artifact::Nullification(nullification)).await
+    }
+
+    async fn batch_upload(&self, artifacts: Vec<Artifact>) -> Result<Vec<bool>, Self::Error> {
+        self.inner
+            .batch_upload(artifacts)
+            .await
+            .map_err(|e| BatchError::Upload(Arc::new(e)))
+    }
+}
+
+/// Collects artifacts queued in a [Batcher] into batches.
+struct Collector<I: Indexer> {
+    indexer: I,
+    max_items: usize,
+    max_delay: Duration,
+
+    batches: Counter,
+    batched: Counter,
+}
+
+impl<I: Indexer> Collector<I> {
+    async fn run<E: Spawner + Clock + Metrics>(
+        self,
+        context: E,
+        mut receiver: mpsc::Receiver<Queued<I::Error>>,
+    ) {
+        let mut batch = Vec::new();
+        let mut deadline = context.current();
+        loop {
+            let expired = match batch.is_empty() {
+                false => Either::Left(context.sleep_until(deadline)),
+                true => Either::Right(future::pending()),
+            };
+            let full = select! {
+                queued = receiver.next() => {
+                    let Some(queued) = queued else {
+                        // Upload whatever is left once every [Batcher] is dropped
+                        if !batch.is_empty() {
+                            self.flush(&context, std::mem::take(&mut batch));
+                        }
+                        debug!("batcher stopped");
+                        return;
+                    };
+                    if batch.is_empty() {
+                        deadline = context.current() + self.max_delay;
+                    }
+                    batch.push(queued);
+                    batch.len() >= self.max_items
+                },
+                _ = expired => {
+                    true
+                },
+            };
+            if full {
+                self.flush(&context, std::mem::take(&mut batch));
+            }
+        }
+    }
+
+    /// Upload `batch` (in the background), sending the result of each upload to its waiter.
+    fn flush<E: Spawner + Metrics>(&self, context: &E, batch: Vec<Queued<I::Error>>) {
+        self.batches.inc();
+        self.batched.inc_by(batch.len() as u64);
+        let indexer = self.indexer.clone();
+        context.with_label("batch").spawn(move |_| async move {
+            let (artifacts, waiters): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+            match indexer.batch_upload(artifacts).await {
+                Ok(accepted) => {
+                    for (i, waiter) in waiters.into_iter().enumerate() {
+                        let result = match accepted.get(i) {
+                            Some(true) => Ok(()),
+                            _ => Err(BatchError::Rejected),
+                        };
+                        let _ = waiter.send(result);
+                    }
+                }
+                Err(e) => {
+                    let e = Arc::new(e);
+                    for waiter in waiters {
+                        let _ = waiter.send(Err(BatchError::Upload(e.clone())));
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Maximum time to wait for the seed of a view to be uploaded before uploading its
+/// notarization or finalization anyway.
+pub const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Resolves once the seed of a view is released (or the view is pruned).
+type Released = Shared<oneshot::Receiver<()>>;
+
+/// Sender for (and signal of) the release of a view's seed.
+type Entry = (Option<oneshot::Sender<()>>, Released);
+
+/// Seeds tracked by a [Sequencer].
+#[derive(Default)]
+struct Seeds {
+    /// Views before this have been pruned (and are no longer tracked).
+    pruned: View,
+    entries: BTreeMap<View, Entry>,
+}
+
+impl Seeds {
+    /// Get the entry for `view` (creating it if it does not exist), or `None` if `view` was
+    /// pruned.
+    fn entry(&mut self, view: View) -> Option<&mut Entry> {
+        if view < self.pruned {
+            return None;
+        }
+        Some(self.entries.entry(view).or_insert_with(|| {
+            let (sender, receiver) = oneshot::channel();
+            (Some(sender), receiver.shared())
+        }))
+    }
+}
+
+/// Orders uploads within a view.
+///
+/// Uploads are spawned concurrently (and block uploads must first wait for the block), so
+/// without sequencing the indexer may be asked to serve a notarization or finalization before
+/// the seed of the same view. The [Sequencer] lets block uploads wait (at most
+/// [SEQUENCE_TIMEOUT]) for the seed of their view to be released.
+#[derive(Clone, Default)]
+pub struct Sequencer {
+    seeds: Arc<Mutex<Seeds>>,
+}
+
+impl Sequencer {
+    /// Release uploads waiting on the seed of `view` (after it was uploaded or abandoned).
+    pub fn release(&self, view: View) {
+        let mut seeds = self.seeds.lock().unwrap();
+        let Some((sender, _)) = seeds.entry(view) else {
+            return;
+        };
+        if let Some(sender) = sender.take() {
+            let _ = sender.send(());
+        }
+    }
+
+    /// Wait (at most `timeout`) for the seed of `view` to be released.
+    ///
+    /// Returns `false` if the wait timed out (or the view was pruned).
+    pub async fn wait(&self, context: &impl Clock, view: View, timeout: Duration) -> bool {
+        let released = match self.seeds.lock().unwrap().entry(view) {
+            Some((_, released)) => released.clone(),
+            None => return false,
+        };
+        select! {
+            result = released => {
+                result.is_ok()
+            },
+            _ = context.sleep(timeout) => {
+                false
+            },
+        }
+    }
+
+    /// Forget all views before `view` (releasing any uploads still waiting on them).
+    pub fn prune(&self, view: View) {
+        let mut seeds = self.seeds.lock().unwrap();
+        if view <= seeds.pruned {
+            return;
+        }
+        seeds.pruned = view;
+        seeds.entries.retain(|seeded, _| *seeded >= view);
+    }
+}
+
+/// Labels attached to latency metrics (and their exemplars).
+type Labels = Vec<(String, String)>;
+
+/// Latency histogram with exemplars.
+type Latency = HistogramWithExemplars<Labels>;
+
+/// Upload latencies, labeled by the kind of artifact uploaded.
+type UploadLatency = Family<Labels, Latency, fn() -> Latency>;
+
+/// Create a [Latency] histogram using [LATENCY_BUCKETS].
+fn latency() -> Latency {
+    HistogramWithExemplars::new(LATENCY_BUCKETS.into_iter())
+}
+
+/// Record `elapsed` in `histogram`, using `view` as the exemplar.
+fn observe(histogram: &Latency, elapsed: Duration, view: View) {
+    let exemplar = vec![("view".to_string(), view.to_string())];
+    histogram.observe(elapsed.as_secs_f64(), Some(exemplar), None);
+}
+
+/// Record the latency of an upload of `kind` that started at `start`.
+fn observe_upload(
+    latency: &UploadLatency,
+    context: &impl Clock,
+    kind: &str,
+    start: SystemTime,
+    view: View,
+) {
+    let elapsed = context.current().duration_since(start).unwrap_or_default();
+    let labels = vec![("kind".to_string(), kind.to_string())];
+    observe(&latency.get_or_create(&labels), elapsed, view);
+}
+
+/// Upload `artifact` (from `view`), holding it for retry in `spool` (if any) when the upload fails.
+async fn push<I: Indexer>(
+    context: &impl Clock,
+    indexer: &I,
+    latency: &UploadLatency,
+    failures: &Counter,
+    spool: Option<&spool::Mailbox>,
+    artifact: Artifact,
+    view: View,
+) {
+    let kind = artifact.kind().name();
+    let retry = spool.map(|_| artifact.clone());
+    let start = context.current();
+    let result = artifact.upload(indexer).await;
+    observe_upload(latency, context, kind, start, view);
+    if let Err(Failure { error, retryable }) = result {
+        failures.inc();
+        if !retryable {
+            warn!(?error, %view, "indexer rejected {kind}");
+            return;
+        }
+        warn!(?error, %view, "failed to upload {kind}");
+        if let (Some(spool), Some(artifact)) = (spool, retry) {
+            spool.push(artifact);
+        }
+        return;
+    }
+    debug!(%view, "{kind} uploaded to indexer");
 }
 
 /// An implementation of [Indexer] for the [Reporter] trait.
 #[derive(Clone)]
-pub struct Pusher<E: Spawner + Metrics, I: Indexer> {
+pub struct Pusher<E: Spawner + Metrics + Clock, I: Indexer> {
     context: E,
     indexer: I,
     marshal: marshal::Mailbox<Scheme, Block>,
+
+    notarized_at: Arc<Mutex<BTreeMap<View, SystemTime>>>,
+    sequencer: Sequencer,
+
+    spool: Option<spool::Mailbox>,
+
+    failures: Counter,
+    upload_latency: UploadLatency,
+    seed_to_finalization: Latency,
 }
 
-impl<E: Spawner + Metrics, I: Indexer> Pusher<E, I> {
+impl<E: Spawner + Metrics + Clock, I: Indexer> Pusher<E, I> {
     /// Create a new [Pusher].
     pub fn new(context: E, indexer: I, marshal: marshal::Mailbox<Scheme, Block>) -> Self {
+        let failures = Counter::default();
+        context.register(
+            "upload_failures",
+            "Number of failed uploads to the indexer",
+            failures.clone(),
+        );
+        let upload_latency = UploadLatency::new_with_constructor(latency);
+        context.register(
+            "upload_latency",
+            "Round-trip time (in seconds) of uploads to the indexer",
+            upload_latency.clone(),
+        );
+        let seed_to_finalization = latency();
+        context.register(
+            "seed_to_finalization",
+            "Time (in seconds) between a view's seed (notarization) and its finalization",
+            seed_to_finalization.clone(),
+        );
         Self {
             context,
             indexer,
             marshal,
+            notarized_at: Arc::new(Mutex::new(BTreeMap::new())),
+            sequencer: Sequencer::default(),
+            spool: None,
+            failures,
+            upload_latency,
+            seed_to_finalization,
         }
     }
+
+    /// Hold failed uploads for retry in a [spool::Spool].
+    pub fn with_spool(mut self, spool: spool::Mailbox) -> Self {
+        self.spool = Some(spool);
+        self
+    }
+
+    /// Get the [Counter] of failed uploads.
+    pub fn failures(&self) -> Counter {
+        self.failures.clone()
+    }
 }
 
-impl<E: Spawner + Metrics, I: Indexer> Reporter for Pusher<E, I> {
+impl<E: Spawner + Metrics + Clock, I: Indexer> Reporter for Pusher<E, I> {
     type Activity = Activity;
 
     async fn report(&mut self, activity: Self::Activity) {
         match activity {
             Activity::Notarization(notarization) => {
-                // Upload seed to indexer
+                // Record when the seed became available
                 let view = notarization.view();
-                self.context.with_label("notarized_seed").spawn({
-                    let indexer = self.indexer.clone();
-                    let seed = notarization.seed();
-                    move |_| async move {
-                        let result = indexer.seed_upload(seed).await;
-                        if let Err(e) = result {
-                            warn!(?e, "failed to upload seed");
-                            return;
+                let seeded = {
+                    let mut notarized_at = self.notarized_at.lock().unwrap();
+                    let seeded = notarized_at.contains_key(&view);
+                    notarized_at
+                        .entry(view)
+                        .or_insert_with(|| self.context.current());
+                    seeded
+                };
+
+                // Upload seed to indexer (unless already uploaded for an earlier notarization)
+                if !seeded {
+                    self.context.with_label("notarized_seed").spawn({
+                        let indexer = self.indexer.clone();
+                        let failures = self.failures.clone();
+                        let latency = self.upload_latency.clone();
+                        let spool = self.spool.clone();
+                        let sequencer = self.sequencer.clone();
+                        let seed = Artifact::Seed(notarization.seed());
+                        move |context| async move {
+                            push(
+                                &context,
+                                &indexer,
+                                &latency,
+                                &failures,
+                                spool.as_ref(),
+                                seed,
+                                view,
+                            )
+                            .await;
+                            sequencer.release(view);
                         }
-                        debug!(%view, "seed uploaded to indexer");
-                    }
-                });
+                    });
+                }
 
                 // Upload block to indexer (once we have it)
                 self.context.with_label("notarized_block").spawn({
                     let indexer = self.indexer.clone();
+                    let failures = self.failures.clone();
+                    let latency = self.upload_latency.clone();
+                    let spool = self.spool.clone();
+                    let sequencer = self.sequencer.clone();
                     let mut marshal = self.marshal.clone();
-                    move |_| async move {
+                    move |context| async move {
                         // Wait for block
                         let block = marshal
                             .subscribe(Some(notarization.round()), notarization.proposal.payload)
@@ -149,38 +1159,78 @@ impl<E: Spawner + Metrics, I: Indexer> Reporter for Pusher<E, I> {
                             return;
                         };
 
-                        // Upload to indexer once we have it
-                        let notarization = Notarized::new(notarization, block);
-                        let result = indexer.notarized_upload(notarization).await;
-                        if let Err(e) = result {
-                            warn!(?e, "failed to upload notarization");
-                            return;
+                        // Upload to indexer once we have it (and the seed was uploaded)
+                        if !sequencer.wait(&context, view, SEQUENCE_TIMEOUT).await {
+                            debug!(%view, "uploading notarization before seed");
                         }
-                        debug!(%view, "notarization uploaded to indexer");
+                        let notarization = Artifact::Notarized(Notarized::new(notarization, block));
+                        push(
+                            &context,
+                            &indexer,
+                            &latency,
+                            &failures,
+                            spool.as_ref(),
+                            notarization,
+                            view,
+                        )
+                        .await;
                     }
                 });
             }
             Activity::Finalization(finalization) => {
-                // Upload seed to indexer
+                // Record time since the seed became available (and forget older views)
                 let view = finalization.view();
-                self.context.with_label("finalized_seed").spawn({
-                    let indexer = self.indexer.clone();
-                    let seed = finalization.seed();
-                    move |_| async move {
-                        let result = indexer.seed_upload(seed).await;
-                        if let Err(e) = result {
-                            warn!(?e, "failed to upload seed");
-                            return;
-                        }
-                        debug!(%view, "seed uploaded to indexer");
+                let seeded = {
+                    let mut notarized_at = self.notarized_at.lock().unwrap();
+                    let start = notarized_at.get(&view).copied();
+                    if let Some(start) = start {
+                        let elapsed = self
+                            .context
+                            .current()
+                            .duration_since(start)
+                            .unwrap_or_default();
+                        observe(&self.seed_to_finalization, elapsed, view);
                     }
-                });
+                    notarized_at.retain(|notarized, _| *notarized > view);
+                    start.is_some()
+                };
+                self.sequencer.prune(view);
+
+                // Upload seed to indexer (unless it was already uploaded with the notarization,
+                // which the indexer would reject as a replay if signed in the same millisecond)
+                if !seeded {
+                    self.context.with_label("finalized_seed").spawn({
+                        let indexer = self.indexer.clone();
+                        let failures = self.failures.clone();
+                        let latency = self.upload_latency.clone();
+                        let spool = self.spool.clone();
+                        let sequencer = self.sequencer.clone();
+                        let seed = Artifact::Seed(finalization.seed());
+                        move |context| async move {
+                            push(
+                                &context,
+                                &indexer,
+                                &latency,
+                                &failures,
+                                spool.as_ref(),
+                                seed,
+                                view,
+                            )
+                            .await;
+                            sequencer.release(view);
+                        }
+                    });
+                }
 
                 // Upload block to indexer (once we have it)
                 self.context.with_label("finalized_block").spawn({
                     let indexer = self.indexer.clone();
+                    let failures = self.failures.clone();
+                    let latency = self.upload_latency.clone();
+                    let spool = self.spool.clone();
+                    let sequencer = self.sequencer.clone();
                     let mut marshal = self.marshal.clone();
-                    move |_| async move {
+                    move |context| async move {
                         let block = marshal
                             .subscribe(Some(finalization.round()), finalization.proposal.payload)
                             .await
@@ -190,14 +1240,44 @@ impl<E: Spawner + Metrics, I: Indexer> Reporter for Pusher<E, I> {
                             return;
                         };
 
-                        // Upload to indexer once we have it
-                        let finalization = Finalized::new(finalization, block);
-                        let result = indexer.finalized_upload(finalization).await;
-                        if let Err(e) = result {
-                            warn!(?e, "failed to upload finalization");
-                            return;
+                        // Upload to indexer once we have it (and the seed was uploaded)
+                        if !sequencer.wait(&context, view, SEQUENCE_TIMEOUT).await {
+                            debug!(%view, "uploading finalization before seed");
                         }
-                        debug!(%view, "finalization uploaded to indexer");
+                        let finalization = Artifact::Finalized(Finalized::new(finalization, block));
+                        push(
+                            &context,
+                            &indexer,
+                            &latency,
+                            &failures,
+                            spool.as_ref(),
+                            finalization,
+                            view,
+                        )
+                        .await;
+                    }
+                });
+            }
+            Activity::Nullification(nullification) => {
+                // Upload nullification to indexer
+                let view = nullification.view();
+                self.context.with_label("nullification").spawn({
+                    let indexer = self.indexer.clone();
+                    let failures = self.failures.clone();
+                    let latency = self.upload_latency.clone();
+                    let spool = self.spool.clone();
+                    let nullification = Artifact::Nullification(nullification);
+                    move |context| async move {
+                        push(
+                            &context,
+                            &indexer,
+                            &latency,
+                            &failures,
+                            spool.as_ref(),
+                            nullification,
+                            view,
+                        )
+                        .await;
                     }
                 });
             }
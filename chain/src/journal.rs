@@ -0,0 +1,161 @@
+//! Inspection of the consensus journal.
+//!
+//! Consensus journals every vote and certificate it verifies (sectioned by view) so that it can
+//! recover its state after a restart. [read] replays that journal outside of the engine and
+//! summarizes each view as a [Record], so operators can see what a validator saw (and when it
+//! gave up on a view) during a postmortem.
+//!
+//! Timers are not journaled (consensus re-arms them on restart), so they cannot be recovered.
+//! A [Record] with nullifies but no notarize votes is the trace of a leader timeout.
+
+use crate::engine;
+use commonware_consensus::{
+    simplex::types::{Artifact, Attributable},
+    types::Epoch,
+    Viewable,
+};
+use commonware_cryptography::sha256::Digest;
+use commonware_runtime::{Metrics, Storage};
+use commonware_utils::hex;
+use futures::{pin_mut, StreamExt};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+use thiserror::Error;
+
+/// Errors that can occur when reading the consensus journal.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("journal error: {0}")]
+    Journal(#[from] commonware_storage::journal::Error),
+}
+
+impl From<Error> for alto_types::Error {
+    fn from(error: Error) -> Self {
+        Self::Io(std::io::Error::other(error))
+    }
+}
+
+/// Everything journaled in a single view.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Record {
+    /// View of the record.
+    pub view: u64,
+
+    /// Notarize votes (by signer) and the payload each voted for.
+    pub notarizes: BTreeMap<u32, Digest>,
+
+    /// Payload of the notarization (if one was recovered).
+    pub notarization: Option<Digest>,
+
+    /// Whether the notarized proposal was certified (if certification completed).
+    pub certified: Option<bool>,
+
+    /// Signers of nullify votes.
+    pub nullifies: BTreeSet<u32>,
+
+    /// Whether a nullification was recovered.
+    pub nullification: bool,
+
+    /// Finalize votes (by signer) and the payload each voted for.
+    pub finalizes: BTreeMap<u32, Digest>,
+
+    /// Payload of the finalization (if one was recovered).
+    pub finalization: Option<Digest>,
+}
+
+impl Record {
+    fn new(view: u64) -> Self {
+        Self {
+            view,
+            ..Default::default()
+        }
+    }
+
+    /// Add a journaled artifact to the record.
+    fn apply(&mut self, artifact: Artifact<alto_types::Scheme, Digest>) {
+        match artifact {
+            Artifact::Notarize(notarize) => {
+                self.notarizes
+                    .insert(notarize.signer().get(), notarize.proposal.payload);
+            }
+            Artifact::Notarization(notarization) => {
+                self.notarization = Some(notarization.proposal.payload);
+            }
+            Artifact::Certification(_, success) => {
+                self.certified = Some(success);
+            }
+            Artifact::Nullify(nullify) => {
+                self.nullifies.insert(nullify.signer().get());
+            }
+            Artifact::Nullification(_) => {
+                self.nullification = true;
+            }
+            Artifact::Finalize(finalize) => {
+                self.finalizes
+                    .insert(finalize.signer().get(), finalize.proposal.payload);
+            }
+            Artifact::Finalization(finalization) => {
+                self.finalization = Some(finalization.proposal.payload);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let signers = |signers: &mut dyn Iterator<Item = &u32>| {
+            signers.map(u32::to_string).collect::<Vec<_>>().join(",")
+        };
+        let payload = |payload: &Option<Digest>| payload.map_or("-".to_string(), |d| hex(&d));
+        write!(
+            f,
+            "view={} notarizes=[{}] notarization={} certified={} nullifies=[{}] nullification={} finalizes=[{}] finalization={}",
+            self.view,
+            signers(&mut self.notarizes.keys()),
+            payload(&self.notarization),
+            self.certified.map_or("-".to_string(), |c| c.to_string()),
+            signers(&mut self.nullifies.iter()),
+            self.nullification,
+            signers(&mut self.finalizes.keys()),
+            payload(&self.finalization),
+        )
+    }
+}
+
+/// Read the consensus journal of `epoch` stored under `partition_prefix`, summarizing every view
+/// at or after `from` (in increasing order), with the [engine::Tuning] the validator used.
+///
+/// The journal is replayed (and may be repaired) like consensus does at startup, so the
+/// validator using it must be stopped first.
+pub async fn read<E: Storage + Metrics>(
+    context: E,
+    partition_prefix: &str,
+    epoch: Epoch,
+    tuning: &engine::Tuning,
+    from: u64,
+) -> Result<Vec<Record>, Error> {
+    let journal = engine::init_consensus_journal(
+        context,
+        partition_prefix,
+        epoch,
+        engine::buffer_pool(tuning),
+        tuning,
+    )
+    .await?;
+    let mut records: BTreeMap<u64, Record> = BTreeMap::new();
+    {
+        let stream = journal.replay(from, 0, tuning.replay_buffer).await?;
+        pin_mut!(stream);
+        while let Some(result) = stream.next().await {
+            let (_, _, _, artifact) = result?;
+            let view = artifact.view().get();
+            records
+                .entry(view)
+                .or_insert_with(|| Record::new(view))
+                .apply(artifact);
+        }
+    }
+    Ok(records.into_values().collect())
+}
@@ -0,0 +1,392 @@
+//! Import and export of validator keys in standard formats.
+//!
+//! Validator configs store keys as hex-encoded [commonware_codec] values, which other tooling
+//! (HSMs, key management services, staking CLIs) cannot produce. This module converts the
+//! ed25519 validator key to and from PKCS#8 PEM ([RFC 8410](https://www.rfc-editor.org/rfc/rfc8410))
+//! and the BLS12-381 share to and from an
+//! [EIP-2335](https://eips.ethereum.org/EIPS/eip-2335) keystore (password-encrypted JSON).
+//!
+//! EIP-2335 keystores only hold a secret, so the index of the share in the public polynomial is
+//! stored in an additional `index` field (and can be provided separately when importing a
+//! keystore generated elsewhere). The `pubkey` of a keystore may be encoded in either G2 (as
+//! exported here, matching [MinSig]) or G1 (as exported by Ethereum tooling).
+
+use commonware_codec::{DecodeExt, Encode};
+use commonware_cryptography::{
+    bls12381::primitives::{
+        group::{Private, Share},
+        variant::{MinPk, MinSig},
+    },
+    ed25519,
+};
+use commonware_utils::{from_hex, hex, Participant};
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use pkcs8::{
+    der::{asn1::OctetStringRef, pem::PemLabel, Decode, Encode as _},
+    AlgorithmIdentifierRef, LineEnding, ObjectIdentifier, PrivateKeyInfo, SecretDocument,
+};
+use rand::{CryptoRng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+
+/// Object identifier of Ed25519 keys (RFC 8410).
+const ED25519_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+
+/// Version of EIP-2335 keystores.
+const KEYSTORE_VERSION: u32 = 4;
+
+/// Length of the key derived from the keystore password.
+const DERIVED_KEY_LENGTH: usize = 32;
+
+/// AES-128 in counter mode (with a big-endian counter), as used by EIP-2335.
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// Errors that can occur when importing keys.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid PEM: {0}")]
+    Pem(#[from] pkcs8::der::Error),
+    #[error("invalid PKCS#8: {0}")]
+    Pkcs8(#[from] pkcs8::Error),
+    #[error("unsupported key algorithm: {0}")]
+    UnsupportedAlgorithm(ObjectIdentifier),
+    #[error("invalid keystore: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("unsupported keystore version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("unsupported keystore function: {0}")]
+    UnsupportedFunction(String),
+    #[error("invalid keystore parameter: {0}")]
+    InvalidParameter(&'static str),
+    #[error("invalid password")]
+    InvalidPassword,
+    #[error("missing share index")]
+    MissingIndex,
+    #[error("invalid key")]
+    InvalidKey,
+    #[error("public key does not match the decrypted share")]
+    PublicKeyMismatch,
+}
+
+/// Encode an ed25519 private key as PKCS#8 PEM.
+pub fn ed25519_to_pem(key: &ed25519::PrivateKey) -> String {
+    let seed = key.encode();
+    let seed = OctetStringRef::new(&seed)
+        .and_then(|seed| seed.to_der())
+        .expect("failed to encode seed");
+    let info = PrivateKeyInfo::new(
+        AlgorithmIdentifierRef {
+            oid: ED25519_OID,
+            parameters: None,
+        },
+        &seed,
+    );
+    SecretDocument::encode_msg(&info)
+        .and_then(|document| document.to_pem(PrivateKeyInfo::PEM_LABEL, LineEnding::LF))
+        .expect("failed to encode PEM")
+        .to_string()
+}
+
+/// Decode an ed25519 private key from PKCS#8 PEM.
+pub fn ed25519_from_pem(pem: &str) -> Result<ed25519::PrivateKey, Error> {
+    let (label, document) = SecretDocument::from_pem(pem)?;
+    PrivateKeyInfo::validate_pem_label(label).map_err(pkcs8::der::Error::from)?;
+    let info = PrivateKeyInfo::try_from(document.as_bytes())?;
+    if info.algorithm.oid != ED25519_OID {
+        return Err(Error::UnsupportedAlgorithm(info.algorithm.oid));
+    }
+    let seed = OctetStringRef::from_der(info.private_key)?;
+    ed25519::PrivateKey::decode(seed.as_bytes()).map_err(|_| Error::InvalidKey)
+}
+
+/// Key derivation function used to encrypt a keystore.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    /// scrypt with cost `n` (a power of two), `r = 8`, and `p = 1`.
+    Scrypt { n: u32 },
+
+    /// PBKDF2 (HMAC-SHA256) with `c` iterations.
+    Pbkdf2 { c: u32 },
+}
+
+impl Default for Kdf {
+    /// The parameters recommended by EIP-2335.
+    fn default() -> Self {
+        Self::Scrypt { n: 262_144 }
+    }
+}
+
+/// An EIP-2335 keystore.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub crypto: Crypto,
+    #[serde(default)]
+    pub description: String,
+    pub pubkey: String,
+    #[serde(default)]
+    pub path: String,
+    pub uuid: String,
+    pub version: u32,
+
+    /// Index of the share in the public polynomial (not part of EIP-2335).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u32>,
+}
+
+/// The encrypted secret of a [Keystore].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Crypto {
+    pub kdf: Module<KdfParams>,
+    pub checksum: Module<serde_json::Map<String, serde_json::Value>>,
+    pub cipher: Module<CipherParams>,
+}
+
+/// A step in the decryption of a [Keystore].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Module<P> {
+    pub function: String,
+    pub params: P,
+    pub message: String,
+}
+
+/// Parameters of the key derivation function.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KdfParams {
+    Scrypt {
+        dklen: usize,
+        n: u32,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        dklen: usize,
+        c: u32,
+        prf: String,
+        salt: String,
+    },
+}
+
+/// Parameters of the cipher.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+/// Normalize a password as required by EIP-2335 (NFKD, without control codes).
+fn normalize(password: &str) -> Vec<u8> {
+    password
+        .nfkd()
+        .filter(|c| !matches!(*c as u32, 0x00..=0x1F | 0x7F..=0x9F))
+        .collect::<String>()
+        .into_bytes()
+}
+
+/// Derive the decryption key from `password`.
+fn derive(params: &KdfParams, password: &str) -> Result<[u8; DERIVED_KEY_LENGTH], Error> {
+    let password = normalize(password);
+    let mut key = [0u8; DERIVED_KEY_LENGTH];
+    match params {
+        KdfParams::Scrypt {
+            dklen,
+            n,
+            r,
+            p,
+            salt,
+        } => {
+            if *dklen != DERIVED_KEY_LENGTH {
+                return Err(Error::InvalidParameter("dklen"));
+            }
+            if !n.is_power_of_two() {
+                return Err(Error::InvalidParameter("n"));
+            }
+            let salt = from_hex(salt).ok_or(Error::InvalidParameter("salt"))?;
+            let params = scrypt::Params::new(n.trailing_zeros() as u8, *r, *p, *dklen)
+                .map_err(|_| Error::InvalidParameter("scrypt"))?;
+            scrypt::scrypt(&password, &salt, &params, &mut key)
+                .map_err(|_| Error::InvalidParameter("dklen"))?;
+        }
+        KdfParams::Pbkdf2 {
+            dklen,
+            c,
+            prf,
+            salt,
+        } => {
+            if *dklen != DERIVED_KEY_LENGTH {
+                return Err(Error::InvalidParameter("dklen"));
+            }
+            if prf != "hmac-sha256" {
+                return Err(Error::UnsupportedFunction(prf.clone()));
+            }
+            let salt = from_hex(salt).ok_or(Error::InvalidParameter("salt"))?;
+            pbkdf2::pbkdf2_hmac::<Sha256>(&password, &salt, *c, &mut key);
+        }
+    }
+    Ok(key)
+}
+
+/// Compute the checksum of `cipher_message` under the derived `key`.
+fn checksum(key: &[u8; DERIVED_KEY_LENGTH], cipher_message: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(&key[16..]);
+    hasher.update(cipher_message);
+    hasher.finalize().to_vec()
+}
+
+/// Apply the keystore cipher to `message` (encryption and decryption are the same operation).
+fn apply_cipher(
+    key: &[u8; DERIVED_KEY_LENGTH],
+    iv: &[u8],
+    message: &mut [u8],
+) -> Result<(), Error> {
+    let mut cipher =
+        Aes128Ctr::new_from_slices(&key[..16], iv).map_err(|_| Error::InvalidParameter("iv"))?;
+    cipher.apply_keystream(message);
+    Ok(())
+}
+
+impl Keystore {
+    /// Encrypt `secret` with `password`.
+    fn encrypt(
+        rng: &mut (impl RngCore + CryptoRng),
+        secret: &[u8],
+        password: &str,
+        kdf: Kdf,
+    ) -> Crypto {
+        let mut salt = [0u8; 32];
+        rng.fill_bytes(&mut salt);
+        let mut iv = [0u8; 16];
+        rng.fill_bytes(&mut iv);
+        let (function, params) = match kdf {
+            Kdf::Scrypt { n } => (
+                "scrypt",
+                KdfParams::Scrypt {
+                    dklen: DERIVED_KEY_LENGTH,
+                    n,
+                    r: 8,
+                    p: 1,
+                    salt: hex(&salt),
+                },
+            ),
+            Kdf::Pbkdf2 { c } => (
+                "pbkdf2",
+                KdfParams::Pbkdf2 {
+                    dklen: DERIVED_KEY_LENGTH,
+                    c,
+                    prf: "hmac-sha256".to_string(),
+                    salt: hex(&salt),
+                },
+            ),
+        };
+        let key = derive(&params, password).expect("invalid KDF parameters");
+        let mut message = secret.to_vec();
+        apply_cipher(&key, &iv, &mut message).expect("invalid IV");
+        Crypto {
+            kdf: Module {
+                function: function.to_string(),
+                params,
+                message: String::new(),
+            },
+            checksum: Module {
+                function: "sha256".to_string(),
+                params: Default::default(),
+                message: hex(&checksum(&key, &message)),
+            },
+            cipher: Module {
+                function: "aes-128-ctr".to_string(),
+                params: CipherParams { iv: hex(&iv) },
+                message: hex(&message),
+            },
+        }
+    }
+
+    /// Decrypt the secret with `password`.
+    fn decrypt(&self, password: &str) -> Result<Vec<u8>, Error> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(Error::UnsupportedVersion(self.version));
+        }
+        let Crypto {
+            kdf,
+            checksum: expected,
+            cipher,
+        } = &self.crypto;
+        match (kdf.function.as_str(), &kdf.params) {
+            ("scrypt", KdfParams::Scrypt { .. }) | ("pbkdf2", KdfParams::Pbkdf2 { .. }) => {}
+            (function, _) => return Err(Error::UnsupportedFunction(function.to_string())),
+        }
+        if expected.function != "sha256" {
+            return Err(Error::UnsupportedFunction(expected.function.clone()));
+        }
+        if cipher.function != "aes-128-ctr" {
+            return Err(Error::UnsupportedFunction(cipher.function.clone()));
+        }
+
+        // Ensure the password is correct before decrypting
+        let key = derive(&kdf.params, password)?;
+        let mut message = from_hex(&cipher.message).ok_or(Error::InvalidParameter("message"))?;
+        let expected = from_hex(&expected.message).ok_or(Error::InvalidParameter("checksum"))?;
+        if !bool::from(checksum(&key, &message).ct_eq(&expected)) {
+            return Err(Error::InvalidPassword);
+        }
+        let iv = from_hex(&cipher.params.iv).ok_or(Error::InvalidParameter("iv"))?;
+        apply_cipher(&key, &iv, &mut message)?;
+        Ok(message)
+    }
+
+    /// Encrypt a BLS12-381 `share` with `password`.
+    pub fn from_share(
+        rng: &mut (impl RngCore + CryptoRng),
+        share: &Share,
+        password: &str,
+        kdf: Kdf,
+    ) -> Self {
+        let secret = share.private.encode();
+        Self {
+            crypto: Self::encrypt(rng, &secret, password, kdf),
+            description: format!("alto share {}", share.index),
+            pubkey: hex(&share.public::<MinSig>().encode()),
+            path: String::new(),
+            uuid: uuid::Builder::from_random_bytes(rng.gen())
+                .into_uuid()
+                .to_string(),
+            version: KEYSTORE_VERSION,
+            index: Some(share.index.get()),
+        }
+    }
+
+    /// Decrypt the BLS12-381 share with `password`.
+    ///
+    /// If `index` is provided, it overrides the index stored in the keystore (required for
+    /// keystores generated by other tooling).
+    pub fn to_share(&self, password: &str, index: Option<u32>) -> Result<Share, Error> {
+        let index = index.or(self.index).ok_or(Error::MissingIndex)?;
+        let secret = self.decrypt(password)?;
+        let private = Private::decode(secret.as_ref()).map_err(|_| Error::InvalidKey)?;
+        let share = Share::new(Participant::new(index), private);
+
+        // Ensure the share matches the public key (if provided)
+        if !self.pubkey.is_empty() {
+            let pubkey = from_hex(&self.pubkey).ok_or(Error::InvalidParameter("pubkey"))?;
+            let g2 = share.public::<MinSig>().encode();
+            let g1 = share.public::<MinPk>().encode();
+            if pubkey != g2.as_ref() && pubkey != g1.as_ref() {
+                return Err(Error::PublicKeyMismatch);
+            }
+        }
+        Ok(share)
+    }
+
+    /// Parse a keystore from JSON.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serialize the keystore to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("failed to serialize keystore")
+    }
+}
@@ -0,0 +1,671 @@
+//! Pending transactions.
+//!
+//! Transactions are submitted over a dedicated p2p channel (by participants and any observers
+//! allowed to use it) or, if a `port` is configured, with `POST /tx` (by clients, usually through
+//! an indexer that forwards them). The [Actor] decodes each one and adds it to a [Pool] that the
+//! [crate::application::Application] draws from (in arrival order) when it proposes. Every
+//! transaction added to the pool is broadcast to all peers on the same channel (so transactions
+//! submitted to one participant reach every proposer); peers that already hold it drop the copy
+//! without broadcasting it again. When a block
+//! is finalized, the transactions it includes are removed from the pool (and remembered for a
+//! while, so late copies are not included again).
+//!
+//! Applications that interpret transactions can install a [Validator] (with
+//! [Actor::new_with_validator]). Transactions that fail it are not added to the pool, and blocks
+//! that include one are rejected during verification (so every participant must use the same,
+//! deterministic [Validator]).
+//!
+//! Proposed transactions stay in the pool (marked in-flight, so they are not proposed again)
+//! until a block at the height they were proposed at is finalized. If that block does not include
+//! them (the proposal was not finalized), they can be proposed again.
+
+use alto_types::{Transaction, MAX_ENCODED_TRANSACTION_SIZE};
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, State},
+    http::StatusCode,
+    routing::post,
+    Router,
+};
+use bytes::Buf;
+use commonware_codec::{DecodeExt, Encode, EncodeSize};
+use commonware_cryptography::{ed25519::PublicKey, sha256::Digest, Digestible};
+use commonware_macros::select;
+use commonware_p2p::{Receiver, Recipients, Sender};
+use commonware_runtime::{Handle, Metrics, Spawner};
+use futures::{channel::mpsc, StreamExt};
+use prometheus_client::metrics::{counter::Counter, gauge::Gauge};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+use tokio::net::TcpListener;
+use tracing::{debug, info, warn};
+
+/// Default maximum size (in bytes) of all pending transactions.
+pub const DEFAULT_MAX_SIZE: usize = 64 * 1024 * 1024;
+
+/// Default number of included transactions to remember.
+pub const DEFAULT_INCLUDED: usize = 65_536;
+
+/// Configuration for the mempool.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// Maximum size (in bytes) of all pending transactions (defaults to [DEFAULT_MAX_SIZE]).
+    /// Transactions received while the pool is full are dropped.
+    pub max_size: Option<usize>,
+
+    /// Number of included transactions to remember (defaults to [DEFAULT_INCLUDED]).
+    pub included: Option<usize>,
+
+    /// Port to accept encoded transactions on with `POST /tx` (if any).
+    pub port: Option<u16>,
+}
+
+/// Application-defined check every transaction must pass to be added to a [Pool] (and to be
+/// included in a verified block).
+pub type Validator = Arc<dyn Fn(&Transaction) -> bool + Send + Sync>;
+
+/// Why a transaction was not added to the [Pool].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejected {
+    /// The transaction is already pending.
+    Duplicate,
+    /// The transaction was recently included in a finalized block.
+    Included,
+    /// Adding the transaction would exceed the maximum size of the pool.
+    Full,
+    /// The transaction failed the pool's [Validator].
+    Invalid,
+}
+
+struct Inner {
+    max_size: usize,
+    size: usize,
+    pending: VecDeque<Transaction>,
+    digests: HashSet<Digest>,
+    in_flight: HashMap<Digest, u64>,
+
+    capacity: usize,
+    included: HashSet<Digest>,
+    order: VecDeque<Digest>,
+}
+
+/// Transactions waiting to be proposed (shared between the [Actor] and the
+/// [crate::application::Application]).
+///
+/// The default pool is empty until transactions are inserted.
+#[derive(Clone)]
+pub struct Pool {
+    inner: Arc<Mutex<Inner>>,
+    pending: Gauge,
+    validator: Option<Validator>,
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Self::new(Config::default(), None)
+    }
+}
+
+impl Pool {
+    fn new(cfg: Config, validator: Option<Validator>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                max_size: cfg.max_size.unwrap_or(DEFAULT_MAX_SIZE),
+                size: 0,
+                pending: VecDeque::new(),
+                digests: HashSet::new(),
+                in_flight: HashMap::new(),
+                capacity: cfg.included.unwrap_or(DEFAULT_INCLUDED),
+                included: HashSet::new(),
+                order: VecDeque::new(),
+            })),
+            pending: Gauge::default(),
+            validator,
+        }
+    }
+
+    /// Create a new (empty) pool that only accepts transactions that pass `validator`.
+    pub fn new_with_validator(cfg: Config, validator: Validator) -> Self {
+        Self::new(cfg, Some(validator))
+    }
+
+    /// Returns true if `transaction` passes the pool's [Validator] (or none is installed).
+    pub fn valid(&self, transaction: &Transaction) -> bool {
+        self.validator
+            .as_ref()
+            .is_none_or(|validator| validator(transaction))
+    }
+
+    /// Add `transaction` to the pool.
+    pub fn insert(&self, transaction: Transaction) -> Result<(), Rejected> {
+        if !self.valid(&transaction) {
+            return Err(Rejected::Invalid);
+        }
+        let mut inner = self.inner.lock().unwrap();
+        let digest = transaction.digest();
+        if inner.digests.contains(&digest) {
+            return Err(Rejected::Duplicate);
+        }
+        if inner.included.contains(&digest) {
+            return Err(Rejected::Included);
+        }
+        let size = transaction.encode_size();
+        if inner.size + size > inner.max_size {
+            return Err(Rejected::Full);
+        }
+        inner.size += size;
+        inner.digests.insert(digest);
+        inner.pending.push_back(transaction);
+        self.pending.set(inner.pending.len() as i64);
+        Ok(())
+    }
+
+    /// Select up to `max_count` pending transactions (in arrival order) with a total encoded size
+    /// of at most `max_size` bytes to propose at `height`, skipping those in `exclude` (already
+    /// included by an ancestor of the proposal).
+    ///
+    /// Transactions that do not fit are skipped. Selected transactions stay in the pool (marked
+    /// in-flight, so they are not selected again) until a block at `height` is finalized.
+    pub fn take(
+        &self,
+        height: u64,
+        max_count: usize,
+        max_size: usize,
+        exclude: &HashSet<Digest>,
+    ) -> Vec<Transaction> {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        let mut taken = Vec::new();
+        let mut remaining = max_size;
+        for transaction in &inner.pending {
+            if taken.len() >= max_count {
+                break;
+            }
+            let digest = transaction.digest();
+            if inner.in_flight.contains_key(&digest) || exclude.contains(&digest) {
+                continue;
+            }
+            let size = transaction.encode_size();
+            if size > remaining {
+                continue;
+            }
+            remaining -= size;
+            inner.in_flight.insert(digest, height);
+            taken.push(transaction.clone());
+        }
+        taken
+    }
+
+    /// Remove `transactions` (included in the finalized block at `height`) from the pool and
+    /// remember them.
+    ///
+    /// Transactions proposed at or below `height` that the block does not include are no longer
+    /// in-flight (and can be proposed again).
+    pub fn finalized(&self, height: u64, transactions: &[Transaction]) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.in_flight.retain(|_, proposed| *proposed > height);
+        if transactions.is_empty() {
+            return;
+        }
+        let mut removed = HashSet::new();
+        for transaction in transactions {
+            let digest = transaction.digest();
+            inner.in_flight.remove(&digest);
+            if inner.digests.remove(&digest) {
+                removed.insert(digest);
+            }
+            if inner.included.insert(digest) {
+                inner.order.push_back(digest);
+            }
+        }
+        while inner.order.len() > inner.capacity {
+            let oldest = inner.order.pop_front().unwrap();
+            inner.included.remove(&oldest);
+        }
+        if !removed.is_empty() {
+            let mut size = 0;
+            inner.pending.retain(|transaction| {
+                let retain = !removed.contains(&transaction.digest());
+                if !retain {
+                    size += transaction.encode_size();
+                }
+                retain
+            });
+            inner.size -= size;
+        }
+        self.pending.set(inner.pending.len() as i64);
+    }
+
+    /// Returns true if `digest` was recently included in a finalized block.
+    pub fn included(&self, digest: &Digest) -> bool {
+        self.inner.lock().unwrap().included.contains(digest)
+    }
+
+    /// Number of pending transactions (including in-flight ones).
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().pending.len()
+    }
+
+    /// Returns true if no transactions are pending.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Adds transactions to a [Pool] (counting the outcome and queueing added transactions to be
+/// broadcast).
+#[derive(Clone)]
+struct Intake {
+    pool: Pool,
+    gossip: mpsc::UnboundedSender<Transaction>,
+
+    accepted: Counter,
+    rejected: Counter,
+    invalid: Counter,
+}
+
+impl Intake {
+    /// Decode `message` and add it to the pool (received from `source`).
+    fn add(&self, message: impl Buf, source: impl Display) -> Result<(), Option<Rejected>> {
+        let Ok(transaction) = Transaction::decode(message) else {
+            self.invalid.inc();
+            return Err(None);
+        };
+        let digest = transaction.digest();
+        match self.pool.insert(transaction.clone()) {
+            Ok(()) => {
+                self.accepted.inc();
+                debug!(?digest, %source, "accepted transaction");
+                let _ = self.gossip.unbounded_send(transaction);
+                Ok(())
+            }
+            Err(Rejected::Invalid) => {
+                self.invalid.inc();
+                debug!(?digest, %source, "invalid transaction");
+                Err(Some(Rejected::Invalid))
+            }
+            Err(reason) => {
+                self.rejected.inc();
+                debug!(?digest, %source, ?reason, "rejected transaction");
+                Err(Some(reason))
+            }
+        }
+    }
+}
+
+/// Adds transactions received over p2p (and HTTP, if configured) to a [Pool].
+pub struct Actor<E: Spawner + Metrics> {
+    context: E,
+    port: Option<u16>,
+    intake: Intake,
+    gossip: mpsc::UnboundedReceiver<Transaction>,
+
+    gossiped: Counter,
+}
+
+impl<E: Spawner + Metrics> Actor<E> {
+    /// Create a new [Actor] and the [Pool] it adds transactions to.
+    pub fn new(context: E, cfg: Config) -> (Self, Pool) {
+        Self::init(context, cfg, Pool::new(cfg, None))
+    }
+
+    /// Create a new [Actor] and the [Pool] it adds transactions to (only accepting transactions
+    /// that pass `validator`).
+    pub fn new_with_validator(context: E, cfg: Config, validator: Validator) -> (Self, Pool) {
+        Self::init(context, cfg, Pool::new_with_validator(cfg, validator))
+    }
+
+    fn init(context: E, cfg: Config, pool: Pool) -> (Self, Pool) {
+        context.register(
+            "pending",
+            "Number of transactions waiting to be finalized (including in-flight ones)",
+            pool.pending.clone(),
+        );
+        let accepted = Counter::default();
+        context.register(
+            "accepted",
+            "Number of transactions added to the pool",
+            accepted.clone(),
+        );
+        let rejected = Counter::default();
+        context.register(
+            "rejected",
+            "Number of transactions dropped because they were duplicates, already included, or the pool was full",
+            rejected.clone(),
+        );
+        let invalid = Counter::default();
+        context.register(
+            "invalid",
+            "Number of messages that were not (valid) transactions",
+            invalid.clone(),
+        );
+        let gossiped = Counter::default();
+        context.register(
+            "gossiped",
+            "Number of transactions broadcast to peers",
+            gossiped.clone(),
+        );
+        let (sender, gossip) = mpsc::unbounded();
+        (
+            Self {
+                context,
+                port: cfg.port,
+                intake: Intake {
+                    pool: pool.clone(),
+                    gossip: sender,
+                    accepted,
+                    rejected,
+                    invalid,
+                },
+                gossip,
+                gossiped,
+            },
+            pool,
+        )
+    }
+
+    /// Start adding transactions received on the transactions channel (and over HTTP, if
+    /// configured) to the pool, broadcasting each one added on the same channel.
+    pub fn start(
+        self,
+        transactions: (
+            impl Sender<PublicKey = PublicKey>,
+            impl Receiver<PublicKey = PublicKey>,
+        ),
+    ) -> Handle<()> {
+        if let Some(port) = self.port {
+            let intake = self.intake.clone();
+            self.context
+                .with_label("server")
+                .spawn(move |_| serve(port, intake));
+        }
+        self.context.clone().spawn(|_| self.run(transactions))
+    }
+
+    async fn run(
+        mut self,
+        (mut sender, mut receiver): (
+            impl Sender<PublicKey = PublicKey>,
+            impl Receiver<PublicKey = PublicKey>,
+        ),
+    ) {
+        loop {
+            select! {
+                message = receiver.recv() => {
+                    let Ok((peer, message)) = message else {
+                        warn!("transactions channel closed");
+                        return;
+                    };
+                    let _ = self.intake.add(message, peer);
+                },
+                transaction = self.gossip.next() => {
+                    let Some(transaction) = transaction else {
+                        return;
+                    };
+
+                    // Peers that already hold the transaction drop it (without broadcasting it)
+                    let digest = transaction.digest();
+                    match sender.send(Recipients::All, transaction.encode(), false).await {
+                        Ok(sent) => {
+                            self.gossiped.inc();
+                            debug!(?digest, recipients = sent.len(), "broadcast transaction");
+                        }
+                        Err(e) => warn!(?digest, ?e, "failed to broadcast transaction"),
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Accept transactions with `POST /tx` on `port`.
+async fn serve(port: u16, intake: Intake) {
+    let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port);
+    let listener = match TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!(?e, port, "failed to bind transaction server");
+            return;
+        }
+    };
+    info!(port, "accepting transactions");
+    let router = Router::new()
+        .route(
+            "/tx",
+            post(submit).layer(DefaultBodyLimit::max(MAX_ENCODED_TRANSACTION_SIZE)),
+        )
+        .with_state(intake);
+    if let Err(e) = axum::serve(listener, router).await {
+        warn!(?e, "transaction server failed");
+    }
+}
+
+/// Add a transaction to the pool (`202` if added, `200` if already pending or included, `400`
+/// if not a (valid) transaction, and `503` if the pool is full).
+async fn submit(State(intake): State<Intake>, body: Bytes) -> StatusCode {
+    match intake.add(body, "http") {
+        Ok(()) => StatusCode::ACCEPTED,
+        Err(Some(Rejected::Duplicate | Rejected::Included)) => StatusCode::OK,
+        Err(Some(Rejected::Full)) => StatusCode::SERVICE_UNAVAILABLE,
+        Err(Some(Rejected::Invalid) | None) => StatusCode::BAD_REQUEST,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::QUOTA;
+    use commonware_cryptography::{ed25519::PrivateKey, Signer};
+    use commonware_p2p::{
+        simulated::{self, Link, Network},
+        Manager,
+    };
+    use commonware_runtime::{deterministic, Clock, Runner};
+    use commonware_utils::ordered::Set;
+    use std::time::Duration;
+
+    fn transaction(data: &[u8]) -> Transaction {
+        Transaction::new(data.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_pool() {
+        let pool = Pool::new(
+            Config {
+                max_size: Some(32),
+                included: Some(2),
+                port: None,
+            },
+            None,
+        );
+
+        // Transactions are deduplicated (and bounded by size)
+        let (a, b, c) = (transaction(b"a"), transaction(b"b"), transaction(&[0; 16]));
+        pool.insert(a.clone()).unwrap();
+        assert_eq!(pool.insert(a.clone()), Err(Rejected::Duplicate));
+        pool.insert(c.clone()).unwrap();
+        pool.insert(b.clone()).unwrap();
+        assert_eq!(pool.insert(transaction(&[1; 16])), Err(Rejected::Full));
+        assert_eq!(pool.len(), 3);
+
+        // Transactions are taken in arrival order (skipping those that do not fit)
+        let none = HashSet::new();
+        assert_eq!(pool.take(1, 8, 4, &none), vec![a.clone(), b.clone()]);
+
+        // Taken transactions stay in the pool (in-flight) but are not taken again
+        assert_eq!(pool.len(), 3);
+        assert_eq!(pool.insert(a.clone()), Err(Rejected::Duplicate));
+        assert_eq!(pool.take(1, 8, 1024, &none), vec![c.clone()]);
+        assert!(pool.take(1, 8, 1024, &none).is_empty());
+
+        // Finalized transactions are removed (and those proposed at or below the finalized
+        // height that were not included can be taken again)
+        pool.finalized(1, std::slice::from_ref(&a));
+        assert_eq!(pool.len(), 2);
+        assert!(pool.included(&a.digest()));
+        assert_eq!(pool.insert(a.clone()), Err(Rejected::Included));
+
+        // Transactions included by an ancestor of the proposal are skipped
+        let exclude = HashSet::from([c.digest()]);
+        assert_eq!(pool.take(2, 8, 1024, &exclude), vec![b.clone()]);
+
+        // Only recent inclusions are remembered
+        pool.finalized(2, &[b.clone(), c.clone()]);
+        assert!(pool.is_empty());
+        assert!(!pool.included(&a.digest()));
+        assert_eq!(pool.insert(b), Err(Rejected::Included));
+        pool.insert(a.clone()).unwrap();
+        assert_eq!(pool.take(3, 1, 1024, &none), vec![a]);
+    }
+
+    #[test]
+    fn test_submit() {
+        let (gossip, mut queued) = mpsc::unbounded();
+        let intake = Intake {
+            pool: Pool::new(
+                Config {
+                    max_size: Some(16),
+                    included: None,
+                    port: None,
+                },
+                None,
+            ),
+            gossip,
+            accepted: Counter::default(),
+            rejected: Counter::default(),
+            invalid: Counter::default(),
+        };
+        let submit = |body: Vec<u8>| {
+            futures::executor::block_on(submit(State(intake.clone()), Bytes::from(body)))
+        };
+
+        // New transactions are accepted (and resubmissions are not errors)
+        let encoded = transaction(b"a").encode().to_vec();
+        assert_eq!(submit(encoded.clone()), StatusCode::ACCEPTED);
+        assert_eq!(submit(encoded), StatusCode::OK);
+        assert_eq!(intake.pool.len(), 1);
+
+        // Malformed transactions (and those that do not fit) are rejected
+        assert_eq!(submit(vec![5, 0]), StatusCode::BAD_REQUEST);
+        let large = transaction(&[0; 16]).encode().to_vec();
+        assert_eq!(submit(large), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(intake.accepted.get(), 1);
+        assert_eq!(intake.rejected.get(), 2);
+        assert_eq!(intake.invalid.get(), 1);
+
+        // Only the accepted transaction is queued for broadcast
+        assert_eq!(queued.try_next().unwrap(), Some(transaction(b"a")));
+        assert!(queued.try_next().is_err());
+    }
+
+    #[test]
+    fn test_gossip() {
+        let executor = deterministic::Runner::default();
+        executor.start(|context| async move {
+            let (network, oracle) = Network::new(
+                context.with_label("network"),
+                simulated::Config {
+                    max_size: 1024 * 1024,
+                    disconnect_on_block: true,
+                    tracked_peer_sets: Some(1),
+                },
+            );
+            network.start();
+
+            // Two participants run a mempool (and a client can reach both)
+            let keys: Vec<_> = (0..3)
+                .map(|seed| PrivateKey::from_seed(seed).public_key())
+                .collect();
+            oracle
+                .manager()
+                .update(0, Set::from_iter_dedup(keys.clone()))
+                .await;
+            let link = Link {
+                latency: Duration::from_millis(10),
+                jitter: Duration::ZERO,
+                success_rate: 1.0,
+            };
+            for a in &keys {
+                for b in &keys {
+                    if a != b {
+                        oracle
+                            .add_link(a.clone(), b.clone(), link.clone())
+                            .await
+                            .unwrap();
+                    }
+                }
+            }
+            let mut pools = Vec::new();
+            for (label, key) in ["a", "b"].into_iter().zip(&keys) {
+                let channel = oracle
+                    .control(key.clone())
+                    .register(5, QUOTA)
+                    .await
+                    .unwrap();
+                let (actor, pool) = Actor::new(context.with_label(label), Config::default());
+                actor.start(channel);
+                pools.push(pool);
+            }
+            let (mut client, _) = oracle
+                .control(keys[2].clone())
+                .register(5, QUOTA)
+                .await
+                .unwrap();
+
+            // A transaction sent to one participant reaches the other
+            let transaction = transaction(b"a");
+            client
+                .send(
+                    Recipients::One(keys[0].clone()),
+                    transaction.encode(),
+                    false,
+                )
+                .await
+                .unwrap();
+            context.sleep(Duration::from_secs(1)).await;
+            let none = HashSet::new();
+            assert_eq!(pools[0].take(1, 8, 1024, &none), vec![transaction.clone()]);
+            assert_eq!(pools[1].take(1, 8, 1024, &none), vec![transaction]);
+
+            // Each participant broadcasts it once (and drops copies it already holds)
+            let metrics = context.encode();
+            assert!(metrics.contains("a_gossiped_total 1"));
+            assert!(metrics.contains("b_gossiped_total 1"));
+            assert!(metrics.contains("a_rejected_total 1"));
+        });
+    }
+
+    #[test]
+    fn test_validator() {
+        // Only transactions that pass the validator are added
+        let validator: Validator = Arc::new(|transaction| transaction.data().len() == 1);
+        let pool = Pool::new_with_validator(Config::default(), validator);
+        assert!(pool.valid(&transaction(b"a")));
+        assert!(!pool.valid(&transaction(b"ab")));
+        pool.insert(transaction(b"a")).unwrap();
+        assert_eq!(pool.insert(transaction(b"ab")), Err(Rejected::Invalid));
+        assert_eq!(pool.len(), 1);
+
+        // Invalid submissions are rejected as malformed
+        let intake = Intake {
+            pool,
+            gossip: mpsc::unbounded().0,
+            accepted: Counter::default(),
+            rejected: Counter::default(),
+            invalid: Counter::default(),
+        };
+        let body = Bytes::from(transaction(b"ab").encode().to_vec());
+        let status = futures::executor::block_on(submit(State(intake.clone()), body));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(intake.invalid.get(), 1);
+        assert_eq!(intake.rejected.get(), 0);
+
+        // Pools without a validator accept any transaction
+        assert!(Pool::default().valid(&transaction(b"ab")));
+    }
+}
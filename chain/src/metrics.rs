@@ -0,0 +1,207 @@
+//! Structured metric labels.
+//!
+//! The runtime names metrics after the labels of the context that registered them (e.g.
+//! `engine_marshal_processed_height`), and contexts created for each validator in a shared
+//! process (like the [crate::simulation]) add a `validator_<public key>_` prefix. Aggregating a
+//! metric across validators or subsystems then requires matching on metric names.
+//!
+//! [relabel] rewrites encoded metrics so every sample carries a `subsystem` label (the first
+//! component of its name, like `engine` or `network`) and an `instance` label (the validator's
+//! public key), with any `validator_<public key>_` prefix moved into `instance`. The [Server]
+//! exposes the relabeled metrics of a validator.
+
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Router};
+use commonware_runtime::{Metrics, Spawner};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+/// Prefix of metrics registered by a validator in a shared process.
+const VALIDATOR_PREFIX: &str = "validator_";
+
+/// Split `name` into the instance it was registered by (if prefixed) and its unprefixed name.
+fn split_instance(name: &str) -> (Option<&str>, &str) {
+    let Some(rest) = name.strip_prefix(VALIDATOR_PREFIX) else {
+        return (None, name);
+    };
+    match rest.split_once('_') {
+        Some((instance, name)) => (Some(instance), name),
+        None => (None, name),
+    }
+}
+
+/// A metric family (and its samples) in encoded output.
+#[derive(Default)]
+struct Family {
+    metadata: Vec<String>,
+    samples: Vec<String>,
+}
+
+/// Rewrite encoded (OpenMetrics) `metrics` so every sample is labeled with its `subsystem` and
+/// `instance` (see the [module documentation](self)).
+///
+/// Samples without a `validator_<public key>_` prefix are attributed to `instance`. Families
+/// registered by several instances are merged (so each appears once in the output).
+pub fn relabel(metrics: &str, instance: &str) -> String {
+    let mut order = Vec::new();
+    let mut families: HashMap<String, Family> = HashMap::new();
+    let mut current = String::new();
+    let mut eof = false;
+    for line in metrics.lines() {
+        if line == "# EOF" {
+            eof = true;
+            continue;
+        }
+
+        // Metadata starts a new family
+        if let Some(rest) = line.strip_prefix("# ") {
+            let mut parts = rest.splitn(3, ' ');
+            let (Some(kind), Some(name)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let (_, name) = split_instance(name);
+            if name != current {
+                current = name.to_string();
+                if !families.contains_key(name) {
+                    order.push(name.to_string());
+                }
+            }
+            let family = families.entry(current.clone()).or_default();
+            let line = match parts.next() {
+                Some(text) => format!("# {kind} {name} {text}"),
+                None => format!("# {kind} {name}"),
+            };
+            if !family.metadata.contains(&line) {
+                family.metadata.push(line);
+            }
+            continue;
+        }
+
+        // Move the instance prefix into labels
+        let end = line.find(['{', ' ']).unwrap_or(line.len());
+        let (name, rest) = line.split_at(end);
+        let (prefix, name) = split_instance(name);
+        let subsystem = name.split('_').next().unwrap_or(name);
+        let labels = format!(
+            "subsystem=\"{subsystem}\",instance=\"{}\"",
+            prefix.unwrap_or(instance)
+        );
+        let sample = match rest.strip_prefix('{') {
+            Some(rest) if rest.starts_with('}') => format!("{name}{{{labels}{rest}"),
+            Some(rest) => format!("{name}{{{labels},{rest}"),
+            None => format!("{name}{{{labels}}}{rest}"),
+        };
+        if !families.contains_key(&current) {
+            order.push(current.clone());
+        }
+        families
+            .entry(current.clone())
+            .or_default()
+            .samples
+            .push(sample);
+    }
+
+    // Emit each family once
+    let mut output = String::with_capacity(metrics.len());
+    for name in order {
+        let family = &families[&name];
+        for line in family.metadata.iter().chain(&family.samples) {
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+    if eof {
+        output.push_str("# EOF\n");
+    }
+    output
+}
+
+/// Serves the [relabel]ed metrics of a validator.
+pub struct Server<E: Spawner + Metrics> {
+    context: E,
+    port: u16,
+    instance: String,
+}
+
+impl<E: Spawner + Metrics> Server<E> {
+    /// Create a new [Server] that labels metrics with `instance` (and serves them on `port`).
+    pub fn new(context: E, port: u16, instance: String) -> Self {
+        Self {
+            context,
+            port,
+            instance,
+        }
+    }
+
+    /// Start the [Server].
+    pub fn start(self) -> commonware_runtime::Handle<()> {
+        self.context.clone().spawn(|_| self.run())
+    }
+
+    async fn run(self) {
+        // Like the runtime's metrics server, avoid the runtime's listener (so scrapes are not
+        // counted as network bandwidth)
+        let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), self.port);
+        let listener = match TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!(?e, port = self.port, "failed to bind metrics server");
+                return;
+            }
+        };
+        info!(port = self.port, "serving metrics");
+        let router = Router::new()
+            .route("/metrics", get(encode::<E>))
+            .with_state((self.context, self.instance));
+        if let Err(e) = axum::serve(listener, router).await {
+            warn!(?e, "metrics server failed");
+        }
+    }
+}
+
+/// Encode the [relabel]ed metrics.
+async fn encode<E: Metrics>(State((context, instance)): State<(E, String)>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        relabel(&context.encode(), &instance),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relabel() {
+        let metrics = "\
+# HELP validator_aa_engine_processed Processed height.
+# TYPE validator_aa_engine_processed gauge
+validator_aa_engine_processed 3
+# HELP validator_bb_engine_processed Processed height.
+# TYPE validator_bb_engine_processed gauge
+validator_bb_engine_processed 4
+# HELP network_messages Messages received.
+# TYPE network_messages counter
+network_messages_total{peer=\"cc\"} 7
+# TYPE runtime_tasks gauge
+runtime_tasks{} 2
+# EOF
+";
+        let expected = "\
+# HELP engine_processed Processed height.
+# TYPE engine_processed gauge
+engine_processed{subsystem=\"engine\",instance=\"aa\"} 3
+engine_processed{subsystem=\"engine\",instance=\"bb\"} 4
+# HELP network_messages Messages received.
+# TYPE network_messages counter
+network_messages_total{subsystem=\"network\",instance=\"local\",peer=\"cc\"} 7
+# TYPE runtime_tasks gauge
+runtime_tasks{subsystem=\"runtime\",instance=\"local\"} 2
+# EOF
+";
+        assert_eq!(relabel(metrics, "local"), expected);
+    }
+}
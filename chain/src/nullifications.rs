@@ -0,0 +1,135 @@
+//! Persistence of nullifications.
+//!
+//! Consensus only retains nullifications until their view is pruned (and marshal only stores
+//! finalized data), so skipped views cannot be explained after the fact. The [Store] observes
+//! consensus activity and persists every nullification in a prunable archive (indexed by view)
+//! that survives restarts.
+//!
+//! Views restart at each epoch, so a nullification from a later epoch is not persisted if a
+//! nullification for the same view was persisted in an earlier one.
+//!
+//! Nullifications are written without waiting for them to be durable and synced periodically
+//! (so persisting them never delays other reporters).
+
+use alto_types::{Activity, Nullification};
+use commonware_consensus::{Reporter, Viewable};
+use commonware_runtime::{Clock, Handle, Metrics, Spawner, Storage};
+use commonware_storage::{
+    archive::{prunable, Archive as _, Identifier},
+    translator::EightCap,
+};
+use commonware_utils::sequence::U64;
+use futures::lock::Mutex;
+use prometheus_client::metrics::counter::Counter;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tracing::{debug, error};
+
+/// Number of views (behind the latest nullification) to retain nullifications for.
+pub const RETENTION: u64 = 1_000_000;
+
+/// How often to sync persisted nullifications to disk.
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Archive of nullifications (indexed and keyed by view).
+pub type Archive<E> = prunable::Archive<EightCap, E, U64, Nullification>;
+
+/// Persists nullifications reported by consensus.
+#[derive(Clone)]
+pub struct Store<E: Storage + Metrics> {
+    context: E,
+    archive: Arc<Mutex<Archive<E>>>,
+    dirty: Arc<AtomicBool>,
+
+    persisted: Counter,
+}
+
+impl<E: Storage + Metrics> Store<E> {
+    /// Create a new [Store] backed by `archive`.
+    pub fn new(context: E, archive: Archive<E>) -> Self {
+        let persisted = Counter::default();
+        context.register(
+            "persisted",
+            "Number of nullifications persisted",
+            persisted.clone(),
+        );
+        Self {
+            context,
+            archive: Arc::new(Mutex::new(archive)),
+            dirty: Arc::new(AtomicBool::new(false)),
+            persisted,
+        }
+    }
+
+    /// Sync nullifications persisted since the last sync to disk.
+    pub async fn sync(&self) {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let mut archive = self.archive.lock().await;
+        if let Err(e) = archive.sync().await {
+            error!(?e, "failed to sync nullifications");
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Get the persisted nullification for `view` (if any).
+    pub async fn get(&self, view: u64) -> Option<Nullification> {
+        let archive = self.archive.lock().await;
+        match archive.get(Identifier::Index(view)).await {
+            Ok(nullification) => nullification,
+            Err(e) => {
+                error!(?e, view, "failed to read nullification");
+                None
+            }
+        }
+    }
+}
+
+impl<E: Storage + Metrics + Clock + Spawner> Store<E> {
+    /// Start syncing persisted nullifications every [SYNC_INTERVAL].
+    pub fn start(&self) -> Handle<()> {
+        let store = self.clone();
+        self.context
+            .with_label("sync")
+            .spawn(move |context| async move {
+                loop {
+                    context.sleep(SYNC_INTERVAL).await;
+                    store.sync().await;
+                }
+            })
+    }
+}
+
+impl<E: Storage + Metrics> Reporter for Store<E> {
+    type Activity = Activity;
+
+    async fn report(&mut self, activity: Self::Activity) {
+        let Activity::Nullification(nullification) = activity else {
+            return;
+        };
+        let view = nullification.view().get();
+
+        // Persist the nullification (ignored if already stored)
+        let mut archive = self.archive.lock().await;
+        if let Err(e) = archive.put(view, U64::new(view), nullification).await {
+            error!(?e, view, "failed to persist nullification");
+            return;
+        }
+        self.dirty.store(true, Ordering::Relaxed);
+        self.persisted.inc();
+        debug!(view, "persisted nullification");
+
+        // Prune nullifications outside of the retention window
+        if let Some(min) = view.checked_sub(RETENTION) {
+            if let Err(e) = archive.prune(min).await {
+                error!(?e, min, "failed to prune nullifications");
+            }
+        }
+    }
+}
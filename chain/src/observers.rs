@@ -0,0 +1,242 @@
+//! Access control for observers.
+//!
+//! Only participants are allowed to connect to a validator by default. To support nodes that
+//! follow the chain without participating (like public sync nodes), a validator can also allow
+//! a list of observer keys to connect, restricted to a subset of [Channel]s. Messages observers
+//! send on any other channel are dropped (and counted) before they reach the engine, so
+//! observers cannot consume resources (like backfill) a validator does not want to serve them.
+
+use commonware_codec::DecodeExt;
+use commonware_cryptography::ed25519::PublicKey;
+use commonware_p2p::{Message, Receiver};
+use commonware_runtime::Metrics;
+use commonware_utils::{from_hex_formatted, ordered::Set};
+use prometheus_client::metrics::{counter::Counter, family::Family};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt, sync::Arc};
+use thiserror::Error;
+
+/// A p2p channel observers may be allowed to use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    /// Consensus votes.
+    Pending,
+    /// Consensus certificates.
+    Recovered,
+    /// Requests for missing consensus certificates.
+    Resolver,
+    /// Block dissemination.
+    Broadcaster,
+    /// Backfill of finalized blocks and certificates.
+    Marshal,
+    /// Transaction submission (see [crate::mempool]).
+    Transactions,
+}
+
+impl Channel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Recovered => "recovered",
+            Self::Resolver => "resolver",
+            Self::Broadcaster => "broadcaster",
+            Self::Marshal => "marshal",
+            Self::Transactions => "transactions",
+        }
+    }
+}
+
+/// Configuration for observers.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// Hex-encoded public keys of observers allowed to connect.
+    pub keys: Vec<String>,
+
+    /// Channels observers may send messages on (all others are dropped).
+    pub channels: Vec<Channel>,
+}
+
+/// Errors that can occur when configuring observers.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("invalid observer key: {0}")]
+    InvalidKey(String),
+}
+
+/// Access control list for participants and observers.
+#[derive(Clone)]
+pub struct Acl {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    participants: Set<PublicKey>,
+    observers: HashSet<PublicKey>,
+    channels: HashSet<Channel>,
+
+    dropped: Family<Vec<(String, String)>, Counter>,
+}
+
+impl Acl {
+    /// Create a new [Acl] allowing `participants` on every channel and the observers in `cfg`
+    /// (if any) on the configured channels.
+    pub fn new(
+        context: &impl Metrics,
+        participants: Set<PublicKey>,
+        cfg: Option<&Config>,
+    ) -> Result<Self, Error> {
+        let cfg = cfg.cloned().unwrap_or_default();
+        let mut observers = HashSet::new();
+        for key in &cfg.keys {
+            let observer = from_hex_formatted(key)
+                .and_then(|raw| PublicKey::decode(raw.as_ref()).ok())
+                .ok_or_else(|| Error::InvalidKey(key.clone()))?;
+            if participants.position(&observer).is_none() {
+                observers.insert(observer);
+            }
+        }
+        let dropped = Family::default();
+        context.register(
+            "dropped",
+            "Number of messages dropped from observers (by channel)",
+            dropped.clone(),
+        );
+        Ok(Self {
+            inner: Arc::new(Inner {
+                participants,
+                observers,
+                channels: cfg.channels.into_iter().collect(),
+                dropped,
+            }),
+        })
+    }
+
+    /// Get the set of peers allowed to connect (participants and observers).
+    pub fn peers(&self) -> Set<PublicKey> {
+        let peers = self.inner.participants.iter().cloned();
+        Set::from_iter_dedup(peers.chain(self.inner.observers.iter().cloned()))
+    }
+
+    /// Returns whether `peer` may send messages on `channel`.
+    pub fn allowed(&self, channel: Channel, peer: &PublicKey) -> bool {
+        if self.inner.participants.position(peer).is_some() {
+            return true;
+        }
+        self.inner.observers.contains(peer) && self.inner.channels.contains(&channel)
+    }
+
+    /// Drop messages received on `channel` from peers not allowed to use it.
+    pub fn filter<R: Receiver<PublicKey = PublicKey>>(
+        &self,
+        channel: Channel,
+        receiver: R,
+    ) -> Filtered<R> {
+        Filtered {
+            inner: receiver,
+            acl: self.clone(),
+            channel,
+        }
+    }
+}
+
+/// A [Receiver] that drops messages from peers the [Acl] does not allow on its channel.
+pub struct Filtered<R> {
+    inner: R,
+    acl: Acl,
+    channel: Channel,
+}
+
+impl<R: fmt::Debug> fmt::Debug for Filtered<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Filtered")
+            .field("inner", &self.inner)
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+impl<R: Receiver<PublicKey = PublicKey>> Receiver for Filtered<R> {
+    type Error = R::Error;
+    type PublicKey = PublicKey;
+
+    async fn recv(&mut self) -> Result<Message<Self::PublicKey>, Self::Error> {
+        loop {
+            let (peer, message) = self.inner.recv().await?;
+            if self.acl.allowed(self.channel, &peer) {
+                return Ok((peer, message));
+            }
+            let label = vec![("channel".to_string(), self.channel.as_str().to_string())];
+            self.acl.inner.dropped.get_or_create(&label).inc();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use commonware_cryptography::{ed25519::PrivateKey, Signer};
+    use commonware_runtime::{deterministic, Runner};
+    use std::collections::VecDeque;
+
+    /// A [Receiver] that replays a fixed list of messages.
+    #[derive(Debug)]
+    struct Replay(VecDeque<Message<PublicKey>>);
+
+    impl Receiver for Replay {
+        type Error = std::io::Error;
+        type PublicKey = PublicKey;
+
+        async fn recv(&mut self) -> Result<Message<PublicKey>, Self::Error> {
+            self.0
+                .pop_front()
+                .ok_or_else(|| std::io::ErrorKind::UnexpectedEof.into())
+        }
+    }
+
+    #[test]
+    fn test_acl() {
+        let executor = deterministic::Runner::default();
+        executor.start(|context| async move {
+            let participant = PrivateKey::from_seed(0).public_key();
+            let observer = PrivateKey::from_seed(1).public_key();
+            let stranger = PrivateKey::from_seed(2).public_key();
+            let cfg = Config {
+                keys: vec![observer.to_string()],
+                channels: vec![Channel::Marshal],
+            };
+            let participants = Set::from_iter_dedup([participant.clone()]);
+            let acl = Acl::new(&context, participants, Some(&cfg)).unwrap();
+
+            // Observers may connect (but only use allowed channels)
+            assert_eq!(acl.peers().len(), 2);
+            assert!(acl.allowed(Channel::Pending, &participant));
+            assert!(acl.allowed(Channel::Marshal, &observer));
+            assert!(!acl.allowed(Channel::Pending, &observer));
+            assert!(!acl.allowed(Channel::Marshal, &stranger));
+
+            // Messages from disallowed peers are dropped
+            let messages = [&observer, &stranger, &participant]
+                .into_iter()
+                .map(|peer| (peer.clone(), Bytes::from_static(b"hello")))
+                .collect();
+            let mut filtered = acl.filter(Channel::Pending, Replay(messages));
+            let (peer, _) = filtered.recv().await.unwrap();
+            assert_eq!(peer, participant);
+            assert!(filtered.recv().await.is_err());
+            assert!(context
+                .encode()
+                .contains("dropped_total{channel=\"pending\"} 2"));
+
+            // Invalid keys are rejected
+            let cfg = Config {
+                keys: vec!["zz".to_string()],
+                channels: Vec::new(),
+            };
+            let participants = Set::from_iter_dedup([participant]);
+            let result = Acl::new(&context.with_label("invalid"), participants, Some(&cfg));
+            assert_eq!(result.err(), Some(Error::InvalidKey("zz".to_string())));
+        });
+    }
+}
@@ -0,0 +1,249 @@
+//! Launch profiles.
+//!
+//! The engine defaults are tuned for validators spread across regions, where a local network
+//! would wait seconds for a leader that answers in milliseconds. A [Profile] selects a bundle of
+//! [Parameters] (timeouts, fetch limits, freezer sizes, and rate limits) suited to where the
+//! validator runs. The [Timeouts] of a chain spec (if provided) take precedence over the bundle,
+//! and any [Overrides] set in the validator config take precedence over both.
+
+use crate::engine::{self, Fetch};
+use alto_types::Timeouts;
+use governor::Quota;
+use serde::{Deserialize, Serialize};
+use std::{fmt, num::NonZeroU32, str::FromStr, time::Duration};
+
+/// Default number of messages per second a single peer may send on each consensus channel
+/// (pending, recovered, and resolver).
+pub const DEFAULT_CONSENSUS_RATE_PER_PEER: u32 = 128;
+
+/// A bundle of pre-tuned [Parameters].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    /// Validators on a single machine (or LAN).
+    Dev,
+
+    /// A short-lived, geo-distributed network.
+    Testnet,
+
+    /// A long-lived, geo-distributed network (the engine defaults).
+    #[default]
+    Mainnet,
+}
+
+impl Profile {
+    /// Get the [Parameters] of the profile.
+    pub fn parameters(&self) -> Parameters {
+        let mainnet = Parameters {
+            leader_timeout: engine::DEFAULT_LEADER_TIMEOUT,
+            notarization_timeout: engine::DEFAULT_NOTARIZATION_TIMEOUT,
+            nullify_retry: engine::DEFAULT_NULLIFY_RETRY,
+            fetch: Fetch::default(),
+            freezer_table_initial_size: engine::DEFAULT_FREEZER_TABLE_INITIAL_SIZE,
+            consensus_rate_per_peer: DEFAULT_CONSENSUS_RATE_PER_PEER,
+        };
+        match self {
+            Self::Dev => Parameters {
+                leader_timeout: Duration::from_millis(250),
+                notarization_timeout: Duration::from_millis(500),
+                nullify_retry: Duration::from_secs(2),
+                fetch: Fetch {
+                    timeout: Duration::from_millis(500),
+                    rate_per_peer: per_second(1_024),
+                    ..Fetch::default()
+                },
+                freezer_table_initial_size: 2u32.pow(12),
+                consensus_rate_per_peer: 1_024,
+            },
+            Self::Testnet => Parameters {
+                freezer_table_initial_size: 2u32.pow(16),
+                ..mainnet
+            },
+            Self::Mainnet => mainnet,
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dev => write!(f, "dev"),
+            Self::Testnet => write!(f, "testnet"),
+            Self::Mainnet => write!(f, "mainnet"),
+        }
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dev" => Ok(Self::Dev),
+            "testnet" => Ok(Self::Testnet),
+            "mainnet" => Ok(Self::Mainnet),
+            _ => Err(format!("unknown profile: {s}")),
+        }
+    }
+}
+
+/// Parameters selected by a [Profile].
+#[derive(Clone, Copy, Debug)]
+pub struct Parameters {
+    /// Time to wait for a leader to propose.
+    pub leader_timeout: Duration,
+
+    /// Time to wait for a proposal to be notarized.
+    pub notarization_timeout: Duration,
+
+    /// Time to wait before rebroadcasting a nullify vote.
+    pub nullify_retry: Duration,
+
+    /// Limits on fetching missing consensus artifacts from peers.
+    pub fetch: Fetch,
+
+    /// Initial size of the blocks and finalizations freezer tables.
+    pub freezer_table_initial_size: u32,
+
+    /// Messages per second a single peer may send on each consensus channel.
+    pub consensus_rate_per_peer: u32,
+}
+
+impl Parameters {
+    /// Get the consensus timeouts of the parameters.
+    pub fn timeouts(&self) -> Timeouts {
+        Timeouts {
+            leader_timeout: self.leader_timeout.as_millis() as u64,
+            notarization_timeout: self.notarization_timeout.as_millis() as u64,
+            nullify_retry: self.nullify_retry.as_millis() as u64,
+        }
+    }
+
+    /// Replace the consensus timeouts with `timeouts`.
+    pub fn with_timeouts(mut self, timeouts: &Timeouts) -> Self {
+        self.leader_timeout = Duration::from_millis(timeouts.leader_timeout);
+        self.notarization_timeout = Duration::from_millis(timeouts.notarization_timeout);
+        self.nullify_retry = Duration::from_millis(timeouts.nullify_retry);
+        self
+    }
+
+    /// Replace any parameter set in `overrides`.
+    pub fn with_overrides(mut self, overrides: &Overrides) -> Self {
+        if let Some(ms) = overrides.leader_timeout {
+            self.leader_timeout = Duration::from_millis(ms);
+        }
+        if let Some(ms) = overrides.notarization_timeout {
+            self.notarization_timeout = Duration::from_millis(ms);
+        }
+        if let Some(ms) = overrides.nullify_retry {
+            self.nullify_retry = Duration::from_millis(ms);
+        }
+        if let Some(ms) = overrides.fetch_timeout {
+            self.fetch.timeout = Duration::from_millis(ms);
+        }
+        if let Some(count) = overrides.max_fetch_count {
+            self.fetch.max_count = count;
+        }
+        if let Some(size) = overrides.max_fetch_size {
+            self.fetch.max_size = size;
+        }
+        if let Some(rate) = overrides.fetch_rate_per_peer {
+            self.fetch.rate_per_peer = per_second(rate);
+        }
+        if let Some(size) = overrides.freezer_table_initial_size {
+            self.freezer_table_initial_size = size;
+        }
+        if let Some(rate) = overrides.consensus_rate_per_peer {
+            self.consensus_rate_per_peer = rate;
+        }
+        self
+    }
+}
+
+/// Explicit values that take precedence over those of the selected [Profile].
+///
+/// Durations are in milliseconds and rates are per second.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Overrides {
+    pub leader_timeout: Option<u64>,
+    pub notarization_timeout: Option<u64>,
+    pub nullify_retry: Option<u64>,
+    pub fetch_timeout: Option<u64>,
+    pub max_fetch_count: Option<usize>,
+    pub max_fetch_size: Option<usize>,
+    pub fetch_rate_per_peer: Option<u32>,
+    pub freezer_table_initial_size: Option<u32>,
+    pub consensus_rate_per_peer: Option<u32>,
+}
+
+/// A [Quota] of `rate` per second (at least one).
+fn per_second(rate: u32) -> Quota {
+    Quota::per_second(NonZeroU32::new(rate).unwrap_or(NonZeroU32::MIN))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles() {
+        // Mainnet uses the engine defaults
+        let mainnet = Profile::default().parameters();
+        assert_eq!(mainnet.leader_timeout, engine::DEFAULT_LEADER_TIMEOUT);
+        assert_eq!(
+            mainnet.freezer_table_initial_size,
+            engine::DEFAULT_FREEZER_TABLE_INITIAL_SIZE
+        );
+
+        // Dev is tuned for local networks
+        let dev = Profile::Dev.parameters();
+        assert!(dev.leader_timeout < mainnet.leader_timeout);
+        assert!(dev.notarization_timeout < mainnet.notarization_timeout);
+        assert!(dev.fetch.timeout < mainnet.fetch.timeout);
+        assert!(dev.freezer_table_initial_size < mainnet.freezer_table_initial_size);
+        assert!(dev.leader_timeout <= dev.notarization_timeout);
+
+        // Names round-trip
+        for profile in [Profile::Dev, Profile::Testnet, Profile::Mainnet] {
+            assert_eq!(profile.to_string().parse::<Profile>().unwrap(), profile);
+        }
+        assert!("local".parse::<Profile>().is_err());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let overrides: Overrides =
+            serde_yaml::from_str("leader_timeout: 100\nmax_fetch_count: 4").unwrap();
+        let parameters = Profile::Dev.parameters().with_overrides(&overrides);
+
+        // Explicit values take precedence
+        assert_eq!(parameters.leader_timeout, Duration::from_millis(100));
+        assert_eq!(parameters.fetch.max_count, 4);
+
+        // Everything else comes from the profile
+        let dev = Profile::Dev.parameters();
+        assert_eq!(parameters.notarization_timeout, dev.notarization_timeout);
+        assert_eq!(parameters.fetch.timeout, dev.fetch.timeout);
+        assert_eq!(
+            parameters.freezer_table_initial_size,
+            dev.freezer_table_initial_size
+        );
+    }
+
+    #[test]
+    fn test_timeouts() {
+        // Timeouts round-trip
+        let dev = Profile::Dev.parameters();
+        let mainnet = Profile::Mainnet.parameters().with_timeouts(&dev.timeouts());
+        assert_eq!(mainnet.leader_timeout, dev.leader_timeout);
+        assert_eq!(mainnet.notarization_timeout, dev.notarization_timeout);
+        assert_eq!(mainnet.nullify_retry, dev.nullify_retry);
+
+        // Only timeouts are replaced
+        assert_eq!(
+            mainnet.freezer_table_initial_size,
+            engine::DEFAULT_FREEZER_TABLE_INITIAL_SIZE
+        );
+    }
+}
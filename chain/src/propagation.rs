@@ -0,0 +1,303 @@
+//! Block propagation metrics.
+//!
+//! Blocks are disseminated by the buffered broadcast engine independently of consensus, so a
+//! validator can vote on time while its blocks reach peers slowly (or vice versa). The [Tracker]
+//! wraps the broadcast channel to count blocks sent, received, and received more than once. It
+//! also observes consensus activity to measure the time between broadcasting a block and the
+//! first notarize vote for it from a peer (an acknowledgement that the peer received and
+//! verified the block).
+//!
+//! Because block and certificate sizes drive p2p rate limits (and indexer bandwidth), the
+//! [Tracker] also records the encoded size of each block (the first time it is sent or
+//! received) and of each notarization and finalization certificate.
+
+use alto_types::{Activity, Block, DigestMode, LATENCY_BUCKETS, SIZE_BUCKETS};
+use bytes::{Buf, Bytes};
+use commonware_codec::{Decode, EncodeSize};
+use commonware_consensus::{simplex::types::Attributable, Reporter};
+use commonware_cryptography::{ed25519::PublicKey, sha256::Digest, Digestible};
+use commonware_p2p::{CheckedSender, LimitedSender, Message, Receiver, Recipients};
+use commonware_runtime::{Clock, Metrics};
+use commonware_utils::Participant;
+use prometheus_client::metrics::{counter::Counter, histogram::Histogram};
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+/// Number of recent blocks to remember (for duplicate detection and acknowledgements).
+const WINDOW: usize = 256;
+
+#[derive(Default)]
+struct State {
+    seen: HashSet<Digest>,
+    order: VecDeque<Digest>,
+    broadcasts: VecDeque<(Digest, SystemTime)>,
+}
+
+impl State {
+    /// Remember `digest`, returning false if it was already seen.
+    fn see(&mut self, digest: Digest) -> bool {
+        if !self.seen.insert(digest) {
+            return false;
+        }
+        self.order.push_back(digest);
+        if self.order.len() > WINDOW {
+            let oldest = self.order.pop_front().unwrap();
+            self.seen.remove(&oldest);
+        }
+        true
+    }
+}
+
+/// Tracks block propagation over the broadcast channel.
+#[derive(Clone)]
+pub struct Tracker<E: Clock> {
+    context: E,
+    me: Option<Participant>,
+    digest_mode: DigestMode,
+    state: Arc<Mutex<State>>,
+
+    broadcasts: Counter,
+    received: Counter,
+    duplicates: Counter,
+    first_ack: Histogram,
+    block_size: Histogram,
+    notarization_size: Histogram,
+    finalization_size: Histogram,
+}
+
+impl<E: Clock + Metrics> Tracker<E> {
+    /// Create a new [Tracker] for the participant at index `me` (if any), decoding blocks
+    /// using `digest_mode`.
+    pub fn new(context: E, me: Option<Participant>, digest_mode: DigestMode) -> Self {
+        let broadcasts = Counter::default();
+        context.register(
+            "broadcasts",
+            "Number of blocks broadcast to at least one peer",
+            broadcasts.clone(),
+        );
+        let received = Counter::default();
+        context.register(
+            "received",
+            "Number of blocks received from peers",
+            received.clone(),
+        );
+        let duplicates = Counter::default();
+        context.register(
+            "duplicates",
+            "Number of blocks received from peers that were already seen",
+            duplicates.clone(),
+        );
+        let first_ack = Histogram::new(LATENCY_BUCKETS);
+        context.register(
+            "first_ack",
+            "Time (in seconds) between broadcasting a block and the first peer notarizing it",
+            first_ack.clone(),
+        );
+        let block_size = Histogram::new(SIZE_BUCKETS);
+        context.register(
+            "block_size",
+            "Encoded size (in bytes) of each block sent or received",
+            block_size.clone(),
+        );
+        let notarization_size = Histogram::new(SIZE_BUCKETS);
+        context.register(
+            "notarization_size",
+            "Encoded size (in bytes) of each notarization certificate",
+            notarization_size.clone(),
+        );
+        let finalization_size = Histogram::new(SIZE_BUCKETS);
+        context.register(
+            "finalization_size",
+            "Encoded size (in bytes) of each finalization certificate",
+            finalization_size.clone(),
+        );
+        Self {
+            context,
+            me,
+            digest_mode,
+            state: Arc::new(Mutex::new(State::default())),
+            broadcasts,
+            received,
+            duplicates,
+            first_ack,
+            block_size,
+            notarization_size,
+            finalization_size,
+        }
+    }
+}
+
+impl<E: Clock> Tracker<E> {
+    /// Wrap the broadcast channel so that blocks sent and received are tracked.
+    pub fn wrap<S, R>(
+        &self,
+        (sender, receiver): (S, R),
+    ) -> (TrackedSender<S, E>, TrackedReceiver<R, E>)
+    where
+        S: LimitedSender<PublicKey = PublicKey>,
+        R: Receiver<PublicKey = PublicKey>,
+    {
+        (
+            TrackedSender {
+                inner: sender,
+                tracker: self.clone(),
+            },
+            TrackedReceiver {
+                inner: receiver,
+                tracker: self.clone(),
+            },
+        )
+    }
+
+    /// Record a block (of `size` bytes) broadcast to at least one peer.
+    fn broadcast(&self, digest: Digest, size: usize) {
+        self.broadcasts.inc();
+        let now = self.context.current();
+        let mut state = self.state.lock().unwrap();
+        if state.see(digest) {
+            self.block_size.observe(size as f64);
+        }
+        state.broadcasts.push_back((digest, now));
+        if state.broadcasts.len() > WINDOW {
+            state.broadcasts.pop_front();
+        }
+    }
+
+    /// Record a block (of `size` bytes) received from a peer.
+    fn receive(&self, digest: Digest, size: usize) {
+        self.received.inc();
+        if self.state.lock().unwrap().see(digest) {
+            self.block_size.observe(size as f64);
+        } else {
+            self.duplicates.inc();
+        }
+    }
+}
+
+impl<E: Clock> Reporter for Tracker<E> {
+    type Activity = Activity;
+
+    async fn report(&mut self, activity: Self::Activity) {
+        let notarize = match activity {
+            Activity::Notarize(notarize) => notarize,
+            Activity::Notarization(notarization) => {
+                let size = notarization.encode_size();
+                self.notarization_size.observe(size as f64);
+                return;
+            }
+            Activity::Finalization(finalization) => {
+                let size = finalization.encode_size();
+                self.finalization_size.observe(size as f64);
+                return;
+            }
+            _ => return,
+        };
+        if Some(notarize.signer()) == self.me {
+            return;
+        }
+
+        // Record the first acknowledgement of a block we broadcast
+        let start = {
+            let mut state = self.state.lock().unwrap();
+            let Some(index) = state
+                .broadcasts
+                .iter()
+                .position(|(digest, _)| *digest == notarize.proposal.payload)
+            else {
+                return;
+            };
+            state.broadcasts.remove(index).unwrap().1
+        };
+        let elapsed = self
+            .context
+            .current()
+            .duration_since(start)
+            .unwrap_or_default();
+        self.first_ack.observe(elapsed.as_secs_f64());
+    }
+}
+
+/// A broadcast [LimitedSender] that records blocks sent.
+#[derive(Clone)]
+pub struct TrackedSender<S, E: Clock> {
+    inner: S,
+    tracker: Tracker<E>,
+}
+
+impl<S: LimitedSender<PublicKey = PublicKey>, E: Clock> LimitedSender for TrackedSender<S, E> {
+    type PublicKey = PublicKey;
+    type Checked<'a>
+        = TrackedCheckedSender<S::Checked<'a>, E>
+    where
+        Self: 'a;
+
+    async fn check<'a>(
+        &'a mut self,
+        recipients: Recipients<Self::PublicKey>,
+    ) -> Result<Self::Checked<'a>, SystemTime> {
+        let inner = self.inner.check(recipients).await?;
+        Ok(TrackedCheckedSender {
+            inner,
+            tracker: self.tracker.clone(),
+        })
+    }
+}
+
+/// A [CheckedSender] that records blocks sent.
+pub struct TrackedCheckedSender<C, E: Clock> {
+    inner: C,
+    tracker: Tracker<E>,
+}
+
+impl<C: CheckedSender<PublicKey = PublicKey>, E: Clock> CheckedSender
+    for TrackedCheckedSender<C, E>
+{
+    type PublicKey = PublicKey;
+    type Error = C::Error;
+
+    async fn send(
+        self,
+        mut message: impl Buf + Send,
+        priority: bool,
+    ) -> Result<Vec<Self::PublicKey>, Self::Error> {
+        let message = message.copy_to_bytes(message.remaining());
+        let size = message.len();
+        let block = Block::decode_cfg(message.clone(), &self.tracker.digest_mode).ok();
+        let recipients = self.inner.send(message, priority).await?;
+        if let (Some(block), false) = (block, recipients.is_empty()) {
+            self.tracker.broadcast(block.digest(), size);
+        }
+        Ok(recipients)
+    }
+}
+
+/// A broadcast [Receiver] that records blocks received.
+pub struct TrackedReceiver<R, E: Clock> {
+    inner: R,
+    tracker: Tracker<E>,
+}
+
+impl<R: fmt::Debug, E: Clock> fmt::Debug for TrackedReceiver<R, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrackedReceiver")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<R: Receiver<PublicKey = PublicKey>, E: Clock> Receiver for TrackedReceiver<R, E> {
+    type Error = R::Error;
+    type PublicKey = PublicKey;
+
+    async fn recv(&mut self) -> Result<Message<Self::PublicKey>, Self::Error> {
+        let (peer, message): (PublicKey, Bytes) = self.inner.recv().await?;
+        if let Ok(block) = Block::decode_cfg(message.clone(), &self.tracker.digest_mode) {
+            self.tracker.receive(block.digest(), message.len());
+        }
+        Ok((peer, message))
+    }
+}
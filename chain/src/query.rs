@@ -0,0 +1,302 @@
+//! Local query API.
+//!
+//! The [Server] answers HTTP requests about this validator on a dedicated port, reading finalized
+//! blocks and finalizations from the archives marshal writes to (so operators can query their own
+//! node without an indexer):
+//! - `GET /block/<height>` returns the finalized block at `height`.
+//! - `GET /finalization/<view>` returns the finalization of `view` (in the epoch of the latest
+//!   finalization, or `?epoch=<epoch>`).
+//! - `GET /status` returns a JSON [Status].
+//! - `GET /peers` returns a JSON list of [Peer]s.
+//!
+//! Blocks and finalizations are encoded with [commonware_codec] (`404` if not stored). Reads
+//! share the consensus-critical archives, so explorers should use a [crate::replica] (or the
+//! indexer) instead.
+
+use crate::{application::Processed, batch, views};
+use alto_types::{Block, Finalization, PublicKey, Scheme};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use commonware_codec::Encode;
+use commonware_consensus::{
+    marshal,
+    types::{Epoch, Height, Round, View},
+};
+use commonware_runtime::{Clock, Metrics, Spawner};
+use commonware_utils::ordered::Set;
+use serde::{Deserialize, Serialize};
+use std::{
+    future::Future,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+/// Configuration for the query [Server].
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Config {
+    /// Port to serve queries on.
+    pub port: u16,
+}
+
+/// State of the validator reported by `GET /status`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    /// Public key of the validator.
+    pub public_key: String,
+
+    /// Epoch of the current view.
+    pub epoch: u64,
+
+    /// Current consensus view.
+    pub view: u64,
+
+    /// Leader elected for the current view (if known).
+    pub leader: Option<String>,
+
+    /// Milliseconds spent in the current view.
+    pub time_in_view: u64,
+
+    /// Number of consecutive views that were nullified.
+    pub nullified: u64,
+
+    /// Latest processed height.
+    pub processed: u64,
+
+    /// Highest height in the finalized blocks archive.
+    pub blocks: u64,
+
+    /// Highest height in the finalizations archive.
+    pub finalizations: u64,
+}
+
+/// Authorized peer reported by `GET /peers`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Peer {
+    /// Public key of the peer.
+    pub public_key: String,
+
+    /// Whether the peer participates in consensus in the current epoch.
+    pub participant: bool,
+
+    /// Whether the peer voted in the last [views::VOTER_WINDOW] views.
+    pub voted: bool,
+}
+
+/// Handles to the validator state served by the [Server].
+#[derive(Clone)]
+pub struct Sources<E: Clock> {
+    /// Public key of the validator.
+    pub public_key: PublicKey,
+
+    /// Finalized blocks and finalizations.
+    pub marshal: marshal::Mailbox<Scheme, Block>,
+
+    /// Latest processed height.
+    pub processed: Processed,
+
+    /// Current view (and the participants voting in it).
+    pub views: views::Tracker<E>,
+
+    /// Heights of the finalized blocks archive.
+    pub blocks: batch::Heights,
+
+    /// Heights of the finalizations archive.
+    pub finalizations: batch::Heights,
+
+    /// Authorized peers (participants of every epoch and any observers).
+    pub peers: Set<PublicKey>,
+}
+
+/// Serves queries over HTTP.
+pub struct Server<E: Clock + Spawner + Metrics> {
+    context: E,
+    port: u16,
+    sources: Sources<E>,
+}
+
+impl<E: Clock + Spawner + Metrics> Server<E> {
+    /// Create a new [Server] that serves `sources`.
+    pub fn new(context: E, cfg: Config, sources: Sources<E>) -> Self {
+        Self {
+            context,
+            port: cfg.port,
+            sources,
+        }
+    }
+
+    /// Start the [Server].
+    pub fn start(self) -> commonware_runtime::Handle<()> {
+        self.context.clone().spawn(|_| self.run())
+    }
+
+    async fn run(self) {
+        let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), self.port);
+        let listener = match TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!(?e, port = self.port, "failed to bind query server");
+                return;
+            }
+        };
+        info!(port = self.port, "serving queries");
+        let router = Router::new()
+            .route("/block/{height}", get(block::<E>))
+            .route("/finalization/{view}", get(finalization::<E>))
+            .route("/status", get(status::<E>))
+            .route("/peers", get(peers::<E>))
+            .with_state(self.sources);
+        if let Err(e) = axum::serve(listener, router).await {
+            warn!(?e, "query server failed");
+        }
+    }
+}
+
+/// Epoch to find a finalization in (defaults to the epoch of the latest finalization).
+#[derive(Deserialize)]
+struct EpochQuery {
+    epoch: Option<u64>,
+}
+
+/// Respond with the encoding of `value` (or `404` if `None`).
+fn encoded(value: Option<impl Encode>) -> Response {
+    match value {
+        Some(value) => (
+            [(header::CONTENT_TYPE, "application/octet-stream")],
+            value.encode().to_vec(),
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Serve the finalized block at `height`.
+async fn block<E: Clock>(State(sources): State<Sources<E>>, Path(height): Path<u64>) -> Response {
+    let mut marshal = sources.marshal;
+    encoded(marshal.get_block(Height::new(height)).await)
+}
+
+/// Serve the finalization of `view`.
+async fn finalization<E: Clock>(
+    State(sources): State<Sources<E>>,
+    Path(view): Path<u64>,
+    Query(query): Query<EpochQuery>,
+) -> Response {
+    let mut marshal = sources.marshal;
+    let last = sources.finalizations.written();
+    let lookup = |height: u64| {
+        let mut marshal = marshal.clone();
+        async move {
+            marshal
+                .get_finalization(Height::new(height))
+                .await
+                .map(|finalization| finalization.proposal.round)
+        }
+    };
+    let epoch = match query.epoch {
+        Some(epoch) => Epoch::new(epoch),
+        None => match lookup(last).await {
+            Some(round) => round.epoch(),
+            None => return StatusCode::NOT_FOUND.into_response(),
+        },
+    };
+    let target = Round::new(epoch, View::new(view));
+    let Some(height) = search(last, target, lookup).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let finalization: Option<Finalization> = marshal.get_finalization(Height::new(height)).await;
+    encoded(finalization)
+}
+
+/// Find the height (at most `last`) finalized in `target` using the round `lookup` returns for
+/// each height (if stored).
+///
+/// Finalized rounds increase with height, so this is a binary search. Heights below the first
+/// stored height (not yet backfilled, before a checkpoint, or pruned) are treated as finalized
+/// before `target`.
+async fn search<F, Fut>(last: u64, target: Round, lookup: F) -> Option<u64>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Option<Round>>,
+{
+    let (mut low, mut high) = (1, last);
+    while low <= high {
+        let mid = low + (high - low) / 2;
+        match lookup(mid).await {
+            Some(round) if round == target => return Some(mid),
+            Some(round) if round > target => high = mid - 1,
+            _ => low = mid + 1,
+        }
+    }
+    None
+}
+
+/// Serve the [Status] of the validator.
+async fn status<E: Clock>(State(sources): State<Sources<E>>) -> Json<Status> {
+    let view = sources.views.status();
+    Json(Status {
+        public_key: sources.public_key.to_string(),
+        epoch: view.epoch,
+        view: view.view,
+        leader: view.leader.map(|leader| leader.to_string()),
+        time_in_view: view.time_in_view.as_millis() as u64,
+        nullified: view.nullified,
+        processed: sources.processed.get(),
+        blocks: sources.blocks.written(),
+        finalizations: sources.finalizations.written(),
+    })
+}
+
+/// Serve the authorized [Peer]s (and whether each voted recently).
+async fn peers<E: Clock>(State(sources): State<Sources<E>>) -> Json<Vec<Peer>> {
+    let participants = sources.views.participants();
+    let peers = sources
+        .peers
+        .iter()
+        .map(|peer| {
+            let participant = participants.iter().find(|(key, _)| key == peer);
+            Peer {
+                public_key: peer.to_string(),
+                participant: participant.is_some(),
+                voted: participant.is_some_and(|(_, voted)| *voted),
+            }
+        })
+        .collect();
+    Json(peers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn round(epoch: u64, view: u64) -> Round {
+        Round::new(Epoch::new(epoch), View::new(view))
+    }
+
+    #[test]
+    fn test_search() {
+        // Heights 5..=20 are stored (finalized in every other view of epoch 0, then epoch 1)
+        let stored = |height: u64| match height {
+            5..=12 => Some(round(0, height * 2)),
+            13..=20 => Some(round(1, height * 2)),
+            _ => None,
+        };
+        let lookup = |height| async move { stored(height) };
+        assert_eq!(block_on(search(20, round(0, 10), lookup)), Some(5));
+        assert_eq!(block_on(search(20, round(0, 24), lookup)), Some(12));
+        assert_eq!(block_on(search(20, round(1, 26), lookup)), Some(13));
+        assert_eq!(block_on(search(20, round(1, 40), lookup)), Some(20));
+
+        // Views that were not finalized (or are not stored)
+        assert_eq!(block_on(search(20, round(0, 11), lookup)), None);
+        assert_eq!(block_on(search(20, round(0, 8), lookup)), None);
+        assert_eq!(block_on(search(20, round(1, 42), lookup)), None);
+        assert_eq!(block_on(search(0, round(0, 10), lookup)), None);
+    }
+}
@@ -0,0 +1,328 @@
+//! Gossip-only relays.
+//!
+//! Validators broadcast each block they propose directly to every other participant, so a
+//! proposer with a slow (or distant) link delays its block everywhere. A [Relay] adds fanout
+//! points to the dissemination topology without participating in consensus: it holds no share
+//! (only the key that authenticates it to peers), forwards every new block it receives on the
+//! broadcast channel to the participants that did not send it, and serves recently relayed
+//! blocks to peers that request them over the backfill channel.
+//!
+//! Relays only forward blocks that decode (they cannot verify them), so validators must allow a
+//! relay as an observer on the broadcast and backfill channels (see [crate::observers]).
+
+use alto_types::{Block, DigestMode};
+use bytes::Bytes;
+use commonware_codec::Decode;
+use commonware_consensus::marshal::ingress::handler::{Message, Request};
+use commonware_cryptography::{ed25519::PublicKey, sha256::Digest, Digestible};
+use commonware_macros::select;
+use commonware_p2p::{Receiver, Recipients, Sender};
+use commonware_runtime::{Handle, Metrics, Spawner};
+use commonware_utils::ordered::Set;
+use futures::{channel::mpsc, StreamExt};
+use prometheus_client::metrics::counter::Counter;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tracing::{debug, warn};
+
+/// Default number of recent blocks a relay remembers (and serves).
+pub const DEFAULT_CACHE_SIZE: usize = 1024;
+
+/// Configuration for relay mode.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// Number of recent blocks to remember (defaults to [DEFAULT_CACHE_SIZE]).
+    pub cache_size: Option<usize>,
+}
+
+/// Recently relayed blocks (by digest).
+struct Cache {
+    capacity: usize,
+    blocks: HashMap<Digest, Bytes>,
+    order: VecDeque<Digest>,
+}
+
+impl Cache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            blocks: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Remember `block`, returning false if it was already cached.
+    fn insert(&mut self, digest: Digest, block: Bytes) -> bool {
+        if self.blocks.contains_key(&digest) {
+            return false;
+        }
+        self.blocks.insert(digest, block);
+        self.order.push_back(digest);
+        if self.order.len() > self.capacity {
+            let oldest = self.order.pop_front().unwrap();
+            self.blocks.remove(&oldest);
+        }
+        true
+    }
+
+    fn get(&self, digest: &Digest) -> Option<Bytes> {
+        self.blocks.get(digest).cloned()
+    }
+}
+
+/// Relays blocks between participants.
+pub struct Relay<E: Spawner + Metrics> {
+    context: E,
+    participants: Set<PublicKey>,
+    digest_mode: DigestMode,
+    cache: Cache,
+
+    relayed: Counter,
+    invalid: Counter,
+    served: Counter,
+}
+
+impl<E: Spawner + Metrics> Relay<E> {
+    /// Create a new [Relay] that forwards blocks (decoded using `digest_mode`) to
+    /// `participants`.
+    pub fn new(
+        context: E,
+        cfg: Config,
+        participants: Set<PublicKey>,
+        digest_mode: DigestMode,
+    ) -> Self {
+        let relayed = Counter::default();
+        context.register(
+            "relayed",
+            "Number of new blocks forwarded to participants",
+            relayed.clone(),
+        );
+        let invalid = Counter::default();
+        context.register(
+            "invalid",
+            "Number of messages received on the broadcast channel that were not blocks",
+            invalid.clone(),
+        );
+        let served = Counter::default();
+        context.register(
+            "served",
+            "Number of backfill requests served from recently relayed blocks",
+            served.clone(),
+        );
+        Self {
+            context,
+            participants,
+            digest_mode,
+            cache: Cache::new(cfg.cache_size.unwrap_or(DEFAULT_CACHE_SIZE)),
+            relayed,
+            invalid,
+            served,
+        }
+    }
+
+    /// Start relaying blocks received on `broadcast` (and serving requests from `backfill`).
+    pub fn start(
+        self,
+        broadcast: (
+            impl Sender<PublicKey = PublicKey>,
+            impl Receiver<PublicKey = PublicKey>,
+        ),
+        backfill: mpsc::Receiver<Message<Block>>,
+    ) -> Handle<()> {
+        self.context
+            .clone()
+            .spawn(|_| self.run(broadcast, backfill))
+    }
+
+    async fn run(
+        mut self,
+        (mut sender, mut receiver): (
+            impl Sender<PublicKey = PublicKey>,
+            impl Receiver<PublicKey = PublicKey>,
+        ),
+        mut backfill: mpsc::Receiver<Message<Block>>,
+    ) {
+        loop {
+            select! {
+                message = receiver.recv() => {
+                    let Ok((peer, message)) = message else {
+                        warn!("broadcast channel closed");
+                        return;
+                    };
+                    let Ok(block) = Block::decode_cfg(message.clone(), &self.digest_mode) else {
+                        self.invalid.inc();
+                        continue;
+                    };
+                    let digest = block.digest();
+                    if !self.cache.insert(digest, message.clone()) {
+                        continue;
+                    }
+
+                    // Forward to every participant that did not send the block
+                    let recipients: Vec<_> = self
+                        .participants
+                        .iter()
+                        .filter(|participant| **participant != peer)
+                        .cloned()
+                        .collect();
+                    match sender.send(Recipients::Some(recipients), message, false).await {
+                        Ok(sent) => {
+                            self.relayed.inc();
+                            debug!(?digest, %peer, recipients = sent.len(), "relayed block");
+                        }
+                        Err(e) => warn!(?digest, ?e, "failed to relay block"),
+                    }
+                },
+                message = backfill.next() => {
+                    let Some(message) = message else {
+                        warn!("backfill handler closed");
+                        return;
+                    };
+                    match message {
+                        Message::Produce { key: Request::Block(digest), response } => {
+                            // Requests for blocks we have not seen are left unanswered
+                            if let Some(block) = self.cache.get(&digest) {
+                                self.served.inc();
+                                let _ = response.send(block);
+                            }
+                        }
+                        Message::Produce { .. } => {
+                            // Relays do not store certificates
+                        }
+                        Message::Deliver { response, .. } => {
+                            // Relays never request backfill
+                            let _ = response.send(false);
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::QUOTA;
+    use commonware_codec::Encode;
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{ed25519::PrivateKey, Hasher, Sha256, Signer};
+    use commonware_p2p::{
+        simulated::{self, Link, Network},
+        Manager,
+    };
+    use commonware_runtime::{deterministic, Clock, Runner};
+    use futures::{channel::oneshot, SinkExt};
+    use std::time::Duration;
+
+    #[test]
+    fn test_relay() {
+        let executor = deterministic::Runner::default();
+        executor.start(|context| async move {
+            let (network, oracle) = Network::new(
+                context.with_label("network"),
+                simulated::Config {
+                    max_size: 1024 * 1024,
+                    disconnect_on_block: true,
+                    tracked_peer_sets: Some(1),
+                },
+            );
+            network.start();
+
+            // The proposer can only reach the other participant through the relay
+            let proposer = PrivateKey::from_seed(0).public_key();
+            let participant = PrivateKey::from_seed(1).public_key();
+            let relay = PrivateKey::from_seed(2).public_key();
+            let peers =
+                Set::from_iter_dedup([proposer.clone(), participant.clone(), relay.clone()]);
+            oracle.manager().update(0, peers).await;
+            let link = Link {
+                latency: Duration::from_millis(10),
+                jitter: Duration::ZERO,
+                success_rate: 1.0,
+            };
+            for (a, b) in [(&proposer, &relay), (&relay, &participant)] {
+                oracle
+                    .add_link(a.clone(), b.clone(), link.clone())
+                    .await
+                    .unwrap();
+                oracle
+                    .add_link(b.clone(), a.clone(), link.clone())
+                    .await
+                    .unwrap();
+            }
+            let (mut proposer_sender, _proposer_receiver) = oracle
+                .control(proposer.clone())
+                .register(3, QUOTA)
+                .await
+                .unwrap();
+            let (_participant_sender, mut participant_receiver) = oracle
+                .control(participant.clone())
+                .register(3, QUOTA)
+                .await
+                .unwrap();
+            let broadcast = oracle
+                .control(relay.clone())
+                .register(3, QUOTA)
+                .await
+                .unwrap();
+
+            // Start the relay
+            let participants = Set::from_iter_dedup([proposer.clone(), participant.clone()]);
+            let (mut backfill, handler) = mpsc::channel(8);
+            Relay::new(
+                context.with_label("relay"),
+                Config::default(),
+                participants,
+                DigestMode::Sha256,
+            )
+            .start(broadcast, handler);
+
+            // Blocks are forwarded to participants that did not send them
+            let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+            proposer_sender
+                .send(Recipients::One(relay.clone()), block.encode(), false)
+                .await
+                .unwrap();
+            let (sender, received) = participant_receiver.recv().await.unwrap();
+            assert_eq!(sender, relay);
+            assert_eq!(received, block.encode());
+
+            // Non-blocks are dropped
+            proposer_sender
+                .send(
+                    Recipients::One(relay.clone()),
+                    Bytes::from_static(b"junk"),
+                    false,
+                )
+                .await
+                .unwrap();
+            context.sleep(Duration::from_secs(1)).await;
+            assert!(context.encode().contains("relay_invalid_total 1"));
+
+            // Relayed blocks are served to backfill requests
+            let (response, served) = oneshot::channel();
+            backfill
+                .send(Message::Produce {
+                    key: Request::Block(block.digest()),
+                    response,
+                })
+                .await
+                .unwrap();
+            assert_eq!(served.await.unwrap(), block.encode());
+
+            // Unknown blocks are not
+            let (response, served) = oneshot::channel();
+            backfill
+                .send(Message::Produce {
+                    key: Request::Block(Sha256::hash(b"unknown")),
+                    response,
+                })
+                .await
+                .unwrap();
+            assert!(served.await.is_err());
+            assert!(context.encode().contains("relay_relayed_total 1"));
+            assert!(context.encode().contains("relay_served_total 1"));
+        });
+    }
+}
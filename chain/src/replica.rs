@@ -0,0 +1,303 @@
+//! Read replica of finalized blocks.
+//!
+//! Explorers read finalized blocks far more often than validators write them, and serving those
+//! reads from the archives marshal writes to would put explorer traffic on the consensus-critical
+//! path. Instead, a [Feeder] copies every finalized block (and its finalization) to a replica
+//! that runs on its own thread pool (with its own runtime) and keeps its own archives (under
+//! `directory`). The replica serves them over HTTP:
+//! - `GET /finalized/latest` returns the latest finalized block.
+//! - `GET /finalized/<height>` returns the finalized block at `height`.
+//!
+//! Both respond with the [Kind](alto_types::Kind)-prefixed frame written by [export::encode]
+//! (`404` if the block is not stored).
+//!
+//! Copies are best-effort: if the replica falls behind (or the validator restarts), finalized
+//! blocks are skipped rather than slowing down the validator, so the replica may have gaps.
+
+use crate::{engine, export};
+use alto_types::{Activity, Block, DigestMode, Finalized, Scheme};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use commonware_consensus::{marshal, Reporter, Viewable};
+use commonware_cryptography::Digestible;
+use commonware_runtime::{tokio as runtime, Clock, Metrics, Runner, Spawner, Storage};
+use commonware_storage::archive::{Archive, Identifier};
+use futures::{channel::mpsc, StreamExt};
+use prometheus_client::metrics::counter::Counter;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+};
+use tokio::{net::TcpListener, sync::RwLock};
+use tracing::{debug, info, warn};
+
+/// Prefix for all storage partitions of the replica.
+const PARTITION_PREFIX: &str = "replica";
+
+/// Number of finalized blocks to queue for the replica before skipping them.
+const BACKLOG: usize = 1_024;
+
+/// Configuration for the replica.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Config {
+    /// Port to serve finalized blocks on.
+    pub port: u16,
+
+    /// Number of threads to serve (and store) finalized blocks with.
+    pub worker_threads: usize,
+}
+
+/// Start a replica that stores finalized blocks under `directory` (with blocks encoded using
+/// `digest_mode`), returning the sender to copy finalized blocks to (with a [Feeder]).
+///
+/// The replica runs until the process exits.
+pub fn start(directory: PathBuf, digest_mode: DigestMode, cfg: Config) -> mpsc::Sender<Finalized> {
+    let (sender, receiver) = mpsc::channel(BACKLOG);
+    std::thread::Builder::new()
+        .name("replica".to_string())
+        .spawn(move || {
+            let config = runtime::Config::default()
+                .with_worker_threads(cfg.worker_threads)
+                .with_storage_directory(directory);
+            runtime::Runner::new(config).start(|context| async move {
+                run(context, digest_mode, receiver, cfg.port).await;
+            });
+        })
+        .expect("failed to spawn replica thread");
+    sender
+}
+
+/// Archives of the replica.
+pub struct Archives<E: Storage + Metrics + Clock> {
+    finalizations: engine::FinalizationArchive<E>,
+    blocks: engine::BlockArchive<E>,
+}
+
+impl<E: Storage + Metrics + Clock> Archives<E> {
+    /// Open (or create) the archives of the replica.
+    pub async fn init(context: E, digest_mode: DigestMode) -> Self {
+        let tuning = engine::Tuning::default();
+        let buffer_pool = engine::buffer_pool(&tuning);
+        let finalizations = engine::init_finalizations(
+            context.with_label("finalizations"),
+            PARTITION_PREFIX,
+            engine::DEFAULT_FREEZER_TABLE_INITIAL_SIZE,
+            buffer_pool.clone(),
+            &tuning,
+        )
+        .await;
+        let blocks = engine::init_blocks(
+            context.with_label("blocks"),
+            PARTITION_PREFIX,
+            engine::DEFAULT_FREEZER_TABLE_INITIAL_SIZE,
+            buffer_pool,
+            digest_mode,
+            &tuning,
+        )
+        .await;
+        Self {
+            finalizations,
+            blocks,
+        }
+    }
+
+    /// Store `finalized`.
+    pub async fn put(
+        &mut self,
+        finalized: Finalized,
+    ) -> Result<(), commonware_storage::archive::Error> {
+        let height = finalized.block.height.get();
+        let digest = finalized.block.digest();
+        self.blocks
+            .put_sync(height, digest, finalized.block)
+            .await?;
+        self.finalizations
+            .put_sync(height, digest, finalized.proof)
+            .await
+    }
+
+    /// Get the [Finalized] block at `height` (or the latest, if `None`).
+    pub async fn get(&self, height: Option<u64>) -> Result<Option<Finalized>, export::Error> {
+        let Some(height) = height.or_else(|| self.finalizations.last_index()) else {
+            return Ok(None);
+        };
+        let Some(proof) = self.finalizations.get(Identifier::Index(height)).await? else {
+            return Ok(None);
+        };
+        let Some(block) = self.blocks.get(Identifier::Index(height)).await? else {
+            return Ok(None);
+        };
+        if proof.proposal.payload != block.digest() {
+            return Err(export::Error::Mismatch(height));
+        }
+        Ok(Some(Finalized::new(proof, block)))
+    }
+}
+
+/// Store finalized blocks from `receiver` while serving them on `port`.
+async fn run<E: Storage + Metrics + Clock + Spawner>(
+    context: E,
+    digest_mode: DigestMode,
+    mut receiver: mpsc::Receiver<Finalized>,
+    port: u16,
+) {
+    let archives = Archives::init(context.with_label("archives"), digest_mode).await;
+    let archives = Arc::new(RwLock::new(archives));
+
+    // Serve finalized blocks
+    let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port);
+    match TcpListener::bind(address).await {
+        Ok(listener) => {
+            info!(port, "serving finalized blocks from replica");
+            let router = Router::new()
+                .route("/finalized/{query}", get(finalized::<E>))
+                .with_state(archives.clone());
+            context.with_label("server").spawn(|_| async move {
+                if let Err(e) = axum::serve(listener, router).await {
+                    warn!(?e, "replica server failed");
+                }
+            });
+        }
+        Err(e) => warn!(?e, port, "failed to bind replica server"),
+    }
+
+    // Store finalized blocks
+    while let Some(finalized) = receiver.next().await {
+        let height = finalized.block.height.get();
+        if let Err(e) = archives.write().await.put(finalized).await {
+            warn!(?e, height, "failed to store finalized block in replica");
+            continue;
+        }
+        debug!(height, "stored finalized block in replica");
+    }
+}
+
+/// Serve the finalized block at the height in `query` (or `latest`).
+async fn finalized<E: Storage + Metrics + Clock>(
+    State(archives): State<Arc<RwLock<Archives<E>>>>,
+    Path(query): Path<String>,
+) -> Response {
+    let height = match query.as_str() {
+        "latest" => None,
+        height => match height.parse() {
+            Ok(height) => Some(height),
+            Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+        },
+    };
+    match archives.read().await.get(height).await {
+        Ok(Some(finalized)) => (
+            [(header::CONTENT_TYPE, "application/octet-stream")],
+            export::encode(&finalized),
+        )
+            .into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            warn!(?e, "failed to read finalized block from replica");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Copies finalized blocks (and their finalizations) to a replica.
+#[derive(Clone)]
+pub struct Feeder<E: Spawner + Metrics + Clock> {
+    context: E,
+    sender: mpsc::Sender<Finalized>,
+    marshal: marshal::Mailbox<Scheme, Block>,
+
+    skipped: Counter,
+}
+
+impl<E: Spawner + Metrics + Clock> Feeder<E> {
+    /// Create a new [Feeder] that copies finalized blocks (fetched from `marshal`) to `sender`.
+    pub fn new(
+        context: E,
+        sender: mpsc::Sender<Finalized>,
+        marshal: marshal::Mailbox<Scheme, Block>,
+    ) -> Self {
+        let skipped = Counter::default();
+        context.register(
+            "skipped",
+            "Number of finalized blocks not copied to the replica",
+            skipped.clone(),
+        );
+        Self {
+            context,
+            sender,
+            marshal,
+            skipped,
+        }
+    }
+}
+
+impl<E: Spawner + Metrics + Clock> Reporter for Feeder<E> {
+    type Activity = Activity;
+
+    async fn report(&mut self, activity: Self::Activity) {
+        let Activity::Finalization(finalization) = activity else {
+            return;
+        };
+        self.context.with_label("finalized_block").spawn({
+            let mut sender = self.sender.clone();
+            let skipped = self.skipped.clone();
+            let mut marshal = self.marshal.clone();
+            move |_| async move {
+                let view = finalization.view();
+                let block = marshal
+                    .subscribe(Some(finalization.round()), finalization.proposal.payload)
+                    .await
+                    .await;
+                let Ok(block) = block else {
+                    warn!(%view, "subscription for block cancelled");
+                    return;
+                };
+                if sender
+                    .try_send(Finalized::new(finalization, block))
+                    .is_err()
+                {
+                    skipped.inc();
+                    debug!(%view, "skipped copying finalized block to replica");
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_types::fixtures::{self, Fixture};
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Hasher, Sha256};
+    use commonware_runtime::deterministic;
+
+    #[test]
+    fn test_archives() {
+        let executor = deterministic::Runner::default();
+        executor.start(|context| async move {
+            let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+            let mut archives = Archives::init(context, DigestMode::Sha256).await;
+            assert!(archives.get(None).await.unwrap().is_none());
+
+            // Store finalized blocks (with a gap)
+            let genesis = Sha256::hash(b"genesis");
+            let first = fixtures::finalized(&schemes, 1, Block::new(genesis, Height::new(1), 1));
+            let third = fixtures::finalized(&schemes, 3, Block::new(genesis, Height::new(3), 3));
+            archives.put(first.clone()).await.unwrap();
+            archives.put(third.clone()).await.unwrap();
+
+            // Blocks are served by height (or the latest)
+            assert_eq!(archives.get(Some(1)).await.unwrap(), Some(first));
+            assert_eq!(archives.get(None).await.unwrap(), Some(third.clone()));
+            assert_eq!(archives.get(Some(3)).await.unwrap(), Some(third));
+            assert!(archives.get(Some(2)).await.unwrap().is_none());
+        });
+    }
+}
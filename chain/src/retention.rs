@@ -0,0 +1,269 @@
+//! Retention of finalized history.
+//!
+//! By default, every finalized block (and its finalization) is kept forever in immutable
+//! archives. With [crate::engine::ConfigBuilder::with_retain_blocks], they are instead stored in
+//! prunable archives (under different partitions) that drop heights more than `retain_blocks`
+//! below the latest processed block. Heights above it are never pruned (marshal still has to
+//! deliver them to the application), so a syncing validator keeps everything it has not
+//! processed yet.
+//!
+//! Archives are pruned a section ([crate::engine::Tuning::prunable_items_per_section] heights)
+//! at a time, so up to a section more than `retain_blocks` heights are served to peers that
+//! backfill from this validator. Older heights must be fetched elsewhere (like from the indexer).
+//!
+//! The mode of an existing validator cannot be changed: [check] refuses to open the archives of
+//! one mode while the other holds data (rather than silently starting from an empty history).
+
+use crate::application::Processed;
+use commonware_codec::CodecShared;
+use commonware_cryptography::sha256::Digest;
+use commonware_runtime::{Clock, Metrics, Storage};
+use commonware_storage::{
+    archive::{self, immutable, prunable, Error, Identifier},
+    translator::EightCap,
+};
+use std::num::NonZeroU64;
+use thiserror::Error as ThisError;
+
+/// Error returned when the archives of the other mode hold data.
+#[derive(Debug, ThisError)]
+pub enum ModeError {
+    #[error("finalized history is pruned (retain_blocks must be set)")]
+    Pruned,
+    #[error("finalized history is not pruned (retain_blocks cannot be set)")]
+    Unpruned,
+}
+
+/// How many heights to keep below the latest processed block.
+#[derive(Clone)]
+pub struct Retention {
+    blocks: u64,
+    processed: Processed,
+}
+
+impl Retention {
+    /// Keep `blocks` heights below the height tracked by `processed`.
+    pub fn new(blocks: NonZeroU64, processed: Processed) -> Self {
+        Self {
+            blocks: blocks.get(),
+            processed,
+        }
+    }
+
+    /// Lowest height to keep.
+    fn floor(&self) -> u64 {
+        self.processed.get().saturating_sub(self.blocks)
+    }
+}
+
+/// A finalized archive (keyed by digest and indexed by height) that either keeps every height or
+/// prunes those outside its [Retention].
+#[allow(clippy::large_enum_variant)] // only one of each is opened
+pub enum Archive<E: Storage + Metrics + Clock, V: CodecShared> {
+    /// Keep every height.
+    All(immutable::Archive<E, Digest, V>),
+
+    /// Prune heights below the [Retention] floor.
+    Recent {
+        archive: prunable::Archive<EightCap, E, Digest, V>,
+        retention: Retention,
+    },
+}
+
+impl<E: Storage + Metrics + Clock, V: CodecShared> Archive<E, V> {
+    /// Prune everything below `min` (a no-op if every height is kept).
+    pub async fn prune(&mut self, min: u64) -> Result<(), Error> {
+        match self {
+            Self::All(_) => Ok(()),
+            Self::Recent { archive, .. } => archive.prune(min).await,
+        }
+    }
+}
+
+impl<E: Storage + Metrics + Clock, V: CodecShared> archive::Archive for Archive<E, V> {
+    type Key = Digest;
+    type Value = V;
+
+    async fn put(&mut self, index: u64, key: Digest, value: V) -> Result<(), Error> {
+        match self {
+            Self::All(archive) => archive.put(index, key, value).await,
+            Self::Recent { archive, retention } => {
+                archive.put(index, key, value).await?;
+                archive.prune(retention.floor()).await
+            }
+        }
+    }
+
+    async fn get(&self, identifier: Identifier<'_, Digest>) -> Result<Option<V>, Error> {
+        match self {
+            Self::All(archive) => archive.get(identifier).await,
+            Self::Recent { archive, .. } => archive.get(identifier).await,
+        }
+    }
+
+    async fn has(&self, identifier: Identifier<'_, Digest>) -> Result<bool, Error> {
+        match self {
+            Self::All(archive) => archive.has(identifier).await,
+            Self::Recent { archive, .. } => archive.has(identifier).await,
+        }
+    }
+
+    fn next_gap(&self, index: u64) -> (Option<u64>, Option<u64>) {
+        match self {
+            Self::All(archive) => archive.next_gap(index),
+            Self::Recent { archive, .. } => archive.next_gap(index),
+        }
+    }
+
+    fn missing_items(&self, index: u64, max: usize) -> Vec<u64> {
+        match self {
+            Self::All(archive) => archive.missing_items(index, max),
+            Self::Recent { archive, .. } => archive.missing_items(index, max),
+        }
+    }
+
+    fn ranges(&self) -> impl Iterator<Item = (u64, u64)> {
+        let ranges: Vec<_> = match self {
+            Self::All(archive) => archive.ranges().collect(),
+            Self::Recent { archive, .. } => archive.ranges().collect(),
+        };
+        ranges.into_iter()
+    }
+
+    fn first_index(&self) -> Option<u64> {
+        match self {
+            Self::All(archive) => archive.first_index(),
+            Self::Recent { archive, .. } => archive.first_index(),
+        }
+    }
+
+    fn last_index(&self) -> Option<u64> {
+        match self {
+            Self::All(archive) => archive.last_index(),
+            Self::Recent { archive, .. } => archive.last_index(),
+        }
+    }
+
+    async fn sync(&mut self) -> Result<(), Error> {
+        match self {
+            Self::All(archive) => archive.sync().await,
+            Self::Recent { archive, .. } => archive.sync().await,
+        }
+    }
+
+    async fn destroy(self) -> Result<(), Error> {
+        match self {
+            Self::All(archive) => archive.destroy().await,
+            Self::Recent { archive, .. } => archive.destroy().await,
+        }
+    }
+}
+
+/// Check that the partitions of the other mode (`pruned` partitions if not `pruning`, else
+/// `unpruned` partitions) hold no data.
+pub async fn check<E: Storage>(
+    context: &E,
+    pruning: bool,
+    pruned: &[String],
+    unpruned: &[String],
+) -> Result<(), ModeError> {
+    let (other, error) = match pruning {
+        true => (unpruned, ModeError::Unpruned),
+        false => (pruned, ModeError::Pruned),
+    };
+    for partition in other {
+        // Missing partitions hold no data
+        let blobs = context.scan(partition).await.unwrap_or_default();
+        if !blobs.is_empty() {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine;
+    use alto_types::{Block, DigestMode};
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Digestible, Hasher, Sha256};
+    use commonware_runtime::{deterministic, Runner};
+    use commonware_storage::archive::Archive as _;
+    use commonware_utils::NZU64;
+
+    fn block(height: u64) -> Block {
+        Block::new(Sha256::hash(b"genesis"), Height::new(height), height)
+    }
+
+    async fn init(
+        context: deterministic::Context,
+        retention: Option<Retention>,
+    ) -> engine::BlockStore<deterministic::Context> {
+        let tuning = engine::Tuning {
+            prunable_items_per_section: NZU64!(10),
+            ..Default::default()
+        };
+        engine::init_block_store(
+            context,
+            "retention",
+            engine::DEFAULT_FREEZER_TABLE_INITIAL_SIZE,
+            engine::buffer_pool(&tuning),
+            DigestMode::Sha256,
+            &tuning,
+            retention,
+        )
+        .await
+    }
+
+    #[test]
+    fn test_prune() {
+        let executor = deterministic::Runner::default();
+        executor.start(|context| async move {
+            let processed = Processed::default();
+            let retention = Retention::new(NZU64!(15), processed.clone());
+            let mut archive = init(context, Some(retention)).await;
+
+            // Heights above the latest processed block are kept
+            for height in 1..=30 {
+                archive
+                    .put(height, block(height).digest(), block(height))
+                    .await
+                    .unwrap();
+            }
+            assert_eq!(archive.first_index(), Some(1));
+
+            // Heights more than 15 below it are pruned (a section at a time)
+            for height in 31..=50 {
+                processed.set(height - 1);
+                archive
+                    .put(height, block(height).digest(), block(height))
+                    .await
+                    .unwrap();
+            }
+            assert_eq!(archive.first_index(), Some(30));
+            assert_eq!(archive.last_index(), Some(50));
+            assert!(archive.get(Identifier::Index(29)).await.unwrap().is_none());
+            assert_eq!(
+                archive.get(Identifier::Index(30)).await.unwrap(),
+                Some(block(30))
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "finalized history is pruned")]
+    fn test_mode() {
+        let executor = deterministic::Runner::default();
+        executor.start(|context| async move {
+            let retention = Retention::new(NZU64!(15), Processed::default());
+            let mut archive = init(context.with_label("pruned"), Some(retention)).await;
+            archive.put(1, block(1).digest(), block(1)).await.unwrap();
+            archive.sync().await.unwrap();
+            drop(archive);
+
+            // Pruned history cannot be opened without pruning
+            init(context.with_label("unpruned"), None).await;
+        });
+    }
+}
@@ -0,0 +1,178 @@
+//! Versioned block validity rules.
+//!
+//! Every block commits to the version of the rules it was built under ([Block::rules]). A
+//! [Rules] registry records the height at which each version activates (and what it changes),
+//! so a running network can change its validity rules by scheduling an [Upgrade] at a future
+//! height in every validator's config (rather than restarting from genesis). Proposers build
+//! blocks under the rules active at their height and verifiers reject blocks that commit to any
+//! other version.
+//!
+//! Upgrades are not part of the genesis transcript (they are scheduled after launch), so
+//! operators must ensure every validator schedules the same upgrades before the activation
+//! height is reached.
+//!
+//! [Block::rules]: alto_types::Block::rules
+
+use crate::application::TimestampPolicy;
+use alto_types::INITIAL_RULES;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A change to the validity rules, activated at a height.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Upgrade {
+    /// First height built (and verified) under the new rules.
+    pub height: u64,
+
+    /// Version of the new rules (committed in every block from `height`).
+    pub version: u16,
+
+    /// How block timestamps are assigned and validated from `height` (unchanged if omitted).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_policy: Option<TimestampPolicy>,
+}
+
+/// Validity rules in effect at some height.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ruleset {
+    /// Version of the rules (committed in blocks built under them).
+    pub version: u16,
+
+    /// How block timestamps are assigned and validated.
+    pub timestamp_policy: TimestampPolicy,
+}
+
+/// Errors that can occur when scheduling [Upgrade]s.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("upgrade activates at genesis")]
+    Genesis,
+    #[error("upgrade at height {0} does not follow the previous upgrade")]
+    HeightNotIncreasing(u64),
+    #[error("upgrade at height {0} does not increase the rules version")]
+    VersionNotIncreasing(u64),
+}
+
+/// Registry of the [Ruleset] active at each height.
+#[derive(Clone, Debug)]
+pub struct Rules {
+    /// Rulesets (sorted by the first height they are active at, starting at genesis).
+    rulesets: Vec<(u64, Ruleset)>,
+}
+
+impl Rules {
+    /// Create a new [Rules] registry where the [INITIAL_RULES] use `timestamp_policy` and each
+    /// of `upgrades` (ordered by height) is applied at its height.
+    pub fn new(timestamp_policy: TimestampPolicy, upgrades: &[Upgrade]) -> Result<Self, Error> {
+        let mut rulesets = vec![(
+            0,
+            Ruleset {
+                version: INITIAL_RULES,
+                timestamp_policy,
+            },
+        )];
+        for upgrade in upgrades {
+            let (height, previous) = rulesets[rulesets.len() - 1];
+            if upgrade.height == 0 {
+                return Err(Error::Genesis);
+            }
+            if upgrade.height <= height {
+                return Err(Error::HeightNotIncreasing(upgrade.height));
+            }
+            if upgrade.version <= previous.version {
+                return Err(Error::VersionNotIncreasing(upgrade.height));
+            }
+            rulesets.push((
+                upgrade.height,
+                Ruleset {
+                    version: upgrade.version,
+                    timestamp_policy: upgrade
+                        .timestamp_policy
+                        .unwrap_or(previous.timestamp_policy),
+                },
+            ));
+        }
+        Ok(Self { rulesets })
+    }
+
+    /// Get the [Ruleset] active at `height`.
+    pub fn at(&self, height: u64) -> Ruleset {
+        let index = self.rulesets.partition_point(|(start, _)| *start <= height);
+        self.rulesets[index - 1].1
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::new(TimestampPolicy::default(), &[]).expect("no upgrades are valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upgrade(height: u64, version: u16) -> Upgrade {
+        Upgrade {
+            height,
+            version,
+            timestamp_policy: None,
+        }
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = Rules::new(
+            TimestampPolicy::WallClock,
+            &[
+                upgrade(100, 1),
+                Upgrade {
+                    timestamp_policy: Some(TimestampPolicy::Monotonic),
+                    ..upgrade(200, 3)
+                },
+                upgrade(300, 4),
+            ],
+        )
+        .unwrap();
+
+        // Each height uses the rules active at that height
+        let initial = Ruleset {
+            version: INITIAL_RULES,
+            timestamp_policy: TimestampPolicy::WallClock,
+        };
+        assert_eq!(rules.at(0), initial);
+        assert_eq!(rules.at(99), initial);
+        assert_eq!(rules.at(100).version, 1);
+        assert_eq!(rules.at(199).timestamp_policy, TimestampPolicy::WallClock);
+        assert_eq!(rules.at(200).version, 3);
+        assert_eq!(rules.at(200).timestamp_policy, TimestampPolicy::Monotonic);
+
+        // Upgrades inherit unchanged rules
+        assert_eq!(
+            rules.at(u64::MAX),
+            Ruleset {
+                version: 4,
+                timestamp_policy: TimestampPolicy::Monotonic,
+            }
+        );
+
+        // Invalid schedules are rejected
+        let policy = TimestampPolicy::default();
+        assert_eq!(
+            Rules::new(policy, &[upgrade(0, 1)]).unwrap_err(),
+            Error::Genesis
+        );
+        assert_eq!(
+            Rules::new(policy, &[upgrade(100, 1), upgrade(100, 2)]).unwrap_err(),
+            Error::HeightNotIncreasing(100)
+        );
+        assert_eq!(
+            Rules::new(policy, &[upgrade(100, 2), upgrade(200, 2)]).unwrap_err(),
+            Error::VersionNotIncreasing(200)
+        );
+        assert_eq!(
+            Rules::new(policy, &[upgrade(100, INITIAL_RULES)]).unwrap_err(),
+            Error::VersionNotIncreasing(100)
+        );
+    }
+}
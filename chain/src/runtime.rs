@@ -0,0 +1,245 @@
+//! Runtime tuning and saturation metrics.
+//!
+//! Consensus, p2p, and storage run on the runtime's worker threads (`worker_threads`) and its
+//! blocking pool, while BLS signatures are verified on a separate pool of signature threads
+//! (`signature_threads`). When any of them is saturated, messages are handled late and views time
+//! out without anything being logged. The [Probe] periodically schedules a no-op task on each and
+//! records how long it waited to run:
+//! - `poll_delay`: time between a timer firing and the woken task being polled by a worker
+//!   thread,
+//! - `blocking_delay`: time between spawning a blocking task and it starting, and
+//! - `signature_delay`: time between submitting work to the signature threads and it starting.
+//!
+//! Timers have millisecond granularity, so a `poll_delay` of up to 1ms is expected on an idle
+//! runtime.
+
+use commonware_parallel::ThreadPool;
+use commonware_runtime::{Clock, Handle, Metrics, Spawner};
+use futures::channel::oneshot;
+use prometheus_client::metrics::histogram::Histogram;
+use rayon::{ThreadPoolBuildError, ThreadPoolBuilder};
+use serde::{Deserialize, Serialize};
+use std::{num::NonZeroUsize, sync::Arc, time::Duration};
+use tracing::warn;
+
+/// Maximum number of threads in the blocking pool (if not configured).
+pub const DEFAULT_MAX_BLOCKING_THREADS: usize = 512;
+
+/// Prefix of signature thread names (if not configured).
+pub const DEFAULT_THREAD_NAME: &str = "alto";
+
+/// Milliseconds between probes (if not configured).
+pub const DEFAULT_PROBE_INTERVAL: u64 = 1_000;
+
+/// Buckets (in seconds) of the scheduling delays.
+const DELAY_BUCKETS: [f64; 12] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// Configuration of the runtime (beyond `worker_threads` and `signature_threads`).
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// Maximum number of threads in the blocking pool (defaults to
+    /// [DEFAULT_MAX_BLOCKING_THREADS]). Blocking threads are only created when needed.
+    pub max_blocking_threads: Option<usize>,
+
+    /// Prefix of signature thread names (defaults to [DEFAULT_THREAD_NAME]). Threads are named
+    /// `<thread_name>-signature-<index>`.
+    pub thread_name: Option<String>,
+
+    /// Milliseconds between probes (defaults to [DEFAULT_PROBE_INTERVAL]; 0 disables the
+    /// [Probe]).
+    pub probe_interval: Option<u64>,
+}
+
+impl Config {
+    /// Maximum number of threads in the blocking pool.
+    pub fn max_blocking_threads(&self) -> usize {
+        self.max_blocking_threads
+            .unwrap_or(DEFAULT_MAX_BLOCKING_THREADS)
+    }
+
+    /// Interval between probes (if enabled).
+    pub fn probe_interval(&self) -> Option<Duration> {
+        match self.probe_interval.unwrap_or(DEFAULT_PROBE_INTERVAL) {
+            0 => None,
+            interval => Some(Duration::from_millis(interval)),
+        }
+    }
+
+    /// Create the pool of `threads` signature threads.
+    pub fn signature_pool(
+        &self,
+        threads: NonZeroUsize,
+    ) -> Result<ThreadPool, ThreadPoolBuildError> {
+        let name = self
+            .thread_name
+            .clone()
+            .unwrap_or_else(|| DEFAULT_THREAD_NAME.to_string());
+        ThreadPoolBuilder::new()
+            .num_threads(threads.get())
+            .thread_name(move |index| format!("{name}-signature-{index}"))
+            .build()
+            .map(Arc::new)
+    }
+}
+
+/// Measures how long tasks wait to run on the worker threads, the blocking pool, and the
+/// signature threads.
+pub struct Probe<E: Clock + Spawner + Metrics> {
+    context: E,
+    interval: Duration,
+    pool: ThreadPool,
+
+    poll_delay: Histogram,
+    blocking_delay: Histogram,
+    signature_delay: Histogram,
+}
+
+impl<E: Clock + Spawner + Metrics> Probe<E> {
+    /// Create a new [Probe] (registering its metrics) that probes every `interval`.
+    pub fn new(context: E, interval: Duration, pool: ThreadPool) -> Self {
+        let poll_delay = Histogram::new(DELAY_BUCKETS);
+        context.register(
+            "poll_delay",
+            "Time (in seconds) between a timer firing and the woken task being polled",
+            poll_delay.clone(),
+        );
+        let blocking_delay = Histogram::new(DELAY_BUCKETS);
+        context.register(
+            "blocking_delay",
+            "Time (in seconds) between spawning a blocking task and it starting",
+            blocking_delay.clone(),
+        );
+        let signature_delay = Histogram::new(DELAY_BUCKETS);
+        context.register(
+            "signature_delay",
+            "Time (in seconds) between submitting work to the signature threads and it starting",
+            signature_delay.clone(),
+        );
+        Self {
+            context,
+            interval,
+            pool,
+            poll_delay,
+            blocking_delay,
+            signature_delay,
+        }
+    }
+
+    /// Start probing.
+    pub fn start(self) -> Handle<()> {
+        self.context.clone().spawn(|_| self.run())
+    }
+
+    async fn run(self) {
+        loop {
+            // Worker threads
+            let deadline = self.context.current() + self.interval;
+            self.context.sleep_until(deadline).await;
+            let delay = self
+                .context
+                .current()
+                .duration_since(deadline)
+                .unwrap_or_default();
+            self.poll_delay.observe(delay.as_secs_f64());
+
+            // Blocking pool
+            let submitted = self.context.current();
+            let started = self
+                .context
+                .clone()
+                .shared(true)
+                .spawn(|context| async move { context.current() })
+                .await;
+            let Ok(started) = started else {
+                warn!("blocking probe failed");
+                return;
+            };
+            let delay = started.duration_since(submitted).unwrap_or_default();
+            self.blocking_delay.observe(delay.as_secs_f64());
+
+            // Signature threads
+            let (sender, receiver) = oneshot::channel();
+            let submitted = self.context.current();
+            let context = self.context.clone();
+            self.pool.spawn(move || {
+                let _ = sender.send(context.current());
+            });
+            let Ok(started) = receiver.await else {
+                warn!("signature probe failed");
+                return;
+            };
+            let delay = started.duration_since(submitted).unwrap_or_default();
+            self.signature_delay.observe(delay.as_secs_f64());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commonware_runtime::{tokio, Runner};
+    use commonware_utils::NZUsize;
+
+    #[test]
+    fn test_config() {
+        let config = Config::default();
+        assert_eq!(config.max_blocking_threads(), DEFAULT_MAX_BLOCKING_THREADS);
+        assert_eq!(
+            config.probe_interval(),
+            Some(Duration::from_millis(DEFAULT_PROBE_INTERVAL))
+        );
+
+        let config = Config {
+            max_blocking_threads: Some(16),
+            thread_name: None,
+            probe_interval: Some(0),
+        };
+        assert_eq!(config.max_blocking_threads(), 16);
+        assert_eq!(config.probe_interval(), None);
+    }
+
+    #[test]
+    fn test_signature_pool() {
+        let config = Config {
+            thread_name: Some("validator".to_string()),
+            ..Default::default()
+        };
+        let pool = config.signature_pool(NZUsize!(2)).unwrap();
+        let mut names: Vec<_> = pool.broadcast(|_| std::thread::current().name().map(String::from));
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                Some("validator-signature-0".to_string()),
+                Some("validator-signature-1".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_probe() {
+        let executor = tokio::Runner::default();
+        executor.start(|context| async move {
+            let pool = Config::default().signature_pool(NZUsize!(1)).unwrap();
+            Probe::new(
+                context.with_label("saturation"),
+                Duration::from_millis(10),
+                pool,
+            )
+            .start();
+            context.sleep(Duration::from_millis(200)).await;
+
+            // Every probe observed a delay
+            let metrics = context.encode();
+            for delay in ["poll_delay", "blocking_delay", "signature_delay"] {
+                let count = metrics
+                    .lines()
+                    .find_map(|line| line.strip_prefix(&format!("saturation_{delay}_count ")))
+                    .unwrap();
+                assert!(count.parse::<u64>().unwrap() > 0, "{delay}");
+            }
+        });
+    }
+}
@@ -0,0 +1,331 @@
+//! Deterministic simulation of an alto network.
+//!
+//! Runs a set of validators over a simulated network (in the deterministic runtime) to predict
+//! view time and finalization latency for a given validator count, link latency distribution,
+//! and block size. The same harness is used by the integration tests.
+
+use crate::{
+    engine::{self, Engine},
+    indexer::Indexer,
+};
+use alto_types::{Finalized, Notarized, Nullification, Seed, NAMESPACE};
+use commonware_consensus::{marshal, simplex::scheme::bls12381_threshold};
+use commonware_cryptography::{
+    bls12381::primitives::variant::MinSig, certificate::mocks::Fixture, ed25519::PublicKey, Signer,
+};
+use commonware_p2p::{
+    simulated::{self, Link, Network, Oracle, Receiver, Sender},
+    Manager,
+};
+use commonware_parallel::Sequential;
+use commonware_runtime::{
+    deterministic::{self, Runner},
+    Clock, Metrics, Runner as _,
+};
+use commonware_utils::{ordered::Set, SystemTimeExt, NZU32};
+use governor::Quota;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use thiserror::Error;
+
+/// (Effectively) unlimited quota for simulated channels.
+pub const QUOTA: Quota = Quota::per_second(NZU32!(u32::MAX));
+
+/// Limit the freezer table size to 1MB because the deterministic runtime stores
+/// everything in RAM.
+pub const FREEZER_TABLE_INITIAL_SIZE: u32 = 2u32.pow(14); // 1MB
+
+/// Default simulated time to wait for the required blocks to be finalized.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// A simulated channel.
+pub type Channel = (
+    Sender<PublicKey, deterministic::Context>,
+    Receiver<PublicKey>,
+);
+
+/// The channels used by a validator (pending, recovered, resolver, broadcast, backfill).
+pub type Channels = (Channel, Channel, Channel, Channel, Channel);
+
+/// Registers all validators using the oracle.
+pub async fn register_validators(
+    oracle: &mut Oracle<PublicKey, deterministic::Context>,
+    validators: &[PublicKey],
+) -> HashMap<PublicKey, Channels> {
+    oracle
+        .manager()
+        .update(0, Set::from_iter_dedup(validators.iter().cloned()))
+        .await;
+    let mut registrations = HashMap::new();
+    for validator in validators.iter() {
+        let oracle = oracle.control(validator.clone());
+        let pending = oracle.register(0, QUOTA).await.unwrap();
+        let recovered = oracle.register(1, QUOTA).await.unwrap();
+        let resolver = oracle.register(2, QUOTA).await.unwrap();
+        let broadcast = oracle.register(3, QUOTA).await.unwrap();
+        let backfill = oracle.register(4, QUOTA).await.unwrap();
+        registrations.insert(
+            validator.clone(),
+            (pending, recovered, resolver, broadcast, backfill),
+        );
+    }
+    registrations
+}
+
+/// Links validators using the oracle.
+///
+/// The `restrict_to` function can be used to restrict the linking to certain connections,
+/// otherwise all validators will be linked to all other validators.
+pub async fn link_validators(
+    oracle: &mut Oracle<PublicKey, deterministic::Context>,
+    validators: &[PublicKey],
+    link: Link,
+    restrict_to: Option<fn(usize, usize, usize) -> bool>,
+) {
+    for (i1, v1) in validators.iter().enumerate() {
+        for (i2, v2) in validators.iter().enumerate() {
+            // Ignore self
+            if v2 == v1 {
+                continue;
+            }
+
+            // Restrict to certain connections
+            if let Some(f) = restrict_to {
+                if !f(validators.len(), i1, i2) {
+                    continue;
+                }
+            }
+
+            // Add link
+            oracle
+                .add_link(v1.clone(), v2.clone(), link.clone())
+                .await
+                .unwrap();
+        }
+    }
+}
+
+/// Configuration for a simulation.
+#[derive(Clone)]
+pub struct Config {
+    /// Seed for the deterministic runtime.
+    pub seed: u64,
+
+    /// Number of validators.
+    pub validators: u32,
+
+    /// Link between every pair of validators.
+    pub link: Link,
+
+    /// Size (in bytes) of each block.
+    ///
+    /// alto blocks carry no transactions, so the time to transmit `block_size` bytes at
+    /// `bandwidth` is added to the latency of every link (a conservative estimate, as it also
+    /// applies to votes and certificates).
+    pub block_size: usize,
+
+    /// Bandwidth (in bytes per second) of each link.
+    pub bandwidth: usize,
+
+    /// Number of blocks every validator must finalize before the simulation ends.
+    pub blocks: u64,
+
+    /// Simulated time to wait for every validator to finalize `blocks` before giving up.
+    pub timeout: Duration,
+}
+
+/// Errors that can occur during a simulation.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("timed out after {elapsed:?} (lowest processed height: {height})")]
+    Timeout { elapsed: Duration, height: u64 },
+}
+
+/// Results of a simulation.
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// Simulated time elapsed until every validator finalized the required blocks.
+    pub elapsed: Duration,
+
+    /// Highest view reached by any validator.
+    pub views: u64,
+
+    /// Time between each block's proposal and its finalization at each validator (sorted).
+    pub finalization: Vec<Duration>,
+}
+
+impl Report {
+    /// Average time per view.
+    pub fn view_time(&self) -> Duration {
+        self.elapsed / self.views.max(1) as u32
+    }
+
+    /// Finalization latency at percentile `p` (in range \[0,100\]).
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.finalization.is_empty() {
+            return None;
+        }
+        let index = ((p / 100.0) * (self.finalization.len() - 1) as f64).round() as usize;
+        Some(self.finalization[index.min(self.finalization.len() - 1)])
+    }
+}
+
+/// An [Indexer] that records the finalization latency of each block.
+#[derive(Clone)]
+struct Recorder {
+    context: deterministic::Context,
+    latencies: Arc<Mutex<Vec<Duration>>>,
+}
+
+impl Indexer for Recorder {
+    type Error = std::io::Error;
+
+    async fn seed_upload(&self, _: Seed) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn notarized_upload(&self, _: Notarized) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn finalized_upload(&self, finalized: Finalized) -> Result<(), Self::Error> {
+        let now = self.context.current().epoch_millis();
+        let latency = Duration::from_millis(now.saturating_sub(finalized.block.timestamp));
+        self.latencies.lock().unwrap().push(latency);
+        Ok(())
+    }
+
+    async fn nullification_upload(&self, _: Nullification) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Returns the value of every metric ending with `suffix` (for validators only).
+fn values(metrics: &str, suffix: &str) -> Vec<u64> {
+    metrics
+        .lines()
+        .filter(|line| line.starts_with("validator_"))
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let metric = parts.next()?;
+            let value = parts.next()?;
+            metric.ends_with(suffix).then(|| value.parse().ok())?
+        })
+        .collect()
+}
+
+/// Run a simulation (returning [Error::Timeout] if the required blocks are not finalized within
+/// `timeout`).
+pub fn run(cfg: Config) -> Result<Report, Error> {
+    let executor = Runner::from(deterministic::Config::default().with_seed(cfg.seed));
+    executor.start(|mut context| async move {
+        // Create simulated network
+        let (network, mut oracle) = Network::new(
+            context.with_label("network"),
+            simulated::Config {
+                max_size: 1024 * 1024,
+                disconnect_on_block: true,
+                tracked_peer_sets: Some(1),
+            },
+        );
+        network.start();
+
+        // Register participants
+        let Fixture {
+            schemes,
+            private_keys,
+            participants,
+            ..
+        } = bls12381_threshold::fixture::<MinSig, _>(&mut context, NAMESPACE, cfg.validators);
+        let mut registrations = register_validators(&mut oracle, &participants).await;
+        let participants_set = Set::from_iter_dedup(participants.clone());
+
+        // Link all validators (accounting for block transmission time)
+        let transmission = Duration::from_secs_f64(cfg.block_size as f64 / cfg.bandwidth as f64);
+        let link = Link {
+            latency: cfg.link.latency + transmission,
+            ..cfg.link
+        };
+        link_validators(&mut oracle, &participants, link, None).await;
+
+        // Start validators
+        let latencies = Arc::new(Mutex::new(Vec::new()));
+        let start = context.current();
+        for (signer, scheme) in private_keys.into_iter().zip(schemes) {
+            let public_key = signer.public_key();
+            let uid = format!("validator_{public_key}");
+            let recorder = Recorder {
+                context: context.clone(),
+                latencies: latencies.clone(),
+            };
+            let config = engine::Config::builder(
+                oracle.control(public_key.clone()),
+                public_key.clone(),
+                scheme.polynomial().clone(),
+                scheme.share().cloned(),
+                participants_set.clone(),
+                Sequential,
+            )
+            .with_partition_prefix(&uid)
+            .with_freezer_table_initial_size(FREEZER_TABLE_INITIAL_SIZE)
+            .with_mailbox_size(1024)
+            .with_indexer(recorder)
+            .build()
+            .unwrap();
+            let engine = Engine::new(context.with_label(&uid), config).await;
+
+            // Configure marshal resolver
+            let (pending, recovered, resolver, broadcast, backfill) =
+                registrations.remove(&public_key).unwrap();
+            let marshal_resolver_cfg = marshal::resolver::p2p::Config {
+                public_key: public_key.clone(),
+                manager: oracle.manager(),
+                blocker: oracle.control(public_key.clone()),
+                mailbox_size: 1024,
+                initial: Duration::from_secs(1),
+                timeout: Duration::from_secs(2),
+                fetch_retry_timeout: Duration::from_millis(100),
+                priority_requests: false,
+                priority_responses: false,
+            };
+            let marshal_resolver =
+                marshal::resolver::p2p::init(&context, marshal_resolver_cfg, backfill);
+
+            // Start engine
+            engine.start(pending, recovered, resolver, broadcast, marshal_resolver);
+        }
+
+        // Wait for all validators to finalize the required blocks
+        let views = loop {
+            let metrics = context.encode();
+            let heights = values(&metrics, "_marshal_processed_height");
+            if heights.len() == cfg.validators as usize
+                && heights.iter().all(|height| *height >= cfg.blocks)
+            {
+                break values(&metrics, "_current_view")
+                    .into_iter()
+                    .max()
+                    .unwrap_or_default();
+            }
+            let elapsed = context.current().duration_since(start).unwrap();
+            if elapsed >= cfg.timeout {
+                let height = heights.into_iter().min().unwrap_or_default();
+                return Err(Error::Timeout { elapsed, height });
+            }
+            context.sleep(Duration::from_millis(100)).await;
+        };
+        let elapsed = context.current().duration_since(start).unwrap();
+
+        // Summarize
+        let mut finalization = latencies.lock().unwrap().clone();
+        finalization.sort();
+        Ok(Report {
+            elapsed,
+            views,
+            finalization,
+        })
+    })
+}
@@ -0,0 +1,118 @@
+//! Clock skew detection.
+//!
+//! Blocks are only verified if their timestamp is not too far ahead of the local clock, so a
+//! validator whose clock drifts will silently reject (or have others reject) valid proposals.
+//! The [Detector] tracks the median offset between the timestamps of blocks proposed by peers
+//! and the local clock (at verification) and warns when peers are more than [THRESHOLD] ahead.
+//!
+//! Blocks are always received some time after they are proposed, so negative offsets (peers
+//! behind the local clock) are expected and only reflect propagation and verification latency.
+
+use commonware_runtime::Metrics;
+use prometheus_client::metrics::gauge::Gauge;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+use tracing::{info, warn};
+
+/// Number of recent offsets to consider.
+const WINDOW: usize = 64;
+
+/// Minimum number of offsets required before reporting skew.
+const MIN_SAMPLES: usize = 8;
+
+/// Median offset (in milliseconds) at which the local clock is considered skewed.
+pub const THRESHOLD: i64 = 250;
+
+struct State {
+    offsets: VecDeque<i64>,
+    skewed: bool,
+}
+
+/// Tracks the offset between peer block timestamps and the local clock.
+#[derive(Clone)]
+pub struct Detector {
+    state: Arc<Mutex<State>>,
+
+    median: Gauge,
+}
+
+impl Default for Detector {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                offsets: VecDeque::with_capacity(WINDOW),
+                skewed: false,
+            })),
+            median: Gauge::default(),
+        }
+    }
+}
+
+impl Detector {
+    /// Create a new [Detector] (registering its metrics).
+    pub fn new(context: impl Metrics) -> Self {
+        let detector = Self::default();
+        context.register(
+            "median_offset",
+            "Median offset (in milliseconds) of peer block timestamps from the local clock",
+            detector.median.clone(),
+        );
+        detector
+    }
+
+    /// Record the timestamp of a block proposed by a peer, observed at local time `now`
+    /// (both in milliseconds since the epoch).
+    ///
+    /// A positive offset means peers are ahead of the local clock.
+    pub fn observe(&self, timestamp: u64, now: u64) {
+        let offset = timestamp as i64 - now as i64;
+        let mut state = self.state.lock().unwrap();
+        if state.offsets.len() == WINDOW {
+            state.offsets.pop_front();
+        }
+        state.offsets.push_back(offset);
+        if state.offsets.len() < MIN_SAMPLES {
+            return;
+        }
+
+        // Update median
+        let median = median(&state.offsets);
+        self.median.set(median);
+
+        // Warn on transitions
+        let skewed = median > THRESHOLD;
+        if skewed && !state.skewed {
+            warn!(
+                median,
+                threshold = THRESHOLD,
+                "local clock appears skewed relative to peers (check NTP)"
+            );
+        } else if !skewed && state.skewed {
+            info!(median, "local clock no longer skewed");
+        }
+        state.skewed = skewed;
+    }
+
+    /// Returns the median offset (in milliseconds), if enough blocks have been observed.
+    pub fn median(&self) -> Option<i64> {
+        let state = self.state.lock().unwrap();
+        if state.offsets.len() < MIN_SAMPLES {
+            return None;
+        }
+        Some(median(&state.offsets))
+    }
+
+    /// Returns true if the local clock is considered skewed (peers are ahead of it).
+    pub fn skewed(&self) -> bool {
+        self.state.lock().unwrap().skewed
+    }
+}
+
+/// Compute the median of a non-empty set of offsets.
+fn median(offsets: &VecDeque<i64>) -> i64 {
+    let mut sorted: Vec<_> = offsets.iter().copied().collect();
+    sorted.sort_unstable();
+    sorted[sorted.len() / 2]
+}
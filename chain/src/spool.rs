@@ -0,0 +1,385 @@
+//! Durable retries of failed indexer uploads.
+//!
+//! The [crate::indexer::Pusher] uploads each artifact once, so an upload that fails (because the
+//! indexer is down or unreachable) would otherwise leave a gap in the indexer. The [Spool]
+//! instead appends every failed artifact to a journal (synced before it is retried) and retries
+//! it with exponential backoff (starting at [MIN_BACKOFF], up to [Config::max_backoff]) until
+//! the indexer accepts it. Artifacts the indexer rejects outright (see [Indexer::retryable]) are
+//! dropped instead of retried.
+//!
+//! Artifacts still waiting when the validator stops are retried after it restarts. The journal
+//! is pruned by section, so some artifacts that were already accepted may be uploaded again
+//! after a restart (which the indexer ignores).
+
+use crate::indexer::{Artifact, Failure, Indexer};
+use alto_types::{DigestMode, Finalized, Kind, Notarized, Nullification, Seed};
+use commonware_codec::{Decode, DecodeExt, RangeCfg};
+use commonware_macros::select;
+use commonware_runtime::{buffer::PoolRef, Clock, Handle, Metrics, Spawner, Storage};
+use commonware_storage::journal::contiguous::variable::{Config as JournalConfig, Journal};
+use commonware_utils::{NZUsize, NZU64};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{self, Either},
+    StreamExt,
+};
+use prometheus_client::metrics::{counter::Counter, gauge::Gauge};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    num::{NonZeroU64, NonZeroUsize},
+    time::{Duration, SystemTime},
+};
+use tracing::{debug, error, info, warn};
+
+/// Default maximum number of artifacts to hold for retry.
+pub const DEFAULT_MAX_ITEMS: usize = 10_000;
+
+/// Default maximum delay (in milliseconds) between retries of an artifact.
+pub const DEFAULT_MAX_BACKOFF: u64 = 60_000;
+
+/// Delay before the first retry of an artifact (doubled after every failed retry).
+pub const MIN_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Number of artifacts stored in each section of the journal (the granularity of pruning).
+const ITEMS_PER_SECTION: NonZeroU64 = NZU64!(128);
+
+/// Number of bytes to buffer when replaying the journal.
+const REPLAY_BUFFER: NonZeroUsize = NZUsize!(1024 * 1024);
+
+/// Configuration for retries of failed uploads.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// Maximum number of artifacts to hold for retry, dropping the oldest once exceeded
+    /// (defaults to [DEFAULT_MAX_ITEMS]).
+    pub max_items: Option<usize>,
+
+    /// Maximum delay (in milliseconds) between retries of an artifact (defaults to
+    /// [DEFAULT_MAX_BACKOFF]).
+    pub max_backoff: Option<u64>,
+}
+
+impl Config {
+    fn max_items(&self) -> usize {
+        self.max_items.unwrap_or(DEFAULT_MAX_ITEMS).max(1)
+    }
+
+    fn max_backoff(&self) -> Duration {
+        Duration::from_millis(self.max_backoff.unwrap_or(DEFAULT_MAX_BACKOFF)).max(MIN_BACKOFF)
+    }
+}
+
+/// Encode an artifact as its [Kind] followed by its encoding (like a consensus websocket frame).
+fn frame(artifact: &Artifact) -> Vec<u8> {
+    let mut frame = vec![artifact.kind() as u8];
+    frame.extend_from_slice(&artifact.encode());
+    frame
+}
+
+/// Decode an artifact encoded with [frame].
+fn unframe(frame: &[u8], digest_mode: &DigestMode) -> Option<Artifact> {
+    let (kind, data) = frame.split_first()?;
+    let artifact = match Kind::from_u8(*kind)? {
+        Kind::Seed => Artifact::Seed(Seed::decode(data).ok()?),
+        Kind::Notarization => Artifact::Notarized(Notarized::decode_cfg(data, digest_mode).ok()?),
+        Kind::Finalization => Artifact::Finalized(Finalized::decode_cfg(data, digest_mode).ok()?),
+        Kind::Nullification => Artifact::Nullification(Nullification::decode(data).ok()?),
+    };
+    Some(artifact)
+}
+
+/// Sends failed uploads to a [Spool].
+#[derive(Clone)]
+pub struct Mailbox {
+    sender: mpsc::UnboundedSender<Artifact>,
+}
+
+impl Mailbox {
+    /// Hold `artifact` for retry.
+    pub fn push(&self, artifact: Artifact) {
+        if self.sender.unbounded_send(artifact).is_err() {
+            warn!("spool stopped, dropping failed upload");
+        }
+    }
+}
+
+/// The outcome of a retry.
+enum Outcome {
+    Accepted,
+    Failed,
+    Rejected,
+}
+
+/// An artifact held for retry.
+struct Entry {
+    artifact: Artifact,
+    attempts: u32,
+    retry_at: SystemTime,
+}
+
+/// Retries failed uploads to an [Indexer] (see the [module](self) documentation).
+pub struct Spool<E: Clock + Spawner + Storage + Metrics, I: Indexer> {
+    context: E,
+    indexer: I,
+    journal: Journal<E, Vec<u8>>,
+    receiver: mpsc::UnboundedReceiver<Artifact>,
+
+    max_items: usize,
+    max_backoff: Duration,
+
+    /// Artifacts held for retry (by position in the journal).
+    entries: BTreeMap<u64, Entry>,
+    /// Positions of held artifacts (ordered by when they should next be retried).
+    schedule: BTreeSet<(SystemTime, u64)>,
+
+    spooled: Counter,
+    retried: Counter,
+    dropped: Counter,
+    rejected: Counter,
+    pending: Gauge,
+}
+
+impl<E: Clock + Spawner + Storage + Metrics, I: Indexer> Spool<E, I> {
+    /// Open the spool stored in `partition`, restoring any artifacts still held for retry.
+    pub async fn init(
+        context: E,
+        indexer: I,
+        partition: String,
+        digest_mode: DigestMode,
+        buffer_pool: PoolRef,
+        write_buffer: NonZeroUsize,
+        config: Config,
+    ) -> (Self, Mailbox) {
+        let journal: Journal<E, Vec<u8>> = Journal::init(
+            context.with_label("journal"),
+            JournalConfig {
+                partition,
+                items_per_section: ITEMS_PER_SECTION,
+                compression: None,
+                codec_config: (RangeCfg::from(..), ()),
+                buffer_pool,
+                write_buffer,
+            },
+        )
+        .await
+        .expect("failed to initialize spool");
+
+        // Restore artifacts that were not retried before the last shutdown
+        let now = context.current();
+        let mut entries = BTreeMap::new();
+        {
+            let stream = journal
+                .replay(journal.pruning_boundary(), REPLAY_BUFFER)
+                .await
+                .expect("failed to replay spool");
+            futures::pin_mut!(stream);
+            while let Some(result) = stream.next().await {
+                let (position, frame) = result.expect("failed to read spool");
+                let Some(artifact) = unframe(&frame, &digest_mode) else {
+                    warn!(position, "skipping malformed spooled upload");
+                    continue;
+                };
+                let entry = Entry {
+                    artifact,
+                    attempts: 0,
+                    retry_at: now,
+                };
+                entries.insert(position, entry);
+            }
+        }
+        let max_items = config.max_items();
+        while entries.len() > max_items {
+            entries.pop_first();
+        }
+        if !entries.is_empty() {
+            info!(uploads = entries.len(), "restored spooled uploads");
+        }
+        let schedule = entries
+            .iter()
+            .map(|(position, entry)| (entry.retry_at, *position))
+            .collect();
+
+        // Register metrics
+        let spooled = Counter::default();
+        context.register(
+            "spooled",
+            "Number of failed uploads held for retry",
+            spooled.clone(),
+        );
+        let retried = Counter::default();
+        context.register(
+            "retried",
+            "Number of held uploads accepted by a retry",
+            retried.clone(),
+        );
+        let dropped = Counter::default();
+        context.register(
+            "dropped",
+            "Number of held uploads dropped (to stay within the limit)",
+            dropped.clone(),
+        );
+        let rejected = Counter::default();
+        context.register(
+            "rejected",
+            "Number of held uploads dropped because the indexer rejected them",
+            rejected.clone(),
+        );
+        let pending = Gauge::default();
+        context.register(
+            "pending",
+            "Number of uploads held for retry",
+            pending.clone(),
+        );
+        pending.set(entries.len() as i64);
+
+        let (sender, receiver) = mpsc::unbounded();
+        let spool = Self {
+            context,
+            indexer,
+            journal,
+            receiver,
+            max_items,
+            max_backoff: config.max_backoff(),
+            entries,
+            schedule,
+            spooled,
+            retried,
+            dropped,
+            rejected,
+            pending,
+        };
+        (spool, Mailbox { sender })
+    }
+
+    /// Start retrying held artifacts.
+    pub fn start(self) -> Handle<()> {
+        self.context.clone().spawn(|context| self.run(context))
+    }
+
+    async fn run(mut self, context: E) {
+        // Artifacts are retried one at a time
+        let mut retrying: Option<(u64, oneshot::Receiver<Outcome>)> = None;
+        loop {
+            let due = match (&retrying, self.schedule.first()) {
+                (None, Some((retry_at, _))) => Either::Left(context.sleep_until(*retry_at)),
+                _ => Either::Right(future::pending()),
+            };
+            let retried = match &mut retrying {
+                Some((_, receiver)) => Either::Left(receiver),
+                None => Either::Right(future::pending()),
+            };
+            select! {
+                artifact = self.receiver.next() => {
+                    let Some(artifact) = artifact else {
+                        debug!("spool stopped");
+                        return;
+                    };
+                    self.hold(&context, artifact).await;
+                },
+                _ = due => {
+                    let Some((_, position)) = self.schedule.pop_first() else {
+                        continue;
+                    };
+                    let artifact = self.entries[&position].artifact.clone();
+                    let indexer = self.indexer.clone();
+                    let (sender, receiver) = oneshot::channel();
+                    context.with_label("retry").spawn(move |_| async move {
+                        let outcome = match artifact.upload(&indexer).await {
+                            Ok(()) => Outcome::Accepted,
+                            Err(Failure { error, retryable }) => {
+                                debug!(?error, retryable, "retry of upload failed");
+                                match retryable {
+                                    true => Outcome::Failed,
+                                    false => Outcome::Rejected,
+                                }
+                            }
+                        };
+                        let _ = sender.send(outcome);
+                    });
+                    retrying = Some((position, receiver));
+                },
+                outcome = retried => {
+                    let (position, _) = retrying.take().expect("retry in progress");
+                    self.retried(&context, position, outcome.unwrap_or(Outcome::Failed)).await;
+                },
+            }
+        }
+    }
+
+    /// Persist `artifact` and schedule its first retry.
+    async fn hold(&mut self, context: &E, artifact: Artifact) {
+        let position = match self.journal.append(frame(&artifact)).await {
+            Ok(position) => position,
+            Err(e) => {
+                error!(?e, "failed to spool upload");
+                return;
+            }
+        };
+        if let Err(e) = self.journal.sync().await {
+            error!(?e, "failed to sync spool");
+        }
+        self.spooled.inc();
+        let retry_at = context.current() + MIN_BACKOFF;
+        self.entries.insert(
+            position,
+            Entry {
+                artifact,
+                attempts: 0,
+                retry_at,
+            },
+        );
+        self.schedule.insert((retry_at, position));
+
+        // Drop the oldest artifacts once over the limit
+        while self.entries.len() > self.max_items {
+            let (oldest, entry) = self.entries.pop_first().expect("spool is not empty");
+            self.schedule.remove(&(entry.retry_at, oldest));
+            self.dropped.inc();
+            warn!(position = oldest, "dropped spooled upload");
+        }
+        self.pending.set(self.entries.len() as i64);
+        self.prune().await;
+    }
+
+    /// Handle the `outcome` of a retry of the artifact at `position`.
+    async fn retried(&mut self, context: &E, position: u64, outcome: Outcome) {
+        // The artifact may have been dropped while it was retried
+        let Some(entry) = self.entries.get_mut(&position) else {
+            return;
+        };
+        match outcome {
+            Outcome::Accepted => {
+                self.entries.remove(&position);
+                self.retried.inc();
+                debug!(position, "spooled upload accepted");
+            }
+            Outcome::Rejected => {
+                self.entries.remove(&position);
+                self.rejected.inc();
+                warn!(position, "indexer rejected spooled upload, dropping it");
+            }
+            Outcome::Failed => {
+                entry.attempts = entry.attempts.saturating_add(1);
+                let backoff = MIN_BACKOFF
+                    .saturating_mul(1 << entry.attempts.min(16))
+                    .min(self.max_backoff);
+                entry.retry_at = context.current() + backoff;
+                self.schedule.insert((entry.retry_at, position));
+                return;
+            }
+        }
+        self.pending.set(self.entries.len() as i64);
+        self.prune().await;
+    }
+
+    /// Prune artifacts that are no longer held from the journal.
+    async fn prune(&mut self) {
+        let min = match self.entries.first_key_value() {
+            Some((position, _)) => *position,
+            None => self.journal.size(),
+        };
+        if let Err(e) = self.journal.prune(min).await {
+            error!(?e, min, "failed to prune spool");
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Guardrails for the size of the storage directory.
+//!
+//! Unless [crate::engine::Config::retain_blocks] is set, finalized blocks and finalizations are
+//! kept forever (in immutable archives), so storage grows with the length of the chain. When set,
+//! they are stored in prunable archives instead and heights more than `retain_blocks` below the
+//! last processed block are pruned (a section at a time). Either way, the only safe response to a
+//! full disk is to stop producing new data before a journal write fails mid-flight. When usage
+//! approaches the configured limit, the [Monitor] warns loudly and trips a [Guardrail] that the
+//! [crate::application::Application] checks before proposing.
+//!
+//! Because corrupted records are only detected when read, [verify] can be used at startup to
+//! read every stored item and report the sections that fail checksum validation. Corrupted
+//! sections can then be moved aside with [quarantine] (so they are no longer served but remain
+//! available for inspection).
+
+use commonware_runtime::{Blob, Clock, Metrics, Spawner, Storage};
+use commonware_storage::archive::{Archive, Identifier};
+use prometheus_client::metrics::gauge::Gauge;
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tracing::{error, info, warn};
+
+/// How often to measure the size of the storage directory.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Fraction of the limit (in percent) at which to start warning.
+const WARN_PERCENT: u64 = 90;
+
+/// Fraction of the limit (in percent) at which to refuse new proposals.
+const REFUSE_PERCENT: u64 = 95;
+
+/// Shared signal indicating whether the storage directory is (nearly) full.
+#[derive(Clone, Default)]
+pub struct Guardrail {
+    exhausted: Arc<AtomicBool>,
+}
+
+impl Guardrail {
+    /// Returns true if new data should not be produced.
+    pub fn exhausted(&self) -> bool {
+        self.exhausted.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set(&self, exhausted: bool) {
+        self.exhausted.store(exhausted, Ordering::Relaxed);
+    }
+}
+
+/// Periodically measures the size of the storage directory and trips a [Guardrail]
+/// when it approaches `max_usage` bytes.
+pub struct Monitor<E: Clock + Spawner + Metrics> {
+    context: E,
+    directory: PathBuf,
+    max_usage: u64,
+    guardrail: Guardrail,
+
+    usage: Gauge<u64, AtomicU64>,
+}
+
+impl<E: Clock + Spawner + Metrics> Monitor<E> {
+    /// Create a new [Monitor] and the [Guardrail] it controls.
+    pub fn new(context: E, directory: PathBuf, max_usage: u64) -> (Self, Guardrail) {
+        let usage = Gauge::default();
+        context.register(
+            "usage",
+            "Size of the storage directory (in bytes)",
+            usage.clone(),
+        );
+        let limit: Gauge<u64, AtomicU64> = Gauge::default();
+        limit.set(max_usage);
+        context.register(
+            "limit",
+            "Maximum size of the storage directory (in bytes)",
+            limit,
+        );
+
+        let guardrail = Guardrail::default();
+        (
+            Self {
+                context,
+                directory,
+                max_usage,
+                guardrail: guardrail.clone(),
+                usage,
+            },
+            guardrail,
+        )
+    }
+
+    /// Start the [Monitor].
+    pub fn start(self) -> commonware_runtime::Handle<()> {
+        self.context.clone().spawn(|_| self.run())
+    }
+
+    async fn run(self) {
+        let warn_at = self.max_usage / 100 * WARN_PERCENT;
+        let refuse_at = self.max_usage / 100 * REFUSE_PERCENT;
+        loop {
+            // Walking the directory may block, so run it on the blocking pool
+            let directory = self.directory.clone();
+            let result = self
+                .context
+                .clone()
+                .shared(true)
+                .spawn(move |_| async move { directory_size(&directory) })
+                .await;
+            match result {
+                Ok(Ok(usage)) => {
+                    self.usage.set(usage);
+                    let exhausted = usage >= refuse_at;
+                    if exhausted {
+                        error!(
+                            usage,
+                            limit = self.max_usage,
+                            "storage nearly exhausted: refusing to propose"
+                        );
+                    } else if usage >= warn_at {
+                        warn!(usage, limit = self.max_usage, "storage approaching limit");
+                    }
+                    if self.guardrail.exhausted() && !exhausted {
+                        info!(
+                            usage,
+                            limit = self.max_usage,
+                            "storage below limit: resuming"
+                        );
+                    }
+                    self.guardrail.set(exhausted);
+                }
+                Ok(Err(e)) => warn!(?e, "failed to measure storage directory"),
+                Err(e) => warn!(?e, "storage measurement task failed"),
+            }
+            self.context.sleep(CHECK_INTERVAL).await;
+        }
+    }
+}
+
+/// Compute the total size (in bytes) of all files in a directory (recursively).
+pub fn directory_size(path: &Path) -> io::Result<u64> {
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            total += directory_size(&entry.path())?;
+        } else {
+            total += metadata.len();
+        }
+    }
+    Ok(total)
+}
+
+/// Outcome of [verify]ing an [Archive].
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Number of items read.
+    pub checked: u64,
+
+    /// Sections containing at least one item that could not be read.
+    pub corrupted: BTreeSet<u64>,
+}
+
+/// Read every item stored in `archive`, recording the sections (of `items_per_section` items)
+/// that contain corrupted or missing records.
+pub async fn verify<A: Archive>(archive: &A, items_per_section: u64) -> Report {
+    let mut report = Report::default();
+    let ranges: Vec<_> = archive.ranges().collect();
+    for (start, end) in ranges {
+        for index in start..=end {
+            report.checked += 1;
+            match archive.get(Identifier::Index(index)).await {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    warn!(index, "missing item");
+                    report.corrupted.insert(index / items_per_section);
+                }
+                Err(e) => {
+                    warn!(index, ?e, "corrupted item");
+                    report.corrupted.insert(index / items_per_section);
+                }
+            }
+        }
+    }
+    report
+}
+
+/// Suffix of the partition that quarantined sections are moved to.
+pub const QUARANTINE_SUFFIX: &str = "-quarantine";
+
+/// Move the blobs of `sections` from the ordinal `partition` (the index of an immutable
+/// archive) to `<partition>-quarantine`.
+///
+/// The items in a quarantined section are no longer indexed (so they are treated as missing
+/// when the archive is next opened). The archive must not be open.
+pub async fn quarantine<E: Storage>(
+    context: &E,
+    partition: &str,
+    sections: &BTreeSet<u64>,
+) -> Result<(), commonware_runtime::Error> {
+    let destination = format!("{partition}{QUARANTINE_SUFFIX}");
+    for section in sections {
+        let name = section.to_be_bytes();
+        let (blob, len) = context.open(partition, &name).await?;
+        let data = blob.read_at(vec![0u8; len as usize], 0).await?;
+        let (copy, _) = context.open(&destination, &name).await?;
+        copy.write_at(data, 0).await?;
+        copy.sync().await?;
+        context.remove(partition, Some(&name)).await?;
+        warn!(partition, section, "quarantined section");
+    }
+    Ok(())
+}
@@ -0,0 +1,378 @@
+//! Consensus view visibility.
+//!
+//! A flat finalized height does not distinguish a network that is slow to finalize from one
+//! stuck in a view (where each leader is nullified in turn). The [Tracker] observes consensus
+//! certificates to follow the current view, its elected leader, how long the view has been
+//! active, how many consecutive views were nullified, and which participants voted recently,
+//! so stalls can be detected as they happen.
+//!
+//! Every nullified view is also attributed to the leader elected for it (logged along with the
+//! number of consecutive nullifications and counted per leader), so operators can tell which
+//! validator is stalling the chain.
+//!
+//! Views restart in every epoch (see [crate::epochs]), so the tracker follows the latest epoch
+//! it has observed a certificate from (electing leaders among that epoch's participants) and
+//! ignores activity from earlier epochs.
+
+use alto_types::{Activity, PublicKey, Signature, EPOCH};
+use commonware_consensus::{
+    simplex::elector::Random,
+    simplex::types::Attributable,
+    types::{Epoch, Round, View},
+    Reporter,
+};
+use commonware_cryptography::bls12381::primitives::variant::MinSig;
+use commonware_runtime::{Clock, Handle, Metrics, Spawner};
+use commonware_utils::ordered::Set;
+use prometheus_client::metrics::{counter::Counter, family::Family, gauge::Gauge};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use tracing::{debug, info, warn};
+
+/// How often to refresh the time spent in the current view.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of recent views to count voting participants over.
+pub const VOTER_WINDOW: u64 = 10;
+
+/// Number of consecutive nullified views after which a stall is reported.
+pub const STALL_THRESHOLD: u64 = 3;
+
+/// Snapshot of the current view.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Status {
+    /// Epoch of the current view.
+    pub epoch: u64,
+
+    /// Current view (`0` until a certificate is observed).
+    pub view: u64,
+
+    /// Leader elected for the current view (if known).
+    pub leader: Option<PublicKey>,
+
+    /// Time since the current view was entered.
+    pub time_in_view: Duration,
+
+    /// Number of consecutive views that were nullified (reset when a view is notarized or
+    /// finalized).
+    pub nullified: u64,
+
+    /// Leaders of the last (at most [VOTER_WINDOW]) consecutive views that were nullified
+    /// (oldest first, if known).
+    pub nullified_leaders: Vec<PublicKey>,
+
+    /// Number of participants that voted in the last [VOTER_WINDOW] views.
+    pub voters: usize,
+}
+
+struct State {
+    epoch: Epoch,
+    view: u64,
+    leader: Option<PublicKey>,
+    started: SystemTime,
+    nullified: u64,
+    nullified_leaders: VecDeque<PublicKey>,
+    votes: BTreeMap<u64, BTreeSet<u32>>,
+}
+
+impl State {
+    /// Oldest view whose votes are counted.
+    fn horizon(&self) -> u64 {
+        self.view.saturating_sub(VOTER_WINDOW)
+    }
+
+    /// Number of distinct participants that voted since the horizon.
+    fn voters(&self) -> usize {
+        self.votes.values().flatten().collect::<BTreeSet<_>>().len()
+    }
+}
+
+/// Tracks the current view and leader from consensus activity.
+#[derive(Clone)]
+pub struct Tracker<E: Clock> {
+    context: E,
+    participants: BTreeMap<Epoch, Set<PublicKey>>,
+    state: Arc<Mutex<State>>,
+
+    epoch: Gauge,
+    view: Gauge,
+    leader: Gauge,
+    time_in_view: Gauge,
+    nullified: Gauge,
+    nullified_by_leader: Family<Vec<(String, String)>, Counter>,
+    voters: Gauge,
+}
+
+impl<E: Clock + Metrics> Tracker<E> {
+    /// Create a new [Tracker] for `participants` (of [EPOCH]).
+    pub fn new(context: E, participants: Set<PublicKey>) -> Self {
+        let epoch = Gauge::default();
+        context.register("epoch", "Epoch of the current view", epoch.clone());
+        let view = Gauge::default();
+        context.register("view", "Current consensus view", view.clone());
+        let leader = Gauge::default();
+        context.register(
+            "leader",
+            "Index of the leader elected for the current view (-1 if unknown)",
+            leader.clone(),
+        );
+        leader.set(-1);
+        let time_in_view = Gauge::default();
+        context.register(
+            "time_in_view",
+            "Time (in milliseconds) spent in the current view",
+            time_in_view.clone(),
+        );
+        let nullified = Gauge::default();
+        context.register(
+            "nullified",
+            "Number of consecutive views that were nullified",
+            nullified.clone(),
+        );
+        let nullified_by_leader = Family::<Vec<(String, String)>, Counter>::default();
+        context.register(
+            "nullified_by_leader",
+            "Number of views that were nullified (by the leader elected for them)",
+            nullified_by_leader.clone(),
+        );
+        let voters = Gauge::default();
+        context.register(
+            "voters",
+            "Number of participants that voted in recent views",
+            voters.clone(),
+        );
+        let started = context.current();
+        Self {
+            context,
+            participants: BTreeMap::from([(EPOCH, participants)]),
+            state: Arc::new(Mutex::new(State {
+                epoch: EPOCH,
+                view: 0,
+                leader: None,
+                started,
+                nullified: 0,
+                nullified_leaders: VecDeque::new(),
+                votes: BTreeMap::new(),
+            })),
+            epoch,
+            view,
+            leader,
+            time_in_view,
+            nullified,
+            nullified_by_leader,
+            voters,
+        }
+    }
+}
+
+impl<E: Clock> Tracker<E> {
+    /// Elect leaders among `participants` in `epoch` (and later epochs, unless configured).
+    pub fn with_epoch(mut self, epoch: Epoch, participants: Set<PublicKey>) -> Self {
+        self.participants.insert(epoch, participants);
+        self
+    }
+}
+
+impl<E: Clock + Spawner + Metrics> Tracker<E> {
+    /// Start refreshing the time spent in the current view (even if no activity is observed).
+    pub fn start(&self) -> Handle<()> {
+        let tracker = self.clone();
+        self.context
+            .with_label("sampler")
+            .spawn(move |context| async move {
+                loop {
+                    tracker.refresh();
+                    context.sleep(SAMPLE_INTERVAL).await;
+                }
+            })
+    }
+}
+
+impl<E: Clock> Tracker<E> {
+    /// Get the [Status] of the current view.
+    pub fn status(&self) -> Status {
+        self.snapshot(&self.state.lock().unwrap())
+    }
+
+    /// Get the [Status] of the current view, unless it is being updated (useful where blocking
+    /// could deadlock, like in a panic hook).
+    pub fn try_status(&self) -> Option<Status> {
+        let state = self.state.try_lock().ok()?;
+        Some(self.snapshot(&state))
+    }
+
+    /// Get the participants of the current epoch (in order) and whether each voted in the last
+    /// [VOTER_WINDOW] views.
+    pub fn participants(&self) -> Vec<(PublicKey, bool)> {
+        let state = self.state.lock().unwrap();
+        let voted: BTreeSet<_> = state.votes.values().flatten().collect();
+        self.participants
+            .range(..=state.epoch)
+            .next_back()
+            .map(|(_, participants)| participants)
+            .expect("participants of the first epoch are known")
+            .iter()
+            .enumerate()
+            .map(|(index, participant)| (participant.clone(), voted.contains(&(index as u32))))
+            .collect()
+    }
+
+    fn snapshot(&self, state: &State) -> Status {
+        Status {
+            epoch: state.epoch.get(),
+            view: state.view,
+            leader: state.leader.clone(),
+            time_in_view: self.elapsed(state.started),
+            nullified: state.nullified,
+            nullified_leaders: state.nullified_leaders.iter().cloned().collect(),
+            voters: state.voters(),
+        }
+    }
+
+    /// Update the time spent in the current view.
+    fn refresh(&self) {
+        let started = self.state.lock().unwrap().started;
+        self.time_in_view
+            .set(self.elapsed(started).as_millis() as i64);
+    }
+
+    /// Time elapsed since `started`.
+    fn elapsed(&self, started: SystemTime) -> Duration {
+        self.context
+            .current()
+            .duration_since(started)
+            .unwrap_or_default()
+    }
+
+    /// Enter the view after `round` (if not already past it), electing its leader using the
+    /// seed signature of the certificate that ended `round`.
+    fn advance(&self, round: Round, seed: &Signature, nullified: bool) {
+        let mut state = self.state.lock().unwrap();
+        let (epoch, view) = (round.epoch(), round.view().get());
+        if epoch < state.epoch {
+            return;
+        }
+        if epoch > state.epoch {
+            // Views restart in a new epoch
+            state.epoch = epoch;
+            state.view = 0;
+            state.leader = None;
+            state.nullified = 0;
+            state.nullified_leaders.clear();
+            state.votes.clear();
+            self.epoch.set(epoch.get() as i64);
+            info!(epoch = epoch.get(), "entered epoch");
+        }
+        let next = view + 1;
+        if next <= state.view {
+            return;
+        }
+        let participants = self
+            .participants
+            .range(..=epoch)
+            .next_back()
+            .map(|(_, participants)| participants)
+            .expect("participants of the first epoch are known");
+        let round = Round::new(epoch, View::new(next));
+        let n = participants.len() as u32;
+        let index = Random::select_leader::<MinSig>(round, n, Some(*seed));
+        let leader = participants.get(index.get() as usize).cloned();
+
+        // Attribute the nullified view to its leader (if it was entered)
+        let stalled = match nullified && state.view == view {
+            true => state.leader.clone(),
+            false => None,
+        };
+        if nullified {
+            state.nullified += 1;
+        } else {
+            state.nullified = 0;
+            state.nullified_leaders.clear();
+        }
+        if let Some(stalled) = &stalled {
+            self.nullified_by_leader
+                .get_or_create(&vec![("leader".to_string(), stalled.to_string())])
+                .inc();
+            state.nullified_leaders.push_back(stalled.clone());
+            if state.nullified_leaders.len() as u64 > VOTER_WINDOW {
+                state.nullified_leaders.pop_front();
+            }
+            info!(
+                view,
+                leader = %stalled,
+                nullified = state.nullified,
+                "view nullified"
+            );
+        }
+        state.view = next;
+        state.leader = leader;
+        state.started = self.context.current();
+        self.view.set(next as i64);
+        self.leader.set(index.get() as i64);
+        self.nullified.set(state.nullified as i64);
+
+        // Forget votes that fell out of the window
+        let horizon = state.horizon();
+        state.votes = state.votes.split_off(&horizon);
+        self.voters.set(state.voters() as i64);
+        debug!(view = next, leader = ?state.leader, "entered view");
+        if nullified && state.nullified == STALL_THRESHOLD {
+            let leaders: Vec<_> = state
+                .nullified_leaders
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            warn!(
+                view = next,
+                nullified = state.nullified,
+                ?leaders,
+                "consecutive views nullified"
+            );
+        }
+    }
+}
+
+impl<E: Clock> Tracker<E> {
+    /// Record a vote by `signer` in `round` (if within the window of the current epoch).
+    fn vote(&self, round: Round, signer: u32) {
+        let mut state = self.state.lock().unwrap();
+        let view = round.view().get();
+        if round.epoch() != state.epoch || view < state.horizon() {
+            return;
+        }
+        if state.votes.entry(view).or_default().insert(signer) {
+            self.voters.set(state.voters() as i64);
+        }
+    }
+}
+
+impl<E: Clock> Reporter for Tracker<E> {
+    type Activity = Activity;
+
+    async fn report(&mut self, activity: Self::Activity) {
+        match &activity {
+            Activity::Notarization(notarization) => self.advance(
+                notarization.round(),
+                &notarization.certificate.seed_signature,
+                false,
+            ),
+            Activity::Finalization(finalization) => self.advance(
+                finalization.round(),
+                &finalization.certificate.seed_signature,
+                false,
+            ),
+            Activity::Nullification(nullification) => self.advance(
+                nullification.round(),
+                &nullification.certificate.seed_signature,
+                true,
+            ),
+            Activity::Notarize(notarize) => self.vote(notarize.round(), notarize.signer().get()),
+            Activity::Nullify(nullify) => self.vote(nullify.round(), nullify.signer().get()),
+            Activity::Finalize(finalize) => self.vote(finalize.round(), finalize.signer().get()),
+            _ => {}
+        }
+    }
+}
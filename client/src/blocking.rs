@@ -0,0 +1,127 @@
+//! Blocking wrapper around [crate::Client] (requires the `blocking` feature).
+//!
+//! Scripts and build tooling that only need a few one-shot queries can use [Client] without
+//! setting up an async runtime: it drives each request to completion on its own current-thread
+//! runtime. Every artifact is verified exactly like the async client does.
+//!
+//! Methods block the calling thread, so they must not be called from an async task (use
+//! [crate::Client] there instead, or move the call to a blocking thread).
+
+use crate::{verify, Error, IndexQuery, Query};
+use alto_types::{Consistency, Finalized, Payload, Seed};
+use commonware_parallel::Strategy;
+use tokio::runtime::{Builder, Runtime};
+
+/// A [crate::Client] whose methods block until a response is received.
+pub struct Client<S: Strategy> {
+    inner: crate::Client<S>,
+    runtime: Runtime,
+}
+
+impl<S: Strategy> Client<S> {
+    /// Wrap `client` (built with [crate::Client::new] or [crate::ClientBuilder]).
+    pub fn new(client: crate::Client<S>) -> Self {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build runtime");
+        Self {
+            inner: client,
+            runtime,
+        }
+    }
+
+    /// Get the wrapped [crate::Client].
+    pub fn inner(&self) -> &crate::Client<S> {
+        &self.inner
+    }
+
+    /// Check that the indexer is healthy.
+    pub fn health(&self) -> Result<(), Error> {
+        self.runtime.block_on(self.inner.health())
+    }
+
+    /// Get the latest finalized block.
+    pub fn tip(&self) -> Result<Finalized, Error> {
+        match self.block(Query::Latest)? {
+            Payload::Finalized(finalized) => Ok(*finalized),
+            Payload::Block(_) => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Get a block (see [crate::Client::block_get]).
+    pub fn block(&self, query: Query) -> Result<Payload, Error> {
+        self.runtime.block_on(self.inner.block_get(query))
+    }
+
+    /// Get all finalized blocks with heights in `[start, end)` (see
+    /// [crate::Client::block_get_range]).
+    pub fn blocks(&self, start: u64, end: u64) -> Result<Vec<Finalized>, Error> {
+        self.runtime
+            .block_on(self.inner.block_get_range(start, end))
+    }
+
+    /// Get a finalized block by view (see [crate::Client::finalized_get]).
+    pub fn finalized(&self, query: IndexQuery) -> Result<Finalized, Error> {
+        self.runtime.block_on(self.inner.finalized_get(query))
+    }
+
+    /// Get a seed by view (see [crate::Client::seed_get]).
+    pub fn seed(&self, query: IndexQuery) -> Result<Seed, Error> {
+        self.runtime.block_on(self.inner.seed_get(query))
+    }
+
+    /// Get a verified proof that the finalized block at height `to` descends from the block at
+    /// height `from` (see [crate::Client::consistency_get]).
+    pub fn consistency(&self, from: u64, to: u64) -> Result<Consistency, Error> {
+        self.runtime.block_on(self.inner.consistency_get(from, to))
+    }
+
+    /// Decode and verify an encoded [Finalized] block obtained out-of-band (like from
+    /// `export-finalization`) with the identities and digest mode of the client.
+    pub fn verify(&self, bytes: &[u8]) -> Result<Finalized, Error> {
+        verify::finalized(
+            &self.inner.identities,
+            &self.inner.strategy,
+            &self.inner.digest_mode,
+            bytes,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_types::{fixtures, Block};
+    use commonware_codec::Encode;
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Hasher, Sha256};
+    use commonware_parallel::Sequential;
+
+    #[test]
+    fn test_blocking() {
+        let fixture = fixtures::seeded(0, 4);
+        let identity = fixtures::identity(&fixture.schemes);
+        let client = Client::new(crate::Client::new(
+            "http://127.0.0.1:1",
+            identity,
+            Sequential,
+        ));
+
+        // Requests complete without an external runtime
+        assert!(matches!(client.health(), Err(Error::Reqwest(_))));
+        assert!(matches!(client.tip(), Err(Error::Reqwest(_))));
+
+        // Finalized blocks are verified against the identity of the client
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1);
+        let finalized = fixtures::finalized(&fixture.schemes, 1, block);
+        assert_eq!(client.verify(&finalized.encode()).unwrap(), finalized);
+        let other = fixtures::seeded(1, 4);
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1);
+        let forged = fixtures::finalized(&other.schemes, 1, block);
+        assert!(matches!(
+            client.verify(&forged.encode()),
+            Err(Error::InvalidSignature)
+        ));
+    }
+}
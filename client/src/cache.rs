@@ -0,0 +1,81 @@
+//! Content-addressed cache of blocks.
+//!
+//! A block is identified by its digest, so a block fetched once never needs to be fetched again.
+//! [crate::ClientBuilder::with_block_cache] keeps the most recently fetched blocks in memory and
+//! answers [crate::Query::Digest] lookups from it (without contacting the indexer).
+
+use alto_types::Block;
+use commonware_cryptography::{sha256::Digest, Digestible};
+use std::{
+    collections::{HashMap, VecDeque},
+    num::NonZeroUsize,
+};
+
+/// A bounded cache of blocks (keyed by digest).
+///
+/// When full, the block inserted first is evicted.
+pub(crate) struct BlockCache {
+    capacity: NonZeroUsize,
+    blocks: HashMap<Digest, Block>,
+    order: VecDeque<Digest>,
+}
+
+impl BlockCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            capacity,
+            blocks: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Get the block with `digest` (if cached).
+    pub fn get(&self, digest: &Digest) -> Option<Block> {
+        self.blocks.get(digest).cloned()
+    }
+
+    /// Cache `block` (which must have been verified against its digest).
+    pub fn insert(&mut self, block: Block) {
+        let digest = block.digest();
+        if self.blocks.insert(digest, block).is_some() {
+            return;
+        }
+        self.order.push_back(digest);
+        if self.order.len() > self.capacity.get() {
+            let evicted = self.order.pop_front().expect("cache is not empty");
+            self.blocks.remove(&evicted);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Hasher, Sha256};
+    use commonware_utils::NZUsize;
+
+    #[test]
+    fn test_eviction() {
+        let blocks: Vec<_> = (1..=3)
+            .map(|height| Block::new(Sha256::hash(b"genesis"), Height::new(height), height))
+            .collect();
+        let mut cache = BlockCache::new(NZUsize!(2));
+
+        // Cached blocks are returned by digest
+        cache.insert(blocks[0].clone());
+        cache.insert(blocks[1].clone());
+        assert_eq!(cache.get(&blocks[0].digest()), Some(blocks[0].clone()));
+        assert_eq!(cache.get(&blocks[2].digest()), None);
+
+        // Re-inserting a block does not count against capacity
+        cache.insert(blocks[0].clone());
+        assert_eq!(cache.get(&blocks[1].digest()), Some(blocks[1].clone()));
+
+        // The oldest block is evicted when full
+        cache.insert(blocks[2].clone());
+        assert_eq!(cache.get(&blocks[0].digest()), None);
+        assert_eq!(cache.get(&blocks[1].digest()), Some(blocks[1].clone()));
+        assert_eq!(cache.get(&blocks[2].digest()), Some(blocks[2].clone()));
+    }
+}
@@ -1,11 +1,57 @@
-use crate::{Client, Error, IndexQuery, Query};
-use alto_types::{Block, Finalized, Kind, Notarized, Seed};
-use commonware_codec::{DecodeExt, Encode};
+use crate::{
+    stream::Decoder, verify, Client, Error, IndexQuery, Query, API_VERSION, CURRENT_API_VERSION,
+    MAX_CONCURRENT_FETCHES, MAX_EXPORT, MAX_EXPORT_RESUMES, MAX_PREFIX_MATCHES, MAX_RANGE,
+    TRACEPARENT,
+};
+use alto_types::{
+    Ack, Block, Consistency, DigestMode, Finalization, Finalized, Genesis, IdentitySchedule, Kind,
+    Notarization, Notarized, Nullification, Payload, Seed, Subscription, Upload, ValidatorInfo,
+    ACK_FRAME, LAGGED_FRAME, MAX_BATCH_UPLOADS, MAX_GENESIS_PARTICIPANTS,
+};
+use bytes::Bytes;
+use commonware_codec::{Decode, DecodeExt, Encode, RangeCfg};
 use commonware_consensus::Viewable;
-use commonware_cryptography::Digestible;
+use commonware_cryptography::{sha256::Digest, Digestible};
 use commonware_parallel::Strategy;
-use futures::{channel::mpsc::unbounded, Stream, StreamExt};
-use tokio_tungstenite::{connect_async_tls_with_config, tungstenite::Message as TMessage};
+use commonware_utils::hex;
+use futures::{
+    channel::{
+        mpsc::{unbounded, UnboundedReceiver},
+        oneshot,
+    },
+    future,
+    stream::{self, SplitSink, SplitStream},
+    SinkExt, Stream, StreamExt,
+};
+use reqwest::{
+    header::{ACCEPT_RANGES, ETAG, IF_RANGE, RANGE},
+    Response, StatusCode,
+};
+use std::{
+    collections::HashMap,
+    future::Future,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    client_async_tls_with_config, connect_async_tls_with_config,
+    tungstenite::{
+        client::IntoClientRequest, handshake::client::Request, http::HeaderValue,
+        protocol::WebSocketConfig, Message as TMessage,
+    },
+    MaybeTlsStream, WebSocketStream,
+};
+use tracing::{instrument, warn};
+
+/// Maximum size of a message received over the consensus websocket.
+///
+/// Consensus messages are small (a certificate and a block), so anything larger
+/// is rejected before it is buffered.
+pub const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// First API version to serve [IndexQuery::Range].
+const RANGE_API_VERSION: u32 = 1;
 
 fn seed_upload_path(base: String) -> String {
     format!("{base}/seed")
@@ -23,6 +69,10 @@ fn notarization_get_path(base: String, query: &IndexQuery) -> String {
     format!("{base}/notarization/{}", query.serialize())
 }
 
+fn notarization_proof_get_path(base: String, query: &IndexQuery) -> String {
+    format!("{base}/notarization/{}/proof", query.serialize())
+}
+
 fn finalization_upload_path(base: String) -> String {
     format!("{base}/finalization")
 }
@@ -31,34 +81,259 @@ fn finalization_get_path(base: String, query: &IndexQuery) -> String {
     format!("{base}/finalization/{}", query.serialize())
 }
 
+fn finalization_proof_get_path(base: String, query: &IndexQuery) -> String {
+    format!("{base}/finalization/{}/proof", query.serialize())
+}
+
+fn nullification_upload_path(base: String) -> String {
+    format!("{base}/nullification")
+}
+
+fn nullification_get_path(base: String, query: &IndexQuery) -> String {
+    format!("{base}/nullification/{}", query.serialize())
+}
+
+fn batch_upload_path(base: String) -> String {
+    format!("{base}/batch")
+}
+
 /// There is no block upload path. Blocks are uploaded as a byproduct of notarization
 /// and finalization uploads.
 fn block_get_path(base: String, query: &Query) -> String {
     format!("{base}/block/{}", query.serialize())
 }
 
-fn listen_path(base: String) -> String {
-    format!("{base}/consensus/ws")
+fn block_get_range_path(base: String, start: u64, end: u64) -> String {
+    format!("{base}/block/{}", IndexQuery::Range(start, end).serialize())
 }
 
-pub enum Payload {
-    Finalized(Box<Finalized>),
-    Block(Block),
+fn consistency_get_path(base: String, from: u64, to: u64) -> String {
+    format!("{base}/consistency?from={from}&to={to}")
+}
+
+fn export_path(base: String, start: u64, end: u64) -> String {
+    format!(
+        "{base}/export/{}",
+        IndexQuery::Range(start, end).serialize()
+    )
+}
+
+fn genesis_get_path(base: String) -> String {
+    format!("{base}/genesis")
+}
+
+fn validators_get_path(base: String) -> String {
+    format!("{base}/validators")
+}
+
+fn listen_path(base: String, subscription: &Subscription) -> String {
+    match subscription.to_string() {
+        query if query.is_empty() => format!("{base}/consensus/ws"),
+        query => format!("{base}/consensus/ws?{query}"),
+    }
+}
+
+/// Return the index a single-artifact response must match (if any).
+///
+/// Ranges must be fetched with the `*_get_range` methods.
+fn expected_index(query: &IndexQuery) -> Result<Option<u64>, Error> {
+    match query {
+        IndexQuery::Latest => Ok(None),
+        IndexQuery::Index(index) => Ok(Some(*index)),
+        IndexQuery::Range(..) => Err(Error::InvalidQuery),
+    }
+}
+
+/// Split `[start, end)` into ranges of at most [MAX_RANGE] views.
+fn chunks(start: u64, end: u64) -> Result<impl Iterator<Item = (u64, u64)>, Error> {
+    if start > end {
+        return Err(Error::InvalidQuery);
+    }
+    Ok((start..end)
+        .step_by(MAX_RANGE as usize)
+        .map(move |start| (start, start.saturating_add(MAX_RANGE).min(end))))
+}
+
+/// Decoding config for a range response.
+fn range_cfg<C>(cfg: C) -> (RangeCfg<usize>, C) {
+    ((..=MAX_RANGE as usize).into(), cfg)
+}
+
+/// Ensure the views of a range response are strictly increasing and within `[start, end)`.
+fn check_views(views: impl Iterator<Item = u64>, start: u64, end: u64) -> Result<(), Error> {
+    let mut next = start;
+    for view in views {
+        if view < next || view >= end {
+            return Err(Error::UnexpectedResponse);
+        }
+        next = view + 1;
+    }
+    Ok(())
+}
+
+/// Progress of [Client::finalized_stream_range] through a chunk of views.
+enum Streaming {
+    /// Decoding a range response (expecting views in `[next, end)`).
+    Range {
+        response: Response,
+        decoder: Decoder<Finalized>,
+        next: u64,
+        end: u64,
+    },
+
+    /// Fetching each view individually (the indexer does not support ranges).
+    Views(std::ops::Range<u64>),
+}
+
+/// Progress of [Client::finalized_export] through an export response.
+struct Export {
+    response: Response,
+    decoder: Decoder<Finalized>,
+
+    /// First view of the requested export.
+    start: u64,
+
+    /// Next view the export may contain.
+    next: u64,
+
+    /// End (exclusive) of the requested export.
+    end: u64,
+
+    /// Number of bytes of the response received so far.
+    received: u64,
+
+    /// Tag of the response (used to resume it), if the indexer supports resuming.
+    etag: Option<HeaderValue>,
+
+    /// Number of consecutive times the export has been resumed without receiving any bytes.
+    resumes: usize,
+}
+
+impl Export {
+    /// Start decoding `response` (an export of `[start, end)`).
+    fn new(digest_mode: DigestMode, response: Response, start: u64, end: u64) -> Self {
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .filter(|_| {
+                response
+                    .headers()
+                    .get(ACCEPT_RANGES)
+                    .is_some_and(|v| v == "bytes")
+            })
+            .cloned();
+        Self {
+            response,
+            decoder: Decoder::new(digest_mode, MAX_EXPORT as usize),
+            start,
+            next: start,
+            end,
+            received: 0,
+            etag,
+            resumes: 0,
+        }
+    }
 }
 
 pub enum Message {
     Seed(Seed),
     Notarization(Notarized),
     Finalization(Finalized),
+    Nullification(Nullification),
+    /// The indexer dropped this many messages because the subscriber fell behind.
+    Lagged(u64),
+}
+
+/// Parse and verify a consensus message received over the websocket.
+///
+/// Each frame consists of a [Kind] byte followed by the encoded artifact (or [LAGGED_FRAME]
+/// followed by the number of skipped messages). Malformed
+/// frames (empty, unknown kind, trailing data, invalid encoding) return an error rather
+/// than panicking.
+pub fn parse_message(
+    identities: &IdentitySchedule,
+    strategy: &impl Strategy,
+    digest_mode: &DigestMode,
+    data: &[u8],
+) -> Result<Message, Error> {
+    let (kind, data) = data.split_first().ok_or(Error::EmptyFrame)?;
+    if *kind == LAGGED_FRAME {
+        let skipped = u64::decode(data).map_err(Error::InvalidData)?;
+        return Ok(Message::Lagged(skipped));
+    }
+    let kind = Kind::from_u8(*kind).ok_or(Error::UnknownKind(*kind))?;
+    match kind {
+        Kind::Seed => verify::seed(identities, data).map(Message::Seed),
+        Kind::Notarization => {
+            verify::notarized(identities, strategy, digest_mode, data).map(Message::Notarization)
+        }
+        Kind::Finalization => {
+            verify::finalized(identities, strategy, digest_mode, data).map(Message::Finalization)
+        }
+        Kind::Nullification => {
+            verify::nullification(identities, strategy, data).map(Message::Nullification)
+        }
+    }
 }
 
 impl<S: Strategy> Client<S> {
+    /// Fetch an [IndexQuery::Range] from `path`.
+    ///
+    /// Returns `None` if the indexer does not support ranges (determined by the [API_VERSION]
+    /// header of the first successful range response and remembered afterwards).
+    async fn range_get(&self, path: String) -> Result<Option<Bytes>, Error> {
+        let Some(response) = self.range_request(path).await? else {
+            return Ok(None);
+        };
+        let bytes = response.bytes().await.map_err(Error::Reqwest)?;
+        Ok(Some(bytes))
+    }
+
+    /// Request an [IndexQuery::Range] from `path` (without reading the body).
+    ///
+    /// Returns `None` if the indexer does not support ranges (see [Client::range_get]).
+    async fn range_request(&self, path: String) -> Result<Option<Response>, Error> {
+        if self.ranges.get() == Some(&false) {
+            return Ok(None);
+        }
+        let result = self
+            .execute(
+                self.traced(
+                    self.http_client
+                        .get(path)
+                        .header(API_VERSION, CURRENT_API_VERSION),
+                ),
+            )
+            .await
+            .map_err(Error::Reqwest)?;
+        let version = result
+            .headers()
+            .get(API_VERSION)
+            .and_then(|version| version.to_str().ok())
+            .and_then(|version| version.parse::<u32>().ok())
+            .unwrap_or_default();
+        let supported = version >= RANGE_API_VERSION;
+
+        // Only remember whether ranges are supported once the indexer (and not some
+        // intermediary failing transiently) has responded successfully
+        if !result.status().is_success() {
+            if !supported {
+                return Ok(None);
+            }
+            return Err(Error::Failed(result.status()));
+        }
+        let _ = self.ranges.set(supported);
+        if !supported {
+            return Ok(None);
+        }
+        Ok(Some(result))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(view = %seed.view(), traceparent))]
     pub async fn seed_upload(&self, seed: Seed) -> Result<(), Error> {
+        let request = self.traced(self.http_client.post(seed_upload_path(self.uri.clone())));
         let result = self
-            .http_client
-            .post(seed_upload_path(self.uri.clone()))
-            .body(seed.encode().to_vec())
-            .send()
+            .execute(self.signed(request, seed.encode().to_vec()))
             .await
             .map_err(Error::Reqwest)?;
         if !result.status().is_success() {
@@ -67,41 +342,77 @@ impl<S: Strategy> Client<S> {
         Ok(())
     }
 
+    #[instrument(level = "debug", skip_all, fields(query = %query.serialize(), traceparent))]
     pub async fn seed_get(&self, query: IndexQuery) -> Result<Seed, Error> {
+        let expected = expected_index(&query)?;
+
         // Get the seed
         let result = self
-            .http_client
-            .get(seed_get_path(self.uri.clone(), &query))
-            .send()
+            .execute(
+                self.traced(
+                    self.http_client
+                        .get(seed_get_path(self.uri.clone(), &query)),
+                ),
+            )
             .await
             .map_err(Error::Reqwest)?;
         if !result.status().is_success() {
             return Err(Error::Failed(result.status()));
         }
         let bytes = result.bytes().await.map_err(Error::Reqwest)?;
-        let seed = Seed::decode(bytes.as_ref()).map_err(Error::InvalidData)?;
-        if !seed.verify(&self.certificate_verifier) {
-            return Err(Error::InvalidSignature);
-        }
+        let seed = verify::seed(&self.identities, bytes.as_ref())?;
 
         // Verify the seed matches the query
-        match query {
-            IndexQuery::Latest => {}
-            IndexQuery::Index(index) => {
-                if seed.view().get() != index {
-                    return Err(Error::UnexpectedResponse);
+        if expected.is_some_and(|index| seed.view().get() != index) {
+            return Err(Error::UnexpectedResponse);
+        }
+        Ok(seed)
+    }
+
+    /// Get all [Seed]s with views in `[start, end)`.
+    ///
+    /// Uses [IndexQuery::Range] if the indexer supports it, otherwise falls back to fetching
+    /// each view individually. Views without a seed are skipped.
+    #[instrument(level = "debug", skip_all, fields(start, end, traceparent))]
+    pub async fn seed_get_range(&self, start: u64, end: u64) -> Result<Vec<Seed>, Error> {
+        let mut seeds = Vec::new();
+        for (start, end) in chunks(start, end)? {
+            // Fall back to sequential fetches if ranges are unsupported
+            let path = seed_get_path(self.uri.clone(), &IndexQuery::Range(start, end));
+            let Some(bytes) = self.range_get(path).await? else {
+                for view in start..end {
+                    match self.seed_get(IndexQuery::Index(view)).await {
+                        Ok(seed) => seeds.push(seed),
+                        Err(Error::Failed(StatusCode::NOT_FOUND)) => {}
+                        Err(err) => return Err(err),
+                    }
                 }
+                continue;
+            };
+
+            // Verify the batch matches the query
+            let batch = Vec::<Seed>::decode_cfg(bytes.as_ref(), &range_cfg(()))
+                .map_err(Error::InvalidData)?;
+            check_views(batch.iter().map(|seed| seed.view().get()), start, end)?;
+            if !batch
+                .iter()
+                .all(|seed| seed.verify(self.identities.verifier(seed.round)))
+            {
+                return Err(Error::InvalidSignature);
             }
+            seeds.extend(batch);
         }
-        Ok(seed)
+        Ok(seeds)
     }
 
+    #[instrument(level = "debug", skip_all, fields(view = %notarized.proof.view(), traceparent))]
     pub async fn notarized_upload(&self, notarized: Notarized) -> Result<(), Error> {
+        let request = self.traced(
+            self.http_client
+                .post(notarization_upload_path(self.uri.clone())),
+        );
         let result = self
-            .http_client
-            .post(notarization_upload_path(self.uri.clone()))
-            .body(notarized.encode().to_vec())
-            .send()
+            .execute(self.signed(request, notarized.encode().to_vec()))
             .await
             .map_err(Error::Reqwest)?;
         if !result.status().is_success() {
@@ -110,41 +421,118 @@ impl<S: Strategy> Client<S> {
         Ok(())
     }
 
+    #[instrument(level = "debug", skip_all, fields(query = %query.serialize(), traceparent))]
     pub async fn notarized_get(&self, query: IndexQuery) -> Result<Notarized, Error> {
+        let expected = expected_index(&query)?;
+
         // Get the notarization
         let result = self
-            .http_client
-            .get(notarization_get_path(self.uri.clone(), &query))
-            .send()
+            .execute(
+                self.traced(
+                    self.http_client
+                        .get(notarization_get_path(self.uri.clone(), &query)),
+                ),
+            )
             .await
             .map_err(Error::Reqwest)?;
         if !result.status().is_success() {
             return Err(Error::Failed(result.status()));
         }
         let bytes = result.bytes().await.map_err(Error::Reqwest)?;
-        let notarized = Notarized::decode(bytes.as_ref()).map_err(Error::InvalidData)?;
-        if !notarized.verify(&self.certificate_verifier, &self.strategy) {
-            return Err(Error::InvalidSignature);
-        }
+        let notarized = verify::notarized(
+            &self.identities,
+            &self.strategy,
+            &self.digest_mode,
+            bytes.as_ref(),
+        )?;
 
         // Verify the notarization matches the query
-        match query {
-            IndexQuery::Latest => {}
-            IndexQuery::Index(index) => {
-                if notarized.proof.view().get() != index {
-                    return Err(Error::UnexpectedResponse);
+        if expected.is_some_and(|index| notarized.proof.view().get() != index) {
+            return Err(Error::UnexpectedResponse);
+        }
+        Ok(notarized)
+    }
+
+    /// Get all [Notarized] blocks with views in `[start, end)`.
+    ///
+    /// Uses [IndexQuery::Range] if the indexer supports it, otherwise falls back to fetching
+    /// each view individually. Views without a notarization are skipped.
+    #[instrument(level = "debug", skip_all, fields(start, end, traceparent))]
+    pub async fn notarized_get_range(&self, start: u64, end: u64) -> Result<Vec<Notarized>, Error> {
+        let mut notarizations = Vec::new();
+        for (start, end) in chunks(start, end)? {
+            // Fall back to sequential fetches if ranges are unsupported
+            let path = notarization_get_path(self.uri.clone(), &IndexQuery::Range(start, end));
+            let Some(bytes) = self.range_get(path).await? else {
+                for view in start..end {
+                    match self.notarized_get(IndexQuery::Index(view)).await {
+                        Ok(notarized) => notarizations.push(notarized),
+                        Err(Error::Failed(StatusCode::NOT_FOUND)) => {}
+                        Err(err) => return Err(err),
+                    }
                 }
+                continue;
+            };
+
+            // Verify the batch matches the query
+            let batch = Vec::<Notarized>::decode_cfg(bytes.as_ref(), &range_cfg(self.digest_mode))
+                .map_err(Error::InvalidData)?;
+            check_views(
+                batch.iter().map(|notarized| notarized.proof.view().get()),
+                start,
+                end,
+            )?;
+            if !batch.iter().all(|notarized| {
+                notarized.verify(
+                    self.identities.verifier(notarized.proof.round()),
+                    &self.strategy,
+                )
+            }) {
+                return Err(Error::InvalidSignature);
             }
+            notarizations.extend(batch);
         }
-        Ok(notarized)
+        Ok(notarizations)
     }
 
+    /// Get a [Notarization] (without the notarized block).
+    ///
+    /// The block can be fetched independently with [Query::Digest].
+    #[instrument(level = "debug", skip_all, fields(query = %query.serialize(), traceparent))]
+    pub async fn notarization_get(&self, query: IndexQuery) -> Result<Notarization, Error> {
+        let expected = expected_index(&query)?;
+
+        // Get the notarization
+        let result = self
+            .execute(
+                self.traced(
+                    self.http_client
+                        .get(notarization_proof_get_path(self.uri.clone(), &query)),
+                ),
+            )
+            .await
+            .map_err(Error::Reqwest)?;
+        if !result.status().is_success() {
+            return Err(Error::Failed(result.status()));
+        }
+        let bytes = result.bytes().await.map_err(Error::Reqwest)?;
+        let notarization = verify::notarization(&self.identities, &self.strategy, bytes.as_ref())?;
+
+        // Verify the notarization matches the query
+        if expected.is_some_and(|index| notarization.view().get() != index) {
+            return Err(Error::UnexpectedResponse);
+        }
+        Ok(notarization)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(view = %finalized.proof.view(), traceparent))]
     pub async fn finalized_upload(&self, finalized: Finalized) -> Result<(), Error> {
+        let request = self.traced(
+            self.http_client
+                .post(finalization_upload_path(self.uri.clone())),
+        );
         let result = self
-            .http_client
-            .post(finalization_upload_path(self.uri.clone()))
-            .body(finalized.encode().to_vec())
-            .send()
+            .execute(self.signed(request, finalized.encode().to_vec()))
             .await
             .map_err(Error::Reqwest)?;
         if !result.status().is_success() {
@@ -153,175 +541,1223 @@ impl<S: Strategy> Client<S> {
         Ok(())
     }
 
+    #[instrument(level = "debug", skip_all, fields(query = %query.serialize(), traceparent))]
     pub async fn finalized_get(&self, query: IndexQuery) -> Result<Finalized, Error> {
+        let expected = expected_index(&query)?;
+
         // Get the finalization
         let result = self
-            .http_client
-            .get(finalization_get_path(self.uri.clone(), &query))
-            .send()
+            .execute(
+                self.traced(
+                    self.http_client
+                        .get(finalization_get_path(self.uri.clone(), &query)),
+                ),
+            )
             .await
             .map_err(Error::Reqwest)?;
         if !result.status().is_success() {
             return Err(Error::Failed(result.status()));
         }
         let bytes = result.bytes().await.map_err(Error::Reqwest)?;
-        let finalized = Finalized::decode(bytes.as_ref()).map_err(Error::InvalidData)?;
-        if !finalized.verify(&self.certificate_verifier, &self.strategy) {
+        let finalized = verify::finalized(
+            &self.identities,
+            &self.strategy,
+            &self.digest_mode,
+            bytes.as_ref(),
+        )?;
+
+        // Verify the finalization matches the query
+        if expected.is_some_and(|index| finalized.proof.view().get() != index) {
+            return Err(Error::UnexpectedResponse);
+        }
+        Ok(finalized)
+    }
+
+    /// Get all [Finalized] blocks with views in `[start, end)`.
+    ///
+    /// Uses [IndexQuery::Range] if the indexer supports it, otherwise falls back to fetching
+    /// each view individually. Views without a finalization are skipped.
+    #[instrument(level = "debug", skip_all, fields(start, end, traceparent))]
+    pub async fn finalized_get_range(&self, start: u64, end: u64) -> Result<Vec<Finalized>, Error> {
+        let mut finalizations = Vec::new();
+        for (start, end) in chunks(start, end)? {
+            // Fall back to sequential fetches if ranges are unsupported
+            let path = finalization_get_path(self.uri.clone(), &IndexQuery::Range(start, end));
+            let Some(bytes) = self.range_get(path).await? else {
+                for view in start..end {
+                    match self.finalized_get(IndexQuery::Index(view)).await {
+                        Ok(finalized) => finalizations.push(finalized),
+                        Err(Error::Failed(StatusCode::NOT_FOUND)) => {}
+                        Err(err) => return Err(err),
+                    }
+                }
+                continue;
+            };
+
+            // Verify the batch matches the query
+            let batch = Vec::<Finalized>::decode_cfg(bytes.as_ref(), &range_cfg(self.digest_mode))
+                .map_err(Error::InvalidData)?;
+            check_views(
+                batch.iter().map(|finalized| finalized.proof.view().get()),
+                start,
+                end,
+            )?;
+            if !self
+                .identities
+                .verify_finalized(&self.strategy, &batch)
+                .is_empty()
+            {
+                return Err(Error::InvalidSignature);
+            }
+            finalizations.extend(batch);
+        }
+        Ok(finalizations)
+    }
+
+    /// Stream all [Finalized] blocks with views in `[start, end)`.
+    ///
+    /// Unlike [Client::finalized_get_range], each finalization is decoded (and verified) as
+    /// soon as it is received, so only a single finalization is buffered at a time. The stream
+    /// ends after the first error.
+    pub fn finalized_stream_range(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> impl Stream<Item = Result<Finalized, Error>> + '_ {
+        let chunks = chunks(start, end);
+        stream::unfold(
+            (chunks, None, false),
+            move |(mut chunks, mut current, failed)| async move {
+                if failed {
+                    return None;
+                }
+                let item = match &mut chunks {
+                    Ok(chunks) => self.next_finalized(chunks, &mut current).await?,
+                    Err(_) => Err(Error::InvalidQuery),
+                };
+                let failed = item.is_err();
+                Some((item, (chunks, current, failed)))
+            },
+        )
+    }
+
+    /// Get the next [Finalized] block for [Client::finalized_stream_range].
+    async fn next_finalized(
+        &self,
+        chunks: &mut impl Iterator<Item = (u64, u64)>,
+        current: &mut Option<Streaming>,
+    ) -> Option<Result<Finalized, Error>> {
+        loop {
+            match current {
+                None => {
+                    // Fall back to sequential fetches if ranges are unsupported
+                    let (start, end) = chunks.next()?;
+                    let path =
+                        finalization_get_path(self.uri.clone(), &IndexQuery::Range(start, end));
+                    *current = Some(match self.range_request(path).await {
+                        Ok(Some(response)) => Streaming::Range {
+                            response,
+                            decoder: Decoder::new(self.digest_mode, MAX_RANGE as usize),
+                            next: start,
+                            end,
+                        },
+                        Ok(None) => Streaming::Views(start..end),
+                        Err(err) => return Some(Err(err)),
+                    });
+                }
+                Some(Streaming::Views(views)) => {
+                    let Some(view) = views.next() else {
+                        *current = None;
+                        continue;
+                    };
+                    match self.finalized_get(IndexQuery::Index(view)).await {
+                        Ok(finalized) => return Some(Ok(finalized)),
+                        Err(Error::Failed(StatusCode::NOT_FOUND)) => {}
+                        Err(err) => return Some(Err(err)),
+                    }
+                }
+                Some(Streaming::Range {
+                    response,
+                    decoder,
+                    next,
+                    end,
+                }) => match self.next_decoded(response, decoder, next, *end).await {
+                    Some(item) => return Some(item),
+                    None => *current = None,
+                },
+            }
+        }
+    }
+
+    /// Decode (and verify) the next [Finalized] block from `response`, which must have a view in
+    /// `[next, end)`.
+    ///
+    /// Returns `None` once the response is complete.
+    async fn next_decoded(
+        &self,
+        response: &mut Response,
+        decoder: &mut Decoder<Finalized>,
+        next: &mut u64,
+        end: u64,
+    ) -> Option<Result<Finalized, Error>> {
+        loop {
+            // Verify the next finalization matches the query (if fully received)
+            if let Some(item) = self.decode_next(decoder, next, end).transpose() {
+                return Some(item);
+            }
+
+            // Otherwise, wait for more of the response
+            match response.chunk().await {
+                Ok(Some(chunk)) => decoder.push(&chunk),
+                Ok(None) => return decoder.finish().err().map(Err),
+                Err(err) => return Some(Err(Error::Reqwest(err))),
+            }
+        }
+    }
+
+    /// Decode (and verify) the next [Finalized] block from `decoder` (if fully received), which
+    /// must have a view in `[next, end)`.
+    fn decode_next(
+        &self,
+        decoder: &mut Decoder<Finalized>,
+        next: &mut u64,
+        end: u64,
+    ) -> Result<Option<Finalized>, Error> {
+        let Some(finalized) = decoder.decode()? else {
+            return Ok(None);
+        };
+        let view = finalized.proof.view().get();
+        if view < *next || view >= end {
+            return Err(Error::UnexpectedResponse);
+        }
+        *next = view + 1;
+        if !finalized.verify(
+            self.identities.verifier(finalized.proof.round()),
+            &self.strategy,
+        ) {
             return Err(Error::InvalidSignature);
         }
+        Ok(Some(finalized))
+    }
 
-        // Verify the finalization matches the query
-        match query {
-            IndexQuery::Latest => {}
-            IndexQuery::Index(index) => {
-                if finalized.proof.view().get() != index {
-                    return Err(Error::UnexpectedResponse);
+    /// Decode (and verify) the next [Finalized] block from `export`, resuming the response (up
+    /// to [MAX_EXPORT_RESUMES] consecutive times) if it is interrupted.
+    ///
+    /// Returns `None` once the export is complete.
+    async fn next_exported(&self, export: &mut Export) -> Option<Result<Finalized, Error>> {
+        loop {
+            // Verify the next finalization matches the query (if fully received)
+            let item = self.decode_next(&mut export.decoder, &mut export.next, export.end);
+            if let Some(item) = item.transpose() {
+                return Some(item);
+            }
+
+            // Otherwise, wait for more of the response
+            let err = match export.response.chunk().await {
+                Ok(Some(chunk)) => {
+                    export.received += chunk.len() as u64;
+                    export.resumes = 0;
+                    export.decoder.push(&chunk);
+                    continue;
                 }
+                Ok(None) => return export.decoder.finish().err().map(Err),
+                Err(err) => err,
+            };
+
+            // Resume the response (if supported)
+            if export.etag.is_none() || export.resumes == MAX_EXPORT_RESUMES {
+                return Some(Err(Error::Reqwest(err)));
+            }
+            export.resumes += 1;
+            warn!(received = export.received, error = %err, "export interrupted, resuming");
+            if let Err(err) = self.resume_export(export).await {
+                return Some(Err(err));
             }
         }
-        Ok(finalized)
     }
 
+    /// Request the rest of `export` (from the first byte not yet received).
+    ///
+    /// If the export changed since it was started (finalizations were added to its range), it
+    /// is restarted from the first view not yet received instead.
+    async fn resume_export(&self, export: &mut Export) -> Result<(), Error> {
+        let etag = export.etag.clone().expect("export is not resumable");
+        let request = self
+            .http_client
+            .get(export_path(self.uri.clone(), export.start, export.end))
+            .header(RANGE, format!("bytes={}-", export.received))
+            .header(IF_RANGE, etag);
+        let response = self
+            .execute(self.traced(request))
+            .await
+            .map_err(Error::Reqwest)?;
+        if response.status() == StatusCode::PARTIAL_CONTENT {
+            export.response = response;
+            return Ok(());
+        }
+        if !response.status().is_success() {
+            return Err(Error::Failed(response.status()));
+        }
+        let response = self.export_request(export.next, export.end).await?;
+        *export = Export {
+            resumes: export.resumes,
+            ..Export::new(self.digest_mode, response, export.next, export.end)
+        };
+        Ok(())
+    }
+
+    /// Stream the export of all [Finalized] blocks with views in `[start, end)` (spanning at
+    /// most [MAX_EXPORT] views).
+    ///
+    /// The export is served as a single response, which is decoded (and verified) incrementally
+    /// so only a single finalization is buffered at a time. If the response is interrupted, it
+    /// is resumed from the last byte received (with a `Range` request). The stream ends after
+    /// the first error.
+    pub fn finalized_export(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> impl Stream<Item = Result<Finalized, Error>> + '_ {
+        stream::unfold((None, false), move |(export, failed)| async move {
+            if failed {
+                return None;
+            }
+            let mut export = match export {
+                Some(export) => export,
+                None => match self.export_request(start, end).await {
+                    Ok(response) => Export::new(self.digest_mode, response, start, end),
+                    Err(err) => return Some((Err(err), (None, true))),
+                },
+            };
+            let item = self.next_exported(&mut export).await?;
+            let failed = item.is_err();
+            Some((item, (Some(export), failed)))
+        })
+    }
+
+    /// Request the export of `[start, end)` (without reading the body).
+    async fn export_request(&self, start: u64, end: u64) -> Result<Response, Error> {
+        if start > end || end - start > MAX_EXPORT {
+            return Err(Error::InvalidQuery);
+        }
+        let result = self
+            .execute(
+                self.traced(
+                    self.http_client
+                        .get(export_path(self.uri.clone(), start, end)),
+                ),
+            )
+            .await
+            .map_err(Error::Reqwest)?;
+        if !result.status().is_success() {
+            return Err(Error::Failed(result.status()));
+        }
+        Ok(result)
+    }
+
+    /// Get a [Finalization] (without the finalized block).
+    ///
+    /// The block can be fetched independently with [Query::Digest].
+    #[instrument(level = "debug", skip_all, fields(query = %query.serialize(), traceparent))]
+    pub async fn finalization_get(&self, query: IndexQuery) -> Result<Finalization, Error> {
+        let expected = expected_index(&query)?;
+
+        // Get the finalization
+        let result = self
+            .execute(
+                self.traced(
+                    self.http_client
+                        .get(finalization_proof_get_path(self.uri.clone(), &query)),
+                ),
+            )
+            .await
+            .map_err(Error::Reqwest)?;
+        if !result.status().is_success() {
+            return Err(Error::Failed(result.status()));
+        }
+        let bytes = result.bytes().await.map_err(Error::Reqwest)?;
+        let finalization = verify::finalization(&self.identities, &self.strategy, bytes.as_ref())?;
+
+        // Verify the finalization matches the query
+        if expected.is_some_and(|index| finalization.view().get() != index) {
+            return Err(Error::UnexpectedResponse);
+        }
+        Ok(finalization)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(view = %nullification.view(), traceparent))]
+    pub async fn nullification_upload(&self, nullification: Nullification) -> Result<(), Error> {
+        let request = self.traced(
+            self.http_client
+                .post(nullification_upload_path(self.uri.clone())),
+        );
+        let result = self
+            .execute(self.signed(request, nullification.encode().to_vec()))
+            .await
+            .map_err(Error::Reqwest)?;
+        if !result.status().is_success() {
+            return Err(Error::Failed(result.status()));
+        }
+        Ok(())
+    }
+
+    /// Upload encoded artifacts (of the paired [Kind]) in a single request.
+    ///
+    /// The batch is signed as a whole (if a signer is configured). Returns an [Ack] for each
+    /// artifact (in order) whether or not it was accepted (see [Ack::accepted]).
+    #[instrument(level = "debug", skip_all, fields(uploads = artifacts.len(), traceparent))]
+    pub async fn batch_upload(&self, artifacts: Vec<(Kind, Vec<u8>)>) -> Result<Vec<Ack>, Error> {
+        if artifacts.len() > MAX_BATCH_UPLOADS {
+            return Err(Error::InvalidQuery);
+        }
+        let uploads: Vec<Upload> = artifacts
+            .into_iter()
+            .enumerate()
+            .map(|(id, (kind, body))| Upload {
+                id: id as u64,
+                kind,
+                auth: None,
+                body: body.into(),
+            })
+            .collect();
+        let count = uploads.len();
+        let request = self.traced(self.http_client.post(batch_upload_path(self.uri.clone())));
+        let result = self
+            .execute(self.signed(request, uploads.encode().to_vec()))
+            .await
+            .map_err(Error::Reqwest)?;
+        if !result.status().is_success() {
+            return Err(Error::Failed(result.status()));
+        }
+        let bytes = result.bytes().await.map_err(Error::Reqwest)?;
+        let acks = Vec::<Ack>::decode_cfg(bytes, &(RangeCfg::exact(count), ()))?;
+        if acks.iter().enumerate().any(|(id, ack)| ack.id != id as u64) {
+            return Err(Error::UnexpectedResponse);
+        }
+        Ok(acks)
+    }
+
+    /// Get a [Nullification] (a certificate that a view was skipped).
+    #[instrument(level = "debug", skip_all, fields(query = %query.serialize(), traceparent))]
+    pub async fn nullification_get(&self, query: IndexQuery) -> Result<Nullification, Error> {
+        let expected = expected_index(&query)?;
+
+        // Get the nullification
+        let result = self
+            .execute(
+                self.traced(
+                    self.http_client
+                        .get(nullification_get_path(self.uri.clone(), &query)),
+                ),
+            )
+            .await
+            .map_err(Error::Reqwest)?;
+        if !result.status().is_success() {
+            return Err(Error::Failed(result.status()));
+        }
+        let bytes = result.bytes().await.map_err(Error::Reqwest)?;
+        let nullification =
+            verify::nullification(&self.identities, &self.strategy, bytes.as_ref())?;
+
+        // Verify the nullification matches the query
+        if expected.is_some_and(|index| nullification.view().get() != index) {
+            return Err(Error::UnexpectedResponse);
+        }
+        Ok(nullification)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(query = %query.serialize(), traceparent))]
     pub async fn block_get(&self, query: Query) -> Result<Payload, Error> {
+        // Serve blocks by digest from the cache (if enabled)
+        if let (Query::Digest(digest), Some(cache)) = (&query, &self.block_cache) {
+            if let Some(block) = cache.lock().unwrap().get(digest) {
+                return Ok(block.into());
+            }
+        }
+
         // Get the block
         let result = self
-            .http_client
-            .get(block_get_path(self.uri.clone(), &query))
-            .send()
+            .execute(
+                self.traced(
+                    self.http_client
+                        .get(block_get_path(self.uri.clone(), &query)),
+                ),
+            )
             .await
             .map_err(Error::Reqwest)?;
+        if let (Query::Prefix(prefix), StatusCode::MULTIPLE_CHOICES) = (&query, result.status()) {
+            // Return the blocks matching an ambiguous prefix
+            let bytes = result.bytes().await.map_err(Error::Reqwest)?;
+            let digests =
+                Vec::<Digest>::decode_cfg(bytes.as_ref(), &((..=MAX_PREFIX_MATCHES).into(), ()))
+                    .map_err(Error::InvalidData)?;
+            if digests.len() < 2 || !digests.iter().all(|digest| hex(digest).starts_with(prefix)) {
+                return Err(Error::UnexpectedResponse);
+            }
+            return Err(Error::AmbiguousPrefix(digests));
+        }
         if !result.status().is_success() {
             return Err(Error::Failed(result.status()));
         }
         let bytes = result.bytes().await.map_err(Error::Reqwest)?;
 
         // Verify the block matches the query
-        let result = match query {
+        let result: Payload = match query {
             Query::Latest => {
-                let result = Finalized::decode(bytes.as_ref()).map_err(Error::InvalidData)?;
-                if !result.verify(&self.certificate_verifier, &self.strategy) {
-                    return Err(Error::InvalidSignature);
-                }
-                Payload::Finalized(Box::new(result))
+                let result = verify::finalized(
+                    &self.identities,
+                    &self.strategy,
+                    &self.digest_mode,
+                    bytes.as_ref(),
+                )?;
+                result.into()
             }
             Query::Index(index) => {
-                let result = Finalized::decode(bytes.as_ref()).map_err(Error::InvalidData)?;
-                if !result.verify(&self.certificate_verifier, &self.strategy) {
-                    return Err(Error::InvalidSignature);
-                }
+                let result = verify::finalized(
+                    &self.identities,
+                    &self.strategy,
+                    &self.digest_mode,
+                    bytes.as_ref(),
+                )?;
                 if result.block.height.get() != index {
                     return Err(Error::UnexpectedResponse);
                 }
-                Payload::Finalized(Box::new(result))
+                result.into()
             }
             Query::Digest(digest) => {
-                let result = Block::decode(bytes.as_ref()).map_err(Error::InvalidData)?;
+                let result = Block::decode_cfg(bytes.as_ref(), &self.digest_mode)
+                    .map_err(Error::InvalidData)?;
                 if result.digest() != digest {
                     return Err(Error::UnexpectedResponse);
                 }
-                Payload::Block(result)
+                result.into()
+            }
+            Query::Prefix(prefix) => {
+                let result = Block::decode_cfg(bytes.as_ref(), &self.digest_mode)
+                    .map_err(Error::InvalidData)?;
+                if !hex(&result.digest()).starts_with(&prefix) {
+                    return Err(Error::UnexpectedResponse);
+                }
+                result.into()
             }
         };
+        if let Some(cache) = &self.block_cache {
+            cache.lock().unwrap().insert(result.block().clone());
+        }
         Ok(result)
     }
 
-    pub async fn listen(&self) -> Result<impl Stream<Item = Result<Message, Error>>, Error> {
-        // Connect to the websocket endpoint
-        let (stream, _) = connect_async_tls_with_config(
-            listen_path(self.ws_uri.clone()),
-            None,
-            false,
-            Some(self.ws_connector.clone()),
+    /// Get all finalized blocks with heights in `[start, end)`.
+    ///
+    /// Uses a single request per [MAX_RANGE] heights if the indexer supports ranges, otherwise
+    /// falls back to fetching each height individually. Heights without a finalized block are
+    /// skipped.
+    #[instrument(level = "debug", skip_all, fields(start, end, traceparent))]
+    pub async fn block_get_range(&self, start: u64, end: u64) -> Result<Vec<Finalized>, Error> {
+        let mut blocks = Vec::new();
+        for (start, end) in chunks(start, end)? {
+            // Fall back to sequential fetches if ranges are unsupported
+            let Some(batch) = self.block_range(start, end).await? else {
+                for height in start..end {
+                    blocks.extend(self.block_at(height).await?);
+                }
+                continue;
+            };
+            blocks.extend(batch);
+        }
+        Ok(blocks)
+    }
+
+    /// Stream all finalized blocks with heights in `heights` (in height order).
+    ///
+    /// Like [Client::block_get_range], blocks are fetched with a single request per [MAX_RANGE]
+    /// heights if the indexer supports ranges, otherwise with up to [MAX_CONCURRENT_FETCHES]
+    /// concurrent requests for individual heights. Blocks are only fetched as the stream is
+    /// polled, so at most one range (or [MAX_CONCURRENT_FETCHES] blocks) is buffered at a time.
+    /// Heights without a finalized block are skipped, and the stream ends after the first error.
+    pub fn blocks(&self, heights: Range<u64>) -> impl Stream<Item = Result<Finalized, Error>> + '_ {
+        let chunks = match chunks(heights.start, heights.end) {
+            Ok(chunks) => stream::iter(chunks).map(Ok).left_stream(),
+            Err(err) => stream::once(future::ready(Err(err))).right_stream(),
+        };
+        chunks
+            .then(move |chunk| async move {
+                let batch = match chunk {
+                    Ok((start, end)) => self
+                        .block_range(start, end)
+                        .await
+                        .map(|batch| batch.ok_or(start..end)),
+                    Err(err) => Err(err),
+                };
+                match batch {
+                    Ok(Ok(batch)) => {
+                        stream::iter(batch.into_iter().map(Ok).collect::<Vec<_>>()).left_stream()
+                    }
+                    Ok(Err(heights)) => {
+                        // Fall back to concurrent fetches if ranges are unsupported
+                        stream::iter(heights)
+                            .map(move |height| self.block_at(height))
+                            .buffered(MAX_CONCURRENT_FETCHES)
+                            .filter_map(|block| future::ready(block.transpose()))
+                            .right_stream()
+                    }
+                    Err(err) => stream::iter(vec![Err(err)]).left_stream(),
+                }
+            })
+            .flatten()
+            .scan(false, |failed, item| {
+                if *failed {
+                    return future::ready(None);
+                }
+                *failed = item.is_err();
+                future::ready(Some(item))
+            })
+    }
+
+    /// Download the finalized blocks with heights in `[start_height, end_height)` as a chain
+    /// (in height order).
+    ///
+    /// Blocks are fetched (and their finalizations verified) a page at a time like
+    /// [Client::blocks], and each block is checked to extend the one before it (its `parent` is
+    /// the digest of the block at the previous height). Unlike [Client::blocks], missing heights
+    /// are not skipped but fail with [Error::MissingBlock]. The first block's parent is not
+    /// checked (compare it against a block you already trust). The stream ends after the first
+    /// error.
+    pub fn download_chain(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> impl Stream<Item = Result<Finalized, Error>> + '_ {
+        let blocks = Box::pin(self.blocks(start_height..end_height));
+        stream::unfold(
+            (blocks, start_height, None, false),
+            move |(mut blocks, height, parent, failed)| async move {
+                if failed {
+                    return None;
+                }
+                let item = match blocks.next().await {
+                    Some(Ok(finalized)) if finalized.block.height.get() != height => {
+                        Err(Error::MissingBlock(height))
+                    }
+                    Some(Ok(finalized))
+                        if parent.is_some_and(|parent| finalized.block.parent != parent) =>
+                    {
+                        Err(Error::BrokenChain(height))
+                    }
+                    Some(item) => item,
+                    None if height < end_height => Err(Error::MissingBlock(height)),
+                    None => return None,
+                };
+                let parent = item.as_ref().ok().map(|finalized| finalized.block.digest());
+                let failed = item.is_err();
+                Some((item, (blocks, height + 1, parent, failed)))
+            },
         )
-        .await
-        .map_err(Error::from)?;
-        let (_, read) = stream.split();
+    }
+
+    /// Get (and verify) all finalized blocks with heights in `[start, end)` (spanning at most
+    /// [MAX_RANGE] heights) with a single request.
+    ///
+    /// Returns `None` if the indexer does not support ranges (see [Client::range_get]).
+    async fn block_range(&self, start: u64, end: u64) -> Result<Option<Vec<Finalized>>, Error> {
+        let path = block_get_range_path(self.uri.clone(), start, end);
+        let Some(bytes) = self.range_get(path).await? else {
+            return Ok(None);
+        };
+
+        // Verify the batch matches the query (heights must be strictly increasing)
+        let batch = Vec::<Finalized>::decode_cfg(bytes.as_ref(), &range_cfg(self.digest_mode))
+            .map_err(Error::InvalidData)?;
+        check_views(
+            batch.iter().map(|finalized| finalized.block.height.get()),
+            start,
+            end,
+        )?;
+        if !self
+            .identities
+            .verify_finalized(&self.strategy, &batch)
+            .is_empty()
+        {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(Some(batch))
+    }
+
+    /// Get the finalized block at `height` (or `None` if there is none).
+    async fn block_at(&self, height: u64) -> Result<Option<Finalized>, Error> {
+        match self.block_get(Query::Index(height)).await {
+            Ok(Payload::Finalized(finalized)) => Ok(Some(*finalized)),
+            Ok(Payload::Block(_)) => Err(Error::UnexpectedResponse),
+            Err(Error::Failed(StatusCode::NOT_FOUND)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get a [Consistency] proof that the finalized block at height `to` descends from
+    /// the block at height `from`.
+    ///
+    /// The returned proof is verified (finalization and parent digest chain). Callers
+    /// should compare [Consistency::from] against the block they already trust.
+    #[instrument(level = "debug", skip_all, fields(from, to, traceparent))]
+    pub async fn consistency_get(&self, from: u64, to: u64) -> Result<Consistency, Error> {
+        // Get the consistency proof
+        let result = self
+            .execute(self.traced(self.http_client.get(consistency_get_path(
+                self.uri.clone(),
+                from,
+                to,
+            ))))
+            .await
+            .map_err(Error::Reqwest)?;
+        if !result.status().is_success() {
+            return Err(Error::Failed(result.status()));
+        }
+        let bytes = result.bytes().await.map_err(Error::Reqwest)?;
+        let consistency = Consistency::decode_cfg(bytes.as_ref(), &self.digest_mode)
+            .map_err(Error::InvalidData)?;
+        let verifier = self
+            .identities
+            .verifier(consistency.finalized.proof.round());
+        if !consistency.verify(verifier, &self.strategy) {
+            return Err(Error::InvalidSignature);
+        }
+
+        // Verify the proof spans the requested heights
+        if consistency.from().height.get() != from || consistency.to().height.get() != to {
+            return Err(Error::UnexpectedResponse);
+        }
+        Ok(consistency)
+    }
+
+    /// Fetch the genesis transcript served by the indexer.
+    ///
+    /// The returned transcript is verified (signed by every participant) and committed to the
+    /// identity of the client.
+    #[instrument(level = "debug", skip_all, fields(traceparent))]
+    pub async fn genesis_get(&self) -> Result<Genesis, Error> {
+        // Get the genesis transcript
+        let result = self
+            .execute(self.traced(self.http_client.get(genesis_get_path(self.uri.clone()))))
+            .await
+            .map_err(Error::Reqwest)?;
+        if !result.status().is_success() {
+            return Err(Error::Failed(result.status()));
+        }
+        let bytes = result.bytes().await.map_err(Error::Reqwest)?;
+        let genesis = Genesis::decode_cfg(bytes.as_ref(), &MAX_GENESIS_PARTICIPANTS)
+            .map_err(Error::InvalidData)?;
+        if !genesis.verify() {
+            return Err(Error::InvalidSignature);
+        }
+
+        // Verify the transcript is for the expected network
+        if genesis.identity != *self.identities.genesis() {
+            return Err(Error::UnexpectedResponse);
+        }
+        Ok(genesis)
+    }
+
+    /// Get the operator-provided metadata about each validator (ordered by public key).
+    ///
+    /// Metadata is not signed, so it should only be used for display.
+    pub async fn validators_get(&self) -> Result<Vec<ValidatorInfo>, Error> {
+        let result = self
+            .execute(self.traced(self.http_client.get(validators_get_path(self.uri.clone()))))
+            .await
+            .map_err(Error::Reqwest)?;
+        if !result.status().is_success() {
+            return Err(Error::Failed(result.status()));
+        }
+        let text = result.text().await.map_err(Error::Reqwest)?;
+        ValidatorInfo::from_json(&text).map_err(|_| Error::UnexpectedResponse)
+    }
 
+    #[instrument(level = "debug", skip_all, fields(traceparent))]
+    /// Open a websocket connection (to the next reachable replica, if the client was built with
+    /// replicas).
+    async fn connect(
+        &self,
+        request: Request,
+        config: WebSocketConfig,
+    ) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, Error> {
+        if self.replicas.is_empty() {
+            let (stream, _) = connect_async_tls_with_config(
+                request,
+                Some(config),
+                false,
+                Some(self.ws_connector.clone()),
+            )
+            .await
+            .map_err(Error::from)?;
+            return Ok(stream);
+        }
+        let mut failure = None;
+        for replica in self.replicas.order() {
+            let socket = match TcpStream::connect(replica.addr).await {
+                Ok(socket) => socket,
+                Err(e) => {
+                    warn!(addr = %replica.addr, error = %e, "indexer replica unreachable");
+                    replica.failed();
+                    failure = Some(e);
+                    continue;
+                }
+            };
+            replica.succeeded();
+            let (stream, _) = client_async_tls_with_config(
+                request,
+                socket,
+                Some(config),
+                Some(self.ws_connector.clone()),
+            )
+            .await
+            .map_err(Error::from)?;
+            return Ok(stream);
+        }
+        Err(Error::from(tokio_tungstenite::tungstenite::Error::Io(
+            failure.expect("no replicas"),
+        )))
+    }
+
+    /// Open the consensus websocket (receiving messages matching `subscription`).
+    async fn subscribe(
+        &self,
+        subscription: &Subscription,
+    ) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, Error> {
+        let mut request = listen_path(self.ws_uri.clone(), subscription)
+            .into_client_request()
+            .map_err(Error::from)?;
+        if let Some(traceparent) = self.traceparent() {
+            let value = HeaderValue::from_str(&traceparent).expect("invalid traceparent");
+            request.headers_mut().insert(TRACEPARENT, value);
+        }
+        let config = WebSocketConfig::default()
+            .max_message_size(Some(MAX_MESSAGE_SIZE))
+            .max_frame_size(Some(MAX_MESSAGE_SIZE));
+        self.connect(request, config).await
+    }
+
+    /// Parse every message read from the consensus websocket (delivering [Ack]s to `pending`).
+    fn spawn_reader(
+        &self,
+        read: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+        pending: Pending,
+    ) -> UnboundedReceiver<Result<Message, Error>> {
         // Create an unbounded channel for streaming consensus messages
         let (sender, receiver) = unbounded();
         tokio::spawn({
-            let certificate_verifier = self.certificate_verifier.clone();
+            let identities = self.identities.clone();
             let strategy = self.strategy.clone();
+            let digest_mode = self.digest_mode;
             async move {
                 read.for_each(|message| async {
-                    match message {
-                        Ok(TMessage::Binary(data)) => {
-                            // Get kind
-                            let kind = data[0];
-                            let Some(kind) = Kind::from_u8(kind) else {
-                                let _ = sender.unbounded_send(Err(Error::UnexpectedResponse));
-                                return;
-                            };
-                            let data = &data[1..];
-
-                            // Deserialize the message
-                            match kind {
-                                Kind::Seed => {
-                                    let result = Seed::decode(data);
-                                    match result {
-                                        Ok(seed) => {
-                                            if !seed.verify(&certificate_verifier) {
-                                                let _ = sender
-                                                    .unbounded_send(Err(Error::InvalidSignature));
-                                                return;
-                                            }
-                                            let _ = sender.unbounded_send(Ok(Message::Seed(seed)));
-                                        }
-                                        Err(e) => {
-                                            let _ =
-                                                sender.unbounded_send(Err(Error::InvalidData(e)));
-                                        }
-                                    }
-                                }
-                                Kind::Notarization => {
-                                    let result = Notarized::decode(data);
-                                    match result {
-                                        Ok(notarized) => {
-                                            if !notarized.verify(&certificate_verifier, &strategy) {
-                                                let _ = sender
-                                                    .unbounded_send(Err(Error::InvalidSignature));
-                                                return;
-                                            }
-                                            let _ = sender.unbounded_send(Ok(
-                                                Message::Notarization(notarized),
-                                            ));
-                                        }
-                                        Err(e) => {
-                                            let _ =
-                                                sender.unbounded_send(Err(Error::InvalidData(e)));
-                                        }
-                                    }
-                                }
-                                Kind::Finalization => {
-                                    let result = Finalized::decode(data);
-                                    match result {
-                                        Ok(finalized) => {
-                                            if !finalized.verify(&certificate_verifier, &strategy) {
-                                                let _ = sender
-                                                    .unbounded_send(Err(Error::InvalidSignature));
-                                                return;
-                                            }
-                                            let _ = sender.unbounded_send(Ok(
-                                                Message::Finalization(finalized),
-                                            ));
-                                        }
-                                        Err(e) => {
-                                            let _ =
-                                                sender.unbounded_send(Err(Error::InvalidData(e)));
-                                        }
+                    let result = match message {
+                        Ok(TMessage::Binary(data)) if data.first() == Some(&ACK_FRAME) => {
+                            match Ack::decode(&data[1..]) {
+                                Ok(ack) => {
+                                    let waiter = pending
+                                        .lock()
+                                        .unwrap()
+                                        .as_mut()
+                                        .and_then(|pending| pending.remove(&ack.id));
+                                    if let Some(waiter) = waiter {
+                                        let _ = waiter.send(ack);
                                     }
+                                    return;
                                 }
+                                Err(e) => Err(Error::InvalidData(e)),
                             }
                         }
-                        Ok(_) => {} // Ignore non-binary messages.
-                        Err(e) => {
-                            let _ = sender.unbounded_send(Err(Error::from(e)));
+                        Ok(TMessage::Binary(data)) => {
+                            parse_message(&identities, &strategy, &digest_mode, &data)
                         }
-                    }
+                        Ok(TMessage::Text(_)) => Err(Error::UnexpectedResponse),
+                        Ok(_) => return, // Ignore control messages.
+                        Err(e) => Err(Error::from(e)),
+                    };
+                    let _ = sender.unbounded_send(result);
                 })
                 .await;
+
+                // Fail any uploads still waiting for an acknowledgment (or made afterwards)
+                *pending.lock().unwrap() = None;
             }
         });
+        receiver
+    }
+
+    pub async fn listen(&self) -> Result<impl Stream<Item = Result<Message, Error>>, Error> {
+        self.listen_filtered(&Subscription::default()).await
+    }
+
+    /// Listen to the consensus websocket, first receiving the stored messages with views at or
+    /// after `from_view` (so no message between an HTTP poll and the socket opening is missed).
+    ///
+    /// The indexer replays a bounded number of views: older messages are reported with a
+    /// [Message::Lagged] (and must be fetched over HTTP).
+    pub async fn listen_from(
+        &self,
+        from_view: u64,
+    ) -> Result<impl Stream<Item = Result<Message, Error>>, Error> {
+        self.listen_filtered(&Subscription::default().with_from_view(from_view))
+            .await
+    }
+
+    /// Listen to the consensus websocket, only receiving the messages matching `subscription`.
+    ///
+    /// Messages are filtered by the indexer (so filtered messages are never sent), but
+    /// [Message::Lagged] counts every message the indexer dropped (whether or not it matched).
+    pub async fn listen_filtered(
+        &self,
+        subscription: &Subscription,
+    ) -> Result<impl Stream<Item = Result<Message, Error>>, Error> {
+        let stream = self.subscribe(subscription).await?;
+        let (_, read) = stream.split();
+        Ok(self.spawn_reader(read, Pending::new(Mutex::new(Some(HashMap::new())))))
+    }
+
+    /// Listen to the consensus websocket and upload artifacts over the same connection.
+    ///
+    /// Uploads are signed like HTTP uploads (if a signer is configured) and each is
+    /// acknowledged by the indexer with the status the HTTP upload would have received. This
+    /// lets uploaders with restrictive egress hold a single connection open instead of issuing
+    /// a request per artifact.
+    pub async fn socket(
+        &self,
+    ) -> Result<(Uploader<S>, impl Stream<Item = Result<Message, Error>>), Error> {
+        self.socket_filtered(&Subscription::default()).await
+    }
+
+    /// Like [Client::socket], but only receiving the messages matching `subscription` (uploads
+    /// are acknowledged regardless).
+    pub async fn socket_filtered(
+        &self,
+        subscription: &Subscription,
+    ) -> Result<(Uploader<S>, impl Stream<Item = Result<Message, Error>>), Error> {
+        let stream = self.subscribe(subscription).await?;
+        let (write, read) = stream.split();
+        let pending = Pending::new(Mutex::new(Some(HashMap::new())));
+        let messages = self.spawn_reader(read, pending.clone());
+        let uploader = Uploader {
+            client: self.clone(),
+            write,
+            pending,
+            next: 0,
+        };
+        Ok((uploader, messages))
+    }
+}
+
+/// Uploads awaiting an [Ack] (by identifier), or `None` once the websocket is closed.
+type Pending = Arc<Mutex<Option<HashMap<u64, oneshot::Sender<Ack>>>>>;
+
+/// Uploads artifacts over a consensus websocket (see [Client::socket]).
+pub struct Uploader<S: Strategy> {
+    client: Client<S>,
+    write: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, TMessage>,
+    pending: Pending,
+    next: u64,
+}
+
+impl<S: Strategy> Uploader<S> {
+    /// Upload an encoded artifact of `kind` and wait for the indexer to acknowledge it.
+    ///
+    /// Returns the [Ack] whether or not the upload was accepted (see [Ack::accepted]).
+    pub async fn upload(&mut self, kind: Kind, body: Vec<u8>) -> Result<Ack, Error> {
+        let receiver = self.enqueue(kind, body).await?;
+        receiver.await.map_err(|_| Error::StreamClosed)
+    }
+
+    /// Upload an encoded artifact of `kind` without waiting for the indexer to acknowledge it.
+    ///
+    /// Returns a future that resolves once the upload is acknowledged (failing if it is not
+    /// accepted), so other artifacts can be uploaded in the meantime.
+    pub async fn send(
+        &mut self,
+        kind: Kind,
+        body: Vec<u8>,
+    ) -> Result<impl Future<Output = Result<(), Error>> + Send + 'static, Error> {
+        let receiver = self.enqueue(kind, body).await?;
+        Ok(async move { accepted(receiver.await.map_err(|_| Error::StreamClosed)?) })
+    }
+
+    /// Send an upload of an encoded artifact of `kind`, returning the receiver of its [Ack].
+    async fn enqueue(
+        &mut self,
+        kind: Kind,
+        body: Vec<u8>,
+    ) -> Result<oneshot::Receiver<Ack>, Error> {
+        let id = self.next;
+        self.next += 1;
+        let upload = Upload {
+            id,
+            kind,
+            auth: self.client.sign(&body),
+            body: body.into(),
+        };
+        let (sender, receiver) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap()
+            .as_mut()
+            .ok_or(Error::StreamClosed)?
+            .insert(id, sender);
+        if let Err(e) = self.write.send(TMessage::Binary(upload.encode())).await {
+            if let Some(pending) = self.pending.lock().unwrap().as_mut() {
+                pending.remove(&id);
+            }
+            return Err(Error::from(e));
+        }
         Ok(receiver)
     }
+
+    /// Upload an encoded artifact of `kind`, failing if it is not accepted.
+    async fn accepted(&mut self, kind: Kind, body: Vec<u8>) -> Result<(), Error> {
+        accepted(self.upload(kind, body).await?)
+    }
+
+    pub async fn seed_upload(&mut self, seed: Seed) -> Result<(), Error> {
+        self.accepted(Kind::Seed, seed.encode().to_vec()).await
+    }
+
+    pub async fn notarized_upload(&mut self, notarized: Notarized) -> Result<(), Error> {
+        self.accepted(Kind::Notarization, notarized.encode().to_vec())
+            .await
+    }
+
+    pub async fn finalized_upload(&mut self, finalized: Finalized) -> Result<(), Error> {
+        self.accepted(Kind::Finalization, finalized.encode().to_vec())
+            .await
+    }
+
+    pub async fn nullification_upload(
+        &mut self,
+        nullification: Nullification,
+    ) -> Result<(), Error> {
+        self.accepted(Kind::Nullification, nullification.encode().to_vec())
+            .await
+    }
+}
+
+/// Fail if `ack` reports that its upload was not accepted.
+fn accepted(ack: Ack) -> Result<(), Error> {
+    if !ack.accepted() {
+        let status = StatusCode::from_u16(ack.status).map_err(|_| Error::UnexpectedResponse)?;
+        return Err(Error::Failed(status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_types::{
+        fixtures::{self, Fixture},
+        Scheme,
+    };
+    use commonware_codec::Encode;
+    use commonware_codec::FixedSize;
+    use commonware_parallel::Sequential;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Number of fuzz iterations to run.
+    const ITERATIONS: usize = 1_000;
+
+    /// Create a verifier and a valid (kind-prefixed) frame for each artifact [Kind].
+    fn frames() -> (IdentitySchedule, Vec<Vec<u8>>) {
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let frames = fixtures::frames(&schemes);
+        (IdentitySchedule::new(fixtures::identity(&schemes)), frames)
+    }
+
+    #[test]
+    fn test_parse_rotation() {
+        let Fixture { schemes: old, .. } = fixtures::seeded(0, 4);
+        let Fixture { schemes: new, .. } = fixtures::seeded(1, 4);
+        let mut identities = IdentitySchedule::new(fixtures::identity(&old));
+        identities.rotate(2, fixtures::identity(&new));
+        let parse =
+            |frame: &[u8]| parse_message(&identities, &Sequential, &DigestMode::Sha256, frame);
+
+        // Artifacts before the rotation are verified with the old identity
+        let old_frames = fixtures::frames(&old);
+        assert!(matches!(parse(&old_frames[0]), Ok(Message::Seed(_))));
+        assert!(matches!(
+            parse(&old_frames[1]),
+            Ok(Message::Finalization(_))
+        ));
+        let new_frames = fixtures::frames(&new);
+        assert!(matches!(
+            parse(&new_frames[0]),
+            Err(Error::InvalidSignature)
+        ));
+
+        // Artifacts after the rotation are verified with the new identity
+        assert!(matches!(
+            parse(&new_frames[2]),
+            Ok(Message::Nullification(_))
+        ));
+        assert!(matches!(
+            parse(&old_frames[2]),
+            Err(Error::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_parse_epochs() {
+        let Fixture { schemes: first, .. } = fixtures::seeded(0, 4);
+        let Fixture {
+            schemes: second, ..
+        } = fixtures::seeded(1, 5);
+        let mut identities = IdentitySchedule::new(fixtures::identity(&first));
+        identities.reconfigure(1, fixtures::identity(&second));
+        let parse =
+            |frame: &[u8]| parse_message(&identities, &Sequential, &DigestMode::Sha256, frame);
+
+        // Views restart in the new epoch, so the same view is verified with either identity
+        let frame = |schemes: &[Scheme], epoch| {
+            let nullification = fixtures::nullification_in(schemes, epoch, 3);
+            fixtures::frame(Kind::Nullification, &nullification)
+        };
+        assert!(matches!(
+            parse(&frame(&first, 0)),
+            Ok(Message::Nullification(_))
+        ));
+        assert!(matches!(
+            parse(&frame(&second, 1)),
+            Ok(Message::Nullification(_))
+        ));
+
+        // Artifacts signed by the wrong validator set for their epoch are rejected
+        assert!(matches!(
+            parse(&frame(&second, 0)),
+            Err(Error::InvalidSignature)
+        ));
+        assert!(matches!(
+            parse(&frame(&first, 2)),
+            Err(Error::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_parse_valid() {
+        let (verifier, frames) = frames();
+        assert!(matches!(
+            parse_message(&verifier, &Sequential, &DigestMode::Sha256, &frames[0]),
+            Ok(Message::Seed(_))
+        ));
+        assert!(matches!(
+            parse_message(&verifier, &Sequential, &DigestMode::Sha256, &frames[1]),
+            Ok(Message::Finalization(_))
+        ));
+        assert!(matches!(
+            parse_message(&verifier, &Sequential, &DigestMode::Sha256, &frames[2]),
+            Ok(Message::Nullification(_))
+        ));
+
+        // Lag notifications carry the number of skipped messages
+        let mut frame = vec![LAGGED_FRAME];
+        frame.extend_from_slice(&3u64.encode());
+        assert!(matches!(
+            parse_message(&verifier, &Sequential, &DigestMode::Sha256, &frame),
+            Ok(Message::Lagged(3))
+        ));
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let (verifier, frames) = frames();
+        assert!(matches!(
+            parse_message(&verifier, &Sequential, &DigestMode::Sha256, &[]),
+            Err(Error::EmptyFrame)
+        ));
+        assert!(matches!(
+            parse_message(&verifier, &Sequential, &DigestMode::Sha256, &[5]),
+            Err(Error::UnknownKind(5))
+        ));
+        assert!(matches!(
+            parse_message(&verifier, &Sequential, &DigestMode::Sha256, &[LAGGED_FRAME]),
+            Err(Error::InvalidData(_))
+        ));
+        assert!(matches!(
+            parse_message(
+                &verifier,
+                &Sequential,
+                &DigestMode::Sha256,
+                &[Kind::Seed as u8]
+            ),
+            Err(Error::InvalidData(_))
+        ));
+
+        // Trailing data is rejected
+        let mut frame = frames[0].clone();
+        frame.push(0);
+        assert!(matches!(
+            parse_message(&verifier, &Sequential, &DigestMode::Sha256, &frame),
+            Err(Error::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_fuzz_random_frames() {
+        let (verifier, _) = frames();
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..ITERATIONS {
+            let len = rng.gen_range(0..512);
+            let mut frame = vec![0u8; len];
+            rng.fill(frame.as_mut_slice());
+            if let Some(kind) = frame.first_mut() {
+                // Bias towards known kinds (and lag notifications) to exercise the decoders
+                *kind %= 6;
+                if *kind == 4 {
+                    *kind = LAGGED_FRAME;
+                }
+            }
+
+            // Only lag notifications (which carry no signature) may decode from random bytes
+            match parse_message(&verifier, &Sequential, &DigestMode::Sha256, &frame) {
+                Ok(Message::Lagged(_)) => assert_eq!(frame.len(), 1 + u64::SIZE),
+                Ok(_) => panic!("random frame decoded as an artifact"),
+                Err(_) => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_mutated_frames() {
+        let (verifier, frames) = frames();
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..ITERATIONS {
+            let mut frame = frames[rng.gen_range(0..frames.len())].clone();
+            match rng.gen_range(0..3) {
+                0 => {
+                    // Truncate
+                    frame.truncate(rng.gen_range(0..frame.len()));
+                }
+                1 => {
+                    // Flip a bit (after the kind byte)
+                    let index = rng.gen_range(1..frame.len());
+                    frame[index] ^= 1 << rng.gen_range(0..8);
+                }
+                _ => {
+                    // Extend
+                    let extra: Vec<u8> = (0..rng.gen_range(1..64)).map(|_| rng.gen()).collect();
+                    frame.extend_from_slice(&extra);
+                }
+            }
+
+            // Mutations must never panic (and never yield a verified artifact that
+            // differs from the original)
+            if let Ok(message) = parse_message(&verifier, &Sequential, &DigestMode::Sha256, &frame)
+            {
+                let encoded = match message {
+                    Message::Seed(seed) => seed.encode(),
+                    Message::Notarization(notarized) => notarized.encode(),
+                    Message::Finalization(finalized) => finalized.encode(),
+                    Message::Nullification(nullification) => nullification.encode(),
+                    Message::Lagged(skipped) => skipped.encode(),
+                };
+                assert_eq!(&encoded[..], &frame[1..]);
+            }
+        }
+    }
 }
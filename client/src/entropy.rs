@@ -0,0 +1,137 @@
+use crate::{consensus::Message, Client, Error};
+use alto_types::{Seed, SeedExt, RANDOMNESS_SIZE};
+use commonware_consensus::{
+    types::{Epoch, View},
+    Viewable,
+};
+use commonware_parallel::Strategy;
+use futures::{future, Stream, StreamExt};
+
+/// Size (in bytes) of each entropy output.
+pub const ENTROPY_SIZE: usize = RANDOMNESS_SIZE;
+
+/// Entropy derived from a verified [Seed].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entropy {
+    /// Epoch of the seed.
+    pub epoch: Epoch,
+
+    /// View of the seed.
+    pub view: View,
+
+    /// Seed the entropy was derived from (for auditing).
+    pub seed: Seed,
+
+    /// Derived entropy.
+    pub output: [u8; ENTROPY_SIZE],
+}
+
+impl Entropy {
+    /// Derive [Entropy] from a (verified) [Seed] (see [SeedExt::randomness]).
+    pub fn derive(seed: Seed) -> Self {
+        let output = seed.randomness();
+        Self {
+            epoch: seed.round.epoch(),
+            view: seed.view(),
+            seed,
+            output,
+        }
+    }
+}
+
+impl<S: Strategy> Client<S> {
+    /// Stream [Entropy] derived from verified seeds, in increasing view order.
+    ///
+    /// Seeds for views at or below the last emitted view are skipped, so no entropy output is
+    /// ever emitted twice.
+    pub async fn entropy_stream(
+        &self,
+    ) -> Result<impl Stream<Item = Result<Entropy, Error>>, Error> {
+        let stream = self.listen().await?;
+        Ok(entropy(stream))
+    }
+}
+
+/// Derive [Entropy] from the seeds in a stream of [Message]s (skipping seeds that do not advance
+/// the view).
+fn entropy(
+    messages: impl Stream<Item = Result<Message, Error>>,
+) -> impl Stream<Item = Result<Entropy, Error>> {
+    let mut last = None;
+    messages.filter_map(move |message| {
+        let result = match message {
+            Ok(Message::Seed(seed)) => {
+                let view = seed.view();
+                if last.is_some_and(|last| view <= last) {
+                    None
+                } else {
+                    last = Some(view);
+                    Some(Ok(Entropy::derive(seed)))
+                }
+            }
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        };
+        future::ready(result)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_types::{
+        fixtures::{self, seed, Fixture},
+        EPOCH,
+    };
+
+    #[test]
+    fn test_entropy_derivation() {
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+
+        // Derivation is deterministic and carries metadata
+        let first = Entropy::derive(seed(&schemes, 1));
+        assert_eq!(first, Entropy::derive(seed(&schemes, 1)));
+        assert_eq!(first.epoch, EPOCH);
+        assert_eq!(first.view, View::new(1));
+
+        // Different views yield different entropy
+        let second = Entropy::derive(seed(&schemes, 2));
+        assert_eq!(second.view, View::new(2));
+        assert_ne!(first.output, second.output);
+
+        // Entropy is not the raw signature
+        assert_ne!(
+            first.output.as_slice(),
+            &first.seed.signature_bytes()[..ENTROPY_SIZE]
+        );
+    }
+
+    #[test]
+    fn test_entropy_stream() {
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+
+        // Only seeds that advance the view yield entropy (errors are passed through)
+        let messages = vec![
+            Ok(Message::Seed(seed(&schemes, 1))),
+            Ok(Message::Lagged(5)),
+            Ok(Message::Seed(seed(&schemes, 3))),
+            Ok(Message::Seed(seed(&schemes, 3))),
+            Ok(Message::Seed(seed(&schemes, 2))),
+            Err(Error::InvalidSignature),
+            Ok(Message::Seed(seed(&schemes, 4))),
+        ];
+        let outputs: Vec<_> =
+            futures::executor::block_on(entropy(futures::stream::iter(messages)).collect());
+        assert_eq!(outputs.len(), 4);
+        assert_eq!(outputs[0].as_ref().unwrap().view, View::new(1));
+        assert_eq!(outputs[1].as_ref().unwrap().view, View::new(3));
+        assert!(matches!(outputs[2], Err(Error::InvalidSignature)));
+        assert_eq!(outputs[3].as_ref().unwrap().view, View::new(4));
+
+        // Outputs match direct derivation
+        assert_eq!(
+            outputs[1].as_ref().unwrap(),
+            &Entropy::derive(seed(&schemes, 3))
+        );
+    }
+}
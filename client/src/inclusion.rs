@@ -0,0 +1,138 @@
+use crate::{consensus::Message, Client, Error, IndexQuery, Query};
+use alto_types::{Finalized, Notarized, Payload};
+use commonware_cryptography::{sha256::Digest, Digestible};
+use commonware_parallel::Strategy;
+use futures::{Stream, StreamExt};
+use reqwest::StatusCode;
+
+/// A certificate over a block (returned by [Client::wait_for_digest]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Inclusion {
+    Notarized(Box<Notarized>),
+    Finalized(Box<Finalized>),
+}
+
+impl From<Notarized> for Inclusion {
+    fn from(notarized: Notarized) -> Self {
+        Self::Notarized(Box::new(notarized))
+    }
+}
+
+impl From<Finalized> for Inclusion {
+    fn from(finalized: Finalized) -> Self {
+        Self::Finalized(Box::new(finalized))
+    }
+}
+
+impl<S: Strategy> Client<S> {
+    /// Wait for a (verified) notarization or finalization of the block with `digest`.
+    ///
+    /// The consensus stream is subscribed to before the indexer is queried over HTTP, so a
+    /// certificate that arrives while the lookup is in flight is not missed. If the block was
+    /// already finalized (or is the latest notarized block), its certificate is returned without
+    /// waiting.
+    pub async fn wait_for_digest(&self, digest: Digest) -> Result<Inclusion, Error> {
+        let stream = self.listen().await?;
+        if let Some(inclusion) = self.lookup_inclusion(digest).await? {
+            return Ok(inclusion);
+        }
+        wait(stream, digest).await
+    }
+
+    /// Look up an existing certificate over the block with `digest`.
+    async fn lookup_inclusion(&self, digest: Digest) -> Result<Option<Inclusion>, Error> {
+        // Check the latest notarization
+        match self.notarized_get(IndexQuery::Latest).await {
+            Ok(notarized) if notarized.block.digest() == digest => {
+                return Ok(Some(notarized.into()));
+            }
+            Ok(_) | Err(Error::Failed(StatusCode::NOT_FOUND)) => {}
+            Err(e) => return Err(e),
+        }
+
+        // Check the finalization at the block's height (if the indexer has the block)
+        let height = match self.block_get(Query::Digest(digest)).await {
+            Ok(payload) => payload.block().height.get(),
+            Err(Error::Failed(StatusCode::NOT_FOUND)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        match self.block_get(Query::Index(height)).await {
+            Ok(Payload::Finalized(finalized)) if finalized.block.digest() == digest => {
+                Ok(Some(Inclusion::Finalized(finalized)))
+            }
+            Ok(_) | Err(Error::Failed(StatusCode::NOT_FOUND)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Wait for the first notarization or finalization of the block with `digest` in a stream of
+/// [Message]s.
+async fn wait(
+    messages: impl Stream<Item = Result<Message, Error>>,
+    digest: Digest,
+) -> Result<Inclusion, Error> {
+    let mut messages = std::pin::pin!(messages);
+    while let Some(message) = messages.next().await {
+        match message? {
+            Message::Notarization(notarized) if notarized.block.digest() == digest => {
+                return Ok(notarized.into());
+            }
+            Message::Finalization(finalized) if finalized.block.digest() == digest => {
+                return Ok(finalized.into());
+            }
+            _ => {}
+        }
+    }
+    Err(Error::StreamClosed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_types::{fixtures, Block};
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Hasher, Sha256};
+    use futures::stream;
+
+    #[tokio::test]
+    async fn test_wait() {
+        let fixtures::Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+        let other = Block::new(Sha256::hash(b"genesis"), Height::new(1), 2000);
+        let notarized = |view, block: &Block| {
+            let proposal = fixtures::proposal(view, block.digest());
+            Notarized::new(fixtures::notarization(&schemes, proposal), block.clone())
+        };
+
+        // Certificates over other blocks are skipped
+        let messages = vec![
+            Ok(Message::Seed(fixtures::seed(&schemes, 1))),
+            Ok(Message::Notarization(notarized(1, &other))),
+            Ok(Message::Notarization(notarized(2, &block))),
+            Ok(Message::Finalization(fixtures::finalized(
+                &schemes,
+                2,
+                block.clone(),
+            ))),
+        ];
+        let inclusion = wait(stream::iter(messages), block.digest()).await.unwrap();
+        assert_eq!(inclusion, notarized(2, &block).into());
+
+        // Finalizations resolve the wait
+        let finalized = fixtures::finalized(&schemes, 3, block.clone());
+        let messages = vec![Ok(Message::Finalization(finalized.clone()))];
+        let inclusion = wait(stream::iter(messages), block.digest()).await.unwrap();
+        assert_eq!(inclusion, finalized.into());
+
+        // Errors are surfaced
+        let messages = vec![Err(Error::InvalidSignature)];
+        let result = wait(stream::iter(messages), block.digest()).await;
+        assert!(matches!(result, Err(Error::InvalidSignature)));
+
+        // A closed stream without the block is an error
+        let messages = vec![Ok(Message::Notarization(notarized(1, &other)))];
+        let result = wait(stream::iter(messages), block.digest()).await;
+        assert!(matches!(result, Err(Error::StreamClosed)));
+    }
+}
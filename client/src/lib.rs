@@ -1,36 +1,145 @@
 //! Client for interacting with `alto`.
 
-use alto_types::{Identity, Scheme, NAMESPACE};
-use commonware_cryptography::sha256::Digest;
+use alto_types::{DigestMode, Identity, IdentitySchedule, PublicKey, NAMESPACE};
+use cache::BlockCache;
+use commonware_codec::{Encode, FixedSize};
+use commonware_cryptography::{ed25519, sha256::Digest, Hasher, Sha256, Signer};
 use commonware_parallel::Strategy;
-use commonware_utils::hex;
-use std::sync::Arc;
+use commonware_utils::{hex, union_unique, SystemTimeExt};
+use opentelemetry::trace::TraceContextExt;
+use rand::RngCore;
+use replicas::{Replica, Replicas};
+use std::{
+    net::SocketAddr,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex, OnceLock},
+    time::SystemTime,
+};
 use thiserror::Error;
+use tracing::{warn, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod cache;
 pub mod consensus;
+pub mod entropy;
+pub mod inclusion;
+pub mod replicas;
+pub mod stream;
+pub mod transaction;
 pub mod utils;
+pub mod verify;
 
 pub const LATEST: &str = "latest";
 
+/// Header used to propagate [W3C trace context](https://www.w3.org/TR/trace-context/).
+pub const TRACEPARENT: &str = "traceparent";
+
+/// Header carrying the (hex-encoded) public key of the validator that signed an upload.
+pub const UPLOAD_SIGNER: &str = "x-alto-signer";
+
+/// Header carrying the time (in milliseconds since the epoch) an upload was signed.
+pub const UPLOAD_TIMESTAMP: &str = "x-alto-timestamp";
+
+/// Header carrying the (hex-encoded) signature over [upload_message].
+pub const UPLOAD_SIGNATURE: &str = "x-alto-signature";
+
+/// Header carrying the (hex-encoded) public key of the validator submitting an unsigned upload.
+///
+/// The indexer does not verify this header (it is only used to attribute uploads when upload
+/// authentication is disabled).
+pub const UPLOAD_VALIDATOR: &str = "x-alto-validator";
+
+/// Header carrying the API version spoken by the indexer (and expected by the client).
+pub const API_VERSION: &str = "x-alto-api-version";
+
+/// Current API version.
+///
+/// Version 1 added [IndexQuery::Range] (indexers that omit [API_VERSION] only serve single
+/// artifacts).
+pub const CURRENT_API_VERSION: u32 = 1;
+
+/// Maximum number of views that can be requested with a single [IndexQuery::Range].
+pub const MAX_RANGE: u64 = 256;
+
+/// Maximum number of views that can be exported with a single request to `/export`.
+pub const MAX_EXPORT: u64 = 65_536;
+
+/// Maximum number of consecutive times an interrupted export is resumed (without receiving
+/// any bytes in between).
+pub const MAX_EXPORT_RESUMES: usize = 8;
+
+/// Maximum number of blocks fetched concurrently by [Client::blocks] (when the indexer does not
+/// serve ranges).
+pub const MAX_CONCURRENT_FETCHES: usize = 16;
+
+/// Minimum number of hex characters in a [Query::Prefix].
+pub const MIN_PREFIX: usize = 4;
+
+/// Maximum number of digests returned for an ambiguous [Query::Prefix].
+pub const MAX_PREFIX_MATCHES: usize = 16;
+
+/// Suffix appended to [NAMESPACE] when signing uploads.
+pub const UPLOAD_SUFFIX: &[u8] = b"_UPLOAD";
+
+/// Namespace used when signing uploads.
+pub fn upload_namespace() -> Vec<u8> {
+    union_unique(NAMESPACE, UPLOAD_SUFFIX)
+}
+
+/// Message signed to authenticate an upload of `body` at `timestamp`.
+pub fn upload_message(body: &[u8], timestamp: u64) -> Vec<u8> {
+    let mut message = Sha256::hash(body).to_vec();
+    message.extend_from_slice(&timestamp.to_be_bytes());
+    message
+}
+
 pub enum Query {
     Latest,
     Index(u64),
     Digest(Digest),
+    /// The block whose (hex-encoded) digest starts with a unique prefix (see [Query::prefix]).
+    Prefix(String),
 }
 
 impl Query {
+    /// Create a [Query::Prefix] from hex characters (with an optional `0x`).
+    ///
+    /// Returns `None` if `prefix` is not a valid [is_digest_prefix].
+    pub fn prefix(prefix: &str) -> Option<Self> {
+        let prefix = prefix.strip_prefix("0x").unwrap_or(prefix).to_lowercase();
+        is_digest_prefix(&prefix).then_some(Query::Prefix(prefix))
+    }
+
     pub fn serialize(&self) -> String {
         match self {
             Query::Latest => LATEST.to_string(),
             Query::Index(index) => hex(&index.to_be_bytes()),
             Query::Digest(digest) => hex(digest),
+            Query::Prefix(prefix) => prefix.clone(),
         }
     }
 }
 
+/// Returns whether `query` is a prefix of a hex-encoded digest.
+///
+/// Prefixes are lowercase hex characters, at least [MIN_PREFIX] long and shorter than a digest.
+/// Because heights are serialized as 16 hex characters, prefixes of that length are not
+/// allowed (use one more character instead).
+pub fn is_digest_prefix(query: &str) -> bool {
+    (MIN_PREFIX..Digest::SIZE * 2).contains(&query.len())
+        && query.len() != u64::SIZE * 2
+        && query
+            .bytes()
+            .all(|c| c.is_ascii_digit() || (b'a'..=b'f').contains(&c))
+}
+
 pub enum IndexQuery {
     Latest,
     Index(u64),
+    /// All views in `[start, end)` (views without an artifact are skipped).
+    Range(u64, u64),
 }
 
 impl IndexQuery {
@@ -38,6 +147,9 @@ impl IndexQuery {
         match self {
             IndexQuery::Latest => LATEST.to_string(),
             IndexQuery::Index(index) => hex(&index.to_be_bytes()),
+            IndexQuery::Range(start, end) => {
+                format!("{}..{}", hex(&start.to_be_bytes()), hex(&end.to_be_bytes()))
+            }
         }
     }
 }
@@ -56,17 +168,95 @@ pub enum Error {
     InvalidSignature,
     #[error("unexpected response")]
     UnexpectedResponse,
+    #[error("empty frame")]
+    EmptyFrame,
+    #[error("unknown kind: {0}")]
+    UnknownKind(u8),
+    #[error("invalid query")]
+    InvalidQuery,
+    #[error("ambiguous prefix (matches {} blocks)", .0.len())]
+    AmbiguousPrefix(Vec<Digest>),
+    #[error("HTTP/3 requires an https:// URI")]
+    InsecureHttp3,
+    #[error("stream closed")]
+    StreamClosed,
+    #[error("missing block at height {0}")]
+    MissingBlock(u64),
+    #[error("block at height {0} does not extend its parent")]
+    BrokenChain(u64),
+    #[error("invalid URI: {0}")]
+    InvalidUri(String),
+    #[error("failed to resolve indexer replicas: {0}")]
+    Discovery(std::io::Error),
+}
+
+impl Error {
+    /// Whether a request that failed with this error may succeed if retried.
+    ///
+    /// Requests the server rejected as invalid (any `4xx` status other than `408`, `425`, or
+    /// `429`) fail the same way every time. Connection failures, server errors, and everything
+    /// else are considered transient.
+    pub fn retryable(&self) -> bool {
+        let Self::Failed(status) = self else {
+            return true;
+        };
+        !status.is_client_error()
+            || matches!(
+                *status,
+                reqwest::StatusCode::REQUEST_TIMEOUT
+                    | reqwest::StatusCode::TOO_EARLY
+                    | reqwest::StatusCode::TOO_MANY_REQUESTS
+            )
+    }
+}
+
+impl From<Error> for alto_types::Error {
+    fn from(error: Error) -> Self {
+        use std::io::{Error as IoError, ErrorKind};
+        match error {
+            Error::Failed(status) if status == reqwest::StatusCode::NOT_FOUND => {
+                Self::NotFound(error.to_string())
+            }
+            Error::AmbiguousPrefix(_) | Error::MissingBlock(_) => Self::NotFound(error.to_string()),
+            Error::InvalidData(e) => Self::Codec(e),
+            Error::EmptyFrame => Self::Codec(commonware_codec::Error::EndOfBuffer),
+            Error::UnknownKind(kind) => Self::Codec(commonware_codec::Error::InvalidEnum(kind)),
+            Error::InvalidQuery => {
+                Self::Codec(commonware_codec::Error::Invalid("Query", "invalid query"))
+            }
+            Error::InvalidSignature => Self::Signature,
+            Error::UnexpectedResponse | Error::BrokenChain(_) => {
+                Self::Consistency(error.to_string())
+            }
+            Error::Discovery(e) => Self::Io(e),
+            Error::StreamClosed => Self::Io(IoError::new(ErrorKind::UnexpectedEof, error)),
+            Error::InsecureHttp3 | Error::InvalidUri(_) => {
+                Self::Io(IoError::new(ErrorKind::InvalidInput, error))
+            }
+            Error::Reqwest(_) | Error::Tungstenite(_) | Error::Failed(_) => {
+                Self::Io(IoError::other(error))
+            }
+        }
+    }
 }
 
 /// TLS connector for WebSocket connections.
 type WsConnector = tokio_tungstenite::Connector;
 
 /// Builder for creating a [`Client`].
+#[derive(Clone)]
 pub struct ClientBuilder<S: Strategy> {
     uri: String,
     ws_uri: String,
-    identity: Identity,
+    identities: IdentitySchedule,
     tls_certs: Vec<Vec<u8>>,
+    trace_propagation: bool,
+    signer: Option<ed25519::PrivateKey>,
+    digest_mode: DigestMode,
+    #[cfg(feature = "http3")]
+    http3: bool,
+    replicas: Vec<SocketAddr>,
+    block_cache: Option<NonZeroUsize>,
     strategy: S,
 }
 
@@ -84,12 +274,39 @@ impl<S: Strategy> ClientBuilder<S> {
         Self {
             uri,
             ws_uri,
-            identity,
+            identities: IdentitySchedule::new(identity),
             tls_certs: Vec::new(),
+            trace_propagation: false,
+            signer: None,
+            digest_mode: DigestMode::default(),
+            #[cfg(feature = "http3")]
+            http3: false,
+            replicas: Vec::new(),
+            block_cache: None,
             strategy,
         }
     }
 
+    /// Verify artifacts from `view` onwards with `identity` (instead of the identity the
+    /// builder was created with).
+    ///
+    /// Use this to keep following a network across a re-key: artifacts before `view` are still
+    /// verified against the previous identity (see [IdentitySchedule]).
+    pub fn with_rotation(mut self, view: u64, identity: Identity) -> Self {
+        self.identities.rotate(view, identity);
+        self
+    }
+
+    /// Verify artifacts from `epoch` onwards with `identity` (the identity of the validator set
+    /// that took over at the start of `epoch`).
+    ///
+    /// Use this to keep following a network that reconfigures: artifacts from earlier epochs
+    /// are still verified against their own identity (see [IdentitySchedule]).
+    pub fn with_epoch(mut self, epoch: u64, identity: Identity) -> Self {
+        self.identities.reconfigure(epoch, identity);
+        self
+    }
+
     /// Add a trusted TLS certificate (DER-encoded).
     ///
     /// Use this for self-signed certificates that should be trusted.
@@ -98,17 +315,117 @@ impl<S: Strategy> ClientBuilder<S> {
         self
     }
 
-    /// Build the client.
-    pub fn build(self) -> Client<S> {
-        let certificate_verifier = Scheme::certificate_verifier(NAMESPACE, self.identity);
+    /// Attach a `traceparent` header to every HTTP and WebSocket request.
+    ///
+    /// The trace context of each operation's span (or, if it has none, a generated one) is
+    /// propagated and recorded on that span, allowing client requests to be correlated with
+    /// the caller's trace and indexer server spans.
+    pub fn with_trace_propagation(mut self) -> Self {
+        self.trace_propagation = true;
+        self
+    }
+
+    /// Sign every upload with the given validator key.
+    ///
+    /// Indexers configured with a set of allowed uploaders reject unsigned uploads.
+    pub fn with_signer(mut self, signer: ed25519::PrivateKey) -> Self {
+        self.signer = Some(signer);
+        self
+    }
 
-        // Build HTTP client
+    /// Decode blocks using digests computed with `digest_mode` (must match the network).
+    pub fn with_digest_mode(mut self, digest_mode: DigestMode) -> Self {
+        self.digest_mode = digest_mode;
+        self
+    }
+
+    /// Send all HTTP requests over HTTP/3 (QUIC) instead of TCP.
+    ///
+    /// The indexer must be reachable over `https://` and serve HTTP/3 on the same port
+    /// (the consensus WebSocket is still established over TCP). Requires the `http3` feature,
+    /// which must be compiled with `--cfg reqwest_unstable` (set in `.cargo/config.toml`).
+    #[cfg(feature = "http3")]
+    pub fn with_http3(mut self) -> Result<Self, Error> {
+        if !self.uri.starts_with("https://") {
+            return Err(Error::InsecureHttp3);
+        }
+        self.http3 = true;
+        Ok(self)
+    }
+
+    /// Spread requests across indexer replicas at `addrs` (all serving the indexer URI).
+    ///
+    /// Replicas are expected to listen on the port of the indexer URI (the port of each address
+    /// is ignored).
+    ///
+    /// Requests are sent to replicas in round-robin order, and replicas that cannot be reached
+    /// are avoided for [replicas::COOLDOWN] (see [replicas]).
+    pub fn with_replicas(mut self, addrs: Vec<SocketAddr>) -> Self {
+        self.replicas = addrs;
+        self
+    }
+
+    /// Keep up to `capacity` fetched blocks in memory, answering [Query::Digest] lookups for
+    /// them without contacting the indexer.
+    ///
+    /// Blocks are content-addressed (a digest always identifies the same block), so cached
+    /// blocks never go stale.
+    pub fn with_block_cache(mut self, capacity: NonZeroUsize) -> Self {
+        self.block_cache = Some(capacity);
+        self
+    }
+
+    /// Resolve the host of the indexer URI to every address it points to and spread requests
+    /// across them (see [Self::with_replicas]).
+    ///
+    /// The name is resolved once (when called), so replicas added later are not discovered
+    /// until the client is rebuilt.
+    pub async fn discover(self) -> Result<Self, Error> {
+        let (host, port) = host_port(&self.uri)?;
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+            .await
+            .map_err(Error::Discovery)?
+            .collect();
+        if addrs.is_empty() {
+            return Err(Error::Discovery(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{host} did not resolve to any address"),
+            )));
+        }
+        Ok(self.with_replicas(addrs))
+    }
+
+    /// Build an HTTP client (connecting to `replica`, if provided).
+    fn http_client(&self, replica: Option<SocketAddr>) -> reqwest::Client {
         let mut http_builder = reqwest::Client::builder();
         for cert_der in &self.tls_certs {
             let cert = reqwest::Certificate::from_der(cert_der).expect("invalid DER certificate");
             http_builder = http_builder.add_root_certificate(cert);
         }
-        let http_client = http_builder.build().expect("failed to build HTTP client");
+        #[cfg(feature = "http3")]
+        if self.http3 {
+            http_builder = http_builder.http3_prior_knowledge();
+        }
+        if let Some(addr) = replica {
+            let (host, _) = host_port(&self.uri).expect("invalid URI");
+            http_builder = http_builder.resolve(&host, addr);
+        }
+        http_builder.build().expect("failed to build HTTP client")
+    }
+
+    /// Build the client.
+    pub fn build(self) -> Client<S> {
+        // Build HTTP clients
+        let http_client = self.http_client(None);
+        let replicas = self
+            .replicas
+            .iter()
+            .map(|addr| {
+                let (_, port) = host_port(&self.uri).expect("invalid URI");
+                let addr = SocketAddr::new(addr.ip(), port);
+                Replica::new(addr, self.http_client(Some(addr)))
+            })
+            .collect();
 
         // Build WebSocket TLS connector with native root certificates
         let mut root_store = rustls::RootCertStore::empty();
@@ -133,9 +450,19 @@ impl<S: Strategy> ClientBuilder<S> {
         Client {
             uri: self.uri,
             ws_uri: self.ws_uri,
-            certificate_verifier,
+            identities: self.identities,
             http_client,
             ws_connector,
+            trace_propagation: self.trace_propagation,
+            signer: self.signer,
+            digest_mode: self.digest_mode,
+            #[cfg(feature = "http3")]
+            http3: self.http3,
+            ranges: Arc::new(OnceLock::new()),
+            replicas: Arc::new(Replicas::new(replicas)),
+            block_cache: self
+                .block_cache
+                .map(|capacity| Arc::new(Mutex::new(BlockCache::new(capacity)))),
             strategy: self.strategy,
         }
     }
@@ -145,10 +472,18 @@ impl<S: Strategy> ClientBuilder<S> {
 pub struct Client<S: Strategy> {
     uri: String,
     ws_uri: String,
-    certificate_verifier: Scheme,
+    identities: IdentitySchedule,
 
     http_client: reqwest::Client,
     ws_connector: WsConnector,
+    trace_propagation: bool,
+    signer: Option<ed25519::PrivateKey>,
+    digest_mode: DigestMode,
+    #[cfg(feature = "http3")]
+    http3: bool,
+    ranges: Arc<OnceLock<bool>>,
+    replicas: Arc<Replicas>,
+    block_cache: Option<Arc<Mutex<BlockCache>>>,
     strategy: S,
 }
 
@@ -164,4 +499,122 @@ impl<S: Strategy> Client<S> {
     pub fn new(uri: &str, identity: Identity, strategy: S) -> Self {
         ClientBuilder::new(uri, identity, strategy).build()
     }
+
+    /// Get the `traceparent` of the current span (if trace propagation is enabled) and record it
+    /// on that span.
+    fn traceparent(&self) -> Option<String> {
+        if !self.trace_propagation {
+            return None;
+        }
+        let traceparent = traceparent();
+        Span::current().record("traceparent", traceparent.as_str());
+        Some(traceparent)
+    }
+
+    /// Attach a `traceparent` header to the request (if trace propagation is enabled).
+    ///
+    /// Also requests HTTP/3 (if enabled), as all requests pass through here.
+    fn traced(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        #[cfg(feature = "http3")]
+        let request = if self.http3 {
+            request.version(reqwest::Version::HTTP_3)
+        } else {
+            request
+        };
+        match self.traceparent() {
+            Some(traceparent) => request.header(TRACEPARENT, traceparent),
+            None => request,
+        }
+    }
+
+    /// Send a request.
+    ///
+    /// If the client was built with replicas, the request is sent to the next replica (failing
+    /// over to the others if it cannot be reached).
+    async fn execute(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        if self.replicas.is_empty() {
+            return request.send().await;
+        }
+        let request = request.build()?;
+        let mut failure = None;
+        for replica in self.replicas.order() {
+            let Some(attempt) = request.try_clone() else {
+                break;
+            };
+            match replica.http_client.execute(attempt).await {
+                Ok(response) => {
+                    replica.succeeded();
+                    return Ok(response);
+                }
+                Err(e) if e.is_connect() || e.is_timeout() => {
+                    warn!(addr = %replica.addr, error = %e, "indexer replica unreachable");
+                    replica.failed();
+                    failure = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        match failure {
+            Some(e) => Err(e),
+            None => self.http_client.execute(request).await,
+        }
+    }
+
+    /// Sign an upload of `body` (if a signer is configured).
+    fn sign(&self, body: &[u8]) -> Option<(PublicKey, u64, ed25519::Signature)> {
+        let signer = self.signer.as_ref()?;
+        let timestamp = SystemTime::now().epoch_millis();
+        let signature = signer.sign(&upload_namespace(), &upload_message(body, timestamp));
+        Some((signer.public_key(), timestamp, signature))
+    }
+
+    /// Attach `body` to the request, signing it (if a signer is configured).
+    fn signed(&self, request: reqwest::RequestBuilder, body: Vec<u8>) -> reqwest::RequestBuilder {
+        let Some((signer, timestamp, signature)) = self.sign(&body) else {
+            return request.body(body);
+        };
+        request
+            .header(UPLOAD_SIGNER, hex(&signer.encode()))
+            .header(UPLOAD_TIMESTAMP, timestamp.to_string())
+            .header(UPLOAD_SIGNATURE, hex(&signature.encode()))
+            .body(body)
+    }
+}
+
+/// Get the host and port of `uri`.
+fn host_port(uri: &str) -> Result<(String, u16), Error> {
+    let url = reqwest::Url::parse(uri).map_err(|e| Error::InvalidUri(e.to_string()))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| Error::InvalidUri(format!("{uri} has no host")))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| Error::InvalidUri(format!("{uri} has no port")))?;
+    Ok((host.to_string(), port))
+}
+
+/// Get the `traceparent` value of the current span's trace context.
+///
+/// If the current span has no trace context (e.g. no OpenTelemetry layer is installed), a random
+/// (sampled) one is generated.
+fn traceparent() -> String {
+    let context = Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    if span_context.is_valid() {
+        return format!(
+            "00-{}-{}-{:02x}",
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags().to_u8()
+        );
+    }
+    let mut trace_id = [0u8; 16];
+    let mut parent_id = [0u8; 8];
+    rand::thread_rng().fill_bytes(&mut trace_id);
+    rand::thread_rng().fill_bytes(&mut parent_id);
+    format!("00-{}-{}-01", hex(&trace_id), hex(&parent_id))
 }
@@ -0,0 +1,138 @@
+//! Client-side load balancing across indexer replicas.
+//!
+//! An indexer deployment may run several replicas behind a single DNS name. Instead of relying
+//! on the resolver to pick one (and sticking to it if it goes down), [crate::ClientBuilder::discover]
+//! resolves the name to every replica address. Requests are spread across replicas in
+//! round-robin order, and a replica that cannot be reached is skipped for [COOLDOWN] (falling
+//! back to it only if no other replica is available).
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// How long to avoid a replica after failing to reach it.
+pub const COOLDOWN: Duration = Duration::from_secs(30);
+
+/// A single indexer replica.
+pub(crate) struct Replica {
+    /// Address the replica was resolved to.
+    pub addr: SocketAddr,
+
+    /// HTTP client that connects to `addr` (for requests to the indexer URI).
+    pub http_client: reqwest::Client,
+
+    /// Time before which the replica should be avoided (if it recently failed).
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+impl Replica {
+    pub fn new(addr: SocketAddr, http_client: reqwest::Client) -> Self {
+        Self {
+            addr,
+            http_client,
+            unhealthy_until: Mutex::new(None),
+        }
+    }
+
+    /// Whether the replica is healthy at `now`.
+    fn healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until
+            .lock()
+            .unwrap()
+            .is_none_or(|until| until <= now)
+    }
+
+    /// Record a successful request.
+    pub fn succeeded(&self) {
+        *self.unhealthy_until.lock().unwrap() = None;
+    }
+
+    /// Record a failure to reach the replica (avoiding it for [COOLDOWN]).
+    pub fn failed(&self) {
+        *self.unhealthy_until.lock().unwrap() = Some(Instant::now() + COOLDOWN);
+    }
+}
+
+/// A set of indexer replicas.
+#[derive(Default)]
+pub(crate) struct Replicas {
+    replicas: Vec<Replica>,
+    next: AtomicUsize,
+}
+
+impl Replicas {
+    pub fn new(replicas: Vec<Replica>) -> Self {
+        Self {
+            replicas,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replicas.is_empty()
+    }
+
+    /// Get the replicas in the order they should be tried: healthy replicas (starting from the
+    /// next in round-robin order) followed by unhealthy ones.
+    pub fn order(&self) -> Vec<&Replica> {
+        if self.replicas.is_empty() {
+            return Vec::new();
+        }
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % self.replicas.len();
+        let now = Instant::now();
+        let (mut healthy, unhealthy): (Vec<_>, Vec<_>) = self.replicas[start..]
+            .iter()
+            .chain(&self.replicas[..start])
+            .partition(|replica| replica.healthy(now));
+        healthy.extend(unhealthy);
+        healthy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replicas(n: u16) -> Replicas {
+        Replicas::new(
+            (0..n)
+                .map(|i| {
+                    let addr = SocketAddr::from(([127, 0, 0, 1], 8000 + i));
+                    Replica::new(addr, reqwest::Client::new())
+                })
+                .collect(),
+        )
+    }
+
+    fn ports(order: Vec<&Replica>) -> Vec<u16> {
+        order.iter().map(|replica| replica.addr.port()).collect()
+    }
+
+    #[test]
+    fn test_order() {
+        let replicas = replicas(3);
+
+        // Requests rotate across replicas
+        assert_eq!(ports(replicas.order()), vec![8000, 8001, 8002]);
+        assert_eq!(ports(replicas.order()), vec![8001, 8002, 8000]);
+        assert_eq!(ports(replicas.order()), vec![8002, 8000, 8001]);
+
+        // Failed replicas are tried last
+        replicas.replicas[1].failed();
+        assert_eq!(ports(replicas.order()), vec![8000, 8002, 8001]);
+        assert_eq!(ports(replicas.order()), vec![8002, 8000, 8001]);
+
+        // Recovered replicas rejoin the rotation
+        replicas.replicas[1].succeeded();
+        assert_eq!(ports(replicas.order()), vec![8002, 8000, 8001]);
+        assert_eq!(ports(replicas.order()), vec![8000, 8001, 8002]);
+
+        // No replicas, no order
+        assert!(Replicas::default().order().is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+//! Incremental decoding of range responses.
+//!
+//! Range responses (and any other response carrying a length-prefixed sequence of artifacts,
+//! like an archive export) can be large. [Decoder] decodes each artifact as soon as its bytes
+//! arrive, so a client only ever buffers a single (partial) artifact.
+
+use crate::Error;
+use bytes::{Buf, BytesMut};
+use commonware_codec::{Error as CodecError, RangeCfg, Read};
+
+/// Decodes a length-prefixed sequence of artifacts (encoded like a `Vec<T>`) from chunks.
+pub struct Decoder<T: Read> {
+    cfg: T::Cfg,
+    max: usize,
+    buffer: BytesMut,
+    remaining: Option<usize>,
+}
+
+impl<T: Read> Decoder<T> {
+    /// Create a new [Decoder] for a sequence of at most `max` artifacts decoded with `cfg`.
+    pub fn new(cfg: T::Cfg, max: usize) -> Self {
+        Self {
+            cfg,
+            max,
+            buffer: BytesMut::new(),
+            remaining: None,
+        }
+    }
+
+    /// Append a chunk of the sequence.
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Decode the next artifact.
+    ///
+    /// Returns `None` if the next artifact has not been fully received (or the sequence is
+    /// complete).
+    pub fn decode(&mut self) -> Result<Option<T>, Error> {
+        // Read the number of artifacts
+        let remaining = match self.remaining {
+            Some(remaining) => remaining,
+            None => {
+                let range = RangeCfg::from(..=self.max);
+                let read = |reader: &mut &[u8]| usize::read_cfg(reader, &range);
+                let Some(remaining) = read_from(&mut self.buffer, read)? else {
+                    return Ok(None);
+                };
+                self.remaining = Some(remaining);
+                remaining
+            }
+        };
+        if remaining == 0 {
+            return Ok(None);
+        }
+
+        // Read the next artifact
+        let read = |reader: &mut &[u8]| T::read_cfg(reader, &self.cfg);
+        let Some(artifact) = read_from(&mut self.buffer, read)? else {
+            return Ok(None);
+        };
+        self.remaining = Some(remaining - 1);
+        Ok(Some(artifact))
+    }
+
+    /// Ensure the sequence was complete (and nothing followed it) once all chunks are pushed.
+    pub fn finish(&self) -> Result<(), Error> {
+        match self.remaining {
+            Some(0) if self.buffer.is_empty() => Ok(()),
+            Some(0) => Err(Error::InvalidData(CodecError::ExtraData(self.buffer.len()))),
+            _ => Err(Error::InvalidData(CodecError::EndOfBuffer)),
+        }
+    }
+}
+
+/// Read a value from `buffer`, consuming its bytes only if it was fully received.
+fn read_from<V>(
+    buffer: &mut BytesMut,
+    read: impl FnOnce(&mut &[u8]) -> Result<V, CodecError>,
+) -> Result<Option<V>, Error> {
+    let mut reader = buffer.as_ref();
+    match read(&mut reader) {
+        Ok(value) => {
+            let consumed = buffer.len() - reader.len();
+            buffer.advance(consumed);
+            Ok(Some(value))
+        }
+        Err(CodecError::EndOfBuffer) => Ok(None),
+        Err(err) => Err(Error::InvalidData(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commonware_codec::Encode;
+
+    #[test]
+    fn test_decoder() {
+        let artifacts: Vec<u64> = (0..300).collect();
+        let encoded = artifacts.encode();
+
+        // Artifacts are decoded as soon as they are received
+        let mut decoder = Decoder::<u64>::new((), 300);
+        let mut decoded = Vec::new();
+        for byte in encoded.iter() {
+            decoder.push(&[*byte]);
+            while let Some(artifact) = decoder.decode().unwrap() {
+                decoded.push(artifact);
+            }
+        }
+        decoder.finish().unwrap();
+        assert_eq!(decoded, artifacts);
+
+        // Truncated sequences are rejected
+        let mut decoder = Decoder::<u64>::new((), 300);
+        decoder.push(&encoded[..encoded.len() - 1]);
+        while decoder.decode().unwrap().is_some() {}
+        assert!(decoder.finish().is_err());
+
+        // Trailing data is rejected
+        let mut decoder = Decoder::<u64>::new((), 300);
+        decoder.push(&encoded);
+        decoder.push(&[0]);
+        while decoder.decode().unwrap().is_some() {}
+        assert!(decoder.finish().is_err());
+
+        // Sequences longer than the maximum are rejected
+        let mut decoder = Decoder::<u64>::new((), 10);
+        decoder.push(&encoded);
+        assert!(decoder.decode().is_err());
+    }
+}
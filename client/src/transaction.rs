@@ -0,0 +1,33 @@
+use crate::{Client, Error};
+use alto_types::Transaction;
+use commonware_codec::Encode;
+use commonware_parallel::Strategy;
+use tracing::instrument;
+
+fn transaction_submit_path(base: String) -> String {
+    format!("{base}/tx")
+}
+
+impl<S: Strategy> Client<S> {
+    /// Submit `transaction` to the indexer (which forwards it to validators).
+    ///
+    /// Succeeds once at least one validator has accepted the transaction into its mempool (which
+    /// does not guarantee it will be included in a block).
+    #[instrument(level = "debug", skip_all, fields(traceparent))]
+    pub async fn transaction_submit(&self, transaction: &Transaction) -> Result<(), Error> {
+        let result = self
+            .execute(
+                self.traced(
+                    self.http_client
+                        .post(transaction_submit_path(self.uri.clone()))
+                        .body(transaction.encode().to_vec()),
+                ),
+            )
+            .await
+            .map_err(Error::Reqwest)?;
+        if !result.status().is_success() {
+            return Err(Error::Failed(result.status()));
+        }
+        Ok(())
+    }
+}
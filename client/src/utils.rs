@@ -1,16 +1,16 @@
 use crate::{Client, Error};
 use commonware_parallel::Strategy;
+use tracing::instrument;
 
 fn healthy_path(base: String) -> String {
     format!("{base}/health")
 }
 
 impl<S: Strategy> Client<S> {
+    #[instrument(level = "debug", skip_all, fields(traceparent))]
     pub async fn health(&self) -> Result<(), Error> {
         let result = self
-            .http_client
-            .get(healthy_path(self.uri.clone()))
-            .send()
+            .execute(self.traced(self.http_client.get(healthy_path(self.uri.clone()))))
             .await
             .map_err(Error::from)?;
         if !result.status().is_success() {
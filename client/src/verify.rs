@@ -0,0 +1,200 @@
+//! Verification of artifacts obtained out-of-band.
+//!
+//! The [crate::Client] decodes and verifies every artifact it fetches with the functions in this
+//! module. Tools that obtain artifacts some other way (files, other APIs, `export-finalization`)
+//! can apply the exact same checks to raw bytes with the `verify_*_bytes` functions, without
+//! connecting to an indexer.
+
+use crate::Error;
+use alto_types::{
+    DigestMode, Finalization, Finalized, Identity, IdentitySchedule, Notarization, Notarized,
+    Nullification, Seed,
+};
+use commonware_codec::{Decode, DecodeExt};
+use commonware_cryptography::sha256::Digest;
+use commonware_parallel::{Sequential, Strategy};
+use rand::rngs::OsRng;
+
+/// Decode and verify a [Seed].
+pub(crate) fn seed(identities: &IdentitySchedule, bytes: &[u8]) -> Result<Seed, Error> {
+    let seed = Seed::decode(bytes).map_err(Error::InvalidData)?;
+    if !seed.verify(identities.verifier(seed.round)) {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(seed)
+}
+
+/// Decode and verify a [Notarized] block.
+pub(crate) fn notarized(
+    identities: &IdentitySchedule,
+    strategy: &impl Strategy,
+    digest_mode: &DigestMode,
+    bytes: &[u8],
+) -> Result<Notarized, Error> {
+    let notarized = Notarized::decode_cfg(bytes, digest_mode).map_err(Error::InvalidData)?;
+    if !notarized.verify(identities.verifier(notarized.proof.round()), strategy) {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(notarized)
+}
+
+/// Decode and verify a [Notarization] (without its block).
+pub(crate) fn notarization(
+    identities: &IdentitySchedule,
+    strategy: &impl Strategy,
+    bytes: &[u8],
+) -> Result<Notarization, Error> {
+    let notarization = Notarization::decode(bytes).map_err(Error::InvalidData)?;
+    if !notarization.verify(
+        &mut OsRng,
+        identities.verifier(notarization.round()),
+        strategy,
+    ) {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(notarization)
+}
+
+/// Decode and verify a [Finalized] block.
+pub(crate) fn finalized(
+    identities: &IdentitySchedule,
+    strategy: &impl Strategy,
+    digest_mode: &DigestMode,
+    bytes: &[u8],
+) -> Result<Finalized, Error> {
+    let finalized = Finalized::decode_cfg(bytes, digest_mode).map_err(Error::InvalidData)?;
+    if !finalized.verify(identities.verifier(finalized.proof.round()), strategy) {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(finalized)
+}
+
+/// Decode and verify a [Finalization] (without its block).
+pub(crate) fn finalization(
+    identities: &IdentitySchedule,
+    strategy: &impl Strategy,
+    bytes: &[u8],
+) -> Result<Finalization, Error> {
+    let finalization = Finalization::decode(bytes).map_err(Error::InvalidData)?;
+    if !finalization.verify(
+        &mut OsRng,
+        identities.verifier(finalization.round()),
+        strategy,
+    ) {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(finalization)
+}
+
+/// Decode and verify a [Nullification].
+pub(crate) fn nullification(
+    identities: &IdentitySchedule,
+    strategy: &impl Strategy,
+    bytes: &[u8],
+) -> Result<Nullification, Error> {
+    let nullification = Nullification::decode(bytes).map_err(Error::InvalidData)?;
+    if !nullification.verify::<_, Digest>(
+        &mut OsRng,
+        identities.verifier(nullification.round()),
+        strategy,
+    ) {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(nullification)
+}
+
+/// Decode a [Seed] from `bytes` and verify it was signed by the network with `identity`.
+pub fn verify_seed_bytes(identity: Identity, bytes: &[u8]) -> Result<Seed, Error> {
+    seed(&IdentitySchedule::new(identity), bytes)
+}
+
+/// Decode a [Notarized] block from `bytes` and verify it was notarized by the network with
+/// `identity`.
+pub fn verify_notarized_bytes(
+    identity: Identity,
+    digest_mode: &DigestMode,
+    bytes: &[u8],
+) -> Result<Notarized, Error> {
+    notarized(
+        &IdentitySchedule::new(identity),
+        &Sequential,
+        digest_mode,
+        bytes,
+    )
+}
+
+/// Decode a [Finalized] block from `bytes` and verify it was finalized by the network with
+/// `identity`.
+pub fn verify_finalized_bytes(
+    identity: Identity,
+    digest_mode: &DigestMode,
+    bytes: &[u8],
+) -> Result<Finalized, Error> {
+    finalized(
+        &IdentitySchedule::new(identity),
+        &Sequential,
+        digest_mode,
+        bytes,
+    )
+}
+
+/// Decode a [Nullification] from `bytes` and verify it was signed by the network with
+/// `identity`.
+pub fn verify_nullification_bytes(
+    identity: Identity,
+    bytes: &[u8],
+) -> Result<Nullification, Error> {
+    nullification(&IdentitySchedule::new(identity), &Sequential, bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_types::{fixtures, Block};
+    use commonware_codec::Encode;
+    use commonware_consensus::{types::Height, Viewable};
+    use commonware_cryptography::{Digestible, Hasher, Sha256};
+
+    #[test]
+    fn test_verify_bytes() {
+        let fixtures::Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let identity = fixtures::identity(&schemes);
+        let fixtures::Fixture { schemes: other, .. } = fixtures::seeded(1, 4);
+        let mode = DigestMode::Sha256;
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+
+        // Valid artifacts are returned
+        let finalized = fixtures::finalized(&schemes, 1, block.clone());
+        let bytes = finalized.encode();
+        assert_eq!(
+            verify_finalized_bytes(identity, &mode, &bytes).unwrap(),
+            finalized
+        );
+        let seed = fixtures::seed(&schemes, 1);
+        assert_eq!(verify_seed_bytes(identity, &seed.encode()).unwrap(), seed);
+        let nullification = fixtures::nullification(&schemes, 2);
+        let verified = verify_nullification_bytes(identity, &nullification.encode()).unwrap();
+        assert_eq!(verified.view().get(), 2);
+        let proposal = fixtures::proposal(1, block.digest());
+        let notarized = Notarized::new(fixtures::notarization(&schemes, proposal), block.clone());
+        let verified = verify_notarized_bytes(identity, &mode, &notarized.encode()).unwrap();
+        assert_eq!(verified, notarized);
+
+        // Artifacts from another network are rejected
+        let forged = fixtures::finalized(&other, 1, block).encode();
+        assert!(matches!(
+            verify_finalized_bytes(identity, &mode, &forged),
+            Err(Error::InvalidSignature)
+        ));
+
+        // Malformed bytes are rejected
+        assert!(matches!(
+            verify_finalized_bytes(identity, &mode, &bytes[..bytes.len() - 1]),
+            Err(Error::InvalidData(_))
+        ));
+        assert!(matches!(
+            verify_finalized_bytes(identity, &DigestMode::Blake3, &bytes),
+            Err(Error::InvalidData(_))
+        ));
+    }
+}
@@ -0,0 +1,576 @@
+//! A key-value store built on `alto` transactions.
+//!
+//! Validators treat [Transaction]s as opaque data: they propose whatever their mempool holds (see
+//! `alto_chain::mempool`) and only check that blocks are well-formed. Applications built on
+//! `alto` interpret transactions themselves by executing finalized blocks in height order. Because
+//! every honest executor sees the same finalized blocks, they all arrive at the same [State].
+//!
+//! Each transaction carries a single [Operation]. Validators that install [valid] as their
+//! mempool validator (see `alto_chain::mempool::Validator`) neither accept nor verify blocks
+//! with transactions that do not decode as an [Operation]. On networks whose validators do not,
+//! such transactions are still included (and skipped during execution), so submitters should
+//! [Operation::decode] their own transactions before submitting them.
+//!
+//! Validators that also install an [Executor] (see `alto_chain::application::Executor`) execute
+//! every block they propose or verify on top of the [State] after its parent and commit the
+//! resulting [Receipt] to its digest, so clients can learn the outcome of a block (which depends on
+//! the state it was executed on) without executing it.
+//!
+//! # Status
+//!
+//! `alto-example-kv` is an example (meant to be copied) and is not published.
+
+use alto_client::{Client, Query};
+use alto_types::{Block, Transaction, MAX_BLOCK_TRANSACTIONS};
+use bytes::{Buf, BufMut, Bytes};
+use commonware_codec::{
+    DecodeExt, Encode, EncodeSize, Error as CodecError, RangeCfg, Read, ReadExt, Write,
+};
+use commonware_cryptography::{sha256::Digest, Digestible};
+use commonware_parallel::Strategy;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+use thiserror::Error;
+
+/// The maximum size (in bytes) of a key.
+pub const MAX_KEY_SIZE: usize = 256;
+
+/// The maximum size (in bytes) of a value.
+pub const MAX_VALUE_SIZE: usize = 32 * 1024;
+
+/// Errors that can occur when executing blocks.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("expected block at height {expected}, got {got}")]
+    UnexpectedHeight { expected: u64, got: u64 },
+    #[error("block does not extend the last executed block")]
+    UnexpectedParent,
+    #[error("client error: {0}")]
+    Client(#[from] alto_client::Error),
+}
+
+/// A change to the [State] (carried by a single [Transaction]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// Set `key` to `value`.
+    Set { key: Bytes, value: Bytes },
+    /// Remove `key` (if present).
+    Delete { key: Bytes },
+}
+
+impl Operation {
+    /// Create a [Transaction] carrying the operation.
+    pub fn transaction(&self) -> Transaction {
+        // Keys and values are bounded well below the maximum transaction size
+        Transaction::new(self.encode()).expect("operation exceeds transaction size")
+    }
+
+    /// Decode the operation carried by `transaction` (if any).
+    pub fn decode(transaction: &Transaction) -> Option<Self> {
+        <Self as DecodeExt<()>>::decode(transaction.data().clone()).ok()
+    }
+}
+
+impl Write for Operation {
+    fn write(&self, writer: &mut impl BufMut) {
+        match self {
+            Self::Set { key, value } => {
+                0u8.write(writer);
+                key.write(writer);
+                value.write(writer);
+            }
+            Self::Delete { key } => {
+                1u8.write(writer);
+                key.write(writer);
+            }
+        }
+    }
+}
+
+impl Read for Operation {
+    type Cfg = ();
+
+    fn read_cfg(reader: &mut impl Buf, _: &Self::Cfg) -> Result<Self, CodecError> {
+        let keys: RangeCfg<usize> = (..=MAX_KEY_SIZE).into();
+        match u8::read(reader)? {
+            0 => {
+                let key = Bytes::read_cfg(reader, &keys)?;
+                let values: RangeCfg<usize> = (..=MAX_VALUE_SIZE).into();
+                let value = Bytes::read_cfg(reader, &values)?;
+                Ok(Self::Set { key, value })
+            }
+            1 => {
+                let key = Bytes::read_cfg(reader, &keys)?;
+                Ok(Self::Delete { key })
+            }
+            tag => Err(CodecError::InvalidEnum(tag)),
+        }
+    }
+}
+
+impl EncodeSize for Operation {
+    fn encode_size(&self) -> usize {
+        match self {
+            Self::Set { key, value } => 1 + key.encode_size() + value.encode_size(),
+            Self::Delete { key } => 1 + key.encode_size(),
+        }
+    }
+}
+
+/// Returns true if `transaction` carries an [Operation].
+///
+/// Install as the mempool validator of every validator (with
+/// `alto_chain::mempool::Actor::new_with_validator`) to keep other transactions out of blocks.
+pub fn valid(transaction: &Transaction) -> bool {
+    Operation::decode(transaction).is_some()
+}
+
+/// Outcome of executing a single [Transaction].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Set a key that had no value.
+    Inserted,
+    /// Set a key that already had a value.
+    Updated,
+    /// Deleted a key that had a value.
+    Deleted,
+    /// Deleted a key that had no value (leaving the [State] unchanged).
+    Missing,
+    /// The transaction did not carry an [Operation] (and was skipped).
+    Skipped,
+}
+
+impl Write for Outcome {
+    fn write(&self, writer: &mut impl BufMut) {
+        let tag: u8 = match self {
+            Self::Inserted => 0,
+            Self::Updated => 1,
+            Self::Deleted => 2,
+            Self::Missing => 3,
+            Self::Skipped => 4,
+        };
+        tag.write(writer);
+    }
+}
+
+impl Read for Outcome {
+    type Cfg = ();
+
+    fn read_cfg(reader: &mut impl Buf, _: &Self::Cfg) -> Result<Self, CodecError> {
+        match u8::read(reader)? {
+            0 => Ok(Self::Inserted),
+            1 => Ok(Self::Updated),
+            2 => Ok(Self::Deleted),
+            3 => Ok(Self::Missing),
+            4 => Ok(Self::Skipped),
+            tag => Err(CodecError::InvalidEnum(tag)),
+        }
+    }
+}
+
+impl EncodeSize for Outcome {
+    fn encode_size(&self) -> usize {
+        1
+    }
+}
+
+/// Outcome of executing a block (the [Outcome] of each of its transactions, in order).
+///
+/// Outcomes depend on the [State] the block is executed on, so the same transactions produce
+/// different receipts on different forks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Receipt {
+    pub outcomes: Vec<Outcome>,
+}
+
+impl Receipt {
+    /// Number of operations applied.
+    pub fn applied(&self) -> usize {
+        self.outcomes.len() - self.skipped()
+    }
+
+    /// Number of transactions that did not carry an [Operation].
+    pub fn skipped(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| **outcome == Outcome::Skipped)
+            .count()
+    }
+
+    /// Decode the receipt committed to by `block` (if any).
+    pub fn decode(block: &Block) -> Option<Self> {
+        <Self as DecodeExt<()>>::decode(block.receipts.clone()).ok()
+    }
+}
+
+impl Write for Receipt {
+    fn write(&self, writer: &mut impl BufMut) {
+        self.outcomes.write(writer);
+    }
+}
+
+impl Read for Receipt {
+    type Cfg = ();
+
+    fn read_cfg(reader: &mut impl Buf, _: &Self::Cfg) -> Result<Self, CodecError> {
+        let range: RangeCfg<usize> = (..=MAX_BLOCK_TRANSACTIONS).into();
+        let outcomes = Vec::<Outcome>::read_cfg(reader, &(range, ()))?;
+        Ok(Self { outcomes })
+    }
+}
+
+impl EncodeSize for Receipt {
+    fn encode_size(&self) -> usize {
+        self.outcomes.encode_size()
+    }
+}
+
+/// Key-value pairs after executing every finalized block up to some height.
+#[derive(Clone, Debug, Default)]
+pub struct State {
+    height: u64,
+    tip: Option<Digest>,
+    entries: BTreeMap<Bytes, Bytes>,
+}
+
+impl State {
+    /// Height of the last executed block (`0` before any block is executed).
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// Get the value of `key`.
+    pub fn get(&self, key: &[u8]) -> Option<&Bytes> {
+        self.entries.get(key)
+    }
+
+    /// Number of keys with a value.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no key has a value.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Execute the finalized `block` (which must be the child of the last executed block).
+    pub fn execute(&mut self, block: &Block) -> Result<Receipt, Error> {
+        let expected = self.height + 1;
+        if block.height.get() != expected {
+            return Err(Error::UnexpectedHeight {
+                expected,
+                got: block.height.get(),
+            });
+        }
+        if self.tip.is_some_and(|tip| tip != block.parent) {
+            return Err(Error::UnexpectedParent);
+        }
+
+        // Apply operations in the order the proposer included them
+        let outcomes = block
+            .transactions
+            .iter()
+            .map(|transaction| match Operation::decode(transaction) {
+                Some(Operation::Set { key, value }) => match self.entries.insert(key, value) {
+                    Some(_) => Outcome::Updated,
+                    None => Outcome::Inserted,
+                },
+                Some(Operation::Delete { key }) => match self.entries.remove(&key) {
+                    Some(_) => Outcome::Deleted,
+                    None => Outcome::Missing,
+                },
+                None => Outcome::Skipped,
+            })
+            .collect();
+        self.height = expected;
+        self.tip = Some(block.digest());
+        Ok(Receipt { outcomes })
+    }
+}
+
+/// The [State] after the last finalized block and after each unfinalized block executed on top
+/// of it.
+#[derive(Default)]
+struct States {
+    finalized: State,
+    pending: HashMap<Digest, State>,
+}
+
+/// Executes blocks on behalf of a validator, committing the [Receipt] of each block to its digest.
+///
+/// Install on every validator with `alto_chain::engine::ConfigBuilder::with_executor`. States are
+/// only held in memory: finalized blocks are not executed again after a restart, so a restarted
+/// validator must create its executor from a [State] that has executed every block it finalized
+/// (for example, with [sync]).
+#[derive(Default)]
+pub struct Executor {
+    states: Mutex<States>,
+}
+
+impl Executor {
+    /// Create a new [Executor] that executes blocks on top of `finalized` (the [State] after the
+    /// last finalized block).
+    pub fn new(finalized: State) -> Self {
+        Self {
+            states: Mutex::new(States {
+                finalized,
+                pending: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Get the [State] after the last finalized block.
+    pub fn finalized(&self) -> State {
+        self.states.lock().unwrap().finalized.clone()
+    }
+}
+
+impl alto_chain::application::Executor for Executor {
+    fn executed(&self, digest: &Digest) -> bool {
+        let states = self.states.lock().unwrap();
+        states.finalized.tip == Some(*digest) || states.pending.contains_key(digest)
+    }
+
+    fn execute(&self, block: &Block) -> Bytes {
+        let mut states = self.states.lock().unwrap();
+        let mut state = states
+            .pending
+            .get(&block.parent)
+            .unwrap_or(&states.finalized)
+            .clone();
+        let receipt = state
+            .execute(block)
+            .expect("parent of executed block was not executed");
+        states.pending.insert(block.digest(), state);
+        receipt.encode()
+    }
+
+    fn finalize(&self, block: &Block) {
+        let mut states = self.states.lock().unwrap();
+        if let Some(state) = states.pending.remove(&block.digest()) {
+            states.finalized = state;
+        }
+        let height = block.height.get();
+        states.pending.retain(|_, state| state.height() > height);
+    }
+}
+
+/// Execute every block finalized after [State::height] (fetched from an indexer with `client`).
+///
+/// Returns the number of blocks executed. Blocks are verified by `client` before they are
+/// executed.
+pub async fn sync<S: Strategy>(client: &Client<S>, state: &mut State) -> Result<u64, Error> {
+    let latest = client.block_get(Query::Latest).await?;
+    let latest = latest.block().height.get();
+    let mut executed = 0;
+    for height in state.height() + 1..=latest {
+        let payload = client.block_get(Query::Index(height)).await?;
+        state.execute(payload.block())?;
+        executed += 1;
+    }
+    Ok(executed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_chain::{
+        application::Executor as _,
+        mempool::{Config, Pool, Rejected},
+    };
+    use alto_types::DigestMode;
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Hasher, Sha256};
+    use std::{collections::HashSet, sync::Arc};
+
+    fn set(key: &str, value: &str) -> Operation {
+        Operation::Set {
+            key: Bytes::copy_from_slice(key.as_bytes()),
+            value: Bytes::copy_from_slice(value.as_bytes()),
+        }
+    }
+
+    /// Build a block on `parent` with `transactions`.
+    fn block(parent: Digest, height: u64, transactions: Vec<Transaction>) -> Block {
+        Block::new_with_transactions(
+            DigestMode::Sha256,
+            0,
+            parent,
+            Height::new(height),
+            height,
+            transactions,
+        )
+    }
+
+    /// Rebuild `block` with `receipts` (as a validator with an [Executor] would propose it).
+    fn with_receipts(block: &Block, receipts: Bytes) -> Block {
+        Block::new_with_receipts(
+            DigestMode::Sha256,
+            block.rules,
+            block.parent,
+            block.height,
+            block.timestamp,
+            block.transactions.clone(),
+            receipts,
+        )
+    }
+
+    /// Build the block a validator would propose on `parent` (taking transactions from `pool`).
+    fn propose(pool: &Pool, parent: Digest, height: u64) -> Block {
+        block(
+            parent,
+            height,
+            pool.take(height, MAX_BLOCK_TRANSACTIONS, usize::MAX, &HashSet::new()),
+        )
+    }
+
+    #[test]
+    fn test_operation_codec() {
+        let operations = [
+            set("a", "1"),
+            Operation::Delete {
+                key: Bytes::from_static(b"a"),
+            },
+        ];
+        for operation in operations {
+            let transaction = operation.transaction();
+            assert_eq!(Operation::decode(&transaction), Some(operation));
+        }
+
+        // Oversized keys and unknown tags are not operations
+        let key = Bytes::from(vec![0; MAX_KEY_SIZE + 1]);
+        let oversized = Operation::Delete { key }.transaction();
+        assert_eq!(Operation::decode(&oversized), None);
+        let unknown = Transaction::new(vec![2]).unwrap();
+        assert_eq!(Operation::decode(&unknown), None);
+    }
+
+    #[test]
+    fn test_validator() {
+        // Validators running the key-value store only accept operations
+        let pool = Pool::new_with_validator(Config::default(), Arc::new(valid));
+        pool.insert(set("a", "1").transaction()).unwrap();
+        let junk = Transaction::new(b"junk".to_vec()).unwrap();
+        assert!(!valid(&junk));
+        assert_eq!(pool.insert(junk), Err(Rejected::Invalid));
+
+        // Blocks only include operations (so none are skipped)
+        let block = propose(&pool, Sha256::hash(b"genesis"), 1);
+        assert!(block.transactions.iter().all(|t| pool.valid(t)));
+        let receipt = State::default().execute(&block).unwrap();
+        assert_eq!(receipt.outcomes, vec![Outcome::Inserted]);
+    }
+
+    #[test]
+    fn test_execute() {
+        // Submit operations (and a transaction that is not an operation)
+        let pool = Pool::default();
+        pool.insert(set("a", "1").transaction()).unwrap();
+        pool.insert(set("b", "2").transaction()).unwrap();
+        pool.insert(Transaction::new(b"junk".to_vec()).unwrap())
+            .unwrap();
+
+        // Execute the proposed block
+        let mut state = State::default();
+        let first = propose(&pool, Sha256::hash(b"genesis"), 1);
+        let receipt = state.execute(&first).unwrap();
+        assert_eq!(receipt.applied(), 2);
+        assert_eq!(receipt.skipped(), 1);
+        assert_eq!(state.height(), 1);
+        assert_eq!(state.get(b"a").unwrap().as_ref(), b"1");
+        assert_eq!(state.len(), 2);
+
+        // Later operations override earlier ones
+        pool.insert(set("a", "3").transaction()).unwrap();
+        pool.insert(
+            Operation::Delete {
+                key: Bytes::from_static(b"b"),
+            }
+            .transaction(),
+        )
+        .unwrap();
+        let second = propose(&pool, first.digest(), 2);
+
+        // Blocks must be executed in order
+        let mut skipped = State::default();
+        assert!(matches!(
+            skipped.execute(&second),
+            Err(Error::UnexpectedHeight {
+                expected: 1,
+                got: 2
+            })
+        ));
+        let fork = propose(&pool, Sha256::hash(b"fork"), 2);
+        assert!(matches!(state.execute(&fork), Err(Error::UnexpectedParent)));
+
+        let receipt = state.execute(&second).unwrap();
+        assert_eq!(receipt.outcomes, vec![Outcome::Updated, Outcome::Deleted]);
+        assert_eq!(state.get(b"a").unwrap().as_ref(), b"3");
+        assert!(state.get(b"b").is_none());
+        assert_eq!(state.len(), 1);
+    }
+
+    #[test]
+    fn test_executor() {
+        let executor = Executor::default();
+        let delete = Operation::Delete {
+            key: Bytes::from_static(b"a"),
+        };
+
+        // Receipts describe the outcome of each transaction
+        let first = block(
+            Sha256::hash(b"genesis"),
+            1,
+            vec![
+                set("a", "1").transaction(),
+                Transaction::new(b"junk".to_vec()).unwrap(),
+            ],
+        );
+        assert!(!executor.executed(&first.digest()));
+        let receipts = executor.execute(&first);
+        assert!(executor.executed(&first.digest()));
+        let executed = with_receipts(&first, receipts);
+        let receipt = Receipt::decode(&executed).unwrap();
+        assert_eq!(receipt.outcomes, vec![Outcome::Inserted, Outcome::Skipped]);
+        assert_eq!(receipt, State::default().execute(&first).unwrap());
+
+        // The same transactions produce different receipts on different forks
+        let updated = block(first.digest(), 2, vec![set("a", "2").transaction()]);
+        let deleted = block(first.digest(), 2, vec![delete.transaction()]);
+        let on_updated = block(updated.digest(), 3, vec![set("a", "3").transaction()]);
+        let on_deleted = block(deleted.digest(), 3, vec![set("a", "3").transaction()]);
+        for (parent, child, outcome) in [
+            (&updated, &on_updated, Outcome::Updated),
+            (&deleted, &on_deleted, Outcome::Inserted),
+        ] {
+            executor.execute(parent);
+            let receipts = executor.execute(child);
+            let receipt = Receipt::decode(&with_receipts(child, receipts)).unwrap();
+            assert_eq!(receipt.outcomes, vec![outcome]);
+        }
+
+        // Finalizing a block commits its state and drops competing forks
+        executor.finalize(&first);
+        executor.finalize(&updated);
+        let finalized = executor.finalized();
+        assert_eq!(finalized.height(), 2);
+        assert_eq!(finalized.get(b"a").unwrap().as_ref(), b"2");
+        assert!(executor.executed(&updated.digest()));
+        assert!(!executor.executed(&deleted.digest()));
+        assert!(executor.executed(&on_updated.digest()));
+
+        // Restarted executors continue from the finalized state
+        let restarted = Executor::new(finalized);
+        assert!(restarted.executed(&updated.digest()));
+        let receipts = restarted.execute(&on_updated);
+        assert_eq!(
+            Receipt::decode(&with_receipts(&on_updated, receipts)),
+            Some(Receipt {
+                outcomes: vec![Outcome::Updated]
+            })
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Build key-value transactions and query the key-value store of an `alto` network.
+
+use alto_client::ClientBuilder;
+use alto_example_kv::{sync, Operation, State};
+use alto_types::{DigestMode, Identity};
+use bytes::Bytes;
+use clap::{value_parser, Arg, Command};
+use commonware_codec::DecodeExt;
+use commonware_cryptography::Digestible;
+use commonware_parallel::Sequential;
+use commonware_utils::{from_hex_formatted, hex};
+use tracing::info;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    let key = || {
+        Arg::new("key")
+            .required(true)
+            .value_parser(value_parser!(String))
+            .help("Key (UTF-8)")
+    };
+    let matches = Command::new("alto-kv")
+        .about("Build key-value transactions and query the key-value store of an alto network.")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("set")
+                .about("Print the transaction that sets a key")
+                .arg(key())
+                .arg(
+                    Arg::new("value")
+                        .required(true)
+                        .value_parser(value_parser!(String))
+                        .help("Value (UTF-8)"),
+                ),
+        )
+        .subcommand(
+            Command::new("delete")
+                .about("Print the transaction that deletes a key")
+                .arg(key()),
+        )
+        .subcommand(
+            Command::new("get")
+                .about("Execute every finalized block and print the value of a key")
+                .arg(key())
+                .arg(
+                    Arg::new("indexer")
+                        .long("indexer")
+                        .required(true)
+                        .value_parser(value_parser!(String))
+                        .help("URL of the indexer"),
+                )
+                .arg(
+                    Arg::new("identity")
+                        .long("identity")
+                        .required(true)
+                        .value_parser(value_parser!(String))
+                        .help("Hex-encoded public key of the identity"),
+                )
+                .arg(
+                    Arg::new("digest-mode")
+                        .long("digest-mode")
+                        .value_parser(value_parser!(DigestMode))
+                        .default_value("sha256")
+                        .help("Hash function used to compute block digests (sha256 or blake3)"),
+                ),
+        )
+        .get_matches();
+
+    let (command, matches) = matches.subcommand().unwrap();
+    let key = Bytes::from(matches.get_one::<String>("key").unwrap().clone());
+    let operation = match command {
+        "set" => {
+            let value = matches.get_one::<String>("value").unwrap().clone();
+            Operation::Set {
+                key,
+                value: Bytes::from(value),
+            }
+        }
+        "delete" => Operation::Delete { key },
+        "get" => {
+            let indexer = matches.get_one::<String>("indexer").unwrap();
+            let identity = matches.get_one::<String>("identity").unwrap();
+            let identity = from_hex_formatted(identity).expect("Failed to decode identity");
+            let identity = Identity::decode(identity.as_ref()).expect("Invalid identity");
+            let digest_mode = *matches.get_one::<DigestMode>("digest-mode").unwrap();
+            let client = ClientBuilder::new(indexer, identity, Sequential)
+                .with_digest_mode(digest_mode)
+                .build();
+            let mut state = State::default();
+            let executed = sync(&client, &mut state)
+                .await
+                .expect("Failed to execute finalized blocks");
+            info!(
+                executed,
+                height = state.height(),
+                keys = state.len(),
+                "synced"
+            );
+            match state.get(&key) {
+                Some(value) => println!("{}", String::from_utf8_lossy(value)),
+                None => println!("<none>"),
+            }
+            return;
+        }
+        _ => unreachable!(),
+    };
+
+    // Print the transaction (to submit to an indexer or over the transactions channel)
+    let transaction = operation.transaction();
+    info!(digest = ?transaction.digest(), "built transaction");
+    println!("{}", hex(transaction.data()));
+}
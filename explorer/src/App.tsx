@@ -4,8 +4,8 @@ import { DivIcon, LatLng } from "leaflet";
 import "leaflet/dist/leaflet.css";
 import init, { parse_seed, parse_notarized, parse_finalized, leader_index } from "./alto_types/alto_types.js";
 import { getClusterConfig, getClusters, Cluster, DEFAULT_CLUSTER, MODE } from "./config";
-import { SeedJs, NotarizedJs, FinalizedJs, ViewData } from "./types";
-import { hexToUint8Array, hexUint8Array } from "./utils";
+import { SeedJs, NotarizedJs, FinalizedJs, ViewData, ValidatorInfo } from "./types";
+import { hexToUint8Array, shortHex } from "./utils";
 import "./App.css";
 import AboutModal from './AboutModal';
 import './AboutModal.css';
@@ -92,6 +92,7 @@ const App: React.FC = () => {
   const { BACKEND_URL, PUBLIC_KEY_HEX, LOCATIONS } = clusterConfig;
   const PUBLIC_KEY = useMemo(() => hexToUint8Array(PUBLIC_KEY_HEX), [PUBLIC_KEY_HEX]);
 
+  const [locations, setLocations] = useState<[[number, number], string][]>(LOCATIONS);
   const [views, setViews] = useState<ViewData[]>([]);
   const [lastObservedView, setLastObservedView] = useState<number | null>(null);
   const [isAboutModalOpen, setIsAboutModalOpen] = useState<boolean>(false);
@@ -166,6 +167,28 @@ const App: React.FC = () => {
     setShowError(false);
   }, [selectedCluster]);
 
+  // Load validator locations from the indexer (falling back to the cluster config if the
+  // indexer doesn't serve them or any validator is missing coordinates)
+  useEffect(() => {
+    setLocations(LOCATIONS);
+    if (MODE !== 'public') {
+      return;
+    }
+    let cancelled = false;
+    fetch(`https://${BACKEND_URL}/validators`)
+      .then(response => response.ok ? response.json() : Promise.reject(response.status))
+      .then((validators: ValidatorInfo[]) => {
+        if (cancelled || validators.length === 0 || validators.some(v => !v.coordinates)) {
+          return;
+        }
+        setLocations(validators.map(v => [v.coordinates!, v.location ?? v.name ?? v.region ?? ""]));
+      })
+      .catch(error => console.log("Using configured validator locations:", error));
+    return () => {
+      cancelled = true;
+    };
+  }, [BACKEND_URL, LOCATIONS]);
+
   // Health check function
   const checkHealth = useCallback(async () => {
     try {
@@ -295,9 +318,9 @@ const App: React.FC = () => {
         const existingStatus = newViews[existingIndex].status;
         if (existingStatus === "finalized" || existingStatus === "notarized") {
           // Only update location if in public mode
-          const locationIndex = MODE === 'public' ? leader_index(seed, LOCATIONS.length) : -1;
-          const location = locationIndex >= 0 ? LOCATIONS[locationIndex][0] : undefined;
-          const locationName = locationIndex >= 0 ? LOCATIONS[locationIndex][1] : undefined;
+          const locationIndex = MODE === 'public' ? leader_index(seed, locations.length) : -1;
+          const location = locationIndex >= 0 ? locations[locationIndex][0] : undefined;
+          const locationName = locationIndex >= 0 ? locations[locationIndex][1] : undefined;
 
           // Only update location and signature info, preserve all timing and status
           newViews[existingIndex] = {
@@ -322,9 +345,9 @@ const App: React.FC = () => {
       }
 
       // Create the new view data
-      const locationIndex = MODE === 'public' ? leader_index(seed, LOCATIONS.length) : -1;
-      const location = locationIndex >= 0 ? LOCATIONS[locationIndex][0] : undefined;
-      const locationName = locationIndex >= 0 ? LOCATIONS[locationIndex][1] : undefined;
+      const locationIndex = MODE === 'public' ? leader_index(seed, locations.length) : -1;
+      const location = locationIndex >= 0 ? locations[locationIndex][0] : undefined;
+      const locationName = locationIndex >= 0 ? locations[locationIndex][1] : undefined;
       const newView: ViewData = {
         view,
         location,
@@ -384,7 +407,7 @@ const App: React.FC = () => {
 
       return newViews;
     });
-  }, [lastObservedView, adjustTime, LOCATIONS]);
+  }, [lastObservedView, adjustTime, locations]);
 
   const handleNotarization = useCallback((notarized: NotarizedJs) => {
     const view = notarized.proof.view;
@@ -836,7 +859,7 @@ const App: React.FC = () => {
                   </Popup>
                 </Marker>
               )}
-              <MapOverlay numValidators={LOCATIONS.length} />
+              <MapOverlay numValidators={locations.length} />
             </MapContainer>
           </div>
         )}
@@ -1058,7 +1081,7 @@ const Bar: React.FC<BarProps> = ({ viewData, currentTime, isMobile }) => {
   } else if (status === "unknown") {
     inBarText = "PENDING";
   } else if (block) {
-    inBarText = `#${block.height} | ${hexUint8Array(block.digest)}`;
+    inBarText = `#${block.height} | ${shortHex(block.digest)}`;
   }
 
   // Calculate positions for timing labels to prevent overlap
@@ -1109,7 +1132,7 @@ const Bar: React.FC<BarProps> = ({ viewData, currentTime, isMobile }) => {
       <div className="view-info" style={{ width: `${viewInfoWidth}px` }}>
         <div className="view-number">{view}</div>
         <div className="view-signature">
-          {signature ? hexUint8Array(signature) : ""}
+          {signature ? shortHex(signature) : ""}
         </div>
       </div>
 
@@ -2,7 +2,7 @@ import React, { useState, useEffect } from 'react';
 import './SearchModal.css';
 import { ClusterConfig, MODE } from './config';
 import { FinalizedJs, NotarizedJs, BlockJs, SearchType, SearchResult } from './types';
-import { hexToUint8Array, hexUint8Array, formatAge } from './utils';
+import { hexToUint8Array, shortHex, formatAge } from './utils';
 import init, { parse_seed, parse_notarized, parse_finalized, parse_block } from "./alto_types/alto_types.js";
 
 interface SearchModalProps {
@@ -222,7 +222,7 @@ const SearchModal: React.FC<SearchModalProps> = ({ isOpen, onClose, clusterConfi
             resultType = 'Seed';
             formattedResult = {
                 view: result.view,
-                signature: hexUint8Array(result.signature as Uint8Array, 64)
+                signature: shortHex(result.signature as string, 64)
             };
         } else if ('proof' in result && 'block' in result) {
             resultType = (lastSearchType === 'finalization' || lastSearchType === 'block') ? 'Finalization' : 'Notarization';
@@ -233,14 +233,14 @@ const SearchModal: React.FC<SearchModalProps> = ({ isOpen, onClose, clusterConfi
 
             formattedResult = {
                 height: block.height,
-                parent: hexUint8Array(block.parent, 64),
+                parent: shortHex(block.parent, 64),
                 timestamp: `${new Date(Number(block.timestamp)).toLocaleString()} (${formatAge(age)})`,
                 view: dataObj.proof.view,
-                digest: hexUint8Array(block.digest as Uint8Array, 64),
+                digest: shortHex(block.digest, 64),
             };
 
             if (dataObj.proof.signature) {
-                formattedResult.signature = hexUint8Array(dataObj.proof.signature, 64);
+                formattedResult.signature = shortHex(dataObj.proof.signature, 64);
             }
         } else if ('height' in result && 'timestamp' in result && 'digest' in result) {
             resultType = 'Block';
@@ -250,18 +250,13 @@ const SearchModal: React.FC<SearchModalProps> = ({ isOpen, onClose, clusterConfi
 
             formattedResult = {
                 height: block.height,
-                parent: hexUint8Array(block.parent, 64),
+                parent: shortHex(block.parent, 64),
                 timestamp: `${new Date(Number(block.timestamp)).toLocaleString()} (${formatAge(age)})`,
             };
         } else {
             resultType = 'Unknown';
             formattedResult = {
-                raw: JSON.stringify(result, (key, value) => {
-                    if (value && value.constructor === Uint8Array) {
-                        return hexUint8Array(value as Uint8Array, 64);
-                    }
-                    return value;
-                }, 2)
+                raw: JSON.stringify(result, null, 2)
             };
         }
 
@@ -10,7 +10,7 @@ export interface ViewData {
     startTime: number;
     notarizationTime?: number;
     finalizationTime?: number;
-    signature?: Uint8Array;
+    signature?: string;
     block?: any; // BlockJs
     timeoutId?: any; // NodeJS.Timeout
     actualNotarizationLatency?: number;
@@ -4,25 +4,41 @@ export type ViewStatus = "growing" | "notarized" | "finalized" | "timed_out" | "
 // Search types
 export type SearchType = 'block' | 'notarization' | 'finalization' | 'seed';
 
-// Block data
+// Block data (byte strings are hex, see the canonical JSON schema in alto_types::json)
 export interface BlockJs {
+    rules: number;
+    parent: string;
     height: number;
     timestamp: number;
-    digest: Uint8Array;
-    parent: Uint8Array;
+    transactions: string[];
+    digest: string;
+}
+
+// Validator metadata (served by the indexer at /validators, sorted by public key)
+export interface ValidatorInfo {
+    public_key: string;
+    name?: string;
+    region?: string;
+    location?: string;
+    coordinates?: [number, number];
 }
 
 // Seed (for leader election)
 export interface SeedJs {
+    epoch: number;
     view: number;
-    signature: Uint8Array;
+    signature: string;
+    randomness: string;
 }
 
 // Proof for notarizations and finalizations
 export interface ProofJs {
+    epoch: number;
     view: number;
-    threshold?: number;
-    signature?: Uint8Array;
+    parent: number;
+    payload: string;
+    signature: string;
+    seed_signature: string;
 }
 
 // Notarized block
@@ -34,7 +50,6 @@ export interface NotarizedJs {
 // Finalized block
 export interface FinalizedJs {
     proof: ProofJs;
-    quorum: boolean;
     block: BlockJs;
 }
 
@@ -47,7 +62,7 @@ export interface ViewData {
     startTime: number;
     notarizationTime?: number;
     finalizationTime?: number;
-    signature?: Uint8Array;
+    signature?: string;
     block?: BlockJs;
     timeoutId?: NodeJS.Timeout;
     actualNotarizationLatency?: number;
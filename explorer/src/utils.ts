@@ -28,19 +28,16 @@ export function hexToUint8Array(hex: string): Uint8Array {
 }
 
 /**
- * Converts a Uint8Array to a hex string (keeping up to len).
- * @param arr - The Uint8Array to convert
+ * Shortens a hex string (keeping up to len).
+ * @param hex - The hex string to shorten
  * @param len - Max number of characters to keep (default: 8)
- * @returns A representation of the Uint8Array as a hex string.
+ * @returns The last len characters of the hex string.
  */
-export function hexUint8Array(arr: Uint8Array | undefined, len: number = 8): string {
-    if (!arr || arr.length === 0) return "";
-
-    // Convert the entire array to hex
-    const fullHex = Array.from(arr, (b) => b.toString(16).padStart(2, "0")).join("");
+export function shortHex(hex: string | undefined, len: number = 8): string {
+    if (!hex) return "";
 
     // Get last characters of the hex string
-    return fullHex.slice(-len);
+    return hex.slice(-len);
 }
 
 /**
@@ -0,0 +1,42 @@
+//! Throughput of upload verification with different [Strategy]s.
+
+use alto_indexer::Indexer;
+use alto_types::{fixtures, Block};
+use commonware_consensus::types::Height;
+use commonware_cryptography::{Hasher, Sha256};
+use commonware_parallel::{Rayon, Sequential, Strategy};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::{hint::black_box, num::NonZeroUsize};
+
+fn bench<S: Strategy>(c: &mut Criterion, name: &str, strategy: S) {
+    let fixtures::Fixture { schemes, .. } = fixtures::seeded(0, 4);
+    let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+    let finalized = fixtures::finalized(&schemes, 1, block);
+    let verifier = alto_types::Scheme::certificate_verifier(
+        alto_types::NAMESPACE,
+        fixtures::identity(&schemes),
+    );
+    let indexer = Indexer::new(verifier, strategy);
+    c.bench_with_input(
+        BenchmarkId::new("submit_finalization", name),
+        &finalized,
+        |b, finalized| {
+            b.iter(|| {
+                indexer
+                    .submit_finalization(black_box(finalized.clone()))
+                    .unwrap()
+            })
+        },
+    );
+}
+
+fn benchmark_verification(c: &mut Criterion) {
+    bench(c, "sequential", Sequential);
+    for threads in [2, 4] {
+        let strategy = Rayon::new(NonZeroUsize::new(threads).unwrap()).unwrap();
+        bench(c, &format!("rayon_{threads}"), strategy);
+    }
+}
+
+criterion_group!(benches, benchmark_verification);
+criterion_main!(benches);
@@ -0,0 +1,300 @@
+//! gRPC interface for the indexer API.
+//!
+//! Serves the artifacts of the REST API (generated from `proto/indexer.proto`) so backends in
+//! other languages can use generated clients instead of decoding [commonware_codec] by hand.
+//! Every message carries both decoded fields and the `encoded` artifact (to verify against the
+//! network identity before trusting it). Uploads are only accepted over HTTP.
+
+use crate::Indexer;
+use alto_client::Query;
+use alto_types::{
+    Block, DigestMode, Finalized, Kind, Notarized, Nullification, Payload, Seed, SeedExt,
+    Subscription, LAGGED_FRAME,
+};
+use commonware_codec::{Decode, DecodeExt, Encode};
+use commonware_consensus::{
+    simplex::{scheme::bls12381_threshold, types::Proposal},
+    Viewable,
+};
+use commonware_cryptography::{bls12381::primitives::variant::MinSig, sha256::Digest, Digestible};
+use commonware_parallel::Strategy;
+use futures::{stream, Stream, StreamExt};
+use std::{pin::Pin, sync::Arc};
+use tonic::{Request, Response, Status};
+
+/// Types and service definitions generated from `proto/indexer.proto`.
+pub mod proto {
+    tonic::include_proto!("alto.indexer.v1");
+}
+
+use proto::{indexer_server::IndexerServer, query::Target, ConsensusMessage};
+
+/// Certificate of a notarization or finalization.
+type Certificate = bls12381_threshold::Signature<MinSig>;
+
+/// Serves the indexer over gRPC.
+pub struct Service<S: Strategy> {
+    indexer: Arc<Indexer<S>>,
+}
+
+impl<S: Strategy> Service<S> {
+    pub fn new(indexer: Arc<Indexer<S>>) -> Self {
+        Self { indexer }
+    }
+
+    /// Wrap the service in a server (to add to a [tonic::transport::Server]).
+    pub fn server(self) -> IndexerServer<Self> {
+        IndexerServer::new(self)
+    }
+}
+
+/// Convert a gRPC query into the query served by the REST API.
+fn query(query: proto::Query) -> Result<Query, Status> {
+    match query.target {
+        None => Ok(Query::Latest),
+        Some(Target::Index(index)) => Ok(Query::Index(index)),
+        Some(Target::Digest(digest)) => Digest::decode(digest.as_slice())
+            .map(Query::Digest)
+            .map_err(|_| Status::invalid_argument("invalid digest")),
+    }
+}
+
+/// Convert a gRPC query for an artifact of a view into the query served by the REST API.
+fn view_query(request: Request<proto::Query>) -> Result<String, Status> {
+    match query(request.into_inner())? {
+        Query::Digest(_) => Err(Status::invalid_argument("views cannot be found by digest")),
+        query => Ok(query.serialize()),
+    }
+}
+
+/// Respond with `value` (or `NOT_FOUND` if `None`).
+fn found<T>(value: Option<T>) -> Result<Response<T>, Status> {
+    value
+        .map(Response::new)
+        .ok_or_else(|| Status::not_found("not found"))
+}
+
+impl From<&Block> for proto::Block {
+    fn from(block: &Block) -> Self {
+        Self {
+            rules: block.rules.into(),
+            parent: block.parent.to_vec(),
+            height: block.height.get(),
+            timestamp: block.timestamp,
+            transactions: block
+                .transactions
+                .iter()
+                .map(|transaction| transaction.data().to_vec())
+                .collect(),
+            receipts: block.receipts.to_vec(),
+            digest: block.digest().to_vec(),
+        }
+    }
+}
+
+/// Convert the proposal and certificate of a notarization or finalization.
+fn proof(proposal: &Proposal<Digest>, certificate: &Certificate) -> proto::Proof {
+    proto::Proof {
+        epoch: proposal.round.epoch().get(),
+        view: proposal.round.view().get(),
+        parent: proposal.parent.get(),
+        payload: proposal.payload.to_vec(),
+        signature: certificate.vote_signature.encode().to_vec(),
+        seed_signature: certificate.seed_signature.encode().to_vec(),
+    }
+}
+
+impl From<&Seed> for proto::Seed {
+    fn from(seed: &Seed) -> Self {
+        Self {
+            epoch: seed.round.epoch().get(),
+            view: seed.view().get(),
+            signature: seed.signature_bytes(),
+            randomness: seed.randomness().to_vec(),
+            encoded: seed.encode().to_vec(),
+        }
+    }
+}
+
+impl From<&Notarized> for proto::Notarized {
+    fn from(notarized: &Notarized) -> Self {
+        let notarization = &notarized.proof;
+        Self {
+            proof: Some(proof(&notarization.proposal, &notarization.certificate)),
+            block: Some((&notarized.block).into()),
+            encoded: notarized.encode().to_vec(),
+        }
+    }
+}
+
+impl From<&Finalized> for proto::Finalized {
+    fn from(finalized: &Finalized) -> Self {
+        let finalization = &finalized.proof;
+        Self {
+            proof: Some(proof(&finalization.proposal, &finalization.certificate)),
+            block: Some((&finalized.block).into()),
+            encoded: finalized.encode().to_vec(),
+        }
+    }
+}
+
+impl From<&Nullification> for proto::Nullification {
+    fn from(nullification: &Nullification) -> Self {
+        Self {
+            epoch: nullification.round.epoch().get(),
+            view: nullification.view().get(),
+            encoded: nullification.encode().to_vec(),
+        }
+    }
+}
+
+impl From<&Payload> for proto::Payload {
+    fn from(payload: &Payload) -> Self {
+        match payload {
+            Payload::Finalized(finalized) => {
+                let finalization = &finalized.proof;
+                Self {
+                    block: Some((&finalized.block).into()),
+                    proof: Some(proof(&finalization.proposal, &finalization.certificate)),
+                    encoded: finalized.encode().to_vec(),
+                }
+            }
+            Payload::Block(block) => Self {
+                block: Some(block.as_ref().into()),
+                proof: None,
+                encoded: block.encode().to_vec(),
+            },
+        }
+    }
+}
+
+impl TryFrom<proto::Subscription> for Subscription {
+    type Error = Status;
+
+    fn try_from(subscription: proto::Subscription) -> Result<Self, Self::Error> {
+        let kinds = match subscription.kinds.is_empty() {
+            true => None,
+            false => Some(
+                subscription
+                    .kinds
+                    .iter()
+                    .map(|kind| {
+                        u8::try_from(*kind)
+                            .ok()
+                            .and_then(Kind::from_u8)
+                            .ok_or_else(|| Status::invalid_argument("unknown kind"))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        Ok(Self {
+            kinds,
+            start_view: subscription.start_view,
+            end_view: subscription.end_view,
+            from_view: subscription.from_view,
+        })
+    }
+}
+
+/// Convert a frame broadcast to consensus subscribers into a gRPC message.
+fn message(digest_mode: &DigestMode, frame: &[u8]) -> Option<ConsensusMessage> {
+    use proto::consensus_message::Message;
+    let (tag, data) = frame.split_first()?;
+    let message = if *tag == LAGGED_FRAME {
+        Message::Lagged(u64::decode(data).ok()?)
+    } else {
+        match Kind::from_u8(*tag)? {
+            Kind::Seed => Message::Seed((&Seed::decode(data).ok()?).into()),
+            Kind::Notarization => {
+                Message::Notarization((&Notarized::decode_cfg(data, digest_mode).ok()?).into())
+            }
+            Kind::Finalization => {
+                Message::Finalization((&Finalized::decode_cfg(data, digest_mode).ok()?).into())
+            }
+            Kind::Nullification => {
+                Message::Nullification((&Nullification::decode(data).ok()?).into())
+            }
+        }
+    };
+    Some(ConsensusMessage {
+        message: Some(message),
+    })
+}
+
+type ConsensusStream = Pin<Box<dyn Stream<Item = Result<ConsensusMessage, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl<S: Strategy> proto::indexer_server::Indexer for Service<S> {
+    async fn get_seed(
+        &self,
+        request: Request<proto::Query>,
+    ) -> Result<Response<proto::Seed>, Status> {
+        let query = view_query(request)?;
+        found(self.indexer.get_seed(&query).map(|seed| (&seed).into()))
+    }
+
+    async fn get_notarization(
+        &self,
+        request: Request<proto::Query>,
+    ) -> Result<Response<proto::Notarized>, Status> {
+        let query = view_query(request)?;
+        let notarized = self.indexer.get_notarization(&query);
+        found(notarized.map(|notarized| (&notarized).into()))
+    }
+
+    async fn get_finalization(
+        &self,
+        request: Request<proto::Query>,
+    ) -> Result<Response<proto::Finalized>, Status> {
+        let query = view_query(request)?;
+        let finalized = self.indexer.get_finalization(&query);
+        found(finalized.map(|finalized| (&finalized).into()))
+    }
+
+    async fn get_block(
+        &self,
+        request: Request<proto::Query>,
+    ) -> Result<Response<proto::Payload>, Status> {
+        let query = query(request.into_inner())?.serialize();
+        found(
+            self.indexer
+                .get_block(&query)
+                .map(|payload| (&payload).into()),
+        )
+    }
+
+    type StreamConsensusStream = ConsensusStream;
+
+    /// Stream consensus messages like the consensus websocket.
+    ///
+    /// Subscribers disconnected under [crate::OverflowPolicy::Disconnect] receive a
+    /// `RESOURCE_EXHAUSTED` status.
+    async fn stream_consensus(
+        &self,
+        request: Request<proto::Subscription>,
+    ) -> Result<Response<Self::StreamConsensusStream>, Status> {
+        let subscription: Subscription = request.into_inner().try_into()?;
+        let (consensus, replay) = self.indexer.consensus_replay(&subscription);
+        let digest_mode = self.indexer.digest_mode;
+        let live = stream::unfold(
+            Some((self.indexer.clone(), consensus, subscription)),
+            |state| async move {
+                let (indexer, mut consensus, subscription) = state?;
+                match indexer.next_frame(&mut consensus, &subscription).await {
+                    Some(frame) => Some((Ok(frame), Some((indexer, consensus, subscription)))),
+                    None => Some((Err(Status::resource_exhausted("lagged")), None)),
+                }
+            },
+        );
+        let messages = stream::iter(replay.into_iter().map(Ok))
+            .chain(live)
+            .filter_map(move |frame| {
+                let message = match frame {
+                    Ok(frame) => message(&digest_mode, &frame).map(Ok),
+                    Err(status) => Some(Err(status)),
+                };
+                futures::future::ready(message)
+            });
+        Ok(Response::new(Box::pin(messages)))
+    }
+}
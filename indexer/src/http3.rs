@@ -0,0 +1,142 @@
+//! HTTP/3 (QUIC) transport for the indexer API.
+//!
+//! Serves the same [Router] as the TCP listener over QUIC, so clients fetching many small
+//! artifacts are not stalled by head-of-line blocking. WebSocket routes (`/consensus/ws`) are
+//! only available over TCP.
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, Response},
+    Router,
+};
+use bytes::{Buf, Bytes, BytesMut};
+use h3::server::RequestStream;
+use std::{net::SocketAddr, sync::Arc};
+use thiserror::Error;
+use tower::ServiceExt;
+use tracing::{debug, warn};
+
+/// ALPN protocol identifier for HTTP/3.
+pub const ALPN: &[u8] = b"h3";
+
+/// Maximum size (in bytes) of a request or response body served over HTTP/3.
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// Errors that can occur when serving HTTP/3.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("invalid TLS config: {0}")]
+    Tls(#[from] quinn::crypto::rustls::NoInitialCipherSuite),
+    #[error("failed to bind endpoint: {0}")]
+    Bind(#[from] std::io::Error),
+}
+
+impl From<Error> for alto_types::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Bind(e) => Self::Io(e),
+            Error::Tls(_) => Self::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, error)),
+        }
+    }
+}
+
+/// Serve `router` over HTTP/3 on `addr` until the endpoint is closed.
+///
+/// The provided TLS config must contain a certificate (QUIC always uses TLS 1.3). The
+/// `h3` ALPN protocol is added if missing.
+pub async fn serve(
+    addr: SocketAddr,
+    mut tls: rustls::ServerConfig,
+    router: Router,
+) -> Result<(), Error> {
+    // Create endpoint
+    if !tls.alpn_protocols.iter().any(|alpn| alpn == ALPN) {
+        tls.alpn_protocols.push(ALPN.to_vec());
+    }
+    let crypto = quinn::crypto::rustls::QuicServerConfig::try_from(tls)?;
+    let config = quinn::ServerConfig::with_crypto(Arc::new(crypto));
+    let endpoint = quinn::Endpoint::server(config, addr)?;
+
+    // Accept connections
+    while let Some(incoming) = endpoint.accept().await {
+        let router = router.clone();
+        tokio::spawn(async move {
+            let connection = match incoming.await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    debug!(?e, "failed to accept QUIC connection");
+                    return;
+                }
+            };
+            let mut connection =
+                match h3::server::Connection::new(h3_quinn::Connection::new(connection)).await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        debug!(?e, "failed to establish HTTP/3 connection");
+                        return;
+                    }
+                };
+
+            // Handle requests
+            loop {
+                match connection.accept().await {
+                    Ok(Some(resolver)) => {
+                        let router = router.clone();
+                        tokio::spawn(async move {
+                            let (request, stream) = match resolver.resolve_request().await {
+                                Ok(resolved) => resolved,
+                                Err(e) => {
+                                    debug!(?e, "failed to resolve HTTP/3 request");
+                                    return;
+                                }
+                            };
+                            if let Err(e) = handle(router, request, stream).await {
+                                debug!(?e, "failed to handle HTTP/3 request");
+                            }
+                        });
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        debug!(?e, "HTTP/3 connection closed");
+                        break;
+                    }
+                }
+            }
+        });
+    }
+    warn!("HTTP/3 endpoint closed");
+    Ok(())
+}
+
+/// Dispatch a single request to `router` and write the response to `stream`.
+async fn handle<S: h3::quic::BidiStream<Bytes>>(
+    router: Router,
+    request: Request<()>,
+    mut stream: RequestStream<S, Bytes>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Read body
+    let mut body = BytesMut::new();
+    while let Some(mut chunk) = stream.recv_data().await? {
+        if body.len() + chunk.remaining() > MAX_BODY_SIZE {
+            return Err("request body too large".into());
+        }
+        body.extend_from_slice(&chunk.copy_to_bytes(chunk.remaining()));
+    }
+    let (parts, ()) = request.into_parts();
+    let request = Request::from_parts(parts, Body::from(body.freeze()));
+
+    // Dispatch to router
+    let response = router.oneshot(request).await?;
+    let (parts, body) = response.into_parts();
+    let body = to_bytes(body, MAX_BODY_SIZE).await?;
+
+    // Write response
+    stream
+        .send_response(Response::from_parts(parts, ()))
+        .await?;
+    if !body.is_empty() {
+        stream.send_data(body).await?;
+    }
+    stream.finish().await?;
+    Ok(())
+}
@@ -1,661 +1,5714 @@
-use alto_client::LATEST;
-use alto_types::{Block, Finalized, Kind, Notarized, Scheme, Seed};
+use alto_client::{
+    is_digest_prefix, upload_message, upload_namespace, API_VERSION, CURRENT_API_VERSION, LATEST,
+    MAX_EXPORT, MAX_PREFIX_MATCHES, MAX_RANGE, TRACEPARENT, UPLOAD_SIGNATURE, UPLOAD_SIGNER,
+    UPLOAD_TIMESTAMP, UPLOAD_VALIDATOR,
+};
+use alto_types::wasm::{EventJs, NullificationJs};
+use alto_types::{
+    Ack, Block, Consistency, DigestMode, Error, Finalized, Genesis, Identity, IdentitySchedule,
+    Kind, Notarized, Nullification, Payload, PublicKey, Scheme, Seed, SeedExt, SeedInfo, Seedable,
+    Subscription, Transaction, Upload, ValidatorInfo, ACK_FRAME, EPOCH, LAGGED_FRAME,
+    LATENCY_BUCKETS, MAX_BATCH_UPLOADS, MAX_CONSISTENCY_HEADERS, MAX_ENCODED_TRANSACTION_SIZE,
+    SIZE_BUCKETS,
+};
 use axum::{
-    body::Bytes,
-    extract::{ws::WebSocketUpgrade, Path, State as AxumState},
-    http::StatusCode,
-    response::IntoResponse,
+    body::{Body, Bytes},
+    extract::{
+        rejection::BytesRejection,
+        ws::{close_code, CloseFrame, WebSocketUpgrade},
+        DefaultBodyLimit, Path, Query as AxumQuery, RawQuery, Request, State as AxumState,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     routing::{get, post},
-    Router,
+    Json, Router,
 };
-use commonware_codec::{DecodeExt, Encode, EncodeSize, FixedSize, Write};
-use commonware_consensus::{types::View, Viewable};
-use commonware_cryptography::{sha256::Digest, Digestible};
+use bytes::BytesMut;
+use commonware_codec::{Decode, DecodeExt, Encode, EncodeSize, FixedSize, Read, Write};
+use commonware_consensus::{
+    types::{Epoch, Round, View},
+    Viewable,
+};
+use commonware_cryptography::{ed25519, sha256::Digest, Digestible, Hasher, Sha256, Verifier};
 use commonware_parallel::Strategy;
-use commonware_utils::from_hex;
-use futures::{SinkExt, StreamExt};
+use commonware_utils::{from_hex, hex, SystemTimeExt};
+use futures::{
+    future,
+    stream::{self, FuturesUnordered},
+    SinkExt, StreamExt,
+};
+use prometheus_client::{
+    encoding::text::encode,
+    metrics::{
+        counter::Counter, exemplar::HistogramWithExemplars, gauge::Gauge, histogram::Histogram,
+    },
+    registry::Registry,
+};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeMap,
-    sync::{Arc, RwLock},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    convert::Infallible,
+    fmt,
+    ops::Bound,
+    str::FromStr,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::sync::broadcast;
-use tower_http::cors::CorsLayer;
+use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tracing::{debug, debug_span, error, info, Span};
 
-#[derive(Default)]
-pub struct State {
-    seeds: BTreeMap<View, Seed>,
-    notarizations: BTreeMap<View, Notarized>,
-    finalizations: BTreeMap<View, Finalized>,
-    finalized_height_to_view: BTreeMap<u64, View>,
-    blocks_by_digest: BTreeMap<Digest, Block>,
-}
+pub mod grpc;
+#[cfg(feature = "http3")]
+pub mod http3;
+pub mod storage;
 
-#[derive(Clone)]
-pub struct Indexer<S: Strategy> {
-    scheme: Scheme,
-    state: Arc<RwLock<State>>,
-    consensus_tx: broadcast::Sender<Vec<u8>>,
-    strategy: S,
+use storage::{Cold, Demotion, Found, Stored, Table, Tier};
+
+/// Maximum difference (in milliseconds) between an upload's signed timestamp and the
+/// indexer's clock.
+pub const MAX_UPLOAD_SKEW: u64 = 30_000;
+
+/// A consensus message (and its view) broadcast to websocket subscribers.
+pub type Broadcast = (u64, Vec<u8>);
+
+/// Default number of messages buffered for consensus websocket subscribers.
+pub const DEFAULT_BROADCAST_CAPACITY: usize = 1024;
+
+/// Maximum number of views replayed to a consensus websocket subscriber that connects with
+/// `from_view` (older messages are reported as dropped).
+pub const MAX_REPLAY_VIEWS: u64 = 10_000;
+
+/// Default maximum size (in bytes) of an upload body.
+///
+/// Every uploaded artifact has a small, fixed-size encoding (a certificate and, for
+/// notarizations and finalizations, a block header), so this leaves ample room while bounding
+/// the memory a single request can consume before it is decoded.
+pub const DEFAULT_MAX_UPLOAD_SIZE: usize = 16 * 1024;
+
+/// Default number of views a notarization upload may be ahead of the latest seed.
+///
+/// Validators upload each view's seed alongside its notarization, so honest uploads are never
+/// far ahead of the latest seed. Bounding how far ahead they can be bounds the memory used by
+/// notarizations even when their signatures verify.
+pub const DEFAULT_NOTARIZATION_WINDOW: u64 = 10_000;
+
+/// Default interval (in seconds) between integrity audits of stored artifacts (see
+/// [Indexer::audit]).
+pub const DEFAULT_AUDIT_INTERVAL: u64 = 300;
+
+/// Default number of views (behind the latest stored view) kept in memory when artifacts are
+/// demoted to cold storage (see [Indexer::with_cold_storage]).
+///
+/// Matches [MAX_REPLAY_VIEWS], so replaying consensus messages to new subscribers never reads
+/// from cold storage.
+pub const DEFAULT_HOT_VIEWS: u64 = MAX_REPLAY_VIEWS;
+
+/// Maximum time (including connecting) to wait for a submitter to accept a forwarded
+/// transaction (see [Indexer::with_submitters]).
+pub const FORWARD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Upper bound on the bytes (identifier, kind, and length) framing each upload in a batch.
+const BATCH_FRAMING: usize = 16;
+
+/// `Cache-Control` of blocks requested by digest (which can never change).
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// Media type clients send (in `Accept`) to receive artifacts as JSON.
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// Maximum size (in bytes) of the body of each upload route.
+///
+/// Bodies are buffered up to the limit (and rejected with `413 Payload Too Large` once it is
+/// exceeded) before being authenticated and decoded. Decoding itself is bounded: uploaded
+/// artifacts contain no variable-length fields, so anything beyond their fixed encoding (like
+/// trailing bytes) is rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UploadLimits {
+    pub seed: usize,
+    pub notarization: usize,
+    pub finalization: usize,
+    pub nullification: usize,
 }
 
-impl<S: Strategy> Indexer<S> {
-    pub fn new(scheme: Scheme, strategy: S) -> Self {
-        let (consensus_tx, _) = broadcast::channel(1024);
-        let state = Arc::new(RwLock::new(State::default()));
+impl UploadLimits {
+    /// Get the limit for uploads of `kind`.
+    pub fn get(&self, kind: Kind) -> usize {
+        match kind {
+            Kind::Seed => self.seed,
+            Kind::Notarization => self.notarization,
+            Kind::Finalization => self.finalization,
+            Kind::Nullification => self.nullification,
+        }
+    }
+
+    /// Get the largest limit of any kind (the bound on uploads sent over the consensus
+    /// websocket, before their kind is known).
+    pub fn max(&self) -> usize {
+        self.seed
+            .max(self.notarization)
+            .max(self.finalization)
+            .max(self.nullification)
+    }
 
+    /// Get the limit for batches of uploads (enough for [MAX_BATCH_UPLOADS] uploads of any kind,
+    /// each with a few bytes of framing).
+    pub fn batch(&self) -> usize {
+        self.max()
+            .saturating_add(BATCH_FRAMING)
+            .saturating_mul(MAX_BATCH_UPLOADS)
+    }
+}
+
+impl Default for UploadLimits {
+    fn default() -> Self {
         Self {
-            scheme,
-            state,
-            consensus_tx,
-            strategy,
+            seed: DEFAULT_MAX_UPLOAD_SIZE,
+            notarization: DEFAULT_MAX_UPLOAD_SIZE,
+            finalization: DEFAULT_MAX_UPLOAD_SIZE,
+            nullification: DEFAULT_MAX_UPLOAD_SIZE,
         }
     }
+}
 
-    pub fn submit_seed(&self, seed: Seed) -> Result<(), &'static str> {
-        // Verify signature with identity
-        if !seed.verify(&self.scheme) {
-            return Err("Invalid seed signature");
-        }
+/// What to do when a consensus websocket subscriber falls more than the broadcast capacity
+/// behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Skip the oldest messages and notify the subscriber (with a [LAGGED_FRAME]) of how
+    /// many were dropped.
+    #[default]
+    DropOldest,
 
-        let mut state = self.state.write().unwrap();
-        if state.seeds.insert(seed.view(), seed.clone()).is_some() {
-            return Ok(()); // Already exists
-        }
+    /// Close the connection (so the subscriber can reconnect and backfill).
+    Disconnect,
+}
 
-        // Broadcast seed
-        let mut data = vec![0u8; u8::SIZE + seed.encode_size()];
-        data[0] = Kind::Seed as u8;
-        seed.write(&mut data[1..].as_mut());
-        let _ = self.consensus_tx.send(data);
-        Ok(())
+impl fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DropOldest => write!(f, "drop-oldest"),
+            Self::Disconnect => write!(f, "disconnect"),
+        }
     }
+}
 
-    pub fn get_seed(&self, query: &str) -> Option<Seed> {
-        let state = self.state.read().unwrap();
-        if query == LATEST {
-            state.seeds.last_key_value().map(|(_, seed)| seed.clone())
-        } else {
-            // Parse as hex-encoded index
-            let raw = from_hex(query)?;
-            let index = u64::decode(raw.as_slice()).ok()?;
-            state.seeds.get(&View::new(index)).cloned()
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop-oldest" => Ok(Self::DropOldest),
+            "disconnect" => Ok(Self::Disconnect),
+            _ => Err(format!("unknown overflow policy: {s}")),
         }
     }
+}
 
-    pub fn submit_notarization(&self, notarized: Notarized) -> Result<(), &'static str> {
-        // Verify signature with identity
-        if !notarized.verify(&self.scheme, &self.strategy) {
-            return Err("Invalid notarization signature");
-        }
+/// Authentication attached to an upload: the signer, the signed timestamp, and the signature.
+pub type UploadAuth = (PublicKey, u64, ed25519::Signature);
 
-        let mut state = self.state.write().unwrap();
+/// Recently accepted uploads, keyed by (timestamp, signature).
+type SeenUploads = BTreeSet<(u64, Vec<u8>)>;
 
-        // Store block by digest
-        state
-            .blocks_by_digest
-            .insert(notarized.block.digest(), notarized.block.clone());
+/// Upload statistics of a single uploader.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploaderStats {
+    /// Hex-encoded public key of the uploader.
+    pub uploader: String,
+    /// Number of seeds accepted.
+    pub seeds: u64,
+    /// Number of notarizations accepted.
+    pub notarizations: u64,
+    /// Number of finalizations accepted.
+    pub finalizations: u64,
+    /// Number of nullifications accepted.
+    pub nullifications: u64,
+    /// Number of uploads that could not be decoded or verified.
+    pub rejected: u64,
+    /// Time (in milliseconds since the epoch) of the last upload.
+    pub last_upload: u64,
+    /// Mean time (in milliseconds) between signing and receiving an upload (if any were signed).
+    pub mean_latency: Option<u64>,
+    /// Maximum time (in milliseconds) between signing and receiving an upload (if any were
+    /// signed).
+    pub max_latency: Option<u64>,
+}
 
-        // Store notarization
-        let view = notarized.proof.view();
-        if state
-            .notarizations
-            .insert(view, notarized.clone())
-            .is_some()
-        {
-            return Ok(()); // Already exists
-        }
+/// Running upload statistics of a single uploader.
+#[derive(Default)]
+struct Uploads {
+    stats: UploaderStats,
+    latency_sum: u64,
+    latency_samples: u64,
+}
 
-        // Broadcast notarization
-        let mut data = vec![0u8; u8::SIZE + notarized.encode_size()];
-        data[0] = Kind::Notarization as u8;
-        notarized.write(&mut data[1..].as_mut());
-        let _ = self.consensus_tx.send(data);
-        Ok(())
-    }
+/// Outcome of an integrity audit of stored artifacts (see [Indexer::audit]).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// Time (in milliseconds since the epoch) the audit completed.
+    pub completed: u64,
+    /// Number of finalizations checked.
+    pub finalizations: usize,
+    /// Heights whose finalized block conflicts with another finalized block at the same height
+    /// or does not extend the finalized block at the previous height (these are not repaired).
+    pub discontinuities: Vec<u64>,
+    /// Views of finalizations whose stored encoding was rebuilt.
+    pub reencoded: Vec<u64>,
+    /// Heights whose entry in the height index was added, corrected, or removed.
+    pub reindexed: Vec<u64>,
+    /// Views of finalizations whose block was restored to the digest index.
+    pub restored: Vec<u64>,
+}
 
-    pub fn get_notarization(&self, query: &str) -> Option<Notarized> {
-        let state = self.state.read().unwrap();
-        if query == LATEST {
-            state.notarizations.last_key_value().map(|(_, n)| n.clone())
-        } else {
-            // Parse as hex-encoded index
-            let raw = from_hex(query)?;
-            let index = u64::decode(raw.as_slice()).ok()?;
-            state.notarizations.get(&View::new(index)).cloned()
-        }
+impl AuditReport {
+    /// Returns true if the audit repaired anything.
+    pub fn repaired(&self) -> bool {
+        !self.reencoded.is_empty() || !self.reindexed.is_empty() || !self.restored.is_empty()
     }
+}
 
-    pub fn submit_finalization(&self, finalized: Finalized) -> Result<(), &'static str> {
-        // Verify signature with identity
-        if !finalized.verify(&self.scheme, &self.strategy) {
-            return Err("Invalid finalization signature");
-        }
+/// Number of finalizations an audit copies out of the [State] (while holding its lock) at a time.
+const AUDIT_CHUNK: usize = 256;
 
-        let mut state = self.state.write().unwrap();
+/// A finalization held in memory (and the block stored under its digest) copied out of the
+/// [State] by an audit.
+struct Sample {
+    round: Round,
+    finalization: Stored<Finalized>,
+    block: Option<Stored<Block>>,
+    demoted: bool,
+}
 
-        // Store block by digest
-        state
-            .blocks_by_digest
-            .insert(finalized.block.digest(), finalized.block.clone());
+/// Returns true if the block stored under `digest` was demoted (it was audited before it was
+/// demoted) or is held in memory with a matching digest and encoding.
+fn intact(digest: &Digest, stored: Option<&Stored<Block>>, demoted: bool) -> bool {
+    demoted
+        || stored.is_some_and(|stored| {
+            stored.value.digest() == *digest && stored.encoded == stored.value.encode()
+        })
+}
 
-        // Store finalization
-        let view = finalized.proof.view();
-        if state
+/// Returns true if `round` holds a finalization of a block at `height` (or was demoted).
+fn finalized_at(state: &State, height: u64, round: &Round) -> bool {
+    state.finalizations.is_cold(round)
+        || state
             .finalizations
-            .insert(view, finalized.clone())
-            .is_some()
-        {
-            return Ok(()); // Already exists
-        }
-        state
-            .finalized_height_to_view
-            .insert(finalized.block.height.get(), view);
+            .hot()
+            .get(round)
+            .is_some_and(|stored| stored.value.block.height.get() == height)
+}
 
-        // Broadcast finalization
-        let mut data = vec![0u8; u8::SIZE + finalized.encode_size()];
-        data[0] = Kind::Finalization as u8;
-        finalized.write(&mut data[1..].as_mut());
-        let _ = self.consensus_tx.send(data);
-        Ok(())
-    }
+/// Inconsistencies found in the [State] by an audit.
+#[derive(Default)]
+struct Inspection {
+    finalizations: usize,
+    discontinuities: Vec<u64>,
+    reencode: Vec<Round>,
+    reindex: Vec<(u64, Option<Round>)>,
+    restore: Vec<Round>,
+}
 
-    pub fn get_finalization(&self, query: &str) -> Option<Finalized> {
-        let state = self.state.read().unwrap();
-        if query == LATEST {
-            state.finalizations.last_key_value().map(|(_, f)| f.clone())
-        } else {
-            // Parse as hex-encoded index
-            let raw = from_hex(query)?;
-            let index = u64::decode(raw.as_slice()).ok()?;
-            state.finalizations.get(&View::new(index)).cloned()
-        }
-    }
+impl Inspection {
+    /// Check every finalization held in memory against the height index, the digest index, and
+    /// the finalization at the previous height (demoted finalizations were audited before they
+    /// were demoted).
+    ///
+    /// Finalizations are copied out of `state` [AUDIT_CHUNK] at a time (and the height index once)
+    /// and checked after its lock is released, so uploads are only blocked while they are copied.
+    /// Artifacts stored in the meantime may be reported as inconsistent, so each inconsistency
+    /// must be checked again before it is repaired.
+    fn new(state: &RwLock<State>) -> Self {
+        let mut inspection = Self::default();
 
-    pub fn get_block(&self, query: &str) -> Option<BlockResult> {
-        let state = self.state.read().unwrap();
+        // Verify stored encodings and finalized blocks (keeping the first round of each height)
+        let mut heights: BTreeMap<u64, Round> = BTreeMap::new();
+        let mut blocks: HashMap<Round, (u64, Digest, Digest)> = HashMap::new();
+        let mut after = Bound::Unbounded;
+        loop {
+            let chunk: Vec<Sample> = {
+                let state = state.read().unwrap();
+                state
+                    .finalizations
+                    .hot()
+                    .range((after, Bound::Unbounded))
+                    .take(AUDIT_CHUNK)
+                    .map(|(round, stored)| {
+                        let digest = stored.value.block.digest();
+                        Sample {
+                            round: *round,
+                            finalization: stored.clone(),
+                            block: state.blocks_by_digest.hot().get(&digest).cloned(),
+                            demoted: state.blocks_by_digest.is_cold(&digest),
+                        }
+                    })
+                    .collect()
+            };
+            let Some(last) = chunk.last() else {
+                break;
+            };
+            after = Bound::Excluded(last.round);
+            for sample in chunk {
+                inspection.finalizations += 1;
+                let stored = &sample.finalization;
+                if stored.encoded != stored.value.encode() {
+                    inspection.reencode.push(sample.round);
+                }
+                let block = &stored.value.block;
+                let digest = block.digest();
+                if !intact(&digest, sample.block.as_ref(), sample.demoted) {
+                    inspection.restore.push(sample.round);
+                }
+                let height = block.height.get();
+                if *heights.entry(height).or_insert(sample.round) != sample.round {
+                    inspection.discontinuities.push(height);
+                }
+                blocks.insert(sample.round, (height, digest, block.parent));
+            }
+        }
 
-        if query == LATEST {
-            // Return latest finalized block
+        // Verify the height index (an entry is valid if it points to a finalization at its height)
+        let index: Vec<(u64, Round, bool)> = {
+            let state = state.read().unwrap();
             state
-                .finalizations
-                .last_key_value()
-                .map(|(_, f)| BlockResult::Finalized(f.clone()))
-        } else if let Some(raw) = from_hex(query) {
-            // Try to parse as index (8 bytes)
-            if raw.len() == u64::SIZE {
-                let index = u64::decode(raw.as_slice()).ok()?;
-                state.finalized_height_to_view.get(&index).and_then(|view| {
-                    state
-                        .finalizations
-                        .get(view)
-                        .map(|f| BlockResult::Finalized(f.clone()))
-                })
-            } else if raw.len() == Digest::SIZE {
-                let digest = Digest::decode(raw.as_slice()).ok()?;
-                state
-                    .blocks_by_digest
-                    .get(&digest)
-                    .map(|b| BlockResult::Block(b.clone()))
-            } else {
-                None
+                .finalized_height_to_round
+                .iter()
+                .map(|(height, round)| (*height, *round, state.finalizations.is_cold(round)))
+                .collect()
+        };
+        for (height, round, demoted) in &index {
+            if *demoted {
+                continue; // audited before it was demoted
+            }
+            if blocks
+                .get(round)
+                .is_none_or(|(finalized, _, _)| finalized != height)
+            {
+                inspection
+                    .reindex
+                    .push((*height, heights.get(height).copied()));
+            }
+        }
+        let indexed: HashSet<u64> = index.iter().map(|(height, _, _)| *height).collect();
+        for (height, round) in &heights {
+            if !indexed.contains(height) {
+                inspection.reindex.push((*height, Some(*round)));
+            }
+        }
+
+        // Verify each finalized block extends the finalized block at the previous height
+        for ((previous, parent), (height, child)) in heights.iter().zip(heights.iter().skip(1)) {
+            if previous + 1 != *height {
+                continue;
+            }
+            let (_, parent, _) = blocks[parent];
+            let (_, _, child) = blocks[child];
+            if child != parent {
+                inspection.discontinuities.push(*height);
             }
-        } else {
-            None
         }
+        inspection.discontinuities.sort_unstable();
+        inspection.discontinuities.dedup();
+        inspection
     }
 
-    pub fn consensus_subscriber(&self) -> broadcast::Receiver<Vec<u8>> {
-        self.consensus_tx.subscribe()
+    /// Returns true if any inconsistency can be repaired.
+    fn repairable(&self) -> bool {
+        !self.reencode.is_empty() || !self.reindex.is_empty() || !self.restore.is_empty()
     }
 }
 
-#[allow(clippy::large_enum_variant)]
-pub enum BlockResult {
-    Block(Block),
-    Finalized(Finalized),
+/// Default number of entries in a [Listing].
+pub const DEFAULT_LISTING_LIMIT: usize = 100;
+
+/// Maximum number of entries in a [Listing].
+pub const MAX_LISTING_LIMIT: usize = 1_000;
+
+/// Position in a listing of artifacts ordered by view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Only list artifacts with views greater than this view (from the first view, if omitted).
+    pub after_view: Option<u64>,
+    /// Maximum number of entries to list (defaults to [DEFAULT_LISTING_LIMIT] and is capped at
+    /// [MAX_LISTING_LIMIT]).
+    pub limit: Option<usize>,
 }
 
-pub struct Api<S: Strategy> {
-    indexer: Arc<Indexer<S>>,
+/// Summary of a stored artifact (without its certificate).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// View of the artifact.
+    pub view: u64,
+    /// Height of the artifact's block (if it has one).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    /// Hex-encoded digest of the artifact's block (if it has one).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    /// Summary of the seed (if the artifact is one).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<SeedInfo>,
 }
 
-impl<S: Strategy> Api<S> {
-    pub fn new(indexer: Arc<Indexer<S>>) -> Self {
-        Self { indexer }
+impl Entry {
+    /// Summarize an artifact with `block`.
+    fn block(block: &Block) -> Self {
+        Self {
+            height: Some(block.height.get()),
+            digest: Some(hex(&block.digest())),
+            ..Default::default()
+        }
     }
+}
 
-    pub fn router(self) -> Router {
-        Router::new()
-            .route("/health", get(health_check))
-            .route("/seed", post(seed_upload))
-            .route("/seed/{query}", get(seed_get))
-            .route("/notarization", post(notarization_upload))
-            .route("/notarization/{query}", get(notarization_get))
-            .route("/finalization", post(finalization_upload))
-            .route("/finalization/{query}", get(finalization_get))
-            .route("/block/{query}", get(block_get))
-            .route("/consensus/ws", get(consensus_ws))
-            .layer(CorsLayer::permissive())
-            .with_state(self.indexer)
-    }
+/// A page of [Entry]s ordered by view.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Listing {
+    pub entries: Vec<Entry>,
+    /// Cursor for the next page (if there are more entries).
+    pub next: Option<Cursor>,
 }
 
-async fn health_check() -> impl IntoResponse {
-    (StatusCode::OK, "ok")
+pub struct State {
+    seeds: Tier<Round, Seed>,
+    notarizations: Tier<Round, Notarized>,
+    finalizations: Tier<Round, Finalized>,
+    nullifications: Tier<Round, Nullification>,
+    finalized_height_to_round: BTreeMap<u64, Round>,
+    blocks_by_digest: Tier<Digest, Block>,
+    seeds_received: BTreeMap<Round, Instant>,
+
+    /// Rounds in which the blocks held in memory were first stored (to demote them by age).
+    block_rounds: BTreeMap<Round, Vec<Digest>>,
+    /// Number of views (behind the latest stored view) to keep in memory.
+    hot_views: Option<u64>,
 }
 
-async fn seed_upload<S: Strategy>(
-    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
-    body: Bytes,
-) -> impl IntoResponse {
-    match Seed::decode(&mut body.as_ref()) {
-        Ok(seed) => match indexer.submit_seed(seed) {
-            Ok(_) => StatusCode::OK,
-            Err(_) => StatusCode::UNAUTHORIZED,
-        },
-        Err(_) => StatusCode::BAD_REQUEST,
+impl Default for State {
+    fn default() -> Self {
+        let digest_mode = DigestMode::default();
+        Self {
+            seeds: Tier::new(Table::Seeds, ()),
+            notarizations: Tier::new(Table::Notarizations, digest_mode),
+            finalizations: Tier::new(Table::Finalizations, digest_mode),
+            nullifications: Tier::new(Table::Nullifications, ()),
+            finalized_height_to_round: BTreeMap::new(),
+            blocks_by_digest: Tier::new(Table::Blocks, digest_mode),
+            seeds_received: BTreeMap::new(),
+            block_rounds: BTreeMap::new(),
+            hot_views: None,
+        }
     }
 }
 
-async fn seed_get<S: Strategy>(
-    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
-    Path(query): Path<String>,
-) -> impl IntoResponse {
-    match indexer.get_seed(&query) {
-        Some(seed) => (StatusCode::OK, seed.encode().to_vec()).into_response(),
-        None => StatusCode::NOT_FOUND.into_response(),
+impl State {
+    /// Get the latest round with a stored artifact.
+    fn latest(&self) -> Option<Round> {
+        [
+            self.seeds.last_key(),
+            self.notarizations.last_key(),
+            self.finalizations.last_key(),
+            self.nullifications.last_key(),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
     }
-}
 
-async fn notarization_upload<S: Strategy>(
-    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
-    body: Bytes,
-) -> impl IntoResponse {
-    match Notarized::decode(&mut body.as_ref()) {
-        Ok(notarized) => match indexer.submit_notarization(notarized) {
-            Ok(_) => StatusCode::OK,
-            Err(_) => StatusCode::UNAUTHORIZED,
-        },
-        Err(_) => StatusCode::BAD_REQUEST,
+    /// Get the latest epoch with a stored artifact.
+    ///
+    /// Views restart in every epoch, so artifacts requested by view are served from this epoch
+    /// (artifacts from earlier epochs remain available by height and digest).
+    fn epoch(&self) -> Epoch {
+        self.latest().map_or(EPOCH, |round| round.epoch())
     }
-}
 
-async fn notarization_get<S: Strategy>(
-    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
-    Path(query): Path<String>,
-) -> impl IntoResponse {
-    match indexer.get_notarization(&query) {
-        Some(notarized) => (StatusCode::OK, notarized.encode().to_vec()).into_response(),
-        None => StatusCode::NOT_FOUND.into_response(),
+    /// Decode demoted blocks (and certified blocks) using digests computed with `digest_mode`.
+    fn set_digest_mode(&mut self, digest_mode: DigestMode) {
+        self.notarizations.set_cfg(digest_mode);
+        self.finalizations.set_cfg(digest_mode);
+        self.blocks_by_digest.set_cfg(digest_mode);
     }
-}
 
-async fn finalization_upload<S: Strategy>(
-    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
-    body: Bytes,
-) -> impl IntoResponse {
-    match Finalized::decode(&mut body.as_ref()) {
-        Ok(finalized) => match indexer.submit_finalization(finalized) {
-            Ok(_) => StatusCode::OK,
-            Err(_) => StatusCode::UNAUTHORIZED,
-        },
-        Err(_) => StatusCode::BAD_REQUEST,
+    /// Demote artifacts more than `hot_views` views behind the latest stored view to `store`,
+    /// indexing the artifacts it already holds (see [Cold::keys]).
+    ///
+    /// Returns how many artifacts were indexed and the demoted finalizations (to index by height).
+    fn set_cold(
+        &mut self,
+        store: Arc<dyn Cold>,
+        hot_views: u64,
+    ) -> (usize, Vec<(Round, Found<Finalized>)>) {
+        let mut restored = self.seeds.set_store(store.clone()).len()
+            + self.notarizations.set_store(store.clone()).len()
+            + self.nullifications.set_store(store.clone()).len()
+            + self.blocks_by_digest.set_store(store.clone()).len();
+        let rounds = self.finalizations.set_store(store);
+        restored += rounds.len();
+        let finalizations = rounds
+            .into_iter()
+            .filter_map(|round| Some((round, self.finalizations.find(&round)?)))
+            .collect();
+        self.hot_views = Some(hot_views);
+        (restored, finalizations)
     }
-}
 
-async fn finalization_get<S: Strategy>(
-    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
-    Path(query): Path<String>,
-) -> impl IntoResponse {
-    match indexer.get_finalization(&query) {
-        Some(finalized) => (StatusCode::OK, finalized.encode().to_vec()).into_response(),
-        None => StatusCode::NOT_FOUND.into_response(),
+    /// Select artifacts (and blocks first stored in views) more than `hot_views` views behind
+    /// the latest stored view for demotion (if any).
+    ///
+    /// Views restart in every epoch, so all artifacts from earlier epochs are demoted.
+    fn select(&mut self) -> Option<Demotions> {
+        let (Some(hot_views), Some(latest)) = (self.hot_views, self.latest()) else {
+            return None;
+        };
+        let view = latest.view().get().saturating_sub(hot_views);
+        let floor = Round::new(latest.epoch(), View::new(view));
+        let recent = self.block_rounds.split_off(&floor);
+        let old = std::mem::replace(&mut self.block_rounds, recent);
+        let demotions = Demotions {
+            seeds: self.seeds.select_before(&floor),
+            notarizations: self.notarizations.select_before(&floor),
+            finalizations: self.finalizations.select_before(&floor),
+            nullifications: self.nullifications.select_before(&floor),
+            blocks: self.blocks_by_digest.select(old.into_values().flatten()),
+        };
+        (!demotions.is_empty()).then_some(demotions)
     }
-}
 
-async fn block_get<S: Strategy>(
-    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
-    Path(query): Path<String>,
-) -> impl IntoResponse {
-    match indexer.get_block(&query) {
-        Some(BlockResult::Block(block)) => {
-            (StatusCode::OK, block.encode().to_vec()).into_response()
-        }
-        Some(BlockResult::Finalized(finalized)) => {
-            (StatusCode::OK, finalized.encode().to_vec()).into_response()
-        }
-        None => StatusCode::NOT_FOUND.into_response(),
+    /// Move the artifacts of written `demotions` out of memory, returning how many were moved.
+    fn complete(&mut self, demotions: Demotions) -> usize {
+        self.seeds.complete(demotions.seeds)
+            + self.notarizations.complete(demotions.notarizations)
+            + self.finalizations.complete(demotions.finalizations)
+            + self.nullifications.complete(demotions.nullifications)
+            + self.blocks_by_digest.complete(demotions.blocks)
     }
 }
 
-async fn consensus_ws<S: Strategy>(
-    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
-    ws: WebSocketUpgrade,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_consensus_ws(socket, indexer))
+/// Artifacts selected for demotion from each tier of a [State] (see [State::select]).
+struct Demotions {
+    seeds: Demotion<Round>,
+    notarizations: Demotion<Round>,
+    finalizations: Demotion<Round>,
+    nullifications: Demotion<Round>,
+    blocks: Demotion<Digest>,
 }
 
-async fn handle_consensus_ws<S: Strategy>(
-    socket: axum::extract::ws::WebSocket,
-    indexer: Arc<Indexer<S>>,
-) {
-    let (mut sender, _receiver) = socket.split();
-    let mut consensus = indexer.consensus_subscriber();
+impl Demotions {
+    fn is_empty(&self) -> bool {
+        self.seeds.is_empty()
+            && self.notarizations.is_empty()
+            && self.finalizations.is_empty()
+            && self.nullifications.is_empty()
+            && self.blocks.is_empty()
+    }
 
-    while let Ok(data) = consensus.recv().await {
-        if sender
-            .send(axum::extract::ws::Message::Binary(data.into()))
-            .await
-            .is_err()
-        {
-            break;
-        }
+    /// Write the selected artifacts to the cold store (see [Demotion::write]).
+    fn write(&mut self) {
+        self.seeds.write();
+        self.notarizations.write();
+        self.finalizations.write();
+        self.nullifications.write();
+        self.blocks.write();
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use alto_client::{Client, ClientBuilder, IndexQuery, Query};
-    use alto_types::{Identity, Seedable, EPOCH, NAMESPACE};
-    use commonware_consensus::{
-        simplex::{
-            scheme::bls12381_threshold,
-            types::{Finalization, Finalize, Notarization, Notarize, Proposal},
-        },
-        types::{Height, Round, View},
-        Viewable,
-    };
-    use commonware_cryptography::{
-        bls12381::primitives::variant::MinSig, certificate::mocks::Fixture, Digestible, Hasher,
-        Sha256,
-    };
-    use commonware_parallel::Sequential;
-    use futures::StreamExt;
-    use rand::{rngs::StdRng, SeedableRng};
-    use rcgen::{generate_simple_self_signed, CertifiedKey, KeyPair};
-    use rustls::pki_types::{CertificateDer, PrivateKeyDer};
-    use std::net::SocketAddr;
-    use tokio::net::TcpListener;
-    use tokio_rustls::TlsAcceptor;
-    use tower::ServiceExt;
+/// Exemplar labels attached to latency observations.
+type Exemplar = Vec<(String, String)>;
 
-    /// Test context containing common setup for indexer tests.
-    struct TestContext {
-        schemes: Vec<Scheme>,
-        client: Client<Sequential>,
+/// Metrics exported by the [Indexer].
+struct IndexerMetrics {
+    registry: Registry,
+    seed_to_finalization: HistogramWithExemplars<Exemplar>,
+    lagged_subscribers: Counter,
+    lagged_messages: Counter,
+    disconnected_subscribers: Counter,
+    oversized_uploads: Counter,
+    out_of_window_uploads: Counter,
+    block_size: Histogram,
+    notarization_size: Histogram,
+    finalization_size: Histogram,
+    forwarded_transactions: Counter,
+    unforwarded_transactions: Counter,
+    audits: Counter,
+    audit_repairs: Counter,
+    audit_discontinuities: Gauge,
+    demoted_artifacts: Counter,
+}
+
+impl IndexerMetrics {
+    fn new() -> Self {
+        let mut registry = Registry::with_prefix("indexer");
+        let seed_to_finalization = HistogramWithExemplars::new(LATENCY_BUCKETS.into_iter());
+        registry.register(
+            "seed_to_finalization",
+            "Time (in seconds) between receiving a view's seed and its finalization",
+            seed_to_finalization.clone(),
+        );
+        let lagged_subscribers = Counter::default();
+        registry.register(
+            "lagged_subscribers",
+            "Number of times a consensus websocket subscriber fell behind the broadcast channel",
+            lagged_subscribers.clone(),
+        );
+        let lagged_messages = Counter::default();
+        registry.register(
+            "lagged_messages",
+            "Number of messages dropped for consensus websocket subscribers that fell behind",
+            lagged_messages.clone(),
+        );
+        let disconnected_subscribers = Counter::default();
+        registry.register(
+            "disconnected_subscribers",
+            "Number of consensus websocket subscribers disconnected for falling behind",
+            disconnected_subscribers.clone(),
+        );
+        let oversized_uploads = Counter::default();
+        registry.register(
+            "oversized_uploads",
+            "Number of uploads rejected for exceeding the body size limit of their route",
+            oversized_uploads.clone(),
+        );
+        let out_of_window_uploads = Counter::default();
+        registry.register(
+            "out_of_window_uploads",
+            "Number of notarization uploads rejected for being too far ahead of the latest seed",
+            out_of_window_uploads.clone(),
+        );
+        let block_size = Histogram::new(SIZE_BUCKETS);
+        registry.register(
+            "block_size",
+            "Encoded size (in bytes) of each block stored",
+            block_size.clone(),
+        );
+        let notarization_size = Histogram::new(SIZE_BUCKETS);
+        registry.register(
+            "notarization_size",
+            "Encoded size (in bytes) of each notarization certificate stored",
+            notarization_size.clone(),
+        );
+        let finalization_size = Histogram::new(SIZE_BUCKETS);
+        registry.register(
+            "finalization_size",
+            "Encoded size (in bytes) of each finalization certificate stored",
+            finalization_size.clone(),
+        );
+        let forwarded_transactions = Counter::default();
+        registry.register(
+            "forwarded_transactions",
+            "Number of submitted transactions accepted by at least one validator",
+            forwarded_transactions.clone(),
+        );
+        let unforwarded_transactions = Counter::default();
+        registry.register(
+            "unforwarded_transactions",
+            "Number of submitted transactions not accepted by any validator",
+            unforwarded_transactions.clone(),
+        );
+        let audits = Counter::default();
+        registry.register(
+            "audits",
+            "Number of integrity audits of stored artifacts completed",
+            audits.clone(),
+        );
+        let audit_repairs = Counter::default();
+        registry.register(
+            "audit_repairs",
+            "Number of inconsistencies in stored artifacts repaired by integrity audits",
+            audit_repairs.clone(),
+        );
+        let audit_discontinuities = Gauge::default();
+        registry.register(
+            "audit_discontinuities",
+            "Number of finalized heights that do not extend the previous finalized height (as of the last audit)",
+            audit_discontinuities.clone(),
+        );
+        let demoted_artifacts = Counter::default();
+        registry.register(
+            "demoted_artifacts",
+            "Number of stored artifacts demoted from memory to cold storage",
+            demoted_artifacts.clone(),
+        );
+        Self {
+            registry,
+            seed_to_finalization,
+            lagged_subscribers,
+            lagged_messages,
+            disconnected_subscribers,
+            oversized_uploads,
+            out_of_window_uploads,
+            block_size,
+            notarization_size,
+            finalization_size,
+            forwarded_transactions,
+            unforwarded_transactions,
+            audits,
+            audit_repairs,
+            audit_discontinuities,
+            demoted_artifacts,
+        }
     }
+}
 
-    impl TestContext {
-        /// Create a new test context with a running server and client.
-        async fn new() -> Self {
-            let mut rng = StdRng::seed_from_u64(0);
-            let Fixture { schemes, .. } =
-                bls12381_threshold::fixture::<MinSig, _>(&mut rng, NAMESPACE, 4);
-            let identity = *schemes[0].polynomial().public();
+/// An export of finalizations (see [Indexer::export]).
+pub struct Export {
+    /// The encoding of the export, split into chunks (see [Indexer::export_finalizations]).
+    pub chunks: Vec<Bytes>,
+
+    /// A (quoted) digest of the encoding, so a resumed download can detect if finalizations
+    /// were added since it started.
+    pub etag: String,
+
+    /// The length of the encoding.
+    pub len: u64,
+}
+
+/// Identifies the finalizations covered by a cached [Export].
+#[derive(PartialEq, Eq)]
+struct ExportKey {
+    epoch: Epoch,
+    start: u64,
+    end: u64,
+    count: usize,
+    latest: Option<Round>,
+    changes: u64,
+}
+
+/// The latest [Export] (and the finalizations it covers).
+type CachedExport = (ExportKey, Arc<Export>);
+
+#[derive(Clone)]
+pub struct Indexer<S: Strategy> {
+    identities: IdentitySchedule,
+    state: Arc<RwLock<State>>,
+    consensus_tx: broadcast::Sender<Broadcast>,
+    overflow_policy: OverflowPolicy,
+    strategy: S,
+    digest_mode: DigestMode,
+    genesis: Option<Arc<Stored<Genesis>>>,
+    validators: Option<Arc<Vec<ValidatorInfo>>>,
+    upload_limits: UploadLimits,
+    notarization_window: u64,
+
+    uploaders: Option<Arc<HashSet<PublicKey>>>,
+    seen_uploads: Arc<Mutex<SeenUploads>>,
+    uploads: Arc<Mutex<BTreeMap<PublicKey, Uploads>>>,
+
+    submitters: Arc<Vec<String>>,
+    http_client: reqwest::Client,
+
+    audit: Arc<Mutex<Option<AuditReport>>>,
+    export: Arc<Mutex<Option<CachedExport>>>,
+    metrics: Arc<IndexerMetrics>,
+}
+
+impl<S: Strategy> Indexer<S> {
+    pub fn new(scheme: Scheme, strategy: S) -> Self {
+        let (consensus_tx, _) = broadcast::channel(DEFAULT_BROADCAST_CAPACITY);
+        let state = Arc::new(RwLock::new(State::default()));
+
+        Self {
+            identities: IdentitySchedule::new(*scheme.identity()),
+            state,
+            consensus_tx,
+            overflow_policy: OverflowPolicy::default(),
+            strategy,
+            digest_mode: DigestMode::default(),
+            genesis: None,
+            validators: None,
+            upload_limits: UploadLimits::default(),
+            notarization_window: DEFAULT_NOTARIZATION_WINDOW,
+            uploaders: None,
+            seen_uploads: Arc::new(Mutex::new(BTreeSet::new())),
+            uploads: Arc::new(Mutex::new(BTreeMap::new())),
+            submitters: Arc::new(Vec::new()),
+            http_client: reqwest::Client::builder()
+                .timeout(FORWARD_TIMEOUT)
+                .build()
+                .expect("failed to build http client"),
+            audit: Arc::new(Mutex::new(None)),
+            export: Arc::new(Mutex::new(None)),
+            metrics: Arc::new(IndexerMetrics::new()),
+        }
+    }
+
+    /// Encode all metrics in the OpenMetrics text format (including exemplars).
+    pub fn encode_metrics(&self) -> String {
+        let mut buffer = String::new();
+        encode(&mut buffer, &self.metrics.registry).expect("failed to encode metrics");
+        buffer
+    }
+
+    /// Decode uploaded blocks using digests computed with `digest_mode` (must match the network).
+    pub fn with_digest_mode(mut self, digest_mode: DigestMode) -> Self {
+        self.digest_mode = digest_mode;
+        self.state.write().unwrap().set_digest_mode(digest_mode);
+        self
+    }
+
+    /// Demote artifacts more than `hot_views` views behind the latest stored view (and blocks
+    /// first stored in those views) from memory to `store` (see [storage]).
+    ///
+    /// Demoted artifacts remain queryable (read back from `store` on demand), including those
+    /// demoted before a restart (which are indexed again, so call after
+    /// [Indexer::with_digest_mode]). Demotions are written on blocking threads of the current
+    /// tokio runtime.
+    pub fn with_cold_storage(self, store: impl Cold, hot_views: u64) -> Self {
+        let (restored, finalizations) = self
+            .state
+            .write()
+            .unwrap()
+            .set_cold(Arc::new(store), hot_views);
+
+        // Index demoted finalizations by height (reading them without holding the lock)
+        let mut heights = Vec::with_capacity(finalizations.len());
+        let mut unreadable = Vec::new();
+        for (round, found) in finalizations {
+            match found.load() {
+                Some(stored) => heights.push((stored.value.block.height.get(), round)),
+                None => unreadable.push(round),
+            }
+        }
+        let mut state = self.state.write().unwrap();
+        state.finalized_height_to_round.extend(heights);
+        for round in &unreadable {
+            state.finalizations.forget(round);
+        }
+        drop(state);
+        if restored > 0 {
+            info!(
+                restored,
+                unreadable = unreadable.len(),
+                "indexed demoted artifacts"
+            );
+        }
+        self
+    }
+
+    /// Buffer up to `capacity` messages for consensus websocket subscribers (defaults to
+    /// [DEFAULT_BROADCAST_CAPACITY]).
+    ///
+    /// Must be called before any subscriber is created.
+    pub fn with_broadcast_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "broadcast capacity must be greater than zero");
+        let (consensus_tx, _) = broadcast::channel(capacity);
+        self.consensus_tx = consensus_tx;
+        self
+    }
+
+    /// Handle consensus websocket subscribers that fall behind with `policy` (defaults to
+    /// [OverflowPolicy::DropOldest]).
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Limit the size of upload bodies per route (defaults to [DEFAULT_MAX_UPLOAD_SIZE] for
+    /// every route).
+    pub fn with_upload_limits(mut self, limits: UploadLimits) -> Self {
+        self.upload_limits = limits;
+        self
+    }
+
+    /// Reject notarization uploads more than `window` views ahead of the latest seed (defaults
+    /// to [DEFAULT_NOTARIZATION_WINDOW]).
+    pub fn with_notarization_window(mut self, window: u64) -> Self {
+        self.notarization_window = window;
+        self
+    }
+
+    /// Verify artifacts from `epoch` onwards with `identity` (the identity of the validator set
+    /// that took over at the start of `epoch`).
+    ///
+    /// Artifacts from earlier epochs are still verified against their own identity (see
+    /// [IdentitySchedule]).
+    pub fn with_epoch(mut self, epoch: u64, identity: Identity) -> Self {
+        self.identities.reconfigure(epoch, identity);
+        self
+    }
+
+    /// Serve `genesis` (the network's genesis transcript) at `/genesis`.
+    ///
+    /// Panics if `genesis` is not fully signed or is for a different network.
+    pub fn with_genesis(mut self, genesis: Genesis) -> Self {
+        assert!(genesis.verify(), "genesis transcript is invalid");
+        assert_eq!(
+            &genesis.identity,
+            self.identities.genesis(),
+            "genesis transcript is for a different network"
+        );
+        self.genesis = Some(Arc::new(Stored::new(genesis)));
+        self
+    }
+
+    /// Serve operator-provided metadata about `validators` (ordered by public key) at
+    /// `/validators`.
+    pub fn with_validators(mut self, validators: Vec<ValidatorInfo>) -> Self {
+        self.validators = Some(Arc::new(validators));
+        self
+    }
+
+    /// Get the genesis transcript (if configured).
+    pub fn get_genesis(&self) -> Option<&Genesis> {
+        self.genesis.as_ref().map(|stored| &stored.value)
+    }
+
+    /// Only accept uploads signed by one of `uploaders`.
+    pub fn with_uploaders(mut self, uploaders: impl IntoIterator<Item = PublicKey>) -> Self {
+        self.uploaders = Some(Arc::new(uploaders.into_iter().collect()));
+        self
+    }
+
+    /// Forward transactions submitted with `POST /tx` to each of `submitters` (the URLs of
+    /// validator transaction endpoints, like `http://validator:9092/tx`).
+    ///
+    /// If no submitters are configured, submissions are rejected.
+    pub fn with_submitters(mut self, submitters: impl IntoIterator<Item = String>) -> Self {
+        self.submitters = Arc::new(submitters.into_iter().collect());
+        self
+    }
+
+    /// Forward an encoded `transaction` to every submitter (concurrently).
+    ///
+    /// Returns true as soon as any submitter accepts it (abandoning the remaining requests) or
+    /// false once all have failed (each request is bounded by [FORWARD_TIMEOUT]).
+    ///
+    /// One acceptance is enough by design: validators broadcast every transaction they add to
+    /// their mempool to all of their peers, so a transaction accepted by any validator reaches
+    /// every proposer without the indexer waiting on (or retrying) the others.
+    pub async fn forward_transaction(&self, transaction: Bytes) -> bool {
+        let mut requests: FuturesUnordered<_> = self.submitters.iter().map(|submitter| {
+            let request = self.http_client.post(submitter).body(transaction.clone());
+            async move {
+                match request.send().await {
+                    Ok(response) if response.status().is_success() => true,
+                    Ok(response) => {
+                        debug!(submitter, status = %response.status(), "submitter rejected transaction");
+                        false
+                    }
+                    Err(e) => {
+                        debug!(submitter, ?e, "failed to forward transaction");
+                        false
+                    }
+                }
+            }
+        }).collect();
+        let mut forwarded = false;
+        while let Some(accepted) = requests.next().await {
+            if accepted {
+                forwarded = true;
+                break;
+            }
+        }
+        if forwarded {
+            self.metrics.forwarded_transactions.inc();
+        } else {
+            self.metrics.unforwarded_transactions.inc();
+        }
+        forwarded
+    }
+
+    /// Authenticate an upload of `body` received at `now` (in milliseconds since the epoch).
+    ///
+    /// If no uploaders are configured, all uploads are accepted. Otherwise, the upload must be
+    /// signed by a configured uploader within [MAX_UPLOAD_SKEW] of `now` and must not have been
+    /// seen before.
+    pub fn authenticate_upload(
+        &self,
+        auth: Option<UploadAuth>,
+        body: &[u8],
+        now: u64,
+    ) -> Result<(), &'static str> {
+        let Some(uploaders) = &self.uploaders else {
+            return Ok(());
+        };
+        let Some((signer, timestamp, signature)) = auth else {
+            return Err("missing upload signature");
+        };
+        if !uploaders.contains(&signer) {
+            return Err("unknown uploader");
+        }
+        if timestamp.abs_diff(now) > MAX_UPLOAD_SKEW {
+            return Err("stale upload");
+        }
+        if !signer.verify(
+            &upload_namespace(),
+            &upload_message(body, timestamp),
+            &signature,
+        ) {
+            return Err("invalid upload signature");
+        }
+
+        // Reject replays (only uploads within the skew window need to be remembered)
+        let mut seen = self.seen_uploads.lock().unwrap();
+        let cutoff = now.saturating_sub(MAX_UPLOAD_SKEW);
+        *seen = seen.split_off(&(cutoff, Vec::new()));
+        if !seen.insert((timestamp, signature.encode().to_vec())) {
+            return Err("replayed upload");
+        }
+        Ok(())
+    }
+
+    /// Record an upload of `kind` by `uploader` received at `now` (in milliseconds since the
+    /// epoch).
+    ///
+    /// If the upload was signed at `signed` (in milliseconds since the epoch), the time between
+    /// signing and receiving it is included in the uploader's latency.
+    pub fn record_upload(
+        &self,
+        uploader: PublicKey,
+        kind: Kind,
+        accepted: bool,
+        signed: Option<u64>,
+        now: u64,
+    ) {
+        let mut uploads = self.uploads.lock().unwrap();
+        let entry = uploads.entry(uploader).or_default();
+        let stats = &mut entry.stats;
+        match (accepted, kind) {
+            (false, _) => stats.rejected += 1,
+            (true, Kind::Seed) => stats.seeds += 1,
+            (true, Kind::Notarization) => stats.notarizations += 1,
+            (true, Kind::Finalization) => stats.finalizations += 1,
+            (true, Kind::Nullification) => stats.nullifications += 1,
+        }
+        stats.last_upload = stats.last_upload.max(now);
+        if let Some(signed) = signed {
+            let latency = now.saturating_sub(signed);
+            entry.latency_sum = entry.latency_sum.saturating_add(latency);
+            entry.latency_samples += 1;
+            let stats = &mut entry.stats;
+            stats.max_latency = Some(stats.max_latency.unwrap_or(0).max(latency));
+            stats.mean_latency = Some(entry.latency_sum / entry.latency_samples);
+        }
+    }
+
+    /// Get the upload statistics of every uploader seen (ordered by public key).
+    pub fn upload_stats(&self) -> Vec<UploaderStats> {
+        let uploads = self.uploads.lock().unwrap();
+        uploads
+            .iter()
+            .map(|(uploader, uploads)| UploaderStats {
+                uploader: hex(&uploader.encode()),
+                ..uploads.stats.clone()
+            })
+            .collect()
+    }
+
+    pub fn submit_seed(&self, seed: Seed) -> Result<(), Error> {
+        // Verify signature with identity
+        if !seed.verify(self.identities.verifier(seed.round)) {
+            return Err(Error::Signature);
+        }
+
+        let mut state = self.state.write().unwrap();
+        if !state.seeds.insert(seed.round, Stored::new(seed.clone())) {
+            return Ok(()); // Already exists
+        }
+        state.seeds_received.insert(seed.round, Instant::now());
+        self.demote(&mut state);
+
+        // Broadcast seed
+        self.broadcast(Kind::Seed, seed.view(), &seed);
+        Ok(())
+    }
+
+    pub fn get_seed(&self, query: &str) -> Option<Seed> {
+        self.load(|state| lookup(&state.seeds, state.epoch(), query))
+            .map(|stored| stored.value)
+    }
+
+    /// Get all seeds with views in `[start, end)`.
+    pub fn get_seed_range(&self, start: u64, end: u64) -> Vec<Seed> {
+        self.load_all(|state| range(&state.seeds, state.epoch(), start, end).collect())
+            .into_iter()
+            .map(|stored| stored.value)
+            .collect()
+    }
+
+    pub fn submit_notarization(&self, notarized: Notarized) -> Result<(), Error> {
+        // Reject notarizations too far ahead of the latest seed (before verifying them)
+        let round = notarized.proof.round();
+        let view = round.view();
+        let latest = {
+            let state = self.state.read().unwrap();
+            state.seeds.last_key()
+        };
+
+        // Views restart in every epoch, so notarizations from a later epoch are bounded from its
+        // first view (and those from an earlier epoch are not bounded by the latest seed)
+        let base = match latest {
+            Some(seed) if seed.epoch() == round.epoch() => Some(seed.view().get()),
+            Some(seed) if seed.epoch() > round.epoch() => None,
+            _ => Some(0),
+        };
+        if base.is_some_and(|base| view.get() > base.saturating_add(self.notarization_window)) {
+            self.metrics.out_of_window_uploads.inc();
+            return Err(Error::Consistency(format!(
+                "view {view} is more than {} views ahead of the latest seed",
+                self.notarization_window
+            )));
+        }
+
+        // Verify signature with identity
+        if !notarized.verify(self.identities.verifier(round), &self.strategy) {
+            return Err(Error::Signature);
+        }
+
+        let mut state = self.state.write().unwrap();
+
+        // Store block by digest
+        self.store_block(&mut state, round, &notarized.block);
+
+        // Store notarization
+        if !state
+            .notarizations
+            .insert(round, Stored::new(notarized.clone()))
+        {
+            return Ok(()); // Already exists
+        }
+        self.demote(&mut state);
+        self.metrics
+            .notarization_size
+            .observe(notarized.proof.encode_size() as f64);
+
+        // Broadcast notarization
+        self.broadcast(Kind::Notarization, view, &notarized);
+        Ok(())
+    }
+
+    /// Store `block` (certified in `round`) by digest (if not already stored), recording its
+    /// size.
+    fn store_block(&self, state: &mut State, round: Round, block: &Block) {
+        let digest = block.digest();
+        let stored = Stored::new(block.clone());
+        let size = stored.encoded.len();
+        if state.blocks_by_digest.insert(digest, stored) {
+            state.block_rounds.entry(round).or_default().push(digest);
+            self.metrics.block_size.observe(size as f64);
+        }
+    }
+
+    /// Demote artifacts that fell out of the hot window (see [storage]).
+    ///
+    /// Selected artifacts are written to the cold store on a blocking thread (that only takes
+    /// the state lock once they are written).
+    fn demote(&self, state: &mut State) {
+        let Some(mut demotions) = state.select() else {
+            return;
+        };
+        let shared = self.state.clone();
+        let metrics = self.metrics.clone();
+        tokio::task::spawn_blocking(move || {
+            demotions.write();
+            let demoted = shared.write().unwrap().complete(demotions);
+            metrics.demoted_artifacts.inc_by(demoted as u64);
+        });
+    }
+
+    /// Find an artifact with `find` (holding the state lock) and load it (without holding the
+    /// lock, see [Found::load]).
+    fn load<T: Read>(&self, find: impl FnOnce(&State) -> Option<Found<T>>) -> Option<Stored<T>> {
+        let found = find(&self.state.read().unwrap());
+        found?.load()
+    }
+
+    /// Find artifacts with `find` (holding the state lock) and load them (without holding the
+    /// lock, skipping any that cannot be read).
+    fn load_all<T: Read>(&self, find: impl FnOnce(&State) -> Vec<Found<T>>) -> Vec<Stored<T>> {
+        let found = find(&self.state.read().unwrap());
+        found.into_iter().filter_map(Found::load).collect()
+    }
+
+    pub fn get_notarization(&self, query: &str) -> Option<Notarized> {
+        self.load(|state| lookup(&state.notarizations, state.epoch(), query))
+            .map(|stored| stored.value)
+    }
+
+    /// Get all notarizations with views in `[start, end)`.
+    pub fn get_notarization_range(&self, start: u64, end: u64) -> Vec<Notarized> {
+        self.load_all(|state| range(&state.notarizations, state.epoch(), start, end).collect())
+            .into_iter()
+            .map(|stored| stored.value)
+            .collect()
+    }
+
+    pub fn submit_finalization(&self, finalized: Finalized) -> Result<(), Error> {
+        // Verify signature with identity
+        let round = finalized.proof.round();
+        if !finalized.verify(self.identities.verifier(round), &self.strategy) {
+            return Err(Error::Signature);
+        }
+        self.store_finalization(finalized);
+        Ok(())
+    }
+
+    /// Verify the signatures of all `finalized` blocks with aggregated checks (see
+    /// [IdentitySchedule::verify_finalized]), returning the indices of the invalid ones.
+    pub fn verify_finalizations(&self, finalized: &[Finalized]) -> Vec<usize> {
+        self.identities.verify_finalized(&self.strategy, finalized)
+    }
+
+    /// Store a (verified) finalization.
+    fn store_finalization(&self, finalized: Finalized) {
+        let round = finalized.proof.round();
+        let mut state = self.state.write().unwrap();
+
+        // Store block by digest
+        self.store_block(&mut state, round, &finalized.block);
+
+        // Store finalization
+        let view = round.view();
+        if !state
+            .finalizations
+            .insert(round, Stored::new(finalized.clone()))
+        {
+            return; // Already exists
+        }
+        self.metrics
+            .finalization_size
+            .observe(finalized.proof.encode_size() as f64);
+        state
+            .finalized_height_to_round
+            .insert(finalized.block.height.get(), round);
+
+        // Recover the seed (if it was never uploaded) from the finalization's certificate
+        state
+            .seeds
+            .insert(round, Stored::new(finalized.proof.seed()));
+
+        // Record time since the seed was received (and forget older views)
+        if let Some(received) = state.seeds_received.get(&round) {
+            let exemplar = vec![("view".to_string(), view.to_string())];
+            self.metrics.seed_to_finalization.observe(
+                received.elapsed().as_secs_f64(),
+                Some(exemplar),
+                None,
+            );
+        }
+        state.seeds_received = state
+            .seeds_received
+            .split_off(&Round::new(round.epoch(), view.next()));
+        self.demote(&mut state);
+
+        // Broadcast finalization
+        self.broadcast(Kind::Finalization, view, &finalized);
+    }
+
+    pub fn get_finalization(&self, query: &str) -> Option<Finalized> {
+        self.load(|state| lookup(&state.finalizations, state.epoch(), query))
+            .map(|stored| stored.value)
+    }
+
+    /// Get all finalizations with views in `[start, end)`.
+    pub fn get_finalization_range(&self, start: u64, end: u64) -> Vec<Finalized> {
+        self.load_all(|state| range(&state.finalizations, state.epoch(), start, end).collect())
+            .into_iter()
+            .map(|stored| stored.value)
+            .collect()
+    }
+
+    pub fn submit_nullification(&self, nullification: Nullification) -> Result<(), Error> {
+        // Verify signature with identity
+        let round = nullification.round();
+        let verifier = self.identities.verifier(round);
+        if !nullification.verify::<_, Digest>(&mut OsRng, verifier, &self.strategy) {
+            return Err(Error::Signature);
+        }
+
+        let mut state = self.state.write().unwrap();
+        let view = round.view();
+        if !state
+            .nullifications
+            .insert(round, Stored::new(nullification.clone()))
+        {
+            return Ok(()); // Already exists
+        }
+        self.demote(&mut state);
+
+        // Broadcast nullification
+        self.broadcast(Kind::Nullification, view, &nullification);
+        Ok(())
+    }
+
+    pub fn get_nullification(&self, query: &str) -> Option<Nullification> {
+        self.load(|state| lookup(&state.nullifications, state.epoch(), query))
+            .map(|stored| stored.value)
+    }
+
+    /// Get the encoding of the artifact of `kind` matching `query` (without re-encoding it).
+    pub fn get_encoded(&self, kind: Kind, query: &str) -> Option<Bytes> {
+        match kind {
+            Kind::Seed => self
+                .load(|state| lookup(&state.seeds, state.epoch(), query))
+                .map(|stored| stored.encoded),
+            Kind::Notarization => self
+                .load(|state| lookup(&state.notarizations, state.epoch(), query))
+                .map(|stored| stored.encoded),
+            Kind::Finalization => self
+                .load(|state| lookup(&state.finalizations, state.epoch(), query))
+                .map(|stored| stored.encoded),
+            Kind::Nullification => self
+                .load(|state| lookup(&state.nullifications, state.epoch(), query))
+                .map(|stored| stored.encoded),
+        }
+    }
+
+    /// Get the encoding (as a `Vec`) of all artifacts of `kind` with views in `[start, end)`.
+    ///
+    /// The response is assembled from the stored encodings of each artifact.
+    pub fn get_encoded_range(&self, kind: Kind, start: u64, end: u64) -> Bytes {
+        match kind {
+            Kind::Seed => encode_range(
+                self.load_all(|state| range(&state.seeds, state.epoch(), start, end).collect()),
+            ),
+            Kind::Notarization => encode_range(self.load_all(|state| {
+                range(&state.notarizations, state.epoch(), start, end).collect()
+            })),
+            Kind::Finalization => encode_range(self.load_all(|state| {
+                range(&state.finalizations, state.epoch(), start, end).collect()
+            })),
+            Kind::Nullification => encode_range(self.load_all(|state| {
+                range(&state.nullifications, state.epoch(), start, end).collect()
+            })),
+        }
+    }
+
+    /// Get the encoding of all finalized blocks with heights in `[start, end)` (the length
+    /// prefix followed by each stored finalization).
+    pub fn get_block_range_encoded(&self, start: u64, end: u64) -> Bytes {
+        encode_range(self.load_all(|state| finalized_range(state, start, end)))
+    }
+
+    /// Get all finalized blocks with heights in `[start, end)`.
+    pub fn get_block_range(&self, start: u64, end: u64) -> Vec<Finalized> {
+        self.load_all(|state| finalized_range(state, start, end))
+            .into_iter()
+            .map(|stored| stored.value)
+            .collect()
+    }
+
+    /// Get the encoding (as a `Vec`) of all finalizations with views in `[start, end)`, split
+    /// into chunks (the length prefix followed by each stored encoding).
+    ///
+    /// Stored encodings are reference-counted, so an export can be streamed without copying (or
+    /// holding the lock, which is also released before demoted finalizations are read).
+    pub fn export_finalizations(&self, start: u64, end: u64) -> Vec<Bytes> {
+        let finalizations: Vec<_> = self
+            .load_all(|state| range(&state.finalizations, state.epoch(), start, end).collect())
+            .into_iter()
+            .map(|stored| stored.encoded)
+            .collect();
+        let mut chunks = Vec::with_capacity(finalizations.len() + 1);
+        chunks.push(finalizations.len().encode());
+        chunks.extend(finalizations);
+        chunks
+    }
+
+    /// Get the [Export] of all finalizations with views in `[start, end)`.
+    ///
+    /// The latest export is cached until a finalization it covers is added, replaced, or
+    /// forgotten, so a client resuming a download (or fetching it in ranges) does not rebuild
+    /// and hash it again.
+    pub fn export(&self, start: u64, end: u64) -> Arc<Export> {
+        let key = {
+            let state = self.state.read().unwrap();
+            let epoch = state.epoch();
+            let first = Round::new(epoch, View::new(start));
+            let last = Round::new(epoch, View::new(end)).max(first);
+            let (count, latest) = state
+                .finalizations
+                .keys(first..last)
+                .fold((0, None), |(count, _), round| (count + 1, Some(round)));
+            ExportKey {
+                epoch,
+                start,
+                end,
+                count,
+                latest,
+                changes: state.finalizations.changes(),
+            }
+        };
+        if let Some((cached, export)) = &*self.export.lock().unwrap() {
+            if *cached == key {
+                return export.clone();
+            }
+        }
+
+        // Build (and cache) the export without holding any lock
+        let chunks = self.export_finalizations(start, end);
+        let mut hasher = Sha256::new();
+        for chunk in &chunks {
+            hasher.update(chunk);
+        }
+        let export = Arc::new(Export {
+            etag: format!("\"{}\"", hex(&hasher.finalize())),
+            len: chunks.iter().map(|chunk| chunk.len() as u64).sum(),
+            chunks,
+        });
+        *self.export.lock().unwrap() = Some((key, export.clone()));
+        export
+    }
+
+    /// List the seeds after `cursor`.
+    pub fn list_seeds(&self, cursor: Cursor) -> Listing {
+        let page = page(&self.state.read().unwrap(), |state| &state.seeds, cursor);
+        list(page, cursor, |seed| Entry {
+            seed: Some(seed.info()),
+            ..Default::default()
+        })
+    }
+
+    /// List the notarizations after `cursor`.
+    pub fn list_notarizations(&self, cursor: Cursor) -> Listing {
+        let page = page(
+            &self.state.read().unwrap(),
+            |state| &state.notarizations,
+            cursor,
+        );
+        list(page, cursor, |notarized| Entry::block(&notarized.block))
+    }
+
+    /// List the finalizations after `cursor`.
+    pub fn list_finalizations(&self, cursor: Cursor) -> Listing {
+        let page = page(
+            &self.state.read().unwrap(),
+            |state| &state.finalizations,
+            cursor,
+        );
+        list(page, cursor, |finalized| Entry::block(&finalized.block))
+    }
+
+    pub fn get_block(&self, query: &str) -> Option<Payload> {
+        let found = lookup_block(&self.state.read().unwrap(), query)?;
+        match found {
+            Ok(block) => Some(block.load()?.value.into()),
+            Err(finalized) => Some(finalized.load()?.value.into()),
+        }
+    }
+
+    /// Get the receipts of the block (or finalized block) matching `query`.
+    pub fn get_receipts(&self, query: &str) -> Option<Bytes> {
+        self.get_block(query)
+            .map(|payload| payload.block().receipts.clone())
+    }
+
+    /// Get the encoding of the block (or finalized block) matching `query` (without re-encoding
+    /// it).
+    pub fn get_block_encoded(&self, query: &str) -> Option<Bytes> {
+        let found = lookup_block(&self.state.read().unwrap(), query)?;
+        match found {
+            Ok(block) => Some(block.load()?.encoded),
+            Err(finalized) => Some(finalized.load()?.encoded),
+        }
+    }
+
+    /// Get the digests of (up to `limit`) blocks whose hex-encoded digest starts with `prefix`
+    /// (a valid [is_digest_prefix]).
+    pub fn find_blocks(&self, prefix: &str, limit: usize) -> Vec<Digest> {
+        // Start from the smallest digest with the prefix
+        let padded = format!("{prefix:0<width$}", width = Digest::SIZE * 2);
+        let Some(start) = from_hex(&padded).and_then(|raw| Digest::decode(raw.as_slice()).ok())
+        else {
+            return Vec::new();
+        };
+        let state = self.state.read().unwrap();
+        state
+            .blocks_by_digest
+            .keys(start..)
+            .take_while(|digest| hex(digest).starts_with(prefix))
+            .take(limit)
+            .collect()
+    }
+
+    /// Get a [Consistency] proof that the finalized block at height `to` descends from
+    /// the block at height `from`.
+    ///
+    /// Ancestors are found by walking parent digests back from the finalized block.
+    pub fn get_consistency(&self, from: u64, to: u64) -> Option<Consistency> {
+        if from > to || to - from > MAX_CONSISTENCY_HEADERS as u64 {
+            return None;
+        }
+        let finalized = self
+            .load(|state| {
+                let round = state.finalized_height_to_round.get(&to)?;
+                state.finalizations.find(round)
+            })?
+            .value;
+
+        // Walk back to the block at height `from`
+        let mut headers = Vec::with_capacity((to - from) as usize);
+        let mut parent = finalized.block.parent;
+        for _ in from..to {
+            let block = self
+                .load(|state| state.blocks_by_digest.find(&parent))?
+                .value;
+            parent = block.parent;
+            headers.push(block);
+        }
+        headers.reverse();
+        Some(Consistency::new(finalized, headers))
+    }
+
+    /// Audit the integrity of stored finalizations, repairing what can be derived from them.
+    ///
+    /// Every finalization was verified when it was stored, so the height index, the digest
+    /// index, and stored encodings are rebuilt from it when they disagree. Finalized blocks that
+    /// conflict with (or do not extend) the finalized block at the previous height cannot be
+    /// repaired and are only reported.
+    pub fn audit(&self) -> AuditReport {
+        // Find inconsistencies (without holding the lock while checking them)
+        let inspection = Inspection::new(&self.state);
+
+        // Repair them (checking each again, as artifacts may have been stored in the meantime)
+        let mut report = AuditReport::default();
+        let mut demotions = Vec::new();
+        if inspection.repairable() {
+            let mut state = self.state.write().unwrap();
+            for round in &inspection.reencode {
+                let Some(stored) = state.finalizations.hot_mut().get_mut(round) else {
+                    continue;
+                };
+                let encoded = stored.value.encode();
+                if stored.encoded != encoded {
+                    stored.encoded = encoded;
+                    report.reencoded.push(round.view().get());
+                }
+            }
+            for (height, round) in &inspection.reindex {
+                let current = state.finalized_height_to_round.get(height).copied();
+                if current.is_some_and(|current| finalized_at(&state, *height, &current)) {
+                    continue;
+                }
+                match round.filter(|round| finalized_at(&state, *height, round)) {
+                    Some(round) => {
+                        state.finalized_height_to_round.insert(*height, round);
+                    }
+                    None if current.is_some() => {
+                        state.finalized_height_to_round.remove(height);
+                    }
+                    None => continue,
+                }
+                report.reindexed.push(*height);
+            }
+            for round in &inspection.restore {
+                let Some(stored) = state.finalizations.hot().get(round) else {
+                    continue;
+                };
+                let block = stored.value.block.clone();
+                let digest = block.digest();
+                let demoted = state.blocks_by_digest.is_cold(&digest);
+                if intact(&digest, state.blocks_by_digest.hot().get(&digest), demoted) {
+                    continue;
+                }
+                let demotion = state.blocks_by_digest.replace(digest, Stored::new(block));
+                if !demotion.is_empty() {
+                    demotions.push(demotion);
+                }
+                state.block_rounds.entry(*round).or_default().push(digest);
+                report.restored.push(round.view().get());
+            }
+        }
+
+        // Overwrite the demoted copies of restored blocks (without holding the lock while
+        // writing them)
+        for mut demotion in demotions {
+            demotion.write();
+            let demoted = self
+                .state
+                .write()
+                .unwrap()
+                .blocks_by_digest
+                .complete(demotion);
+            self.metrics.demoted_artifacts.inc_by(demoted as u64);
+        }
+        report.completed = SystemTime::now().epoch_millis();
+        report.finalizations = inspection.finalizations;
+        report.discontinuities = inspection.discontinuities;
+
+        // Record the outcome
+        let repairs = report.reencoded.len() + report.reindexed.len() + report.restored.len();
+        self.metrics.audits.inc();
+        self.metrics.audit_repairs.inc_by(repairs as u64);
+        self.metrics
+            .audit_discontinuities
+            .set(report.discontinuities.len() as i64);
+        if report.repaired() {
+            info!(
+                reencoded = report.reencoded.len(),
+                reindexed = report.reindexed.len(),
+                restored = report.restored.len(),
+                "repaired stored artifacts"
+            );
+        }
+        if !report.discontinuities.is_empty() {
+            error!(heights = ?report.discontinuities, "finalized blocks are not continuous");
+        }
+        *self.audit.lock().unwrap() = Some(report.clone());
+        report
+    }
+
+    /// Get the report of the last audit (if any).
+    pub fn last_audit(&self) -> Option<AuditReport> {
+        self.audit.lock().unwrap().clone()
+    }
+
+    /// Audit stored artifacts (see [Indexer::audit]) every `interval` until the returned task is
+    /// aborted.
+    pub fn start_audit(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let indexer = self.clone();
+        tokio::spawn(async move {
+            let start = tokio::time::Instant::now() + interval;
+            let mut ticker = tokio::time::interval_at(start, interval);
+            loop {
+                ticker.tick().await;
+                let indexer = indexer.clone();
+                if let Err(e) = tokio::task::spawn_blocking(move || indexer.audit()).await {
+                    error!(?e, "audit failed");
+                }
+            }
+        })
+    }
+
+    /// Send an artifact (prefixed by its [Kind]) to all consensus websocket subscribers.
+    fn broadcast(&self, kind: Kind, view: View, artifact: &(impl Write + EncodeSize)) {
+        let mut data = vec![0u8; u8::SIZE + artifact.encode_size()];
+        data[0] = kind as u8;
+        artifact.write(&mut data[1..].as_mut());
+        let _ = self.consensus_tx.send((view.get(), data));
+    }
+
+    pub fn consensus_subscriber(&self) -> broadcast::Receiver<Broadcast> {
+        self.consensus_tx.subscribe()
+    }
+
+    /// Subscribe to consensus messages, also returning the frames of the stored messages
+    /// matching `subscription` to send first (if it sets `from_view`).
+    ///
+    /// Stored messages are ordered by view (and by [Kind] within a view). The subscriber is
+    /// created while holding the state lock (which uploads hold until they are broadcast), so
+    /// every message is either replayed or received exactly once. At most [MAX_REPLAY_VIEWS]
+    /// views (ending at the latest stored view) are replayed, preceded by a [LAGGED_FRAME] with
+    /// the number of older matching messages that were not. Views restart in every epoch, so
+    /// only messages from the latest epoch are replayed.
+    pub fn consensus_replay(
+        &self,
+        subscription: &Subscription,
+    ) -> (broadcast::Receiver<Broadcast>, Vec<Vec<u8>>) {
+        let state = self.state.read().unwrap();
+        let subscriber = self.consensus_tx.subscribe();
+        let Some(from) = subscription.from_view else {
+            return (subscriber, Vec::new());
+        };
+        let latest = state.latest();
+        let Some(latest) = latest else {
+            return (subscriber, Vec::new());
+        };
+        let epoch = latest.epoch();
+        let end = latest.view().get().saturating_add(1);
+        let start = from.max(end.saturating_sub(MAX_REPLAY_VIEWS));
+
+        // Find matching messages (reading demoted ones once the lock is released)
+        let seeds = (
+            views(&state.seeds, epoch, from, start),
+            replay(&state.seeds, epoch, start, end),
+        );
+        let notarizations = (
+            views(&state.notarizations, epoch, from, start),
+            replay(&state.notarizations, epoch, start, end),
+        );
+        let finalizations = (
+            views(&state.finalizations, epoch, from, start),
+            replay(&state.finalizations, epoch, start, end),
+        );
+        let nullifications = (
+            views(&state.nullifications, epoch, from, start),
+            replay(&state.nullifications, epoch, start, end),
+        );
+        drop(state);
+
+        // Collect matching messages (counting those too old to replay)
+        let mut skipped = 0;
+        let mut messages = Vec::new();
+        let mut collect = |kind: Kind, old: Vec<View>, artifacts: Vec<(View, Bytes)>| {
+            skipped += old
+                .iter()
+                .filter(|view| subscription.matches(kind, view.get()))
+                .count() as u64;
+            for (view, encoded) in artifacts {
+                if subscription.matches(kind, view.get()) {
+                    messages.push((view, kind as u8, encoded));
+                }
+            }
+        };
+        collect(Kind::Seed, seeds.0, encodings(seeds.1));
+        collect(
+            Kind::Notarization,
+            notarizations.0,
+            encodings(notarizations.1),
+        );
+        collect(
+            Kind::Finalization,
+            finalizations.0,
+            encodings(finalizations.1),
+        );
+        collect(
+            Kind::Nullification,
+            nullifications.0,
+            encodings(nullifications.1),
+        );
+        messages.sort_by_key(|(view, kind, _)| (*view, *kind));
+
+        let mut frames = Vec::with_capacity(messages.len() + 1);
+        if skipped > 0 {
+            frames.push(lagged_frame(skipped));
+        }
+        frames.extend(messages.into_iter().map(|(_, kind, encoded)| {
+            let mut data = Vec::with_capacity(u8::SIZE + encoded.len());
+            data.push(kind);
+            data.extend_from_slice(&encoded);
+            data
+        }));
+        (subscriber, frames)
+    }
+
+    /// Receive the next frame matching `subscription` to send to a consensus websocket
+    /// subscriber.
+    ///
+    /// If the subscriber fell behind, the [OverflowPolicy] determines whether a [LAGGED_FRAME]
+    /// frame is returned (and the subscriber continues from the oldest buffered message) or
+    /// `None` is returned (and the subscriber should be disconnected). `None` is also returned
+    /// if the channel is closed.
+    pub async fn next_frame(
+        &self,
+        subscriber: &mut broadcast::Receiver<Broadcast>,
+        subscription: &Subscription,
+    ) -> Option<Vec<u8>> {
+        loop {
+            match subscriber.recv().await {
+                Ok((view, data)) => {
+                    let kind = Kind::from_u8(data[0]).expect("broadcast frames have a kind");
+                    if subscription.matches(kind, view) {
+                        return Some(data);
+                    }
+                }
+                Err(e) => return self.lagged(e),
+            }
+        }
+    }
+
+    /// Handle a subscriber that can no longer receive from the broadcast channel (see
+    /// [Indexer::next_frame]).
+    fn lagged(&self, error: broadcast::error::RecvError) -> Option<Vec<u8>> {
+        match error {
+            broadcast::error::RecvError::Lagged(skipped) => {
+                self.metrics.lagged_subscribers.inc();
+                self.metrics.lagged_messages.inc_by(skipped);
+                match self.overflow_policy {
+                    OverflowPolicy::DropOldest => Some(lagged_frame(skipped)),
+                    OverflowPolicy::Disconnect => {
+                        self.metrics.disconnected_subscribers.inc();
+                        None
+                    }
+                }
+            }
+            broadcast::error::RecvError::Closed => None,
+        }
+    }
+}
+
+/// Find the artifact matching `query` (either [LATEST] or a hex-encoded view in `epoch`).
+fn lookup<T: Read + Encode + Clone>(
+    tier: &Tier<Round, T>,
+    epoch: Epoch,
+    query: &str,
+) -> Option<Found<T>> {
+    if query == LATEST {
+        tier.find(&tier.last_key()?)
+    } else {
+        // Parse as hex-encoded index
+        let raw = from_hex(query)?;
+        let index = u64::decode(raw.as_slice()).ok()?;
+        tier.find(&Round::new(epoch, View::new(index)))
+    }
+}
+
+/// Find the block matching `query`.
+///
+/// Returns the finalized block for [LATEST] or a hex-encoded height, and the block for a
+/// hex-encoded digest.
+fn lookup_block(state: &State, query: &str) -> Option<Result<Found<Block>, Found<Finalized>>> {
+    if query == LATEST {
+        // Return latest finalized block
+        let round = state.finalizations.last_key()?;
+        return state.finalizations.find(&round).map(Err);
+    }
+    let raw = from_hex(query)?;
+    if raw.len() == u64::SIZE {
+        // Parse as index (8 bytes)
+        let index = u64::decode(raw.as_slice()).ok()?;
+        let round = state.finalized_height_to_round.get(&index)?;
+        state.finalizations.find(round).map(Err)
+    } else if raw.len() == Digest::SIZE {
+        let digest = Digest::decode(raw.as_slice()).ok()?;
+        state.blocks_by_digest.find(&digest).map(Ok)
+    } else {
+        None
+    }
+}
+
+/// Find all finalizations of blocks with heights in `[start, end)`.
+fn finalized_range(state: &State, start: u64, end: u64) -> Vec<Found<Finalized>> {
+    state
+        .finalized_height_to_round
+        .range(start..end.max(start))
+        .filter_map(|(_, round)| state.finalizations.find(round))
+        .collect()
+}
+
+/// Encode a [LAGGED_FRAME] reporting `skipped` dropped messages.
+fn lagged_frame(skipped: u64) -> Vec<u8> {
+    let mut data = vec![0u8; u8::SIZE + skipped.encode_size()];
+    data[0] = LAGGED_FRAME;
+    skipped.write(&mut data[1..].as_mut());
+    data
+}
+
+/// Find all artifacts (and their views) in `epoch` with views in `[start, end)`.
+fn replay<T: Read + Encode + Clone>(
+    tier: &Tier<Round, T>,
+    epoch: Epoch,
+    start: u64,
+    end: u64,
+) -> Vec<(View, Found<T>)> {
+    range_rounds(tier, epoch, start, end)
+        .map(|(round, found)| (round.view(), found))
+        .collect()
+}
+
+/// Load the encodings of `found` artifacts (skipping any that cannot be read).
+fn encodings<T: Read>(found: Vec<(View, Found<T>)>) -> Vec<(View, Bytes)> {
+    found
+        .into_iter()
+        .filter_map(|(view, found)| Some((view, found.load()?.encoded)))
+        .collect()
+}
+
+/// Collect the views of all artifacts in `epoch` with views in `[start, end)` (without reading
+/// demoted artifacts).
+fn views<T: Read + Encode + Clone>(
+    tier: &Tier<Round, T>,
+    epoch: Epoch,
+    start: u64,
+    end: u64,
+) -> Vec<View> {
+    let start = Round::new(epoch, View::new(start));
+    let end = Round::new(epoch, View::new(end)).max(start);
+    tier.keys(start..end).map(|round| round.view()).collect()
+}
+
+/// Iterate over all artifacts in `epoch` with views in `[start, end)`.
+fn range<T: Read + Encode + Clone>(
+    tier: &Tier<Round, T>,
+    epoch: Epoch,
+    start: u64,
+    end: u64,
+) -> impl Iterator<Item = Found<T>> + '_ {
+    range_rounds(tier, epoch, start, end).map(|(_, found)| found)
+}
+
+/// Iterate over all artifacts (and their rounds) in `epoch` with views in `[start, end)`.
+fn range_rounds<T: Read + Encode + Clone>(
+    tier: &Tier<Round, T>,
+    epoch: Epoch,
+    start: u64,
+    end: u64,
+) -> impl Iterator<Item = (Round, Found<T>)> + '_ {
+    let start = Round::new(epoch, View::new(start));
+    let end = Round::new(epoch, View::new(end)).max(start);
+    tier.range(start..end)
+}
+
+/// Maximum number of entries to list after `cursor`.
+fn listing_limit(cursor: &Cursor) -> usize {
+    cursor
+        .limit
+        .unwrap_or(DEFAULT_LISTING_LIMIT)
+        .min(MAX_LISTING_LIMIT)
+}
+
+/// Find the artifacts of `tier` to list after `cursor` (in the latest epoch), including one more
+/// than the limit of `cursor` (to tell if there is a next page).
+fn page<T: Read + Encode + Clone>(
+    state: &State,
+    tier: impl Fn(&State) -> &Tier<Round, T>,
+    cursor: Cursor,
+) -> Vec<(Round, Found<T>)> {
+    let epoch = state.epoch();
+    let start = match cursor.after_view {
+        Some(view) => Bound::Excluded(Round::new(epoch, View::new(view))),
+        None => Bound::Included(Round::new(epoch, View::zero())),
+    };
+    let end = Bound::Included(Round::new(epoch, View::new(u64::MAX)));
+    tier(state)
+        .range((start, end))
+        .take(listing_limit(&cursor) + 1)
+        .collect()
+}
+
+/// List (up to the limit of `cursor`) the artifacts of a [page], summarizing the block of each
+/// with `describe`.
+fn list<T: Read>(
+    page: Vec<(Round, Found<T>)>,
+    cursor: Cursor,
+    describe: impl Fn(&T) -> Entry,
+) -> Listing {
+    let limit = listing_limit(&cursor);
+    let mut artifacts = page
+        .into_iter()
+        .filter_map(|(round, found)| Some((round, found.load()?)));
+    let entries: Vec<_> = artifacts
+        .by_ref()
+        .take(limit)
+        .map(|(round, stored)| Entry {
+            view: round.view().get(),
+            ..describe(&stored.value)
+        })
+        .collect();
+    let next = match (entries.last(), artifacts.next()) {
+        (Some(last), Some(_)) => Some(Cursor {
+            after_view: Some(last.view),
+            limit: cursor.limit,
+        }),
+        _ => None,
+    };
+    Listing { entries, next }
+}
+
+/// Encode `artifacts` as a `Vec` (a length prefix followed by each encoding) from their stored
+/// encodings.
+fn encode_range<T>(artifacts: Vec<Stored<T>>) -> Bytes {
+    let size = artifacts.len().encode_size()
+        + artifacts
+            .iter()
+            .map(|stored| stored.encoded.len())
+            .sum::<usize>();
+    let mut buffer = BytesMut::with_capacity(size);
+    artifacts.len().write(&mut buffer);
+    for stored in artifacts {
+        buffer.extend_from_slice(&stored.encoded);
+    }
+    buffer.freeze()
+}
+
+pub struct Api<S: Strategy> {
+    indexer: Arc<Indexer<S>>,
+}
+
+impl<S: Strategy> Api<S> {
+    pub fn new(indexer: Arc<Indexer<S>>) -> Self {
+        Self { indexer }
+    }
+
+    pub fn router(self) -> Router {
+        let limits = self.indexer.upload_limits;
+        Router::new()
+            .route("/health", get(health_check))
+            .route("/metrics", get(metrics_get))
+            .route(
+                "/seed",
+                post(seed_upload).layer(DefaultBodyLimit::max(limits.seed)),
+            )
+            .route("/seed/{query}", get(seed_get))
+            .route(
+                "/notarization",
+                post(notarization_upload).layer(DefaultBodyLimit::max(limits.notarization)),
+            )
+            .route("/notarization/{query}", get(notarization_get))
+            .route("/notarization/{query}/proof", get(notarization_proof_get))
+            .route(
+                "/finalization",
+                post(finalization_upload).layer(DefaultBodyLimit::max(limits.finalization)),
+            )
+            .route("/finalization/{query}", get(finalization_get))
+            .route("/finalization/{query}/proof", get(finalization_proof_get))
+            .route(
+                "/nullification",
+                post(nullification_upload).layer(DefaultBodyLimit::max(limits.nullification)),
+            )
+            .route("/nullification/{query}", get(nullification_get))
+            .route(
+                "/batch",
+                post(batch_upload).layer(DefaultBodyLimit::max(limits.batch())),
+            )
+            .route(
+                "/tx",
+                post(transaction_submit).layer(DefaultBodyLimit::max(MAX_ENCODED_TRANSACTION_SIZE)),
+            )
+            .route("/block/{query}", get(block_get))
+            .route("/receipts/{query}", get(receipts_get))
+            .route("/export/{query}", get(export_get))
+            .route("/consistency", get(consistency_get))
+            .route("/genesis", get(genesis_get))
+            .route("/validators", get(validators_get))
+            .route("/uploaders", get(uploaders_get))
+            .route("/audit", get(audit_get))
+            .route("/seeds", get(seeds_list))
+            .route("/notarizations", get(notarizations_list))
+            .route("/finalizations", get(finalizations_list))
+            .route("/consensus/ws", get(consensus_ws))
+            .route("/consensus/sse", get(consensus_sse))
+            .layer(middleware::map_response(api_version))
+            .layer(CorsLayer::permissive())
+            .layer(TraceLayer::new_for_http().make_span_with(request_span))
+            .with_state(self.indexer)
+    }
+}
+
+/// Create a span for an incoming request, recording any propagated trace context.
+fn request_span(request: &Request) -> Span {
+    let traceparent = request
+        .headers()
+        .get(TRACEPARENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    debug_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        traceparent
+    )
+}
+
+/// Advertise the API version spoken by the indexer.
+async fn api_version(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert(API_VERSION, HeaderValue::from(CURRENT_API_VERSION));
+    response
+}
+
+/// Parse a range query (`start..end`, with hex-encoded bounds).
+///
+/// Returns `None` if the query is not a range and `Some(Err(()))` if it is a malformed range
+/// (or spans more than `max` views).
+fn parse_range(query: &str, max: u64) -> Option<Result<(u64, u64), ()>> {
+    let (start, end) = query.split_once("..")?;
+    let parse = |bound: &str| from_hex(bound).and_then(|raw| u64::decode(raw.as_slice()).ok());
+    let (Some(start), Some(end)) = (parse(start), parse(end)) else {
+        return Some(Err(()));
+    };
+    if start > end || end - start > max {
+        return Some(Err(()));
+    }
+    Some(Ok((start, end)))
+}
+
+/// Parse the upload authentication headers (if present).
+fn upload_auth(headers: &HeaderMap) -> Result<Option<UploadAuth>, ()> {
+    let header = |name| {
+        headers
+            .get(name)
+            .map(|value| value.to_str().map_err(|_| ()))
+            .transpose()
+    };
+    let (Some(signer), Some(timestamp), Some(signature)) = (
+        header(UPLOAD_SIGNER)?,
+        header(UPLOAD_TIMESTAMP)?,
+        header(UPLOAD_SIGNATURE)?,
+    ) else {
+        return Ok(None);
+    };
+    let signer = from_hex(signer).ok_or(())?;
+    let signer = PublicKey::decode(signer.as_ref()).map_err(|_| ())?;
+    let timestamp = timestamp.parse::<u64>().map_err(|_| ())?;
+    let signature = from_hex(signature).ok_or(())?;
+    let signature = ed25519::Signature::decode(signature.as_ref()).map_err(|_| ())?;
+    Ok(Some((signer, timestamp, signature)))
+}
+
+/// Authenticate an upload using its headers.
+fn authenticate<S: Strategy>(
+    indexer: &Indexer<S>,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), &'static str> {
+    let auth = upload_auth(headers).map_err(|_| "malformed upload signature")?;
+    let now = SystemTime::now().epoch_millis();
+    indexer.authenticate_upload(auth, body, now)
+}
+
+/// Identify the uploader of a request (and when the upload was signed, if known).
+///
+/// Uploads are attributed to the signer of their authentication headers or, if unsigned, to
+/// the (unverified) validator in [UPLOAD_VALIDATOR].
+fn uploader(headers: &HeaderMap) -> Option<(PublicKey, Option<u64>)> {
+    if let Ok(Some((signer, timestamp, _))) = upload_auth(headers) {
+        return Some((signer, Some(timestamp)));
+    }
+    let validator = from_hex(headers.get(UPLOAD_VALIDATOR)?.to_str().ok()?)?;
+    let validator = PublicKey::decode(validator.as_ref()).ok()?;
+    Some((validator, None))
+}
+
+/// Get the body of an upload (counting uploads rejected for exceeding their route's limit).
+fn upload_body<S: Strategy>(
+    indexer: &Indexer<S>,
+    body: Result<Bytes, BytesRejection>,
+) -> Result<Bytes, StatusCode> {
+    body.map_err(|rejection| {
+        let status = rejection.status();
+        if status == StatusCode::PAYLOAD_TOO_LARGE {
+            indexer.metrics.oversized_uploads.inc();
+        }
+        status
+    })
+}
+
+/// Record the outcome of an (authenticated) upload of `kind`.
+fn record<S: Strategy>(indexer: &Indexer<S>, headers: &HeaderMap, kind: Kind, status: StatusCode) {
+    let Some((uploader, signed)) = uploader(headers) else {
+        return;
+    };
+    let now = SystemTime::now().epoch_millis();
+    indexer.record_upload(uploader, kind, status.is_success(), signed, now);
+}
+
+/// Decode and submit an (authenticated) upload of `kind`, returning the status of the upload.
+fn submit<S: Strategy>(indexer: &Indexer<S>, kind: Kind, mut body: &[u8]) -> StatusCode {
+    let result = match kind {
+        Kind::Seed => Seed::decode(&mut body).map(|seed| indexer.submit_seed(seed)),
+        Kind::Notarization => Notarized::decode_cfg(&mut body, &indexer.digest_mode)
+            .map(|notarized| indexer.submit_notarization(notarized)),
+        Kind::Finalization => Finalized::decode_cfg(&mut body, &indexer.digest_mode)
+            .map(|finalized| indexer.submit_finalization(finalized)),
+        Kind::Nullification => Nullification::decode(&mut body)
+            .map(|nullification| indexer.submit_nullification(nullification)),
+    };
+    match result {
+        Ok(result) => submitted(result),
+        Err(_) => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// Get the status of a (decoded) upload that was submitted with `result`.
+fn submitted(result: Result<(), Error>) -> StatusCode {
+    match result {
+        Ok(_) => StatusCode::OK,
+        Err(Error::Consistency(_)) => StatusCode::TOO_EARLY,
+        Err(_) => StatusCode::UNAUTHORIZED,
+    }
+}
+
+/// Handle an [Upload] received over the consensus websocket (like the HTTP upload of its kind).
+fn socket_upload<S: Strategy>(indexer: &Indexer<S>, upload: Upload) -> Ack {
+    let Upload {
+        id,
+        kind,
+        auth,
+        body,
+    } = upload;
+    let status = if body.len() > indexer.upload_limits.get(kind) {
+        indexer.metrics.oversized_uploads.inc();
+        StatusCode::PAYLOAD_TOO_LARGE
+    } else {
+        let now = SystemTime::now().epoch_millis();
+        let signed = auth
+            .as_ref()
+            .map(|(signer, timestamp, _)| (signer.clone(), *timestamp));
+        match indexer.authenticate_upload(auth, &body, now) {
+            Ok(()) => {
+                let status = submit(indexer, kind, &body);
+                if let Some((signer, timestamp)) = signed {
+                    indexer.record_upload(signer, kind, status.is_success(), Some(timestamp), now);
+                }
+                status
+            }
+            Err(_) => StatusCode::UNAUTHORIZED,
+        }
+    };
+    Ack {
+        id,
+        status: status.as_u16(),
+    }
+}
+
+/// Handle an [Upload] received in an (authenticated) batch (like the HTTP upload of its kind).
+///
+/// Finalizations in `verified` (decoded from the batch and verified together, see
+/// [verify_batch]) are stored without verifying them again.
+fn batched_upload<S: Strategy>(
+    indexer: &Indexer<S>,
+    headers: &HeaderMap,
+    verified: &mut HashMap<u64, (Finalized, bool)>,
+    upload: Upload,
+) -> Ack {
+    let status = if upload.body.len() > indexer.upload_limits.get(upload.kind) {
+        indexer.metrics.oversized_uploads.inc();
+        StatusCode::PAYLOAD_TOO_LARGE
+    } else {
+        let status = match verified.remove(&upload.id) {
+            Some((finalized, true)) => {
+                indexer.store_finalization(finalized);
+                StatusCode::OK
+            }
+            Some((_, false)) => StatusCode::UNAUTHORIZED,
+            None => submit(indexer, upload.kind, &upload.body),
+        };
+        record(indexer, headers, upload.kind, status);
+        status
+    };
+    Ack {
+        id: upload.id,
+        status: status.as_u16(),
+    }
+}
+
+/// Decode the finalizations in a batch of `uploads` and verify them with aggregated checks
+/// (which is much cheaper than verifying each), returning each (by upload id) with whether it is
+/// valid.
+///
+/// Finalizations that cannot be decoded (or that share an id with another upload) are left to
+/// [batched_upload].
+fn verify_batch<S: Strategy>(
+    indexer: &Indexer<S>,
+    uploads: &[Upload],
+) -> HashMap<u64, (Finalized, bool)> {
+    let mut ids = HashMap::new();
+    for upload in uploads {
+        *ids.entry(upload.id).or_insert(0usize) += 1;
+    }
+    let (ids, finalized): (Vec<u64>, Vec<Finalized>) = uploads
+        .iter()
+        .filter(|upload| {
+            upload.kind == Kind::Finalization
+                && ids[&upload.id] == 1
+                && upload.body.len() <= indexer.upload_limits.get(Kind::Finalization)
+        })
+        .filter_map(|upload| {
+            Finalized::decode_cfg(upload.body.as_ref(), &indexer.digest_mode)
+                .ok()
+                .map(|finalized| (upload.id, finalized))
+        })
+        .unzip();
+    let invalid: HashSet<usize> = indexer
+        .verify_finalizations(&finalized)
+        .into_iter()
+        .collect();
+    ids.into_iter()
+        .zip(finalized)
+        .enumerate()
+        .map(|(index, (id, finalized))| (id, (finalized, !invalid.contains(&index))))
+        .collect()
+}
+
+async fn health_check() -> impl IntoResponse {
+    (StatusCode::OK, "ok")
+}
+
+/// Returns true if the request asks for JSON (`Accept: application/json`) instead of the
+/// binary encoding.
+fn wants_json(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media| {
+            let media = media.split(';').next().unwrap_or_default().trim();
+            media.eq_ignore_ascii_case(JSON_CONTENT_TYPE)
+        })
+}
+
+/// Serve the JSON form (see [alto_types::json]) of the artifact matching `query` (fetched with
+/// `one`) or, if `query` is a range, of all artifacts in the range (fetched with `range`).
+fn json_get<T, J: Serialize>(
+    query: &str,
+    one: impl FnOnce(&str) -> Option<T>,
+    range: impl FnOnce(u64, u64) -> Vec<T>,
+    json: impl Fn(T) -> J,
+) -> Response {
+    match parse_range(query, MAX_RANGE) {
+        Some(Ok((start, end))) => {
+            let artifacts: Vec<J> = range(start, end).into_iter().map(json).collect();
+            Json(artifacts).into_response()
+        }
+        Some(Err(())) => StatusCode::BAD_REQUEST.into_response(),
+        None => match one(query) {
+            Some(artifact) => Json(json(artifact)).into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+    }
+}
+
+async fn metrics_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+) -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(
+            header::CONTENT_TYPE,
+            "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        )],
+        indexer.encode_metrics(),
+    )
+}
+
+async fn seed_upload<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> impl IntoResponse {
+    let body = match upload_body(&indexer, body) {
+        Ok(body) => body,
+        Err(status) => return status,
+    };
+    if authenticate(&indexer, &headers, &body).is_err() {
+        return StatusCode::UNAUTHORIZED;
+    }
+    let status = submit(&indexer, Kind::Seed, &body);
+    record(&indexer, &headers, Kind::Seed, status);
+    status
+}
+
+async fn batch_upload<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> Response {
+    let body = match upload_body(&indexer, body) {
+        Ok(body) => body,
+        Err(status) => return status.into_response(),
+    };
+    if authenticate(&indexer, &headers, &body).is_err() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    // Uploads are authenticated by the batch (and their bodies are checked against the limit
+    // of their kind once it is known)
+    let cfg = ((..=MAX_BATCH_UPLOADS).into(), indexer.upload_limits.max());
+    let Ok(uploads) = Vec::<Upload>::decode_cfg(body.as_ref(), &cfg) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    if uploads.iter().any(|upload| upload.auth.is_some()) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let mut verified = verify_batch(&indexer, &uploads);
+    let acks: Vec<Ack> = uploads
+        .into_iter()
+        .map(|upload| batched_upload(&indexer, &headers, &mut verified, upload))
+        .collect();
+    (StatusCode::OK, acks.encode()).into_response()
+}
+
+async fn seed_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    Path(query): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if wants_json(&headers) {
+        return json_get(
+            &query,
+            |query| indexer.get_seed(query),
+            |start, end| indexer.get_seed_range(start, end),
+            |seed| SeedInfo::from(&seed),
+        );
+    }
+    match parse_range(&query, MAX_RANGE) {
+        Some(Ok((start, end))) => {
+            let seeds = indexer.get_encoded_range(Kind::Seed, start, end);
+            return (StatusCode::OK, seeds).into_response();
+        }
+        Some(Err(())) => return StatusCode::BAD_REQUEST.into_response(),
+        None => {}
+    }
+    match indexer.get_encoded(Kind::Seed, &query) {
+        Some(seed) => (StatusCode::OK, seed).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn notarization_upload<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> impl IntoResponse {
+    let body = match upload_body(&indexer, body) {
+        Ok(body) => body,
+        Err(status) => return status,
+    };
+    if authenticate(&indexer, &headers, &body).is_err() {
+        return StatusCode::UNAUTHORIZED;
+    }
+    let status = submit(&indexer, Kind::Notarization, &body);
+    record(&indexer, &headers, Kind::Notarization, status);
+    status
+}
+
+async fn notarization_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    Path(query): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if wants_json(&headers) {
+        return json_get(
+            &query,
+            |query| indexer.get_notarization(query),
+            |start, end| indexer.get_notarization_range(start, end),
+            |notarized| notarized,
+        );
+    }
+    match parse_range(&query, MAX_RANGE) {
+        Some(Ok((start, end))) => {
+            let notarizations = indexer.get_encoded_range(Kind::Notarization, start, end);
+            return (StatusCode::OK, notarizations).into_response();
+        }
+        Some(Err(())) => return StatusCode::BAD_REQUEST.into_response(),
+        None => {}
+    }
+    match indexer.get_encoded(Kind::Notarization, &query) {
+        Some(notarized) => (StatusCode::OK, notarized).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn notarization_proof_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    Path(query): Path<String>,
+) -> impl IntoResponse {
+    match indexer.get_notarization(&query) {
+        Some(notarized) => (StatusCode::OK, notarized.proof.encode()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn finalization_upload<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> impl IntoResponse {
+    let body = match upload_body(&indexer, body) {
+        Ok(body) => body,
+        Err(status) => return status,
+    };
+    if authenticate(&indexer, &headers, &body).is_err() {
+        return StatusCode::UNAUTHORIZED;
+    }
+    let status = submit(&indexer, Kind::Finalization, &body);
+    record(&indexer, &headers, Kind::Finalization, status);
+    status
+}
+
+async fn finalization_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    Path(query): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if wants_json(&headers) {
+        return json_get(
+            &query,
+            |query| indexer.get_finalization(query),
+            |start, end| indexer.get_finalization_range(start, end),
+            |finalized| finalized,
+        );
+    }
+    match parse_range(&query, MAX_RANGE) {
+        Some(Ok((start, end))) => {
+            let finalizations = indexer.get_encoded_range(Kind::Finalization, start, end);
+            return (StatusCode::OK, finalizations).into_response();
+        }
+        Some(Err(())) => return StatusCode::BAD_REQUEST.into_response(),
+        None => {}
+    }
+    match indexer.get_encoded(Kind::Finalization, &query) {
+        Some(finalized) => (StatusCode::OK, finalized).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn finalization_proof_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    Path(query): Path<String>,
+) -> impl IntoResponse {
+    match indexer.get_finalization(&query) {
+        Some(finalized) => (StatusCode::OK, finalized.proof.encode()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn nullification_upload<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> impl IntoResponse {
+    let body = match upload_body(&indexer, body) {
+        Ok(body) => body,
+        Err(status) => return status,
+    };
+    if authenticate(&indexer, &headers, &body).is_err() {
+        return StatusCode::UNAUTHORIZED;
+    }
+    let status = submit(&indexer, Kind::Nullification, &body);
+    record(&indexer, &headers, Kind::Nullification, status);
+    status
+}
+
+async fn nullification_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    Path(query): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if wants_json(&headers) {
+        return match indexer.get_nullification(&query) {
+            Some(nullification) => Json(NullificationJs {
+                view: nullification.view().get(),
+            })
+            .into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        };
+    }
+    match indexer.get_encoded(Kind::Nullification, &query) {
+        Some(nullification) => (StatusCode::OK, nullification).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Forward a transaction to validators (`202` if any accepted it, `400` if it is not a
+/// transaction, `502` if no validator accepted it, and `503` if no validators are configured).
+async fn transaction_submit<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    body: Result<Bytes, BytesRejection>,
+) -> impl IntoResponse {
+    let body = match body {
+        Ok(body) => body,
+        Err(rejection) => return rejection.status(),
+    };
+    if Transaction::decode(body.as_ref()).is_err() {
+        return StatusCode::BAD_REQUEST;
+    }
+    if indexer.submitters.is_empty() {
+        return StatusCode::SERVICE_UNAVAILABLE;
+    }
+    if indexer.forward_transaction(body).await {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::BAD_GATEWAY
+    }
+}
+
+async fn block_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    Path(query): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if wants_json(&headers) {
+        return block_json(&indexer, &query);
+    }
+
+    // Serve finalized blocks by height range
+    match parse_range(&query, MAX_RANGE) {
+        Some(Ok((start, end))) => {
+            let blocks = indexer.get_block_range_encoded(start, end);
+            return (StatusCode::OK, blocks).into_response();
+        }
+        Some(Err(())) => return StatusCode::BAD_REQUEST.into_response(),
+        None => {}
+    }
+
+    // Resolve a digest prefix (if unique)
+    if is_digest_prefix(&query) {
+        let matches = indexer.find_blocks(&query, MAX_PREFIX_MATCHES);
+        return match matches.as_slice() {
+            [] => StatusCode::NOT_FOUND.into_response(),
+            [digest] => match indexer.get_block_encoded(&hex(digest)) {
+                Some(block) => (StatusCode::OK, block).into_response(),
+                None => StatusCode::NOT_FOUND.into_response(),
+            },
+            _ => (StatusCode::MULTIPLE_CHOICES, matches.encode()).into_response(),
+        };
+    }
+
+    // Blocks requested by digest never change, so let clients (and proxies) cache them forever
+    if query.len() == Digest::SIZE * 2 {
+        let Some(block) = indexer.get_block_encoded(&query) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        let etag = format!("\"{query}\"");
+        let cached = headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                value
+                    .split(',')
+                    .any(|tag| tag.trim() == "*" || tag.trim() == etag)
+            });
+        let headers = [
+            (header::CACHE_CONTROL, IMMUTABLE_CACHE_CONTROL.to_string()),
+            (header::ETAG, etag),
+            (header::VARY, header::ACCEPT.to_string()),
+        ];
+        if cached {
+            return (StatusCode::NOT_MODIFIED, headers).into_response();
+        }
+        return (StatusCode::OK, headers, block).into_response();
+    }
+    match indexer.get_block_encoded(&query) {
+        Some(block) => (StatusCode::OK, block).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Serve the receipts of the block matching `query` (the latest finalized block, a height, or a
+/// digest, like [block_get]).
+async fn receipts_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    Path(query): Path<String>,
+) -> impl IntoResponse {
+    match indexer.get_receipts(&query) {
+        Some(receipts) => (StatusCode::OK, receipts).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Serve the JSON form of the block (or finalized block) matching `query` (see [block_get]).
+fn block_json<S: Strategy>(indexer: &Indexer<S>, query: &str) -> Response {
+    let json = |payload: Payload| payload;
+    if !is_digest_prefix(query) {
+        return json_get(
+            query,
+            |query| indexer.get_block(query),
+            |start, end| {
+                indexer
+                    .get_block_range(start, end)
+                    .into_iter()
+                    .map(|finalized| Payload::Finalized(Box::new(finalized)))
+                    .collect()
+            },
+            json,
+        );
+    }
+
+    // Resolve a digest prefix (if unique)
+    let matches = indexer.find_blocks(query, MAX_PREFIX_MATCHES);
+    match matches.as_slice() {
+        [] => StatusCode::NOT_FOUND.into_response(),
+        [digest] => match indexer.get_block(&hex(digest)) {
+            Some(payload) => Json(payload).into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+        _ => {
+            let digests: Vec<String> = matches.iter().map(|digest| hex(digest)).collect();
+            (StatusCode::MULTIPLE_CHOICES, Json(digests)).into_response()
+        }
+    }
+}
+
+/// Query parameters for [consistency_get].
+#[derive(Deserialize)]
+struct ConsistencyParams {
+    from: u64,
+    to: u64,
+}
+
+async fn export_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    Path(query): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let Some(Ok((start, end))) = parse_range(&query, MAX_EXPORT) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let export = indexer.export(start, end);
+    let etag = export.etag.clone();
+    let total = export.len;
+
+    // Serve the requested bytes (if the export has not changed since the tag the client holds)
+    let unchanged = headers
+        .get(header::IF_RANGE)
+        .is_none_or(|tag| tag.as_bytes() == etag.as_bytes());
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|range| range.to_str().ok())
+        .filter(|_| unchanged)
+        .and_then(|range| byte_range(range, total));
+    let (status, first, last) = match range {
+        None => (StatusCode::OK, 0, total),
+        Some(Ok((first, last))) => (StatusCode::PARTIAL_CONTENT, first, last),
+        Some(Err(())) => {
+            let content_range = format!("bytes */{total}");
+            return (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, content_range)],
+            )
+                .into_response();
+        }
+    };
+    let chunks = slice_chunks(export.chunks.clone(), first, last);
+    let body = Body::from_stream(stream::iter(chunks.into_iter().map(Ok::<_, Infallible>)));
+    let mut response = (
+        status,
+        [
+            (header::ACCEPT_RANGES, "bytes".to_string()),
+            (header::ETAG, etag),
+        ],
+        body,
+    )
+        .into_response();
+    if status == StatusCode::PARTIAL_CONTENT {
+        let content_range = format!("bytes {first}-{}/{total}", last - 1);
+        response.headers_mut().insert(
+            header::CONTENT_RANGE,
+            HeaderValue::from_str(&content_range).unwrap(),
+        );
+    }
+    response
+}
+
+/// Parse a `Range` header (for a body of `total` bytes) into the bytes `[first, last)`.
+///
+/// Returns `None` if the header should be ignored (it is malformed or requests multiple
+/// ranges) and `Some(Err(()))` if the range cannot be satisfied.
+fn byte_range(range: &str, total: u64) -> Option<Result<(u64, u64), ()>> {
+    let (first, last) = range.strip_prefix("bytes=")?.trim().split_once('-')?;
+    let (first, last) = match (first, last) {
+        ("", suffix) => {
+            let suffix = suffix.parse::<u64>().ok()?;
+            (total.saturating_sub(suffix), total)
+        }
+        (first, "") => (first.parse::<u64>().ok()?, total),
+        (first, last) => {
+            let (first, last) = (first.parse::<u64>().ok()?, last.parse::<u64>().ok()?);
+            if first > last {
+                return None;
+            }
+            (first, last.saturating_add(1).min(total))
+        }
+    };
+    if first >= last {
+        return Some(Err(()));
+    }
+    Some(Ok((first, last)))
+}
+
+/// Select the bytes `[first, last)` of the concatenation of `chunks` (without copying).
+fn slice_chunks(chunks: Vec<Bytes>, first: u64, last: u64) -> Vec<Bytes> {
+    let mut offset = 0;
+    let mut sliced = Vec::new();
+    for chunk in chunks {
+        let (start, end) = (offset, offset + chunk.len() as u64);
+        offset = end;
+        if end <= first || start >= last {
+            continue;
+        }
+        let from = first.saturating_sub(start) as usize;
+        let to = (last.min(end) - start) as usize;
+        sliced.push(chunk.slice(from..to));
+    }
+    sliced
+}
+
+async fn consistency_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    AxumQuery(params): AxumQuery<ConsistencyParams>,
+) -> impl IntoResponse {
+    if params.from > params.to || params.to - params.from > MAX_CONSISTENCY_HEADERS as u64 {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    match indexer.get_consistency(params.from, params.to) {
+        Some(consistency) => (StatusCode::OK, consistency.encode()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn genesis_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+) -> impl IntoResponse {
+    match &indexer.genesis {
+        Some(genesis) => (StatusCode::OK, genesis.encoded.clone()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn validators_get<S: Strategy>(AxumState(indexer): AxumState<Arc<Indexer<S>>>) -> Response {
+    match &indexer.validators {
+        Some(validators) => Json(validators.as_ref()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn uploaders_get<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+) -> impl IntoResponse {
+    Json(indexer.upload_stats())
+}
+
+async fn audit_get<S: Strategy>(AxumState(indexer): AxumState<Arc<Indexer<S>>>) -> Response {
+    match indexer.last_audit() {
+        Some(report) => Json(report).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn seeds_list<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    AxumQuery(cursor): AxumQuery<Cursor>,
+) -> impl IntoResponse {
+    Json(indexer.list_seeds(cursor))
+}
+
+async fn notarizations_list<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    AxumQuery(cursor): AxumQuery<Cursor>,
+) -> impl IntoResponse {
+    Json(indexer.list_notarizations(cursor))
+}
+
+async fn finalizations_list<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    AxumQuery(cursor): AxumQuery<Cursor>,
+) -> impl IntoResponse {
+    Json(indexer.list_finalizations(cursor))
+}
+
+async fn consensus_ws<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    RawQuery(query): RawQuery,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let Ok(subscription) = query.unwrap_or_default().parse::<Subscription>() else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    ws.on_upgrade(move |socket| handle_consensus_ws(socket, indexer, subscription))
+}
+
+/// Stream consensus messages matching `subscription` to the subscriber (and acknowledge any
+/// [Upload]s it sends).
+///
+/// Each [Upload] is handled like the HTTP upload of its kind and answered with an [ACK_FRAME]
+/// (followed by an [Ack]) on the same socket, so uploaders can hold a single connection open.
+async fn handle_consensus_ws<S: Strategy>(
+    socket: axum::extract::ws::WebSocket,
+    indexer: Arc<Indexer<S>>,
+    subscription: Subscription,
+) {
+    let (mut sender, mut receiver) = socket.split();
+    let (mut consensus, replay) = indexer.consensus_replay(&subscription);
+    let max_upload = indexer.upload_limits.max();
+
+    // Replay stored messages (if requested) before streaming new ones
+    for data in replay {
+        if sender
+            .send(axum::extract::ws::Message::Binary(data.into()))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+
+    let close = loop {
+        let data = tokio::select! {
+            data = indexer.next_frame(&mut consensus, &subscription) => match data {
+                Some(data) => data,
+                None => {
+                    // Tell the subscriber why it was disconnected
+                    break CloseFrame {
+                        code: close_code::AGAIN,
+                        reason: "lagged".into(),
+                    };
+                }
+            },
+            message = receiver.next() => match message {
+                Some(Ok(axum::extract::ws::Message::Binary(data))) => {
+                    let Ok(upload) = Upload::decode_cfg(data.as_ref(), &max_upload) else {
+                        break CloseFrame {
+                            code: close_code::INVALID,
+                            reason: "malformed upload".into(),
+                        };
+                    };
+                    let ack = socket_upload(&indexer, upload);
+                    let mut data = vec![ACK_FRAME];
+                    ack.write(&mut data);
+                    data
+                }
+                Some(Ok(axum::extract::ws::Message::Text(_))) => {
+                    break CloseFrame {
+                        code: close_code::UNSUPPORTED,
+                        reason: "text frames are not supported".into(),
+                    };
+                }
+                Some(Ok(axum::extract::ws::Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue, // Ignore control messages.
+            },
+        };
+        if sender
+            .send(axum::extract::ws::Message::Binary(data.into()))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    };
+    let _ = sender
+        .send(axum::extract::ws::Message::Close(Some(close)))
+        .await;
+}
+
+/// Stream consensus messages matching the subscription in the query string as server-sent
+/// events (for subscribers that cannot open a websocket).
+///
+/// Each event is named after the kind of its message (or `lagged`) and carries the JSON form of
+/// the message (an [EventJs]). Messages are verified before they are stored, so they are only
+/// decoded here. Subscribers disconnected under [OverflowPolicy::Disconnect] see the stream end.
+async fn consensus_sse<S: Strategy>(
+    AxumState(indexer): AxumState<Arc<Indexer<S>>>,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let Ok(subscription) = query.unwrap_or_default().parse::<Subscription>() else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let (consensus, replay) = indexer.consensus_replay(&subscription);
+    let digest_mode = indexer.digest_mode;
+    let live = stream::unfold(
+        (indexer, consensus, subscription),
+        |(indexer, mut consensus, subscription)| async move {
+            let frame = indexer.next_frame(&mut consensus, &subscription).await?;
+            Some((frame, (indexer, consensus, subscription)))
+        },
+    );
+    let events = stream::iter(replay).chain(live).filter_map(move |frame| {
+        future::ready(sse_event(&digest_mode, &frame).map(Ok::<_, Infallible>))
+    });
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Convert a frame broadcast to consensus subscribers into a server-sent event.
+fn sse_event(digest_mode: &DigestMode, frame: &[u8]) -> Option<Event> {
+    let (tag, data) = frame.split_first()?;
+    let (name, event) = if *tag == LAGGED_FRAME {
+        ("lagged", EventJs::Lagged(u64::decode(data).ok()?))
+    } else {
+        let kind = Kind::from_u8(*tag)?;
+        let event = match kind {
+            Kind::Seed => EventJs::Seed((&Seed::decode(data).ok()?).into()),
+            Kind::Notarization => {
+                EventJs::Notarization(Notarized::decode_cfg(data, digest_mode).ok()?)
+            }
+            Kind::Finalization => {
+                EventJs::Finalization(Finalized::decode_cfg(data, digest_mode).ok()?)
+            }
+            Kind::Nullification => EventJs::Nullification(NullificationJs {
+                view: Nullification::decode(data).ok()?.view().get(),
+            }),
+        };
+        (kind.name(), event)
+    };
+    Event::default().event(name).json_data(event).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_client::{Client, ClientBuilder, Error, IndexQuery, Query, MIN_PREFIX};
+    use alto_types::{
+        fixtures::{self, Fixture},
+        Identity, Seedable, INITIAL_RULES,
+    };
+    use commonware_consensus::{simplex::types::Proposal, types::Height, Viewable};
+    use commonware_cryptography::{ed25519, Digestible, Hasher, Sha256, Signer};
+    use commonware_parallel::Sequential;
+    use commonware_utils::NZUsize;
+    use futures::{StreamExt, TryStreamExt};
+    use opentelemetry::trace::{TraceContextExt, TracerProvider};
+    use rcgen::{generate_simple_self_signed, CertifiedKey, KeyPair};
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+    use std::{
+        net::SocketAddr,
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    };
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+    use tower::ServiceExt;
+    use tracing::Instrument;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Test context containing common setup for indexer tests.
+    struct TestContext {
+        schemes: Vec<Scheme>,
+        client: Client<Sequential>,
+    }
+
+    impl TestContext {
+        /// Create a new test context with a running server and client.
+        async fn new() -> Self {
+            let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+            let identity = fixtures::identity(&schemes);
+
+            let (addr, _) = start_server(router(schemes[0].clone())).await;
+            let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+            wait_for_ready(&client).await;
+
+            Self { schemes, client }
+        }
+
+        /// Create a test block with standard parameters.
+        fn test_block(&self) -> Block {
+            Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000)
+        }
+
+        /// Create a proposal for the given block at view 1.
+        fn proposal(&self, block: &Block) -> Proposal<Digest> {
+            fixtures::proposal(1, block.digest())
+        }
+
+        /// Create a seed by first creating a notarization.
+        fn seed(&self) -> Seed {
+            let block = self.test_block();
+            let proposal = self.proposal(&block);
+            fixtures::notarization(&self.schemes, proposal).seed()
+        }
+
+        /// Create a notarized block.
+        fn notarized(&self) -> Notarized {
+            let block = self.test_block();
+            let proposal = self.proposal(&block);
+            Notarized::new(fixtures::notarization(&self.schemes, proposal), block)
+        }
+
+        /// Create a finalized block.
+        fn finalized(&self) -> Finalized {
+            let block = self.test_block();
+            let proposal = self.proposal(&block);
+            Finalized::new(fixtures::finalization(&self.schemes, proposal), block)
+        }
+    }
+
+    /// Route requests to a new indexer (with default settings) using `scheme`.
+    fn router(scheme: Scheme) -> Router {
+        Api::new(Arc::new(Indexer::new(scheme, Sequential))).router()
+    }
+
+    /// Serve `app` on a local port.
+    async fn start_server(app: Router) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        (addr, handle)
+    }
+
+    async fn wait_for_ready(client: &Client<Sequential>) {
+        loop {
+            if client.health().await.is_ok() {
+                return;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    fn fixture(seed: u64) -> (Vec<Scheme>, Identity) {
+        let Fixture { schemes, .. } = fixtures::seeded(seed, 4);
+        let identity = fixtures::identity(&schemes);
+        (schemes, identity)
+    }
+
+    #[tokio::test]
+    async fn test_seed_operations() {
+        let ctx = TestContext::new().await;
+        let seed = ctx.seed();
+
+        ctx.client.seed_upload(seed.clone()).await.unwrap();
+
+        let retrieved = ctx.client.seed_get(IndexQuery::Latest).await.unwrap();
+        assert_eq!(retrieved.view(), seed.view());
+
+        let retrieved = ctx.client.seed_get(IndexQuery::Index(1)).await.unwrap();
+        assert_eq!(retrieved.view().get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_notarization_operations() {
+        let ctx = TestContext::new().await;
+        let notarized = ctx.notarized();
+
+        ctx.client.notarized_upload(notarized).await.unwrap();
+
+        let retrieved = ctx.client.notarized_get(IndexQuery::Latest).await.unwrap();
+        assert_eq!(retrieved.proof.view().get(), 1);
+
+        let retrieved = ctx
+            .client
+            .notarized_get(IndexQuery::Index(1))
+            .await
+            .unwrap();
+        assert_eq!(retrieved.proof.view().get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_finalization_operations() {
+        let ctx = TestContext::new().await;
+        let finalized = ctx.finalized();
+
+        ctx.client.finalized_upload(finalized).await.unwrap();
+
+        let retrieved = ctx.client.finalized_get(IndexQuery::Latest).await.unwrap();
+        assert_eq!(retrieved.proof.view().get(), 1);
+
+        let retrieved = ctx
+            .client
+            .finalized_get(IndexQuery::Index(1))
+            .await
+            .unwrap();
+        assert_eq!(retrieved.proof.view().get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_client() {
+        let ctx = TestContext::new().await;
+        let finalized = ctx.finalized();
+        ctx.client
+            .finalized_upload(finalized.clone())
+            .await
+            .unwrap();
+
+        // Query the indexer from a thread without a runtime
+        let client = ctx.client.clone();
+        let expected = finalized.clone();
+        tokio::task::spawn_blocking(move || {
+            let client = alto_client::blocking::Client::new(client);
+            client.health().unwrap();
+            assert_eq!(client.tip().unwrap(), expected);
+            assert_eq!(client.finalized(IndexQuery::Index(1)).unwrap(), expected);
+            assert_eq!(
+                client.seed(IndexQuery::Latest).unwrap(),
+                expected.proof.seed()
+            );
+            assert_eq!(client.blocks(1, 2).unwrap(), vec![expected.clone()]);
+            assert_eq!(client.verify(&expected.encode()).unwrap(), expected);
+            assert!(matches!(
+                client.block(Query::Index(2)),
+                Err(Error::Failed(StatusCode::NOT_FOUND))
+            ));
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_seed_recovery() {
+        let ctx = TestContext::new().await;
+        let finalized = ctx.finalized();
+        let seed = finalized.proof.seed();
+
+        // The seed is recovered from the finalization (even if never uploaded)
+        ctx.client.finalized_upload(finalized).await.unwrap();
+        let retrieved = ctx.client.seed_get(IndexQuery::Index(1)).await.unwrap();
+        assert_eq!(retrieved, seed);
+        let retrieved = ctx.client.seed_get(IndexQuery::Latest).await.unwrap();
+        assert_eq!(retrieved, seed);
+
+        // Uploading the seed afterwards is a no-op
+        ctx.client.seed_upload(seed.clone()).await.unwrap();
+        let retrieved = ctx.client.seed_get(IndexQuery::Index(1)).await.unwrap();
+        assert_eq!(retrieved, seed);
+    }
+
+    #[tokio::test]
+    async fn test_proof_retrieval() {
+        let ctx = TestContext::new().await;
+        ctx.client.notarized_upload(ctx.notarized()).await.unwrap();
+        ctx.client.finalized_upload(ctx.finalized()).await.unwrap();
+
+        // Fetch proofs without blocks
+        let notarization = ctx
+            .client
+            .notarization_get(IndexQuery::Latest)
+            .await
+            .unwrap();
+        assert_eq!(notarization.view().get(), 1);
+        let finalization = ctx
+            .client
+            .finalization_get(IndexQuery::Index(1))
+            .await
+            .unwrap();
+        assert_eq!(finalization.view().get(), 1);
+
+        // Fetch the block body independently
+        let payload = ctx
+            .client
+            .block_get(Query::Digest(finalization.proposal.payload))
+            .await
+            .unwrap();
+        match payload {
+            Payload::Block(b) => {
+                assert_eq!(b.digest(), ctx.test_block().digest());
+            }
+            _ => panic!("Expected block"),
+        }
+
+        // Missing proofs are not found
+        let result = ctx.client.finalization_get(IndexQuery::Index(2)).await;
+        assert!(result.is_err());
+    }
+
+    /// Upload a seed, notarization, and finalization for each of `views`.
+    async fn upload_views(client: &Client<Sequential>, schemes: &[Scheme], views: &[u64]) {
+        for view in views {
+            let block = Block::new(Sha256::hash(b"genesis"), Height::new(*view), 1000);
+            let proposal = fixtures::proposal(*view, block.digest());
+            let notarization = fixtures::notarization(schemes, proposal.clone());
+            let finalization = fixtures::finalization(schemes, proposal);
+            client.seed_upload(notarization.seed()).await.unwrap();
+            client
+                .notarized_upload(Notarized::new(notarization, block.clone()))
+                .await
+                .unwrap();
+            client
+                .finalized_upload(Finalized::new(finalization, block))
+                .await
+                .unwrap();
+        }
+    }
+
+    /// Fetch all artifacts in `[start, end)` and return their views (or, for blocks, heights).
+    async fn range_views(client: &Client<Sequential>, start: u64, end: u64) -> Vec<Vec<u64>> {
+        let seeds = client.seed_get_range(start, end).await.unwrap();
+        let notarizations = client.notarized_get_range(start, end).await.unwrap();
+        let finalizations = client.finalized_get_range(start, end).await.unwrap();
+        let streamed: Vec<Finalized> = client
+            .finalized_stream_range(start, end)
+            .try_collect()
+            .await
+            .unwrap();
+        let blocks = client.block_get_range(start, end).await.unwrap();
+        let iterated: Vec<Finalized> = client.blocks(start..end).try_collect().await.unwrap();
+        vec![
+            seeds.iter().map(|s| s.view().get()).collect(),
+            notarizations.iter().map(|n| n.proof.view().get()).collect(),
+            finalizations.iter().map(|f| f.proof.view().get()).collect(),
+            streamed.iter().map(|f| f.proof.view().get()).collect(),
+            blocks.iter().map(|f| f.block.height.get()).collect(),
+            iterated.iter().map(|f| f.block.height.get()).collect(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_range_operations() {
+        let ctx = TestContext::new().await;
+        let views = [1, 2, 4, MAX_RANGE + 10];
+        upload_views(&ctx.client, &ctx.schemes, &views).await;
+
+        // Missing views are skipped
+        for fetched in range_views(&ctx.client, 1, 5).await {
+            assert_eq!(fetched, vec![1, 2, 4]);
+        }
+        for fetched in range_views(&ctx.client, 3, 4).await {
+            assert!(fetched.is_empty());
+        }
+
+        // Ranges larger than the maximum are split
+        for fetched in range_views(&ctx.client, 0, MAX_RANGE * 2).await {
+            assert_eq!(fetched, views.to_vec());
+        }
+
+        // Invalid ranges are rejected
+        let result = ctx.client.seed_get_range(5, 1).await;
+        assert!(matches!(result, Err(Error::InvalidQuery)));
+        let result = ctx.client.seed_get(IndexQuery::Range(1, 5)).await;
+        assert!(matches!(result, Err(Error::InvalidQuery)));
+        let result: Result<Vec<_>, _> = ctx.client.finalized_stream_range(5, 1).try_collect().await;
+        assert!(matches!(result, Err(Error::InvalidQuery)));
+        #[allow(clippy::reversed_empty_ranges)]
+        let result: Result<Vec<_>, _> = ctx.client.blocks(5..1).try_collect().await;
+        assert!(matches!(result, Err(Error::InvalidQuery)));
+    }
+
+    #[tokio::test]
+    async fn test_download_chain() {
+        let ctx = TestContext::new().await;
+
+        // Finalize heights 1-3 and 5-7 (height 7 does not extend height 6)
+        let mut parent = Sha256::hash(b"genesis");
+        for height in [1, 2, 3, 5, 6, 7] {
+            if height == 7 {
+                parent = Sha256::hash(b"fork");
+            }
+            let block = Block::new(parent, Height::new(height), 1000);
+            parent = block.digest();
+            let proposal = fixtures::proposal(height, block.digest());
+            let finalized = Finalized::new(fixtures::finalization(&ctx.schemes, proposal), block);
+            ctx.client.finalized_upload(finalized).await.unwrap();
+        }
+        async fn download(
+            client: &Client<Sequential>,
+            start: u64,
+            end: u64,
+        ) -> (Vec<u64>, Option<Error>) {
+            let items: Vec<_> = client.download_chain(start, end).collect().await;
+            let mut heights = Vec::new();
+            for item in items {
+                match item {
+                    Ok(finalized) => heights.push(finalized.block.height.get()),
+                    Err(err) => return (heights, Some(err)),
+                }
+            }
+            (heights, None)
+        }
+
+        // Linked blocks are downloaded in order (the first block's parent is not checked)
+        let (heights, err) = download(&ctx.client, 1, 4).await;
+        assert_eq!(heights, vec![1, 2, 3]);
+        assert!(err.is_none());
+        let (heights, err) = download(&ctx.client, 5, 7).await;
+        assert_eq!(heights, vec![5, 6]);
+        assert!(err.is_none());
+
+        // Missing heights are not skipped
+        let (heights, err) = download(&ctx.client, 1, 6).await;
+        assert_eq!(heights, vec![1, 2, 3]);
+        assert!(matches!(err, Some(Error::MissingBlock(4))));
+        let (heights, err) = download(&ctx.client, 7, 9).await;
+        assert_eq!(heights, vec![7]);
+        assert!(matches!(err, Some(Error::MissingBlock(8))));
+
+        // Blocks that do not extend their parent end the chain
+        let (heights, err) = download(&ctx.client, 5, 8).await;
+        assert_eq!(heights, vec![5, 6]);
+        assert!(matches!(err, Some(Error::BrokenChain(7))));
+
+        // Invalid ranges are rejected
+        let (heights, err) = download(&ctx.client, 5, 1).await;
+        assert!(heights.is_empty());
+        assert!(matches!(err, Some(Error::InvalidQuery)));
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let ctx = TestContext::new().await;
+        let views = [1, 2, 4, MAX_RANGE + 10];
+        upload_views(&ctx.client, &ctx.schemes, &views).await;
+
+        // Exports are not limited to a single range
+        let exported: Vec<Finalized> = ctx
+            .client
+            .finalized_export(0, MAX_RANGE * 2)
+            .try_collect()
+            .await
+            .unwrap();
+        let exported: Vec<_> = exported.iter().map(|f| f.proof.view().get()).collect();
+        assert_eq!(exported, views.to_vec());
+        let exported: Vec<Finalized> = ctx
+            .client
+            .finalized_export(3, 4)
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(exported.is_empty());
+
+        // Invalid exports are rejected
+        let result: Result<Vec<_>, _> = ctx.client.finalized_export(5, 1).try_collect().await;
+        assert!(matches!(result, Err(Error::InvalidQuery)));
+        let result: Result<Vec<_>, _> = ctx
+            .client
+            .finalized_export(0, MAX_EXPORT + 1)
+            .try_collect()
+            .await;
+        assert!(matches!(result, Err(Error::InvalidQuery)));
+    }
+
+    #[tokio::test]
+    async fn test_export_ranges() {
+        let (schemes, identity) = fixture(0);
+        let (addr, _) = start_server(router(schemes[0].clone())).await;
+        let uri = format!("http://{addr}");
+        let client = Client::new(&uri, identity, Sequential);
+        wait_for_ready(&client).await;
+        upload_views(&client, &schemes, &[1, 2, 4]).await;
+        let path = format!(
+            "{uri}/export/{}",
+            IndexQuery::Range(0, MAX_RANGE).serialize()
+        );
+        let get = |range: Option<&str>, if_range: Option<&str>| {
+            let mut request = reqwest::Client::new().get(&path);
+            if let Some(range) = range {
+                request = request.header(header::RANGE, range);
+            }
+            if let Some(if_range) = if_range {
+                request = request.header(header::IF_RANGE, if_range);
+            }
+            request.send()
+        };
+
+        // Full exports advertise support for ranges
+        let response = get(None, None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ACCEPT_RANGES], "bytes");
+        let etag = response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let full = response.bytes().await.unwrap();
+        let total = full.len();
+
+        // Ranges are served from the requested byte
+        let response = get(Some("bytes=10-"), Some(&etag)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers()[header::CONTENT_RANGE],
+            format!("bytes 10-{}/{total}", total - 1).as_str()
+        );
+        assert_eq!(response.bytes().await.unwrap(), full.slice(10..));
+        let response = get(Some("bytes=5-20"), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.bytes().await.unwrap(), full.slice(5..21));
+        let response = get(Some("bytes=-7"), None).await.unwrap();
+        assert_eq!(response.bytes().await.unwrap(), full.slice(total - 7..));
+
+        // Changed exports are served in full
+        upload_views(&client, &schemes, &[3]).await;
+        let response = get(Some("bytes=10-"), Some(&etag)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers()[header::ETAG], etag.as_str());
+
+        // Unsatisfiable ranges are rejected
+        let response = get(Some("bytes=100000-"), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    }
+
+    #[test]
+    fn test_export_cache() {
+        let (schemes, _) = fixture(0);
+        let indexer = Indexer::new(schemes[0].clone(), Sequential);
+        let finalize = |view: u64| {
+            let block = Block::new(Sha256::hash(b"genesis"), Height::new(view), view);
+            let proposal = fixtures::proposal(view, block.digest());
+            let finalization = fixtures::finalization(&schemes, proposal);
+            indexer
+                .submit_finalization(Finalized::new(finalization, block))
+                .unwrap();
+        };
+        finalize(1);
+        finalize(2);
+
+        // Repeated exports are served from the cache
+        let export = indexer.export(0, 10);
+        assert_eq!(
+            export.len,
+            export.chunks.iter().map(|c| c.len() as u64).sum::<u64>()
+        );
+        assert!(Arc::ptr_eq(&export, &indexer.export(0, 10)));
+
+        // Finalizations outside the export do not invalidate it
+        finalize(20);
+        assert!(Arc::ptr_eq(&export, &indexer.export(0, 10)));
+
+        // Finalizations inside the export do
+        finalize(3);
+        let updated = indexer.export(0, 10);
+        assert!(!Arc::ptr_eq(&export, &updated));
+        assert_ne!(export.etag, updated.etag);
+        assert_eq!(updated.chunks.len(), 4);
+
+        // So do different bounds
+        let other = indexer.export(0, 3);
+        assert_eq!(other.chunks.len(), 3);
+        assert!(!Arc::ptr_eq(&updated, &indexer.export(0, 10)));
+    }
+
+    /// Start a proxy to `upstream` that cuts the first connection after forwarding `limit` bytes
+    /// of its response.
+    async fn flaky_proxy(upstream: SocketAddr, limit: u64) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut first = true;
+            loop {
+                let (mut inbound, _) = listener.accept().await.unwrap();
+                let mut outbound = tokio::net::TcpStream::connect(upstream).await.unwrap();
+                if !std::mem::take(&mut first) {
+                    tokio::spawn(async move {
+                        let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+                    });
+                    continue;
+                }
+                let (mut inbound_read, mut inbound_write) = inbound.into_split();
+                let (outbound_read, mut outbound_write) = outbound.into_split();
+                tokio::spawn(async move {
+                    let _ = tokio::io::copy(&mut inbound_read, &mut outbound_write).await;
+                });
+                let mut limited = tokio::io::AsyncReadExt::take(outbound_read, limit);
+                let _ = tokio::io::copy(&mut limited, &mut inbound_write).await;
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_export_resume() {
+        let (schemes, identity) = fixture(0);
+        let (addr, _) = start_server(router(schemes[0].clone())).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+        let views: Vec<u64> = (1..=20).collect();
+        upload_views(&client, &schemes, &views).await;
+
+        // Interrupted exports are resumed (without restarting)
+        let proxy = flaky_proxy(addr, 1024).await;
+        let client = Client::new(&format!("http://{proxy}"), identity, Sequential);
+        let exported: Vec<Finalized> = client
+            .finalized_export(0, MAX_RANGE)
+            .try_collect()
+            .await
+            .unwrap();
+        let exported: Vec<_> = exported.iter().map(|f| f.proof.view().get()).collect();
+        assert_eq!(exported, views);
+    }
+
+    #[tokio::test]
+    async fn test_listings() {
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let identity = fixtures::identity(&schemes);
+        let (addr, _) = start_server(router(schemes[0].clone())).await;
+        let uri = format!("http://{addr}");
+        let client = Client::new(&uri, identity, Sequential);
+        wait_for_ready(&client).await;
+        let views = [1, 2, 4, 7, 9];
+        upload_views(&client, &schemes, &views).await;
+
+        // Page through finalizations
+        async fn list(uri: &str, path: &str) -> Listing {
+            reqwest::get(format!("{uri}{path}"))
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap()
+        }
+        let mut listed = Vec::new();
+        let mut path = "/finalizations?limit=2".to_string();
+        loop {
+            let listing = list(&uri, &path).await;
+            assert!(listing.entries.len() <= 2);
+            listed.extend(listing.entries);
+            let Some(next) = listing.next else {
+                break;
+            };
+            path = format!(
+                "/finalizations?after_view={}&limit={}",
+                next.after_view.unwrap(),
+                next.limit.unwrap()
+            );
+        }
+        assert_eq!(
+            listed.iter().map(|entry| entry.view).collect::<Vec<_>>(),
+            views.to_vec()
+        );
+        for entry in &listed {
+            let block = Block::new(Sha256::hash(b"genesis"), Height::new(entry.view), 1000);
+            assert_eq!(entry.height, Some(entry.view));
+            assert_eq!(entry.digest, Some(hex(&block.digest())));
+        }
+
+        // Listings start after the cursor (and seeds are summarized instead of a block)
+        let listing = list(&uri, "/seeds?after_view=4").await;
+        assert_eq!(
+            listing.entries,
+            [7, 9]
+                .map(|view| Entry {
+                    view,
+                    seed: Some(fixtures::seed(&schemes, view).info()),
+                    ..Default::default()
+                })
+                .to_vec()
+        );
+        assert!(listing.next.is_none());
+        let listing = list(&uri, "/notarizations").await;
+        assert_eq!(listing.entries.len(), views.len());
+        assert!(listing.next.is_none());
+        let listing = list(&uri, "/notarizations?after_view=9").await;
+        assert!(listing.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_range_fallback() {
+        // Emulate an indexer without range support (no version header, ranges not found)
+        async fn legacy(request: Request, next: middleware::Next) -> Response {
+            if request.uri().path().contains("..") {
+                return StatusCode::NOT_FOUND.into_response();
+            }
+            let mut response = next.run(request).await;
+            response.headers_mut().remove(API_VERSION);
+            response
+        }
+        let (schemes, identity) = fixture(0);
+        let indexer = Arc::new(Indexer::new(schemes[0].clone(), Sequential));
+        let (addr, handle) = start_server(
+            Api::new(indexer)
+                .router()
+                .layer(middleware::from_fn(legacy)),
+        )
+        .await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+        upload_views(&client, &schemes, &[1, 2, 4]).await;
+
+        // Views are fetched individually
+        for fetched in range_views(&client, 1, 5).await {
+            assert_eq!(fetched, vec![1, 2, 4]);
+        }
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_range_transient_failure() {
+        // Fail the first range request (as an intermediary would) and count the rest
+        static FAILED: AtomicBool = AtomicBool::new(false);
+        static RANGES: AtomicUsize = AtomicUsize::new(0);
+        async fn flaky(request: Request, next: middleware::Next) -> Response {
+            if request.uri().path().contains("..") {
+                if !FAILED.swap(true, Ordering::Relaxed) {
+                    return StatusCode::BAD_GATEWAY.into_response();
+                }
+                RANGES.fetch_add(1, Ordering::Relaxed);
+            }
+            next.run(request).await
+        }
+        let (schemes, identity) = fixture(0);
+        let indexer = Arc::new(Indexer::new(schemes[0].clone(), Sequential));
+        let (addr, handle) =
+            start_server(Api::new(indexer).router().layer(middleware::from_fn(flaky))).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+        upload_views(&client, &schemes, &[1, 2, 4]).await;
+
+        // The failed request falls back to individual fetches without disabling ranges
+        for fetched in range_views(&client, 1, 5).await {
+            assert_eq!(fetched, vec![1, 2, 4]);
+        }
+        assert!(FAILED.load(Ordering::Relaxed));
+        assert_eq!(RANGES.load(Ordering::Relaxed), 5);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_block_retrieval() {
+        let ctx = TestContext::new().await;
+        let block = ctx.test_block();
+        let finalized = ctx.finalized();
+
+        ctx.client.finalized_upload(finalized).await.unwrap();
+
+        // Test retrieval by latest
+        let payload = ctx.client.block_get(Query::Latest).await.unwrap();
+        match payload {
+            Payload::Finalized(f) => {
+                assert_eq!(f.block.height.get(), 1);
+            }
+            _ => panic!("Expected finalized block"),
+        }
+
+        // Test retrieval by index
+        let payload = ctx.client.block_get(Query::Index(1)).await.unwrap();
+        match payload {
+            Payload::Finalized(f) => {
+                assert_eq!(f.block.height.get(), 1);
+            }
+            _ => panic!("Expected finalized block"),
+        }
+
+        // Test retrieval by digest
+        let payload = ctx
+            .client
+            .block_get(Query::Digest(block.digest()))
+            .await
+            .unwrap();
+        match payload {
+            Payload::Block(b) => {
+                assert_eq!(b.digest(), block.digest());
+            }
+            _ => panic!("Expected block"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_receipts() {
+        let (schemes, identity) = fixture(0);
+        let (addr, _) = start_server(router(schemes[0].clone())).await;
+        let uri = format!("http://{addr}");
+        let client = Client::new(&uri, identity, Sequential);
+        wait_for_ready(&client).await;
+        let get = |query: String| {
+            let url = format!("{uri}/receipts/{query}");
+            async move {
+                let response = reqwest::get(url).await.unwrap();
+                let status = response.status();
+                (status, response.bytes().await.unwrap())
+            }
+        };
+        let index = |index: u64| hex(&index.to_be_bytes());
+
+        // Upload a finalized block with receipts
+        let block = Block::new_with_receipts(
+            DigestMode::Sha256,
+            INITIAL_RULES,
+            Sha256::hash(b"genesis"),
+            Height::new(1),
+            1000,
+            Vec::new(),
+            Bytes::from_static(b"receipts"),
+        );
+        let proposal = fixtures::proposal(1, block.digest());
+        let finalized = Finalized::new(fixtures::finalization(&schemes, proposal), block.clone());
+        client.finalized_upload(finalized).await.unwrap();
+
+        // Receipts are served by height, digest, and latest
+        for query in [index(1), hex(&block.digest()), LATEST.to_string()] {
+            let (status, receipts) = get(query).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(receipts.as_ref(), b"receipts");
+        }
+
+        // Unknown blocks are not found
+        let (status, _) = get(index(2)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_block_caching() {
+        let (schemes, identity) = fixture(0);
+
+        // Serve until stopped (closing idle connections so the indexer becomes unreachable)
+        let api = Api::new(Arc::new(Indexer::new(schemes[0].clone(), Sequential)));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, api.router())
+                .with_graceful_shutdown(async {
+                    let _ = stopped.await;
+                })
+                .await
+                .unwrap();
+        });
+        let uri = format!("http://{addr}");
+        let client = ClientBuilder::new(&uri, identity, Sequential)
+            .with_block_cache(NZUsize!(8))
+            .build();
+        wait_for_ready(&client).await;
+        upload_views(&client, &schemes, &[1]).await;
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+        let digest = hex(&block.digest());
+
+        // Blocks by digest are immutable (and tagged with their digest)
+        let response = reqwest::get(format!("{uri}/block/{digest}")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers[header::CACHE_CONTROL], IMMUTABLE_CACHE_CONTROL);
+        assert_eq!(headers[header::ETAG], format!("\"{digest}\""));
+
+        // Conditional requests for a known block are not modified
+        let conditional = |tag: String| {
+            reqwest::Client::new()
+                .get(format!("{uri}/block/{digest}"))
+                .header(header::IF_NONE_MATCH, tag)
+                .send()
+        };
+        let response = conditional(format!("\"{digest}\"")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(response.bytes().await.unwrap().is_empty());
+        let response = conditional("\"other\"".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Other queries may change, so they are not cached
+        let response = reqwest::get(format!("{uri}/block/latest")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+
+        // Once fetched, blocks are served from the client cache (even if the indexer is down)
+        client.block_get(Query::Latest).await.unwrap();
+        stop.send(()).unwrap();
+        server.await.unwrap();
+        let payload = client
+            .block_get(Query::Digest(block.digest()))
+            .await
+            .unwrap();
+        assert_eq!(payload.block(), &block);
+        assert!(client.block_get(Query::Latest).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_json_responses() {
+        let (schemes, identity) = fixture(0);
+        let (addr, _) = start_server(router(schemes[0].clone())).await;
+        let uri = format!("http://{addr}");
+        let client = Client::new(&uri, identity, Sequential);
+        wait_for_ready(&client).await;
+        upload_views(&client, &schemes, &[1, 2]).await;
+        let get = |path: String, accept: &'static str| {
+            reqwest::Client::new()
+                .get(format!("{uri}/{path}"))
+                .header(header::ACCEPT, accept)
+                .send()
+        };
+        let json = |path: String| async move {
+            let response = get(path, "text/html, application/json;q=0.9")
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], JSON_CONTENT_TYPE);
+            let body = response.bytes().await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        let index = |index: u64| hex(&index.to_be_bytes());
+        let range = |start: u64, end: u64| format!("{}..{}", index(start), index(end));
+
+        // Artifacts use their canonical JSON form (shared with JavaScript)
+        let seed = json(format!("seed/{}", index(1))).await;
+        assert_eq!(seed["view"], 1);
+        let notarized = json(format!("notarization/{LATEST}")).await;
+        assert_eq!(notarized["proof"]["view"], 2);
+        assert_eq!(notarized["block"]["height"], 2);
+        let finalized = json(format!("finalization/{}", index(1))).await;
+        assert_eq!(finalized["proof"]["view"], 1);
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+        let payload = json(format!("block/{}", hex(&block.digest()))).await;
+        assert_eq!(payload["height"], 1);
+        assert_eq!(payload["digest"], hex(&block.digest()));
+        let payload = json(format!("block/{LATEST}")).await;
+        assert_eq!(payload["proof"]["view"], 2);
+        assert_eq!(payload["block"]["height"], 2);
+
+        // Ranges are arrays
+        let seeds = json(format!("seed/{}", range(1, 3))).await;
+        assert_eq!(seeds.as_array().unwrap().len(), 2);
+        let blocks = json(format!("block/{}", range(2, 10))).await;
+        assert_eq!(blocks[0]["block"]["height"], 2);
+        assert_eq!(blocks.as_array().unwrap().len(), 1);
+
+        // Missing artifacts and invalid ranges are still errors
+        let response = get(format!("nullification/{LATEST}"), JSON_CONTENT_TYPE)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = get(format!("seed/{}", range(3, 1)), JSON_CONTENT_TYPE)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Other media types get the binary encoding
+        let response = get(format!("block/{}", hex(&block.digest())), "*/*")
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::VARY], "accept");
+        assert_eq!(
+            response.bytes().await.unwrap().as_ref(),
+            block.encode().as_ref()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_digest() {
+        let ctx = TestContext::new().await;
+        let block = ctx.test_block();
+
+        // Wait for a block that has not been notarized yet
+        let waiter = tokio::spawn({
+            let client = ctx.client.clone();
+            let digest = block.digest();
+            async move { client.wait_for_digest(digest).await }
+        });
+        let notarized = ctx.notarized();
+        ctx.client
+            .notarized_upload(notarized.clone())
+            .await
+            .unwrap();
+        let inclusion = waiter.await.unwrap().unwrap();
+        assert_eq!(inclusion, notarized.into());
+
+        // Already finalized blocks resolve without waiting
+        let ctx = TestContext::new().await;
+        let finalized = ctx.finalized();
+        ctx.client
+            .finalized_upload(finalized.clone())
+            .await
+            .unwrap();
+        let inclusion = ctx.client.wait_for_digest(block.digest()).await.unwrap();
+        assert_eq!(inclusion, finalized.into());
+    }
+
+    #[tokio::test]
+    async fn test_block_prefix() {
+        let ctx = TestContext::new().await;
+
+        // Store blocks until two digests share a prefix
+        let mut blocks = Vec::new();
+        let mut parent = Sha256::hash(b"genesis");
+        for height in 1..=500 {
+            let block = Block::new(parent, Height::new(height), height);
+            parent = block.digest();
+            let proposal = ctx.proposal(&block);
+            let notarized = Notarized::new(fixtures::notarization(&ctx.schemes, proposal), block);
+            ctx.client
+                .notarized_upload(notarized.clone())
+                .await
+                .unwrap();
+            blocks.push(notarized.block);
+        }
+        let digests: Vec<_> = blocks.iter().map(|block| hex(&block.digest())).collect();
+        let shared =
+            |a: &str, b: &str| a.chars().zip(b.chars()).take_while(|(a, b)| a == b).count();
+        let (ambiguous, other) = digests
+            .iter()
+            .enumerate()
+            .flat_map(|(i, a)| digests[i + 1..].iter().map(move |b| (a, b)))
+            .find(|(a, b)| shared(a, b) >= MIN_PREFIX)
+            .expect("no shared prefix");
+
+        // A unique prefix returns the block
+        let unique = &digests[0][..12];
+        let payload = ctx
+            .client
+            .block_get(Query::prefix(&format!("0x{}", unique.to_uppercase())).unwrap())
+            .await
+            .unwrap();
+        let Payload::Block(block) = payload else {
+            panic!("Expected block");
+        };
+        assert_eq!(block.digest(), blocks[0].digest());
+
+        // An ambiguous prefix returns the matching digests
+        let result = ctx
+            .client
+            .block_get(Query::prefix(&ambiguous[..MIN_PREFIX]).unwrap())
+            .await;
+        let Err(Error::AmbiguousPrefix(matches)) = result else {
+            panic!("Expected ambiguous prefix");
+        };
+        let matches: Vec<_> = matches.iter().map(|digest| hex(digest)).collect();
+        assert!(matches.contains(ambiguous) && matches.contains(other));
+
+        // Unknown prefixes are not found
+        let missing = (0..u16::MAX)
+            .map(|n| format!("{n:04x}"))
+            .find(|prefix| !digests.iter().any(|digest| digest.starts_with(prefix)))
+            .unwrap();
+        let result = ctx.client.block_get(Query::prefix(&missing).unwrap()).await;
+        assert!(matches!(result, Err(Error::Failed(StatusCode::NOT_FOUND))));
+
+        // Invalid prefixes are rejected
+        assert!(Query::prefix("abc").is_none());
+        assert!(Query::prefix("0123456789abcdef").is_none());
+        assert!(Query::prefix("xyz0").is_none());
+        assert!(Query::prefix(&digests[0]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_consistency() {
+        let ctx = TestContext::new().await;
+
+        // Notarize a chain of blocks and finalize the tip
+        let mut parent = Sha256::hash(b"genesis");
+        let mut tip = None;
+        for height in 1..=5 {
+            let block = Block::new(parent, Height::new(height), height * 1000);
+            parent = block.digest();
+            let proposal = fixtures::proposal(height, block.digest());
+            let notarization = fixtures::notarization(&ctx.schemes, proposal.clone());
+            ctx.client
+                .notarized_upload(Notarized::new(notarization, block.clone()))
+                .await
+                .unwrap();
+            tip = Some((proposal, block));
+        }
+        let (proposal, block) = tip.unwrap();
+        let finalization = fixtures::finalization(&ctx.schemes, proposal);
+        ctx.client
+            .finalized_upload(Finalized::new(finalization, block))
+            .await
+            .unwrap();
+
+        // Prove the tip descends from height 2
+        let consistency = ctx.client.consistency_get(2, 5).await.unwrap();
+        assert_eq!(consistency.from().height.get(), 2);
+        assert_eq!(consistency.to().height.get(), 5);
+        assert_eq!(consistency.headers.len(), 3);
+
+        // A proof of a block against itself has no headers
+        let consistency = ctx.client.consistency_get(5, 5).await.unwrap();
+        assert!(consistency.headers.is_empty());
+
+        // Unknown ancestors and invalid ranges are rejected
+        assert!(ctx.client.consistency_get(0, 5).await.is_err());
+        assert!(ctx.client.consistency_get(5, 2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_websocket_streaming() {
+        let ctx = TestContext::new().await;
+        let seed = ctx.seed();
+
+        let mut stream = ctx.client.listen().await.unwrap();
+
+        // Signal that websocket is connected, then upload the seed
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let client = ctx.client.clone();
+        tokio::spawn(async move {
+            rx.await.unwrap();
+            client.seed_upload(seed).await.unwrap();
+        });
+
+        // Signal ready and wait for the seed message
+        tx.send(()).unwrap();
+        if let Some(Ok(msg)) = stream.next().await {
+            match msg {
+                alto_client::consensus::Message::Seed(s) => {
+                    assert_eq!(s.view().get(), 1);
+                }
+                _ => panic!("Expected seed message"),
+            }
+        } else {
+            panic!("Expected to receive a message");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_websocket_filtering() {
+        let ctx = TestContext::new().await;
+        let subscription = Subscription::default()
+            .with_kinds([Kind::Nullification])
+            .with_start_view(3);
+        let mut stream = ctx.client.listen_filtered(&subscription).await.unwrap();
+
+        // Give the server time to subscribe the websocket before uploading
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        ctx.client.seed_upload(ctx.seed()).await.unwrap();
+        for view in 2..=3 {
+            let nullification = fixtures::nullification(&ctx.schemes, view);
+            ctx.client
+                .nullification_upload(nullification)
+                .await
+                .unwrap();
+        }
+
+        // Only matching messages are streamed
+        match stream.next().await {
+            Some(Ok(alto_client::consensus::Message::Nullification(n))) => {
+                assert_eq!(n.view().get(), 3);
+            }
+            _ => panic!("Expected nullification message"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_websocket_replay() {
+        let ctx = TestContext::new().await;
+        ctx.client.seed_upload(ctx.seed()).await.unwrap();
+        for view in 2..=3 {
+            let nullification = fixtures::nullification(&ctx.schemes, view);
+            ctx.client
+                .nullification_upload(nullification)
+                .await
+                .unwrap();
+        }
+
+        // Stored messages from the requested view are replayed in order
+        let mut stream = ctx.client.listen_from(2).await.unwrap();
+        for view in 2..=3 {
+            match stream.next().await {
+                Some(Ok(alto_client::consensus::Message::Nullification(n))) => {
+                    assert_eq!(n.view().get(), view);
+                }
+                _ => panic!("Expected nullification message"),
+            }
+        }
+
+        // New messages follow
+        let nullification = fixtures::nullification(&ctx.schemes, 4);
+        ctx.client
+            .nullification_upload(nullification)
+            .await
+            .unwrap();
+        match stream.next().await {
+            Some(Ok(alto_client::consensus::Message::Nullification(n))) => {
+                assert_eq!(n.view().get(), 4);
+            }
+            _ => panic!("Expected nullification message"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sse_streaming() {
+        let (schemes, identity) = fixture(0);
+        let (addr, handle) = start_server(router(schemes[0].clone())).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+        let finalized = fixtures::finalized(
+            &schemes,
+            1,
+            Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000),
+        );
+        client
+            .seed_upload(fixtures::seed(&schemes, 1))
+            .await
+            .unwrap();
+        client.finalized_upload(finalized).await.unwrap();
+        let nullification = fixtures::nullification(&schemes, 2);
+        client.nullification_upload(nullification).await.unwrap();
+
+        // Read events until `count` have been received
+        async fn events(response: &mut reqwest::Response, count: usize) -> String {
+            let mut text = String::new();
+            while text.matches("event: ").count() < count {
+                let chunk = response.chunk().await.unwrap().unwrap();
+                text.push_str(std::str::from_utf8(&chunk).unwrap());
+            }
+            text
+        }
+
+        // Stored messages matching the filter are replayed as JSON
+        let mut response = reqwest::Client::new()
+            .get(format!(
+                "http://{addr}/consensus/sse?kinds=finalization,nullification&from_view=1"
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+        let text = events(&mut response, 2).await;
+        assert!(!text.contains("event: seed"));
+        let finalization = text.find("event: finalization").unwrap();
+        let nullification = text.find("event: nullification").unwrap();
+        assert!(finalization < nullification);
+        assert!(text.contains(r#"data: {"kind":"nullification","value":{"view":2}}"#));
+        assert!(text.contains(r#""height":1"#));
+
+        // New messages follow
+        let nullification = fixtures::nullification(&schemes, 3);
+        client.nullification_upload(nullification).await.unwrap();
+        let text = events(&mut response, 1).await;
+        assert!(text.contains(r#"data: {"kind":"nullification","value":{"view":3}}"#));
+
+        // Malformed filters are rejected
+        let response = reqwest::get(format!("http://{addr}/consensus/sse?kinds=block"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_grpc() {
+        use grpc::proto::{
+            self, consensus_message::Message, indexer_client::IndexerClient, query::Target,
+        };
+        use tonic::{transport::server::TcpIncoming, Code};
+
+        let (schemes, _) = fixture(0);
+        let indexer = Arc::new(Indexer::new(schemes[0].clone(), Sequential));
+        let block = Block::new_with_receipts(
+            DigestMode::Sha256,
+            INITIAL_RULES,
+            Sha256::hash(b"genesis"),
+            Height::new(1),
+            1000,
+            Vec::new(),
+            Bytes::from_static(b"receipts"),
+        );
+        let proposal = fixtures::proposal(1, block.digest());
+        let notarized = Notarized::new(
+            fixtures::notarization(&schemes, proposal.clone()),
+            block.clone(),
+        );
+        let finalized = Finalized::new(fixtures::finalization(&schemes, proposal), block.clone());
+        indexer.submit_seed(fixtures::seed(&schemes, 1)).unwrap();
+        indexer.submit_notarization(notarized.clone()).unwrap();
+        indexer.submit_finalization(finalized.clone()).unwrap();
+
+        // Start gRPC server
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let service = grpc::Service::new(indexer.clone()).server();
+        let handle = tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(service)
+                .serve_with_incoming(TcpIncoming::from(listener))
+                .await
+                .unwrap();
+        });
+        let mut client = IndexerClient::connect(format!("http://{addr}"))
+            .await
+            .unwrap();
+        let query = |target| proto::Query {
+            target: Some(target),
+        };
+
+        // Artifacts carry their decoded fields and encoding
+        let seed = client
+            .get_seed(proto::Query::default())
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(seed.view, 1);
+        assert_eq!(
+            Seed::decode(seed.encoded.as_slice()).unwrap().view().get(),
+            1
+        );
+        let retrieved = client
+            .get_notarization(query(Target::Index(1)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(retrieved.proof.unwrap().payload, block.digest().to_vec());
+        assert_eq!(retrieved.encoded, notarized.encode().to_vec());
+        let retrieved = client
+            .get_finalization(query(Target::Index(1)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(retrieved.block.unwrap().height, 1);
+        assert_eq!(retrieved.encoded, finalized.encode().to_vec());
+
+        // Blocks are found by height (with their finalization) or digest
+        let payload = client
+            .get_block(query(Target::Index(1)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(payload.proof.is_some());
+        assert_eq!(payload.encoded, finalized.encode().to_vec());
+        let payload = client
+            .get_block(query(Target::Digest(block.digest().to_vec())))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(payload.proof.is_none());
+        let retrieved = payload.block.unwrap();
+        assert_eq!(retrieved.digest, block.digest().to_vec());
+        assert_eq!(retrieved.receipts, b"receipts".to_vec());
+        assert_eq!(payload.encoded, block.encode().to_vec());
+
+        // Missing artifacts and invalid queries are rejected
+        let status = client
+            .get_finalization(query(Target::Index(2)))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+        let status = client
+            .get_seed(query(Target::Digest(block.digest().to_vec())))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let status = client
+            .get_block(query(Target::Digest(vec![0; 3])))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        // Stored messages are replayed before new ones
+        let subscription = proto::Subscription {
+            kinds: vec![
+                proto::Kind::Finalization.into(),
+                proto::Kind::Nullification.into(),
+            ],
+            from_view: Some(1),
+            ..Default::default()
+        };
+        let mut stream = client
+            .stream_consensus(subscription)
+            .await
+            .unwrap()
+            .into_inner();
+        let message = stream.message().await.unwrap().unwrap();
+        let Some(Message::Finalization(retrieved)) = message.message else {
+            panic!("expected finalization");
+        };
+        assert_eq!(retrieved.encoded, finalized.encode().to_vec());
+        indexer
+            .submit_nullification(fixtures::nullification(&schemes, 2))
+            .unwrap();
+        let message = stream.message().await.unwrap().unwrap();
+        let Some(Message::Nullification(nullification)) = message.message else {
+            panic!("expected nullification");
+        };
+        assert_eq!(nullification.view, 2);
+
+        // Unknown kinds are rejected
+        let subscription = proto::Subscription {
+            kinds: vec![4],
+            ..Default::default()
+        };
+        let status = client.stream_consensus(subscription).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        handle.abort();
+    }
+
+    #[test]
+    fn test_consensus_replay() {
+        let (schemes, _) = fixture(0);
+        let seed = |view| {
+            let proposal = fixtures::proposal(view, Sha256::hash(&view.to_be_bytes()));
+            fixtures::notarization(&schemes, proposal).seed()
+        };
+        let indexer = Indexer::new(schemes[0].clone(), Sequential);
+        for view in [1, 2, MAX_REPLAY_VIEWS + 2] {
+            indexer.submit_seed(seed(view)).unwrap();
+        }
+        indexer
+            .submit_nullification(fixtures::nullification(&schemes, 3))
+            .unwrap();
+
+        // Nothing is replayed unless requested
+        let (_, frames) = indexer.consensus_replay(&Subscription::default());
+        assert!(frames.is_empty());
+
+        // Only the latest views are replayed (older messages are reported as dropped)
+        let subscription = Subscription::default()
+            .with_kinds([Kind::Seed])
+            .with_from_view(0);
+        let (_, frames) = indexer.consensus_replay(&subscription);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0][0], LAGGED_FRAME);
+        assert_eq!(u64::decode(&frames[0][1..]).unwrap(), 2);
+        assert_eq!(frames[1][0], Kind::Seed as u8);
+        let replayed = Seed::decode(&frames[1][1..]).unwrap();
+        assert_eq!(replayed.view().get(), MAX_REPLAY_VIEWS + 2);
+    }
+
+    #[tokio::test]
+    async fn test_nullification_operations() {
+        let ctx = TestContext::new().await;
+        let nullification = fixtures::nullification(&ctx.schemes, 2);
+        let mut stream = ctx.client.listen().await.unwrap();
+
+        // Give the server time to subscribe the websocket before uploading
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        ctx.client
+            .nullification_upload(nullification.clone())
+            .await
+            .unwrap();
+
+        // Nullifications are streamed
+        match stream.next().await {
+            Some(Ok(alto_client::consensus::Message::Nullification(n))) => {
+                assert_eq!(n, nullification);
+            }
+            _ => panic!("Expected nullification message"),
+        }
+
+        // Nullifications can be fetched by view
+        let retrieved = ctx
+            .client
+            .nullification_get(IndexQuery::Latest)
+            .await
+            .unwrap();
+        assert_eq!(retrieved.view().get(), 2);
+        let retrieved = ctx
+            .client
+            .nullification_get(IndexQuery::Index(2))
+            .await
+            .unwrap();
+        assert_eq!(retrieved, nullification);
+        let result = ctx.client.nullification_get(IndexQuery::Index(1)).await;
+        assert!(matches!(result, Err(Error::Failed(StatusCode::NOT_FOUND))));
+
+        // Nullifications from another network are rejected
+        let (wrong_schemes, _) = fixture(1);
+        let result = ctx
+            .client
+            .nullification_upload(fixtures::nullification(&wrong_schemes, 3))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_identity_verification() {
+        // Create two different fixtures
+        let (schemes1, _) = fixture(0);
+        let (_, identity2) = fixture(1);
+
+        // Start server with schemes1, but create client expecting identity2
+        let (addr, _handle) = start_server(router(schemes1[0].clone())).await;
+        let client = Client::new(&format!("http://{addr}"), identity2, Sequential);
+        wait_for_ready(&client).await;
+
+        // Create a seed signed by schemes1
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+        let proposal = fixtures::proposal(1, block.digest());
+        let seed = fixtures::notarization(&schemes1, proposal).seed();
+
+        // Server accepts it (signed by schemes1, which server uses)
+        client.seed_upload(seed).await.unwrap();
+
+        // Client fails to verify (expects identity2 but seed is signed by schemes1)
+        let result = client.seed_get(IndexQuery::Latest).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_signature_rejection() {
+        let ctx = TestContext::new().await;
+
+        // Create different schemes (wrong ones)
+        let (wrong_schemes, _) = fixture(1);
+
+        // Create a seed with wrong schemes
+        let block = ctx.test_block();
+        let proposal = ctx.proposal(&block);
+        let bad_seed = fixtures::notarization(&wrong_schemes, proposal).seed();
+
+        // Server rejects it (signature doesn't match server's identity)
+        let result = ctx.client.seed_upload(bad_seed).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trace_propagation() {
+        // Only report healthy if a well-formed traceparent is provided (recording the last one)
+        let received = Arc::new(Mutex::new(None));
+        let app = Router::new()
+            .route(
+                "/health",
+                get(
+                    |AxumState(received): AxumState<Arc<Mutex<Option<String>>>>,
+                     headers: axum::http::HeaderMap| async move {
+                        let Some(traceparent) = headers.get(TRACEPARENT) else {
+                            return StatusCode::BAD_REQUEST;
+                        };
+                        let traceparent = traceparent.to_str().unwrap().to_string();
+                        let parts: Vec<_> = traceparent.split('-').collect();
+                        if parts.len() != 4 || parts[1].len() != 32 || parts[2].len() != 16 {
+                            return StatusCode::BAD_REQUEST;
+                        }
+                        *received.lock().unwrap() = Some(traceparent);
+                        StatusCode::OK
+                    },
+                ),
+            )
+            .with_state(received.clone());
+        let (addr, handle) = start_server(app).await;
+        let (_, identity) = fixture(0);
+
+        // Without propagation, no header is sent
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        assert!(client.health().await.is_err());
+
+        // With propagation, a traceparent is attached
+        let client = ClientBuilder::new(&format!("http://{addr}"), identity, Sequential)
+            .with_trace_propagation()
+            .build();
+        client.health().await.unwrap();
+
+        // With an OpenTelemetry layer, the caller's trace is continued
+        let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let span = tracing::info_span!("caller");
+        let trace_id = span.context().span().span_context().trace_id();
+        client.health().instrument(span).await.unwrap();
+        let traceparent = received.lock().unwrap().clone().unwrap();
+        assert_eq!(
+            traceparent.split('-').nth(1),
+            Some(trace_id.to_string().as_str())
+        );
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_replica_failover() {
+        let (schemes, identity) = fixture(0);
+        let (addr, handle) = start_server(router(schemes[0].clone())).await;
+
+        // The first replica is down (nothing listens on 127.0.0.2)
+        let down = SocketAddr::from(([127, 0, 0, 2], addr.port()));
+        let client = ClientBuilder::new(
+            &format!("http://indexer.test:{}", addr.port()),
+            identity,
+            Sequential,
+        )
+        .with_replicas(vec![down, addr])
+        .build();
+
+        // Requests fail over to the live replica
+        let seed = fixtures::seed(&schemes, 1);
+        client.seed_upload(seed.clone()).await.unwrap();
+        for _ in 0..4 {
+            let retrieved = client.seed_get(IndexQuery::Latest).await.unwrap();
+            assert_eq!(retrieved, seed);
+        }
+
+        // So do websocket connections
+        let mut stream = client.listen().await.unwrap();
+        let seed = fixtures::seed(&schemes, 2);
+        let uploader = client.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            uploader.seed_upload(seed).await.unwrap();
+        });
+        match stream.next().await {
+            Some(Ok(alto_client::consensus::Message::Seed(seed))) => {
+                assert_eq!(seed.view().get(), 2)
+            }
+            _ => panic!("expected seed message"),
+        }
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_latency_metrics() {
+        let (schemes, identity) = fixture(0);
+        let (addr, handle) = start_server(router(schemes[0].clone())).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+
+        // Upload a seed and then its finalization
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+        let proposal = fixtures::proposal(1, block.digest());
+        let finalization = fixtures::finalization(&schemes, proposal);
+        client.seed_upload(finalization.seed()).await.unwrap();
+        client
+            .finalized_upload(Finalized::new(finalization, block))
+            .await
+            .unwrap();
+
+        // Latency is recorded with the view as an exemplar
+        let metrics = reqwest::get(format!("http://{addr}/metrics"))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(metrics.contains("indexer_seed_to_finalization_count 1"));
+        assert!(metrics.contains("# {view=\"1\"}"));
+        assert!(metrics.ends_with("# EOF\n"));
+
+        handle.abort();
+    }
+
+    #[test]
+    fn test_size_metrics() {
+        let (schemes, _) = fixture(0);
+        let indexer = Indexer::new(schemes[0].clone(), Sequential);
+
+        // Notarize and finalize the same block
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+        let proposal = fixtures::proposal(1, block.digest());
+        let notarization = fixtures::notarization(&schemes, proposal.clone());
+        let finalization = fixtures::finalization(&schemes, proposal);
+        indexer.submit_seed(notarization.seed()).unwrap();
+        indexer
+            .submit_notarization(Notarized::new(notarization, block.clone()))
+            .unwrap();
+        indexer
+            .submit_finalization(Finalized::new(finalization.clone(), block.clone()))
+            .unwrap();
+
+        // Each artifact is measured once (even if uploaded again)
+        indexer
+            .submit_finalization(Finalized::new(finalization.clone(), block.clone()))
+            .unwrap();
+        let metrics = indexer.encode_metrics();
+        assert!(metrics.contains("indexer_block_size_count 1"));
+        assert!(metrics.contains("indexer_notarization_size_count 1"));
+        assert!(metrics.contains("indexer_finalization_size_count 1"));
+        assert!(metrics.contains(&format!(
+            "indexer_block_size_sum {}",
+            block.encode_size() as f64
+        )));
+        assert!(metrics.contains(&format!(
+            "indexer_finalization_size_sum {}",
+            finalization.encode_size() as f64
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_upload_authentication() {
+        let (schemes, identity) = fixture(0);
+        let validator = ed25519::PrivateKey::from_seed(0);
+        let outsider = ed25519::PrivateKey::from_seed(1);
+
+        // Only accept uploads from the validator
+        let indexer =
+            Indexer::new(schemes[0].clone(), Sequential).with_uploaders([validator.public_key()]);
+        let (addr, handle) = start_server(Api::new(Arc::new(indexer)).router()).await;
+        let uri = format!("http://{addr}");
+        let proposal = fixtures::proposal(1, Sha256::hash(b"block"));
+        let seed = fixtures::notarization(&schemes, proposal).seed();
+
+        // Unsigned uploads are rejected
+        let client = Client::new(&uri, identity, Sequential);
+        wait_for_ready(&client).await;
+        let result = client.seed_upload(seed.clone()).await;
+        assert!(matches!(result, Err(Error::Failed(status)) if status == StatusCode::UNAUTHORIZED));
+
+        // Uploads signed by unknown keys are rejected
+        let client = ClientBuilder::new(&uri, identity, Sequential)
+            .with_signer(outsider)
+            .build();
+        let result = client.seed_upload(seed.clone()).await;
+        assert!(matches!(result, Err(Error::Failed(status)) if status == StatusCode::UNAUTHORIZED));
+
+        // Uploads signed by the validator are accepted
+        let client = ClientBuilder::new(&uri, identity, Sequential)
+            .with_signer(validator)
+            .build();
+        client.seed_upload(seed).await.unwrap();
+        client.seed_get(IndexQuery::Index(1)).await.unwrap();
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_uploads() {
+        let (schemes, identity) = fixture(0);
+        let validator = ed25519::PrivateKey::from_seed(0);
+
+        // Only accept uploads from the validator
+        let indexer = Arc::new(
+            Indexer::new(schemes[0].clone(), Sequential).with_uploaders([validator.public_key()]),
+        );
+        let (addr, handle) = start_server(Api::new(indexer.clone()).router()).await;
+        let uri = format!("http://{addr}");
+        let seed = fixtures::seed(&schemes, 1);
+        let client = Client::new(&uri, identity, Sequential);
+        wait_for_ready(&client).await;
+
+        // Unsigned uploads are acknowledged as rejected (without closing the socket)
+        let (mut uploader, _) = client.socket().await.unwrap();
+        let ack = uploader
+            .upload(Kind::Seed, seed.encode().to_vec())
+            .await
+            .unwrap();
+        assert_eq!(ack.id, 0);
+        assert_eq!(ack.status, StatusCode::UNAUTHORIZED.as_u16());
+        let result = uploader.seed_upload(seed.clone()).await;
+        assert!(matches!(result, Err(Error::Failed(status)) if status == StatusCode::UNAUTHORIZED));
+
+        // Signed uploads are accepted and broadcast to subscribers (including the uploader)
+        let client = ClientBuilder::new(&uri, identity, Sequential)
+            .with_signer(validator)
+            .build();
+        let (mut uploader, mut messages) = client.socket().await.unwrap();
+        uploader.seed_upload(seed.clone()).await.unwrap();
+        match messages.next().await.unwrap().unwrap() {
+            alto_client::consensus::Message::Seed(s) => assert_eq!(s, seed),
+            _ => panic!("expected seed message"),
+        }
+        client.seed_get(IndexQuery::Index(1)).await.unwrap();
+
+        // Malformed artifacts are rejected like HTTP uploads
+        let ack = uploader
+            .upload(Kind::Finalization, seed.encode().to_vec())
+            .await
+            .unwrap();
+        assert_eq!(ack.id, 1);
+        assert_eq!(ack.status, StatusCode::BAD_REQUEST.as_u16());
+
+        // Uploads are attributed to their signer
+        let stats = indexer.upload_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].seeds, 1);
+        assert_eq!(stats[0].rejected, 1);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_batch_uploads() {
+        let (schemes, identity) = fixture(0);
+        let validator = ed25519::PrivateKey::from_seed(0);
+        let seed = fixtures::seed(&schemes, 1);
+        let finalized = fixtures::finalized(
+            &schemes,
+            1,
+            Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000),
+        );
+
+        // Only accept uploads from the validator (and no nullifications larger than a seed)
+        let indexer = Arc::new(
+            Indexer::new(schemes[0].clone(), Sequential)
+                .with_uploaders([validator.public_key()])
+                .with_upload_limits(UploadLimits {
+                    nullification: seed.encode_size(),
+                    ..UploadLimits::default()
+                }),
+        );
+        let (addr, handle) = start_server(Api::new(indexer.clone()).router()).await;
+        let uri = format!("http://{addr}");
+        let artifacts = vec![
+            (Kind::Seed, seed.encode().to_vec()),
+            (Kind::Finalization, finalized.encode().to_vec()),
+            (Kind::Notarization, seed.encode().to_vec()),
+            (Kind::Nullification, finalized.encode().to_vec()),
+        ];
+
+        // Unsigned batches are rejected
+        let client = Client::new(&uri, identity, Sequential);
+        wait_for_ready(&client).await;
+        let result = client.batch_upload(artifacts.clone()).await;
+        assert!(matches!(result, Err(Error::Failed(status)) if status == StatusCode::UNAUTHORIZED));
+
+        // Each artifact of a signed batch is acknowledged like its HTTP upload
+        let client = ClientBuilder::new(&uri, identity, Sequential)
+            .with_signer(validator)
+            .build();
+        let acks = client.batch_upload(artifacts).await.unwrap();
+        let statuses: Vec<_> = acks.iter().map(|ack| ack.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                StatusCode::OK.as_u16(),
+                StatusCode::OK.as_u16(),
+                StatusCode::BAD_REQUEST.as_u16(),
+                StatusCode::PAYLOAD_TOO_LARGE.as_u16(),
+            ]
+        );
+        assert_eq!(client.seed_get(IndexQuery::Index(1)).await.unwrap(), seed);
+        let stored = client.finalized_get(IndexQuery::Index(1)).await.unwrap();
+        assert_eq!(stored, finalized);
+
+        // Uploads are attributed to the signer of the batch
+        let stats = indexer.upload_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].seeds, 1);
+        assert_eq!(stats[0].finalizations, 1);
+        assert_eq!(stats[0].rejected, 1);
+
+        // Batches with too many uploads are refused
+        let result = client
+            .batch_upload(vec![(Kind::Seed, Vec::new()); MAX_BATCH_UPLOADS + 1])
+            .await;
+        assert!(matches!(result, Err(Error::InvalidQuery)));
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_batch_finalizations() {
+        let (schemes, identity) = fixture(0);
+        let (forger, _) = fixture(1);
+        let validator = ed25519::PrivateKey::from_seed(0);
+        let indexer = Arc::new(
+            Indexer::new(schemes[0].clone(), Sequential).with_uploaders([validator.public_key()]),
+        );
+        let (addr, handle) = start_server(Api::new(indexer.clone()).router()).await;
+        let uri = format!("http://{addr}");
+        let client = ClientBuilder::new(&uri, identity, Sequential)
+            .with_signer(validator)
+            .build();
+        wait_for_ready(&client).await;
+
+        // Finalizations of a batch are verified together (and only the forged one is rejected)
+        let mut parent = Sha256::hash(b"genesis");
+        let mut artifacts = Vec::new();
+        for height in 1..=4 {
+            let block = Block::new(parent, Height::new(height), height * 1000);
+            parent = block.digest();
+            let signers = if height == 3 { &forger } else { &schemes };
+            let finalized = fixtures::finalized(signers, height, block);
+            artifacts.push((Kind::Finalization, finalized.encode().to_vec()));
+        }
+        let acks = client.batch_upload(artifacts).await.unwrap();
+        let statuses: Vec<_> = acks.iter().map(|ack| ack.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                StatusCode::OK.as_u16(),
+                StatusCode::OK.as_u16(),
+                StatusCode::UNAUTHORIZED.as_u16(),
+                StatusCode::OK.as_u16(),
+            ]
+        );
+        for height in [1, 2, 4] {
+            let stored = client
+                .finalized_get(IndexQuery::Index(height))
+                .await
+                .unwrap();
+            assert_eq!(stored.block.height, Height::new(height));
+        }
+        assert!(client.finalized_get(IndexQuery::Index(3)).await.is_err());
+        let stats = indexer.upload_stats();
+        assert_eq!(stats[0].finalizations, 3);
+        assert_eq!(stats[0].rejected, 1);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_upload_limits() {
+        let (schemes, identity) = fixture(0);
+        let seed = fixtures::seed(&schemes, 1);
+        let finalized = fixtures::finalized(
+            &schemes,
+            1,
+            Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000),
+        );
+
+        // Only allow seeds (not finalizations) to be uploaded
+        let indexer = Arc::new(
+            Indexer::new(schemes[0].clone(), Sequential).with_upload_limits(UploadLimits {
+                finalization: seed.encode_size(),
+                ..UploadLimits::default()
+            }),
+        );
+        let (addr, handle) = start_server(Api::new(indexer.clone()).router()).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+
+        // Uploads within their route's limit are accepted
+        client.seed_upload(seed.clone()).await.unwrap();
+
+        // Uploads over their route's limit are rejected before decoding
+        let result = client.finalized_upload(finalized).await;
+        assert!(
+            matches!(result, Err(Error::Failed(status)) if status == StatusCode::PAYLOAD_TOO_LARGE)
+        );
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}/seed"))
+            .body(vec![0u8; DEFAULT_MAX_UPLOAD_SIZE + 1])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(indexer
+            .encode_metrics()
+            .contains("indexer_oversized_uploads_total 2"));
+
+        // Trailing bytes within the limit are rejected by the decoder
+        let mut padded = seed.encode().to_vec();
+        padded.push(0);
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}/seed"))
+            .body(padded)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_notarization_window() {
+        let (schemes, identity) = fixture(0);
+        let notarized = |view| {
+            let block = Block::new(Sha256::hash(b"genesis"), Height::new(view), view);
+            let proposal = fixtures::proposal(view, block.digest());
+            Notarized::new(fixtures::notarization(&schemes, proposal), block)
+        };
+
+        // Only allow notarizations up to 10 views ahead of the latest seed
+        let indexer =
+            Arc::new(Indexer::new(schemes[0].clone(), Sequential).with_notarization_window(10));
+        let (addr, handle) = start_server(Api::new(indexer.clone()).router()).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+
+        // Notarizations within the window are accepted (even before any seed)
+        client.notarized_upload(notarized(10)).await.unwrap();
+
+        // Notarizations beyond the window are rejected (even though they verify)
+        let result = client.notarized_upload(notarized(15)).await;
+        assert!(matches!(result, Err(Error::Failed(status)) if status == StatusCode::TOO_EARLY));
+        assert!(client.notarized_get(IndexQuery::Index(15)).await.is_err());
+        assert!(indexer
+            .encode_metrics()
+            .contains("indexer_out_of_window_uploads_total 1"));
+
+        // The window advances with the latest seed
+        client
+            .seed_upload(fixtures::seed(&schemes, 5))
+            .await
+            .unwrap();
+        client.notarized_upload(notarized(15)).await.unwrap();
+        let retrieved = client.notarized_get(IndexQuery::Index(15)).await.unwrap();
+        assert_eq!(retrieved, notarized(15));
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_upload_stats() {
+        let (schemes, identity) = fixture(0);
+        let (other, _) = fixture(1);
+        let validator = ed25519::PrivateKey::from_seed(0);
+        let outsider = ed25519::PrivateKey::from_seed(1);
+
+        // Only accept uploads from the validator
+        let indexer = Arc::new(
+            Indexer::new(schemes[0].clone(), Sequential).with_uploaders([validator.public_key()]),
+        );
+        let (addr, handle) = start_server(Api::new(indexer.clone()).router()).await;
+        let uri = format!("http://{addr}");
+        let proposal = fixtures::proposal(1, Sha256::hash(b"block"));
+        let seed = fixtures::notarization(&schemes, proposal.clone()).seed();
+        let invalid = fixtures::notarization(&other, proposal).seed();
+
+        // Unauthenticated uploads are not attributed
+        let client = ClientBuilder::new(&uri, identity, Sequential)
+            .with_signer(outsider.clone())
+            .build();
+        wait_for_ready(&client).await;
+        assert!(client.seed_upload(seed.clone()).await.is_err());
+        assert!(indexer.upload_stats().is_empty());
+
+        // Accepted and rejected uploads are attributed to the signer
+        let client = ClientBuilder::new(&uri, identity, Sequential)
+            .with_signer(validator.clone())
+            .build();
+        client.seed_upload(seed.clone()).await.unwrap();
+        assert!(client.seed_upload(invalid).await.is_err());
+        let stats: Vec<UploaderStats> = reqwest::get(format!("{uri}/uploaders"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(stats, indexer.upload_stats());
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].uploader, hex(&validator.public_key().encode()));
+        assert_eq!(stats[0].seeds, 1);
+        assert_eq!(stats[0].notarizations, 0);
+        assert_eq!(stats[0].rejected, 1);
+        assert!(stats[0].last_upload > 0);
+        assert!(stats[0].mean_latency.is_some());
+        assert!(stats[0].max_latency >= stats[0].mean_latency);
+        handle.abort();
+
+        // Without authentication, uploads are attributed to the claimed validator
+        let (addr, handle) = start_server(router(schemes[0].clone())).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}/seed"))
+            .header(UPLOAD_VALIDATOR, hex(&outsider.public_key().encode()))
+            .body(seed.encode().to_vec())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let stats: Vec<UploaderStats> = reqwest::get(format!("http://{addr}/uploaders"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].uploader, hex(&outsider.public_key().encode()));
+        assert_eq!(stats[0].seeds, 1);
+        assert_eq!(stats[0].mean_latency, None);
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_epochs() {
+        let (first, first_identity) = fixture(0);
+        let (second, second_identity) = fixture(1);
+        let indexer = Indexer::new(first[0].clone(), Sequential).with_epoch(1, second_identity);
+        let (addr, handle) = start_server(Api::new(Arc::new(indexer)).router()).await;
+        let uri = format!("http://{addr}");
+        let client = ClientBuilder::new(&uri, first_identity, Sequential)
+            .with_epoch(1, second_identity)
+            .build();
+        wait_for_ready(&client).await;
+
+        // Finalize heights 1 and 2 in the first epoch and height 3 in the second (where views
+        // restart)
+        let mut parent = Sha256::hash(b"genesis");
+        let finalized = [(&first, 0, 1), (&first, 0, 2), (&second, 1, 1)];
+        for (height, (schemes, epoch, view)) in (1..).zip(finalized) {
+            let block = Block::new(parent, Height::new(height), 1000);
+            parent = block.digest();
+            let proposal = fixtures::proposal_in(epoch, view, block.digest());
+            let finalization = fixtures::finalization(schemes, proposal);
+            client
+                .finalized_upload(Finalized::new(finalization, block))
+                .await
+                .unwrap();
+        }
+
+        // Certificates signed by the wrong validator set for their epoch are rejected
+        let block = Block::new(parent, Height::new(4), 1000);
+        let proposal = fixtures::proposal_in(1, 2, block.digest());
+        let forged = Finalized::new(fixtures::finalization(&first, proposal), block);
+        assert!(client.finalized_upload(forged).await.is_err());
+        let nullification = fixtures::nullification_in(&second, 0, 3);
+        assert!(client.nullification_upload(nullification).await.is_err());
+
+        // Blocks from every epoch are served by height (and verified against their epoch)
+        for height in 1..=3 {
+            let Payload::Finalized(finalized) =
+                client.block_get(Query::Index(height)).await.unwrap()
+            else {
+                panic!("expected finalized block");
+            };
+            assert_eq!(finalized.block.height.get(), height);
+        }
+        let blocks = client.block_get_range(1, 4).await.unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[2].proof.round().epoch(), Epoch::new(1));
+
+        // Views are served from the latest epoch
+        let finalized = client.finalized_get(IndexQuery::Index(1)).await.unwrap();
+        assert_eq!(finalized.block.height.get(), 3);
+        assert!(client.finalized_get(IndexQuery::Index(2)).await.is_err());
+        let finalizations = client.finalized_get_range(0, 10).await.unwrap();
+        assert_eq!(finalizations.len(), 1);
+
+        // A client that only knows the genesis identity cannot verify the second epoch
+        let stale = ClientBuilder::new(&uri, first_identity, Sequential).build();
+        assert!(stale.block_get(Query::Index(1)).await.is_ok());
+        assert!(stale.block_get(Query::Index(3)).await.is_err());
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_audit() {
+        let (schemes, _) = fixture(0);
+        let indexer = Arc::new(Indexer::new(schemes[0].clone(), Sequential));
+        let (addr, handle) = start_server(Api::new(indexer.clone()).router()).await;
+        let uri = format!("http://{addr}");
+
+        // Store a chain of finalized blocks
+        let mut parent = Sha256::hash(b"genesis");
+        for height in 1..=3 {
+            let block = Block::new(parent, Height::new(height), 1000);
+            parent = block.digest();
+            let proposal = fixtures::proposal(height, block.digest());
+            let finalization = fixtures::finalization(&schemes, proposal);
+            indexer
+                .submit_finalization(Finalized::new(finalization, block))
+                .unwrap();
+        }
+
+        // No report is served before the first audit
+        let response = reqwest::get(format!("{uri}/audit")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // Consistent artifacts need no repair
+        let report = indexer.audit();
+        assert_eq!(report.finalizations, 3);
+        assert!(report.discontinuities.is_empty());
+        assert!(!report.repaired());
+
+        // Inconsistent indexes and encodings are rebuilt from the finalizations
+        {
+            let mut state = indexer.state.write().unwrap();
+            let round = |view| Round::new(EPOCH, View::new(view));
+            let stored = state.finalizations.hot_mut().get_mut(&round(1)).unwrap();
+            stored.encoded = Bytes::from_static(b"corrupted");
+            let digest = state.finalizations.hot()[&round(2)].value.block.digest();
+            state.blocks_by_digest.hot_mut().remove(&digest);
+            state.finalized_height_to_round.remove(&3);
+            state.finalized_height_to_round.insert(7, round(1));
+        }
+        let report = indexer.audit();
+        assert_eq!(report.reencoded, vec![1]);
+        assert_eq!(report.reindexed, vec![7, 3]);
+        assert_eq!(report.restored, vec![2]);
+        assert!(report.discontinuities.is_empty());
+        assert_eq!(indexer.get_block_range(0, 10).len(), 3);
+        assert!(indexer.get_block_range(7, 8).is_empty());
+        assert!(indexer.get_consistency(1, 3).is_some());
+        assert!(!indexer.audit().repaired());
+
+        // Blocks that do not extend the previous height are reported (but not repaired)
+        let block = Block::new(Sha256::hash(b"fork"), Height::new(4), 1000);
+        let proposal = fixtures::proposal(4, block.digest());
+        let finalization = fixtures::finalization(&schemes, proposal);
+        indexer
+            .submit_finalization(Finalized::new(finalization, block))
+            .unwrap();
+        let report = indexer.audit();
+        assert_eq!(report.discontinuities, vec![4]);
+        assert!(!report.repaired());
+        let served: AuditReport = reqwest::get(format!("{uri}/audit"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(served, report);
+        let metrics = indexer.encode_metrics();
+        assert!(metrics.contains("indexer_audits_total 4"));
+        assert!(metrics.contains("indexer_audit_repairs_total 4"));
+        assert!(metrics.contains("indexer_audit_discontinuities 1"));
 
-            let (addr, _) = start_server(schemes[0].clone(), Sequential).await;
-            let client = Client::new(&format!("http://{addr}"), identity, Sequential);
-            wait_for_ready(&client).await;
+        handle.abort();
+    }
 
-            Self { schemes, client }
-        }
+    #[tokio::test]
+    async fn test_cold_storage() {
+        let (schemes, identity) = fixture(0);
+        let dir = std::env::temp_dir().join(format!("alto-indexer-cold-{}", std::process::id()));
+        let store = storage::Directory::new(&dir).unwrap();
+        let indexer =
+            Arc::new(Indexer::new(schemes[0].clone(), Sequential).with_cold_storage(store, 2));
+        let (addr, handle) = start_server(Api::new(indexer.clone()).router()).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
 
-        /// Create a test block with standard parameters.
-        fn test_block(&self) -> Block {
-            Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000)
+        // Store a chain of finalized blocks (with their seeds and notarizations)
+        let mut parent = Sha256::hash(b"genesis");
+        let mut digests = Vec::new();
+        for height in 1..=6 {
+            let block = Block::new(parent, Height::new(height), 1000);
+            parent = block.digest();
+            digests.push(block.digest());
+            let proposal = fixtures::proposal(height, block.digest());
+            let notarization = fixtures::notarization(&schemes, proposal.clone());
+            let finalization = fixtures::finalization(&schemes, proposal);
+            client.seed_upload(notarization.seed()).await.unwrap();
+            client
+                .notarized_upload(Notarized::new(notarization, block.clone()))
+                .await
+                .unwrap();
+            client
+                .finalized_upload(Finalized::new(finalization, block))
+                .await
+                .unwrap();
         }
 
-        /// Create a proposal for the given block at view 1.
-        fn proposal(&self, block: &Block) -> Proposal<Digest> {
-            Proposal::new(
-                Round::new(EPOCH, View::new(1)),
-                View::new(0),
-                block.digest(),
-            )
+        // Artifacts more than 2 views behind the latest view are demoted (once written)
+        while indexer.metrics.demoted_artifacts.get() < 12 {
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
         }
-
-        /// Create a seed by first creating a notarization.
-        fn seed(&self) -> Seed {
-            let block = self.test_block();
-            let proposal = self.proposal(&block);
-            create_notarization(&self.schemes, proposal).seed()
+        {
+            let state = indexer.state.read().unwrap();
+            let round = |view| Round::new(EPOCH, View::new(view));
+            let hot: Vec<_> = state
+                .finalizations
+                .hot()
+                .keys()
+                .map(|r| r.view().get())
+                .collect();
+            assert_eq!(hot, vec![4, 5, 6]);
+            assert!(state.seeds.is_cold(&round(1)));
+            assert!(state.notarizations.is_cold(&round(3)));
+            assert!(state.blocks_by_digest.is_cold(&digests[0]));
+            assert!(!state.blocks_by_digest.is_cold(&digests[3]));
         }
+        assert_eq!(
+            std::fs::read_dir(dir.join("finalizations"))
+                .unwrap()
+                .count(),
+            3
+        );
+        let metrics = indexer.encode_metrics();
+        assert!(metrics.contains("indexer_demoted_artifacts_total 12"));
 
-        /// Create a notarized block.
-        fn notarized(&self) -> Notarized {
-            let block = self.test_block();
-            let proposal = self.proposal(&block);
-            Notarized::new(create_notarization(&self.schemes, proposal), block)
+        // Demoted artifacts are still served
+        let seed = client.seed_get(IndexQuery::Index(1)).await.unwrap();
+        assert_eq!(seed.view().get(), 1);
+        let notarized = client.notarized_get(IndexQuery::Index(2)).await.unwrap();
+        assert_eq!(notarized.block.digest(), digests[1]);
+        let finalized = client.finalized_get(IndexQuery::Index(3)).await.unwrap();
+        assert_eq!(finalized.block.digest(), digests[2]);
+        let payload = client.block_get(Query::Index(1)).await.unwrap();
+        assert_eq!(payload.block().digest(), digests[0]);
+        let payload = client.block_get(Query::Digest(digests[0])).await.unwrap();
+        assert_eq!(payload.block().digest(), digests[0]);
+        for fetched in range_views(&client, 1, 7).await {
+            assert_eq!(fetched, vec![1, 2, 3, 4, 5, 6]);
         }
+        let listing = indexer.list_finalizations(Cursor {
+            after_view: Some(1),
+            limit: Some(3),
+        });
+        let views: Vec<_> = listing.entries.iter().map(|entry| entry.view).collect();
+        assert_eq!(views, vec![2, 3, 4]);
+        assert_eq!(listing.next.unwrap().after_view, Some(4));
+        assert!(indexer.get_consistency(1, 6).is_some());
 
-        /// Create a finalized block.
-        fn finalized(&self) -> Finalized {
-            let block = self.test_block();
-            let proposal = self.proposal(&block);
-            Finalized::new(create_finalization(&self.schemes, proposal), block)
-        }
-    }
+        // Only artifacts held in memory are audited
+        let report = indexer.audit();
+        assert_eq!(report.finalizations, 3);
+        assert!(!report.repaired());
+        handle.abort();
 
-    fn create_notarization(
-        schemes: &[Scheme],
-        proposal: Proposal<Digest>,
-    ) -> alto_types::Notarization {
-        let notarizes: Vec<_> = schemes
+        // Demoted artifacts are indexed again after a restart (and those in memory are lost)
+        let store = storage::Directory::new(&dir).unwrap();
+        std::fs::write(dir.join("seeds").join("invalid.tmp"), b"partial").unwrap();
+        let restarted = Indexer::new(schemes[0].clone(), Sequential).with_cold_storage(store, 2);
+        let heights: Vec<_> = restarted
+            .get_block_range(1, 7)
             .iter()
-            .map(|scheme| Notarize::sign(scheme, proposal.clone()).unwrap())
+            .map(|finalized| finalized.block.height.get())
             .collect();
-        Notarization::from_notarizes(&schemes[0], &notarizes, &Sequential).unwrap()
-    }
-
-    fn create_finalization(
-        schemes: &[Scheme],
-        proposal: Proposal<Digest>,
-    ) -> alto_types::Finalization {
-        let finalizes: Vec<_> = schemes
+        assert_eq!(heights, vec![1, 2, 3]);
+        let views: Vec<_> = restarted
+            .get_seed_range(1, 7)
             .iter()
-            .map(|scheme| Finalize::sign(scheme, proposal.clone()).unwrap())
+            .map(|seed| seed.view().get())
             .collect();
-        Finalization::from_finalizes(&schemes[0], &finalizes, &Sequential).unwrap()
-    }
-
-    async fn start_server(
-        scheme: Scheme,
-        strategy: impl Strategy,
-    ) -> (SocketAddr, tokio::task::JoinHandle<()>) {
-        let indexer = Arc::new(Indexer::new(scheme, strategy));
-        let api = Api::new(indexer);
-        let app = api.router();
+        assert_eq!(views, vec![1, 2, 3]);
+        assert_eq!(restarted.get_notarization_range(1, 7).len(), 3);
+        assert!(restarted.get_consistency(1, 3).is_some());
+        let latest = restarted.get_finalization(LATEST).unwrap();
+        assert_eq!(latest.block.digest(), digests[2]);
 
-        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
-        let addr = listener.local_addr().unwrap();
-
-        let handle = tokio::spawn(async move {
-            axum::serve(listener, app).await.unwrap();
-        });
-
-        (addr, handle)
+        std::fs::remove_dir_all(dir).unwrap();
     }
 
-    async fn wait_for_ready(client: &Client<Sequential>) {
-        loop {
-            if client.health().await.is_ok() {
-                return;
-            }
+    #[tokio::test]
+    async fn test_cold_storage_replace() {
+        let (schemes, _) = fixture(0);
+        let dir =
+            std::env::temp_dir().join(format!("alto-indexer-cold-replace-{}", std::process::id()));
+        let store = storage::Directory::new(&dir).unwrap();
+        let indexer = Indexer::new(schemes[0].clone(), Sequential).with_cold_storage(store, 2);
+
+        // Store a chain of finalized blocks (demoting the first ones)
+        let mut parent = Sha256::hash(b"genesis");
+        let mut blocks = Vec::new();
+        for height in 1..=6 {
+            let block = Block::new(parent, Height::new(height), 1000);
+            parent = block.digest();
+            let proposal = fixtures::proposal(height, block.digest());
+            let finalization = fixtures::finalization(&schemes, proposal);
+            indexer
+                .submit_finalization(Finalized::new(finalization, block.clone()))
+                .unwrap();
+            blocks.push(block);
+        }
+        while !indexer
+            .state
+            .read()
+            .unwrap()
+            .blocks_by_digest
+            .is_cold(&blocks[0].digest())
+        {
             tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
         }
-    }
 
-    fn fixture(seed: u64) -> (Vec<Scheme>, Identity) {
-        let mut rng = StdRng::seed_from_u64(seed);
-        let Fixture { schemes, .. } =
-            bls12381_threshold::fixture::<MinSig, _>(&mut rng, NAMESPACE, 4);
-        let identity = *schemes[0].polynomial().public();
-        (schemes, identity)
+        // Corrupt the demoted copy of the first block and replace it
+        let digest = blocks[0].digest();
+        storage::Directory::new(&dir)
+            .unwrap()
+            .put(storage::Table::Blocks, &digest.encode(), b"stale")
+            .unwrap();
+        let mut demotion = indexer
+            .state
+            .write()
+            .unwrap()
+            .blocks_by_digest
+            .replace(digest, Stored::new(blocks[0].clone()));
+        assert!(!demotion.is_empty());
+        assert!(indexer.get_block(&hex(&digest)).is_some());
+        demotion.write();
+        let demoted = indexer
+            .state
+            .write()
+            .unwrap()
+            .blocks_by_digest
+            .complete(demotion);
+        assert_eq!(demoted, 1);
+
+        // The replacement (not the stale copy) is served after a restart
+        let store = storage::Directory::new(&dir).unwrap();
+        let restarted = Indexer::new(schemes[0].clone(), Sequential).with_cold_storage(store, 2);
+        let payload = restarted.get_block(&hex(&digest)).unwrap();
+        assert_eq!(payload.block().digest(), digest);
+
+        std::fs::remove_dir_all(dir).unwrap();
     }
 
     #[tokio::test]
-    async fn test_seed_operations() {
-        let ctx = TestContext::new().await;
-        let seed = ctx.seed();
+    async fn test_digest_mode() {
+        let (schemes, identity) = fixture(0);
 
-        ctx.client.seed_upload(seed.clone()).await.unwrap();
+        // Compute block digests with BLAKE3
+        let indexer =
+            Indexer::new(schemes[0].clone(), Sequential).with_digest_mode(DigestMode::Blake3);
+        let (addr, handle) = start_server(Api::new(Arc::new(indexer)).router()).await;
+        let uri = format!("http://{addr}");
+        let client = ClientBuilder::new(&uri, identity, Sequential)
+            .with_digest_mode(DigestMode::Blake3)
+            .build();
+        wait_for_ready(&client).await;
 
-        let retrieved = ctx.client.seed_get(IndexQuery::Latest).await.unwrap();
-        assert_eq!(retrieved.view(), seed.view());
+        // Blocks with SHA-256 digests are rejected
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+        let proposal = fixtures::proposal(1, block.digest());
+        let finalized = Finalized::new(fixtures::finalization(&schemes, proposal), block);
+        let result = client.finalized_upload(finalized).await;
+        assert!(matches!(result, Err(Error::Failed(status)) if status == StatusCode::BAD_REQUEST));
 
-        let retrieved = ctx.client.seed_get(IndexQuery::Index(1)).await.unwrap();
-        assert_eq!(retrieved.view().get(), 1);
+        // Blocks with BLAKE3 digests are accepted
+        let block = Block::new_with_mode(
+            DigestMode::Blake3,
+            Sha256::hash(b"genesis"),
+            Height::new(1),
+            1000,
+        );
+        let proposal = fixtures::proposal(1, block.digest());
+        let finalized = Finalized::new(fixtures::finalization(&schemes, proposal), block.clone());
+        client.finalized_upload(finalized).await.unwrap();
+        let retrieved = client.finalized_get(IndexQuery::Latest).await.unwrap();
+        assert_eq!(retrieved.block, block);
+        let Payload::Block(retrieved) = client
+            .block_get(Query::Digest(block.digest()))
+            .await
+            .unwrap()
+        else {
+            panic!("expected block");
+        };
+        assert_eq!(*retrieved, block);
+
+        // Clients using a different mode fail to decode
+        let client = Client::new(&uri, identity, Sequential);
+        let result = client.finalized_get(IndexQuery::Latest).await;
+        assert!(matches!(result, Err(Error::InvalidData(_))));
+
+        handle.abort();
     }
 
     #[tokio::test]
-    async fn test_notarization_operations() {
-        let ctx = TestContext::new().await;
-        let notarized = ctx.notarized();
+    async fn test_genesis() {
+        let network = fixtures::seeded(0, 4);
+        let identity = fixtures::identity(&network.schemes);
+        let genesis = fixtures::genesis(&network, Sha256::hash(b"config"));
 
-        ctx.client.notarized_upload(notarized).await.unwrap();
+        // Indexers without a genesis transcript don't serve one
+        let (addr, handle) = start_server(router(network.schemes[0].clone())).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+        let result = client.genesis_get().await;
+        assert!(matches!(result, Err(Error::Failed(status)) if status == StatusCode::NOT_FOUND));
+        handle.abort();
 
-        let retrieved = ctx.client.notarized_get(IndexQuery::Latest).await.unwrap();
-        assert_eq!(retrieved.proof.view().get(), 1);
+        // Serve the genesis transcript
+        let indexer =
+            Indexer::new(network.schemes[0].clone(), Sequential).with_genesis(genesis.clone());
+        let (addr, handle) = start_server(Api::new(Arc::new(indexer)).router()).await;
+        let uri = format!("http://{addr}");
+        let client = Client::new(&uri, identity, Sequential);
+        wait_for_ready(&client).await;
+        assert_eq!(client.genesis_get().await.unwrap(), genesis);
 
-        let retrieved = ctx
-            .client
-            .notarized_get(IndexQuery::Index(1))
-            .await
-            .unwrap();
-        assert_eq!(retrieved.proof.view().get(), 1);
+        // Clients of a different network reject the transcript
+        let other = fixtures::identity(&fixtures::seeded(1, 4).schemes);
+        let client = Client::new(&uri, other, Sequential);
+        let result = client.genesis_get().await;
+        assert!(matches!(result, Err(Error::UnexpectedResponse)));
+
+        handle.abort();
     }
 
     #[tokio::test]
-    async fn test_finalization_operations() {
-        let ctx = TestContext::new().await;
-        let finalized = ctx.finalized();
+    async fn test_validators() {
+        let network = fixtures::seeded(0, 4);
+        let identity = fixtures::identity(&network.schemes);
 
-        ctx.client.finalized_upload(finalized).await.unwrap();
+        // Indexers without validator metadata don't serve any
+        let (addr, handle) = start_server(router(network.schemes[0].clone())).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+        let result = client.validators_get().await;
+        assert!(matches!(result, Err(Error::Failed(status)) if status == StatusCode::NOT_FOUND));
+        handle.abort();
 
-        let retrieved = ctx.client.finalized_get(IndexQuery::Latest).await.unwrap();
-        assert_eq!(retrieved.proof.view().get(), 1);
+        // Serve validator metadata
+        let mut participants = network.participants.clone();
+        participants.sort();
+        let mut validators: Vec<_> = participants.iter().map(ValidatorInfo::new).collect();
+        validators[0].name = Some("alpha".to_string());
+        validators[0].region = Some("us-east-1".to_string());
+        validators[0].location = Some("Ashburn".to_string());
+        validators[0].coordinates = Some([38.8339, -77.3074]);
+        let indexer = Indexer::new(network.schemes[0].clone(), Sequential)
+            .with_validators(validators.clone());
+        let (addr, handle) = start_server(Api::new(Arc::new(indexer)).router()).await;
+        let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+        wait_for_ready(&client).await;
+        assert_eq!(client.validators_get().await.unwrap(), validators);
 
-        let retrieved = ctx
-            .client
-            .finalized_get(IndexQuery::Index(1))
-            .await
-            .unwrap();
-        assert_eq!(retrieved.proof.view().get(), 1);
+        handle.abort();
     }
 
     #[tokio::test]
-    async fn test_block_retrieval() {
-        let ctx = TestContext::new().await;
-        let block = ctx.test_block();
-        let finalized = ctx.finalized();
+    async fn test_transaction_submission() {
+        let (schemes, identity) = fixture(0);
 
-        ctx.client.finalized_upload(finalized).await.unwrap();
+        // Start a validator that records submitted transactions
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let validator = Router::new()
+            .route(
+                "/tx",
+                post(|AxumState(received): AxumState<Arc<Mutex<Vec<Bytes>>>>, body: Bytes| async move {
+                    received.lock().unwrap().push(body);
+                    StatusCode::ACCEPTED
+                }),
+            )
+            .with_state(received.clone());
+        let (validator_addr, validator_handle) = start_server(validator).await;
+        let unreachable = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let unreachable_addr = unreachable.local_addr().unwrap();
+        drop(unreachable);
+        let hanging = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hanging_addr = hanging.local_addr().unwrap();
 
-        // Test retrieval by latest
-        let payload = ctx.client.block_get(Query::Latest).await.unwrap();
-        match payload {
-            alto_client::consensus::Payload::Finalized(f) => {
-                assert_eq!(f.block.height.get(), 1);
+        // Start indexers forwarding to the validator (and an unreachable validator)
+        let start = |submitters: Vec<String>| {
+            let indexer = Indexer::new(schemes[0].clone(), Sequential).with_submitters(submitters);
+            let app = Api::new(Arc::new(indexer)).router();
+            async move {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let addr = listener.local_addr().unwrap();
+                let handle = tokio::spawn(async move {
+                    axum::serve(listener, app).await.unwrap();
+                });
+                let client = Client::new(&format!("http://{addr}"), identity, Sequential);
+                wait_for_ready(&client).await;
+                (addr, client, handle)
             }
-            _ => panic!("Expected finalized block"),
-        }
+        };
+        let (addr, client, handle) = start(vec![
+            format!("http://{unreachable_addr}/tx"),
+            format!("http://{hanging_addr}/tx"),
+            format!("http://{validator_addr}/tx"),
+        ])
+        .await;
 
-        // Test retrieval by index
-        let payload = ctx.client.block_get(Query::Index(1)).await.unwrap();
-        match payload {
-            alto_client::consensus::Payload::Finalized(f) => {
-                assert_eq!(f.block.height.get(), 1);
-            }
-            _ => panic!("Expected finalized block"),
-        }
+        // Transactions are forwarded if any validator accepts them (without waiting for
+        // validators that never respond)
+        let transaction = Transaction::new(b"hello".to_vec()).unwrap();
+        let submitted = Instant::now();
+        client.transaction_submit(&transaction).await.unwrap();
+        assert!(submitted.elapsed() < FORWARD_TIMEOUT);
+        assert_eq!(received.lock().unwrap().as_slice(), &[transaction.encode()]);
+        let metrics = reqwest::get(format!("http://{addr}/metrics"))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(metrics.contains("indexer_forwarded_transactions_total 1"));
 
-        // Test retrieval by digest
-        let payload = ctx
-            .client
-            .block_get(Query::Digest(block.digest()))
+        // Malformed transactions are not forwarded
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}/tx"))
+            .body(vec![5, 0])
+            .send()
             .await
             .unwrap();
-        match payload {
-            alto_client::consensus::Payload::Block(b) => {
-                assert_eq!(b.digest(), block.digest());
-            }
-            _ => panic!("Expected block"),
-        }
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(received.lock().unwrap().len(), 1);
+        handle.abort();
+
+        // Transactions fail if no validator accepts them (or none are configured)
+        let (_, client, handle) = start(vec![format!("http://{unreachable_addr}/tx")]).await;
+        let result = client.transaction_submit(&transaction).await;
+        assert!(matches!(
+            result,
+            Err(Error::Failed(StatusCode::BAD_GATEWAY))
+        ));
+        handle.abort();
+        let (_, client, handle) = start(Vec::new()).await;
+        let result = client.transaction_submit(&transaction).await;
+        assert!(matches!(
+            result,
+            Err(Error::Failed(StatusCode::SERVICE_UNAVAILABLE))
+        ));
+        handle.abort();
+        validator_handle.abort();
+        drop(hanging);
+    }
+
+    #[test]
+    #[should_panic(expected = "genesis transcript is invalid")]
+    fn test_genesis_unsigned() {
+        let network = fixtures::seeded(0, 4);
+        let mut genesis = fixtures::genesis(&network, Sha256::hash(b"config"));
+        genesis.signatures[0] = None;
+        let _ = Indexer::new(network.schemes[0].clone(), Sequential).with_genesis(genesis);
     }
 
     #[tokio::test]
-    async fn test_websocket_streaming() {
-        let ctx = TestContext::new().await;
-        let seed = ctx.seed();
+    async fn test_overflow_policy() {
+        let (schemes, _) = fixture(0);
+        let seed = |view| {
+            let proposal = fixtures::proposal(view, Sha256::hash(&view.to_be_bytes()));
+            fixtures::notarization(&schemes, proposal).seed()
+        };
 
-        let mut stream = ctx.client.listen().await.unwrap();
+        // Lagging subscribers are notified of dropped messages and continue
+        let indexer = Indexer::new(schemes[0].clone(), Sequential).with_broadcast_capacity(2);
+        let mut subscriber = indexer.consensus_subscriber();
+        for view in 1..=5 {
+            indexer.submit_seed(seed(view)).unwrap();
+        }
+        let frame = indexer
+            .next_frame(&mut subscriber, &Subscription::default())
+            .await
+            .unwrap();
+        assert_eq!(frame[0], LAGGED_FRAME);
+        assert_eq!(u64::decode(&frame[1..]).unwrap(), 3);
+        for view in 4..=5 {
+            let frame = indexer
+                .next_frame(&mut subscriber, &Subscription::default())
+                .await
+                .unwrap();
+            assert_eq!(frame[0], Kind::Seed as u8);
+            assert_eq!(Seed::decode(&frame[1..]).unwrap().view().get(), view);
+        }
+        let metrics = indexer.encode_metrics();
+        assert!(metrics.contains("indexer_lagged_subscribers_total 1"));
+        assert!(metrics.contains("indexer_lagged_messages_total 3"));
+        assert!(metrics.contains("indexer_disconnected_subscribers_total 0"));
 
-        // Signal that websocket is connected, then upload the seed
-        let (tx, rx) = tokio::sync::oneshot::channel();
-        let client = ctx.client.clone();
-        tokio::spawn(async move {
-            rx.await.unwrap();
-            client.seed_upload(seed).await.unwrap();
-        });
+        // Lagging subscribers are disconnected
+        let indexer = Indexer::new(schemes[0].clone(), Sequential)
+            .with_broadcast_capacity(2)
+            .with_overflow_policy(OverflowPolicy::Disconnect);
+        let mut subscriber = indexer.consensus_subscriber();
+        for view in 1..=5 {
+            indexer.submit_seed(seed(view)).unwrap();
+        }
+        assert!(indexer
+            .next_frame(&mut subscriber, &Subscription::default())
+            .await
+            .is_none());
+        let metrics = indexer.encode_metrics();
+        assert!(metrics.contains("indexer_lagged_subscribers_total 1"));
+        assert!(metrics.contains("indexer_disconnected_subscribers_total 1"));
 
-        // Signal ready and wait for the seed message
-        tx.send(()).unwrap();
-        if let Some(Ok(msg)) = stream.next().await {
-            match msg {
-                alto_client::consensus::Message::Seed(s) => {
-                    assert_eq!(s.view().get(), 1);
-                }
-                _ => panic!("Expected seed message"),
-            }
-        } else {
-            panic!("Expected to receive a message");
+        // Policies round-trip through their string representation
+        for policy in [OverflowPolicy::DropOldest, OverflowPolicy::Disconnect] {
+            assert_eq!(policy.to_string().parse::<OverflowPolicy>(), Ok(policy));
         }
+        assert!("block".parse::<OverflowPolicy>().is_err());
     }
 
-    #[tokio::test]
-    async fn test_identity_verification() {
-        // Create two different fixtures
-        let (schemes1, _) = fixture(0);
-        let (_, identity2) = fixture(1);
+    #[test]
+    fn test_encoded_cache() {
+        let (schemes, _) = fixture(0);
+        let indexer = Indexer::new(schemes[0].clone(), Sequential);
 
-        // Start server with schemes1, but create client expecting identity2
-        let (addr, _handle) = start_server(schemes1[0].clone(), Sequential).await;
-        let client = Client::new(&format!("http://{addr}"), identity2, Sequential);
-        wait_for_ready(&client).await;
+        // Store finalizations at views 1..=3
+        let mut parent = Sha256::hash(b"genesis");
+        let mut finalizations = Vec::new();
+        for view in 1..=3 {
+            let block = Block::new(parent, Height::new(view), view * 1000);
+            parent = block.digest();
+            let proposal = fixtures::proposal(view, block.digest());
+            let finalized = Finalized::new(fixtures::finalization(&schemes, proposal), block);
+            indexer.submit_finalization(finalized.clone()).unwrap();
+            finalizations.push(finalized);
+        }
+        let latest = finalizations.last().unwrap();
 
-        // Create a seed signed by schemes1
-        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
-        let proposal = Proposal::new(
-            Round::new(EPOCH, View::new(1)),
-            View::new(0),
-            block.digest(),
+        // Stored encodings match a fresh encoding and are shared across reads (not re-encoded)
+        let first = indexer.get_encoded(Kind::Finalization, LATEST).unwrap();
+        let second = indexer.get_encoded(Kind::Finalization, LATEST).unwrap();
+        assert_eq!(first, latest.encode());
+        assert_eq!(first.as_ptr(), second.as_ptr());
+        let index = IndexQuery::Index(2).serialize();
+        assert_eq!(
+            indexer.get_encoded(Kind::Finalization, &index).unwrap(),
+            finalizations[1].encode()
         );
-        let seed = create_notarization(&schemes1, proposal).seed();
+        assert_eq!(
+            indexer.get_encoded(Kind::Seed, LATEST).unwrap(),
+            latest.proof.seed().encode()
+        );
+        assert!(indexer.get_encoded(Kind::Notarization, LATEST).is_none());
 
-        // Server accepts it (signed by schemes1, which server uses)
-        client.seed_upload(seed).await.unwrap();
+        // Blocks are served from the stored encodings
+        let block = indexer.get_block_encoded(LATEST).unwrap();
+        assert_eq!(block.as_ptr(), first.as_ptr());
+        let digest = Query::Digest(latest.block.digest()).serialize();
+        assert_eq!(
+            indexer.get_block_encoded(&digest).unwrap(),
+            latest.block.encode()
+        );
 
-        // Client fails to verify (expects identity2 but seed is signed by schemes1)
-        let result = client.seed_get(IndexQuery::Latest).await;
-        assert!(result.is_err());
+        // Ranges are assembled from stored encodings (and match encoding a Vec)
+        assert_eq!(
+            indexer.get_encoded_range(Kind::Finalization, 1, 3),
+            finalizations[..2].to_vec().encode()
+        );
+        assert_eq!(
+            indexer.get_encoded_range(Kind::Finalization, 0, 10),
+            finalizations.encode()
+        );
+        assert_eq!(
+            indexer.get_encoded_range(Kind::Finalization, 3, 1),
+            Vec::<Finalized>::new().encode()
+        );
     }
 
-    #[tokio::test]
-    async fn test_invalid_signature_rejection() {
-        let ctx = TestContext::new().await;
+    #[test]
+    fn test_upload_replay_protection() {
+        let (schemes, _) = fixture(0);
+        let validator = ed25519::PrivateKey::from_seed(0);
+        let indexer =
+            Indexer::new(schemes[0].clone(), Sequential).with_uploaders([validator.public_key()]);
+        let sign = |body: &[u8], timestamp: u64| {
+            let signature = validator.sign(&upload_namespace(), &upload_message(body, timestamp));
+            Some((validator.public_key(), timestamp, signature))
+        };
+        let now = 1_000_000;
 
-        // Create different schemes (wrong ones)
-        let (wrong_schemes, _) = fixture(1);
+        // Valid upload
+        indexer
+            .authenticate_upload(sign(b"body", now), b"body", now)
+            .unwrap();
 
-        // Create a seed with wrong schemes
-        let block = ctx.test_block();
-        let proposal = ctx.proposal(&block);
-        let bad_seed = create_notarization(&wrong_schemes, proposal).seed();
+        // Replays are rejected
+        assert_eq!(
+            indexer.authenticate_upload(sign(b"body", now), b"body", now + 1),
+            Err("replayed upload")
+        );
 
-        // Server rejects it (signature doesn't match server's identity)
-        let result = ctx.client.seed_upload(bad_seed).await;
-        assert!(result.is_err());
+        // Signatures over a different body are rejected
+        assert_eq!(
+            indexer.authenticate_upload(sign(b"other", now + 1), b"body", now + 1),
+            Err("invalid upload signature")
+        );
+
+        // Stale (or future) timestamps are rejected
+        let stale = now - MAX_UPLOAD_SKEW - 1;
+        assert_eq!(
+            indexer.authenticate_upload(sign(b"body", stale), b"body", now),
+            Err("stale upload")
+        );
+        let future = now + MAX_UPLOAD_SKEW + 1;
+        assert_eq!(
+            indexer.authenticate_upload(sign(b"body", future), b"body", now),
+            Err("stale upload")
+        );
+
+        // Missing signatures are rejected
+        assert_eq!(
+            indexer.authenticate_upload(None, b"body", now),
+            Err("missing upload signature")
+        );
+
+        // Without configured uploaders, everything is accepted
+        let open = Indexer::new(schemes[0].clone(), Sequential);
+        open.authenticate_upload(None, b"body", now).unwrap();
     }
 
     fn generate_self_signed_cert() -> CertifiedKey<KeyPair> {
@@ -732,10 +5785,8 @@ mod tests {
     async fn test_tls_https_connection() {
         let cert_key = generate_self_signed_cert();
 
-        let mut rng = StdRng::seed_from_u64(0);
-        let Fixture { schemes, .. } =
-            bls12381_threshold::fixture::<MinSig, _>(&mut rng, NAMESPACE, 4);
-        let identity = *schemes[0].polynomial().public();
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let identity = fixtures::identity(&schemes);
 
         let (addr, handle) = start_tls_server(schemes[0].clone(), &cert_key, Sequential).await;
         let client = create_tls_client(addr, identity, &cert_key);
@@ -743,12 +5794,8 @@ mod tests {
 
         // Create and upload a seed
         let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
-        let proposal = Proposal::new(
-            Round::new(EPOCH, View::new(1)),
-            View::new(0),
-            block.digest(),
-        );
-        let seed = create_notarization(&schemes, proposal).seed();
+        let proposal = fixtures::proposal(1, block.digest());
+        let seed = fixtures::notarization(&schemes, proposal).seed();
 
         // Test HTTPS POST
         client.seed_upload(seed.clone()).await.unwrap();
@@ -764,10 +5811,8 @@ mod tests {
     async fn test_tls_websocket_connection() {
         let cert_key = generate_self_signed_cert();
 
-        let mut rng = StdRng::seed_from_u64(0);
-        let Fixture { schemes, .. } =
-            bls12381_threshold::fixture::<MinSig, _>(&mut rng, NAMESPACE, 4);
-        let identity = *schemes[0].polynomial().public();
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let identity = fixtures::identity(&schemes);
 
         let (addr, handle) = start_tls_server(schemes[0].clone(), &cert_key, Sequential).await;
         let client = create_tls_client(addr, identity, &cert_key);
@@ -775,12 +5820,8 @@ mod tests {
 
         // Create a seed
         let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
-        let proposal = Proposal::new(
-            Round::new(EPOCH, View::new(1)),
-            View::new(0),
-            block.digest(),
-        );
-        let seed = create_notarization(&schemes, proposal).seed();
+        let proposal = fixtures::proposal(1, block.digest());
+        let seed = fixtures::notarization(&schemes, proposal).seed();
 
         // Connect to WebSocket over TLS
         let mut stream = client.listen().await.unwrap();
@@ -808,4 +5849,58 @@ mod tests {
 
         handle.abort();
     }
+
+    #[cfg(feature = "http3")]
+    #[tokio::test]
+    async fn test_http3() {
+        let cert_key = generate_self_signed_cert();
+        let (schemes, identity) = fixture(0);
+        let indexer = Arc::new(Indexer::new(schemes[0].clone(), Sequential));
+        let app = Api::new(indexer).router();
+
+        // HTTP/3 requires TLS
+        assert!(matches!(
+            ClientBuilder::new("http://127.0.0.1:8080", identity, Sequential).with_http3(),
+            Err(Error::InsecureHttp3)
+        ));
+
+        // Reserve a UDP port
+        let addr = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        // Start HTTP/3 server
+        let cert_der = CertificateDer::from(cert_key.cert.der().to_vec());
+        let key_der = PrivateKeyDer::try_from(cert_key.signing_key.serialize_der()).unwrap();
+        let tls = rustls::ServerConfig::builder_with_provider(Arc::new(
+            rustls::crypto::aws_lc_rs::default_provider(),
+        ))
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert_der], key_der)
+        .unwrap();
+        let handle = tokio::spawn(async move {
+            http3::serve(addr, tls, app).await.unwrap();
+        });
+
+        // Connect over HTTP/3
+        let client = ClientBuilder::new(&format!("https://{addr}"), identity, Sequential)
+            .with_tls_cert(cert_key.cert.der().to_vec())
+            .with_http3()
+            .unwrap()
+            .build();
+        wait_for_ready(&client).await;
+
+        // Upload and retrieve a finalization
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+        let proposal = fixtures::proposal(1, block.digest());
+        let finalized = Finalized::new(fixtures::finalization(&schemes, proposal), block);
+        client.finalized_upload(finalized.clone()).await.unwrap();
+        let retrieved = client.finalized_get(IndexQuery::Latest).await.unwrap();
+        assert_eq!(retrieved, finalized);
+
+        handle.abort();
+    }
 }
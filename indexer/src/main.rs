@@ -1,9 +1,17 @@
-use alto_indexer::{Api, Indexer};
-use alto_types::{Identity, Scheme, NAMESPACE};
+use alto_indexer::{
+    grpc, storage, Api, Indexer, OverflowPolicy, UploadLimits, DEFAULT_AUDIT_INTERVAL,
+    DEFAULT_BROADCAST_CAPACITY, DEFAULT_HOT_VIEWS, DEFAULT_MAX_UPLOAD_SIZE,
+    DEFAULT_NOTARIZATION_WINDOW,
+};
+use alto_types::{
+    DigestMode, Genesis, Identity, PublicKey, Scheme, Spec, ValidatorInfo,
+    MAX_GENESIS_PARTICIPANTS, NAMESPACE,
+};
+use axum::Router;
 use clap::Parser;
-use commonware_codec::DecodeExt;
-use commonware_parallel::Sequential;
-use std::sync::Arc;
+use commonware_codec::{Decode, DecodeExt};
+use commonware_parallel::{Rayon, Sequential, Strategy};
+use std::{num::NonZeroUsize, sync::Arc, time::Duration};
 use tracing::info;
 
 #[derive(Parser, Debug)]
@@ -14,9 +22,152 @@ struct Args {
 
     #[clap(
         long,
+        required_unless_present = "spec",
         help = "Identity public key in hex format (BLS12-381 public key)"
     )]
-    identity: String,
+    identity: Option<String>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["identity", "genesis", "digest_mode"],
+        help = "Chain spec (written by setup) providing the identity, genesis transcript, and digest mode"
+    )]
+    spec: Option<std::path::PathBuf>,
+
+    #[clap(
+        long,
+        help = "Identity of the validator set that takes over at an epoch, as <epoch>:<identity hex> (may be repeated; artifacts from that epoch onwards are verified against it)"
+    )]
+    epoch: Vec<String>,
+
+    #[clap(
+        long,
+        help = "Validator public key in hex format (ed25519) allowed to upload (may be repeated; if omitted, uploads are not authenticated)"
+    )]
+    uploader: Vec<String>,
+
+    #[clap(
+        long,
+        help = "URL of a validator transaction endpoint (like http://validator:9092/tx) to forward transactions submitted to /tx to (may be repeated; if omitted, submissions are rejected)"
+    )]
+    submitter: Vec<String>,
+
+    #[clap(
+        long,
+        default_value_t = DigestMode::Sha256,
+        help = "Hash function used to compute block digests (sha256 or blake3; must match the network)"
+    )]
+    digest_mode: DigestMode,
+
+    #[clap(
+        long,
+        default_value_t = DEFAULT_BROADCAST_CAPACITY,
+        help = "Number of consensus messages buffered for websocket subscribers"
+    )]
+    broadcast_capacity: usize,
+
+    #[clap(
+        long,
+        default_value_t = OverflowPolicy::DropOldest,
+        help = "What to do when a websocket subscriber falls more than --broadcast-capacity messages behind (drop-oldest notifies the subscriber of dropped messages; disconnect closes the connection)"
+    )]
+    overflow_policy: OverflowPolicy,
+
+    #[clap(
+        long,
+        help = "File containing the hex-encoded genesis transcript to serve at /genesis (written by setup)"
+    )]
+    genesis: Option<std::path::PathBuf>,
+
+    #[clap(
+        long,
+        help = "JSON file describing the validators (public key, name, region, location, and coordinates) to serve at /validators (written by setup)"
+    )]
+    validators: Option<std::path::PathBuf>,
+
+    #[clap(
+        long,
+        default_value_t = 1,
+        help = "Number of threads used to verify uploaded certificates (1 verifies on the request's task)"
+    )]
+    verification_threads: usize,
+
+    #[clap(
+        long,
+        default_value_t = DEFAULT_MAX_UPLOAD_SIZE,
+        help = "Maximum size (in bytes) of a seed upload"
+    )]
+    max_seed_size: usize,
+
+    #[clap(
+        long,
+        default_value_t = DEFAULT_MAX_UPLOAD_SIZE,
+        help = "Maximum size (in bytes) of a notarization upload"
+    )]
+    max_notarization_size: usize,
+
+    #[clap(
+        long,
+        default_value_t = DEFAULT_MAX_UPLOAD_SIZE,
+        help = "Maximum size (in bytes) of a finalization upload"
+    )]
+    max_finalization_size: usize,
+
+    #[clap(
+        long,
+        default_value_t = DEFAULT_MAX_UPLOAD_SIZE,
+        help = "Maximum size (in bytes) of a nullification upload"
+    )]
+    max_nullification_size: usize,
+
+    #[clap(
+        long,
+        default_value_t = DEFAULT_NOTARIZATION_WINDOW,
+        help = "Maximum number of views a notarization upload may be ahead of the latest seed"
+    )]
+    notarization_window: u64,
+
+    #[clap(
+        long,
+        default_value_t = DEFAULT_AUDIT_INTERVAL,
+        help = "Interval (in seconds) between integrity audits of stored finalizations and blocks (0 disables audits)"
+    )]
+    audit_interval: u64,
+
+    #[clap(
+        long,
+        help = "Directory to demote artifacts older than --hot-views views to (if omitted, all artifacts are kept in memory)"
+    )]
+    cold_storage: Option<std::path::PathBuf>,
+
+    #[clap(
+        long,
+        default_value_t = DEFAULT_HOT_VIEWS,
+        help = "Number of views (behind the latest stored view) to keep in memory when --cold-storage is set"
+    )]
+    hot_views: u64,
+
+    #[clap(
+        long,
+        help = "Port to serve the gRPC interface on (if omitted, only the HTTP API is served)"
+    )]
+    grpc_port: Option<u16>,
+
+    #[cfg(feature = "http3")]
+    #[clap(
+        long,
+        requires = "tls_key",
+        help = "PEM-encoded TLS certificate chain used to serve HTTP/3 (QUIC) on the same port (UDP)"
+    )]
+    tls_cert: Option<std::path::PathBuf>,
+
+    #[cfg(feature = "http3")]
+    #[clap(
+        long,
+        requires = "tls_cert",
+        help = "PEM-encoded TLS private key used to serve HTTP/3 (QUIC)"
+    )]
+    tls_key: Option<std::path::PathBuf>,
 }
 
 #[tokio::main]
@@ -29,16 +180,42 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_max_level(tracing::Level::INFO)
         .init();
 
-    // Parse identity
-    let bytes = commonware_utils::from_hex(&args.identity).ok_or("Invalid identity hex format")?;
-    let identity: Identity =
-        Identity::decode(&mut bytes.as_slice()).map_err(|_| "Failed to decode identity")?;
+    // Load network
+    let network = network(&args)?;
+    let identity = network.identity;
 
     // Initialize indexer
-    let certificate_verifier = Scheme::certificate_verifier(NAMESPACE, identity);
-    let indexer = Arc::new(Indexer::new(certificate_verifier, Sequential));
-    let api = Api::new(indexer);
-    let app = api.router();
+    let app = match args.verification_threads {
+        0 => return Err("Verification threads must be greater than zero".into()),
+        1 => router(&args, network, Sequential)?,
+        threads => {
+            let strategy = Rayon::new(NonZeroUsize::new(threads).unwrap())?;
+            info!(threads, "verifying in parallel");
+            router(&args, network, strategy)?
+        }
+    };
+
+    // Start HTTP/3 server (if configured)
+    #[cfg(feature = "http3")]
+    if let (Some(cert), Some(key)) = (&args.tls_cert, &args.tls_key) {
+        use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+        let certs = CertificateDer::pem_file_iter(cert)?.collect::<Result<Vec<_>, _>>()?;
+        let key = PrivateKeyDer::from_pem_file(key)?;
+        let tls = rustls::ServerConfig::builder_with_provider(Arc::new(
+            rustls::crypto::aws_lc_rs::default_provider(),
+        ))
+        .with_protocol_versions(&[&rustls::version::TLS13])?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+        let addr = std::net::SocketAddr::from(([0, 0, 0, 0], args.port));
+        let app = app.clone();
+        tokio::spawn(async move {
+            if let Err(e) = alto_indexer::http3::serve(addr, tls, app).await {
+                tracing::error!(?e, "HTTP/3 server failed");
+            }
+        });
+        info!(?addr, "serving HTTP/3");
+    }
 
     // Start server
     let addr = format!("0.0.0.0:{}", args.port);
@@ -48,3 +225,137 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// The network served by the indexer.
+struct Network {
+    identity: Identity,
+    digest_mode: DigestMode,
+    genesis: Option<Genesis>,
+}
+
+/// Load the network from the chain spec (or from `--identity`, `--digest-mode`, and
+/// `--genesis`).
+fn network(args: &Args) -> Result<Network, Box<dyn std::error::Error>> {
+    if let Some(path) = &args.spec {
+        let spec = Spec::load(path)?;
+        let genesis = spec.genesis()?;
+        info!(chain_id = spec.chain_id, "loaded chain spec");
+        return Ok(Network {
+            identity: genesis.identity,
+            digest_mode: spec.digest_mode,
+            genesis: Some(genesis),
+        });
+    }
+
+    // Parse identity
+    let identity = args.identity.as_ref().ok_or("Missing identity")?;
+    let bytes = commonware_utils::from_hex(identity).ok_or("Invalid identity hex format")?;
+    let identity: Identity =
+        Identity::decode(&mut bytes.as_slice()).map_err(|_| "Failed to decode identity")?;
+
+    // Parse genesis transcript (if provided)
+    let genesis = match &args.genesis {
+        Some(path) => {
+            let genesis = std::fs::read_to_string(path)?;
+            let bytes = commonware_utils::from_hex_formatted(&genesis)
+                .ok_or("Invalid genesis hex format")?;
+            let genesis = Genesis::decode_cfg(bytes.as_ref(), &MAX_GENESIS_PARTICIPANTS)
+                .map_err(|_| "Failed to decode genesis")?;
+            if !genesis.verify() || genesis.identity != identity {
+                return Err("Invalid genesis transcript".into());
+            }
+            Some(genesis)
+        }
+        None => None,
+    };
+    Ok(Network {
+        identity,
+        digest_mode: args.digest_mode,
+        genesis,
+    })
+}
+
+/// Create the indexer API for `network`, verifying uploads with `strategy`.
+fn router<S: Strategy>(
+    args: &Args,
+    network: Network,
+    strategy: S,
+) -> Result<Router, Box<dyn std::error::Error>> {
+    let certificate_verifier = Scheme::certificate_verifier(NAMESPACE, network.identity);
+    if args.broadcast_capacity == 0 {
+        return Err("Broadcast capacity must be greater than zero".into());
+    }
+    let mut indexer = Indexer::new(certificate_verifier, strategy)
+        .with_digest_mode(network.digest_mode)
+        .with_broadcast_capacity(args.broadcast_capacity)
+        .with_overflow_policy(args.overflow_policy)
+        .with_upload_limits(UploadLimits {
+            seed: args.max_seed_size,
+            notarization: args.max_notarization_size,
+            finalization: args.max_finalization_size,
+            nullification: args.max_nullification_size,
+        })
+        .with_notarization_window(args.notarization_window);
+    for epoch in &args.epoch {
+        let (epoch, identity) = epoch
+            .split_once(':')
+            .ok_or("Invalid epoch format (expected <epoch>:<identity>)")?;
+        let epoch: u64 = epoch.parse().map_err(|_| "Invalid epoch number")?;
+        let bytes = commonware_utils::from_hex(identity).ok_or("Invalid epoch identity hex")?;
+        let identity =
+            Identity::decode(bytes.as_ref()).map_err(|_| "Failed to decode epoch identity")?;
+        info!(epoch, ?identity, "verifying epoch");
+        indexer = indexer.with_epoch(epoch, identity);
+    }
+    if !args.uploader.is_empty() {
+        let mut uploaders = Vec::with_capacity(args.uploader.len());
+        for uploader in &args.uploader {
+            let bytes =
+                commonware_utils::from_hex(uploader).ok_or("Invalid uploader hex format")?;
+            let uploader =
+                PublicKey::decode(bytes.as_ref()).map_err(|_| "Failed to decode uploader")?;
+            uploaders.push(uploader);
+        }
+        info!(uploaders = uploaders.len(), "authenticating uploads");
+        indexer = indexer.with_uploaders(uploaders);
+    }
+    if !args.submitter.is_empty() {
+        info!(submitters = args.submitter.len(), "forwarding transactions");
+        indexer = indexer.with_submitters(args.submitter.clone());
+    }
+    if let Some(genesis) = network.genesis {
+        info!(participants = genesis.participants.len(), "serving genesis");
+        indexer = indexer.with_genesis(genesis);
+    }
+    if let Some(path) = &args.validators {
+        let validators = ValidatorInfo::load(path)?;
+        info!(validators = validators.len(), "serving validators");
+        indexer = indexer.with_validators(validators);
+    }
+    if let Some(path) = &args.cold_storage {
+        let store = storage::Directory::new(path)?;
+        info!(
+            ?path,
+            hot_views = args.hot_views,
+            "demoting artifacts to cold storage"
+        );
+        indexer = indexer.with_cold_storage(store, args.hot_views);
+    }
+    if args.audit_interval > 0 {
+        info!(interval = args.audit_interval, "auditing stored artifacts");
+        indexer.start_audit(Duration::from_secs(args.audit_interval));
+    }
+    let indexer = Arc::new(indexer);
+    if let Some(port) = args.grpc_port {
+        let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+        let service = grpc::Service::new(indexer.clone()).server();
+        tokio::spawn(async move {
+            let server = tonic::transport::Server::builder().add_service(service);
+            if let Err(e) = server.serve(addr).await {
+                tracing::error!(?e, "gRPC server failed");
+            }
+        });
+        info!(?addr, "serving gRPC");
+    }
+    Ok(Api::new(indexer).router())
+}
@@ -0,0 +1,460 @@
+//! Tiered storage of indexed artifacts.
+//!
+//! Every artifact is first stored in memory (the hot tier). With
+//! [crate::Indexer::with_cold_storage], artifacts more than `hot_views` views behind the latest
+//! stored view (and blocks first stored in those views) are demoted to a [Cold] store, keeping
+//! only their keys in memory. Reads fall through to the cold tier transparently, so RAM stays
+//! bounded (by the hot window and the indexes) while the full history remains queryable.
+//!
+//! Demotion never performs I/O while the indexer's state is locked: artifacts are selected
+//! under the lock, written to the [Cold] store (on a blocking thread) without it, and only then
+//! moved out of memory. Likewise, reads find an artifact under the lock and read it from the
+//! [Cold] store after releasing it.
+//!
+//! Demoted artifacts were verified before they were stored, so they are only decoded when read
+//! back (a store that fails to read or decode an artifact is logged and treated as missing).
+//! When the indexer restarts, the keys of the artifacts already in the [Cold] store are indexed
+//! again (see [Cold::keys]), so history demoted before the restart remains queryable (artifacts
+//! that were still held in memory are lost, like before). A demoted artifact that is replaced
+//! (see [Tier::replace]) is written again, so its stale copy is not indexed after a restart.
+
+use bytes::Bytes;
+use commonware_codec::{Decode, DecodeExt, Encode, Read};
+use commonware_utils::{from_hex, hex};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    iter::Peekable,
+    ops::RangeBounds,
+    path::PathBuf,
+    sync::Arc,
+};
+use tracing::{error, warn};
+
+/// A stored artifact and its encoding.
+///
+/// The encoding is computed once (when the artifact is stored), so reads are served without
+/// re-serializing (or copying) the artifact.
+#[derive(Clone)]
+pub(crate) struct Stored<T> {
+    pub(crate) value: T,
+    pub(crate) encoded: Bytes,
+}
+
+impl<T: Encode> Stored<T> {
+    pub(crate) fn new(value: T) -> Self {
+        let encoded = value.encode();
+        Self { value, encoded }
+    }
+}
+
+/// Table of a [Cold] store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Table {
+    Seeds,
+    Notarizations,
+    Finalizations,
+    Nullifications,
+    Blocks,
+}
+
+impl Table {
+    /// All tables.
+    pub const ALL: [Self; 5] = [
+        Self::Seeds,
+        Self::Notarizations,
+        Self::Finalizations,
+        Self::Nullifications,
+        Self::Blocks,
+    ];
+
+    /// Name of the table.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Seeds => "seeds",
+            Self::Notarizations => "notarizations",
+            Self::Finalizations => "finalizations",
+            Self::Nullifications => "nullifications",
+            Self::Blocks => "blocks",
+        }
+    }
+}
+
+/// Persistent storage for artifacts demoted from memory.
+///
+/// Keys are the encoded round (or, for [Table::Blocks], digest) of an artifact and values are
+/// its encoding.
+pub trait Cold: Send + Sync + 'static {
+    /// Store `value` under `key` in `table` (replacing any existing value).
+    fn put(&self, table: Table, key: &[u8], value: &[u8]) -> io::Result<()>;
+
+    /// Get the value stored under `key` in `table` (if any).
+    fn get(&self, table: Table, key: &[u8]) -> io::Result<Option<Vec<u8>>>;
+
+    /// Get the keys stored in `table` (to index the artifacts demoted before a restart).
+    fn keys(&self, table: Table) -> io::Result<Vec<Vec<u8>>>;
+}
+
+/// [Cold] store keeping each artifact in its own file (named by its hex-encoded key) in a
+/// directory per [Table].
+pub struct Directory {
+    root: PathBuf,
+}
+
+impl Directory {
+    /// Store artifacts under `root` (creating it and a directory per [Table] if needed).
+    pub fn new(root: impl Into<PathBuf>) -> io::Result<Self> {
+        let root = root.into();
+        for table in Table::ALL {
+            fs::create_dir_all(root.join(table.name()))?;
+        }
+        Ok(Self { root })
+    }
+
+    fn path(&self, table: Table, key: &[u8]) -> PathBuf {
+        self.root.join(table.name()).join(hex(key))
+    }
+}
+
+impl Cold for Directory {
+    fn put(&self, table: Table, key: &[u8], value: &[u8]) -> io::Result<()> {
+        // Write to a temporary file first so readers never see a partial artifact
+        let path = self.path(table, key);
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, value)?;
+        fs::rename(temporary, path)
+    }
+
+    fn get(&self, table: Table, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(table, key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn keys(&self, table: Table) -> io::Result<Vec<Vec<u8>>> {
+        let mut keys = Vec::new();
+        for entry in fs::read_dir(self.root.join(table.name()))? {
+            // Temporary files (left by a write interrupted by a crash) are not hex-encoded keys
+            let name = entry?.file_name();
+            if let Some(key) = name.to_str().and_then(from_hex) {
+                keys.push(key);
+            }
+        }
+        Ok(keys)
+    }
+}
+
+/// An artifact found in a [Tier] (see [Tier::find]).
+pub(crate) enum Found<T: Read> {
+    /// Held in memory.
+    Hot(Stored<T>),
+    /// Demoted (and read back from the cold store by [Found::load]).
+    Cold {
+        table: Table,
+        key: Vec<u8>,
+        store: Arc<dyn Cold>,
+        cfg: T::Cfg,
+    },
+}
+
+impl<T: Read> Found<T> {
+    /// Get the artifact, reading it from the cold store if it was demoted.
+    ///
+    /// Call without holding any lock on the [Tier] it was found in.
+    pub(crate) fn load(self) -> Option<Stored<T>> {
+        let (table, key, store, cfg) = match self {
+            Self::Hot(stored) => return Some(stored),
+            Self::Cold {
+                table,
+                key,
+                store,
+                cfg,
+            } => (table, key, store, cfg),
+        };
+        let encoded = match store.get(table, &key) {
+            Ok(Some(encoded)) => Bytes::from(encoded),
+            Ok(None) => {
+                error!(table = table.name(), "demoted artifact is missing");
+                return None;
+            }
+            Err(e) => {
+                error!(table = table.name(), ?e, "failed to read demoted artifact");
+                return None;
+            }
+        };
+        match T::decode_cfg(encoded.as_ref(), &cfg) {
+            Ok(value) => Some(Stored { value, encoded }),
+            Err(e) => {
+                error!(
+                    table = table.name(),
+                    ?e,
+                    "failed to decode demoted artifact"
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Artifacts selected for demotion from a [Tier] (see [Tier::select]).
+pub(crate) struct Demotion<K> {
+    table: Table,
+    store: Option<Arc<dyn Cold>>,
+    /// Each selected key, its encoding, and whether it was written.
+    artifacts: Vec<(K, Bytes, bool)>,
+}
+
+impl<K: Encode> Demotion<K> {
+    /// Returns true if no artifacts were selected.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.artifacts.is_empty()
+    }
+
+    /// Write the selected artifacts to the cold store (without holding any lock on the [Tier]).
+    ///
+    /// Artifacts that cannot be written are logged (and kept in memory by [Tier::complete]).
+    pub(crate) fn write(&mut self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        for (key, encoded, written) in &mut self.artifacts {
+            match store.put(self.table, &key.encode(), encoded) {
+                Ok(()) => *written = true,
+                Err(e) => error!(table = self.table.name(), ?e, "failed to demote artifact"),
+            }
+        }
+    }
+}
+
+/// Artifacts of a [Table], split between memory (hot) and a [Cold] store.
+pub(crate) struct Tier<K, T: Read> {
+    table: Table,
+    hot: BTreeMap<K, Stored<T>>,
+    cold: BTreeSet<K>,
+    /// Keys of the artifacts held in memory that are being written to the cold store.
+    demoting: BTreeSet<K>,
+    store: Option<Arc<dyn Cold>>,
+    cfg: T::Cfg,
+    /// Number of times a stored artifact was replaced or forgotten (so readers can tell if
+    /// artifacts they already read changed, see [Tier::changes]).
+    changes: u64,
+}
+
+impl<K: Ord + Copy + Encode + Read<Cfg = ()>, T: Read + Encode + Clone> Tier<K, T> {
+    /// Create an empty tier (decoding demoted artifacts with `cfg`).
+    pub(crate) fn new(table: Table, cfg: T::Cfg) -> Self {
+        Self {
+            table,
+            hot: BTreeMap::new(),
+            cold: BTreeSet::new(),
+            demoting: BTreeSet::new(),
+            store: None,
+            cfg,
+            changes: 0,
+        }
+    }
+
+    /// Decode demoted artifacts with `cfg`.
+    pub(crate) fn set_cfg(&mut self, cfg: T::Cfg) {
+        self.cfg = cfg;
+    }
+
+    /// Demote artifacts to `store` (see [Tier::select]), indexing the artifacts it already holds
+    /// (returning their keys).
+    pub(crate) fn set_store(&mut self, store: Arc<dyn Cold>) -> Vec<K> {
+        let mut restored = Vec::new();
+        match store.keys(self.table) {
+            Ok(keys) => {
+                for key in keys {
+                    match K::decode(key.as_slice()) {
+                        Ok(key) => restored.push(key),
+                        Err(e) => warn!(table = self.table.name(), ?e, "skipping invalid key"),
+                    }
+                }
+            }
+            Err(e) => error!(
+                table = self.table.name(),
+                ?e,
+                "failed to list demoted artifacts"
+            ),
+        }
+        self.cold.extend(restored.iter().copied());
+        self.store = Some(store);
+        restored
+    }
+
+    /// Forget the demoted artifact stored under `key` (if it cannot be read back).
+    pub(crate) fn forget(&mut self, key: &K) {
+        self.cold.remove(key);
+        self.changes += 1;
+    }
+
+    /// Store `stored` under `key`, returning false (and keeping the existing artifact) if one is
+    /// already stored.
+    pub(crate) fn insert(&mut self, key: K, stored: Stored<T>) -> bool {
+        if self.contains_key(&key) {
+            return false;
+        }
+        self.hot.insert(key, stored);
+        true
+    }
+
+    /// Store `stored` under `key` in memory, replacing any existing artifact.
+    ///
+    /// If the existing artifact was demoted, the replacement is selected for demotion (see
+    /// [Tier::select]) so the stale copy in the cold store is overwritten (and not indexed
+    /// again after a restart) once the returned [Demotion] is written and completed.
+    pub(crate) fn replace(&mut self, key: K, stored: Stored<T>) -> Demotion<K> {
+        let demoted = self.cold.remove(&key);
+        self.hot.insert(key, stored);
+        self.changes += 1;
+        self.select(demoted.then_some(key))
+    }
+
+    pub(crate) fn contains_key(&self, key: &K) -> bool {
+        self.hot.contains_key(key) || self.cold.contains(key)
+    }
+
+    /// Returns true if the artifact stored under `key` was demoted.
+    pub(crate) fn is_cold(&self, key: &K) -> bool {
+        self.cold.contains(key)
+    }
+
+    /// Find the artifact stored under `key` (to [Found::load] it once any lock on the tier is
+    /// released).
+    pub(crate) fn find(&self, key: &K) -> Option<Found<T>> {
+        if let Some(stored) = self.hot.get(key) {
+            return Some(Found::Hot(stored.clone()));
+        }
+        if !self.cold.contains(key) {
+            return None;
+        }
+        Some(Found::Cold {
+            table: self.table,
+            key: key.encode().to_vec(),
+            store: self.store.clone()?,
+            cfg: self.cfg.clone(),
+        })
+    }
+
+    /// Get the largest key stored.
+    pub(crate) fn last_key(&self) -> Option<K> {
+        let hot = self.hot.last_key_value().map(|(key, _)| *key);
+        hot.max(self.cold.last().copied())
+    }
+
+    /// Iterate over the keys stored in `range` (in order).
+    pub(crate) fn keys<R: RangeBounds<K> + Clone>(&self, range: R) -> impl Iterator<Item = K> + '_ {
+        Merge {
+            hot: self
+                .hot
+                .range(range.clone())
+                .map(|(key, _)| *key)
+                .peekable(),
+            cold: self.cold.range(range).copied().peekable(),
+        }
+    }
+
+    /// Iterate over the artifacts (and their keys) stored in `range` (in order).
+    pub(crate) fn range<R: RangeBounds<K> + Clone>(
+        &self,
+        range: R,
+    ) -> impl Iterator<Item = (K, Found<T>)> + '_ {
+        self.keys(range)
+            .filter_map(|key| self.find(&key).map(|found| (key, found)))
+    }
+
+    /// Artifacts held in memory.
+    pub(crate) fn hot(&self) -> &BTreeMap<K, Stored<T>> {
+        &self.hot
+    }
+
+    /// Artifacts held in memory (to repair them).
+    pub(crate) fn hot_mut(&mut self) -> &mut BTreeMap<K, Stored<T>> {
+        self.changes += 1;
+        &mut self.hot
+    }
+
+    /// Get the number of times a stored artifact was replaced or forgotten.
+    ///
+    /// Artifacts are otherwise only added (or moved to the cold store, which does not change
+    /// them), so the artifacts stored in a range are unchanged as long as this and the keys in
+    /// the range are.
+    pub(crate) fn changes(&self) -> u64 {
+        self.changes
+    }
+
+    /// Select the artifacts stored in memory under `keys` (that are not already being demoted)
+    /// for demotion to the cold store (if configured).
+    ///
+    /// Selected artifacts are served from memory until the [Demotion] is [Demotion::write]n and
+    /// [Tier::complete]d.
+    pub(crate) fn select(&mut self, keys: impl IntoIterator<Item = K>) -> Demotion<K> {
+        let mut artifacts = Vec::new();
+        if self.store.is_some() {
+            for key in keys {
+                let Some(stored) = self.hot.get(&key) else {
+                    continue;
+                };
+                if self.demoting.insert(key) {
+                    artifacts.push((key, stored.encoded.clone(), false));
+                }
+            }
+        }
+        Demotion {
+            table: self.table,
+            store: self.store.clone(),
+            artifacts,
+        }
+    }
+
+    /// Select the artifacts stored in memory under keys before `key` for demotion (see
+    /// [Tier::select]).
+    pub(crate) fn select_before(&mut self, key: &K) -> Demotion<K> {
+        let keys: Vec<_> = self.hot.range(..key).map(|(key, _)| *key).collect();
+        self.select(keys)
+    }
+
+    /// Move the artifacts of a written `demotion` out of memory, returning how many were moved.
+    ///
+    /// Artifacts that could not be written (or were replaced while being written) are kept in
+    /// memory (and may be selected again).
+    pub(crate) fn complete(&mut self, demotion: Demotion<K>) -> usize {
+        let mut demoted = 0;
+        for (key, encoded, written) in demotion.artifacts {
+            self.demoting.remove(&key);
+            if !written
+                || self
+                    .hot
+                    .get(&key)
+                    .is_none_or(|stored| stored.encoded != encoded)
+            {
+                continue;
+            }
+            self.hot.remove(&key);
+            self.cold.insert(key);
+            demoted += 1;
+        }
+        demoted
+    }
+}
+
+/// Iterator over the (disjoint, ordered) keys of both tiers.
+struct Merge<H: Iterator, C: Iterator> {
+    hot: Peekable<H>,
+    cold: Peekable<C>,
+}
+
+impl<K: Ord, H: Iterator<Item = K>, C: Iterator<Item = K>> Iterator for Merge<H, C> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        match (self.hot.peek(), self.cold.peek()) {
+            (Some(hot), Some(cold)) if cold < hot => self.cold.next(),
+            (Some(_), _) => self.hot.next(),
+            (None, _) => self.cold.next(),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Cross-check artifacts fetched from one indexer against another.
+
+use alto_client::Error;
+use commonware_codec::Encode;
+use commonware_cryptography::{sha256::Digest, Hasher, Sha256};
+use std::collections::BTreeMap;
+use tracing::{info, warn};
+
+/// Digest identifying an artifact at some index (a view or, for blocks, a height).
+pub type Fingerprint = (u64, Digest);
+
+/// Fingerprint an artifact by the digest of its encoding.
+pub fn fingerprint(index: u64, artifact: &impl Encode) -> Fingerprint {
+    (index, Sha256::hash(&artifact.encode()))
+}
+
+/// A difference between the artifacts returned by two indexers.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// Both indexers returned an artifact at this index, but they differ.
+    Mismatch(u64),
+    /// Only the first indexer returned an artifact at this index.
+    Missing(u64),
+    /// Only the second indexer returned an artifact at this index.
+    Unexpected(u64),
+}
+
+/// Compare the fingerprints returned by two indexers (ordered by index).
+pub fn compare(primary: &[Fingerprint], secondary: &[Fingerprint]) -> Vec<Discrepancy> {
+    let secondary: BTreeMap<u64, Digest> = secondary.iter().copied().collect();
+    let mut discrepancies = Vec::new();
+    for (index, digest) in primary {
+        match secondary.get(index) {
+            Some(other) if other == digest => {}
+            Some(_) => discrepancies.push(Discrepancy::Mismatch(*index)),
+            None => discrepancies.push(Discrepancy::Missing(*index)),
+        }
+    }
+    let primary: BTreeMap<u64, Digest> = primary.iter().copied().collect();
+    for index in secondary.keys() {
+        if !primary.contains_key(index) {
+            discrepancies.push(Discrepancy::Unexpected(*index));
+        }
+    }
+    discrepancies.sort_by_key(|discrepancy| match discrepancy {
+        Discrepancy::Mismatch(index)
+        | Discrepancy::Missing(index)
+        | Discrepancy::Unexpected(index) => *index,
+    });
+    discrepancies
+}
+
+/// Log how the artifacts of `kind` returned by `indexer` compare to `primary`.
+///
+/// Returns true if both indexers returned the same artifacts.
+pub fn report(
+    indexer: &str,
+    kind: &str,
+    primary: &[Fingerprint],
+    secondary: Result<Vec<Fingerprint>, Error>,
+) -> bool {
+    let secondary = match secondary {
+        Ok(secondary) => secondary,
+        Err(e) => {
+            warn!(%indexer, kind, error = ?e, "failed to verify against indexer");
+            return false;
+        }
+    };
+    let discrepancies = compare(primary, &secondary);
+    for discrepancy in &discrepancies {
+        match discrepancy {
+            Discrepancy::Mismatch(index) => {
+                warn!(%indexer, kind, index, "artifact differs from indexer")
+            }
+            Discrepancy::Missing(index) => {
+                warn!(%indexer, kind, index, "artifact missing from indexer")
+            }
+            Discrepancy::Unexpected(index) => {
+                warn!(%indexer, kind, index, "artifact only returned by indexer")
+            }
+        }
+    }
+    if discrepancies.is_empty() {
+        info!(%indexer, kind, artifacts = primary.len(), "verified against indexer");
+    }
+    discrepancies.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let a = fingerprint(1, &1u64);
+        let b = fingerprint(2, &2u64);
+        let c = fingerprint(4, &4u64);
+        let tampered = fingerprint(2, &3u64);
+
+        // Identical responses agree
+        assert!(compare(&[a, b], &[a, b]).is_empty());
+        assert!(compare(&[], &[]).is_empty());
+
+        // Differences are reported by index
+        assert_eq!(
+            compare(&[a, b, c], &[a, tampered]),
+            vec![Discrepancy::Mismatch(2), Discrepancy::Missing(4)]
+        );
+        assert_eq!(compare(&[b], &[a, b]), vec![Discrepancy::Unexpected(1)]);
+    }
+}
@@ -40,6 +40,12 @@
 //! inspector get notarization 100..110
 //! ```
 //!
+//! ## Get the nullification for view 75
+//!
+//! ```bash
+//! inspector get nullification 75
+//! ```
+//!
 //! ## Get the finalization for view 50
 //!
 //! ```bash
@@ -70,31 +76,84 @@
 //! inspector -- get block 0x65016ff40e824e21fffe903953c07b6d604dbcf39f681c62e7b3ed57ab1d1994
 //! ```
 //!
+//! ## Get the block with a digest starting with a (unique) prefix
+//!
+//! ```bash
+//! inspector -- get block 0x65016ff4
+//! ```
+//!
+//! _Prefixes must be at least 4 hex characters. If more than one block matches, the matching digests are printed instead._
+//!
+//! ## Cross-check a finalization against a second indexer
+//!
+//! ```bash
+//! inspector get finalization 50 --verify-against https://b.example.com
+//! ```
+//!
+//! _Fetches the same artifacts from the second indexer and compares their digests (blocks are compared by block digest), flagging any that differ or are missing from either indexer. Exits with a non-zero status if the indexers disagree, making it easy to detect a tampered or stale indexer._
+//!
+//! ## Inspect a network using BLAKE3 block digests
+//!
+//! ```bash
+//! inspector get block latest --digest-mode blake3
+//! ```
+//!
+//! ## Inspect a network described by a chain spec
+//!
+//! ```bash
+//! inspector get block latest --indexer http://localhost:8080 --spec spec.json
+//! ```
+//!
+//! _Takes the identity and digest mode from the chain spec written by `setup` (instead of `--identity` and `--digest-mode`)._
+//!
+//! ## Summarize the last 500 finalized blocks
+//!
+//! ```bash
+//! inspector stats --last 500
+//! ```
+//!
+//! _Prints the distribution of time between blocks, the average number of views per block (above 1.0 when views are nullified), and the number of blocks each participant led (using the genesis transcript served by the indexer)._
+//!
 //! ## Listen for consensus events
 //!
 //! ```bash
 //! inspector listen
 //! ```
+//!
+//! ## Listen for consensus events from multiple networks
+//!
+//! ```bash
+//! inspector listen --indexer https://a.example.com --identity <identity A> --indexer https://b.example.com --identity <identity B>
+//! ```
+//!
+//! _Each `--indexer` is paired with the `--identity` at the same position (or with the only `--identity`, if one is provided). Events are printed as they arrive, tagged with the indexer they came from._
 
-use alto_client::{
-    consensus::{Message, Payload},
-    Client, IndexQuery, Query,
-};
-use alto_types::Identity;
-use clap::{value_parser, Arg, Command};
+use alto_client::{consensus::Message, ClientBuilder, Error, IndexQuery, Query};
+use alto_types::{DigestMode, Identity, Payload, Spec};
+use clap::{value_parser, Arg, ArgMatches, Command};
 use commonware_codec::DecodeExt;
+use commonware_consensus::Viewable;
+use commonware_cryptography::Digestible;
 use commonware_parallel::Sequential;
 use commonware_utils::from_hex_formatted;
-use futures::StreamExt;
-use tracing::{info, warn, Level};
+use crosscheck::{fingerprint, report};
+use futures::{stream, StreamExt};
+use stats::Stats;
+use std::path::PathBuf;
+use tracing::{error, info, info_span, warn, Level};
 use utils::{
-    log_block, log_finalization, log_latency, log_notarization, log_seed, parse_index_query,
-    parse_query, IndexQueryKind, QueryKind,
+    log_block, log_finalization, log_latency, log_notarization, log_nullification, log_seed,
+    log_stats, parse_index_query, parse_query, QueryKind,
 };
 
+mod crosscheck;
+mod stats;
 mod utils;
 
 const DEFAULT_INDEXER: &str = "https://global.alto.exoware.xyz";
+/// Minimum number of views to request at once when collecting finalizations for `stats`.
+const MIN_STATS_WINDOW: u64 = 100;
+
 const DEFAULT_IDENTITY: &str = "945351b23f5c55bda9e928799b651368f67b789cd9d15123239ec1570f4adfcb9fcfb18c1f7d0216c408908fe3936960194e64f1cc541a4fd6149e197036c0bb69d860f630c73f8dc1dfc623c1aa13a776120e2ee1df929e8881668b4dd04198";
 
 #[tokio::main]
@@ -109,6 +168,21 @@ async fn main() {
                 .global(true)
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("digest-mode")
+                .long("digest-mode")
+                .value_parser(value_parser!(DigestMode))
+                .default_value("sha256")
+                .global(true)
+                .help("Hash function used to compute block digests (sha256 or blake3)"),
+        )
+        .arg(
+            Arg::new("spec")
+                .long("spec")
+                .value_parser(value_parser!(PathBuf))
+                .global(true)
+                .help("Chain spec (written by setup) providing the identity and digest mode (replaces --identity and --digest-mode)"),
+        )
         .subcommand(
             Command::new("listen")
                 .about("Listen for consensus messages")
@@ -117,14 +191,16 @@ async fn main() {
                         .long("indexer")
                         .value_parser(value_parser!(String))
                         .default_value(DEFAULT_INDEXER)
-                        .help("URL of the indexer to connect to"),
+                        .action(clap::ArgAction::Append)
+                        .help("URL of an indexer to connect to (repeat to listen to multiple networks)"),
                 )
                 .arg(
                     Arg::new("identity")
                         .long("identity")
                         .value_parser(value_parser!(String))
                         .default_value(DEFAULT_IDENTITY)
-                        .help("Hex-encoded public key of the identity"),
+                        .action(clap::ArgAction::Append)
+                        .help("Hex-encoded public key of the identity (one per indexer, or one for all indexers)"),
                 ),
         )
         .subcommand(
@@ -133,14 +209,20 @@ async fn main() {
                 .arg(
                     Arg::new("type")
                         .required(true)
-                        .value_parser(["seed", "notarization", "finalization", "block"])
+                        .value_parser([
+                            "seed",
+                            "notarization",
+                            "nullification",
+                            "finalization",
+                            "block",
+                        ])
                         .help("Type of data to retrieve"),
                 )
                 .arg(
                     Arg::new("query")
                         .required(true)
                         .value_parser(value_parser!(String))
-                        .help("Query parameter (e.g., 'latest', number, range like '23..45', or hex digest for block)"),
+                        .help("Query parameter (e.g., 'latest', number, range like '23..45', or hex digest (or unique digest prefix) for block)"),
                 )
                 .arg(
                     Arg::new("indexer")
@@ -156,6 +238,12 @@ async fn main() {
                         .default_value(DEFAULT_IDENTITY)
                         .help("Hex-encoded public key of the identity"),
                 )
+                .arg(
+                    Arg::new("verify-against")
+                        .long("verify-against")
+                        .value_parser(value_parser!(String))
+                        .help("URL of a second indexer to cross-check the returned artifacts against"),
+                )
                 .arg(
                     Arg::new("prepare")
                         .long("prepare")
@@ -164,6 +252,31 @@ async fn main() {
                         .action(clap::ArgAction::SetTrue),
                 ),
         )
+        .subcommand(
+            Command::new("stats")
+                .about("Summarize recent finalizations (block intervals, skipped views, and leaders)")
+                .arg(
+                    Arg::new("last")
+                        .long("last")
+                        .value_parser(value_parser!(u64))
+                        .default_value("100")
+                        .help("Number of finalized blocks to summarize"),
+                )
+                .arg(
+                    Arg::new("indexer")
+                        .long("indexer")
+                        .value_parser(value_parser!(String))
+                        .default_value(DEFAULT_INDEXER)
+                        .help("URL of the indexer to connect to"),
+                )
+                .arg(
+                    Arg::new("identity")
+                        .long("identity")
+                        .value_parser(value_parser!(String))
+                        .default_value(DEFAULT_IDENTITY)
+                        .help("Hex-encoded public key of the identity"),
+                ),
+        )
         .get_matches();
 
     let log_level = if matches.get_flag("verbose") {
@@ -174,157 +287,413 @@ async fn main() {
     tracing_subscriber::fmt().with_max_level(log_level).init();
 
     if let Some(matches) = matches.subcommand_matches("listen") {
-        let indexer = matches.get_one::<String>("indexer").unwrap();
-        let identity = matches.get_one::<String>("identity").unwrap();
-        let identity = from_hex_formatted(identity).expect("Failed to decode identity");
-        let identity = Identity::decode(identity.as_ref()).expect("Invalid identity");
-        let client = Client::new(indexer, identity, Sequential);
+        let indexers: Vec<&String> = matches.get_many::<String>("indexer").unwrap().collect();
+        let identities: Vec<&String> = matches.get_many::<String>("identity").unwrap().collect();
+        if identities.len() != 1 && identities.len() != indexers.len() {
+            panic!("Provide one --identity per --indexer (or a single --identity for all)");
+        }
+
+        // Connect to every indexer, tagging each stream with its indexer
+        let mut streams = Vec::with_capacity(indexers.len());
+        for (i, indexer) in indexers.into_iter().enumerate() {
+            let (identity, digest_mode) = network(matches, identities[i.min(identities.len() - 1)]);
+            let client = ClientBuilder::new(indexer, identity, Sequential)
+                .with_digest_mode(digest_mode)
+                .build();
+            let stream = client.listen().await.expect("Failed to connect to indexer");
+            let network = indexer.clone();
+            streams.push(
+                stream
+                    .map(move |message| (network.clone(), message))
+                    .boxed(),
+            );
+        }
 
-        let mut stream = client.listen().await.expect("Failed to connect to indexer");
+        // Interleave messages from all networks as they arrive
+        let mut stream = stream::select_all(streams);
         info!("listening for consensus messages...");
-        while let Some(message) = stream.next().await {
-            let message = message.expect("Failed to receive message");
+        while let Some((network, message)) = stream.next().await {
+            let _span = info_span!("network", indexer = %network).entered();
+            let message = match message {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!(error = ?e, "failed to receive message");
+                    continue;
+                }
+            };
             match message {
                 Message::Seed(seed) => log_seed(seed),
                 Message::Notarization(notarized) => log_notarization(notarized),
+                Message::Nullification(nullification) => log_nullification(nullification),
                 Message::Finalization(finalized) => log_finalization(finalized),
+                Message::Lagged(skipped) => warn!(skipped, "indexer dropped messages"),
             }
         }
     } else if let Some(matches) = matches.subcommand_matches("get") {
         let type_ = matches.get_one::<String>("type").unwrap();
         let query_str = matches.get_one::<String>("query").unwrap();
         let indexer = matches.get_one::<String>("indexer").unwrap();
-        let identity = matches.get_one::<String>("identity").unwrap();
-        let identity = from_hex_formatted(identity).expect("Failed to decode identity");
-        let identity = Identity::decode(identity.as_ref()).expect("Invalid identity");
-        let client = Client::new(indexer, identity, Sequential);
+        let (identity, digest_mode) =
+            network(matches, matches.get_one::<String>("identity").unwrap());
+        let client = ClientBuilder::new(indexer, identity, Sequential)
+            .with_digest_mode(digest_mode)
+            .build();
         let prepare_flag = matches.get_flag("prepare");
 
+        // Connect to the indexer to cross-check against (if any)
+        let other = matches.get_one::<String>("verify-against").map(|indexer| {
+            let client = ClientBuilder::new(indexer, identity, Sequential)
+                .with_digest_mode(digest_mode)
+                .build();
+            (indexer.as_str(), client)
+        });
+
         if prepare_flag {
             client.health().await.expect("Failed to prepare connection");
             info!("connection prepared");
         }
 
-        match type_.as_str() {
+        let verified = match type_.as_str() {
             "seed" => {
-                let query_kind = parse_index_query(query_str).expect("Invalid query");
-                match query_kind {
-                    IndexQueryKind::Single(query) => {
-                        let start = std::time::Instant::now();
-                        let seed = client.seed_get(query).await.expect("Failed to get seed");
-                        log_latency(start);
-                        log_seed(seed);
-                    }
-                    IndexQueryKind::Range(start_view, end_view) => {
-                        for view in start_view..end_view {
-                            let start = std::time::Instant::now();
-                            let query = IndexQuery::Index(view);
-                            match client.seed_get(query).await {
-                                Ok(seed) => {
-                                    log_latency(start);
-                                    log_seed(seed);
-                                }
-                                Err(e) => {
-                                    warn!(view, error=?e, "failed to get seed");
-                                }
+                let query = parse_index_query(query_str).expect("Invalid query");
+                let range = match query {
+                    IndexQuery::Range(start_view, end_view) => Some((start_view, end_view)),
+                    _ => None,
+                };
+                let start = std::time::Instant::now();
+                let seeds = match query {
+                    IndexQuery::Range(start_view, end_view) => client
+                        .seed_get_range(start_view, end_view)
+                        .await
+                        .expect("Failed to get seeds"),
+                    query => vec![client.seed_get(query).await.expect("Failed to get seed")],
+                };
+                log_latency(start);
+                let primary: Vec<_> = seeds
+                    .iter()
+                    .map(|seed| fingerprint(seed.view().get(), seed))
+                    .collect();
+                for seed in seeds {
+                    log_seed(seed);
+                }
+                match &other {
+                    Some((indexer, other)) => {
+                        let secondary = match range {
+                            Some((start_view, end_view)) => {
+                                other.seed_get_range(start_view, end_view).await
                             }
-                        }
+                            None => other
+                                .seed_get(IndexQuery::Index(primary[0].0))
+                                .await
+                                .map(|seed| vec![seed]),
+                        };
+                        let secondary = secondary.map(|seeds| {
+                            seeds
+                                .iter()
+                                .map(|seed| fingerprint(seed.view().get(), seed))
+                                .collect()
+                        });
+                        report(indexer, type_, &primary, secondary)
                     }
+                    None => true,
                 }
             }
             "notarization" => {
-                let query_kind = parse_index_query(query_str).expect("Invalid query");
-                match query_kind {
-                    IndexQueryKind::Single(query) => {
-                        let start = std::time::Instant::now();
-                        let notarized = client
-                            .notarized_get(query)
-                            .await
-                            .expect("Failed to get notarization");
-                        log_latency(start);
-                        log_notarization(notarized);
-                    }
-                    IndexQueryKind::Range(start_view, end_view) => {
-                        for view in start_view..end_view {
-                            let start = std::time::Instant::now();
-                            let query = IndexQuery::Index(view);
-                            match client.notarized_get(query).await {
-                                Ok(notarized) => {
-                                    log_latency(start);
-                                    log_notarization(notarized);
-                                }
-                                Err(e) => {
-                                    warn!(view, error=?e, "failed to get notarization");
-                                }
+                let query = parse_index_query(query_str).expect("Invalid query");
+                let range = match query {
+                    IndexQuery::Range(start_view, end_view) => Some((start_view, end_view)),
+                    _ => None,
+                };
+                let start = std::time::Instant::now();
+                let notarizations = match query {
+                    IndexQuery::Range(start_view, end_view) => client
+                        .notarized_get_range(start_view, end_view)
+                        .await
+                        .expect("Failed to get notarizations"),
+                    query => vec![client
+                        .notarized_get(query)
+                        .await
+                        .expect("Failed to get notarization")],
+                };
+                log_latency(start);
+                let primary: Vec<_> = notarizations
+                    .iter()
+                    .map(|notarized| fingerprint(notarized.proof.view().get(), notarized))
+                    .collect();
+                for notarized in notarizations {
+                    log_notarization(notarized);
+                }
+                match &other {
+                    Some((indexer, other)) => {
+                        let secondary = match range {
+                            Some((start_view, end_view)) => {
+                                other.notarized_get_range(start_view, end_view).await
                             }
-                        }
+                            None => other
+                                .notarized_get(IndexQuery::Index(primary[0].0))
+                                .await
+                                .map(|notarized| vec![notarized]),
+                        };
+                        let secondary = secondary.map(|notarizations| {
+                            notarizations
+                                .iter()
+                                .map(|notarized| {
+                                    fingerprint(notarized.proof.view().get(), notarized)
+                                })
+                                .collect()
+                        });
+                        report(indexer, type_, &primary, secondary)
                     }
+                    None => true,
                 }
             }
-            "finalization" => {
-                let query_kind = parse_index_query(query_str).expect("Invalid query");
-                match query_kind {
-                    IndexQueryKind::Single(query) => {
-                        let start = std::time::Instant::now();
-                        let finalized = client
-                            .finalized_get(query)
+            "nullification" => {
+                let query = parse_index_query(query_str).expect("Invalid query");
+                if matches!(query, IndexQuery::Range(_, _)) {
+                    panic!("Range queries are not supported for nullifications");
+                }
+                let start = std::time::Instant::now();
+                let nullification = client
+                    .nullification_get(query)
+                    .await
+                    .expect("Failed to get nullification");
+                log_latency(start);
+                let view = nullification.view().get();
+                let primary = [fingerprint(view, &nullification)];
+                log_nullification(nullification);
+                match &other {
+                    Some((indexer, other)) => {
+                        let secondary = other
+                            .nullification_get(IndexQuery::Index(view))
                             .await
-                            .expect("Failed to get finalization");
-                        log_latency(start);
-                        log_finalization(finalized);
+                            .map(|nullification| vec![fingerprint(view, &nullification)]);
+                        report(indexer, type_, &primary, secondary)
                     }
-                    IndexQueryKind::Range(start_view, end_view) => {
-                        for view in start_view..end_view {
-                            let start = std::time::Instant::now();
-                            let query = IndexQuery::Index(view);
-                            match client.finalized_get(query).await {
-                                Ok(finalized) => {
-                                    log_latency(start);
-                                    log_finalization(finalized);
-                                }
-                                Err(e) => {
-                                    warn!(view, error=?e, "failed to get finalization");
-                                }
+                    None => true,
+                }
+            }
+            "finalization" => {
+                let query = parse_index_query(query_str).expect("Invalid query");
+                let range = match query {
+                    IndexQuery::Range(start_view, end_view) => Some((start_view, end_view)),
+                    _ => None,
+                };
+                let start = std::time::Instant::now();
+                let finalizations = match query {
+                    IndexQuery::Range(start_view, end_view) => client
+                        .finalized_get_range(start_view, end_view)
+                        .await
+                        .expect("Failed to get finalizations"),
+                    query => vec![client
+                        .finalized_get(query)
+                        .await
+                        .expect("Failed to get finalization")],
+                };
+                log_latency(start);
+                let primary: Vec<_> = finalizations
+                    .iter()
+                    .map(|finalized| fingerprint(finalized.proof.view().get(), finalized))
+                    .collect();
+                for finalized in finalizations {
+                    log_finalization(finalized);
+                }
+                match &other {
+                    Some((indexer, other)) => {
+                        let secondary = match range {
+                            Some((start_view, end_view)) => {
+                                other.finalized_get_range(start_view, end_view).await
                             }
-                        }
+                            None => other
+                                .finalized_get(IndexQuery::Index(primary[0].0))
+                                .await
+                                .map(|finalized| vec![finalized]),
+                        };
+                        let secondary = secondary.map(|finalizations| {
+                            finalizations
+                                .iter()
+                                .map(|finalized| {
+                                    fingerprint(finalized.proof.view().get(), finalized)
+                                })
+                                .collect()
+                        });
+                        report(indexer, type_, &primary, secondary)
                     }
+                    None => true,
                 }
             }
             "block" => {
+                // Blocks are compared by digest (at their height)
                 let query_kind = parse_query(query_str).expect("Invalid query");
                 match query_kind {
                     QueryKind::Single(query) => {
                         let start = std::time::Instant::now();
-                        let payload = client.block_get(query).await.expect("Failed to get block");
+                        let payload = match client.block_get(query).await {
+                            Ok(payload) => payload,
+                            Err(Error::AmbiguousPrefix(digests)) => {
+                                for digest in digests {
+                                    warn!(?digest, "matching block");
+                                }
+                                panic!("Prefix matches multiple blocks");
+                            }
+                            Err(e) => panic!("Failed to get block: {e}"),
+                        };
                         log_latency(start);
+                        let block = payload.block();
+                        let height = block.height.get();
+                        let primary = [(height, block.digest())];
+
+                        // Finalized blocks are looked up by height (and others by digest)
+                        let counterpart = match &payload {
+                            Payload::Finalized(_) => Query::Index(height),
+                            Payload::Block(block) => Query::Digest(block.digest()),
+                        };
                         match payload {
                             Payload::Finalized(finalized) => log_finalization(*finalized),
-                            Payload::Block(block) => log_block(block),
+                            Payload::Block(block) => log_block(*block),
+                        }
+                        match &other {
+                            Some((indexer, other)) => {
+                                let secondary = other.block_get(counterpart).await.map(|payload| {
+                                    let block = payload.block();
+                                    vec![(block.height.get(), block.digest())]
+                                });
+                                report(indexer, type_, &primary, secondary)
+                            }
+                            None => true,
                         }
                     }
                     QueryKind::Range(start_height, end_height) => {
-                        for height in start_height..end_height {
-                            let start = std::time::Instant::now();
-                            let query = Query::Index(height);
-                            match client.block_get(query).await {
-                                Ok(payload) => {
-                                    log_latency(start);
-                                    match payload {
-                                        Payload::Finalized(finalized) => {
-                                            log_finalization(*finalized)
+                        let start = std::time::Instant::now();
+                        let blocks = match client.block_get_range(start_height, end_height).await {
+                            Ok(blocks) => {
+                                log_latency(start);
+                                blocks
+                            }
+                            Err(e) => {
+                                // Fall back to fetching each height (skipping any that fail)
+                                warn!(error = ?e, "failed to get blocks (fetching each height)");
+                                let mut blocks = Vec::new();
+                                for height in start_height..end_height {
+                                    let start = std::time::Instant::now();
+                                    match client.block_get(Query::Index(height)).await {
+                                        Ok(Payload::Finalized(finalized)) => {
+                                            log_latency(start);
+                                            blocks.push(*finalized);
+                                        }
+                                        Ok(Payload::Block(block)) => {
+                                            warn!(height, digest = ?block.digest(), "block not finalized");
+                                        }
+                                        Err(e) => {
+                                            warn!(height, error = ?e, "failed to get block");
                                         }
-                                        Payload::Block(block) => log_block(block),
                                     }
                                 }
-                                Err(e) => {
-                                    warn!(height, error=?e, "failed to get block");
-                                }
+                                blocks
                             }
+                        };
+                        let primary: Vec<_> = blocks
+                            .iter()
+                            .map(|finalized| {
+                                (finalized.block.height.get(), finalized.block.digest())
+                            })
+                            .collect();
+                        for finalized in blocks {
+                            log_finalization(finalized);
+                        }
+                        match &other {
+                            Some((indexer, other)) => {
+                                let secondary = other
+                                    .block_get_range(start_height, end_height)
+                                    .await
+                                    .map(|blocks| {
+                                        blocks
+                                            .iter()
+                                            .map(|finalized| {
+                                                (
+                                                    finalized.block.height.get(),
+                                                    finalized.block.digest(),
+                                                )
+                                            })
+                                            .collect()
+                                    });
+                                report(indexer, type_, &primary, secondary)
+                            }
+                            None => true,
                         }
                     }
                 }
             }
             _ => unreachable!(),
+        };
+        if !verified {
+            error!("indexers disagree");
+            std::process::exit(1);
+        }
+    } else if let Some(matches) = matches.subcommand_matches("stats") {
+        let last = *matches.get_one::<u64>("last").unwrap();
+        let indexer = matches.get_one::<String>("indexer").unwrap();
+        let (identity, digest_mode) =
+            network(matches, matches.get_one::<String>("identity").unwrap());
+        let client = ClientBuilder::new(indexer, identity, Sequential)
+            .with_digest_mode(digest_mode)
+            .build();
+
+        // Walk back from the latest finalization until enough blocks are collected
+        let latest = client
+            .finalized_get(IndexQuery::Latest)
+            .await
+            .expect("Failed to get latest finalization");
+        let latest_view = latest.proof.view().get();
+        let mut finalized = Vec::new();
+        let mut end = latest_view + 1;
+        while (finalized.len() as u64) < last && end > 0 {
+            let needed = last - finalized.len() as u64;
+            let start = end.saturating_sub(needed.max(MIN_STATS_WINDOW));
+            let batch = client
+                .finalized_get_range(start, end)
+                .await
+                .expect("Failed to get finalizations");
+            finalized.extend(batch);
+            end = start;
         }
+        finalized.sort_by_key(|finalized| std::cmp::Reverse(finalized.block.height));
+        finalized.truncate(last as usize);
+
+        // Fetch the seeds (and participants) needed to elect the leader of each view
+        let first_view = finalized
+            .iter()
+            .map(|finalized| finalized.proof.view().get())
+            .min()
+            .unwrap_or(latest_view);
+        let seeds = client
+            .seed_get_range(first_view.saturating_sub(1), latest_view)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = ?e, "failed to get seeds (leaders unknown)");
+                Vec::new()
+            });
+        let participants = match client.genesis_get().await {
+            Ok(genesis) => Some(genesis.participants),
+            Err(e) => {
+                warn!(error = ?e, "failed to get genesis (leaders unknown)");
+                None
+            }
+        };
+        let n = participants.as_ref().map(|p| p.len() as u32);
+        log_stats(Stats::new(finalized, &seeds, n), participants.as_deref());
+    }
+}
+
+/// Get the identity and digest mode of the network from the chain spec (if `--spec` is
+/// provided) or from `identity` and `--digest-mode`.
+fn network(matches: &ArgMatches, identity: &str) -> (Identity, DigestMode) {
+    if let Some(path) = matches.get_one::<PathBuf>("spec") {
+        let spec = Spec::load(path).expect("Failed to read chain spec");
+        let genesis = spec.genesis().expect("Invalid chain spec");
+        return (genesis.identity, spec.digest_mode);
     }
+    let identity = from_hex_formatted(identity).expect("Failed to decode identity");
+    let identity = Identity::decode(identity.as_ref()).expect("Invalid identity");
+    let digest_mode = *matches.get_one::<DigestMode>("digest-mode").unwrap();
+    (identity, digest_mode)
 }
@@ -0,0 +1,165 @@
+//! Summarize the health of a network from its recent finalizations.
+
+use alto_types::{Finalized, Seed, EPOCH};
+use commonware_consensus::{
+    simplex::elector::Random,
+    types::{Round, View},
+    Viewable,
+};
+use commonware_cryptography::bls12381::primitives::variant::MinSig;
+use std::collections::{BTreeMap, HashMap};
+
+/// Distribution of a set of values.
+pub struct Distribution {
+    pub min: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+    pub mean: u64,
+}
+
+impl Distribution {
+    /// Summarize `values` (if any).
+    pub fn new(mut values: Vec<u64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+        let percentile = |p: usize| values[(values.len() - 1) * p / 100];
+        Some(Self {
+            min: values[0],
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: values[values.len() - 1],
+            mean: values.iter().sum::<u64>() / values.len() as u64,
+        })
+    }
+}
+
+/// Statistics over a window of finalized blocks.
+pub struct Stats {
+    /// Number of finalized blocks in the window.
+    pub blocks: usize,
+
+    /// Heights of the first and last block in the window.
+    pub heights: (u64, u64),
+
+    /// Milliseconds between the timestamps of blocks at consecutive heights.
+    pub intervals: Option<Distribution>,
+
+    /// Average number of views per block (1.0 if no view was skipped).
+    pub views_per_block: Option<f64>,
+
+    /// Number of blocks proposed by each participant (keyed by index), if the leader of each
+    /// view could be elected.
+    pub leaders: BTreeMap<u32, usize>,
+}
+
+impl Stats {
+    /// Compute [Stats] over `finalized` (in any order), electing the leader of each block's
+    /// view from `seeds` (if `participants` is known).
+    pub fn new(mut finalized: Vec<Finalized>, seeds: &[Seed], participants: Option<u32>) -> Self {
+        finalized.sort_by_key(|finalized| finalized.block.height);
+        finalized.dedup_by_key(|finalized| finalized.block.height);
+
+        // Measure time (and views) between blocks
+        let mut intervals = Vec::new();
+        let mut views = 0;
+        let mut heights = 0;
+        for pair in finalized.windows(2) {
+            let (previous, next) = (&pair[0], &pair[1]);
+            let height = next.block.height.get() - previous.block.height.get();
+            if height == 1 {
+                intervals.push(
+                    next.block
+                        .timestamp
+                        .saturating_sub(previous.block.timestamp),
+                );
+            }
+            views += next
+                .proof
+                .view()
+                .get()
+                .saturating_sub(previous.proof.view().get());
+            heights += height;
+        }
+
+        // Elect the leader of each view (using the seed of the previous view)
+        let mut leaders = BTreeMap::new();
+        if let Some(participants) = participants.filter(|n| *n > 0) {
+            let seeds: HashMap<_, _> = seeds
+                .iter()
+                .map(|seed| (seed.view().get(), seed.signature))
+                .collect();
+            for finalized in &finalized {
+                let view = finalized.proof.view().get();
+                let seed = seeds.get(&view.saturating_sub(1)).copied();
+                if seed.is_none() && view != 1 {
+                    continue;
+                }
+                let round = Round::new(EPOCH, View::new(view));
+                let leader = Random::select_leader::<MinSig>(round, participants, seed);
+                *leaders.entry(leader.get()).or_default() += 1;
+            }
+        }
+
+        Self {
+            blocks: finalized.len(),
+            heights: (
+                finalized.first().map_or(0, |f| f.block.height.get()),
+                finalized.last().map_or(0, |f| f.block.height.get()),
+            ),
+            intervals: Distribution::new(intervals),
+            views_per_block: (heights > 0).then(|| views as f64 / heights as f64),
+            leaders,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alto_types::{fixtures, Block, Seedable};
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Digestible, Hasher, Sha256};
+
+    #[test]
+    fn test_stats() {
+        let fixtures::Fixture { schemes, .. } = fixtures::seeded(0, 4);
+
+        // Finalize heights 1..=4 at views 1, 2, 4, 5 (skipping view 3)
+        let mut parent = Sha256::hash(b"genesis");
+        let mut finalized = Vec::new();
+        let mut seeds = Vec::new();
+        for (height, view) in [(1, 1), (2, 2), (3, 4), (4, 5)] {
+            let block = Block::new(parent, Height::new(height), height * 1000);
+            parent = block.digest();
+            let block = fixtures::finalized(&schemes, view, block);
+            seeds.push(block.proof.seed());
+            finalized.push(block);
+        }
+        finalized.reverse();
+
+        let stats = Stats::new(finalized, &seeds, Some(4));
+        assert_eq!(stats.blocks, 4);
+        assert_eq!(stats.heights, (1, 4));
+        let intervals = stats.intervals.unwrap();
+        assert_eq!(
+            (intervals.min, intervals.max, intervals.mean),
+            (1000, 1000, 1000)
+        );
+        assert_eq!(stats.views_per_block, Some(4.0 / 3.0));
+
+        // Leaders are only elected for views with a known seed (the seed of view 3 is missing)
+        assert_eq!(stats.leaders.values().sum::<usize>(), 3);
+
+        // Leaders are unknown without participants
+        let stats = Stats::new(Vec::new(), &seeds, None);
+        assert_eq!(stats.blocks, 0);
+        assert!(stats.intervals.is_none());
+        assert!(stats.views_per_block.is_none());
+        assert!(stats.leaders.is_empty());
+    }
+}
@@ -1,5 +1,6 @@
+use crate::stats::Stats;
 use alto_client::{IndexQuery, Query};
-use alto_types::{Finalized, Notarized, Seed};
+use alto_types::{Finalized, Notarized, Nullification, PublicKey, Seed, SeedExt};
 use commonware_codec::DecodeExt;
 use commonware_consensus::Viewable;
 use commonware_cryptography::{sha256::Digest, Digestible};
@@ -8,24 +9,19 @@ use std::time;
 use tracing::{debug, info};
 
 // Define enums for query kinds
-pub enum IndexQueryKind {
-    Single(IndexQuery),
-    Range(u64, u64),
-}
-
 pub enum QueryKind {
     Single(Query),
     Range(u64, u64),
 }
 
 // Parse IndexQuery for seed, notarization, and finalization
-pub fn parse_index_query(query: &str) -> Option<IndexQueryKind> {
+pub fn parse_index_query(query: &str) -> Option<IndexQuery> {
     if query == "latest" {
-        Some(IndexQueryKind::Single(IndexQuery::Latest))
+        Some(IndexQuery::Latest)
     } else if let Some((start, end)) = parse_range(query) {
-        Some(IndexQueryKind::Range(start, end))
+        Some(IndexQuery::Range(start, end))
     } else if let Ok(index) = query.parse::<u64>() {
-        Some(IndexQueryKind::Single(IndexQuery::Index(index)))
+        Some(IndexQuery::Index(index))
     } else {
         None
     }
@@ -40,9 +36,12 @@ pub fn parse_query(query: &str) -> Option<QueryKind> {
     } else if let Ok(index) = query.parse::<u64>() {
         Some(QueryKind::Single(Query::Index(index)))
     } else {
-        let bytes = commonware_utils::from_hex(query)?;
-        let digest = Digest::decode(bytes.as_ref()).ok()?;
-        Some(QueryKind::Single(Query::Digest(digest)))
+        let hex = query.strip_prefix("0x").unwrap_or(query);
+        match commonware_utils::from_hex(hex).and_then(|bytes| Digest::decode(bytes.as_ref()).ok())
+        {
+            Some(digest) => Some(QueryKind::Single(Query::Digest(digest))),
+            None => Query::prefix(hex).map(QueryKind::Single),
+        }
     }
 }
 
@@ -85,7 +84,13 @@ pub fn format_age(age: u64) -> String {
 }
 
 pub fn log_seed(seed: Seed) {
-    info!(view = %seed.view(), signature = ?seed.signature, "seed");
+    let info = seed.info();
+    info!(
+        view = info.view,
+        signature = %info.signature,
+        randomness = %info.randomness,
+        "seed"
+    );
 }
 
 pub fn log_notarization(notarized: Notarized) {
@@ -95,13 +100,20 @@ pub fn log_notarization(notarized: Notarized) {
     info!(
         view = %notarized.proof.view(),
         height = %notarized.block.height,
+        rules = notarized.block.rules,
         timestamp = notarized.block.timestamp,
+        transactions = notarized.block.transactions.len(),
+        receipts = notarized.block.receipts.len(),
         age = %age_str,
         digest = ?notarized.block.digest(),
         "notarized"
     );
 }
 
+pub fn log_nullification(nullification: Nullification) {
+    info!(view = %nullification.view(), "nullified");
+}
+
 pub fn log_finalization(finalized: Finalized) {
     let now = time::SystemTime::now().epoch_millis();
     let age_ms = now.saturating_sub(finalized.block.timestamp);
@@ -109,7 +121,10 @@ pub fn log_finalization(finalized: Finalized) {
     info!(
         view = %finalized.proof.view(),
         height = %finalized.block.height,
+        rules = finalized.block.rules,
         timestamp = finalized.block.timestamp,
+        transactions = finalized.block.transactions.len(),
+        receipts = finalized.block.receipts.len(),
         age = %age_str,
         digest = ?finalized.block.digest(),
         "finalized"
@@ -122,7 +137,10 @@ pub fn log_block(block: alto_types::Block) {
     let age_str = format_age(age_ms);
     info!(
         height = %block.height,
+        rules = block.rules,
         timestamp = block.timestamp,
+        transactions = block.transactions.len(),
+        receipts = block.receipts.len(),
         age = %age_str,
         digest = ?block.digest(),
         "block"
@@ -135,3 +153,44 @@ pub fn log_latency(start: time::Instant) {
     let elapsed_str = format_age(elapsed_ms as u64);
     debug!(elapsed = %elapsed_str, "latency");
 }
+
+pub fn log_stats(stats: Stats, participants: Option<&[PublicKey]>) {
+    info!(
+        blocks = stats.blocks,
+        first_height = stats.heights.0,
+        last_height = stats.heights.1,
+        "finalized"
+    );
+    if let Some(intervals) = stats.intervals {
+        info!(
+            min = %format_age(intervals.min),
+            p50 = %format_age(intervals.p50),
+            p90 = %format_age(intervals.p90),
+            p99 = %format_age(intervals.p99),
+            max = %format_age(intervals.max),
+            mean = %format_age(intervals.mean),
+            "block interval"
+        );
+    }
+    if let Some(views_per_block) = stats.views_per_block {
+        info!(
+            views_per_block = %format!("{views_per_block:.2}"),
+            "gap ratio"
+        );
+    }
+    let elected: usize = stats.leaders.values().sum();
+    for (index, blocks) in stats.leaders {
+        let leader = participants
+            .and_then(|participants| participants.get(index as usize))
+            .map(|leader| leader.to_string())
+            .unwrap_or_default();
+        let share = 100.0 * blocks as f64 / elected as f64;
+        info!(
+            index,
+            %leader,
+            blocks,
+            share = %format!("{share:.1}%"),
+            "leader"
+        );
+    }
+}
@@ -1,13 +1,111 @@
-use crate::consensus::{Finalization, Notarization, Scheme};
-use bytes::{Buf, BufMut};
-use commonware_codec::{varint::UInt, EncodeSize, Error, Read, ReadExt, Write};
-use commonware_consensus::{types::Height, Heightable};
-use commonware_cryptography::{sha256::Digest, Committable, Digestible, Hasher, Sha256};
+use crate::{
+    consensus::{Finalization, Notarization, Scheme},
+    Transaction, MAX_BLOCK_TRANSACTIONS,
+};
+use bytes::{Buf, BufMut, Bytes};
+use commonware_codec::{varint::UInt, EncodeSize, Error, RangeCfg, Read, ReadExt, Write};
+use commonware_consensus::{simplex::types::Subject, types::Height, Heightable};
+use commonware_cryptography::{
+    certificate::Scheme as _, sha256::Digest, Blake3, Committable, Digestible, Hasher, Sha256,
+};
 use commonware_parallel::Strategy;
+use commonware_utils::N3f1;
 use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Hash function used to compute block digests.
+///
+/// The mode is fixed for a network (all participants must agree on it) and is required to
+/// decode blocks (and any type containing them). Regardless of mode, digests are 32 bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestMode {
+    /// SHA-256 (the default).
+    #[default]
+    Sha256,
+
+    /// BLAKE3 (cheaper to verify in circuits used by downstream proving systems).
+    Blake3,
+}
+
+impl DigestMode {
+    /// Compute the digest of a block header (committing to the digest of each of its
+    /// `transactions`, hashed with the same function as the header, and to its `receipts`).
+    fn digest(
+        &self,
+        rules: u16,
+        parent: &Digest,
+        height: Height,
+        timestamp: u64,
+        transactions: &[Transaction],
+        receipts: &[u8],
+    ) -> Digest {
+        match self {
+            Self::Sha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update(&rules.to_be_bytes());
+                hasher.update(parent);
+                hasher.update(&height.get().to_be_bytes());
+                hasher.update(&timestamp.to_be_bytes());
+                hasher.update(&(transactions.len() as u64).to_be_bytes());
+                for transaction in transactions {
+                    hasher.update(&transaction.digest());
+                }
+                hasher.update(&(receipts.len() as u64).to_be_bytes());
+                hasher.update(receipts);
+                hasher.finalize()
+            }
+            Self::Blake3 => {
+                let mut hasher = Blake3::new();
+                hasher.update(&rules.to_be_bytes());
+                hasher.update(parent);
+                hasher.update(&height.get().to_be_bytes());
+                hasher.update(&timestamp.to_be_bytes());
+                hasher.update(&(transactions.len() as u64).to_be_bytes());
+                for transaction in transactions {
+                    hasher.update(&Blake3::hash(transaction.data()));
+                }
+                hasher.update(&(receipts.len() as u64).to_be_bytes());
+                hasher.update(receipts);
+                Digest::from(hasher.finalize().0)
+            }
+        }
+    }
+}
+
+impl fmt::Display for DigestMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sha256 => write!(f, "sha256"),
+            Self::Blake3 => write!(f, "blake3"),
+        }
+    }
+}
+
+impl FromStr for DigestMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha256" => Ok(Self::Sha256),
+            "blake3" => Ok(Self::Blake3),
+            _ => Err(format!("unknown digest mode: {s}")),
+        }
+    }
+}
+
+/// Version of the validity rules in effect at genesis.
+pub const INITIAL_RULES: u16 = 0;
+
+/// The maximum size (in bytes) of a [Block]'s receipts.
+pub const MAX_RECEIPTS_SIZE: usize = 64 * 1024;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Block {
+    /// Version of the validity rules the block was built (and must be verified) under.
+    pub rules: u16,
+
     /// The parent block's digest.
     pub parent: Digest,
 
@@ -17,25 +115,82 @@ pub struct Block {
     /// The timestamp of the block (in milliseconds since the Unix epoch).
     pub timestamp: u64,
 
+    /// The transactions included in the block (in order).
+    pub transactions: Vec<Transaction>,
+
+    /// Opaque output of executing the block's transactions (empty unless the network installs
+    /// an executor).
+    pub receipts: Bytes,
+
     /// Pre-computed digest of the block.
     digest: Digest,
 }
 
 impl Block {
-    fn compute_digest(parent: &Digest, height: Height, timestamp: u64) -> Digest {
-        let mut hasher = Sha256::new();
-        hasher.update(parent);
-        hasher.update(&height.get().to_be_bytes());
-        hasher.update(&timestamp.to_be_bytes());
-        hasher.finalize()
+    /// Create a new block (with a [DigestMode::Sha256] digest and the [INITIAL_RULES]).
+    pub fn new(parent: Digest, height: Height, timestamp: u64) -> Self {
+        Self::new_with_mode(DigestMode::Sha256, parent, height, timestamp)
     }
 
-    pub fn new(parent: Digest, height: Height, timestamp: u64) -> Self {
-        let digest = Self::compute_digest(&parent, height, timestamp);
+    /// Create a new block (with the [INITIAL_RULES]) with a digest computed using `mode`.
+    pub fn new_with_mode(mode: DigestMode, parent: Digest, height: Height, timestamp: u64) -> Self {
+        Self::new_with_rules(mode, INITIAL_RULES, parent, height, timestamp)
+    }
+
+    /// Create a new block (without transactions) built under version `rules` of the validity
+    /// rules with a digest computed using `mode`.
+    pub fn new_with_rules(
+        mode: DigestMode,
+        rules: u16,
+        parent: Digest,
+        height: Height,
+        timestamp: u64,
+    ) -> Self {
+        Self::new_with_transactions(mode, rules, parent, height, timestamp, Vec::new())
+    }
+
+    /// Create a new block including `transactions` (without receipts) built under version
+    /// `rules` of the validity rules with a digest computed using `mode`.
+    pub fn new_with_transactions(
+        mode: DigestMode,
+        rules: u16,
+        parent: Digest,
+        height: Height,
+        timestamp: u64,
+        transactions: Vec<Transaction>,
+    ) -> Self {
+        Self::new_with_receipts(
+            mode,
+            rules,
+            parent,
+            height,
+            timestamp,
+            transactions,
+            Bytes::new(),
+        )
+    }
+
+    /// Create a new block including `transactions` and the `receipts` of executing them built
+    /// under version `rules` of the validity rules with a digest computed using `mode`.
+    ///
+    /// `receipts` should not exceed [MAX_RECEIPTS_SIZE] (or the block will fail to decode).
+    pub fn new_with_receipts(
+        mode: DigestMode,
+        rules: u16,
+        parent: Digest,
+        height: Height,
+        timestamp: u64,
+        transactions: Vec<Transaction>,
+        receipts: Bytes,
+    ) -> Self {
+        let digest = mode.digest(rules, &parent, height, timestamp, &transactions, &receipts);
         Self {
+            rules,
             parent,
             height,
             timestamp,
+            transactions,
+            receipts,
             digest,
         }
     }
@@ -43,26 +198,37 @@ impl Block {
 
 impl Write for Block {
     fn write(&self, writer: &mut impl BufMut) {
+        UInt(self.rules).write(writer);
         self.parent.write(writer);
         self.height.write(writer);
         UInt(self.timestamp).write(writer);
+        self.transactions.write(writer);
+        self.receipts.write(writer);
     }
 }
 
 impl Read for Block {
-    type Cfg = ();
+    type Cfg = DigestMode;
 
-    fn read_cfg(reader: &mut impl Buf, _: &Self::Cfg) -> Result<Self, Error> {
+    fn read_cfg(reader: &mut impl Buf, mode: &Self::Cfg) -> Result<Self, Error> {
+        let rules = UInt::read(reader)?.into();
         let parent = Digest::read(reader)?;
         let height = Height::read(reader)?;
         let timestamp = UInt::read(reader)?.into();
+        let range: RangeCfg<usize> = (..=MAX_BLOCK_TRANSACTIONS).into();
+        let transactions = Vec::<Transaction>::read_cfg(reader, &(range, ()))?;
+        let range: RangeCfg<usize> = (..=MAX_RECEIPTS_SIZE).into();
+        let receipts = Bytes::read_cfg(reader, &range)?;
 
         // Pre-compute the digest
-        let digest = Self::compute_digest(&parent, height, timestamp);
+        let digest = mode.digest(rules, &parent, height, timestamp, &transactions, &receipts);
         Ok(Self {
+            rules,
             parent,
             height,
             timestamp,
+            transactions,
+            receipts,
 
             digest,
         })
@@ -71,7 +237,12 @@ impl Read for Block {
 
 impl EncodeSize for Block {
     fn encode_size(&self) -> usize {
-        self.parent.encode_size() + self.height.encode_size() + UInt(self.timestamp).encode_size()
+        UInt(self.rules).encode_size()
+            + self.parent.encode_size()
+            + self.height.encode_size()
+            + UInt(self.timestamp).encode_size()
+            + self.transactions.encode_size()
+            + self.receipts.encode_size()
     }
 }
 
@@ -115,11 +286,11 @@ impl Write for Notarized {
 }
 
 impl Read for Notarized {
-    type Cfg = ();
+    type Cfg = DigestMode;
 
-    fn read_cfg(buf: &mut impl Buf, _: &Self::Cfg) -> Result<Self, Error> {
+    fn read_cfg(buf: &mut impl Buf, mode: &Self::Cfg) -> Result<Self, Error> {
         let proof = Notarization::read(buf)?;
-        let block = Block::read(buf)?;
+        let block = Block::read_cfg(buf, mode)?;
 
         // Ensure the proof is for the block
         if proof.proposal.payload != block.digest() {
@@ -152,6 +323,45 @@ impl Finalized {
     pub fn verify(&self, scheme: &Scheme, strategy: &impl Strategy) -> bool {
         self.proof.verify(&mut OsRng, scheme, strategy)
     }
+
+    /// Verify the proofs of all `finalized` blocks (signed by `scheme`) with a single aggregated
+    /// check, returning the indices of the invalid ones (empty if all are valid).
+    ///
+    /// If the aggregated check fails, each proof is verified individually to identify the
+    /// offenders (so a batch containing an invalid proof costs more than verifying each proof).
+    pub fn verify_batch(
+        scheme: &Scheme,
+        strategy: &impl Strategy,
+        finalized: &[Finalized],
+    ) -> Vec<usize> {
+        let finalized: Vec<&Finalized> = finalized.iter().collect();
+        Self::verify_all(scheme, strategy, &finalized)
+    }
+
+    /// Like [Finalized::verify_batch], but over references.
+    pub(crate) fn verify_all(
+        scheme: &Scheme,
+        strategy: &impl Strategy,
+        finalized: &[&Finalized],
+    ) -> Vec<usize> {
+        let certificates = finalized.iter().map(|finalized| {
+            (
+                Subject::Finalize {
+                    proposal: &finalized.proof.proposal,
+                },
+                &finalized.proof.certificate,
+            )
+        });
+        if scheme.verify_certificates::<_, Digest, _, N3f1>(&mut OsRng, certificates, strategy) {
+            return Vec::new();
+        }
+        finalized
+            .iter()
+            .enumerate()
+            .filter(|(_, finalized)| !finalized.verify(scheme, strategy))
+            .map(|(index, _)| index)
+            .collect()
+    }
 }
 
 impl Write for Finalized {
@@ -162,11 +372,11 @@ impl Write for Finalized {
 }
 
 impl Read for Finalized {
-    type Cfg = ();
+    type Cfg = DigestMode;
 
-    fn read_cfg(buf: &mut impl Buf, _: &Self::Cfg) -> Result<Self, Error> {
+    fn read_cfg(buf: &mut impl Buf, mode: &Self::Cfg) -> Result<Self, Error> {
         let proof = Finalization::read(buf)?;
-        let block = Block::read(buf)?;
+        let block = Block::read_cfg(buf, mode)?;
 
         // Ensure the proof is for the block
         if proof.proposal.payload != block.digest() {
@@ -185,6 +395,110 @@ impl EncodeSize for Finalized {
     }
 }
 
+/// A block returned by a lookup.
+///
+/// Lookups by height (or of the latest block) return the [Finalized] block (which carries its
+/// proof), while lookups by digest return the bare [Block]. Both variants are boxed so that the
+/// response stays pointer-sized when moved through handlers and streams.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged))]
+pub enum Payload {
+    Finalized(Box<Finalized>),
+    Block(Box<Block>),
+}
+
+impl Payload {
+    /// The block returned by the lookup.
+    pub fn block(&self) -> &Block {
+        match self {
+            Self::Finalized(finalized) => &finalized.block,
+            Self::Block(block) => block,
+        }
+    }
+}
+
+impl From<Finalized> for Payload {
+    fn from(finalized: Finalized) -> Self {
+        Self::Finalized(Box::new(finalized))
+    }
+}
+
+impl From<Block> for Payload {
+    fn from(block: Block) -> Self {
+        Self::Block(Box::new(block))
+    }
+}
+
+/// The maximum number of headers included in a [Consistency] proof.
+pub const MAX_CONSISTENCY_HEADERS: usize = 4_096;
+
+/// Proof that a finalized block descends from a block at a lower height.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Consistency {
+    /// The finalized block at the upper height.
+    pub finalized: Finalized,
+
+    /// The ancestors of the finalized block (in ascending height order), starting
+    /// at the lower height.
+    pub headers: Vec<Block>,
+}
+
+impl Consistency {
+    pub fn new(finalized: Finalized, headers: Vec<Block>) -> Self {
+        Self { finalized, headers }
+    }
+
+    /// The block at the lower height.
+    pub fn from(&self) -> &Block {
+        self.headers.first().unwrap_or(&self.finalized.block)
+    }
+
+    /// The finalized block at the upper height.
+    pub fn to(&self) -> &Block {
+        &self.finalized.block
+    }
+
+    /// Verify the finalization and that each header is the parent of the next (ending
+    /// with the finalized block).
+    pub fn verify(&self, scheme: &Scheme, strategy: &impl Strategy) -> bool {
+        if !self.finalized.verify(scheme, strategy) {
+            return false;
+        }
+        let mut child = &self.finalized.block;
+        for header in self.headers.iter().rev() {
+            if child.parent != header.digest() || child.height != header.height.next() {
+                return false;
+            }
+            child = header;
+        }
+        true
+    }
+}
+
+impl Write for Consistency {
+    fn write(&self, buf: &mut impl BufMut) {
+        self.finalized.write(buf);
+        self.headers.write(buf);
+    }
+}
+
+impl Read for Consistency {
+    type Cfg = DigestMode;
+
+    fn read_cfg(buf: &mut impl Buf, mode: &Self::Cfg) -> Result<Self, Error> {
+        let finalized = Finalized::read_cfg(buf, mode)?;
+        let range: RangeCfg<usize> = (..=MAX_CONSISTENCY_HEADERS).into();
+        let headers = Vec::<Block>::read_cfg(buf, &(range, *mode))?;
+        Ok(Self { finalized, headers })
+    }
+}
+
+impl EncodeSize for Consistency {
+    fn encode_size(&self) -> usize {
+        self.finalized.encode_size() + self.headers.encode_size()
+    }
+}
+
 impl commonware_consensus::Block for Block {
     fn parent(&self) -> Digest {
         self.parent
@@ -0,0 +1,369 @@
+//! JavaScript client for the indexer (fetching artifacts and verifying them before they are
+//! returned).
+//!
+//! [Client] issues requests with the `fetch` and `WebSocket` APIs of the browser (or web
+//! worker) it runs in and verifies every artifact with a [Worker], so callers only ever see
+//! artifacts signed by the network (in their canonical JSON form, see [crate::json]).
+
+use crate::{json::Canonical, wasm::Worker, Block, Finalized, Payload, Subscription};
+use commonware_codec::{Decode, DecodeExt, FixedSize};
+use commonware_consensus::Viewable;
+use commonware_cryptography::{sha256::Digest, Digestible};
+use commonware_utils::{from_hex, hex};
+use js_sys::{ArrayBuffer, Function, Promise, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{
+    BinaryType, CloseEvent, MessageEvent, Response, WebSocket, Window, WorkerGlobalScope,
+};
+
+/// Artifact to get from the indexer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Query {
+    Latest,
+    /// A height (for blocks) or view (for finalizations).
+    Index(u64),
+    Digest(Digest),
+}
+
+impl Query {
+    /// Parse `latest`, a decimal index, or a hex-encoded digest (with an optional `0x`).
+    fn parse(query: &str) -> Option<Self> {
+        if query == "latest" {
+            return Some(Self::Latest);
+        }
+        let digest = query.strip_prefix("0x").unwrap_or(query);
+        if digest.len() == Digest::SIZE * 2 {
+            let digest = from_hex(digest)?;
+            return Digest::decode(digest.as_ref()).ok().map(Self::Digest);
+        }
+        query.parse().ok().map(Self::Index)
+    }
+
+    /// Convert a query from JavaScript (`undefined` or `null` for the latest artifact, a number
+    /// or bigint for an index, or a string accepted by [Query::parse]).
+    fn from_js(query: JsValue) -> Option<Self> {
+        if query.is_undefined() || query.is_null() {
+            return Some(Self::Latest);
+        }
+        if let Some(query) = query.as_string() {
+            return Self::parse(&query);
+        }
+        if let Some(index) = query.as_f64() {
+            let safe = index >= 0.0 && index.fract() == 0.0 && index <= MAX_SAFE_INTEGER;
+            return safe.then_some(Self::Index(index as u64));
+        }
+        u64::try_from(query).ok().map(Self::Index)
+    }
+
+    /// Serialize the query as a path segment of the indexer API.
+    fn serialize(&self) -> String {
+        match self {
+            Self::Latest => "latest".to_string(),
+            Self::Index(index) => hex(&index.to_be_bytes()),
+            Self::Digest(digest) => hex(digest),
+        }
+    }
+}
+
+/// Largest integer a JavaScript number represents exactly.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// Construct a JavaScript error (to reject a promise with).
+fn error(message: impl AsRef<str>) -> JsValue {
+    JsError::new(message.as_ref()).into()
+}
+
+/// Fetches (and verifies) artifacts from an indexer.
+///
+/// Subscriptions verify events with the identity schedule of the client when they are opened,
+/// so rotate (or reconfigure) the client before subscribing.
+#[wasm_bindgen]
+pub struct Client {
+    uri: String,
+    ws_uri: String,
+    worker: Worker,
+}
+
+#[wasm_bindgen]
+impl Client {
+    /// Create a [Client] for the indexer at `uri` (starting with `http://` or `https://`) of the
+    /// network with `identity` (and an optional digest mode).
+    ///
+    /// Returns `undefined` if the URI, identity, or digest mode is invalid.
+    pub fn init_with_identity(
+        uri: String,
+        identity: Vec<u8>,
+        digest_mode: Option<String>,
+    ) -> Option<Client> {
+        let uri = uri.trim_end_matches('/').to_string();
+        let ws_uri = if let Some(rest) = uri.strip_prefix("https://") {
+            format!("wss://{rest}")
+        } else {
+            format!("ws://{}", uri.strip_prefix("http://")?)
+        };
+        Some(Self {
+            uri,
+            ws_uri,
+            worker: Worker::init_with_identity(identity, digest_mode)?,
+        })
+    }
+
+    /// Verify artifacts from `view` onwards with `identity` (see [Worker::rotate]).
+    pub fn rotate(&mut self, view: u64, identity: Vec<u8>) -> bool {
+        self.worker.rotate(view, identity)
+    }
+
+    /// Verify artifacts from the start of `epoch` onwards with `identity` (see
+    /// [Worker::reconfigure]).
+    pub fn reconfigure(&mut self, epoch: u64, identity: Vec<u8>) -> bool {
+        self.worker.reconfigure(epoch, identity)
+    }
+
+    /// Get a block by height (resolving to its finalization), by digest (resolving to the
+    /// block), or the latest finalized block (if `query` is omitted or `"latest"`).
+    ///
+    /// Resolves to `null` if the indexer does not have the block and rejects if the request
+    /// fails or the response is invalid (or does not match the query).
+    #[wasm_bindgen(js_name = getBlock)]
+    pub fn get_block(&self, query: JsValue) -> Promise {
+        let query = Query::from_js(query);
+        let uri = self.uri.clone();
+        let worker = self.worker.clone();
+        future_to_promise(async move {
+            let query = query.ok_or_else(|| error("invalid query"))?;
+            let Some(bytes) = get(&format!("{uri}/block/{}", query.serialize())).await? else {
+                return Ok(JsValue::NULL);
+            };
+            let payload =
+                verify_block(&worker, &query, &bytes).ok_or_else(|| error("invalid block"))?;
+            serde_wasm_bindgen::to_value(&Canonical(&payload)).map_err(Into::into)
+        })
+    }
+
+    /// Get the finalization of a view (or the latest finalization, if `query` is omitted or
+    /// `"latest"`).
+    ///
+    /// Resolves to `null` if the indexer does not have the finalization and rejects if the
+    /// request fails or the response is invalid (or does not match the query).
+    #[wasm_bindgen(js_name = getFinalization)]
+    pub fn get_finalization(&self, query: JsValue) -> Promise {
+        let query = Query::from_js(query);
+        let uri = self.uri.clone();
+        let worker = self.worker.clone();
+        future_to_promise(async move {
+            let query = match query {
+                Some(Query::Digest(_)) => return Err(error("views cannot be found by digest")),
+                Some(query) => query,
+                None => return Err(error("invalid query")),
+            };
+            let path = format!("{uri}/finalization/{}", query.serialize());
+            let Some(bytes) = get(&path).await? else {
+                return Ok(JsValue::NULL);
+            };
+            let finalized = verify_finalization(&worker, &query, &bytes)
+                .ok_or_else(|| error("invalid finalization"))?;
+            serde_wasm_bindgen::to_value(&Canonical(&finalized)).map_err(Into::into)
+        })
+    }
+
+    /// Subscribe to the consensus websocket, calling `on_event` with every verified event (see
+    /// [Worker::push]) and `on_close` (if provided) with the close code once the connection
+    /// closes.
+    ///
+    /// `subscription` is a query string (see [Subscription]), receiving every new message if
+    /// omitted. Malformed and invalid frames are dropped.
+    pub fn subscribe(
+        &self,
+        on_event: Function,
+        subscription: Option<String>,
+        on_close: Option<Function>,
+    ) -> Result<Subscriber, JsValue> {
+        let subscription: Subscription = subscription
+            .unwrap_or_default()
+            .parse()
+            .map_err(|e: crate::Error| error(e.to_string()))?;
+        let socket = WebSocket::new(&listen_uri(&self.ws_uri, &subscription))?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let worker = self.worker.clone();
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |message: MessageEvent| {
+            let Ok(buffer) = message.data().dyn_into::<ArrayBuffer>() else {
+                return;
+            };
+            let Some(event) = worker.parse(&Uint8Array::new(&buffer).to_vec()) else {
+                return;
+            };
+            if let Ok(event) = serde_wasm_bindgen::to_value(&event) {
+                let _ = on_event.call1(&JsValue::NULL, &event);
+            }
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        let on_close = on_close.map(|on_close| {
+            let on_close = Closure::<dyn FnMut(CloseEvent)>::new(move |event: CloseEvent| {
+                let _ = on_close.call1(&JsValue::NULL, &JsValue::from(event.code()));
+            });
+            socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+            on_close
+        });
+        Ok(Subscriber {
+            socket,
+            _on_message: on_message,
+            _on_close: on_close,
+        })
+    }
+}
+
+/// An open subscription to the consensus websocket (closed when dropped or freed).
+#[wasm_bindgen]
+pub struct Subscriber {
+    socket: WebSocket,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Option<Closure<dyn FnMut(CloseEvent)>>,
+}
+
+#[wasm_bindgen]
+impl Subscriber {
+    /// Close the connection (without calling `on_close`).
+    pub fn close(&self) {
+        // The callbacks are freed with the subscriber, so they must not outlive it
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+        let _ = self.socket.close();
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Get the path of the consensus websocket for `subscription`.
+fn listen_uri(ws_uri: &str, subscription: &Subscription) -> String {
+    match subscription.to_string() {
+        query if query.is_empty() => format!("{ws_uri}/consensus/ws"),
+        query => format!("{ws_uri}/consensus/ws?{query}"),
+    }
+}
+
+/// Fetch `uri` from the global scope (a window or a worker), returning `None` if it is not
+/// found.
+async fn get(uri: &str) -> Result<Option<Vec<u8>>, JsValue> {
+    let global = js_sys::global();
+    let request = if let Some(window) = global.dyn_ref::<Window>() {
+        window.fetch_with_str(uri)
+    } else if let Some(scope) = global.dyn_ref::<WorkerGlobalScope>() {
+        scope.fetch_with_str(uri)
+    } else {
+        return Err(error("fetch is unavailable"));
+    };
+    let response: Response = JsFuture::from(request).await?.dyn_into()?;
+    if response.status() == 404 {
+        return Ok(None);
+    }
+    if !response.ok() {
+        return Err(error(format!("request failed: {}", response.status())));
+    }
+    let buffer = JsFuture::from(response.array_buffer()?).await?;
+    Ok(Some(Uint8Array::new(&buffer).to_vec()))
+}
+
+/// Verify a block returned for `query`.
+fn verify_block(worker: &Worker, query: &Query, bytes: &[u8]) -> Option<Payload> {
+    match query {
+        Query::Latest => worker.finalized(bytes).map(Payload::from),
+        Query::Index(height) => {
+            let finalized = worker.finalized(bytes)?;
+            (finalized.block.height.get() == *height).then(|| finalized.into())
+        }
+        Query::Digest(digest) => {
+            let block = Block::decode_cfg(bytes, &worker.mode).ok()?;
+            (block.digest() == *digest).then(|| block.into())
+        }
+    }
+}
+
+/// Verify a finalization returned for `query`.
+fn verify_finalization(worker: &Worker, query: &Query, bytes: &[u8]) -> Option<Finalized> {
+    let finalized = worker.finalized(bytes)?;
+    match query {
+        Query::Latest => Some(finalized),
+        Query::Index(view) => (finalized.proof.view().get() == *view).then_some(finalized),
+        Query::Digest(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use commonware_codec::Encode;
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Hasher, Sha256};
+
+    #[test]
+    fn test_client() {
+        let fixtures::Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let identity = fixtures::identity(&schemes).encode().to_vec();
+        let client =
+            Client::init_with_identity("https://indexer.test/".into(), identity.clone(), None)
+                .unwrap();
+        assert_eq!(client.uri, "https://indexer.test");
+        assert_eq!(client.ws_uri, "wss://indexer.test");
+        let subscription = Subscription::default().with_start_view(3);
+        assert_eq!(
+            listen_uri(&client.ws_uri, &subscription),
+            "wss://indexer.test/consensus/ws?start_view=3"
+        );
+        assert!(Client::init_with_identity("indexer.test".into(), identity, None).is_none());
+
+        // Queries are parsed from heights, digests, or "latest"
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(2), 1000);
+        let digest = block.digest();
+        assert_eq!(Query::parse("latest"), Some(Query::Latest));
+        assert_eq!(Query::parse("2"), Some(Query::Index(2)));
+        assert_eq!(Query::parse(&hex(&digest)), Some(Query::Digest(digest)));
+        assert_eq!(
+            Query::parse(&format!("0x{}", hex(&digest))),
+            Some(Query::Digest(digest))
+        );
+        assert_eq!(Query::parse("0xzz"), None);
+        assert_eq!(Query::Index(2).serialize(), "0000000000000002");
+
+        // Blocks must match the query (and be signed by the network)
+        let worker = &client.worker;
+        let finalized = fixtures::finalized(&schemes, 3, block.clone());
+        let bytes = finalized.encode();
+        let expected = Payload::from(finalized.clone());
+        assert_eq!(
+            verify_block(worker, &Query::Latest, &bytes),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            verify_block(worker, &Query::Index(2), &bytes),
+            Some(expected)
+        );
+        assert_eq!(verify_block(worker, &Query::Index(3), &bytes), None);
+        assert_eq!(
+            verify_block(worker, &Query::Digest(digest), &block.encode()),
+            Some(Payload::from(block.clone()))
+        );
+        let other = Sha256::hash(b"other");
+        assert_eq!(
+            verify_block(worker, &Query::Digest(other), &block.encode()),
+            None
+        );
+        let fixtures::Fixture { schemes: other, .. } = fixtures::seeded(1, 4);
+        let forged = fixtures::finalized(&other, 3, block).encode();
+        assert_eq!(verify_block(worker, &Query::Latest, &forged), None);
+
+        // Finalizations must match the queried view
+        assert_eq!(
+            verify_finalization(worker, &Query::Index(3), &bytes),
+            Some(finalized)
+        );
+        assert_eq!(verify_finalization(worker, &Query::Index(2), &bytes), None);
+        assert_eq!(verify_finalization(worker, &Query::Latest, &forged), None);
+    }
+}
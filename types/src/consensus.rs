@@ -1,6 +1,7 @@
 use commonware_consensus::simplex::scheme::bls12381_threshold;
 use commonware_consensus::simplex::types::{
     Activity as CActivity, Finalization as CFinalization, Notarization as CNotarization,
+    Nullification as CNullification,
 };
 use commonware_cryptography::{
     bls12381::primitives::variant::{MinSig, Variant},
@@ -14,6 +15,7 @@ pub type Scheme = bls12381_threshold::Scheme<PublicKey, MinSig>;
 pub type Seed = bls12381_threshold::Seed<MinSig>;
 pub type Notarization = CNotarization<Scheme, Digest>;
 pub type Finalization = CFinalization<Scheme, Digest>;
+pub type Nullification = CNullification<Scheme>;
 pub type Activity = CActivity<Scheme, Digest>;
 
 pub type PublicKey = ed25519::PublicKey;
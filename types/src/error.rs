@@ -0,0 +1,51 @@
+//! Error categories shared by `alto` crates.
+//!
+//! Each crate keeps its own (detailed) error type but converts it into [Error], so code that
+//! uses several crates can match on one shape (e.g. retrying [Error::Io] and reporting
+//! [Error::Signature]) instead of on each crate's enum.
+
+use thiserror::Error;
+
+/// Category of an error returned by an `alto` crate.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Data could not be decoded (or a request could not be encoded).
+    #[error("codec error: {0}")]
+    Codec(#[from] commonware_codec::Error),
+
+    /// A signature (or certificate) did not verify.
+    #[error("invalid signature")]
+    Signature,
+
+    /// The requested artifact does not exist (or could not be uniquely identified).
+    #[error("not found: {0}")]
+    NotFound(String),
+
+    /// Verified artifacts do not agree with each other (or with the request).
+    #[error("inconsistent: {0}")]
+    Consistency(String),
+
+    /// Transport, storage, or configuration failure (the operation may succeed if retried).
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        let error: Error = commonware_codec::Error::EndOfBuffer.into();
+        assert!(matches!(
+            error,
+            Error::Codec(commonware_codec::Error::EndOfBuffer)
+        ));
+        let error: Error = std::io::Error::from(std::io::ErrorKind::TimedOut).into();
+        assert!(matches!(error, Error::Io(e) if e.kind() == std::io::ErrorKind::TimedOut));
+        assert_eq!(
+            Error::NotFound("block".to_string()).to_string(),
+            "not found: block"
+        );
+    }
+}
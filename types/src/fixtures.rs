@@ -0,0 +1,126 @@
+//! Helpers for constructing valid networks and certificates in tests.
+//!
+//! Requires the `test-support` feature.
+
+use crate::{
+    Block, Finalization, Finalized, Genesis, Identity, Kind, Notarization, Nullification, Scheme,
+    Seed, Seedable, EPOCH, NAMESPACE,
+};
+use commonware_codec::Encode;
+use commonware_consensus::{
+    simplex::{
+        scheme::bls12381_threshold,
+        types::{Finalize, Notarize, Nullify, Proposal},
+    },
+    types::{Epoch, Height, Round, View},
+};
+pub use commonware_cryptography::certificate::mocks::Fixture;
+use commonware_cryptography::{
+    bls12381::primitives::variant::MinSig, sha256::Digest, Digestible, Hasher, Sha256,
+};
+use commonware_parallel::Sequential;
+use rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng};
+
+/// Create a network of `n` participants (with ed25519 identities and threshold shares).
+pub fn network<R: RngCore + CryptoRng>(rng: &mut R, n: u32) -> Fixture<Scheme> {
+    bls12381_threshold::fixture::<MinSig, _>(rng, NAMESPACE, n)
+}
+
+/// Create a network of `n` participants deterministically from `seed`.
+pub fn seeded(seed: u64, n: u32) -> Fixture<Scheme> {
+    network(&mut StdRng::seed_from_u64(seed), n)
+}
+
+/// Returns the network [Identity] shared by `schemes`.
+pub fn identity(schemes: &[Scheme]) -> Identity {
+    *schemes[0].polynomial().public()
+}
+
+/// Create a [Genesis] transcript for `fixture` (committing to `config`) signed by all participants.
+pub fn genesis(fixture: &Fixture<Scheme>, config: Digest) -> Genesis {
+    let polynomial = fixture.schemes[0].polynomial().clone();
+    let mut genesis = Genesis::new(fixture.participants.clone(), polynomial, config);
+    for private_key in &fixture.private_keys {
+        assert!(genesis.sign(private_key));
+    }
+    genesis
+}
+
+/// Create a proposal for `payload` at `view` (with parent `view - 1`) in [EPOCH].
+pub fn proposal(view: u64, payload: Digest) -> Proposal<Digest> {
+    proposal_in(EPOCH.get(), view, payload)
+}
+
+/// Create a proposal for `payload` at `view` (with parent `view - 1`) in `epoch`.
+pub fn proposal_in(epoch: u64, view: u64, payload: Digest) -> Proposal<Digest> {
+    Proposal::new(
+        Round::new(Epoch::new(epoch), View::new(view)),
+        View::new(view.saturating_sub(1)),
+        payload,
+    )
+}
+
+/// Create a [Notarization] of `proposal` signed by all `schemes`.
+pub fn notarization(schemes: &[Scheme], proposal: Proposal<Digest>) -> Notarization {
+    let notarizes: Vec<_> = schemes
+        .iter()
+        .map(|scheme| Notarize::sign(scheme, proposal.clone()).unwrap())
+        .collect();
+    Notarization::from_notarizes(&schemes[0], &notarizes, &Sequential).unwrap()
+}
+
+/// Create a [Finalization] of `proposal` signed by all `schemes`.
+pub fn finalization(schemes: &[Scheme], proposal: Proposal<Digest>) -> Finalization {
+    let finalizes: Vec<_> = schemes
+        .iter()
+        .map(|scheme| Finalize::sign(scheme, proposal.clone()).unwrap())
+        .collect();
+    Finalization::from_finalizes(&schemes[0], &finalizes, &Sequential).unwrap()
+}
+
+/// Create a [Nullification] of `view` (in [EPOCH]) signed by all `schemes`.
+pub fn nullification(schemes: &[Scheme], view: u64) -> Nullification {
+    nullification_in(schemes, EPOCH.get(), view)
+}
+
+/// Create a [Nullification] of `view` in `epoch` signed by all `schemes`.
+pub fn nullification_in(schemes: &[Scheme], epoch: u64, view: u64) -> Nullification {
+    let round = Round::new(Epoch::new(epoch), View::new(view));
+    let nullifies: Vec<_> = schemes
+        .iter()
+        .map(|scheme| Nullify::sign::<Digest>(scheme, round).unwrap())
+        .collect();
+    Nullification::from_nullifies(&schemes[0], &nullifies, &Sequential).unwrap()
+}
+
+/// Create the [Seed] of a notarization at `view` signed by all `schemes`.
+pub fn seed(schemes: &[Scheme], view: u64) -> Seed {
+    let payload = Sha256::hash(&view.to_be_bytes());
+    notarization(schemes, proposal(view, payload)).seed()
+}
+
+/// Create a [Finalized] `block` (finalized at `view`) signed by all `schemes`.
+pub fn finalized(schemes: &[Scheme], view: u64, block: Block) -> Finalized {
+    let finalization = finalization(schemes, proposal(view, block.digest()));
+    Finalized::new(finalization, block)
+}
+
+/// Prefix an encoded artifact with its [Kind] (as sent over the consensus websocket).
+pub fn frame(kind: Kind, artifact: &impl Encode) -> Vec<u8> {
+    let mut frame = vec![kind as u8];
+    frame.extend_from_slice(&artifact.encode());
+    frame
+}
+
+/// Create a valid frame for a seed, finalization, and nullification (in that order) signed by
+/// all `schemes`.
+pub fn frames(schemes: &[Scheme]) -> Vec<Vec<u8>> {
+    let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+    let finalized = finalized(schemes, 1, block);
+    let seed = finalized.proof.seed();
+    vec![
+        frame(Kind::Seed, &seed),
+        frame(Kind::Finalization, &finalized),
+        frame(Kind::Nullification, &nullification(schemes, 2)),
+    ]
+}
@@ -0,0 +1,165 @@
+use crate::{consensus::Identity, NAMESPACE};
+use bytes::{Buf, BufMut};
+use commonware_codec::{Encode, EncodeSize, Error, RangeCfg, Read, ReadExt, Write};
+use commonware_cryptography::{
+    bls12381::primitives::{sharing::Sharing, variant::MinSig},
+    ed25519::{PrivateKey, PublicKey, Signature},
+    sha256::Digest,
+    Hasher, Sha256, Signer, Verifier,
+};
+use commonware_utils::{union_unique, NZU32};
+use std::num::NonZeroU32;
+
+/// Suffix (appended to [NAMESPACE]) used when signing a [Genesis] transcript.
+pub const GENESIS_SUFFIX: &[u8] = b"_GENESIS";
+
+/// Namespace used when signing a [Genesis] transcript.
+pub fn genesis_namespace() -> Vec<u8> {
+    union_unique(NAMESPACE, GENESIS_SUFFIX)
+}
+
+/// Maximum number of participants in a [Genesis] transcript decoded without knowledge of the
+/// network size.
+pub const MAX_GENESIS_PARTICIPANTS: NonZeroU32 = NZU32!(u16::MAX as u32);
+
+/// Transcript of a network's genesis ceremony.
+///
+/// The transcript commits to everything participants must agree on to form a working network:
+/// the participant set, the public polynomial (and the identity derived from it), and a digest
+/// of the network-wide configuration. Every participant signs the transcript with its ed25519
+/// key, so a validator (or client) that verifies it knows all participants were configured
+/// identically.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Genesis {
+    /// Participants (sorted) in the network.
+    pub participants: Vec<PublicKey>,
+
+    /// Public polynomial of the network's threshold key.
+    pub polynomial: Sharing<MinSig>,
+
+    /// Identity (constant term of `polynomial`) of the network.
+    pub identity: Identity,
+
+    /// Digest of the network-wide configuration.
+    pub config: Digest,
+
+    /// Signature of each participant (in the same order as `participants`) over the transcript
+    /// (if collected).
+    pub signatures: Vec<Option<Signature>>,
+}
+
+impl Genesis {
+    /// Create an unsigned transcript (sorting `participants`).
+    pub fn new(
+        mut participants: Vec<PublicKey>,
+        polynomial: Sharing<MinSig>,
+        config: Digest,
+    ) -> Self {
+        participants.sort();
+        let identity = *polynomial.public();
+        let signatures = vec![None; participants.len()];
+        Self {
+            participants,
+            polynomial,
+            identity,
+            config,
+            signatures,
+        }
+    }
+
+    /// Digest of the transcript (excluding signatures).
+    pub fn digest(&self) -> Digest {
+        let mut hasher = Sha256::new();
+        hasher.update(&(self.participants.len() as u32).to_be_bytes());
+        for participant in &self.participants {
+            hasher.update(participant.as_ref());
+        }
+        hasher.update(&self.polynomial.encode());
+        hasher.update(&self.identity.encode());
+        hasher.update(&self.config);
+        hasher.finalize()
+    }
+
+    /// Sign the transcript with `signer` (which must be a participant).
+    ///
+    /// Returns `false` if `signer` is not a participant.
+    pub fn sign(&mut self, signer: &PrivateKey) -> bool {
+        let Ok(index) = self.participants.binary_search(&signer.public_key()) else {
+            return false;
+        };
+        self.signatures[index] = Some(signer.sign(&genesis_namespace(), &self.digest()));
+        true
+    }
+
+    /// Verify the transcript is internally consistent and signed by every participant.
+    pub fn verify(&self) -> bool {
+        // Ensure participants are sorted and unique
+        if !self.participants.windows(2).all(|w| w[0] < w[1]) {
+            return false;
+        }
+
+        // Ensure the polynomial covers exactly the participants
+        if self.polynomial.total().get() as usize != self.participants.len() {
+            return false;
+        }
+        if *self.polynomial.public() != self.identity {
+            return false;
+        }
+
+        // Ensure every participant signed the transcript
+        if self.signatures.len() != self.participants.len() {
+            return false;
+        }
+        let namespace = genesis_namespace();
+        let digest = self.digest();
+        self.participants
+            .iter()
+            .zip(&self.signatures)
+            .all(|(participant, signature)| {
+                signature
+                    .as_ref()
+                    .is_some_and(|signature| participant.verify(&namespace, &digest, signature))
+            })
+    }
+}
+
+impl Write for Genesis {
+    fn write(&self, writer: &mut impl BufMut) {
+        self.participants.write(writer);
+        self.polynomial.write(writer);
+        self.identity.write(writer);
+        self.config.write(writer);
+        self.signatures.write(writer);
+    }
+}
+
+impl Read for Genesis {
+    /// Maximum number of participants.
+    type Cfg = NonZeroU32;
+
+    fn read_cfg(reader: &mut impl Buf, max: &Self::Cfg) -> Result<Self, Error> {
+        let range = RangeCfg::from(1..=max.get() as usize);
+        let participants = Vec::<PublicKey>::read_cfg(reader, &(range, ()))?;
+        let polynomial = Sharing::<MinSig>::read_cfg(reader, max)?;
+        let identity = Identity::read(reader)?;
+        let config = Digest::read(reader)?;
+        let signatures = Vec::<Option<Signature>>::read_cfg(reader, &(range, ()))?;
+        Ok(Self {
+            participants,
+            polynomial,
+            identity,
+            config,
+            signatures,
+        })
+    }
+}
+
+impl EncodeSize for Genesis {
+    fn encode_size(&self) -> usize {
+        self.participants.encode_size()
+            + self.polynomial.encode_size()
+            + self.identity.encode_size()
+            + self.config.encode_size()
+            + self.signatures.encode_size()
+    }
+}
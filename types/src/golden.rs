@@ -0,0 +1,146 @@
+//! Golden encodings of every artifact `alto` sends over the wire.
+//!
+//! Each artifact is built deterministically (from [fixtures::seeded]) and its encoding is
+//! compared against the hex stored in `types/golden/<name>.hex`, so any change to a codec
+//! (including to an upstream type) fails here instead of in a deployed network.
+//!
+//! To intentionally change an encoding, bump the workspace version and run the tests with
+//! `ALTO_BLESS_GOLDEN=1` (which rewrites the files and records the version they were blessed
+//! at in `types/golden/VERSION`). Blessing a changed encoding without a version bump fails.
+
+use crate::{
+    fixtures, Block, DigestMode, Finalized, Kind, Notarized, Seed, Seedable, Transaction,
+    INITIAL_RULES, LAGGED_FRAME,
+};
+use bytes::Bytes;
+use commonware_codec::{Decode, Encode};
+use commonware_consensus::types::Height;
+use commonware_cryptography::{Digestible, Hasher, Sha256};
+use commonware_utils::{from_hex, hex};
+use std::{fs, path::PathBuf};
+
+/// Environment variable that rewrites golden files instead of checking them.
+const BLESS: &str = "ALTO_BLESS_GOLDEN";
+
+/// Version the golden files were last blessed at.
+const VERSION: &str = "VERSION";
+
+fn directory() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("golden")
+}
+
+/// Build every golden artifact (and its encoding).
+fn artifacts() -> Vec<(&'static str, Vec<u8>)> {
+    let fixtures::Fixture { schemes, .. } = fixtures::seeded(0, 4);
+    let parent = Sha256::hash(b"genesis");
+    let block = Block::new(parent, Height::new(1), 1000);
+    let blake3 = Block::new_with_mode(DigestMode::Blake3, parent, Height::new(1), 1000);
+    let transactions = Block::new_with_transactions(
+        DigestMode::Sha256,
+        INITIAL_RULES,
+        parent,
+        Height::new(1),
+        1000,
+        vec![
+            Transaction::new(b"hello".to_vec()).unwrap(),
+            Transaction::new(b"world".to_vec()).unwrap(),
+        ],
+    );
+    let receipts = Block::new_with_receipts(
+        DigestMode::Sha256,
+        INITIAL_RULES,
+        parent,
+        Height::new(1),
+        1000,
+        vec![Transaction::new(b"hello".to_vec()).unwrap()],
+        Bytes::from_static(b"ok"),
+    );
+    let notarization = fixtures::notarization(&schemes, fixtures::proposal(1, block.digest()));
+    let notarized = Notarized::new(notarization.clone(), block.clone());
+    let finalized = fixtures::finalized(&schemes, 1, block.clone());
+    let seed = notarization.seed();
+    let nullification = fixtures::nullification(&schemes, 2);
+    let mut lagged = vec![LAGGED_FRAME];
+    lagged.extend_from_slice(&7u64.encode());
+    vec![
+        ("block", block.encode().to_vec()),
+        ("block_blake3", blake3.encode().to_vec()),
+        ("block_transactions", transactions.encode().to_vec()),
+        ("block_receipts", receipts.encode().to_vec()),
+        ("seed", seed.encode().to_vec()),
+        ("notarized", notarized.encode().to_vec()),
+        ("finalized", finalized.encode().to_vec()),
+        ("frame_seed", fixtures::frame(Kind::Seed, &seed)),
+        (
+            "frame_notarization",
+            fixtures::frame(Kind::Notarization, &notarized),
+        ),
+        (
+            "frame_finalization",
+            fixtures::frame(Kind::Finalization, &finalized),
+        ),
+        (
+            "frame_nullification",
+            fixtures::frame(Kind::Nullification, &nullification),
+        ),
+        ("frame_lagged", lagged),
+    ]
+}
+
+/// Ensure `encoded` decodes as the artifact named `name` and re-encodes to the same bytes.
+fn roundtrip(name: &str, encoded: &[u8]) {
+    let reencoded = match name {
+        "block" => Block::decode_cfg(encoded, &DigestMode::Sha256).map(|v| v.encode()),
+        "block_blake3" => Block::decode_cfg(encoded, &DigestMode::Blake3).map(|v| v.encode()),
+        "block_transactions" => Block::decode_cfg(encoded, &DigestMode::Sha256).map(|v| v.encode()),
+        "block_receipts" => Block::decode_cfg(encoded, &DigestMode::Sha256).map(|v| v.encode()),
+        "seed" => Seed::decode_cfg(encoded, &()).map(|v| v.encode()),
+        "notarized" => Notarized::decode_cfg(encoded, &DigestMode::Sha256).map(|v| v.encode()),
+        "finalized" => Finalized::decode_cfg(encoded, &DigestMode::Sha256).map(|v| v.encode()),
+        _ => return, // Frames are checked by the (encoded) artifacts they wrap.
+    };
+    let reencoded = reencoded.unwrap_or_else(|e| panic!("{name}: failed to decode: {e}"));
+    assert_eq!(reencoded.as_ref(), encoded, "{name}: re-encoding changed");
+}
+
+#[test]
+fn test_golden() {
+    let directory = directory();
+    let version = env!("CARGO_PKG_VERSION");
+    let blessed = fs::read_to_string(directory.join(VERSION)).ok();
+    let bless = std::env::var_os(BLESS).is_some();
+
+    let mut changed = Vec::new();
+    for (name, encoded) in artifacts() {
+        roundtrip(name, &encoded);
+        let path = directory.join(format!("{name}.hex"));
+        let golden = fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| from_hex(s.trim()));
+        if golden.as_deref() == Some(encoded.as_slice()) {
+            continue;
+        }
+        if !bless {
+            panic!(
+                "{name}: encoding does not match {} (see the wire compatibility policy in types/README.md)",
+                path.display()
+            );
+        }
+        changed.push((path, encoded));
+    }
+    if changed.is_empty() {
+        return;
+    }
+
+    // Changing an encoding requires a new version
+    assert_ne!(
+        blessed.as_deref().map(str::trim),
+        Some(version),
+        "encodings changed without a version bump"
+    );
+    fs::create_dir_all(&directory).unwrap();
+    for (path, encoded) in changed {
+        fs::write(path, hex(&encoded) + "\n").unwrap();
+    }
+    fs::write(directory.join(VERSION), format!("{version}\n")).unwrap();
+}
@@ -0,0 +1,362 @@
+//! Canonical JSON form of the core types.
+//!
+//! With the `serde` feature, [Block], [Notarized], [Finalized], and [Payload] implement
+//! `Serialize` and `Deserialize` with the schema below (used by the indexer's JSON responses).
+//! The [crate::wasm] bindings always return this form (with or without the feature). Seeds are
+//! represented by their [SeedInfo]. Byte strings (digests, signatures, transactions, and
+//! receipts) are lowercase hex without a `0x` prefix.
+//!
+//! ```text
+//! Block = {
+//!   "rules": u16,
+//!   "parent": hex digest,
+//!   "height": u64,
+//!   "timestamp": u64 (milliseconds since the Unix epoch),
+//!   "transactions": [hex data],
+//!   "receipts": hex,
+//!   "digest": hex digest
+//! }
+//!
+//! Proof = {
+//!   "epoch": u64,
+//!   "view": u64,
+//!   "parent": u64 (view of the parent proposal),
+//!   "payload": hex digest (of the block),
+//!   "signature": hex (recovered vote signature),
+//!   "seed_signature": hex (recovered seed signature)
+//! }
+//!
+//! Notarized = Finalized = { "proof": Proof, "block": Block }
+//! Payload = Finalized | Block
+//! Seed = { "epoch": u64, "view": u64, "signature": hex, "randomness": hex }
+//! ```
+//!
+//! Deserializing checks that the `digest` of a block matches its fields (under either
+//! [DigestMode]) and that a proof is for its block, but (like decoding) does not verify
+//! signatures.
+//!
+//! [SeedInfo]: crate::SeedInfo
+
+use crate::{Block, Finalized, Notarized, Payload};
+use commonware_codec::Encode;
+use commonware_consensus::simplex::{scheme::bls12381_threshold, types::Proposal};
+use commonware_cryptography::{bls12381::primitives::variant::MinSig, sha256::Digest, Digestible};
+use commonware_utils::hex;
+use serde::{Serialize, Serializer};
+#[cfg(feature = "serde")]
+use {
+    crate::{DigestMode, Finalization, Notarization, Signature, Transaction, MAX_RECEIPTS_SIZE},
+    bytes::Bytes,
+    commonware_codec::DecodeExt,
+    commonware_consensus::types::{Epoch, Height, Round, View},
+    commonware_utils::from_hex,
+    serde::{de::Error as _, Deserialize, Deserializer},
+};
+
+/// Certificate of a notarization or finalization.
+type Certificate = bls12381_threshold::Signature<MinSig>;
+
+/// Serializes a core type in its canonical form (with or without the `serde` feature).
+pub(crate) struct Canonical<'a, T>(pub(crate) &'a T);
+
+/// Serialize `value` in its canonical form (for `#[serde(serialize_with = "...")]`).
+pub(crate) fn canonical<T, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    for<'a> Canonical<'a, T>: Serialize,
+{
+    Canonical(value).serialize(serializer)
+}
+
+/// Decode the hex encoding of a `T`.
+#[cfg(feature = "serde")]
+fn decode<T: DecodeExt<()>>(field: &'static str, value: &str) -> Result<T, String> {
+    let bytes = from_hex(value).ok_or_else(|| format!("{field} is not hex"))?;
+    T::decode(bytes.as_ref()).map_err(|_| format!("{field} is invalid"))
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+struct BlockJson {
+    rules: u16,
+    parent: String,
+    height: u64,
+    timestamp: u64,
+    transactions: Vec<String>,
+    receipts: String,
+    digest: String,
+}
+
+impl From<&Block> for BlockJson {
+    fn from(block: &Block) -> Self {
+        Self {
+            rules: block.rules,
+            parent: hex(&block.parent),
+            height: block.height.get(),
+            timestamp: block.timestamp,
+            transactions: block
+                .transactions
+                .iter()
+                .map(|transaction| hex(transaction.data()))
+                .collect(),
+            receipts: hex(&block.receipts),
+            digest: hex(&block.digest()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BlockJson> for Block {
+    type Error = String;
+
+    fn try_from(json: BlockJson) -> Result<Self, Self::Error> {
+        let parent: Digest = decode("parent", &json.parent)?;
+        let digest: Digest = decode("digest", &json.digest)?;
+        let transactions = json
+            .transactions
+            .iter()
+            .map(|data| {
+                let data = from_hex(data).ok_or("transaction is not hex")?;
+                Transaction::new(data).ok_or("transaction is too large")
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let receipts = from_hex(&json.receipts).ok_or("receipts are not hex")?;
+        if receipts.len() > MAX_RECEIPTS_SIZE {
+            return Err("receipts are too large".to_string());
+        }
+        let receipts = Bytes::from(receipts);
+
+        // Find the digest mode the block was built with
+        [DigestMode::Sha256, DigestMode::Blake3]
+            .into_iter()
+            .map(|mode| {
+                Block::new_with_receipts(
+                    mode,
+                    json.rules,
+                    parent,
+                    Height::new(json.height),
+                    json.timestamp,
+                    transactions.clone(),
+                    receipts.clone(),
+                )
+            })
+            .find(|block| block.digest() == digest)
+            .ok_or_else(|| "digest does not match block".to_string())
+    }
+}
+
+impl Serialize for Canonical<'_, Block> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BlockJson::from(self.0).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Block {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Canonical(self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Block {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BlockJson::deserialize(deserializer)?
+            .try_into()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+struct ProofJson {
+    epoch: u64,
+    view: u64,
+    parent: u64,
+    payload: String,
+    signature: String,
+    seed_signature: String,
+}
+
+impl ProofJson {
+    fn new(proposal: &Proposal<Digest>, certificate: &Certificate) -> Self {
+        Self {
+            epoch: proposal.round.epoch().get(),
+            view: proposal.round.view().get(),
+            parent: proposal.parent.get(),
+            payload: hex(&proposal.payload),
+            signature: hex(&certificate.vote_signature.encode()),
+            seed_signature: hex(&certificate.seed_signature.encode()),
+        }
+    }
+
+    /// Get the proposal and certificate of the proof (which must be for `block`).
+    #[cfg(feature = "serde")]
+    fn parse(&self, block: &Block) -> Result<(Proposal<Digest>, Certificate), String> {
+        let payload: Digest = decode("payload", &self.payload)?;
+        if payload != block.digest() {
+            return Err("proof payload does not match block digest".to_string());
+        }
+        let proposal = Proposal::new(
+            Round::new(Epoch::new(self.epoch), View::new(self.view)),
+            View::new(self.parent),
+            payload,
+        );
+        let certificate = Certificate {
+            vote_signature: decode::<Signature>("signature", &self.signature)?,
+            seed_signature: decode::<Signature>("seed_signature", &self.seed_signature)?,
+        };
+        Ok((proposal, certificate))
+    }
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+struct CertifiedJson<B> {
+    proof: ProofJson,
+    block: B,
+}
+
+impl Serialize for Canonical<'_, Notarized> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let notarized = self.0;
+        let proof = ProofJson::new(&notarized.proof.proposal, &notarized.proof.certificate);
+        let block = BlockJson::from(&notarized.block);
+        CertifiedJson { proof, block }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Notarized {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Canonical(self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Notarized {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = CertifiedJson::<Block>::deserialize(deserializer)?;
+        let (proposal, certificate) = json.proof.parse(&json.block).map_err(D::Error::custom)?;
+        let proof = Notarization {
+            proposal,
+            certificate,
+        };
+        Ok(Self::new(proof, json.block))
+    }
+}
+
+impl Serialize for Canonical<'_, Finalized> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let finalized = self.0;
+        let proof = ProofJson::new(&finalized.proof.proposal, &finalized.proof.certificate);
+        let block = BlockJson::from(&finalized.block);
+        CertifiedJson { proof, block }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Finalized {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Canonical(self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Finalized {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = CertifiedJson::<Block>::deserialize(deserializer)?;
+        let (proposal, certificate) = json.proof.parse(&json.block).map_err(D::Error::custom)?;
+        let proof = Finalization {
+            proposal,
+            certificate,
+        };
+        Ok(Self::new(proof, json.block))
+    }
+}
+
+impl Serialize for Canonical<'_, Payload> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Payload::Finalized(finalized) => Canonical(finalized.as_ref()).serialize(serializer),
+            Payload::Block(block) => Canonical(block.as_ref()).serialize(serializer),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use commonware_cryptography::{Hasher, Sha256};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_json() {
+        let fixtures::Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let transactions = vec![Transaction::new(b"hello".to_vec()).unwrap()];
+        let block = Block::new_with_receipts(
+            DigestMode::Blake3,
+            1,
+            Sha256::hash(b"genesis"),
+            Height::new(1),
+            1_000,
+            transactions,
+            Bytes::from_static(b"ok"),
+        );
+
+        // Byte strings are hex
+        let value = serde_json::to_value(&block).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "rules": 1,
+                "parent": hex(&Sha256::hash(b"genesis")),
+                "height": 1,
+                "timestamp": 1_000,
+                "transactions": [hex(b"hello")],
+                "receipts": hex(b"ok"),
+                "digest": hex(&block.digest()),
+            })
+        );
+        assert_eq!(serde_json::from_value::<Block>(value).unwrap(), block);
+
+        // Proofs carry their round and certificate
+        let finalized = fixtures::finalized(&schemes, 3, block.clone());
+        let value = serde_json::to_value(&finalized).unwrap();
+        assert_eq!(value["proof"]["view"], 3);
+        assert_eq!(value["proof"]["payload"], hex(&block.digest()));
+        assert_eq!(
+            value["proof"]["signature"],
+            hex(&finalized.proof.certificate.vote_signature.encode())
+        );
+        assert_eq!(value["block"], serde_json::to_value(&block).unwrap());
+        assert_eq!(value, serde_json::to_value(Canonical(&finalized)).unwrap());
+        let decoded: Finalized = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(decoded, finalized);
+        let payload: Payload = serde_json::from_value(value).unwrap();
+        assert_eq!(payload, Payload::from(finalized));
+        let payload: Payload = serde_json::to_value(&block)
+            .and_then(serde_json::from_value)
+            .unwrap();
+        assert_eq!(payload, Payload::from(block.clone()));
+        let proposal = fixtures::proposal(4, block.digest());
+        let notarized = Notarized::new(fixtures::notarization(&schemes, proposal), block.clone());
+        let value = serde_json::to_value(&notarized).unwrap();
+        assert_eq!(
+            serde_json::from_value::<Notarized>(value).unwrap(),
+            notarized
+        );
+
+        // Blocks must match their digest and proofs their block
+        let mut value = serde_json::to_value(&block).unwrap();
+        value["timestamp"] = Value::from(1_001);
+        assert!(serde_json::from_value::<Block>(value).is_err());
+        let mut value = serde_json::to_value(&block).unwrap();
+        value["receipts"] = Value::from(hex(b"no"));
+        assert!(serde_json::from_value::<Block>(value).is_err());
+        let other = Block::new(Sha256::hash(b"genesis"), Height::new(2), 2_000);
+        let mut value = serde_json::to_value(fixtures::finalized(&schemes, 3, block)).unwrap();
+        value["block"] = serde_json::to_value(&other).unwrap();
+        assert!(serde_json::from_value::<Finalized>(value).is_err());
+    }
+}
@@ -1,60 +1,147 @@
 //! Common types used throughout `alto`.
 
 use commonware_consensus::types::Epoch;
-use commonware_utils::{hex, NZU64};
-use std::num::NonZero;
+use commonware_utils::hex;
 
 mod block;
-pub use block::{Block, Finalized, Notarized};
+pub use block::{
+    Block, Consistency, DigestMode, Finalized, Notarized, Payload, INITIAL_RULES,
+    MAX_CONSISTENCY_HEADERS, MAX_RECEIPTS_SIZE,
+};
 
 mod consensus;
 pub use consensus::{
-    Activity, Evaluation, Finalization, Identity, Notarization, PublicKey, Scheme, Seed, Seedable,
-    Signature,
+    Activity, Evaluation, Finalization, Identity, Notarization, Nullification, PublicKey, Scheme,
+    Seed, Seedable, Signature,
+};
+
+mod error;
+pub use error::Error;
+
+pub mod json;
+
+mod genesis;
+pub use genesis::{genesis_namespace, Genesis, GENESIS_SUFFIX, MAX_GENESIS_PARTICIPANTS};
+
+pub mod light;
+pub use light::LightClient;
+
+mod schedule;
+pub use schedule::IdentitySchedule;
+
+mod seed;
+pub use seed::{SeedExt, SeedInfo, RANDOMNESS_SIZE};
+
+mod spec;
+pub use spec::{Spec, Timeouts};
+
+mod subscription;
+pub use subscription::Subscription;
+
+mod transaction;
+pub use transaction::{
+    Transaction, MAX_BLOCK_TRANSACTIONS, MAX_ENCODED_TRANSACTION_SIZE, MAX_TRANSACTION_SIZE,
 };
 
+mod upload;
+pub use upload::{Ack, Upload, ACK_FRAME, MAX_BATCH_UPLOADS};
+
+mod validator;
+pub use validator::ValidatorInfo;
+
+#[cfg(any(target_arch = "wasm32", test))]
+pub mod browser;
+
 pub mod wasm;
 
+#[cfg(any(test, feature = "test-support"))]
+pub mod fixtures;
+
+#[cfg(test)]
+mod golden;
+
 /// The unique namespace prefix used in all signing operations to prevent signature replay attacks.
 pub const NAMESPACE: &[u8] = b"_ALTO";
 
-/// The epoch number used in [commonware_consensus::simplex].
-///
-/// Because alto does not implement reconfiguration (validator set changes and resharing), we hardcode the epoch to 0.
+/// Histogram buckets (in seconds) for consensus latencies (seed to finalization, upload
+/// round-trips).
 ///
-/// For an example of how to implement reconfiguration and resharing, see [commonware-reshare](https://github.com/commonwarexyz/monorepo/tree/main/examples/reshare).
-pub const EPOCH: Epoch = Epoch::zero();
+/// Buckets are dense below one second (where healthy finality lives) so that p99 regressions
+/// are visible.
+pub const LATENCY_BUCKETS: [f64; 20] = [
+    0.01, 0.025, 0.05, 0.075, 0.1, 0.125, 0.15, 0.175, 0.2, 0.25, 0.3, 0.35, 0.4, 0.5, 0.6, 0.75,
+    1.0, 2.0, 5.0, 10.0,
+];
 
-/// The epoch length used in [commonware_consensus::simplex].
+/// Histogram buckets (in bytes) for the encoded sizes of blocks and certificates.
 ///
-/// Because alto does not implement reconfiguration (validator set changes and resharing), we hardcode the epoch length to u64::MAX (to
-/// stay in the first epoch forever).
+/// Buckets double from 128 bytes to 4 MiB so that growth is visible long before it reaches p2p
+/// rate limits (or upload body limits).
+pub const SIZE_BUCKETS: [f64; 16] = [
+    128.0, 256.0, 512.0, 1024.0, 2048.0, 4096.0, 8192.0, 16384.0, 32768.0, 65536.0, 131072.0,
+    262144.0, 524288.0, 1048576.0, 2097152.0, 4194304.0,
+];
+
+/// The first epoch used in [commonware_consensus::simplex] (the one that starts at genesis).
 ///
-/// For an example of how to implement reconfiguration and resharing, see [commonware-reshare](https://github.com/commonwarexyz/monorepo/tree/main/examples/reshare).
-pub const EPOCH_LENGTH: NonZero<u64> = NZU64!(u64::MAX);
+/// Networks that reconfigure (change their validator set or reshare) enter later epochs at
+/// scheduled heights. Views restart in every epoch, so artifacts must be verified against the
+/// identity of their epoch (see [IdentitySchedule]).
+pub const EPOCH: Epoch = Epoch::zero();
 
 #[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Kind {
     Seed = 0,
     Notarization = 1,
     Finalization = 2,
+    Nullification = 3,
 }
 
+/// Tag of the frame sent over the consensus websocket (followed by the number of skipped
+/// messages) when a subscriber fell behind and messages were dropped.
+///
+/// This is not a [Kind] (it carries no artifact), so it is chosen to never collide with one.
+pub const LAGGED_FRAME: u8 = u8::MAX;
+
 impl Kind {
     pub fn from_u8(value: u8) -> Option<Self> {
         match value {
             0 => Some(Self::Seed),
             1 => Some(Self::Notarization),
             2 => Some(Self::Finalization),
+            3 => Some(Self::Nullification),
             _ => None,
         }
     }
 
+    /// Parse the name of a kind (as returned by [Kind::name]).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "seed" => Some(Self::Seed),
+            "notarization" => Some(Self::Notarization),
+            "finalization" => Some(Self::Finalization),
+            "nullification" => Some(Self::Nullification),
+            _ => None,
+        }
+    }
+
+    /// Name of the kind (as used in query strings).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Seed => "seed",
+            Self::Notarization => "notarization",
+            Self::Finalization => "finalization",
+            Self::Nullification => "nullification",
+        }
+    }
+
     pub fn to_hex(&self) -> String {
         match self {
             Self::Seed => hex(&[0]),
             Self::Notarization => hex(&[1]),
             Self::Finalization => hex(&[2]),
+            Self::Nullification => hex(&[3]),
         }
     }
 }
@@ -62,50 +149,33 @@ impl Kind {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use commonware_codec::{DecodeExt, Encode};
-    use commonware_consensus::{
-        simplex::{
-            scheme::bls12381_threshold,
-            types::{Finalization, Finalize, Notarization, Notarize, Proposal},
-        },
-        types::{Height, Round, View},
-    };
-    use commonware_cryptography::{
-        bls12381::primitives::variant::MinSig, certificate::mocks::Fixture, Digestible, Hasher,
-        Sha256,
-    };
+    use bytes::Bytes;
+    use commonware_codec::{Decode, DecodeExt, Encode, EncodeSize};
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{sha256::Digest, Blake3, Digestible, Hasher, Sha256};
     use commonware_parallel::Sequential;
-    use rand::{rngs::StdRng, SeedableRng};
+    use commonware_utils::NZU32;
+    use fixtures::Fixture;
+    use rand::rngs::OsRng;
 
     #[test]
     fn test_notarized() {
         // Create network key
-        let mut rng = StdRng::seed_from_u64(0);
-        let n = 4;
-        let Fixture { schemes, .. } =
-            bls12381_threshold::fixture::<MinSig, _>(&mut rng, NAMESPACE, n);
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
 
         // Create a block
         let digest = Sha256::hash(b"hello world");
         let block = Block::new(digest, Height::new(10), 100);
-        let proposal = Proposal::new(
-            Round::new(EPOCH, View::new(9)),
-            View::new(8),
-            block.digest(),
-        );
+        let proposal = fixtures::proposal(9, block.digest());
 
         // Create a notarization
-        let notarizes: Vec<_> = schemes
-            .iter()
-            .map(|scheme| Notarize::sign(scheme, proposal.clone()).unwrap())
-            .collect();
-        let notarization =
-            Notarization::from_notarizes(&schemes[0], &notarizes, &Sequential).unwrap();
+        let notarization = fixtures::notarization(&schemes, proposal);
         let notarized = Notarized::new(notarization, block.clone());
 
         // Serialize and deserialize
         let encoded = notarized.encode();
-        let decoded = Notarized::decode(encoded).expect("failed to decode notarized");
+        let decoded = Notarized::decode_cfg(encoded, &DigestMode::Sha256)
+            .expect("failed to decode notarized");
         assert_eq!(notarized, decoded);
 
         // Verify notarized
@@ -115,35 +185,342 @@ mod tests {
     #[test]
     fn test_finalized() {
         // Create network key
-        let mut rng = StdRng::seed_from_u64(0);
-        let n = 4;
-        let Fixture { schemes, .. } =
-            bls12381_threshold::fixture::<MinSig, _>(&mut rng, NAMESPACE, n);
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
 
         // Create a block
         let digest = Sha256::hash(b"hello world");
         let block = Block::new(digest, Height::new(10), 100);
-        let proposal = Proposal::new(
-            Round::new(EPOCH, View::new(9)),
-            View::new(8),
-            block.digest(),
-        );
+        let proposal = fixtures::proposal(9, block.digest());
 
         // Create a finalization
-        let finalizes: Vec<_> = schemes
-            .iter()
-            .map(|scheme| Finalize::sign(scheme, proposal.clone()).unwrap())
-            .collect();
-        let finalization =
-            Finalization::from_finalizes(&schemes[0], &finalizes, &Sequential).unwrap();
+        let finalization = fixtures::finalization(&schemes, proposal);
         let finalized = Finalized::new(finalization, block.clone());
 
         // Serialize and deserialize
         let encoded = finalized.encode();
-        let decoded = Finalized::decode(encoded).expect("failed to decode finalized");
+        let decoded = Finalized::decode_cfg(encoded, &DigestMode::Sha256)
+            .expect("failed to decode finalized");
         assert_eq!(finalized, decoded);
 
         // Verify finalized
         assert!(finalized.verify(&schemes[0], &Sequential));
     }
+
+    #[test]
+    fn test_verify_batch() {
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let Fixture { schemes: other, .. } = fixtures::seeded(1, 4);
+        let verifier = Scheme::certificate_verifier(NAMESPACE, fixtures::identity(&schemes));
+        let finalized = |schemes: &[Scheme], view: u64| {
+            let block = Block::new(Sha256::hash(b"genesis"), Height::new(view), view);
+            fixtures::finalized(schemes, view, block)
+        };
+
+        // Valid proofs are verified together
+        let mut batch: Vec<_> = (1..=8).map(|view| finalized(&schemes, view)).collect();
+        assert!(Finalized::verify_batch(&verifier, &Sequential, &batch).is_empty());
+        assert!(Finalized::verify_batch(&verifier, &Sequential, &[]).is_empty());
+
+        // Invalid proofs are identified
+        batch[2] = finalized(&other, 3);
+        batch[5] = finalized(&other, 6);
+        assert_eq!(
+            Finalized::verify_batch(&verifier, &Sequential, &batch),
+            vec![2, 5]
+        );
+    }
+
+    #[test]
+    fn test_consistency() {
+        // Create network key
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+
+        // Create a chain of blocks
+        let mut headers = vec![Block::new(Sha256::hash(b"genesis"), Height::new(1), 1)];
+        for i in 2..=5 {
+            let parent = headers.last().unwrap();
+            headers.push(Block::new(parent.digest(), Height::new(i), i));
+        }
+        let block = headers.pop().unwrap();
+
+        // Finalize the tip
+        let proposal = fixtures::proposal(5, block.digest());
+        let finalization = fixtures::finalization(&schemes, proposal);
+        let consistency = Consistency::new(Finalized::new(finalization, block), headers);
+        assert_eq!(consistency.from().height, Height::new(1));
+        assert_eq!(consistency.to().height, Height::new(5));
+
+        // Serialize and deserialize
+        let encoded = consistency.encode();
+        let decoded = Consistency::decode_cfg(encoded, &DigestMode::Sha256)
+            .expect("failed to decode consistency");
+        assert_eq!(consistency, decoded);
+
+        // Verify consistency
+        assert!(consistency.verify(&schemes[0], &Sequential));
+
+        // Verification fails if a header is missing
+        let mut broken = consistency.clone();
+        broken.headers.remove(1);
+        assert!(!broken.verify(&schemes[0], &Sequential));
+
+        // Verification fails if a header is replaced
+        let mut broken = consistency;
+        broken.headers[0] = Block::new(Sha256::hash(b"other"), Height::new(1), 1);
+        assert!(!broken.verify(&schemes[0], &Sequential));
+    }
+
+    #[test]
+    fn test_digest_mode() {
+        // Create network key
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+
+        // Digests depend on the mode
+        let parent = Sha256::hash(b"genesis");
+        let sha256 = Block::new(parent, Height::new(1), 1);
+        let blake3 = Block::new_with_mode(DigestMode::Blake3, parent, Height::new(1), 1);
+        assert_eq!(sha256.encode(), blake3.encode());
+        assert_ne!(sha256.digest(), blake3.digest());
+
+        // Finalize a block using BLAKE3 digests
+        let proposal = fixtures::proposal(1, blake3.digest());
+        let finalization = fixtures::finalization(&schemes, proposal);
+        let finalized = Finalized::new(finalization, blake3);
+        let encoded = finalized.encode();
+        let decoded = Finalized::decode_cfg(encoded.clone(), &DigestMode::Blake3)
+            .expect("failed to decode finalized");
+        assert_eq!(finalized, decoded);
+        assert!(decoded.verify(&schemes[0], &Sequential));
+
+        // Decoding with the wrong mode fails (the proof no longer matches the block)
+        assert!(Finalized::decode_cfg(encoded, &DigestMode::Sha256).is_err());
+
+        // Modes round-trip through their string representation
+        for mode in [DigestMode::Sha256, DigestMode::Blake3] {
+            assert_eq!(mode.to_string().parse::<DigestMode>(), Ok(mode));
+        }
+        assert!("poseidon".parse::<DigestMode>().is_err());
+    }
+
+    #[test]
+    fn test_receipts() {
+        // Blocks commit to their receipts
+        let parent = Sha256::hash(b"genesis");
+        let transaction = Transaction::new(b"transaction".to_vec()).unwrap();
+        let receipts = |receipts: &'static [u8]| {
+            Block::new_with_receipts(
+                DigestMode::Sha256,
+                INITIAL_RULES,
+                parent,
+                Height::new(1),
+                1,
+                vec![transaction.clone()],
+                Bytes::from_static(receipts),
+            )
+        };
+        let executed = receipts(b"ok");
+        assert_ne!(executed.digest(), receipts(b"").digest());
+        assert_ne!(executed.digest(), receipts(b"failed").digest());
+        let unexecuted = Block::new_with_transactions(
+            DigestMode::Sha256,
+            INITIAL_RULES,
+            parent,
+            Height::new(1),
+            1,
+            vec![transaction.clone()],
+        );
+        assert_eq!(unexecuted, receipts(b""));
+
+        // Receipts round-trip (and are covered by the digest)
+        let decoded = Block::decode_cfg(executed.encode(), &DigestMode::Blake3).unwrap();
+        assert_eq!(decoded.receipts.as_ref(), b"ok");
+        assert_ne!(decoded.digest(), executed.digest());
+        let decoded = Block::decode_cfg(executed.encode(), &DigestMode::Sha256).unwrap();
+        assert_eq!(decoded, executed);
+        assert_eq!(decoded.digest(), executed.digest());
+
+        // Oversized receipts are rejected
+        let mut block = receipts(b"");
+        block.receipts = Bytes::from(vec![0; MAX_RECEIPTS_SIZE]);
+        assert!(Block::decode_cfg(block.encode(), &DigestMode::Sha256).is_ok());
+        block.receipts = Bytes::from(vec![0; MAX_RECEIPTS_SIZE + 1]);
+        assert!(Block::decode_cfg(block.encode(), &DigestMode::Sha256).is_err());
+    }
+
+    #[test]
+    fn test_rules() {
+        // Blocks commit to the version of the rules they were built under
+        let parent = Sha256::hash(b"genesis");
+        let initial = Block::new(parent, Height::new(1), 1);
+        let upgraded = Block::new_with_rules(DigestMode::Sha256, 1, parent, Height::new(1), 1);
+        assert_eq!(initial.rules, INITIAL_RULES);
+        assert_eq!(upgraded.rules, 1);
+        assert_ne!(initial.digest(), upgraded.digest());
+        assert_ne!(initial.encode(), upgraded.encode());
+
+        // The version round-trips (and is covered by the digest)
+        for block in [initial, upgraded] {
+            let decoded = Block::decode_cfg(block.encode(), &DigestMode::Sha256).unwrap();
+            assert_eq!(decoded, block);
+            assert_eq!(decoded.digest(), block.digest());
+        }
+    }
+
+    #[test]
+    fn test_transactions() {
+        // Blocks commit to their transactions (and their order)
+        let parent = Sha256::hash(b"genesis");
+        let first = Transaction::new(b"first".to_vec()).unwrap();
+        let second = Transaction::new(b"second".to_vec()).unwrap();
+        let empty = Block::new(parent, Height::new(1), 1);
+        let block = Block::new_with_transactions(
+            DigestMode::Sha256,
+            INITIAL_RULES,
+            parent,
+            Height::new(1),
+            1,
+            vec![first.clone(), second.clone()],
+        );
+        let reordered = Block::new_with_transactions(
+            DigestMode::Sha256,
+            INITIAL_RULES,
+            parent,
+            Height::new(1),
+            1,
+            vec![second, first],
+        );
+        assert_ne!(empty.digest(), block.digest());
+        assert_ne!(block.digest(), reordered.digest());
+
+        // Transactions round-trip (and are covered by the digest)
+        let decoded = Block::decode_cfg(block.encode(), &DigestMode::Sha256).unwrap();
+        assert_eq!(decoded, block);
+        assert_eq!(decoded.digest(), block.digest());
+        assert_eq!(decoded.transactions[0].data().as_ref(), b"first");
+
+        // Oversized transactions are rejected
+        let largest = Transaction::new(vec![0; MAX_TRANSACTION_SIZE]).unwrap();
+        assert!(largest.encode_size() <= MAX_ENCODED_TRANSACTION_SIZE);
+        assert!(Transaction::new(vec![0; MAX_TRANSACTION_SIZE + 1]).is_none());
+        let mut encoded = empty.encode().to_vec();
+        encoded.pop();
+        encoded.push(1);
+        encoded.extend_from_slice(&(MAX_TRANSACTION_SIZE + 1).encode());
+        encoded.extend_from_slice(&vec![0; MAX_TRANSACTION_SIZE + 1]);
+        assert!(Block::decode_cfg(encoded.as_slice(), &DigestMode::Sha256).is_err());
+    }
+
+    #[test]
+    fn test_transactions_blake3() {
+        // A BLAKE3 block commits to its transactions using only BLAKE3
+        let parent = Sha256::hash(b"genesis");
+        let transaction = Transaction::new(b"transaction".to_vec()).unwrap();
+        let block = Block::new_with_transactions(
+            DigestMode::Blake3,
+            INITIAL_RULES,
+            parent,
+            Height::new(1),
+            1,
+            vec![transaction.clone()],
+        );
+        let mut hasher = Blake3::new();
+        hasher.update(&INITIAL_RULES.to_be_bytes());
+        hasher.update(&parent);
+        hasher.update(&1u64.to_be_bytes());
+        hasher.update(&1u64.to_be_bytes());
+        hasher.update(&1u64.to_be_bytes());
+        hasher.update(&Blake3::hash(transaction.data()));
+        hasher.update(&0u64.to_be_bytes());
+        assert_eq!(block.digest(), Digest::from(hasher.finalize().0));
+
+        // The SHA-256 transaction digest is not part of the commitment
+        let mut hasher = Blake3::new();
+        hasher.update(&INITIAL_RULES.to_be_bytes());
+        hasher.update(&parent);
+        hasher.update(&1u64.to_be_bytes());
+        hasher.update(&1u64.to_be_bytes());
+        hasher.update(&1u64.to_be_bytes());
+        hasher.update(&transaction.digest());
+        hasher.update(&0u64.to_be_bytes());
+        assert_ne!(block.digest(), Digest::from(hasher.finalize().0));
+    }
+
+    #[test]
+    fn test_genesis() {
+        // Create network key
+        let fixture = fixtures::seeded(0, 4);
+        let config = Sha256::hash(b"config");
+
+        // Unsigned transcripts are rejected
+        let polynomial = fixture.schemes[0].polynomial().clone();
+        let mut genesis = Genesis::new(fixture.participants.clone(), polynomial, config);
+        assert_eq!(genesis.identity, fixtures::identity(&fixture.schemes));
+        assert!(!genesis.verify());
+
+        // Transcripts missing a signature are rejected
+        for private_key in &fixture.private_keys[1..] {
+            assert!(genesis.sign(private_key));
+        }
+        assert!(!genesis.verify());
+
+        // Non-participants can't sign
+        let outsider = fixtures::seeded(1, 4);
+        assert!(!genesis.sign(&outsider.private_keys[0]));
+
+        // Fully signed transcripts are accepted
+        assert!(genesis.sign(&fixture.private_keys[0]));
+        assert!(genesis.verify());
+        assert_eq!(genesis, fixtures::genesis(&fixture, config));
+
+        // Serialize and deserialize
+        let encoded = genesis.encode();
+        let decoded = Genesis::decode_cfg(encoded, &NZU32!(4)).expect("failed to decode genesis");
+        assert_eq!(genesis, decoded);
+
+        // Modified transcripts are rejected
+        let mut modified = genesis.clone();
+        modified.config = Sha256::hash(b"other");
+        assert!(!modified.verify());
+        let mut modified = genesis.clone();
+        modified.identity = fixtures::identity(&outsider.schemes);
+        assert!(!modified.verify());
+        let mut modified = genesis;
+        modified.participants.swap(0, 1);
+        assert!(!modified.verify());
+    }
+
+    #[test]
+    fn test_nullification() {
+        // Create network key
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+
+        // Create a nullification
+        let nullification = fixtures::nullification(&schemes, 9);
+
+        // Serialize and deserialize
+        let encoded = nullification.encode();
+        let decoded = Nullification::decode(encoded).expect("failed to decode nullification");
+        assert_eq!(nullification, decoded);
+
+        // Verify nullification
+        assert!(nullification.verify::<_, Digest>(&mut OsRng, &schemes[0], &Sequential));
+    }
+
+    #[test]
+    fn test_payload() {
+        let Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let block = Block::new(Sha256::hash(b"genesis"), Height::new(1), 1000);
+
+        // Both variants expose the block
+        let payload = Payload::from(block.clone());
+        assert!(matches!(payload, Payload::Block(_)));
+        assert_eq!(payload.block(), &block);
+        let finalized = fixtures::finalized(&schemes, 1, block.clone());
+        let payload = Payload::from(finalized);
+        assert!(matches!(payload, Payload::Finalized(_)));
+        assert_eq!(payload.block(), &block);
+
+        // Boxing keeps the response pointer-sized
+        assert!(std::mem::size_of::<Payload>() <= 2 * std::mem::size_of::<usize>());
+    }
 }
@@ -0,0 +1,260 @@
+//! Light client that follows the finalized chain.
+//!
+//! A [LightClient] tracks the latest finalized block it has verified and only advances to a
+//! [Finalized] block that is signed by the identity of its round (see [IdentitySchedule]), is
+//! finalized in a later round at a greater height, and extends the latest block (directly, or
+//! through the headers of a [Consistency] proof). It performs no I/O (and so also compiles to
+//! WASM), leaving it to the caller to fetch artifacts from an indexer, a validator, or a cache.
+//!
+//! The first block accepted (or the checkpoint the client is created with) is trusted as the
+//! anchor of the chain, so it should be fetched from a trusted source (or checked against a
+//! known digest).
+
+use crate::{Block, Consistency, Finalized, IdentitySchedule, Scheme};
+use commonware_consensus::types::Round;
+use commonware_cryptography::{sha256::Digest, Digestible};
+use commonware_parallel::Strategy;
+use thiserror::Error;
+
+/// Errors that can occur when advancing a [LightClient].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("block at height {0} is not above the latest height")]
+    HeightNotIncreasing(u64),
+    #[error("block at height {0} is not finalized after the latest round")]
+    RoundNotIncreasing(u64),
+    #[error("block at height {0} does not follow the latest height")]
+    Gap(u64),
+    #[error("block at height {0} does not extend the latest block")]
+    BrokenChain(u64),
+}
+
+impl From<Error> for crate::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::InvalidSignature => Self::Signature,
+            _ => Self::Consistency(error.to_string()),
+        }
+    }
+}
+
+/// Follows the finalized chain, verifying every block it advances to.
+#[derive(Clone)]
+pub struct LightClient<S: Strategy> {
+    identities: IdentitySchedule,
+    strategy: S,
+    latest: Option<Finalized>,
+}
+
+impl<S: Strategy> LightClient<S> {
+    /// Create a new [LightClient] that verifies finalizations against `identities` (with
+    /// `strategy`) and trusts the first block it accepts.
+    pub fn new(identities: IdentitySchedule, strategy: S) -> Self {
+        Self {
+            identities,
+            strategy,
+            latest: None,
+        }
+    }
+
+    /// Create a new [LightClient] that follows the chain from `checkpoint` (which must be
+    /// signed by the identity of its round).
+    pub fn from_checkpoint(
+        identities: IdentitySchedule,
+        strategy: S,
+        checkpoint: Finalized,
+    ) -> Result<Self, Error> {
+        let mut client = Self::new(identities, strategy);
+        client.verify(&checkpoint)?;
+        client.latest = Some(checkpoint);
+        Ok(client)
+    }
+
+    /// Get the latest verified finalized block (if any).
+    pub fn latest(&self) -> Option<&Finalized> {
+        self.latest.as_ref()
+    }
+
+    /// Get the height of the latest verified block (if any).
+    pub fn height(&self) -> Option<u64> {
+        self.latest.as_ref().map(|latest| latest.block.height.get())
+    }
+
+    /// Get the digest of the latest verified block (if any).
+    pub fn digest(&self) -> Option<Digest> {
+        self.latest.as_ref().map(|latest| latest.block.digest())
+    }
+
+    /// Advance to `finalized`, which must be the block at the height after the latest block
+    /// (and extend it).
+    pub fn accept(&mut self, finalized: Finalized) -> Result<(), Error> {
+        self.check(&finalized)?;
+        if let Some(latest) = &self.latest {
+            let height = finalized.block.height.get();
+            if finalized.block.height != latest.block.height.next() {
+                return Err(Error::Gap(height));
+            }
+            if finalized.block.parent != latest.block.digest() {
+                return Err(Error::BrokenChain(height));
+            }
+        }
+        self.verify(&finalized)?;
+        self.latest = Some(finalized);
+        Ok(())
+    }
+
+    /// Advance to each of `finalized` (in order), stopping at the first that cannot be
+    /// accepted.
+    ///
+    /// Returns the number of blocks accepted (and the error that stopped it, if any).
+    pub fn accept_all(
+        &mut self,
+        finalized: impl IntoIterator<Item = Finalized>,
+    ) -> (usize, Option<Error>) {
+        let mut accepted = 0;
+        for finalized in finalized {
+            if let Err(e) = self.accept(finalized) {
+                return (accepted, Some(e));
+            }
+            accepted += 1;
+        }
+        (accepted, None)
+    }
+
+    /// Skip ahead to the finalized block of `consistency`, whose headers must start at the
+    /// latest block (proving the finalized block descends from it).
+    ///
+    /// Without a latest block, the finalized block is accepted (and trusted) on its own.
+    pub fn advance(&mut self, consistency: Consistency) -> Result<(), Error> {
+        let finalized = &consistency.finalized;
+        self.check(finalized)?;
+        if let Some(latest) = &self.latest {
+            let height = finalized.block.height.get();
+            if !links(consistency.from(), &latest.block) {
+                return Err(Error::BrokenChain(height));
+            }
+        }
+        if !consistency.verify(self.verifier(finalized), &self.strategy) {
+            // Distinguish a bad signature from headers that do not link
+            self.verify(finalized)?;
+            return Err(Error::BrokenChain(finalized.block.height.get()));
+        }
+        self.latest = Some(consistency.finalized);
+        Ok(())
+    }
+
+    /// Check `finalized` is above the latest block (in both height and round).
+    fn check(&self, finalized: &Finalized) -> Result<(), Error> {
+        let Some(latest) = &self.latest else {
+            return Ok(());
+        };
+        let height = finalized.block.height.get();
+        if finalized.block.height <= latest.block.height {
+            return Err(Error::HeightNotIncreasing(height));
+        }
+        if round(finalized) <= round(latest) {
+            return Err(Error::RoundNotIncreasing(height));
+        }
+        Ok(())
+    }
+
+    /// Verify the signature of `finalized` against the identity of its round.
+    fn verify(&self, finalized: &Finalized) -> Result<(), Error> {
+        if !finalized.verify(self.verifier(finalized), &self.strategy) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// Get the verifier for the round `finalized` was finalized in.
+    fn verifier(&self, finalized: &Finalized) -> &Scheme {
+        self.identities.verifier(round(finalized))
+    }
+}
+
+/// Get the round `finalized` was finalized in.
+fn round(finalized: &Finalized) -> Round {
+    finalized.proof.proposal.round
+}
+
+/// Returns true if `header` is the `latest` block.
+fn links(header: &Block, latest: &Block) -> bool {
+    header.height == latest.height && header.digest() == latest.digest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Hasher, Sha256};
+    use commonware_parallel::Sequential;
+
+    /// Build a chain of `length` finalized blocks (finalized in the view of their height).
+    fn chain(schemes: &[Scheme], length: u64) -> Vec<Finalized> {
+        let mut parent = Sha256::hash(b"genesis");
+        (1..=length)
+            .map(|height| {
+                let block = Block::new(parent, Height::new(height), 1000 + height);
+                parent = block.digest();
+                fixtures::finalized(schemes, height, block)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_light_client() {
+        let schemes = fixtures::seeded(0, 4).schemes;
+        let identities = IdentitySchedule::new(fixtures::identity(&schemes));
+        let chain = chain(&schemes, 6);
+
+        // The first block is trusted and each block must extend the latest
+        let mut client = LightClient::new(identities.clone(), Sequential);
+        assert!(client.latest().is_none());
+        assert_eq!(client.accept_all(chain[..3].to_vec()), (3, None));
+        assert_eq!(client.height(), Some(3));
+        assert_eq!(client.digest(), Some(chain[2].block.digest()));
+
+        // Old, skipped, and unlinked blocks are rejected
+        assert_eq!(
+            client.accept(chain[1].clone()),
+            Err(Error::HeightNotIncreasing(2))
+        );
+        assert_eq!(client.accept(chain[4].clone()), Err(Error::Gap(5)));
+        let fork = Block::new(Sha256::hash(b"fork"), Height::new(4), 2000);
+        let forked = fixtures::finalized(&schemes, 4, fork);
+        assert_eq!(client.accept(forked), Err(Error::BrokenChain(4)));
+        let late = fixtures::finalized(&schemes, 3, chain[3].block.clone());
+        assert_eq!(client.accept(late), Err(Error::RoundNotIncreasing(4)));
+
+        // Blocks signed by another identity are rejected
+        let other = fixtures::seeded(1, 4).schemes;
+        let unsigned = fixtures::finalized(&other, 4, chain[3].block.clone());
+        assert_eq!(client.accept(unsigned), Err(Error::InvalidSignature));
+        assert_eq!(client.height(), Some(3));
+
+        // Consistency proofs skip ahead (if their headers start at the latest block)
+        let headers = chain[2..5].iter().map(|f| f.block.clone()).collect();
+        let consistency = Consistency::new(chain[5].clone(), headers);
+        let mut skipping = client.clone();
+        skipping.advance(consistency).unwrap();
+        assert_eq!(skipping.height(), Some(6));
+        let headers = chain[3..5].iter().map(|f| f.block.clone()).collect();
+        let consistency = Consistency::new(chain[5].clone(), headers);
+        assert_eq!(client.advance(consistency), Err(Error::BrokenChain(6)));
+        assert_eq!(client.accept_all(chain[3..].to_vec()), (3, None));
+        assert_eq!(client.latest(), Some(&chain[5]));
+
+        // Checkpoints must be signed
+        let checkpoint = LightClient::from_checkpoint(identities, Sequential, chain[2].clone());
+        assert_eq!(checkpoint.unwrap().height(), Some(3));
+        let unsigned = fixtures::finalized(&other, 3, chain[2].block.clone());
+        let identities = IdentitySchedule::new(fixtures::identity(&schemes));
+        assert!(matches!(
+            LightClient::from_checkpoint(identities, Sequential, unsigned),
+            Err(Error::InvalidSignature)
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+//! Identity schedules for networks that re-key.
+//!
+//! A network's [Identity] is fixed for as long as its validator set shares the same threshold
+//! key. If the network reshares to a new key (or starts a new epoch with a different validator
+//! set), artifacts from before the transition are still signed by the old identity while
+//! artifacts after it are signed by the new one. An [IdentitySchedule] records which identity is
+//! valid from which round, so that verifiers pick the right one for each artifact (and keep
+//! verifying across the transition).
+
+use crate::{Finalized, Identity, Scheme, EPOCH, NAMESPACE};
+use commonware_consensus::types::{Epoch, Round, View};
+use commonware_parallel::Strategy;
+use std::collections::BTreeMap;
+
+/// The [Identity] (and certificate verifier) valid from each round.
+#[derive(Clone)]
+pub struct IdentitySchedule {
+    /// Verifiers (sorted by the first round they are valid for, starting at genesis).
+    verifiers: Vec<(Round, Scheme)>,
+}
+
+impl IdentitySchedule {
+    /// Create a new [IdentitySchedule] where `identity` is valid from genesis.
+    pub fn new(identity: Identity) -> Self {
+        Self {
+            verifiers: vec![(
+                Round::new(EPOCH, View::zero()),
+                Scheme::certificate_verifier(NAMESPACE, identity),
+            )],
+        }
+    }
+
+    /// Make `identity` valid from `view` (of the first epoch) onwards (replacing any identity
+    /// already scheduled at `view`).
+    pub fn rotate(&mut self, view: u64, identity: Identity) {
+        self.schedule(Round::new(EPOCH, View::new(view)), identity);
+    }
+
+    /// Make `identity` valid from the start of `epoch` onwards (replacing any identity already
+    /// scheduled at the start of `epoch`).
+    pub fn reconfigure(&mut self, epoch: u64, identity: Identity) {
+        self.schedule(Round::new(Epoch::new(epoch), View::zero()), identity);
+    }
+
+    fn schedule(&mut self, round: Round, identity: Identity) {
+        let verifier = Scheme::certificate_verifier(NAMESPACE, identity);
+        match self
+            .verifiers
+            .binary_search_by_key(&round, |(start, _)| *start)
+        {
+            Ok(index) => self.verifiers[index].1 = verifier,
+            Err(index) => self.verifiers.insert(index, (round, verifier)),
+        }
+    }
+
+    /// Get the identity the network had at genesis.
+    pub fn genesis(&self) -> &Identity {
+        self.verifiers[0].1.identity()
+    }
+
+    /// Get the certificate verifier for artifacts from `round`.
+    pub fn verifier(&self, round: Round) -> &Scheme {
+        &self.verifiers[self.position(round)].1
+    }
+
+    /// Verify the proofs of all `finalized` blocks against the verifier of their round (batching
+    /// those that share a verifier, see [Finalized::verify_batch]), returning the indices of the
+    /// invalid ones (empty if all are valid).
+    pub fn verify_finalized(
+        &self,
+        strategy: &impl Strategy,
+        finalized: &[Finalized],
+    ) -> Vec<usize> {
+        // Group blocks by verifier
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (index, finalized) in finalized.iter().enumerate() {
+            let position = self.position(finalized.proof.round());
+            groups.entry(position).or_default().push(index);
+        }
+
+        // Verify each group in a batch
+        let mut invalid = Vec::new();
+        for (position, indices) in groups {
+            let group: Vec<&Finalized> = indices.iter().map(|index| &finalized[*index]).collect();
+            let verifier = &self.verifiers[position].1;
+            invalid.extend(
+                Finalized::verify_all(verifier, strategy, &group)
+                    .into_iter()
+                    .map(|offset| indices[offset]),
+            );
+        }
+        invalid.sort_unstable();
+        invalid
+    }
+
+    /// Get the position of the verifier for artifacts from `round`.
+    fn position(&self, round: Round) -> usize {
+        self.verifiers.partition_point(|(start, _)| *start <= round) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, Block};
+    use commonware_consensus::types::Height;
+    use commonware_cryptography::{Hasher, Sha256};
+    use commonware_parallel::Sequential;
+
+    fn round(epoch: u64, view: u64) -> Round {
+        Round::new(Epoch::new(epoch), View::new(view))
+    }
+
+    #[test]
+    fn test_schedule() {
+        let old = fixtures::identity(&fixtures::seeded(0, 4).schemes);
+        let new = fixtures::identity(&fixtures::seeded(1, 4).schemes);
+        let newest = fixtures::identity(&fixtures::seeded(2, 4).schemes);
+        let mut schedule = IdentitySchedule::new(old);
+        schedule.rotate(200, newest);
+        schedule.rotate(100, new);
+
+        // Each view uses the identity valid at that view
+        assert_eq!(*schedule.verifier(round(0, 0)).identity(), old);
+        assert_eq!(*schedule.verifier(round(0, 99)).identity(), old);
+        assert_eq!(*schedule.verifier(round(0, 100)).identity(), new);
+        assert_eq!(*schedule.verifier(round(0, 199)).identity(), new);
+        assert_eq!(*schedule.verifier(round(0, u64::MAX)).identity(), newest);
+        assert_eq!(*schedule.genesis(), old);
+
+        // Rotating at an existing view replaces the identity
+        schedule.rotate(0, new);
+        assert_eq!(*schedule.verifier(round(0, 0)).identity(), new);
+        assert_eq!(*schedule.genesis(), new);
+    }
+
+    #[test]
+    fn test_reconfigure() {
+        let first = fixtures::identity(&fixtures::seeded(0, 4).schemes);
+        let second = fixtures::identity(&fixtures::seeded(1, 5).schemes);
+        let third = fixtures::identity(&fixtures::seeded(2, 3).schemes);
+        let mut schedule = IdentitySchedule::new(first);
+        schedule.reconfigure(2, third);
+        schedule.reconfigure(1, second);
+
+        // Views restart in every epoch, so later epochs use their identity from their first view
+        assert_eq!(*schedule.verifier(round(0, 1_000)).identity(), first);
+        assert_eq!(*schedule.verifier(round(1, 1)).identity(), second);
+        assert_eq!(*schedule.verifier(round(1, 1_000)).identity(), second);
+        assert_eq!(*schedule.verifier(round(2, 1)).identity(), third);
+        assert_eq!(*schedule.verifier(round(7, 1)).identity(), third);
+        assert_eq!(*schedule.genesis(), first);
+    }
+
+    #[test]
+    fn test_verify_finalized() {
+        let old = fixtures::seeded(0, 4).schemes;
+        let new = fixtures::seeded(1, 4).schemes;
+        let mut schedule = IdentitySchedule::new(fixtures::identity(&old));
+        schedule.rotate(5, fixtures::identity(&new));
+        let finalized = |schemes: &[Scheme], view: u64| {
+            let block = Block::new(Sha256::hash(b"genesis"), Height::new(view), view);
+            fixtures::finalized(schemes, view, block)
+        };
+
+        // Each block is verified against the identity of its view
+        let mut batch: Vec<_> = (1..5)
+            .map(|view| finalized(&old, view))
+            .chain((5..9).map(|view| finalized(&new, view)))
+            .collect();
+        assert!(schedule.verify_finalized(&Sequential, &batch).is_empty());
+
+        // Blocks signed by the wrong identity are identified (in order)
+        batch[1] = finalized(&new, 2);
+        batch[6] = finalized(&old, 7);
+        assert_eq!(schedule.verify_finalized(&Sequential, &batch), vec![1, 6]);
+    }
+}
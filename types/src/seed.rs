@@ -0,0 +1,102 @@
+//! Helpers for inspecting [Seed]s.
+
+use crate::{Seed, NAMESPACE};
+use commonware_codec::Encode;
+use commonware_consensus::Viewable;
+use commonware_utils::{hex, union_unique};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// Size (in bytes) of the randomness derived from a [Seed].
+pub const RANDOMNESS_SIZE: usize = 32;
+
+/// Suffix appended to [NAMESPACE] to derive the salt used for [SeedExt::randomness].
+const RANDOMNESS_SUFFIX: &[u8] = b"_ENTROPY";
+
+/// Convenience accessors for [Seed]s.
+pub trait SeedExt {
+    /// Get the encoded signature of the seed.
+    fn signature_bytes(&self) -> Vec<u8>;
+
+    /// Derive randomness from the seed using HKDF-SHA256 over its signature, bound to its round.
+    ///
+    /// The seed must be verified first (anyone can derive "randomness" from a forged seed).
+    fn randomness(&self) -> [u8; RANDOMNESS_SIZE];
+
+    /// Summarize the seed as a [SeedInfo].
+    fn info(&self) -> SeedInfo;
+}
+
+impl SeedExt for Seed {
+    fn signature_bytes(&self) -> Vec<u8> {
+        self.signature.encode().to_vec()
+    }
+
+    fn randomness(&self) -> [u8; RANDOMNESS_SIZE] {
+        let salt = union_unique(NAMESPACE, RANDOMNESS_SUFFIX);
+        let hkdf = Hkdf::<Sha256>::new(Some(&salt), &self.signature_bytes());
+        let mut output = [0u8; RANDOMNESS_SIZE];
+        hkdf.expand(&self.round.encode(), &mut output)
+            .expect("output is a valid length");
+        output
+    }
+
+    fn info(&self) -> SeedInfo {
+        SeedInfo {
+            epoch: self.round.epoch().get(),
+            view: self.view().get(),
+            signature: hex(&self.signature_bytes()),
+            randomness: hex(&self.randomness()),
+        }
+    }
+}
+
+/// Summary of a [Seed] (for display and JSON serialization).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedInfo {
+    /// Epoch of the seed.
+    pub epoch: u64,
+
+    /// View of the seed.
+    pub view: u64,
+
+    /// Hex-encoded signature of the seed.
+    pub signature: String,
+
+    /// Hex-encoded randomness derived from the seed (see [SeedExt::randomness]).
+    pub randomness: String,
+}
+
+impl From<&Seed> for SeedInfo {
+    fn from(seed: &Seed) -> Self {
+        seed.info()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, EPOCH};
+
+    #[test]
+    fn test_seed_info() {
+        let fixtures::Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let seed = fixtures::seed(&schemes, 1);
+
+        // Summaries carry the round and hex-encoded signature
+        let info = seed.info();
+        assert_eq!(info.epoch, EPOCH.get());
+        assert_eq!(info.view, 1);
+        assert_eq!(info.signature, hex(&seed.signature.encode()));
+        assert_eq!(info.randomness, hex(&seed.randomness()));
+
+        // Randomness is deterministic, differs across views, and is not the raw signature
+        assert_eq!(seed.randomness(), fixtures::seed(&schemes, 1).randomness());
+        assert_ne!(seed.randomness(), fixtures::seed(&schemes, 2).randomness());
+        assert_ne!(
+            seed.randomness().as_slice(),
+            &seed.signature_bytes()[..RANDOMNESS_SIZE]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{
+    consensus::{Identity, PublicKey},
+    genesis::{Genesis, MAX_GENESIS_PARTICIPANTS},
+    DigestMode, Error, NAMESPACE,
+};
+use commonware_codec::{Decode, DecodeExt, Encode};
+use commonware_utils::{from_hex_formatted, hex};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Consensus timeouts (in milliseconds) every validator of a network should use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timeouts {
+    /// Time to wait for a leader to propose.
+    pub leader_timeout: u64,
+
+    /// Time to wait for a proposal to be notarized.
+    pub notarization_timeout: u64,
+
+    /// Time to wait before rebroadcasting a nullify vote.
+    pub nullify_retry: u64,
+}
+
+/// Description of a network shared by every tool that interacts with it.
+///
+/// The spec is written (as JSON) by `setup` next to the validator configs and replaces the
+/// identity, genesis, and digest mode each tool would otherwise be configured with separately.
+/// Everything but `chain_id` and `timeouts` is committed to by the (signed) genesis transcript,
+/// so [Spec::genesis] rejects a spec that was edited after it was generated.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spec {
+    /// Human-readable name of the network (like `alto-local`).
+    pub chain_id: String,
+
+    /// Namespace prefix used in all signing operations (must be [NAMESPACE]).
+    pub namespace: String,
+
+    /// Hex-encoded identity of the network.
+    pub identity: String,
+
+    /// Hex-encoded participants (sorted) of the network.
+    pub participants: Vec<String>,
+
+    /// Hex-encoded genesis transcript.
+    pub genesis: String,
+
+    /// Hash function used to compute block digests.
+    #[serde(default)]
+    pub digest_mode: DigestMode,
+
+    /// Consensus timeouts (if not set, validators use those of their profile).
+    #[serde(default)]
+    pub timeouts: Option<Timeouts>,
+}
+
+impl Spec {
+    /// Create a spec for the network described by `genesis`.
+    pub fn new(
+        chain_id: impl Into<String>,
+        genesis: &Genesis,
+        digest_mode: DigestMode,
+        timeouts: Option<Timeouts>,
+    ) -> Self {
+        Self {
+            chain_id: chain_id.into(),
+            namespace: String::from_utf8_lossy(NAMESPACE).into_owned(),
+            identity: hex(&genesis.identity.encode()),
+            participants: genesis
+                .participants
+                .iter()
+                .map(|participant| hex(&participant.encode()))
+                .collect(),
+            genesis: hex(&genesis.encode()),
+            digest_mode,
+            timeouts,
+        }
+    }
+
+    /// Parse a JSON-encoded spec.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|_| invalid("invalid json"))
+    }
+
+    /// Encode the spec as (pretty-printed) JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("spec is serializable")
+    }
+
+    /// Read a JSON-encoded spec from `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Decode the identity of the network.
+    pub fn identity(&self) -> Result<Identity, Error> {
+        let bytes = from_hex_formatted(&self.identity).ok_or(invalid("invalid identity hex"))?;
+        Ok(Identity::decode(bytes.as_ref())?)
+    }
+
+    /// Decode the participants of the network.
+    pub fn participants(&self) -> Result<Vec<PublicKey>, Error> {
+        self.participants
+            .iter()
+            .map(|participant| {
+                let bytes =
+                    from_hex_formatted(participant).ok_or(invalid("invalid participant hex"))?;
+                Ok(PublicKey::decode(bytes.as_ref())?)
+            })
+            .collect()
+    }
+
+    /// Decode the genesis transcript, verifying it is signed by every participant and agrees
+    /// with the rest of the spec.
+    pub fn genesis(&self) -> Result<Genesis, Error> {
+        if self.namespace.as_bytes() != NAMESPACE {
+            return Err(Error::Consistency(format!(
+                "namespace {} is not supported",
+                self.namespace
+            )));
+        }
+        let bytes = from_hex_formatted(&self.genesis).ok_or(invalid("invalid genesis hex"))?;
+        let genesis = Genesis::decode_cfg(bytes.as_ref(), &MAX_GENESIS_PARTICIPANTS)?;
+        if !genesis.verify() {
+            return Err(Error::Signature);
+        }
+        if genesis.identity != self.identity()? {
+            return Err(Error::Consistency(
+                "identity does not match the genesis transcript".to_string(),
+            ));
+        }
+        if genesis.participants != self.participants()? {
+            return Err(Error::Consistency(
+                "participants do not match the genesis transcript".to_string(),
+            ));
+        }
+        Ok(genesis)
+    }
+}
+
+/// Construct an error for a malformed spec.
+fn invalid(reason: &'static str) -> Error {
+    Error::Codec(commonware_codec::Error::Invalid("Spec", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use commonware_cryptography::sha256::Digest;
+
+    #[test]
+    fn test_spec() {
+        let fixture = fixtures::seeded(0, 4);
+        let genesis = fixtures::genesis(&fixture, Digest::from([0u8; 32]));
+        let timeouts = Timeouts {
+            leader_timeout: 250,
+            notarization_timeout: 500,
+            nullify_retry: 2_000,
+        };
+        let spec = Spec::new("alto-test", &genesis, DigestMode::Blake3, Some(timeouts));
+
+        // Round-trips through JSON
+        let decoded = Spec::from_json(&spec.to_json()).unwrap();
+        assert_eq!(decoded, spec);
+        assert_eq!(decoded.genesis().unwrap(), genesis);
+        assert_eq!(decoded.identity().unwrap(), genesis.identity);
+
+        // Optional fields take their defaults
+        let mut value: serde_json::Value = serde_json::from_str(&spec.to_json()).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("digest_mode");
+        object.remove("timeouts");
+        let minimal = Spec::from_json(&value.to_string()).unwrap();
+        assert_eq!(minimal.digest_mode, DigestMode::Sha256);
+        assert_eq!(minimal.timeouts, None);
+
+        // Edits are rejected
+        let mut edited = spec.clone();
+        edited.participants.pop();
+        assert!(matches!(edited.genesis(), Err(Error::Consistency(_))));
+        let mut edited = spec.clone();
+        let other = fixtures::seeded(1, 4);
+        edited.identity = hex(&fixtures::identity(&other.schemes).encode());
+        assert!(matches!(edited.genesis(), Err(Error::Consistency(_))));
+        let mut edited = spec.clone();
+        edited.namespace = "_OTHER".to_string();
+        assert!(matches!(edited.genesis(), Err(Error::Consistency(_))));
+        assert!(matches!(Spec::from_json("{}"), Err(Error::Codec(_))));
+
+        // Unsigned transcripts are rejected
+        let unsigned = Genesis::new(
+            genesis.participants.clone(),
+            genesis.polynomial.clone(),
+            genesis.config,
+        );
+        let spec = Spec::new("alto-test", &unsigned, DigestMode::Sha256, None);
+        assert!(matches!(spec.genesis(), Err(Error::Signature)));
+    }
+}
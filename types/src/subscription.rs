@@ -0,0 +1,168 @@
+use crate::{Error, Kind};
+use std::{fmt, str::FromStr};
+
+/// The consensus messages a websocket subscriber wants to receive.
+///
+/// Subscriptions are sent as the query string of `/consensus/ws` (like
+/// `kinds=seed,finalization&start_view=10&end_view=20`). The default subscription (an empty
+/// query string) receives every new message.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Subscription {
+    /// Kinds of messages to receive (all kinds, if `None`).
+    pub kinds: Option<Vec<Kind>>,
+
+    /// Only receive messages with views at or after this view.
+    pub start_view: Option<u64>,
+
+    /// Only receive messages with views before this view.
+    pub end_view: Option<u64>,
+
+    /// Replay stored messages with views at or after this view before receiving new ones.
+    pub from_view: Option<u64>,
+}
+
+impl Subscription {
+    /// Only receive messages of `kinds`.
+    pub fn with_kinds(mut self, kinds: impl IntoIterator<Item = Kind>) -> Self {
+        self.kinds = Some(kinds.into_iter().collect());
+        self
+    }
+
+    /// Only receive messages with views at or after `view`.
+    pub fn with_start_view(mut self, view: u64) -> Self {
+        self.start_view = Some(view);
+        self
+    }
+
+    /// Only receive messages with views before `view`.
+    pub fn with_end_view(mut self, view: u64) -> Self {
+        self.end_view = Some(view);
+        self
+    }
+
+    /// Replay stored messages with views at or after `view` before receiving new ones.
+    pub fn with_from_view(mut self, view: u64) -> Self {
+        self.from_view = Some(view);
+        self
+    }
+
+    /// Returns true if a message of `kind` at `view` should be sent to the subscriber.
+    pub fn matches(&self, kind: Kind, view: u64) -> bool {
+        if let Some(kinds) = &self.kinds {
+            if !kinds.contains(&kind) {
+                return false;
+            }
+        }
+        if self.start_view.is_some_and(|start| view < start) {
+            return false;
+        }
+        if self.end_view.is_some_and(|end| view >= end) {
+            return false;
+        }
+        true
+    }
+}
+
+impl fmt::Display for Subscription {
+    /// Format the subscription as a query string (without the leading `?`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params = Vec::new();
+        if let Some(kinds) = &self.kinds {
+            let kinds: Vec<_> = kinds.iter().map(Kind::name).collect();
+            params.push(format!("kinds={}", kinds.join(",")));
+        }
+        if let Some(start) = self.start_view {
+            params.push(format!("start_view={start}"));
+        }
+        if let Some(end) = self.end_view {
+            params.push(format!("end_view={end}"));
+        }
+        if let Some(from) = self.from_view {
+            params.push(format!("from_view={from}"));
+        }
+        write!(f, "{}", params.join("&"))
+    }
+}
+
+impl FromStr for Subscription {
+    type Err = Error;
+
+    /// Parse a query string (without the leading `?`), ignoring unknown parameters.
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        let mut subscription = Self::default();
+        for param in query.split('&').filter(|param| !param.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            match key {
+                "kinds" => {
+                    let kinds = value
+                        .split(',')
+                        .map(|name| Kind::from_name(name).ok_or(invalid("unknown kind")))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    subscription.kinds = Some(kinds);
+                }
+                "start_view" => {
+                    let view = value.parse().map_err(|_| invalid("invalid start_view"))?;
+                    subscription.start_view = Some(view);
+                }
+                "end_view" => {
+                    let view = value.parse().map_err(|_| invalid("invalid end_view"))?;
+                    subscription.end_view = Some(view);
+                }
+                "from_view" => {
+                    let view = value.parse().map_err(|_| invalid("invalid from_view"))?;
+                    subscription.from_view = Some(view);
+                }
+                _ => {}
+            }
+        }
+        Ok(subscription)
+    }
+}
+
+/// Construct an error for a malformed subscription.
+fn invalid(reason: &'static str) -> Error {
+    Error::Codec(commonware_codec::Error::Invalid("Subscription", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription() {
+        // The default subscription receives everything (and has no query string)
+        let all = Subscription::default();
+        assert_eq!(all.to_string(), "");
+        assert_eq!("".parse::<Subscription>().unwrap(), all);
+        assert!(all.matches(Kind::Nullification, 0));
+
+        // Filters round-trip through the query string
+        let filtered = Subscription::default()
+            .with_kinds([Kind::Seed, Kind::Finalization])
+            .with_start_view(10)
+            .with_end_view(20)
+            .with_from_view(12);
+        let query = filtered.to_string();
+        assert_eq!(
+            query,
+            "kinds=seed,finalization&start_view=10&end_view=20&from_view=12"
+        );
+        assert_eq!(query.parse::<Subscription>().unwrap(), filtered);
+        assert_eq!(
+            "other=1&start_view=10".parse::<Subscription>().unwrap(),
+            Subscription::default().with_start_view(10)
+        );
+
+        // Only matching kinds and views (in `[start_view, end_view)`) are received
+        assert!(filtered.matches(Kind::Seed, 10));
+        assert!(filtered.matches(Kind::Finalization, 19));
+        assert!(!filtered.matches(Kind::Notarization, 15));
+        assert!(!filtered.matches(Kind::Seed, 9));
+        assert!(!filtered.matches(Kind::Seed, 20));
+
+        // Malformed filters are rejected
+        assert!("kinds=block".parse::<Subscription>().is_err());
+        assert!("kinds=".parse::<Subscription>().is_err());
+        assert!("start_view=latest".parse::<Subscription>().is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use bytes::{Buf, BufMut, Bytes};
+use commonware_codec::{EncodeSize, Error, RangeCfg, Read, Write};
+use commonware_cryptography::{sha256::Digest, Digestible, Hasher, Sha256};
+
+/// The maximum size (in bytes) of a [Transaction]'s data.
+pub const MAX_TRANSACTION_SIZE: usize = 64 * 1024;
+
+/// The maximum encoded size (in bytes) of a [Transaction] (its data and a varint length prefix).
+pub const MAX_ENCODED_TRANSACTION_SIZE: usize = MAX_TRANSACTION_SIZE + 5;
+
+/// The maximum number of [Transaction]s in a [crate::Block].
+pub const MAX_BLOCK_TRANSACTIONS: usize = 16_384;
+
+/// Opaque data included in a [crate::Block].
+///
+/// `alto` does not interpret transactions: any data (up to [MAX_TRANSACTION_SIZE] bytes) is
+/// valid. A transaction is identified by the SHA-256 digest of its data (regardless of the
+/// [crate::DigestMode] of the blocks that include it). Blocks, however, commit to the digest of
+/// each transaction's data computed with their own [crate::DigestMode].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Transaction {
+    data: Bytes,
+
+    /// Pre-computed digest of the data.
+    digest: Digest,
+}
+
+impl Transaction {
+    /// Create a new transaction (returning `None` if `data` exceeds [MAX_TRANSACTION_SIZE]).
+    pub fn new(data: impl Into<Bytes>) -> Option<Self> {
+        let data = data.into();
+        if data.len() > MAX_TRANSACTION_SIZE {
+            return None;
+        }
+        let digest = Sha256::hash(&data);
+        Some(Self { data, digest })
+    }
+
+    /// The transaction's data.
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+}
+
+impl Write for Transaction {
+    fn write(&self, writer: &mut impl BufMut) {
+        self.data.write(writer);
+    }
+}
+
+impl Read for Transaction {
+    type Cfg = ();
+
+    fn read_cfg(reader: &mut impl Buf, _: &Self::Cfg) -> Result<Self, Error> {
+        let range: RangeCfg<usize> = (..=MAX_TRANSACTION_SIZE).into();
+        let data = Bytes::read_cfg(reader, &range)?;
+
+        // Pre-compute the digest
+        let digest = Sha256::hash(&data);
+        Ok(Self { data, digest })
+    }
+}
+
+impl EncodeSize for Transaction {
+    fn encode_size(&self) -> usize {
+        self.data.encode_size()
+    }
+}
+
+impl Digestible for Transaction {
+    type Digest = Digest;
+
+    fn digest(&self) -> Digest {
+        self.digest
+    }
+}
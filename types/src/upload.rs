@@ -0,0 +1,176 @@
+use crate::{Kind, PublicKey};
+use bytes::{Buf, BufMut, Bytes};
+use commonware_codec::{
+    varint::UInt, EncodeSize, Error, FixedSize, RangeCfg, Read, ReadExt, Write,
+};
+use commonware_cryptography::ed25519::Signature;
+
+/// Tag of the frame sent over the consensus websocket (followed by an [Ack]) in response to an
+/// [Upload] received on the same socket.
+///
+/// Like [crate::LAGGED_FRAME], this is not a [Kind] and is chosen to never collide with one.
+pub const ACK_FRAME: u8 = u8::MAX - 1;
+
+/// Maximum number of [Upload]s in a single batch (sent to the indexer's `/batch` route).
+pub const MAX_BATCH_UPLOADS: usize = 256;
+
+/// An artifact uploaded over the consensus websocket (instead of with an HTTP `POST`).
+///
+/// The indexer responds to each upload with an [Ack] carrying the same `id`. Uploads are
+/// authenticated like HTTP uploads: `auth` (if present) is the signer, the signed timestamp, and
+/// the signature over the upload message of `body` (see `alto_client::upload_message`).
+///
+/// Uploads are also sent in batches (of at most [MAX_BATCH_UPLOADS]) to the indexer's `/batch`
+/// route. A batch is authenticated as a whole (like any HTTP upload), so its uploads carry no
+/// `auth` of their own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Upload {
+    /// Identifier chosen by the uploader (echoed in the [Ack]).
+    pub id: u64,
+    /// Kind of artifact in `body`.
+    pub kind: Kind,
+    /// Authentication of the upload (if signed).
+    pub auth: Option<(PublicKey, u64, Signature)>,
+    /// The encoded artifact.
+    pub body: Bytes,
+}
+
+impl Write for Upload {
+    fn write(&self, writer: &mut impl BufMut) {
+        UInt(self.id).write(writer);
+        (self.kind as u8).write(writer);
+        match &self.auth {
+            Some((signer, timestamp, signature)) => {
+                true.write(writer);
+                signer.write(writer);
+                timestamp.write(writer);
+                signature.write(writer);
+            }
+            None => false.write(writer),
+        }
+        self.body.write(writer);
+    }
+}
+
+impl Read for Upload {
+    /// Maximum size (in bytes) of the body.
+    type Cfg = usize;
+
+    fn read_cfg(reader: &mut impl Buf, max: &Self::Cfg) -> Result<Self, Error> {
+        let id = UInt::read(reader)?.into();
+        let kind = u8::read(reader)?;
+        let kind = Kind::from_u8(kind).ok_or(Error::InvalidEnum(kind))?;
+        let auth = if bool::read(reader)? {
+            let signer = PublicKey::read(reader)?;
+            let timestamp = u64::read(reader)?;
+            let signature = Signature::read(reader)?;
+            Some((signer, timestamp, signature))
+        } else {
+            None
+        };
+        let range: RangeCfg<usize> = (..=*max).into();
+        let body = Bytes::read_cfg(reader, &range)?;
+        Ok(Self {
+            id,
+            kind,
+            auth,
+            body,
+        })
+    }
+}
+
+impl EncodeSize for Upload {
+    fn encode_size(&self) -> usize {
+        let auth = match &self.auth {
+            Some(_) => PublicKey::SIZE + u64::SIZE + Signature::SIZE,
+            None => 0,
+        };
+        UInt(self.id).encode_size() + u8::SIZE + bool::SIZE + auth + self.body.encode_size()
+    }
+}
+
+/// The outcome of an [Upload] (sent after [ACK_FRAME]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ack {
+    /// Identifier of the acknowledged [Upload].
+    pub id: u64,
+    /// HTTP status code the upload would have received over HTTP (`200` if accepted).
+    pub status: u16,
+}
+
+impl Ack {
+    /// Returns true if the upload was accepted.
+    pub fn accepted(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+impl Write for Ack {
+    fn write(&self, writer: &mut impl BufMut) {
+        UInt(self.id).write(writer);
+        self.status.write(writer);
+    }
+}
+
+impl Read for Ack {
+    type Cfg = ();
+
+    fn read_cfg(reader: &mut impl Buf, _: &Self::Cfg) -> Result<Self, Error> {
+        let id = UInt::read(reader)?.into();
+        let status = u16::read(reader)?;
+        Ok(Self { id, status })
+    }
+}
+
+impl EncodeSize for Ack {
+    fn encode_size(&self) -> usize {
+        UInt(self.id).encode_size() + u16::SIZE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commonware_codec::{Decode, DecodeExt, Encode};
+    use commonware_cryptography::{ed25519, Signer};
+
+    #[test]
+    fn test_upload_codec() {
+        let signer = ed25519::PrivateKey::from_seed(0);
+        let signature = signer.sign(b"namespace", b"message");
+        for auth in [None, Some((signer.public_key(), 1_000, signature))] {
+            let upload = Upload {
+                id: 300,
+                kind: Kind::Finalization,
+                auth,
+                body: Bytes::from_static(b"artifact"),
+            };
+            let encoded = upload.encode();
+            assert_eq!(encoded.len(), upload.encode_size());
+            assert_eq!(Upload::decode_cfg(encoded.clone(), &8).unwrap(), upload);
+
+            // Bodies larger than the limit are rejected
+            assert!(Upload::decode_cfg(encoded, &7).is_err());
+        }
+
+        // Unknown kinds are rejected
+        let mut encoded = Upload {
+            id: 0,
+            kind: Kind::Seed,
+            auth: None,
+            body: Bytes::new(),
+        }
+        .encode()
+        .to_vec();
+        encoded[1] = ACK_FRAME;
+        assert!(Upload::decode_cfg(encoded.as_slice(), &8).is_err());
+    }
+
+    #[test]
+    fn test_ack_codec() {
+        let ack = Ack { id: 7, status: 200 };
+        assert!(ack.accepted());
+        assert_eq!(Ack::decode(ack.encode()).unwrap(), ack);
+        assert!(!Ack { id: 7, status: 401 }.accepted());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{Error, PublicKey};
+use commonware_codec::DecodeExt;
+use commonware_utils::from_hex_formatted;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Public metadata describing a validator (served by the indexer at `/validators`).
+///
+/// Metadata is provided by the operator (`setup explorer` writes it from the deployment
+/// topology), so only the public key is checked.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorInfo {
+    /// Hex-encoded public key of the validator.
+    pub public_key: String,
+
+    /// Human-readable name of the validator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Region the validator is deployed in (like `us-east-1`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    /// Human-readable location of the validator (like `Ashburn`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+
+    /// Latitude and longitude of the validator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coordinates: Option<[f64; 2]>,
+}
+
+impl ValidatorInfo {
+    /// Create metadata (without any details) for the validator with `public_key`.
+    pub fn new(public_key: &PublicKey) -> Self {
+        Self {
+            public_key: public_key.to_string(),
+            name: None,
+            region: None,
+            location: None,
+            coordinates: None,
+        }
+    }
+
+    /// Decode the public key of the validator.
+    pub fn public_key(&self) -> Result<PublicKey, Error> {
+        let bytes = from_hex_formatted(&self.public_key).ok_or(Error::Codec(
+            commonware_codec::Error::Invalid("ValidatorInfo", "invalid public key hex"),
+        ))?;
+        Ok(PublicKey::decode(bytes.as_ref())?)
+    }
+
+    /// Parse a JSON-encoded list of validators, ordered by public key (the order participants
+    /// are indexed in).
+    ///
+    /// Fails if any public key is invalid or listed more than once.
+    pub fn from_json(json: &str) -> Result<Vec<Self>, Error> {
+        let validators: Vec<Self> = serde_json::from_str(json).map_err(|_| {
+            Error::Codec(commonware_codec::Error::Invalid(
+                "ValidatorInfo",
+                "invalid json",
+            ))
+        })?;
+        let mut keyed = validators
+            .into_iter()
+            .map(|validator| Ok((validator.public_key()?, validator)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if keyed.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err(Error::Consistency(
+                "validator listed more than once".to_string(),
+            ));
+        }
+        Ok(keyed.into_iter().map(|(_, validator)| validator).collect())
+    }
+
+    /// Read a JSON-encoded list of validators from `path` (see [ValidatorInfo::from_json]).
+    pub fn load(path: impl AsRef<Path>) -> Result<Vec<Self>, Error> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_from_json() {
+        let fixture = fixtures::seeded(0, 3);
+        let mut validators: Vec<_> = fixture
+            .participants
+            .iter()
+            .rev()
+            .map(ValidatorInfo::new)
+            .collect();
+        validators[0].name = Some("alpha".to_string());
+        validators[0].coordinates = Some([38.8339, -77.3074]);
+
+        // Validators are ordered by public key
+        let loaded =
+            ValidatorInfo::from_json(&serde_json::to_string(&validators).unwrap()).unwrap();
+        let keys: Vec<_> = loaded.iter().map(|v| v.public_key().unwrap()).collect();
+        let mut sorted = fixture.participants.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+        assert!(loaded.iter().any(|v| v.name.as_deref() == Some("alpha")));
+
+        // Missing details are omitted
+        let encoded = serde_json::to_string(&ValidatorInfo::new(&sorted[0])).unwrap();
+        assert_eq!(encoded, format!("{{\"public_key\":\"{}\"}}", sorted[0]));
+
+        // Invalid and duplicate keys are rejected
+        validators.push(validators[0].clone());
+        let json = serde_json::to_string(&validators).unwrap();
+        assert!(matches!(
+            ValidatorInfo::from_json(&json),
+            Err(Error::Consistency(_))
+        ));
+        validators[0].public_key = "zz".to_string();
+        let json = serde_json::to_string(&validators).unwrap();
+        assert!(matches!(
+            ValidatorInfo::from_json(&json),
+            Err(Error::Codec(_))
+        ));
+        assert!(ValidatorInfo::from_json("{}").is_err());
+    }
+}
@@ -1,47 +1,52 @@
-use crate::{Block, Finalized, Identity, Notarized, Scheme, Seed, Signature, EPOCH, NAMESPACE};
-use commonware_codec::{DecodeExt, Encode};
+//! JavaScript bindings (returning artifacts in their canonical JSON form, see [crate::json]).
+
+use crate::{
+    json::{canonical, Canonical},
+    Block, DigestMode, Finalized, Identity, IdentitySchedule, Kind, Notarized, Nullification,
+    Scheme, Seed, SeedInfo, Signature, Transaction, EPOCH, LAGGED_FRAME, MAX_RECEIPTS_SIZE,
+    NAMESPACE,
+};
+use commonware_codec::{Decode, DecodeExt};
 use commonware_consensus::{
     simplex::elector::Random,
-    types::{Round, View},
+    types::{Height, Round, View},
     Viewable,
 };
-use commonware_cryptography::{bls12381::primitives::variant::MinSig, Digestible};
+use commonware_cryptography::{bls12381::primitives::variant::MinSig, sha256::Digest, Digestible};
 use commonware_parallel::Sequential;
-use serde::{Deserialize, Serialize};
+use commonware_utils::from_hex;
+use rand::rngs::OsRng;
+use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
-#[derive(Deserialize, Serialize)]
-pub struct SeedJs {
-    pub view: u64,
-    pub signature: Vec<u8>,
-}
+/// A seed as seen by JavaScript (see [SeedInfo]).
+pub type SeedJs = SeedInfo;
 
 #[derive(Serialize)]
-pub struct ProofJs {
+pub struct NullificationJs {
     pub view: u64,
-    pub parent: u64,
-    pub payload: Vec<u8>,
-    pub signature: Vec<u8>,
 }
 
+/// An event parsed (and verified) from a consensus websocket frame by a [Worker].
 #[derive(Serialize)]
-pub struct BlockJs {
-    pub parent: Vec<u8>,
-    pub height: u64,
-    pub timestamp: u64,
-    pub digest: Vec<u8>,
+#[serde(tag = "kind", content = "value", rename_all = "lowercase")]
+pub enum EventJs {
+    Seed(SeedJs),
+    #[serde(serialize_with = "canonical")]
+    Notarization(Notarized),
+    #[serde(serialize_with = "canonical")]
+    Finalization(Finalized),
+    Nullification(NullificationJs),
+    /// The indexer dropped this many messages because the subscriber fell behind.
+    Lagged(u64),
 }
 
-#[derive(Serialize)]
-pub struct NotarizedJs {
-    pub proof: ProofJs,
-    pub block: BlockJs,
-}
-
-#[derive(Serialize)]
-pub struct FinalizedJs {
-    pub proof: ProofJs,
-    pub block: BlockJs,
+/// Parse an (optional) digest mode (defaulting to [DigestMode::Sha256]).
+fn digest_mode(mode: Option<String>) -> Option<DigestMode> {
+    match mode {
+        Some(mode) => mode.parse().ok(),
+        None => Some(DigestMode::default()),
+    }
 }
 
 #[wasm_bindgen]
@@ -55,80 +60,223 @@ pub fn parse_seed(identity: Vec<u8>, bytes: Vec<u8>) -> JsValue {
     if !seed.verify(&certificate_verifier) {
         return JsValue::NULL;
     }
-    let seed_js = SeedJs {
-        view: seed.view().get(),
-        signature: seed.signature.encode().to_vec(),
-    };
-    serde_wasm_bindgen::to_value(&seed_js).unwrap_or(JsValue::NULL)
+    serde_wasm_bindgen::to_value(&SeedJs::from(&seed)).unwrap_or(JsValue::NULL)
 }
 
 #[wasm_bindgen]
-pub fn parse_notarized(identity: Vec<u8>, bytes: Vec<u8>) -> JsValue {
+pub fn parse_notarized(identity: Vec<u8>, bytes: Vec<u8>, digest_mode: Option<String>) -> JsValue {
     let identity = Identity::decode(identity.as_ref()).expect("invalid identity");
     let certificate_verifier = Scheme::certificate_verifier(NAMESPACE, identity);
 
-    let Ok(notarized) = Notarized::decode(bytes.as_ref()) else {
+    let Some(mode) = self::digest_mode(digest_mode) else {
+        return JsValue::NULL;
+    };
+    let Ok(notarized) = Notarized::decode_cfg(bytes.as_ref(), &mode) else {
         return JsValue::NULL;
     };
     if !notarized.verify(&certificate_verifier, &Sequential) {
         return JsValue::NULL;
     }
-    let notarized_js = NotarizedJs {
-        proof: ProofJs {
-            view: notarized.proof.view().get(),
-            parent: notarized.proof.proposal.parent.get(),
-            payload: notarized.proof.proposal.payload.to_vec(),
-            signature: notarized.proof.certificate.vote_signature.encode().to_vec(),
-        },
-        block: BlockJs {
-            parent: notarized.block.parent.to_vec(),
-            height: notarized.block.height.get(),
-            timestamp: notarized.block.timestamp,
-            digest: notarized.block.digest().to_vec(),
-        },
-    };
-    serde_wasm_bindgen::to_value(&notarized_js).unwrap_or(JsValue::NULL)
+    serde_wasm_bindgen::to_value(&Canonical(&notarized)).unwrap_or(JsValue::NULL)
 }
 
 #[wasm_bindgen]
-pub fn parse_finalized(identity: Vec<u8>, bytes: Vec<u8>) -> JsValue {
+pub fn parse_finalized(identity: Vec<u8>, bytes: Vec<u8>, digest_mode: Option<String>) -> JsValue {
     let identity = Identity::decode(identity.as_ref()).expect("invalid identity");
     let certificate_verifier = Scheme::certificate_verifier(NAMESPACE, identity);
-    let Ok(finalized) = Finalized::decode(bytes.as_ref()) else {
+    let Some(mode) = self::digest_mode(digest_mode) else {
+        return JsValue::NULL;
+    };
+    let Ok(finalized) = Finalized::decode_cfg(bytes.as_ref(), &mode) else {
         return JsValue::NULL;
     };
     if !finalized.verify(&certificate_verifier, &Sequential) {
         return JsValue::NULL;
     }
-    let finalized_js = FinalizedJs {
-        proof: ProofJs {
-            view: finalized.proof.view().get(),
-            parent: finalized.proof.proposal.parent.get(),
-            payload: finalized.proof.proposal.payload.to_vec(),
-            signature: finalized.proof.certificate.vote_signature.encode().to_vec(),
-        },
-        block: BlockJs {
-            parent: finalized.block.parent.to_vec(),
-            height: finalized.block.height.get(),
-            timestamp: finalized.block.timestamp,
-            digest: finalized.block.digest().to_vec(),
-        },
-    };
-    serde_wasm_bindgen::to_value(&finalized_js).unwrap_or(JsValue::NULL)
+    serde_wasm_bindgen::to_value(&Canonical(&finalized)).unwrap_or(JsValue::NULL)
 }
 
 #[wasm_bindgen]
-pub fn parse_block(bytes: Vec<u8>) -> JsValue {
-    let Ok(block) = Block::decode(bytes.as_ref()) else {
+pub fn parse_block(bytes: Vec<u8>, digest_mode: Option<String>) -> JsValue {
+    let Some(mode) = self::digest_mode(digest_mode) else {
         return JsValue::NULL;
     };
-    let block_js = BlockJs {
-        parent: block.parent.to_vec(),
-        height: block.height.get(),
-        timestamp: block.timestamp,
-        digest: block.digest().to_vec(),
+    let Ok(block) = Block::decode_cfg(bytes.as_ref(), &mode) else {
+        return JsValue::NULL;
     };
-    serde_wasm_bindgen::to_value(&block_js).unwrap_or(JsValue::NULL)
+    serde_wasm_bindgen::to_value(&Canonical(&block)).unwrap_or(JsValue::NULL)
+}
+
+/// Compute the digest of the block with the given fields (without encoding it).
+///
+/// `rules` defaults to `0`, `transactions` (an array of byte arrays) to none, and `receipts` to
+/// empty. Returns `undefined` if `parent` is not a digest, a transaction or the receipts are too
+/// large, or the digest mode is invalid.
+#[wasm_bindgen]
+pub fn compute_block_digest(
+    parent: Vec<u8>,
+    height: u64,
+    timestamp: u64,
+    rules: Option<u16>,
+    transactions: JsValue,
+    receipts: Option<Vec<u8>>,
+    digest_mode: Option<String>,
+) -> Option<Vec<u8>> {
+    let transactions = if transactions.is_undefined() || transactions.is_null() {
+        Vec::new()
+    } else {
+        serde_wasm_bindgen::from_value(transactions).ok()?
+    };
+    block_digest(
+        &parent,
+        height,
+        timestamp,
+        rules.unwrap_or_default(),
+        transactions,
+        receipts.unwrap_or_default(),
+        self::digest_mode(digest_mode)?,
+    )
+    .map(|digest| digest.to_vec())
+}
+
+/// Compute the digest of the block with the given fields (see [compute_block_digest]).
+fn block_digest(
+    parent: &[u8],
+    height: u64,
+    timestamp: u64,
+    rules: u16,
+    transactions: Vec<Vec<u8>>,
+    receipts: Vec<u8>,
+    mode: DigestMode,
+) -> Option<Digest> {
+    let parent = Digest::decode(parent).ok()?;
+    let transactions = transactions
+        .into_iter()
+        .map(Transaction::new)
+        .collect::<Option<Vec<_>>>()?;
+    if receipts.len() > MAX_RECEIPTS_SIZE {
+        return None;
+    }
+    let block = Block::new_with_receipts(
+        mode,
+        rules,
+        parent,
+        Height::new(height),
+        timestamp,
+        transactions,
+        receipts.into(),
+    );
+    Some(block.digest())
+}
+
+/// Verifies consensus websocket frames, keeping the verifier across calls.
+///
+/// Decoding the identity and building the verifier once (with [Worker::init_with_identity])
+/// lets a web worker verify a stream of frames with [Worker::push] without repeating that
+/// work for every message. If the network re-keys, [Worker::rotate] (or [Worker::reconfigure],
+/// for a new epoch) schedules the new identity (see [IdentitySchedule]).
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct Worker {
+    identities: IdentitySchedule,
+    pub(crate) mode: DigestMode,
+}
+
+#[wasm_bindgen]
+impl Worker {
+    /// Create a [Worker] for the network with `identity` (and an optional digest mode).
+    ///
+    /// Returns `undefined` if the identity or digest mode is invalid.
+    pub fn init_with_identity(identity: Vec<u8>, digest_mode: Option<String>) -> Option<Worker> {
+        let identity = Identity::decode(identity.as_ref()).ok()?;
+        Some(Self {
+            identities: IdentitySchedule::new(identity),
+            mode: self::digest_mode(digest_mode)?,
+        })
+    }
+
+    /// Verify frames from `view` onwards with `identity`.
+    ///
+    /// Returns `false` (leaving the schedule unchanged) if the identity is invalid.
+    pub fn rotate(&mut self, view: u64, identity: Vec<u8>) -> bool {
+        let Ok(identity) = Identity::decode(identity.as_ref()) else {
+            return false;
+        };
+        self.identities.rotate(view, identity);
+        true
+    }
+
+    /// Verify frames from the start of `epoch` onwards with `identity`.
+    ///
+    /// Returns `false` (leaving the schedule unchanged) if the identity is invalid.
+    pub fn reconfigure(&mut self, epoch: u64, identity: Vec<u8>) -> bool {
+        let Ok(identity) = Identity::decode(identity.as_ref()) else {
+            return false;
+        };
+        self.identities.reconfigure(epoch, identity);
+        true
+    }
+
+    /// Parse and verify a frame (a kind byte followed by the artifact), returning an event
+    /// (`{ kind, value }`) or `null` if the frame is malformed or invalid.
+    pub fn push(&self, frame: Vec<u8>) -> JsValue {
+        match self.parse(&frame) {
+            Some(event) => serde_wasm_bindgen::to_value(&event).unwrap_or(JsValue::NULL),
+            None => JsValue::NULL,
+        }
+    }
+}
+
+impl Worker {
+    /// Decode and verify a [Finalized] block.
+    pub(crate) fn finalized(&self, data: &[u8]) -> Option<Finalized> {
+        let finalized = Finalized::decode_cfg(data, &self.mode).ok()?;
+        finalized
+            .verify(
+                self.identities.verifier(finalized.proof.round()),
+                &Sequential,
+            )
+            .then_some(finalized)
+    }
+
+    /// Parse and verify `frame`.
+    pub(crate) fn parse(&self, frame: &[u8]) -> Option<EventJs> {
+        let (kind, data) = frame.split_first()?;
+        if *kind == LAGGED_FRAME {
+            return u64::decode(data).ok().map(EventJs::Lagged);
+        }
+        match Kind::from_u8(*kind)? {
+            Kind::Seed => {
+                let seed = Seed::decode(data).ok()?;
+                seed.verify(self.identities.verifier(seed.round))
+                    .then(|| EventJs::Seed((&seed).into()))
+            }
+            Kind::Notarization => {
+                let notarized = Notarized::decode_cfg(data, &self.mode).ok()?;
+                notarized
+                    .verify(
+                        self.identities.verifier(notarized.proof.round()),
+                        &Sequential,
+                    )
+                    .then_some(EventJs::Notarization(notarized))
+            }
+            Kind::Finalization => self.finalized(data).map(EventJs::Finalization),
+            Kind::Nullification => {
+                let nullification = Nullification::decode(data).ok()?;
+                nullification
+                    .verify::<_, Digest>(
+                        &mut OsRng,
+                        self.identities.verifier(nullification.round()),
+                        &Sequential,
+                    )
+                    .then(|| {
+                        EventJs::Nullification(NullificationJs {
+                            view: nullification.view().get(),
+                        })
+                    })
+            }
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -137,7 +285,10 @@ pub fn leader_index(seed: JsValue, participants: usize) -> usize {
         return 0;
     };
 
-    let Ok(signature) = Signature::decode(seed.signature.as_ref()) else {
+    let Some(signature) = from_hex(&seed.signature) else {
+        return 0;
+    };
+    let Ok(signature) = Signature::decode(signature.as_ref()) else {
         return 0;
     };
 
@@ -151,3 +302,117 @@ pub fn leader_index(seed: JsValue, participants: usize) -> usize {
     )
     .get() as usize
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use commonware_codec::Encode;
+
+    #[test]
+    fn test_worker() {
+        let fixtures::Fixture { schemes, .. } = fixtures::seeded(0, 4);
+        let identity = fixtures::identity(&schemes).encode().to_vec();
+        let worker = Worker::init_with_identity(identity.clone(), None).unwrap();
+
+        // Valid frames are parsed (in order)
+        let events: Vec<_> = fixtures::frames(&schemes)
+            .iter()
+            .map(|frame| worker.parse(frame))
+            .collect();
+        assert!(matches!(
+            events[0],
+            Some(EventJs::Seed(SeedJs { view: 1, .. }))
+        ));
+        assert!(matches!(
+            &events[1],
+            Some(EventJs::Finalization(finalized)) if finalized.block.height.get() == 1
+        ));
+        assert!(matches!(
+            events[2],
+            Some(EventJs::Nullification(NullificationJs { view: 2 }))
+        ));
+        let mut lagged = vec![LAGGED_FRAME];
+        lagged.extend_from_slice(&7u64.encode());
+        assert!(matches!(worker.parse(&lagged), Some(EventJs::Lagged(7))));
+
+        // Malformed frames are rejected
+        assert!(worker.parse(&[]).is_none());
+        assert!(worker.parse(&[Kind::Seed as u8]).is_none());
+        assert!(worker.parse(&[42, 0]).is_none());
+
+        // Frames signed by another network are rejected
+        let fixtures::Fixture { schemes: other, .. } = fixtures::seeded(1, 4);
+        for frame in fixtures::frames(&other) {
+            assert!(worker.parse(&frame).is_none());
+        }
+
+        // Frames after a rotation are verified with the new identity
+        let mut worker = worker;
+        let rotated = fixtures::identity(&other).encode().to_vec();
+        assert!(worker.rotate(2, rotated));
+        assert!(!worker.rotate(3, vec![0; 3]));
+        let frames = fixtures::frames(&schemes);
+        assert!(worker.parse(&frames[0]).is_some());
+        assert!(worker.parse(&frames[2]).is_none());
+        let frames = fixtures::frames(&other);
+        assert!(worker.parse(&frames[0]).is_none());
+        assert!(worker.parse(&frames[2]).is_some());
+
+        // Invalid configuration is rejected
+        assert!(Worker::init_with_identity(vec![0; 3], None).is_none());
+        assert!(Worker::init_with_identity(identity, Some("md5".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_block_digest() {
+        let parent = Digest::from([1; 32]);
+        let transactions = vec![b"first".to_vec(), b"second".to_vec()];
+        for mode in [DigestMode::Sha256, DigestMode::Blake3] {
+            // Digests match those of constructed blocks
+            let block = Block::new_with_receipts(
+                mode,
+                2,
+                parent,
+                Height::new(5),
+                1_000,
+                transactions
+                    .iter()
+                    .map(|data| Transaction::new(data.clone()).unwrap())
+                    .collect(),
+                b"receipts".to_vec().into(),
+            );
+            let digest = block_digest(
+                &parent,
+                5,
+                1_000,
+                2,
+                transactions.clone(),
+                b"receipts".to_vec(),
+                mode,
+            );
+            assert_eq!(digest, Some(block.digest()));
+
+            // Every field is committed to
+            let empty = Block::new_with_mode(mode, parent, Height::new(5), 1_000);
+            let digest = block_digest(&parent, 5, 1_000, 0, Vec::new(), Vec::new(), mode);
+            assert_eq!(digest, Some(empty.digest()));
+            assert_ne!(
+                digest,
+                block_digest(&parent, 5, 1_001, 0, Vec::new(), Vec::new(), mode)
+            );
+            assert_ne!(
+                digest,
+                block_digest(&parent, 5, 1_000, 0, Vec::new(), vec![0], mode)
+            );
+        }
+
+        // Invalid fields are rejected
+        let mode = DigestMode::Sha256;
+        assert!(block_digest(&[0; 31], 1, 1, 0, Vec::new(), Vec::new(), mode).is_none());
+        let oversized = vec![vec![0; crate::MAX_TRANSACTION_SIZE + 1]];
+        assert!(block_digest(&parent, 1, 1, 0, oversized, Vec::new(), mode).is_none());
+        let oversized = vec![0; MAX_RECEIPTS_SIZE + 1];
+        assert!(block_digest(&parent, 1, 1, 0, Vec::new(), oversized, mode).is_none());
+    }
+}